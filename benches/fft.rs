@@ -13,4 +13,15 @@ pub fn fft_in_place(c: &mut Criterion) {
     });
 }
 
-criterion_group!(group, fft_in_place);
\ No newline at end of file
+pub fn fft_plan(c: &mut Criterion) {
+
+    let size: usize = 1 << 16;
+    let mut values = field::rand_vector(size);
+    let plan = fft::FftPlan::new(size);
+
+    c.bench_function("FFT (planned, forward)", |bench| {
+        bench.iter(|| plan.forward(black_box(&mut values)))
+    });
+}
+
+criterion_group!(group, fft_in_place, fft_plan);
\ No newline at end of file
@@ -0,0 +1,74 @@
+use crypto_box::{ PublicKey, SecretKey, aead::OsRng };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Size of public and secret keys in bytes.
+pub const KEY_SIZE: usize = crypto_box::KEY_SIZE;
+
+/// Number of bytes by which a sealed message is longer than the original message: the ephemeral
+/// public key of the sender and the authentication tag.
+pub const SEAL_OVERHEAD: usize = KEY_SIZE + 16;
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Generates a new random key pair and returns it as a (secret key, public key) tuple. Keys are
+/// X25519 keys, the same as used by libsodium `crypto_box` functions.
+pub fn generate_keys() -> ([u8; KEY_SIZE], [u8; KEY_SIZE]) {
+    let secret_key = SecretKey::generate(&mut OsRng);
+    return (secret_key.to_bytes(), secret_key.public_key().to_bytes());
+}
+
+/// Returns the public key corresponding to the `secret_key`.
+pub fn get_public_key(secret_key: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    return SecretKey::from(*secret_key).public_key().to_bytes();
+}
+
+/// Encrypts the `message` to the `public_key` of the recipient using an ephemeral key pair; this
+/// is the same as libsodium `crypto_box_seal` function. The sender does not need a key pair of
+/// its own, and only the holder of the matching secret key can open the message.
+pub fn seal(message: &[u8], public_key: &[u8; KEY_SIZE]) -> Vec<u8> {
+    return PublicKey::from(*public_key).seal(&mut OsRng, message).expect("failed to seal message");
+}
+
+/// Decrypts a message sealed with `seal()` function using the `secret_key` of the recipient;
+/// returns an error if the message was sealed to a different key or was modified.
+pub fn unseal(sealed: &[u8], secret_key: &[u8; KEY_SIZE]) -> Result<Vec<u8>, String> {
+    if sealed.len() < SEAL_OVERHEAD {
+        return Err(format!("sealed message must be at least {} bytes long, but was {} bytes",
+            SEAL_OVERHEAD, sealed.len()));
+    }
+    return SecretKey::from(*secret_key).unseal(sealed)
+        .map_err(|_| format!("failed to open sealed message: wrong key or corrupted message"));
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn seal_unseal() {
+        let (secret_key, public_key) = super::generate_keys();
+        assert_eq!(public_key, super::get_public_key(&secret_key));
+
+        let message = b"secret inputs";
+        let sealed = super::seal(message, &public_key);
+        assert_eq!(message.len() + super::SEAL_OVERHEAD, sealed.len());
+        assert_eq!(Ok(message.to_vec()), super::unseal(&sealed, &secret_key));
+
+        // every message is sealed with a fresh ephemeral key
+        assert_ne!(sealed, super::seal(message, &public_key));
+
+        // sealed messages cannot be opened with a different key
+        let (other_key, _) = super::generate_keys();
+        assert!(super::unseal(&sealed, &other_key).is_err());
+
+        // modified and truncated messages are rejected
+        let mut modified = sealed.clone();
+        modified[super::SEAL_OVERHEAD] ^= 1;
+        assert!(super::unseal(&modified, &secret_key).is_err());
+        assert!(super::unseal(&sealed[..super::SEAL_OVERHEAD - 1], &secret_key).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::HASH_STATE_RATE;
+use super::rescue;
+
+// COMMITMENT FUNCTIONS
+// ================================================================================================
+
+/// Returns the digest which a VM program computes by executing `hash.n` instruction against a
+/// stack holding the specified `values` followed by the `blinding` factor (if provided).
+///
+/// * `values` are expected in stack order - i.e. `values[0]` is at the top of the stack;
+/// * `n` is the number of values plus 1 if the blinding factor is provided, and thus, cannot
+///   exceed 4;
+/// * the digest is returned in stack order as well - i.e. the first element of the result is the
+///   element which ends up at the top of the stack after `hash.n` is executed.
+pub fn commit(values: &[u128], blinding: Option<u128>) -> [u128; 2] {
+    let mut inputs = values.to_vec();
+    if let Some(blinding) = blinding {
+        inputs.push(blinding);
+    }
+
+    assert!(inputs.len() > 0, "at least one value must be committed to");
+    assert!(inputs.len() <= HASH_STATE_RATE,
+        "cannot commit to more than {} values including the blinding factor, but received {}",
+        HASH_STATE_RATE, inputs.len());
+
+    // hash.n pads the stack with zeros such that the inputs end up at the bottom of the hash
+    // state; the hasher fills the state from the end, so the inputs are passed in reverse order
+    inputs.reverse();
+    let digest = rescue::digest(&inputs);
+    return [digest[1], digest[0]];
+}
+
+/// Returns the digest which the epilogue of a program compiled with
+/// `assembly::compile_with_output_digest()` computes over the outputs which are not returned
+/// directly (i.e. all outputs except for the top 4), with `outputs[0]` being the output closest
+/// to the top of the stack.
+///
+/// The outputs are absorbed 2 at a time (the last output is absorbed alone if the number of
+/// outputs is odd) into a digest which starts out as 2 zeros: on every step, the new digest is
+/// computed as `commit(&[digest[0], digest[1], a, b], None)`.
+pub fn digest_outputs(outputs: &[u128]) -> [u128; 2] {
+    let mut digest = [0, 0];
+    for chunk in outputs.chunks(2) {
+        let mut values = digest.to_vec();
+        values.extend_from_slice(chunk);
+        digest = commit(&values, None);
+    }
+    return digest;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        programs::{ assembly, ProgramInputs },
+        processor, stark::TraceState,
+    };
+
+    #[test]
+    fn commit_matches_vm() {
+        let values = [1, 2, 3, 4];
+        for n in 1..5 {
+            // without blinding
+            let expected = hash_on_vm(&values[..n]);
+            assert_eq!(expected, super::commit(&values[..n], None));
+
+            // with blinding
+            if n < 4 {
+                let mut stack = values[..n].to_vec();
+                stack.push(42);
+                let expected = hash_on_vm(&stack);
+                assert_eq!(expected, super::commit(&values[..n], Some(42)));
+            }
+        }
+    }
+
+    #[test]
+    fn commit_test_vectors() {
+        assert_eq!(TEST_VECTORS[0], super::commit(&[1], None));
+        assert_eq!(TEST_VECTORS[1], super::commit(&[1, 2], None));
+        assert_eq!(TEST_VECTORS[2], super::commit(&[1, 2, 3], Some(7)));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot commit to more than 4 values including the blinding factor, but received 5")]
+    fn commit_too_many_values() {
+        super::commit(&[1, 2, 3, 4], Some(5));
+    }
+
+    const TEST_VECTORS: [[u128; 2]; 3] = [
+        [307758163315608979237983299782144502219, 325770709507943575793020394214788590230],
+        [  5692339104812587984850603656456373787, 172712696712285685907327688908132542982],
+        [ 50666516386980998164054604641247060553, 106553749036212950188482365282325759817],
+    ];
+
+    fn hash_on_vm(stack: &[u128]) -> [u128; 2] {
+        let source = format!("begin hash.{} end", stack.len());
+        let program = assembly::compile(&source).unwrap();
+        let inputs = ProgramInputs::from_public(stack);
+
+        let (trace, ctx_depth, loop_depth) = processor::execute(&program, &inputs);
+        let stack_depth = TraceState::compute_stack_depth(trace.len(), ctx_depth, loop_depth);
+        let mut state = TraceState::new(ctx_depth, loop_depth, stack_depth);
+        state.update_from_trace(&trace, trace[0].len() - 1);
+        return [state.user_stack()[0], state.user_stack()[1]];
+    }
+}
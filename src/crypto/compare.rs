@@ -0,0 +1,53 @@
+use std::hint::black_box;
+
+// CONSTANT-TIME COMPARISONS
+// ================================================================================================
+
+/// Returns true if `a` and `b` contain the same bytes. For slices of equal length, the time it
+/// takes to compare them does not depend on their contents (e.g. on the position of the first
+/// differing byte); lengths are not considered secret. This should be used for comparing digests
+/// and other values received from untrusted parties.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    return black_box(diff) == 0;
+}
+
+/// Returns true if `a` and `b` contain the same field elements; same as `ct_eq()`, the time it
+/// takes to compare slices of equal length does not depend on their contents.
+pub fn ct_eq_elements(a: &[u128], b: &[u128]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u128;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    return black_box(diff) == 0;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use super::{ ct_eq, ct_eq_elements };
+
+    #[test]
+    fn compare() {
+        assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(ct_eq(&[], &[]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!ct_eq(&[0, 2, 3], &[1, 2, 3]));
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+
+        assert!(ct_eq_elements(&[1, u128::MAX], &[1, u128::MAX]));
+        assert!(!ct_eq_elements(&[1, u128::MAX], &[1, u128::MAX - 1]));
+        assert!(!ct_eq_elements(&[1], &[1, 2]));
+    }
+}
@@ -121,9 +121,7 @@ pub fn poseidon(values: &[u8], result: &mut [u8]) {
     debug_assert!(result.len() == 32, "expected result to be exactly 32 bytes but received {}", result.len());
 
     // copy values into state and set the remaining state elements to 0
-    let mut state = [0u128; 6];
-    let state_bytes: &mut [u8; 64] = unsafe { &mut *(&state as *const _ as *mut [u8; 64]) };
-    state_bytes[..values.len()].copy_from_slice(values);
+    let mut state = init_state(values);
 
     // execute round function 48 times
     for i in 0..91 {
@@ -153,9 +151,7 @@ pub fn rescue(values: &[u8], result: &mut [u8]) {
     debug_assert!(result.len() == 32, "expected result to be exactly 32 bytes but received {}", result.len());
 
     // copy values into state and set the remaining state elements to 0
-    let mut state = [0u128; 6];
-    let state_bytes: &mut [u8; 64] = unsafe { &mut *(&state as *const _ as *mut [u8; 64]) };
-    state_bytes[..values.len()].copy_from_slice(values);
+    let mut state = init_state(values);
 
     // apply round function 10 times
     add_constants(&mut state, 0);
@@ -183,9 +179,7 @@ pub fn gmimc(values: &[u8], result: &mut [u8]) {
     debug_assert!(result.len() == 32, "expected result to be exactly 32 bytes but received {}", result.len());
 
     // copy values into state and set the remaining state elements to 0
-    let mut state = [0u128; 6];
-    let state_bytes: &mut [u8; 64] = unsafe { &mut *(&state as *const _ as *mut [u8; 64]) };
-    state_bytes[..values.len()].copy_from_slice(values);
+    let mut state = init_state(values);
 
     for i in 0..166 {
         let s0 = state[0];
@@ -219,6 +213,16 @@ pub fn sha3(values: &[u8], result: &mut [u8]) {
 
 // HELPER FUNCTIONS
 // ================================================================================================
+fn init_state(values: &[u8]) -> [u128; 6] {
+    let mut state = [0u128; 6];
+    for (element, chunk) in state.iter_mut().zip(values.chunks(16)) {
+        let mut bytes = [0u8; 16];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        *element = u128::from_ne_bytes(bytes);
+    }
+    return state;
+}
+
 fn add_constants(state: &mut[u128; 6], offset: usize) {
     for i in 0..6 {
         state[i] = field::add(state[i], ARK[offset + i]);
@@ -1,7 +1,8 @@
 use std::slice;
 use std::collections::{ HashMap, BTreeSet };
 use serde::{ Serialize, Deserialize };
-use crate::crypto::{ HashFunction };
+use crate::crypto::{ HashFunction, ct_eq };
+use crate::utils::zeroed_vector;
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -10,7 +11,7 @@ pub struct MerkleTree {
     values  : Vec<[u8; 32]>
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BatchMerkleProof {
     pub values  : Vec<[u8; 32]>,
     pub nodes   : Vec<Vec<[u8; 32]>>,
@@ -62,65 +63,7 @@ impl MerkleTree {
 
     /// Computes merkle paths for the provided indexes and compresses the paths into a single proof.
     pub fn prove_batch(&self, indexes: &[usize]) -> BatchMerkleProof {
-        let n = self.values.len();
-
-        let index_map = map_indexes(indexes, n);
-        let indexes = normalize_indexes(indexes);
-        let mut values = vec![[0u8; 32]; index_map.len()];
-        let mut nodes: Vec<Vec<[u8; 32]>> = Vec::with_capacity(indexes.len());
-
-        // populate the proof with leaf node values
-        let mut next_indexes: Vec<usize> = Vec::new();
-        for index in indexes {
-            let v1 = self.values[index];
-            let v2 = self.values[index + 1];
-
-            // only values for indexes that were explicitly requested are included in values array
-            let input_index1 = index_map.get(&index);
-            let input_index2 = index_map.get(&(index + 1));
-            if input_index1.is_some() {
-                if input_index2.is_some() {
-                    values[*input_index1.unwrap()] = v1;
-                    values[*input_index2.unwrap()] = v2;
-                    nodes.push(Vec::new());
-                }
-                else {
-                    values[*input_index1.unwrap()] = v1;
-                    nodes.push(vec![v2]);
-                }
-            }
-            else {
-                values[*input_index2.unwrap()] = v2;
-                nodes.push(vec![v1]);
-            }
-
-            next_indexes.push((index + n) >> 1);
-        }
-
-        // add required internal nodes to the proof, skipping redundancies
-        let depth = self.values.len().trailing_zeros() as u8;
-        for _ in 1..depth {
-            let indexes = next_indexes.clone();
-            next_indexes.truncate(0);
-
-            let mut i = 0;
-            while i < indexes.len() {
-                let sibling_index = indexes[i] ^ 1;
-                if i + 1 < indexes.len() && indexes[i + 1] == sibling_index {
-                    i += 1;
-                }
-                else {
-                    nodes[i].push(self.nodes[sibling_index]);
-                }
-
-                // add parent index to the set of next indexes
-                next_indexes.push(sibling_index >> 1);
-
-                i += 1;
-            }
-        }
-
-        return BatchMerkleProof { values, nodes, depth };
+        return build_batch_proof(indexes, self.values.len(), |i| self.values[i], |i| self.nodes[i]);
     }
 
     /// Checks whether the path for the specified index is valid.
@@ -147,7 +90,7 @@ impl MerkleTree {
             index = index >> 1;
         }
 
-        return v == *root;
+        return ct_eq(&v, root);
     }
 
     /// Checks whether the batch proof contains merkle paths for the of the specified indexes.
@@ -259,7 +202,40 @@ impl MerkleTree {
             }
         }
      
-        return *root == *v.get(&1).unwrap();
+        return ct_eq(root, v.get(&1).unwrap());
+    }
+}
+
+// BATCH MERKLE PROOF IMPLEMENTATION
+// ================================================================================================
+impl BatchMerkleProof {
+
+    /// Compresses individual merkle paths (as returned by `MerkleTree::prove()`) for the provided
+    /// indexes into a single proof; the result is the same as the proof which `prove_batch()`
+    /// would produce for these indexes. Paths are not verified by this function.
+    pub fn from_paths(indexes: &[usize], paths: &[Vec<[u8; 32]>]) -> BatchMerkleProof {
+        assert!(!paths.is_empty(), "at least one path must be provided");
+        assert!(indexes.len() == paths.len(), "expected {} paths, but received {}", indexes.len(), paths.len());
+        let depth = paths[0].len() - 1;
+        assert!(depth >= 1, "paths must contain at least 2 nodes");
+        let n = usize::pow(2, depth as u32);
+
+        // every path contains a leaf and its sibling, followed by siblings of all internal nodes
+        // on the way to the root; internal nodes are indexed in the same way as in a tree
+        let mut leaves: HashMap<usize, [u8; 32]> = HashMap::new();
+        let mut nodes: HashMap<usize, [u8; 32]> = HashMap::new();
+        for (&index, path) in indexes.iter().zip(paths.iter()) {
+            assert!(path.len() == depth + 1, "all paths must have the same length");
+            leaves.insert(index, path[0]);
+            leaves.insert(index ^ 1, path[1]);
+            let mut node_index = (index + n) >> 1;
+            for &node in path[2..].iter() {
+                nodes.insert(node_index ^ 1, node);
+                node_index = node_index >> 1;
+            }
+        }
+
+        return build_batch_proof(indexes, n, |i| leaves[&i], |i| nodes[&i]);
     }
 }
 
@@ -269,10 +245,8 @@ impl MerkleTree {
 pub fn build_merkle_nodes(leaves: &[[u8; 32]], hash: HashFunction) -> Vec<[u8; 32]> {
     let n = leaves.len() / 2;
 
-    // create un-initialized array to hold all intermediate nodes
-    let mut nodes: Vec<[u8; 32]> = Vec::with_capacity(2 * n);
-    unsafe { nodes.set_len(2 * n); }
-    nodes[0] = [0u8; 32];
+    // create an array to hold all intermediate nodes
+    let mut nodes: Vec<[u8; 32]> = zeroed_vector(2 * n);
 
     // re-interpret leaves as an array of two leaves fused together
     let two_leaves = unsafe { slice::from_raw_parts(leaves.as_ptr() as *const [u8; 64], n) };
@@ -293,6 +267,71 @@ pub fn build_merkle_nodes(leaves: &[[u8; 32]], hash: HashFunction) -> Vec<[u8; 3
     return nodes;
 }
 
+/// Compresses merkle paths for the provided indexes into a single proof; `leaf` and `node` return
+/// leaves and internal nodes of a tree with `n` leaves at the specified indexes.
+fn build_batch_proof<L, N>(indexes: &[usize], n: usize, leaf: L, node: N) -> BatchMerkleProof
+where L: Fn(usize) -> [u8; 32], N: Fn(usize) -> [u8; 32]
+{
+
+    let index_map = map_indexes(indexes, n);
+    let indexes = normalize_indexes(indexes);
+    let mut values = vec![[0u8; 32]; index_map.len()];
+    let mut nodes: Vec<Vec<[u8; 32]>> = Vec::with_capacity(indexes.len());
+
+    // populate the proof with leaf node values
+    let mut next_indexes: Vec<usize> = Vec::new();
+    for index in indexes {
+        let v1 = leaf(index);
+        let v2 = leaf(index + 1);
+
+        // only values for indexes that were explicitly requested are included in values array
+        let input_index1 = index_map.get(&index);
+        let input_index2 = index_map.get(&(index + 1));
+        if input_index1.is_some() {
+            if input_index2.is_some() {
+                values[*input_index1.unwrap()] = v1;
+                values[*input_index2.unwrap()] = v2;
+                nodes.push(Vec::new());
+            }
+            else {
+                values[*input_index1.unwrap()] = v1;
+                nodes.push(vec![v2]);
+            }
+        }
+        else {
+            values[*input_index2.unwrap()] = v2;
+            nodes.push(vec![v1]);
+        }
+
+        next_indexes.push((index + n) >> 1);
+    }
+
+    // add required internal nodes to the proof, skipping redundancies
+    let depth = n.trailing_zeros() as u8;
+    for _ in 1..depth {
+        let indexes = next_indexes.clone();
+        next_indexes.truncate(0);
+
+        let mut i = 0;
+        while i < indexes.len() {
+            let sibling_index = indexes[i] ^ 1;
+            if i + 1 < indexes.len() && indexes[i + 1] == sibling_index {
+                i += 1;
+            }
+            else {
+                nodes[i].push(node(sibling_index));
+            }
+
+            // add parent index to the set of next indexes
+            next_indexes.push(sibling_index >> 1);
+
+            i += 1;
+        }
+    }
+
+    return BatchMerkleProof { values, nodes, depth };
+}
+
 fn map_indexes(indexes: &[usize], max_valid: usize) -> HashMap<usize, usize> {
     let mut map = HashMap::new();
     for (i, index) in indexes.iter().cloned().enumerate() {
@@ -517,6 +556,18 @@ mod tests {
         assert_eq!(true, super::MerkleTree::verify_batch(tree.root(), &[0, 1, 2, 3, 4, 5, 6, 7], &proof, hash::poseidon));
     }
 
+    #[test]
+    fn batch_from_paths() {
+        let leaves = LEAVES8.to_vec();
+        let tree = super::MerkleTree::new(leaves, hash::poseidon);
+
+        for indexes in [vec![1], vec![1, 2], vec![6, 1], vec![1, 3, 6], vec![7, 0, 5, 2, 4]].iter() {
+            let paths: Vec<_> = indexes.iter().map(|&i| tree.prove(i)).collect();
+            let proof = super::BatchMerkleProof::from_paths(indexes, &paths);
+            assert_eq!(tree.prove_batch(indexes), proof);
+        }
+    }
+
     // HELPER FUNCTIONS
     // --------------------------------------------------------------------------------------------
     fn hash_2x1(v1: &[u8; 32], v2: &[u8; 32]) -> [u8; 32] {
@@ -1,6 +1,19 @@
 pub mod hash;
+pub mod rescue;
 
 mod merkle;
 pub use merkle::{ MerkleTree, BatchMerkleProof, build_merkle_nodes };
 
+mod commitment;
+pub use commitment::{ commit, digest_outputs };
+
+mod compare;
+pub use compare::{ ct_eq, ct_eq_elements };
+
+mod prng;
+pub use prng::Prng;
+
+#[cfg(feature = "sealed-inputs")]
+pub mod box_;
+
 pub type HashFunction = fn(&[u8], &mut [u8]);
\ No newline at end of file
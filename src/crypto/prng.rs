@@ -0,0 +1,74 @@
+use crate::{ math::field, HASH_STATE_WIDTH };
+use super::rescue::HASHER;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A pseudo-random number generator which produces the same sequence of values as `prng.seed`
+/// and `prng.next` assembly instructions; this can be used to generate witnesses for programs
+/// which rely on in-VM randomness.
+///
+/// The generator is a Rescue sponge: its state is kept in stack order - i.e. `state[0]` is the
+/// element at the top of the stack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prng {
+    state: [u128; HASH_STATE_WIDTH],
+}
+
+// PRNG IMPLEMENTATION
+// ================================================================================================
+impl Prng {
+
+    /// Returns a generator seeded in the same way as `prng.seed` instruction seeds it from a
+    /// stack which has `seed[0]` at the top and `seed[1]` right below it.
+    pub fn new(seed: [u128; 2]) -> Prng {
+        let mut state = [field::ZERO; HASH_STATE_WIDTH];
+        state[HASH_STATE_WIDTH - 2] = seed[0];
+        state[HASH_STATE_WIDTH - 1] = seed[1];
+        return Prng { state };
+    }
+
+    /// Permutes the state and returns the next 2 values of the sequence in the same order in
+    /// which `prng.next` instruction leaves them on the stack (i.e. the first value is placed
+    /// closer to the top of the stack).
+    pub fn next(&mut self) -> [u128; 2] {
+        for i in 0..HASHER.num_rounds() {
+            HASHER.apply_round(&mut self.state, i);
+        }
+        return [self.state[0], self.state[1]];
+    }
+
+    /// Returns the current state of the generator in stack order.
+    pub fn state(&self) -> &[u128] {
+        return &self.state;
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ programs::{ assembly, ProgramInputs }, processor };
+    use super::Prng;
+
+    #[test]
+    fn prng_matches_vm() {
+        let program = assembly::compile("begin prng.seed prng.next prng.next prng.next end").unwrap();
+        let inputs = ProgramInputs::from_public(&[3, 5]);
+        let stack = processor::interpret(&program, &inputs).unwrap().stack;
+
+        let mut prng = Prng::new([3, 5]);
+        let values = [prng.next(), prng.next(), prng.next()];
+
+        // the state stays at the top of the stack, and the values drawn last are right below it
+        assert_eq!(prng.state(), &stack[..6]);
+        assert_eq!(&values[2], &stack[6..8]);
+        assert_eq!(&values[1], &stack[8..10]);
+        assert_eq!(&values[0], &stack[10..12]);
+        assert_eq!(12, stack.len());
+
+        // different seeds produce different sequences
+        assert_ne!(values[0], Prng::new([5, 3]).next());
+    }
+}
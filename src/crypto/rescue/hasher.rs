@@ -1,100 +1,9 @@
-use crate::{
-    math::field,
-    HASH_STATE_WIDTH as STATE_WIDTH,
-    HASH_STATE_RATE as STATE_RATE,
-    HASH_DIGEST_SIZE as DIGEST_SIZE,
-    BASE_CYCLE_LENGTH as CYCLE_LENGTH,
-    HASH_NUM_ROUNDS as NUM_ROUNDS,
-};
+use crate::{ HASH_STATE_WIDTH as STATE_WIDTH, BASE_CYCLE_LENGTH as CYCLE_LENGTH };
 
-// HASHER FUNCTIONS
+// RESCUE CONSTANTS FOR THE STACK HASH FUNCTION (STATE WIDTH = 6)
 // ================================================================================================
-pub fn digest(values: &[u128]) -> Vec<u128> {
-    assert!(values.len() <= STATE_RATE,
-        "expected no more than {}, but received {}", STATE_RATE, values.len());
 
-    let mut state = [field::ZERO; STATE_WIDTH];
-    state[..values.len()].copy_from_slice(values);
-    state.reverse();
-
-    for i in 0..NUM_ROUNDS {
-        apply_round(&mut state, i);
-    }
-
-    state.reverse();
-    return state[..DIGEST_SIZE].to_vec();
-}
-
-pub fn apply_round(state: &mut [u128], step: usize) {
-    
-    let ark_idx = step % CYCLE_LENGTH;
-
-    // apply Rescue round
-    add_constants(state, ark_idx, 0);
-    apply_sbox(state);
-    apply_mds(state);
-
-    add_constants(state, ark_idx, STATE_WIDTH);
-    apply_inv_sbox(state);
-    apply_mds(state);
-}
-
-pub fn add_constants(state: &mut[u128], idx: usize, offset: usize) {
-    for i in 0..STATE_WIDTH {
-        state[i] = field::add(state[i], ARK[offset + i][idx]);
-    }
-}
-
-pub fn apply_sbox(state: &mut [u128]) {
-    for i in 0..STATE_WIDTH {
-        state[i] = field::exp(state[i], ALPHA);
-    }
-}
-
-pub fn apply_inv_sbox(state: &mut[u128]) {
-    // TODO: optimize
-    for i in 0..STATE_WIDTH {
-        state[i] = field::exp(state[i], INV_ALPHA);
-    }
-}
-
-pub fn apply_mds(state: &mut[u128]) {
-    let mut result = [field::ZERO; STATE_WIDTH];
-    let mut temp = [field::ZERO; STATE_WIDTH];
-    for i in 0..STATE_WIDTH {
-        for j in 0..STATE_WIDTH {
-            temp[j] = field::mul(MDS[i * STATE_WIDTH + j], state[j]);
-        }
-
-        for j in 0..STATE_WIDTH {
-            result[i] = field::add(result[i], temp[j]);
-        }
-    }
-    state.copy_from_slice(&result);
-}
-
-pub fn apply_inv_mds(state: &mut[u128]) {
-    let mut result = [field::ZERO; STATE_WIDTH];
-    let mut temp = [field::ZERO; STATE_WIDTH];
-    for i in 0..STATE_WIDTH {
-        for j in 0..STATE_WIDTH {
-            temp[j] = field::mul(INV_MDS[i * STATE_WIDTH + j], state[j]);
-        }
-
-        for j in 0..STATE_WIDTH {
-            result[i] = field::add(result[i], temp[j]);
-        }
-    }
-    state.copy_from_slice(&result);
-}
-
-// 128-BIT RESCUE CONSTANTS
-// ================================================================================================
-
-const ALPHA: u128 = 3;
-const INV_ALPHA: u128 = 226854911280625642308916371969163307691;
-
-const MDS: [u128; STATE_WIDTH * STATE_WIDTH] = [
+pub const MDS: [u128; STATE_WIDTH * STATE_WIDTH] = [
      34702391375697798808541201166389247321, 292720401120629668097050277338444166479, 252221686506898646925660607780980529565,   1545301432720594930091500405440765270, 249229091188143033873076468277345141138, 220001593723324427188563221285612032538,
     223274184432289781839114239013770504955, 330042960751289206923775620692185805456,  68147806084648525660922442535124284349, 170632587193822854126540173326689266153, 250033902372207462477717017592730125263, 241770281110130121239200125437407593586,
      59697037488579951129595490016876870776, 173037025415440639734730939871096987969, 244520331803890388707106378055030145592,  34432552219210978837375640622811234255, 224883744083395074894597669527169800639, 118987613174044827738657284387435362389,
@@ -103,7 +12,7 @@ const MDS: [u128; STATE_WIDTH * STATE_WIDTH] = [
     215280917571128372543200644166029121446,  95409967251054914374823434415713274752,  47264968375673684314231886553980659586, 324414896710549426218067045352609729751, 134533192639212680415562336758249126966, 113819576569856286031671903516923963713,
 ];
 
-const INV_MDS: [u128; STATE_WIDTH * STATE_WIDTH] = [
+pub const INV_MDS: [u128; STATE_WIDTH * STATE_WIDTH] = [
     262838870629088612431704202279372804365, 222534852307924763539037405488664736598,  81646375972783381860153911140700582070, 199701217011341155249764385939633347172, 332690827334260982039678112409792234722,  89516833564756742553232000561318423105,
     268971102785434627130755364883988908171, 281455956826013141038766773877631706708, 260031388727053904917456703760391819397, 129628250235822118843226947626941528745,   4752366532746298637564749858084292362,  41511161206792419767881138471159674575,
     182137736677911549469583431255398613256,  81590632530312527113593374981163544706, 187388592404931394753634116375361630728,  33930556286904149060086783748137598339, 128856701558652053153053295206366630120, 142358475095617426664163236851553505832,
@@ -125,4 +34,4 @@ pub const ARK: [[u128; CYCLE_LENGTH]; STATE_WIDTH * 2] = [
     [179443458614881887600494128053111694648, 108165142884901978856319583750672324489,  97063282200318501142854934314343169049, 261286087759526359216271155361018330507,  67833038363599207475373040930824843019,  56878992720628535103195481580617360771, 198852036109370286966576164360266278255, 174521831193496100673067735908873646985, 251654188127562510403516067236333482372,  48056343894932757577046683797067209079, 306942787541210815164178987028698818659, 156642260202818413362503062578539720517, 251616653853928459967283575542057535293, 188741644029927191719040650968720800409, 281428110117091114144446350524650424481,  64627937813848943279040280988334503406],
     [289278996656706117461857789813498821934, 274604860873273636237081114376077113475, 126000924558481152083098962591383883438, 129877116445533126989528570413807277693, 172066229584406173063202914726937339958, 298530663250990395227144225232608384365,  16989575615175240495557720305287640349, 102835474498154050313290986853294842906, 297928660776980173370496618733852490961,  96037481352786813748421760769380383926,   2818165229115014774032882127170013258, 293027053537479076557105009345927645442, 249369722351358137898587699909312963803, 300544292992993952360719000252205715076, 323117003802246814764810890058143344905, 243579355010018669877160932197352017974],
     [339223760157195739332845857285008200423, 208632865147351209340449219082125897333,  96675618862527967726114378655626650641, 162892536327655189685235410342890574896, 196910153233132861881308509456401645140, 281841826874183647567546019531929972702, 155276073049009029667373106803046514344, 152642017050116048509158960350000858013, 286456894851095755022390967246767421000, 215531716255970146473658338852472046173, 324452408864695917006896030536225525119, 314094406162389098987684450322979120529, 114910730596486251472791631840513265074,  81795345404219176616297063519210464031,  22603524397731600512825466576357638930,  63900149356112496372337283043133097338],
-];
\ No newline at end of file
+];
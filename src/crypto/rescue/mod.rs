@@ -0,0 +1,211 @@
+use crate::{
+    math::field,
+    BASE_CYCLE_LENGTH as CYCLE_LENGTH,
+    HASH_STATE_WIDTH, HASH_STATE_RATE, HASH_DIGEST_SIZE, HASH_NUM_ROUNDS,
+    SPONGE_WIDTH, HACC_NUM_ROUNDS,
+};
+
+pub(crate) mod hasher;
+pub(crate) mod sponge;
+
+// CONSTANTS
+// ================================================================================================
+
+/// S-Box exponent and its inverse; these are the same for all instantiations of Rescue.
+const ALPHA: u128 = 3;
+const INV_ALPHA: u128 = 226854911280625642308916371969163307691;
+
+/// Maximum state width supported by Rescue instantiations; this allows round functions to keep
+/// intermediate state on the stack.
+pub const MAX_STATE_WIDTH: usize = 12;
+
+/// Rescue instantiation used by RESCR operation to hash values on the stack.
+pub const HASHER: Rescue = Rescue::new(
+    HASH_STATE_WIDTH, HASH_NUM_ROUNDS, &hasher::MDS, &hasher::INV_MDS, &hasher::ARK);
+
+/// Rescue instantiation used by the decoder to hash program operations into the program hash.
+pub const SPONGE: Rescue = Rescue::new(
+    SPONGE_WIDTH, HACC_NUM_ROUNDS, &sponge::MDS, &sponge::INV_MDS, &sponge::ARK);
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Parameters of a [Rescue](https://eprint.iacr.org/2019/426) permutation over the 128-bit
+/// prime field. Round constants repeat every 16 rounds so that they can be used as periodic
+/// columns in the AIR.
+pub struct Rescue {
+    width       : usize,
+    num_rounds  : usize,
+    mds         : &'static [u128],
+    inv_mds     : &'static [u128],
+    ark         : &'static [[u128; CYCLE_LENGTH]],
+}
+
+// RESCUE IMPLEMENTATION
+// ================================================================================================
+impl Rescue {
+
+    /// Creates a new Rescue instantiation with the specified state width and number of rounds.
+    /// `mds` and `inv_mds` must be `width` x `width` matrices in row-major order, and `ark` must
+    /// contain 2 * `width` rows of round constants.
+    pub const fn new(width: usize, num_rounds: usize, mds: &'static [u128], inv_mds: &'static [u128],
+        ark: &'static [[u128; CYCLE_LENGTH]]) -> Rescue
+    {
+        assert!(width <= MAX_STATE_WIDTH, "state width is too big");
+        assert!(mds.len() == width * width, "MDS matrix must be width x width");
+        assert!(inv_mds.len() == width * width, "inverse MDS matrix must be width x width");
+        assert!(ark.len() == 2 * width, "there must be 2 * width rows of round constants");
+        assert!(num_rounds <= CYCLE_LENGTH, "number of rounds cannot exceed the constant cycle length");
+        return Rescue { width, num_rounds, mds, inv_mds, ark };
+    }
+
+    pub fn width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        return self.num_rounds;
+    }
+
+    /// Returns round constants for this instantiation; the first `width` rows are added to the
+    /// state in the first half of a round, and the last `width` rows in the second half.
+    pub fn ark(&self) -> &'static [[u128; CYCLE_LENGTH]] {
+        return self.ark;
+    }
+
+    // ROUND FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Applies a single Rescue round to the `state`; round constants are selected based on the
+    /// `step` of the constant cycle.
+    pub fn apply_round(&self, state: &mut [u128], step: usize) {
+        self.apply_round_with_injection(state, &[], step);
+    }
+
+    /// Executes a modified version of Rescue round where `inputs` are added to the first
+    /// elements of the state in the middle of the round. This modification differs significantly
+    /// form how the function was originally designed, and may potentially be insecure.
+    pub fn apply_round_with_injection(&self, state: &mut [u128], inputs: &[u128], step: usize) {
+
+        let ark_idx = step % CYCLE_LENGTH;
+
+        // apply first half of Rescue round
+        self.add_constants(state, ark_idx, 0);
+        self.apply_sbox(state);
+        self.apply_mds(state);
+
+        // inject inputs into the state
+        for i in 0..inputs.len() {
+            state[i] = field::add(state[i], inputs[i]);
+        }
+
+        // apply second half of Rescue round
+        self.add_constants(state, ark_idx, self.width);
+        self.apply_inv_sbox(state);
+        self.apply_mds(state);
+    }
+
+    pub fn add_constants(&self, state: &mut [u128], idx: usize, offset: usize) {
+        for i in 0..self.width {
+            state[i] = field::add(state[i], self.ark[offset + i][idx]);
+        }
+    }
+
+    pub fn apply_sbox(&self, state: &mut [u128]) {
+        for i in 0..self.width {
+            state[i] = field::exp(state[i], ALPHA);
+        }
+    }
+
+    pub fn apply_inv_sbox(&self, state: &mut [u128]) {
+        // TODO: optimize
+        for i in 0..self.width {
+            state[i] = field::exp(state[i], INV_ALPHA);
+        }
+    }
+
+    pub fn apply_mds(&self, state: &mut [u128]) {
+        apply_matrix(self.mds, &mut state[..self.width]);
+    }
+
+    pub fn apply_inv_mds(&self, state: &mut [u128]) {
+        apply_matrix(self.inv_mds, &mut state[..self.width]);
+    }
+}
+
+// HASH FUNCTIONS
+// ================================================================================================
+
+/// Hashes up to 4 `values` into a 2-element digest using the same procedure as the sequence of
+/// RESCR operations executed by the VM.
+pub fn digest(values: &[u128]) -> Vec<u128> {
+    assert!(values.len() <= HASH_STATE_RATE,
+        "expected no more than {}, but received {}", HASH_STATE_RATE, values.len());
+
+    let mut state = [field::ZERO; HASH_STATE_WIDTH];
+    state[..values.len()].copy_from_slice(values);
+    state.reverse();
+
+    for i in 0..HASHER.num_rounds() {
+        HASHER.apply_round(&mut state, i);
+    }
+
+    state.reverse();
+    return state[..HASH_DIGEST_SIZE].to_vec();
+}
+
+/// Merges an operation with the specified `op_code` and `op_value` into the program hash
+/// `state` using the program hash sponge.
+pub fn apply_hacc_round(state: &mut [u128], op_code: u128, op_value: u128, step: usize) {
+    SPONGE.apply_round_with_injection(state, &[op_code, op_value], step);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn apply_matrix(matrix: &[u128], state: &mut [u128]) {
+    let width = state.len();
+    let mut result = [field::ZERO; MAX_STATE_WIDTH];
+    for i in 0..width {
+        for j in 0..width {
+            result[i] = field::add(result[i], field::mul(matrix[i * width + j], state[j]));
+        }
+    }
+    state.copy_from_slice(&result[..width]);
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(all(test, feature = "std-rand"))]
+mod tests {
+
+    use crate::math::field;
+    use super::{ HASHER, SPONGE };
+
+    #[test]
+    fn inv_mds() {
+        for rescue in [HASHER, SPONGE].iter() {
+            let state = field::rand_vector(rescue.width());
+            let mut result = state.clone();
+            rescue.apply_mds(&mut result);
+            rescue.apply_inv_mds(&mut result);
+            assert_eq!(state, result);
+        }
+    }
+
+    #[test]
+    fn apply_round() {
+        // a plain round is the same as a round with zero-valued injection
+        let mut state1 = field::rand_vector(SPONGE.width());
+        let mut state2 = state1.clone();
+        SPONGE.apply_round(&mut state1, 3);
+        super::apply_hacc_round(&mut state2, 0, 0, 3);
+        assert_eq!(state1, state2);
+
+        // injected values change the state
+        let mut state3 = state1.clone();
+        let mut state4 = state1.clone();
+        super::apply_hacc_round(&mut state3, 0, 0, 4);
+        super::apply_hacc_round(&mut state4, 1, 0, 4);
+        assert_ne!(state3, state4);
+    }
+}
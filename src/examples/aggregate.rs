@@ -0,0 +1,21 @@
+use distaff::{ examples, math::field };
+use super::{ Example, utils::parse_args };
+
+pub fn get_example(args: &[String]) -> Example  {
+
+    // the threshold is specified by the 3rd argument; the remaining arguments are parsed in
+    // the same way as for other examples
+    let threshold: u128 = if args.len() > 2 { args[2].parse().unwrap() } else { u128::pow(2, 31) };
+    let mut other_args = args[..std::cmp::min(args.len(), 2)].to_vec();
+    other_args.extend_from_slice(&args[std::cmp::min(args.len(), 3)..]);
+    let (n, options) = parse_args(&other_args);
+
+    // generate random sequence of 32-bit values, and the program to check that their average
+    // is below the threshold; the values are passed to the secret tape A
+    let values: Vec<u128> = field::rand_vector(n).into_iter().map(|v| (v as u32) as u128).collect();
+    let example = Example::new(examples::average_check(&values, threshold), options);
+    println!("Generated a program to check that the average of {} values is below {}; expected result: {:?}",
+        n, threshold, example.expected_result);
+
+    return example;
+}
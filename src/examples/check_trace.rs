@@ -0,0 +1,67 @@
+use std::{ fs, time::Instant };
+use distaff::{ self, ProgramInputs, assembly };
+
+// TRACE CHECKER
+// ================================================================================================
+
+/// Compiles and executes the program in the file specified by `args[1]`, checks all transition
+/// constraints against its execution trace, and prints the result for every constraint group.
+/// Public inputs and secret inputs for tapes `A` and `B` can be specified by `args[2]`, `args[3]`
+/// and `args[4]` as comma-separated lists of values. The process exits with a non-zero code if
+/// any of the constraints did not hold. If `verbose` is set, NOOPs which the assembler added to
+/// the program are listed as well.
+pub fn run(args: &[String], verbose: bool) {
+    assert!(args.len() >= 2 && args.len() <= 5,
+        "expected arguments: program_file [public_inputs [secret_a [secret_b]]]");
+
+    let source = fs::read_to_string(&args[1])
+        .unwrap_or_else(|err| panic!("failed to read program file {}: {}", args[1], err));
+    let (program, padding) = assembly::compile_with_padding_report(&source)
+        .unwrap_or_else(|err| panic!("failed to compile program: {}", err));
+    if verbose {
+        println!("Padding added to the program:");
+        println!("{}", padding);
+        println!("--------------------------------");
+    }
+
+    let public_inputs = parse_values(args.get(2));
+    let secret_a = parse_values(args.get(3));
+    let secret_b = parse_values(args.get(4));
+    let inputs = ProgramInputs::new(&public_inputs, &secret_a, &secret_b);
+
+    let now = Instant::now();
+    let checks = distaff::check_trace(&program, &inputs);
+    println!("Checked execution trace of program {} in {} ms",
+        hex::encode(program.hash()), now.elapsed().as_millis());
+    println!("--------------------------------");
+
+    let mut num_failed = 0;
+    for check in checks.iter() {
+        match check.failure {
+            None => println!("PASS {} ({} constraints)", check.group, check.num_constraints),
+            Some(ref failure) => {
+                println!("FAIL {} ({} constraints): constraint {} ({}) did not hold at step {}",
+                    check.group, check.num_constraints, failure.index, failure.description, failure.step);
+                num_failed += 1;
+            }
+        }
+    }
+
+    println!("--------------------------------");
+    println!("{} groups passed; {} failed", checks.len() - num_failed, num_failed);
+    if num_failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn parse_values(arg: Option<&String>) -> Vec<u128> {
+    return match arg {
+        Some(arg) => arg.split(',').map(|v| v.trim()).filter(|v| !v.is_empty())
+            .map(|v| v.parse::<u128>().unwrap_or_else(|_| panic!("invalid value '{}'", v)))
+            .collect(),
+        None => Vec::new(),
+    };
+}
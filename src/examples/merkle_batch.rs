@@ -0,0 +1,123 @@
+use distaff::{ Program, ProgramInputs, assembly, math::field, crypto::rescue };
+use super::{ Example, utils::parse_args };
+
+pub fn get_example(args: &[String]) -> Example  {
+
+    // get the number of leaves to prove; the remaining arguments are parsed in the same way as
+    // for other examples, with tree depth taking place of the first parameter
+    let num_leaves: usize = if args.len() > 1 { args[1].parse().unwrap() } else { 8 };
+    let (depth, options) = if args.len() > 1 { parse_args(&args[1..]) } else { parse_args(args) };
+    assert!(depth >= 2, "tree depth must be at least 2, but received {}", depth);
+    assert!(num_leaves >= 1, "number of leaves must be at least 1, but received {}", num_leaves);
+
+    // build a Merkle tree and pick leaves for which membership will be proven
+    let tree = build_merkle_tree(depth);
+    let leaf_count = tree[0].len();
+    let leaf_indexes: Vec<usize> = field::prng_vector([7u8; 32], num_leaves).iter()
+        .map(|&v| (v % leaf_count as u128) as usize)
+        .collect();
+
+    // values on the stack are in reverse order
+    let root = tree[depth - 1][0];
+    let expected_result = vec![root[1], root[0]];
+    println!("Expected tree root: {:?}", root);
+
+    // generate the program to verify Merkle paths in a tree of given depth
+    let program = generate_batch_merkle_program(depth);
+    println!("Generated a program to verify {} Merkle proofs for a tree of depth {}", num_leaves, depth);
+
+    // the root is supplied via public inputs, and leaves and paths via secret tapes
+    let (a, b) = generate_program_inputs(&tree, &leaf_indexes);
+    let inputs = ProgramInputs::new(&expected_result, &a, &b);
+
+    // 2 elements at the top of the stack will be the output
+    let num_outputs = 2;
+
+    return Example {
+        program,
+        inputs,
+        options,
+        expected_result,
+        num_outputs
+    };
+}
+
+/// Returns a program which verifies a sequence of Merkle authentication paths for a tree of
+/// depth `n` against the root provided via public inputs. Before each path, the program reads
+/// a flag from tape A indicating whether there are more paths to verify.
+fn generate_batch_merkle_program(n: usize) -> Program {
+
+    // in the loop body, the stack starts out as [r1, r0]; after computing the root of the path
+    // it becomes [h1, h0, r1, r0]; the top 4 items are then duplicated and rearranged into
+    // [h1, r1, h0, r0] so that both pairs can be compared using assert.eq
+    let source = format!("
+    begin
+        read
+        while.true
+            read.ab
+            smpath.{}
+            dup.4 swap.2 roll.4 swap roll.4
+            assert.eq assert.eq
+            drop.2
+            read
+        end
+    end
+    ", n);
+
+    return assembly::compile(&source).unwrap();
+}
+
+/// Builds a set of inputs for the program created by the function above; for each leaf, tapes
+/// contain a flag, the leaf itself, and the authentication path for the leaf interlaced with
+/// binary decomposition of its index.
+fn generate_program_inputs(tree: &[Vec<[u128; 2]>], leaf_indexes: &[usize]) -> (Vec<u128>, Vec<u128>) {
+
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    let n = tree.len();
+
+    for &leaf_index in leaf_indexes.iter() {
+        // indicate that there is one more path to verify
+        a.push(field::ONE);
+
+        // push the leaf node onto secret input tapes A and B
+        let leaf = tree[0][leaf_index];
+        a.push(leaf[0]);
+        b.push(leaf[1]);
+
+        // push the authentication path together with bits of the position index
+        let mut index = leaf_index;
+        for i in 1..n {
+            let sibling = tree[i - 1][index ^ 1];
+            a.push(field::ZERO);
+            b.push((index & 1) as u128);
+            a.push(sibling[0]);
+            b.push(sibling[1]);
+            index = index >> 1;
+        }
+    }
+
+    // indicate that there are no more paths to verify
+    a.push(field::ZERO);
+
+    return (a, b);
+}
+
+/// Pseudo-randomly generates leaves for a Merkle tree of depth `n` and builds the tree; the
+/// first level of the tree contains the leaves, and the last level contains the root.
+fn build_merkle_tree(n: usize) -> Vec<Vec<[u128; 2]>> {
+    let leaf_count = usize::pow(2, (n - 1) as u32);
+    let values = field::prng_vector([1u8; 32], leaf_count * 2);
+
+    let mut tree = Vec::with_capacity(n);
+    tree.push(values.chunks(2).map(|v| [v[0], v[1]]).collect::<Vec<_>>());
+    for i in 1..n {
+        let level: Vec<[u128; 2]> = tree[i - 1].chunks(2).map(|nodes| {
+            let v = rescue::digest(&[nodes[0][0], nodes[0][1], nodes[1][0], nodes[1][1]]);
+            [v[0], v[1]]
+        }).collect();
+        tree.push(level);
+    }
+
+    return tree;
+}
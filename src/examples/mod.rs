@@ -1,13 +1,18 @@
-use distaff::{ Program, ProgramInputs, ProofOptions };
+use distaff::{ Program, ProgramInputs, ProofOptions, examples };
 
 mod utils;
 
+pub mod aggregate;
+pub mod check_trace;
 pub mod collatz;
 pub mod comparison;
 pub mod conditional;
 pub mod fibonacci;
 pub mod merkle;
+pub mod merkle_batch;
 pub mod range;
+pub mod sealed;
+pub mod suite;
 
 pub struct Example {
     pub program         : Program,
@@ -15,4 +20,18 @@ pub struct Example {
     pub num_outputs     : usize,
     pub options         : ProofOptions,
     pub expected_result : Vec<u128>
-}
\ No newline at end of file
+}
+
+impl Example {
+    /// Wraps an example provided by the library into an example which is executed with the
+    /// specified proof `options`.
+    pub fn new(example: examples::Example, options: ProofOptions) -> Example {
+        return Example {
+            program         : example.program,
+            inputs          : example.inputs,
+            num_outputs     : example.num_outputs,
+            options,
+            expected_result : example.expected_outputs,
+        };
+    }
+}
@@ -1,4 +1,4 @@
-use distaff::{ Program, ProgramInputs, assembly, math::field };
+use distaff::{ examples, math::field };
 use super::{ Example, utils::parse_args };
 
 pub fn get_example(args: &[String]) -> Example  {
@@ -6,29 +6,35 @@ pub fn get_example(args: &[String]) -> Example  {
     // get the number of values to range check and proof options
     let (n, options) = parse_args(args);
     
-    // generate random sequence of 64-bit values
+    // generate random sequence of 64-bit values, and the program to count values which fit
+    // into 63 bits; the values are passed to the secret tape A
     let values = generate_values(n);
-
-    // generate the program and expected results
-    let program = generate_range_check_program(n);
-    let expected_result = vec![count_63_bit_values(&values)];
+    let example = Example::new(examples::range_check(&values), options);
     println!("Generated a program to range-check {} values; expected result: {}", 
         n,
-        expected_result[0]);
+        example.expected_result[0]);
+
+    return example;
+}
 
-    // set public inputs to the initial sum (0), and pass values to the secret tape A
-    let inputs = ProgramInputs::new(&[0], &values, &[]);
+pub fn get_interval_example(args: &[String]) -> Example  {
 
-    // a single element from the top of the stack will be the output
-    let num_outputs = 1;
+    // interval bounds are specified by the 3rd and 4th arguments; the remaining arguments are
+    // parsed in the same way as for other examples
+    let lo: u128 = if args.len() > 2 { args[2].parse().unwrap() } else { u128::pow(2, 62) };
+    let hi: u128 = if args.len() > 3 { args[3].parse().unwrap() } else { u128::pow(2, 63) };
+    let mut other_args = args[..std::cmp::min(args.len(), 2)].to_vec();
+    other_args.extend_from_slice(&args[std::cmp::min(args.len(), 4)..]);
+    let (n, options) = parse_args(&other_args);
 
-    return Example {
-        program,
-        inputs,
-        options,
-        expected_result,
-        num_outputs
-    };
+    // generate random sequence of 64-bit values, and the program to count values which fall
+    // into the interval; the values are passed to the secret tape A
+    let values = generate_values(n);
+    let example = Example::new(examples::interval_check(&values, lo, hi), options);
+    println!("Generated a program to check {} values against interval [{}, {}]; expected result: {}",
+        n, lo, hi, example.expected_result[0]);
+
+    return example;
 }
 
 /// Generates a random sequence of 64-bit values.
@@ -39,34 +45,3 @@ fn generate_values(n: usize) -> Vec<u128> {
     }
     return values;
 }
-
-/// Generates a program to range-check a sequence of values.
-fn generate_range_check_program(n: usize) -> Program {
-
-    let mut program = String::with_capacity(n * 80);
-    program.push_str("begin ");
-
-    // repeat the cycle of the following operations:
-    // 1. read a value from secret tape A
-    // 2. check if it fits into 63 bits (result is 1 if true, 0 otherwise)
-    // 3. add the result into the running sum
-    for _ in 0..n {
-        program.push_str("read rc.63 add ");
-    }
-    program.push_str("end");
-
-    return assembly::compile(&program).unwrap();
-}
-
-/// Counts the number of values smaller than 63-bits in size.
-fn count_63_bit_values(values: &[u128]) -> u128 {
-    let p63: u128 = field::exp(2, 63);
-
-    let mut result = 0;
-    for &value in values.iter() {
-        if value < p63 {
-            result += 1;
-        }
-    }
-    return result;
-}
\ No newline at end of file
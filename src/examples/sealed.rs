@@ -0,0 +1,74 @@
+// CONSTANTS
+// ================================================================================================
+
+/// Environment variable from which the suite runner reads the hex-encoded secret key of the
+/// prover when a suite contains sealed inputs.
+pub const PROVER_KEY_VAR: &str = "DISTAFF_PROVER_KEY";
+
+/// Prefix which marks sealed inputs in the secret_a column of a suite file.
+pub const SEALED_PREFIX: &str = "sealed:";
+
+// COMMANDS
+// ================================================================================================
+
+/// Executes one of the commands for working with sealed inputs:
+///
+/// * `keygen` prints a new prover key pair;
+/// * `seal <public_key> <secret_a> [<secret_b>]` seals the secret tapes (values within a tape are
+///   separated by spaces) to the public key of a prover, and prints the result in the form in
+///   which it can be used in the secret_a column of a suite file.
+#[cfg(feature = "sealed-inputs")]
+pub fn run(args: &[String]) {
+    use distaff::{ ProgramInputs, crypto::box_ };
+
+    match args[0].as_str() {
+        "keygen" => {
+            assert!(args.len() == 1, "keygen command does not take any arguments");
+            let (secret_key, public_key) = box_::generate_keys();
+            println!("secret key: {}", hex::encode(secret_key));
+            println!("public key: {}", hex::encode(public_key));
+        },
+        "seal" => {
+            assert!(args.len() == 3 || args.len() == 4,
+                "expected arguments: <public_key> <secret_a> [<secret_b>]");
+            let public_key = parse_key(&args[1]);
+            let secret_a = parse_values(&args[2]);
+            let secret_b = args.get(3).map(|column| parse_values(column)).unwrap_or_default();
+            let inputs = ProgramInputs::new(&[], &secret_a, &secret_b);
+            println!("{}{}", SEALED_PREFIX, inputs.seal(&public_key));
+        },
+        _ => panic!("unknown command '{}'", args[0]),
+    }
+}
+
+#[cfg(not(feature = "sealed-inputs"))]
+pub fn run(args: &[String]) {
+    panic!("{} command requires the sealed-inputs feature", args[0]);
+}
+
+/// Reads the secret key of the prover from the environment variable specified by PROVER_KEY_VAR.
+#[cfg(feature = "sealed-inputs")]
+pub fn read_prover_key() -> [u8; 32] {
+    let key = std::env::var(PROVER_KEY_VAR)
+        .unwrap_or_else(|_| panic!("{} must be set to open sealed inputs", PROVER_KEY_VAR));
+    return parse_key(&key);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+#[cfg(feature = "sealed-inputs")]
+fn parse_key(key: &str) -> [u8; 32] {
+    let bytes = hex::decode(key.trim()).unwrap_or_else(|err| panic!("key is not a valid hex string: {}", err));
+    assert!(bytes.len() == 32, "key must be 32 bytes long, but was {} bytes", bytes.len());
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&bytes);
+    return result;
+}
+
+#[cfg(feature = "sealed-inputs")]
+fn parse_values(column: &str) -> Vec<u128> {
+    return column.split_whitespace()
+        .map(|v| v.parse::<u128>().unwrap_or_else(|_| panic!("invalid value '{}'", v)))
+        .collect();
+}
@@ -0,0 +1,181 @@
+use std::{ fs, path::Path, panic, time::Instant };
+use distaff::{ self, ProgramInputs, ProofOptions, assembly };
+use super::sealed::SEALED_PREFIX;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A single test case in a suite. Test cases are read from a CSV file in which every line has
+/// the following format (values within a column are separated by spaces):
+///
+/// `program_file, public_inputs, secret_a, secret_b, expected_outputs[, ext_factor[, num_queries[, grinding_factor]]]`
+///
+/// Paths to program files are relative to the location of the CSV file; empty lines and lines
+/// starting with `#` are ignored. Secret inputs sealed to the key of the prover (see `seal`
+/// command) can be specified in the secret_a column as `sealed:<hex>`; the secret_b column
+/// must be empty in this case.
+struct TestCase {
+    line            : usize,
+    program_file    : String,
+    inputs          : ProgramInputs,
+    expected_result : Vec<u128>,
+    options         : ProofOptions,
+}
+
+// SUITE RUNNER
+// ================================================================================================
+
+/// Executes and verifies all programs listed in the suite file specified by `args[1]`, and prints
+/// a summary of the results. The process exits with a non-zero code if any of the cases failed.
+pub fn run(args: &[String]) {
+    assert!(args.len() == 2, "expected exactly one argument: path to the suite file");
+    let suite_path = Path::new(&args[1]);
+    let base_dir = suite_path.parent().unwrap_or(Path::new("."));
+
+    let source = fs::read_to_string(suite_path)
+        .unwrap_or_else(|err| panic!("failed to read suite file {}: {}", suite_path.display(), err));
+    let cases = parse_suite(&source);
+    println!("Running {} test cases from {}", cases.len(), suite_path.display());
+    println!("--------------------------------");
+
+    let mut failures = Vec::new();
+    for case in cases.iter() {
+        let now = Instant::now();
+        match run_case(case, base_dir) {
+            Ok(()) => println!("ok     {} (line {}) in {} ms",
+                case.program_file, case.line, now.elapsed().as_millis()),
+            Err(msg) => {
+                println!("FAILED {} (line {}): {}", case.program_file, case.line, msg);
+                failures.push(case.line);
+            }
+        }
+    }
+
+    println!("--------------------------------");
+    println!("{} passed; {} failed", cases.len() - failures.len(), failures.len());
+    if failures.len() > 0 {
+        std::process::exit(1);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Compiles, executes, and verifies the program of a single test case.
+fn run_case(case: &TestCase, base_dir: &Path) -> Result<(), String> {
+
+    let program_path = base_dir.join(&case.program_file);
+    let source = match fs::read_to_string(&program_path) {
+        Ok(source) => source,
+        Err(err) => return Err(format!("failed to read program file: {}", err))
+    };
+    let program = match assembly::compile(&source) {
+        Ok(program) => program,
+        Err(err) => return Err(format!("failed to compile program: {}", err))
+    };
+
+    // execution failures are reported via panics; catch them so that other cases can still run
+    let num_outputs = case.expected_result.len();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        distaff::execute(&program, &case.inputs, num_outputs, &case.options)
+    }));
+    let (outputs, proof) = match result {
+        Ok(result) => result,
+        Err(_) => return Err(String::from("program execution failed"))
+    };
+
+    if outputs != case.expected_result {
+        return Err(format!("expected outputs {:?}, but received {:?}", case.expected_result, outputs));
+    }
+
+    return match distaff::verify(program.hash(), case.inputs.get_public_inputs(), &outputs, &proof) {
+        Ok(_) => Ok(()),
+        Err(msg) => Err(format!("failed to verify execution: {}", msg))
+    };
+}
+
+/// Parses test cases from the contents of a suite file.
+fn parse_suite(source: &str) -> Vec<TestCase> {
+    let default_options = ProofOptions::default();
+
+    let mut cases = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+
+        let columns: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+        assert!(columns.len() >= 5 && columns.len() <= 8,
+            "line {}: expected between 5 and 8 columns, but found {}", line_num, columns.len());
+
+        let public_inputs = parse_values(columns[1], line_num);
+        let inputs = if columns[2].starts_with(SEALED_PREFIX) {
+            assert!(columns[3].is_empty(), "line {}: secret_b column must be empty for sealed inputs", line_num);
+            unseal_inputs(&public_inputs, &columns[2][SEALED_PREFIX.len()..], line_num)
+        }
+        else {
+            let secret_a = parse_values(columns[2], line_num);
+            let secret_b = parse_values(columns[3], line_num);
+            ProgramInputs::new(&public_inputs, &secret_a, &secret_b)
+        };
+        let expected_result = parse_values(columns[4], line_num);
+
+        let ext_factor = parse_option(&columns, 5, default_options.extension_factor(), line_num);
+        let num_queries = parse_option(&columns, 6, default_options.num_queries(), line_num);
+        let grind_factor = parse_option(&columns, 7, default_options.grinding_factor() as usize, line_num);
+
+        cases.push(TestCase {
+            line            : line_num,
+            program_file    : columns[0].to_string(),
+            inputs          : inputs,
+            expected_result : expected_result,
+            options         : ProofOptions::new(ext_factor, num_queries, grind_factor as u32, default_options.hash_fn()),
+        });
+    }
+
+    return cases;
+}
+
+fn parse_values(column: &str, line_num: usize) -> Vec<u128> {
+    return column.split_whitespace()
+        .map(|v| v.parse::<u128>().unwrap_or_else(|_| panic!("line {}: invalid value '{}'", line_num, v)))
+        .collect();
+}
+
+#[cfg(feature = "sealed-inputs")]
+fn unseal_inputs(public_inputs: &[u128], sealed: &str, line_num: usize) -> ProgramInputs {
+    let secret_key = super::sealed::read_prover_key();
+    return ProgramInputs::unseal(public_inputs, sealed, &secret_key)
+        .unwrap_or_else(|err| panic!("line {}: {}", line_num, err));
+}
+
+#[cfg(not(feature = "sealed-inputs"))]
+fn unseal_inputs(_public_inputs: &[u128], _sealed: &str, line_num: usize) -> ProgramInputs {
+    panic!("line {}: sealed inputs require the sealed-inputs feature", line_num);
+}
+
+fn parse_option(columns: &[&str], index: usize, default: usize, line_num: usize) -> usize {
+    if index >= columns.len() || columns[index].is_empty() { return default; }
+    return columns[index].parse::<usize>()
+        .unwrap_or_else(|_| panic!("line {}: invalid proof option '{}'", line_num, columns[index]));
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use std::{ fs, path::Path };
+
+    #[test]
+    fn run_basic_suite() {
+        let suite_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("suites");
+        let source = fs::read_to_string(suite_dir.join("basic.csv")).unwrap();
+        let cases = super::parse_suite(&source);
+        assert_eq!(4, cases.len());
+
+        for case in cases.iter() {
+            assert_eq!(Ok(()), super::run_case(case, &suite_dir), "line {}", case.line);
+        }
+    }
+}
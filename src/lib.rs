@@ -1,6 +1,6 @@
-use log::debug;
+use std::io::Write;
+use std::panic::{ self, AssertUnwindSafe };
 use std::ops::Range;
-use std::time::Instant;
 
 #[cfg(test)]
 mod tests;
@@ -9,16 +9,39 @@ mod tests;
 // ================================================================================================
 pub mod crypto;
 pub mod math;
+pub use math::Felt;
 pub mod utils;
 
 mod stark;
-pub use stark::{ StarkProof, ProofOptions };
+pub use stark::{
+    StarkProof, FieldMismatch, PROOF_VERSION, ProofOptions, ProverContext, TraceState, Assertion, BoundaryConstraints, AirExtension, Transcript, TranscriptEntry,
+    VerifierLimits,
+    ProgressHandle, ProvingStage, ProvingCancelled, ProverEvent, Verbosity, CommittedTrace, CellOpening,
+    ShardedProver, ProofHeader, QueryTask, ProofPart,
+    describe_constraints, ConstraintGroupCheck, ConstraintFailure };
+
+// FRI internals, operation flags, and the trace table are exported for benchmarks and tooling
+// only; they are not covered by semver guarantees and may change in any release
+#[doc(hidden)]
+pub use stark::fri;
+#[doc(hidden)]
+pub use stark::OpFlags;
+#[doc(hidden)]
+pub use stark::TraceTable;
 
 mod processor;
-pub use processor::{ OpCode, OpHint };
+pub use processor::{ OpCode, OpHint, OpSpec, SecretUse, ExecutionReport, ExecutionObserver, HintProvider, InterpreterState, ExecutionPath, PathReport };
 
 mod programs;
-pub use programs::{ Program, ProgramInputs, assembly, blocks };
+pub use programs::{ Program, ProcedureSet, ProgramInputs, ProgramInputsBuilder, TapePolicy, VerificationKey, Registry, MembershipProof, BatchMembershipProof, get_program_commitment, assembly, blocks };
+#[cfg(feature = "examples")]
+pub use programs::examples;
+
+#[cfg(feature = "arbitrary")]
+pub mod testing;
+
+pub mod prelude;
+pub mod testvectors;
 
 // EXECUTOR
 // ================================================================================================
@@ -27,41 +50,384 @@ pub use programs::{ Program, ProgramInputs, assembly, blocks };
 /// 
 /// * `inputs` specifies the initial stack state and provides secret input tapes;
 /// * `num_outputs` specifies the number of elements from the top of the stack to be returned;
+///
+/// Panics if the execution cannot be proven; use `try_execute()` to get an error instead, or
+/// `execute_with()` to configure the execution further.
 pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> (Vec<u128>, StarkProof)
 {
-    assert!(num_outputs <= MAX_OUTPUTS, 
-        "cannot produce more than {} outputs, but requested {}", MAX_OUTPUTS, num_outputs);
+    return try_execute(program, inputs, num_outputs, options).unwrap_or_else(|msg| panic!("{}", msg));
+}
 
-    // execute the program to create an execution trace
-    let now = Instant::now();
-    let (trace, ctx_depth, loop_depth) = processor::execute(program, inputs);
-    let mut trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, options.extension_factor());
-    debug!("Generated execution trace of {} registers and {} steps in {} ms",
-        trace.register_count(),
-        trace.unextended_length(),
-        now.elapsed().as_millis());
+/// Executes the program and generates a proof of execution as described by the `execution`;
+/// returns the outputs, the proof, and a report describing how many of the provided secret inputs
+/// were consumed by the execution (the report is built from the same execution which is proven).
+///
+/// An error is returned if the execution cannot be proven; for example, if more than
+/// `MAX_OUTPUTS` outputs are requested, if too few operations were executed, if the execution
+/// trace would have too many registers, if any of the assertions or constraints of the AIR
+/// extension does not hold for the execution trace, or if proof generation is cancelled via the
+/// progress handle of the execution.
+pub fn execute_with(execution: Execution) -> Result<(Vec<u128>, StarkProof, ExecutionReport), String>
+{
+    return execute_and_prove(execution)?.map_err(|err| err.to_string());
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but returns
+/// an error instead of panicking if the execution cannot be proven; for example, if more than
+/// `MAX_OUTPUTS` outputs are requested, if too few operations were executed, or if the
+/// execution trace would have too many registers.
+pub fn try_execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> Result<(Vec<u128>, StarkProof), String>
+{
+    let (outputs, proof, _) = execute_with(Execution::new(program, inputs, num_outputs, options))?;
+    return Ok((outputs, proof));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// notifies the `observer` before and after every operation executed by the VM; this is the same
+/// as `execute_with()` for an execution configured via `Execution::with_observer()`.
+pub fn execute_with_observer(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions, observer: &mut dyn ExecutionObserver) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_observer(observer));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but values
+/// for operations marked with `OpHint::Custom` hints are supplied by the `hints` provider; this
+/// is the same as `execute_with()` for an execution configured via `Execution::with_hints()`.
+pub fn execute_with_hints(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions, hints: &mut dyn HintProvider) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_hints(hints));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// binds the `assertions` about intermediate states of the execution to the proof; this is the
+/// same as `execute_with()` for an execution configured via `Execution::with_assertions()`.
+/// 
+/// Panics if any of the assertions does not hold for the execution trace of the program.
+pub fn execute_with_assertions(program: &Program, inputs: &ProgramInputs, num_outputs: usize, assertions: &[Assertion], options: &ProofOptions) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_assertions(assertions));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// enforces constraints of the AIR `extension` against the execution trace of the program; this
+/// is the same as `execute_with()` for an execution configured via `Execution::with_extension()`.
+/// 
+/// Panics if the extension is not valid, or if any of its constraints does not hold for the
+/// execution trace of the program.
+pub fn execute_with_extension(program: &Program, inputs: &ProgramInputs, num_outputs: usize, extension: &dyn AirExtension, options: &ProofOptions) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_extension(extension));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// includes the `salt` of the program commitment in the returned proof; this is the same as
+/// `execute_with()` for an execution configured via `Execution::with_salt()`.
+pub fn execute_blinded(program: &Program, inputs: &ProgramInputs, num_outputs: usize, salt: &[u8; 32], options: &ProofOptions) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_salt(salt));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but reuses
+/// constants, domains, and FFT plans cached in the prover `context`; this is the same as
+/// `execute_with()` for an execution configured via `Execution::with_context()`.
+pub fn execute_with_context(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions, context: &ProverContext) -> (Vec<u128>, StarkProof)
+{
+    return prove_or_panic(Execution::new(program, inputs, num_outputs, options).with_context(context));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but reports
+/// progress of proof generation to the `progress` handle, and stops proof generation if it is
+/// cancelled via the handle; in such a case, `ProvingCancelled` error is returned. This is the
+/// same as `execute_with()` for an execution configured via `Execution::with_progress()`.
+pub fn execute_with_progress(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions, progress: &ProgressHandle) -> Result<(Vec<u128>, StarkProof), ProvingCancelled>
+{
+    let execution = Execution::new(program, inputs, num_outputs, options).with_progress(progress);
+    let (outputs, proof, _) = execute_and_prove(execution).unwrap_or_else(|msg| panic!("{}", msg))?;
+    return Ok((outputs, proof));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// returns a report describing how many of the provided secret inputs were consumed by the
+/// execution; this is the same as `execute_with()` for an execution with default parameters.
+pub fn execute_with_report(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> (Vec<u128>, StarkProof, ExecutionReport)
+{
+    return execute_with(Execution::new(program, inputs, num_outputs, options)).unwrap_or_else(|msg| panic!("{}", msg));
+}
+
+/// Executes the specified `program` in the same way as `execute()` function does, but also
+/// measures peak memory allocated while the execution trace is generated and during every stage
+/// of proof generation. The measurements are taken by `utils::memory::TrackingAllocator`, which
+/// must be installed as the global allocator; otherwise, all measurements are zeros.
+#[cfg(feature = "memory-stats")]
+pub fn execute_with_memory_report(program: &Program, inputs: &ProgramInputs, num_outputs: usize, options: &ProofOptions) -> (Vec<u128>, StarkProof, utils::memory::MemoryReport)
+{
+    use std::sync::{ Arc, Mutex };
+    use utils::memory;
+
+    memory::reset_peak();
+    let (mut trace, boundary, _) = execute_program(&mut Execution::new(program, inputs, num_outputs, options)).unwrap_or_else(|msg| panic!("{}", msg));
+    let trace_generation = memory::reset_peak();
+
+    // when a new stage starts, the peak since the start of the previous stage is attributed
+    // to the previous stage
+    let stages: Arc<Mutex<Vec<(ProvingStage, usize)>>> = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&stages);
+    let progress = ProgressHandle::with_callback(move |stage, _| {
+        let mut stages = recorder.lock().expect("memory report lock is poisoned");
+        if stages.last().map(|&(last, _)| last) != Some(stage) {
+            if let Some(last) = stages.last_mut() {
+                last.1 = memory::reset_peak();
+            }
+            stages.push((stage, 0));
+        }
+    });
+
+    let proof = stark::prove(&mut trace, &boundary, None, options,
+        &ProverContext::new(), &progress).expect("proof generation cannot be cancelled without cancelling the handle");
+
+    let mut stages = stages.lock().expect("memory report lock is poisoned").clone();
+    if let Some(last) = stages.last_mut() {
+        last.1 = memory::reset_peak();
+    }
+    return (boundary.get_outputs().to_vec(), proof, utils::memory::MemoryReport { trace_generation, stages });
+}
+
+/// Executes the program described by the `execution` in the same way as `execute_with()`
+/// function does, but instead of returning the proof, writes it to the `writer` section by
+/// section as the sections are built. This avoids keeping the entire proof object and its
+/// serialized copy in memory at the same time; the written bytes are the same as bincode
+/// serialization of the proof, and thus, can be deserialized with `StarkProof::from_bytes()`. An
+/// error is returned if the execution cannot be proven, or if writing to the `writer` fails.
+pub fn execute_to_writer<W: Write>(mut execution: Execution, writer: &mut W) -> Result<Vec<u128>, String>
+{
+    let (mut trace, boundary, _) = execute_program(&mut execution)?;
+    let default_context = ProverContext::new();
+    let default_progress = ProgressHandle::new();
+    stark::prove_to_writer(&mut trace, &boundary, execution.extension, execution.salt, execution.options,
+        execution.context.unwrap_or(&default_context), execution.progress.unwrap_or(&default_progress), writer)?;
+    return Ok(boundary.get_outputs().to_vec());
+}
+
+/// Executes the program described by the `execution` in the same way as `execute_with()`
+/// function does, but also returns the extended execution trace committed to by the proof. The
+/// prover can keep the trace and later open arbitrary cells of it via
+/// `CommittedTrace::open_cells()`; this gives light clients access to intermediate states of the
+/// VM without generating a new proof. Openings are checked against the proof using
+/// `verify_cells()` function.
+pub fn execute_with_trace(mut execution: Execution) -> Result<(Vec<u128>, StarkProof, CommittedTrace), String>
+{
+    let (trace, boundary, _) = execute_program(&mut execution)?;
+    let default_context = ProverContext::new();
+    let default_progress = ProgressHandle::new();
+    let (mut proof, trace) = stark::prove_committed(trace, &boundary, execution.extension, execution.options,
+        execution.context.unwrap_or(&default_context), execution.progress.unwrap_or(&default_progress))
+        .map_err(|err| err.to_string())?;
+    if let Some(salt) = execution.salt {
+        proof.set_program_salt(salt);
+    }
+    return Ok((boundary.get_outputs().to_vec(), proof, trace));
+}
+
+/// Executes the program described by the `execution` and commits to its execution trace, but
+/// leaves opening the commitments at query positions to `ShardedProver::open()`. This way, the
+/// query phase of very large proofs can be distributed across worker threads: the coordinator
+/// splits query positions into tasks via `ShardedProver::tasks()`, every worker opens the tasks
+/// assigned to it using a clone of the prover (clones share the committed trace), and the opened
+/// parts are merged into a proof by `StarkProof::assemble()`.
+pub fn execute_sharded(mut execution: Execution) -> Result<(Vec<u128>, ShardedProver), String>
+{
+    let (trace, boundary, _) = execute_program(&mut execution)?;
+    let default_context = ProverContext::new();
+    let default_progress = ProgressHandle::new();
+    let mut prover = stark::prove_sharded(trace, &boundary, execution.extension, execution.options,
+        execution.context.unwrap_or(&default_context), execution.progress.unwrap_or(&default_progress))
+        .map_err(|err| err.to_string())?;
+    if let Some(salt) = execution.salt {
+        prover.set_program_salt(salt);
+    }
+    return Ok((boundary.get_outputs().to_vec(), prover));
+}
+
+/// Describes a program execution to be proven by `execute_with()` function (or by
+/// `execute_to_writer()`, `execute_with_trace()`, and `execute_sharded()` functions). Besides
+/// the program, its inputs, the number of outputs, and proof options, an execution can be
+/// configured with any combination of the optional parameters below.
+pub struct Execution<'a> {
+    program     : &'a Program,
+    inputs      : &'a ProgramInputs,
+    num_outputs : usize,
+    options     : &'a ProofOptions,
+    assertions  : &'a [Assertion],
+    extension   : Option<&'a dyn AirExtension>,
+    salt        : Option<&'a [u8; 32]>,
+    context     : Option<&'a ProverContext>,
+    progress    : Option<&'a ProgressHandle>,
+    observer    : Option<&'a mut dyn ExecutionObserver>,
+    hints       : Option<&'a mut dyn HintProvider>,
+}
+
+impl<'a> Execution<'a> {
+
+    /// Returns an execution of the `program` with the specified `inputs` which returns
+    /// `num_outputs` elements from the top of the stack, and is proven using proof `options`.
+    pub fn new(program: &'a Program, inputs: &'a ProgramInputs, num_outputs: usize, options: &'a ProofOptions) -> Execution<'a> {
+        return Execution {
+            program, inputs, num_outputs, options,
+            assertions  : &[],
+            extension   : None,
+            salt        : None,
+            context     : None,
+            progress    : None,
+            observer    : None,
+            hints       : None,
+        };
+    }
+
+    /// Binds the `assertions` about intermediate states of the execution to the proof. Every
+    /// assertion states that a user stack register holds a given value at a given step of the
+    /// execution trace; assertions are enforced as additional boundary constraints, and are
+    /// included in the proof.
+    pub fn with_assertions(mut self, assertions: &'a [Assertion]) -> Execution<'a> {
+        self.assertions = assertions;
+        return self;
+    }
+
+    /// Enforces constraints of the AIR `extension` against the execution trace. The identifier
+    /// of the extension is included in the proof, and the proof can be verified only with the
+    /// same extension (see `Verification::with_extension()`).
+    pub fn with_extension(mut self, extension: &'a dyn AirExtension) -> Execution<'a> {
+        self.extension = Some(extension);
+        return self;
+    }
+
+    /// Includes the `salt` of the program commitment in the proof. This way, the program hash
+    /// can be kept confidential by publishing only the commitment (see `Program::commitment()`),
+    /// and the proof can be verified against the commitment (see
+    /// `Verification::with_commitment()`).
+    ///
+    /// The salt should be random and must be kept secret together with the program hash: anyone
+    /// who has the proof (and thus, the salt) can check whether the commitment is for a given
+    /// program.
+    pub fn with_salt(mut self, salt: &'a [u8; 32]) -> Execution<'a> {
+        self.salt = Some(salt);
+        return self;
+    }
+
+    /// Reuses constants, domains, and FFT plans cached in the prover `context`. The cache is
+    /// keyed by the shape of the execution trace (its length, the extension factor, and the
+    /// depths of context, loop, and user stacks), and so, reusing a context speeds up proving
+    /// many programs of the same shape; the proofs are the same as without the context.
+    pub fn with_context(mut self, context: &'a ProverContext) -> Execution<'a> {
+        self.context = Some(context);
+        return self;
+    }
+
+    /// Reports progress of proof generation to the `progress` handle, and stops proof generation
+    /// if it is cancelled via the handle. Program execution itself cannot be cancelled.
+    pub fn with_progress(mut self, progress: &'a ProgressHandle) -> Execution<'a> {
+        self.progress = Some(progress);
+        return self;
+    }
+
+    /// Notifies the `observer` before and after every operation executed by the VM.
+    pub fn with_observer(mut self, observer: &'a mut dyn ExecutionObserver) -> Execution<'a> {
+        self.observer = Some(observer);
+        return self;
+    }
+
+    /// Supplies values for operations marked with `OpHint::Custom` hints from the `hints`
+    /// provider.
+    pub fn with_hints(mut self, hints: &'a mut dyn HintProvider) -> Execution<'a> {
+        self.hints = Some(hints);
+        return self;
+    }
+}
+
+/// Executes and proves the `execution`; the outer error is returned if the execution cannot be
+/// proven, and the inner one if proof generation is cancelled.
+fn execute_and_prove(mut execution: Execution) -> Result<Result<(Vec<u128>, StarkProof, ExecutionReport), ProvingCancelled>, String>
+{
+    let (mut trace, boundary, report) = execute_program(&mut execution)?;
+    let default_context = ProverContext::new();
+    let default_progress = ProgressHandle::new();
+    let proof = stark::prove(&mut trace, &boundary, execution.extension, execution.options,
+        execution.context.unwrap_or(&default_context), execution.progress.unwrap_or(&default_progress));
+    return Ok(proof.map(|mut proof| {
+        if let Some(salt) = execution.salt {
+            proof.set_program_salt(salt);
+        }
+        (boundary.get_outputs().to_vec(), proof, report)
+    }));
+}
+
+fn prove_or_panic(execution: Execution) -> (Vec<u128>, StarkProof)
+{
+    let (outputs, proof, _) = execute_with(execution).unwrap_or_else(|msg| panic!("{}", msg));
+    return (outputs, proof);
+}
+
+/// Executes the `program` and returns its execution trace together with boundary constraints
+/// binding the trace to the program hash, public inputs, outputs, and `assertions`; also makes
+/// sure that the trace is long enough to be proven, and that all constraints of the AIR
+/// `extension` hold for it. Boundary constraints are checked against the trace by the prover.
+fn execute_program(execution: &mut Execution) -> Result<(stark::TraceTable, BoundaryConstraints, ExecutionReport), String>
+{
+    let (program, inputs, num_outputs, options) = (execution.program, execution.inputs, execution.num_outputs, execution.options);
+    let (assertions, extension) = (execution.assertions, execution.extension);
+    if num_outputs > MAX_OUTPUTS {
+        return Err(format!("cannot produce more than {} outputs, but requested {}", MAX_OUTPUTS, num_outputs));
+    }
+
+    // execute the program to create an execution trace, and make sure the trace is not too
+    // wide to be proven before building the trace table
+    let (mut no_observer, mut no_hints) = ((), ());
+    let observer: &mut dyn ExecutionObserver = match execution.observer.as_deref_mut() {
+        Some(observer) => observer,
+        None => &mut no_observer,
+    };
+    let hints: &mut dyn HintProvider = match execution.hints.as_deref_mut() {
+        Some(hints) => hints,
+        None => &mut no_hints,
+    };
+    let (trace, ctx_depth, loop_depth, report) = processor::execute_with_report(program, inputs, observer, hints);
+    let stack_depth = TraceState::compute_stack_depth(trace.len(), ctx_depth, loop_depth);
+    stark::TraceTable::check_dimensions(ctx_depth, loop_depth, stack_depth)?;
+    let trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, options.extension_factor());
 
     // copy the user stack state the the last step to return as output
     let last_state = trace.get_last_state();
     let outputs = last_state.user_stack()[..num_outputs].to_vec();
 
     // make sure number of executed operations was sufficient
-    assert!(last_state.op_counter() as usize >= MIN_TRACE_LENGTH,
-        "a program must consist of at least {} operation, but only {} were executed",
-        MIN_TRACE_LENGTH,
-        last_state.op_counter());
+    if (last_state.op_counter() as usize) < MIN_TRACE_LENGTH {
+        return Err(format!("a program must consist of at least {} operation, but only {} were executed",
+            MIN_TRACE_LENGTH, last_state.op_counter()));
+    }
 
-    // make sure program hash generated by the VM matches the hash of the program
-    let program_hash = utils::as_bytes(last_state.program_hash());
-    assert!(program.hash() == program_hash,
-        "expected program hash {} does not match trace hash {}",
-        hex::encode(program.hash()),
-        hex::encode(program_hash));
+    // make sure all constraints of the AIR extension hold for the execution trace
+    if let Some(extension) = extension {
+        stark::validate_extension(extension)?;
+        let mut evaluations = vec![math::field::ZERO; extension.constraint_degrees().len()];
+        for step in 0..(trace.unextended_length() - 1) {
+            extension.evaluate(&trace.get_state(step), &trace.get_state(step + 1), &mut evaluations);
+            if let Some(i) = evaluations.iter().position(|&v| v != math::field::ZERO) {
+                return Err(format!("constraint {} of AIR extension {} did not hold at step {}", i, extension.id(), step));
+            }
+        }
+    }
 
-    // generate STARK proof
-    let proof = stark::prove(&mut trace, inputs.get_public_inputs(), &outputs, options);
+    let boundary = build_boundary(program.hash(), inputs.get_public_inputs(), &outputs, assertions);
+    return Ok((trace, boundary, report));
+}
 
-    return (outputs, proof);
+/// Returns boundary constraints which bind a proof to the `program_hash`, public `inputs`,
+/// `outputs`, and `assertions`.
+fn build_boundary(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], assertions: &[Assertion]) -> BoundaryConstraints
+{
+    return BoundaryConstraints::program_hash(program_hash)
+        .with_inputs(inputs)
+        .with_outputs(outputs)
+        .with_assertions(assertions);
 }
 
 // VERIFIER
@@ -69,9 +435,551 @@ pub fn execute(program: &Program, inputs: &ProgramInputs, num_outputs: usize, op
 
 /// Verifies that if a program with the specified `program_hash` is executed with the 
 /// provided `public_inputs` and some secret inputs, the result is equal to the `outputs`.
+/// If the proof contains assertions about intermediate states of the execution, these
+/// assertions are verified as well. Use `verify_with()` to configure the verification further.
 pub fn verify(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
 {
-    return stark::verify(program_hash, public_inputs, outputs, proof);
+    return verify_with(Verification::new(program_hash, public_inputs, outputs), proof);
+}
+
+/// Verifies the `proof` as described by the `verification`: if a program with the specified
+/// program hash is executed with the provided public inputs and some secret inputs, the result is
+/// equal to the specified outputs. If the proof contains assertions about intermediate states of
+/// the execution, these assertions are verified as well.
+pub fn verify_with(verification: Verification, proof: &StarkProof) -> Result<bool, String>
+{
+    let Verification { program_hash, public_inputs, outputs, assertions, extension, commitment, allow_insecure, limits, transcript } = verification;
+    if let Some(assertions) = assertions {
+        if assertions != proof.assertions() {
+            return Err(String::from("assertions do not match the assertions bound by the proof"));
+        }
+    }
+    if let Some(commitment) = commitment {
+        let salt = match proof.program_salt() {
+            Some(salt) => salt,
+            None => return Err(String::from("proof does not contain a program commitment salt")),
+        };
+        if !crypto::ct_eq(&get_program_commitment(program_hash, salt, proof.options().hash_fn()), commitment) {
+            return Err(String::from("program hash does not match the program commitment"));
+        }
+    }
+
+    let default_limits = VerifierLimits::default();
+    let boundary = build_boundary(program_hash, public_inputs, outputs, proof.assertions());
+    return stark::verify(&boundary, extension, proof, allow_insecure, limits.unwrap_or(&default_limits), transcript);
+}
+
+/// Describes how a proof should be verified by `verify_with()` function. Besides the program hash,
+/// public inputs, and outputs of the execution, a verification can be configured with any
+/// combination of the optional parameters below.
+pub struct Verification<'a> {
+    program_hash    : &'a [u8; 32],
+    public_inputs   : &'a [u128],
+    outputs         : &'a [u128],
+    assertions      : Option<&'a [Assertion]>,
+    extension       : Option<&'a dyn AirExtension>,
+    commitment      : Option<&'a [u8; 32]>,
+    allow_insecure  : bool,
+    limits          : Option<&'a VerifierLimits>,
+    transcript      : Option<&'a mut Transcript>,
+}
+
+impl<'a> Verification<'a> {
+
+    /// Returns a verification of an execution of a program with the specified `program_hash`
+    /// with the provided `public_inputs` which resulted in the specified `outputs`.
+    pub fn new(program_hash: &'a [u8; 32], public_inputs: &'a [u128], outputs: &'a [u128]) -> Verification<'a> {
+        return Verification {
+            program_hash, public_inputs, outputs,
+            assertions      : None,
+            extension       : None,
+            commitment      : None,
+            allow_insecure  : false,
+            limits          : None,
+            transcript      : None,
+        };
+    }
+
+    /// Makes sure that the proof binds exactly the specified `assertions` about intermediate
+    /// states of the execution; assertions bound by the proof are verified either way.
+    pub fn with_assertions(mut self, assertions: &'a [Assertion]) -> Verification<'a> {
+        self.assertions = Some(assertions);
+        return self;
+    }
+
+    /// Verifies that the execution trace satisfies constraints of the AIR `extension`. The proof
+    /// must have been generated with an extension with the same identifier.
+    pub fn with_extension(mut self, extension: &'a dyn AirExtension) -> Verification<'a> {
+        self.extension = Some(extension);
+        return self;
+    }
+
+    /// Verifies that the program hash is the one committed to by the program `commitment`. The
+    /// salt of the commitment is taken from the proof, and the commitment is computed using the
+    /// hash function specified in options of the proof.
+    pub fn with_commitment(mut self, commitment: &'a [u8; 32]) -> Verification<'a> {
+        self.commitment = Some(commitment);
+        return self;
+    }
+
+    /// Accepts insecure proofs (e.g. proofs generated using `ProofOptions::dev()`). Such proofs
+    /// provide very little security and should be used only during development.
+    pub fn allow_insecure(mut self) -> Verification<'a> {
+        self.allow_insecure = true;
+        return self;
+    }
+
+    /// Rejects the proof if its structure exceeds the specified `limits`; by default, the
+    /// default limits are enforced.
+    pub fn with_limits(mut self, limits: &'a VerifierLimits) -> Verification<'a> {
+        self.limits = Some(limits);
+        return self;
+    }
+
+    /// Records all checks performed during verification in the `transcript` (e.g. which Merkle
+    /// proofs were checked against which roots, and which FRI layers were checked). If
+    /// verification fails, the last entry of the transcript is the check which failed.
+    pub fn with_transcript(mut self, transcript: &'a mut Transcript) -> Verification<'a> {
+        self.transcript = Some(transcript);
+        return self;
+    }
+}
+
+/// Verifies program execution in the same way as `verify()` function does, but takes the proof
+/// serialized into `proof_bytes`. The proof can be serialized in the current layout or in any
+/// earlier layout which is still supported (see `StarkProof::from_bytes()`); this way, archived
+/// proofs can be verified without knowing the version of the prover which generated them. Proofs
+/// produced by v0.5.1 are read, but rejected with an error since the VM has changed since.
+pub fn verify_bytes(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof_bytes: &[u8]) -> Result<bool, String>
+{
+    let proof = StarkProof::from_bytes(proof_bytes)?;
+    return verify(program_hash, public_inputs, outputs, &proof);
+}
+
+/// Verifies program execution in the same way as `verify_bytes()` function does, but rejects the
+/// proof if its size or structure exceeds the specified `limits`. The limits are enforced while
+/// the proof is deserialized, before any hashing is done; this way, services which verify proofs
+/// received from untrusted parties cannot be made to spend unbounded time and memory on a proof.
+pub fn verify_with_limits(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof_bytes: &[u8], limits: &VerifierLimits) -> Result<bool, String>
+{
+    let proof = StarkProof::from_bytes_with_limits(proof_bytes, limits)?;
+    return verify_with(Verification::new(program_hash, public_inputs, outputs).with_limits(limits), &proof);
+}
+
+/// Quickly checks whether the `proof` was generated for a program with the specified
+/// `program_hash` executed with the provided `public_inputs`, without verifying the proof. This
+/// is meant to reject mismatched proofs before the costlier full verification; a proof which
+/// passes the check must still be verified using `verify()`.
+pub fn verify_program_binding(program_hash: &[u8; 32], public_inputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+{
+    return stark::verify_program_binding(program_hash, public_inputs, proof);
+}
+
+/// Verifies program execution in the same way as `verify()` function does, but takes the program
+/// hash from the verification `key`. In addition, makes sure that the numbers of public inputs and
+/// outputs, as well as the field and options of the proof, match the ones specified by the key.
+pub fn verify_with_key(key: &VerificationKey, public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+{
+    if public_inputs.len() != key.num_inputs() {
+        return Err(format!("expected {} public inputs, but received {}", key.num_inputs(), public_inputs.len()));
+    }
+    if outputs.len() != key.num_outputs() {
+        return Err(format!("expected {} outputs, but received {}", key.num_outputs(), outputs.len()));
+    }
+    if proof.field_modulus() != key.field_modulus() {
+        return Err(String::from("proof was generated in a field different from the one specified by the verification key"));
+    }
+    if proof.options() != key.options() {
+        return Err(String::from("proof options do not match the options specified by the verification key"));
+    }
+    return verify_with(Verification::new(key.program_hash(), public_inputs, outputs), proof);
+}
+
+/// Verifies program execution in the same way as `verify()` function does, but also returns a
+/// transcript of all checks performed during verification (e.g. which Merkle proofs were checked
+/// against which roots, and which FRI layers were checked). The transcript is returned regardless
+/// of the verification outcome; if verification fails, the last entry of the transcript is the
+/// check which failed. This is the same as `verify_with()` for a verification configured via
+/// `Verification::with_transcript()`.
+pub fn verify_with_transcript(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> (Result<bool, String>, Transcript)
+{
+    let mut transcript = Transcript::new();
+    let result = verify_with(Verification::new(program_hash, public_inputs, outputs).with_transcript(&mut transcript), proof);
+    return (result, transcript);
+}
+
+/// Verifies program execution in the same way as `verify()` function does, and also verifies
+/// that the execution trace satisfies the specified `assertions` about its intermediate states.
+/// The assertions must be the same as the ones the proof was generated for. This is the same as
+/// `verify_with()` for a verification configured via `Verification::with_assertions()`.
+pub fn verify_with_assertions(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], assertions: &[Assertion], proof: &StarkProof) -> Result<bool, String>
+{
+    return verify_with(Verification::new(program_hash, public_inputs, outputs).with_assertions(assertions), proof);
+}
+
+/// Verifies program execution in the same way as `verify()` function does, and also verifies
+/// that the execution trace satisfies constraints of the AIR `extension`. The proof must have been
+/// generated with an extension with the same identifier. This is the same as `verify_with()` for
+/// a verification configured via `Verification::with_extension()`.
+pub fn verify_with_extension(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], extension: &dyn AirExtension, proof: &StarkProof) -> Result<bool, String>
+{
+    return verify_with(Verification::new(program_hash, public_inputs, outputs).with_extension(extension), proof);
+}
+
+/// Verifies program execution in the same way as `verify()` function does; but if
+/// `allow_insecure` is set to true, also accepts insecure proofs (e.g. proofs generated using
+/// `ProofOptions::dev()`). Such proofs provide very little security and should be used only during development.
+/// This is the same as `verify_with()` for a verification configured via
+/// `Verification::allow_insecure()`.
+pub fn verify_with_options(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof: &StarkProof, allow_insecure: bool) -> Result<bool, String>
+{
+    let verification = Verification::new(program_hash, public_inputs, outputs);
+    return verify_with(if allow_insecure { verification.allow_insecure() } else { verification }, proof);
+}
+
+/// Verifies that if a program with the specified `program_hash` is executed with the provided
+/// `public_inputs` and some secret inputs, outputs at the specified positions are equal to the
+/// specified values, and all other outputs are committed to by the specified digest.
+///
+/// * `outputs` is a list of (position, value) tuples for the outputs the verifier cares about.
+/// * `masked_digest` is the digest of the outputs at positions not included in `outputs`, taken
+///   in the order of their positions and computed as `crypto::digest_outputs()` does; the values
+///   of these outputs are taken from the proof, and the digest binds them to the verification.
+pub fn verify_masked(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[(usize, u128)], masked_digest: &[u128; 2], proof: &StarkProof) -> Result<bool, String>
+{
+    let proof_outputs = proof.outputs();
+    for (i, &(position, value)) in outputs.iter().enumerate() {
+        if position >= proof_outputs.len() {
+            return Err(format!("output position {} is out of bounds; proof contains only {} outputs",
+                position, proof_outputs.len()));
+        }
+        if outputs[..i].iter().any(|&(p, _)| p == position) {
+            return Err(format!("output position {} is specified more than once", position));
+        }
+        if !crypto::ct_eq_elements(&proof_outputs[position..(position + 1)], &[value]) {
+            return Err(format!("output at position {} does not match the expected value", position));
+        }
+    }
+
+    let masked_outputs: Vec<u128> = proof_outputs.iter().enumerate()
+        .filter(|(i, _)| !outputs.iter().any(|&(position, _)| position == *i))
+        .map(|(_, &value)| value).collect();
+    if !crypto::ct_eq_elements(&crypto::digest_outputs(&masked_outputs), masked_digest) {
+        return Err(String::from("masked outputs do not match the expected digest"));
+    }
+
+    return verify_with(Verification::new(program_hash, public_inputs, proof_outputs), proof);
+}
+
+/// Verifies execution of a program compiled with `assembly::compile_with_output_digest()`. The
+/// `direct_outputs` are the outputs returned by the program directly (at most 4, starting with
+/// the top one), and the `output_digest` is the digest of all other outputs, computed as
+/// `crypto::digest_outputs()` does. Since the digest is at the top of the stack when the program
+/// completes, the proof must have been generated for at least 2 + `direct_outputs.len()` outputs.
+pub fn verify_with_output_digest(program_hash: &[u8; 32], public_inputs: &[u128], direct_outputs: &[u128], output_digest: &[u128; 2], proof: &StarkProof) -> Result<bool, String>
+{
+    if direct_outputs.len() > MAX_DIRECT_OUTPUTS {
+        return Err(format!("expected at most {} direct outputs, but received {}",
+            MAX_DIRECT_OUTPUTS, direct_outputs.len()));
+    }
+    let mut outputs = output_digest.to_vec();
+    outputs.extend_from_slice(direct_outputs);
+    return verify_with(Verification::new(program_hash, public_inputs, &outputs), proof);
+}
+
+/// Verifies execution of a program which committed to some of its secret inputs using
+/// `commit_tape` macro from `assembly::stdlib`, and returns the values revealed by the
+/// `disclosure` as (position, value) tuples. `position` specifies where the digest computed by
+/// the macro is located in the outputs of the program; the digest is taken from the proof and
+/// must match the digest recomputed from the disclosure.
+pub fn verify_disclosure(program_hash: &[u8; 32], public_inputs: &[u128], position: usize, disclosure: &utils::disclosure::Disclosure, proof: &StarkProof) -> Result<Vec<(usize, u128)>, String>
+{
+    let outputs = proof.outputs();
+    if position + 2 > outputs.len() {
+        return Err(format!("digest position {} is out of bounds; proof contains only {} outputs",
+            position, outputs.len()));
+    }
+    disclosure.validate()?;
+    if !crypto::ct_eq_elements(&outputs[position..(position + 2)], &disclosure.digest()) {
+        return Err(format!("disclosed values do not match the digest at output position {}", position));
+    }
+
+    verify_with(Verification::new(program_hash, public_inputs, outputs), proof)?;
+    return Ok(disclosure.revealed());
+}
+
+/// Verifies execution of a program in the same way as `verify()` function does, and then checks
+/// the cells `opening` against the trace commitment of the proof; returns values of the opened
+/// cells in the order in which they were requested.
+///
+/// Only a small number of positions of the extended trace are checked by the verifier, and so,
+/// the proof guarantees that the committed trace is close to a valid execution trace, but not
+/// that every committed row belongs to it. Openings are thus suited for occasional spot checks,
+/// and values which must be guaranteed should be bound to the proof via assertions instead.
+pub fn verify_cells(program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], opening: &CellOpening, proof: &StarkProof) -> Result<Vec<u128>, String>
+{
+    verify_with(Verification::new(program_hash, public_inputs, outputs), proof)?;
+    return opening.verify(proof);
+}
+
+/// Verifies that a program with the specified `program_hash` was executed with the provided
+/// `public_inputs` and some secret inputs, and returns the outputs of the execution. Unlike
+/// `verify()`, the outputs do not need to be known in advance: they are read from the proof,
+/// and since they are bound to the execution trace via boundary constraints, verification fails
+/// if they were tampered with.
+pub fn verify_and_extract(program_hash: &[u8; 32], public_inputs: &[u128], proof: &StarkProof) -> Result<Vec<u128>, String>
+{
+    let outputs = proof.outputs();
+    verify_with(Verification::new(program_hash, public_inputs, outputs), proof)?;
+    return Ok(outputs.to_vec());
+}
+
+/// Verifies program execution in the same way as `verify()` function does, and also verifies
+/// that the executed program is one of the programs in the registry with the specified
+/// `registry_root`. The registry must have been built using the hash function specified in
+/// options of the proof.
+///
+/// Hash of the executed program is taken from the `membership` proof; thus, the verifier does
+/// not need to know it in advance, but the hash is not hidden from the verifier either.
+pub fn verify_with_registry(registry_root: &[u8; 32], membership: &MembershipProof, public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+{
+    let program_hash = match membership.program_hash() {
+        Some(program_hash) => program_hash,
+        None => return Err(String::from("registry membership proof is malformed")),
+    };
+    if !Registry::verify(registry_root, program_hash, membership, proof.options().hash_fn()) {
+        return Err(String::from("program is not a member of the registry"));
+    }
+    return verify_with(Verification::new(program_hash, public_inputs, outputs), proof);
+}
+
+/// Verifies executions of several programs from the registry with the specified root using a
+/// single batch membership proof. The i-th element of `executions` contains public inputs,
+/// outputs, and the proof of execution of the i-th program in the `membership` proof; the
+/// membership proof is checked using the hash function specified in options of the first proof.
+pub fn verify_batch_with_registry(registry_root: &[u8; 32], membership: &BatchMembershipProof, executions: &[(&[u128], &[u128], &StarkProof)]) -> Result<bool, String>
+{
+    let program_hashes = membership.program_hashes();
+    if executions.is_empty() || executions.len() != program_hashes.len() {
+        return Err(format!("expected {} executions for the registry membership proof, but received {}",
+            program_hashes.len(), executions.len()));
+    }
+    let hash_fn = executions[0].2.options().hash_fn();
+    if !Registry::verify_batch(registry_root, program_hashes, membership, hash_fn) {
+        return Err(String::from("programs are not members of the registry"));
+    }
+    for (i, (&(public_inputs, outputs, proof), program_hash)) in executions.iter().zip(program_hashes).enumerate() {
+        if proof.options().hash_fn() as usize != hash_fn as usize {
+            return Err(format!("proof {} uses a hash function different from the registry hash function", i));
+        }
+        let verified = verify_with(Verification::new(program_hash, public_inputs, outputs), proof)
+            .map_err(|msg| format!("verification of proof {} failed: {}", i, msg))?;
+        if !verified {
+            return Ok(false);
+        }
+    }
+    return Ok(true);
+}
+
+/// Verifies program execution in the same way as `verify()` function does, and also verifies
+/// that the program with the specified `program_hash` is the one committed to by the
+/// `program_commitment`. The salt of the commitment is taken from the proof, and the commitment
+/// is computed using the hash function specified in options of the proof. This is the same as
+/// `verify_with()` for a verification configured via `Verification::with_commitment()`.
+pub fn verify_blinded(program_commitment: &[u8; 32], program_hash: &[u8; 32], public_inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+{
+    return verify_with(Verification::new(program_hash, public_inputs, outputs).with_commitment(program_commitment), proof);
+}
+
+// CHAINED EXECUTION
+// ================================================================================================
+
+/// Executes the specified sequence of programs such that outputs of each program become public
+/// inputs of the next program, and returns the outputs of the last program together with a
+/// STARK-based proof of execution for each program in the sequence.
+///
+/// * `segments` specifies programs to execute and their inputs; public inputs can be specified
+/// only for the first program since public inputs of all other programs are set to the outputs
+/// of the preceding program;
+/// * `num_outputs` specifies the number of elements from the top of the stack to be returned
+/// from the last program; all other programs output `MAX_OUTPUTS` elements.
+pub fn prove_chain(segments: &[(&Program, &ProgramInputs)], num_outputs: usize, options: &ProofOptions) -> (Vec<u128>, Vec<StarkProof>)
+{
+    assert!(segments.len() > 0, "a chain must consist of at least one program");
+
+    // segments often have the same trace shape, so prover setups are shared between them
+    let context = ProverContext::new();
+    let mut proofs = Vec::with_capacity(segments.len());
+    let mut outputs = Vec::new();
+    for (i, &(program, inputs)) in segments.iter().enumerate() {
+        let is_last = i == segments.len() - 1;
+        let segment_outputs = if is_last { num_outputs } else { MAX_OUTPUTS };
+
+        let (segment_result, proof) = if i == 0 {
+            execute_with_context(program, inputs, segment_outputs, options, &context)
+        }
+        else {
+            assert!(inputs.get_public_inputs().is_empty(),
+                "public inputs of program {} must be empty since they are set to the outputs of program {}", i, i - 1);
+            let inputs = inputs.with_public_inputs(&outputs);
+            execute_with_context(program, &inputs, segment_outputs, options, &context)
+        };
+
+        outputs = segment_result;
+        proofs.push(proof);
+    }
+
+    return (outputs, proofs);
+}
+
+/// Verifies that if a sequence of programs with the specified `program_hashes` is executed such
+/// that the first program is executed with the provided `public_inputs`, and every other program
+/// is executed with outputs of the preceding program as its public inputs, the outputs of the last
+/// program are equal to the `outputs`.
+///
+/// Intermediate outputs are taken from the proofs; since the outputs of each proof are used as
+/// public inputs when verifying the next proof, a break in the chain causes verification to fail.
+///
+/// Segments are linked directly rather than through a digest of the intermediate outputs. Both
+/// the outputs of a segment and the public inputs of the next segment are enforced by boundary
+/// constraints of the respective proofs, and the verifier builds both sets of constraints from
+/// the same values; a digest would bind the same values, but only up to collision resistance of
+/// the hash function. Since at most `MAX_OUTPUTS` values are carried over between segments,
+/// hashing them would not make the proofs any smaller either.
+pub fn verify_chain(program_hashes: &[[u8; 32]], public_inputs: &[u128], outputs: &[u128], proofs: &[StarkProof]) -> Result<bool, String>
+{
+    if program_hashes.len() != proofs.len() {
+        return Err(format!("expected {} proofs, but received {}", program_hashes.len(), proofs.len()));
+    }
+    if proofs.is_empty() {
+        return Err(String::from("a chain must consist of at least one program"));
+    }
+
+    let mut inputs = public_inputs;
+    for (i, (program_hash, proof)) in program_hashes.iter().zip(proofs.iter()).enumerate() {
+        let is_last = i == proofs.len() - 1;
+        let segment_outputs = if is_last { outputs } else { proof.outputs() };
+
+        if let Err(msg) = verify_with(Verification::new(program_hash, inputs, segment_outputs), proof) {
+            return Err(format!("verification of program {} in the chain failed: {}", i, msg));
+        }
+        inputs = proof.outputs();
+    }
+
+    return Ok(true);
+}
+
+// ANALYSIS
+// ================================================================================================
+
+/// Executes the specified `program` without generating a proof, and returns a list of places in
+/// the execution where values derived from secret inputs were used. This can be used to find
+/// operations and flow control decisions which may leak information about secret inputs via
+/// execution time or trace length; flow control decisions reported here cause execution to fail
+/// in hardened mode (see `ProgramInputs::with_hardened_execution()`).
+pub fn analyze_secret_inputs(program: &Program, inputs: &ProgramInputs) -> Vec<SecretUse>
+{
+    return processor::analyze(program, inputs);
+}
+
+/// Executes all paths through the specified `program` which differ in the branches taken by
+/// `if.true` blocks, forking execution (including positions on the input tapes) at every such
+/// block; at most `max_paths` paths are explored. Branches not selected by the provided `inputs`
+/// are executed by overriding the branch condition, and paths which required an override are
+/// marked as infeasible. The returned report lists the final state (or the error) of every path
+/// together with branch coverage, and can be used to generate tests or to estimate the
+/// worst-case cost of a program across inputs.
+pub fn explore_paths(program: &Program, inputs: &ProgramInputs, max_paths: usize) -> PathReport
+{
+    return processor::explore_paths(program, inputs, max_paths);
+}
+
+/// Executes the specified `program` using both the trace-building executor and a much simpler
+/// reference interpreter, and compares the results: outputs, final states of the stack, and
+/// numbers of executed operations. Returns an error listing all divergences between the two
+/// implementations; an error is also returned if the program fails to execute.
+///
+/// This is much cheaper than generating a proof, and can be used to make sure a program behaves
+/// as expected before starting a long proving run.
+pub fn selftest(program: &Program, inputs: &ProgramInputs) -> Result<(), Vec<String>>
+{
+    let interpreted = processor::interpret(program, inputs);
+    let executed = panic::catch_unwind(AssertUnwindSafe(|| processor::execute(program, inputs)))
+        .map_err(|err| match err.downcast_ref::<String>() {
+            Some(msg) => msg.clone(),
+            None => err.downcast_ref::<&str>().map(|msg| msg.to_string()).unwrap_or_default(),
+        });
+
+    let ((trace, ctx_depth, loop_depth), interpreted) = match (executed, interpreted) {
+        (Ok(executed), Ok(interpreted)) => (executed, interpreted),
+        (Err(err1), Err(err2)) => {
+            return Err(vec![format!("program execution failed; executor: {}; interpreter: {}", err1, err2)]);
+        },
+        (Err(err), Ok(_)) => return Err(vec![format!("executor failed ({}), but interpreter succeeded", err)]),
+        (Ok(_), Err(err)) => return Err(vec![format!("interpreter failed ({}), but executor succeeded", err)]),
+    };
+
+    // read the last state of the execution trace
+    let trace_length = trace[0].len();
+    let stack_depth = TraceState::compute_stack_depth(trace.len(), ctx_depth, loop_depth);
+    let mut last_state = TraceState::new(ctx_depth, loop_depth, stack_depth);
+    last_state.update_from_trace(&trace, trace_length - 1);
+
+    let mut divergences = Vec::new();
+    if last_state.op_counter() != interpreted.op_count as u128 {
+        divergences.push(format!("executor executed {} operations, but interpreter executed {}",
+            last_state.op_counter(), interpreted.op_count));
+    }
+
+    let depth = last_state.depth() as usize;
+    let mut executed_stack = last_state.user_stack().to_vec();
+    executed_stack.resize(usize::max(depth, MAX_OUTPUTS), math::field::ZERO);
+    let mut interpreted_stack = interpreted.stack.clone();
+    interpreted_stack.resize(usize::max(interpreted.stack.len(), MAX_OUTPUTS), math::field::ZERO);
+
+    if executed_stack[..MAX_OUTPUTS] != interpreted_stack[..MAX_OUTPUTS] {
+        divergences.push(format!("executor produced outputs {:?}, but interpreter produced {:?}",
+            &executed_stack[..MAX_OUTPUTS], &interpreted_stack[..MAX_OUTPUTS]));
+    }
+    if depth != interpreted.stack.len() {
+        divergences.push(format!("executor ended with stack depth {}, but interpreter ended with {}",
+            depth, interpreted.stack.len()));
+    }
+    else if executed_stack[..depth] != interpreted.stack[..] {
+        divergences.push(format!("executor ended with stack {:?}, but interpreter ended with {:?}",
+            &executed_stack[..depth], interpreted.stack));
+    }
+
+    return if divergences.is_empty() { Ok(()) } else { Err(divergences) };
+}
+
+/// Executes the specified `program` without generating a proof, and returns the resulting
+/// execution trace together with context depth and loop depth of the execution. Rows of the
+/// trace can be decoded using `TraceState`.
+pub fn generate_trace(program: &Program, inputs: &ProgramInputs) -> (Vec<Vec<u128>>, usize, usize)
+{
+    return processor::execute(program, inputs);
+}
+
+/// Executes the specified `program` without generating a proof, and checks all transition
+/// constraints of the VM against the un-extended execution trace. Returns the result of the
+/// check for every constraint group; a failing group lists the first step at which one of its
+/// constraints did not hold. This is much faster than proof generation, and catches mismatches
+/// between the executor and the constraints (e.g. while developing a new operation).
+pub fn check_trace(program: &Program, inputs: &ProgramInputs) -> Vec<ConstraintGroupCheck>
+{
+    let (trace, ctx_depth, loop_depth) = processor::execute(program, inputs);
+    let trace = stark::TraceTable::new(trace, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+    return stark::check_constraints(&trace);
+}
+
+/// Executes the specified `program` without generating a proof, and returns a report describing
+/// how many of the provided secret inputs were consumed by the program. To get the report for
+/// an execution which is also proven, use `execute_with_report()` instead; this way, the program
+/// is executed only once.
+pub fn report(program: &Program, inputs: &ProgramInputs) -> ExecutionReport
+{
+    return processor::report(program, inputs);
 }
 
 // GLOBAL CONSTANTS
@@ -130,10 +1038,16 @@ const HD_OP_BITS_RANGE      : Range<usize> = Range { start: 13, end: 15 };
 // STACK LAYOUT
 // ------------------------------------------------------------------------------------------------
 //
-// ╒═══════════════════ user registers ════════════════════════╕
-//    0      1    2    .................................    31
-// ├─────┴─────┴─────┴─────┴─────┴─────┴─────┴─────┴─────┴─────┤
+//  depth ╒═ underflow bits ═╕╒═ overflow bits ══╕╒═══════════ user registers ═══════════╕
+//    0     1   ..   ..   6    7   ..   ..   12    13    14   .....................    44
+// ├─────┴─────┴────┴────┴────┴────┴────┴────┴────┴─────┴─────┴─────┴─────┴─────┴─────┴─────┤
+
+const NUM_DEPTH_BITS        : usize = 6;
+const NUM_DEPTH_REGISTERS   : usize = 1 + 2 * NUM_DEPTH_BITS;
 
 pub const MAX_PUBLIC_INPUTS : usize = 8;
 pub const MAX_OUTPUTS       : usize = MAX_PUBLIC_INPUTS;
+pub const MAX_DIRECT_OUTPUTS: usize = 4;
+pub const MAX_ASSERTIONS    : usize = 16;
+pub const MAX_EXTENSION_CONSTRAINTS: usize = 16;
 pub const MAX_STACK_DEPTH   : usize = 32;
\ No newline at end of file
@@ -1,39 +1,89 @@
-use std::{ env, io::Write, time::Instant };
-use distaff::{ self, StarkProof };
+use std::{ env, fs, time::Instant };
+use distaff::{ self, StarkProof, ProgressHandle, Verbosity };
 
 mod examples;
 use examples::{ Example };
 
-fn main() {
+/// File into which the proof is written when `--format json` is specified.
+const JSON_PROOF_FILE: &str = "proof.json";
 
-    // configure logging
-    env_logger::Builder::new()
-        .format(|buf, record| writeln!(buf, "{}", record.args()))
-        .filter_level(log::LevelFilter::Debug).init();
+fn main() {
 
-    // determine the example to run based on command-line inputs
+    // determine the example to run based on command-line inputs; `--format json` can be
+    // specified anywhere in the arguments to also write the proof into a JSON file, and
+    // `--verbose` makes check-trace print the NOOPs the assembler added to the program, and
+    // makes the prover report every FRI layer
     let ex: Example;
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
+    let mut args: Vec<String> = env::args().collect();
+    let json_output = match args.iter().position(|a| a == "--format") {
+        Some(i) => {
+            assert!(i + 1 < args.len(), "--format flag requires a value");
+            let format = args.remove(i + 1);
+            args.remove(i);
+            match format.as_str() {
+                "json"   => true,
+                "binary" => false,
+                _ => panic!("unsupported proof format '{}'; supported formats are: binary, json", format)
+            }
+        },
+        None => false,
+    };
+    let verbose = match args.iter().position(|a| a == "--verbose") {
+        Some(i) => { args.remove(i); true },
+        None => false,
+    };
+    if args.len() >= 2 && args[1] == "suite" {
+        // a suite of programs is executed and verified by its own runner
+        examples::suite::run(&args[1..]);
+        return;
+    }
+    else if args.len() >= 2 && (args[1] == "keygen" || args[1] == "seal") {
+        // sealed inputs are prepared by clients, and are not executed
+        examples::sealed::run(&args[1..]);
+        return;
+    }
+    else if args.len() >= 2 && args[1] == "testvectors" {
+        // test vectors are written into a file instead of being executed as an example
+        let seed = args.get(2).map(|s| s.parse().expect("seed must be an unsigned integer")).unwrap_or(0);
+        std::fs::write("testvectors.json", distaff::testvectors::generate(seed).to_json())
+            .expect("failed to write test vectors into testvectors.json");
+        println!("Wrote test vectors for seed {} into testvectors.json", seed);
+        return;
+    }
+    else if args.len() >= 2 && args[1] == "check-trace" {
+        // constraints are checked against the execution trace without generating a proof
+        examples::check_trace::run(&args[1..], verbose);
+        return;
+    }
+    else if args.len() < 2 {
         ex = examples::fibonacci::get_example(&args);
     }
     else {
         ex = match args[1].as_str() {
+            "averagecheck"  => examples::aggregate::get_example(&args[1..]),
             "collatz"       => examples::collatz::get_example(&args[1..]),
             "comparison"    => examples::comparison::get_example(&args[1..]),
             "conditional"   => examples::conditional::get_example(&args[1..]),
             "fibonacci"     => examples::fibonacci::get_example(&args[1..]),
             "merkle"        => examples::merkle::get_example(&args[1..]),
+            "merklebatch"   => examples::merkle_batch::get_example(&args[1..]),
             "rangecheck"    => examples::range::get_example(&args[1..]),
+            "intervalcheck" => examples::range::get_interval_example(&args[1..]),
             _ => panic!("Could not find example program for '{}'", args[1])
         }
     }
     let Example { program, inputs, num_outputs, options, expected_result } = ex;
     println!("--------------------------------");
 
-    // execute the program and generate the proof of execution
+    // execute the program and generate the proof of execution; prover events are printed
+    // together with the time elapsed since the start of the execution
     let now = Instant::now();
-    let (outputs, proof) = distaff::execute(&program, &inputs, num_outputs, &options);
+    let verbosity = if verbose { Verbosity::Details } else { Verbosity::Stages };
+    let progress = ProgressHandle::with_logger(verbosity, move |event| {
+        println!("[{:>6} ms] {}", now.elapsed().as_millis(), event);
+    });
+    let (outputs, proof) = distaff::execute_with_progress(&program, &inputs, num_outputs, &options, &progress)
+        .expect("proof generation cannot be cancelled without cancelling the handle");
     println!("--------------------------------");
     println!("Executed program with hash {} in {} ms", 
         hex::encode(program.hash()),
@@ -42,14 +92,22 @@ fn main() {
     assert_eq!(expected_result, outputs, "Program result was computed incorrectly");
 
     // serialize the proof to see how big it is
-    let proof_bytes = bincode::serialize(&proof).unwrap();
+    let proof_bytes = proof.to_bytes();
     println!("Execution proof size: {} KB", proof_bytes.len() / 1024);
     println!("Execution proof security: {} bits", options.security_level(true));
     println!("--------------------------------");
 
+    // write the proof and public data into a JSON file for external verifiers
+    if json_output {
+        let json = proof.to_json(program.hash(), inputs.get_public_inputs());
+        fs::write(JSON_PROOF_FILE, &json).expect("failed to write JSON proof file");
+        println!("Wrote proof and public data to {} ({} KB)", JSON_PROOF_FILE, json.len() / 1024);
+        println!("--------------------------------");
+    }
+
     // verify that executing a program with a given hash and given inputs
     // results in the expected output
-    let proof = bincode::deserialize::<StarkProof>(&proof_bytes).unwrap();
+    let proof = StarkProof::from_bytes(&proof_bytes).unwrap();
     let now = Instant::now();
     match distaff::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof) {
         Ok(_) => println!("Execution verified in {} ms", now.elapsed().as_millis()),
@@ -0,0 +1,247 @@
+use std::{ fmt, convert::TryFrom, ops::{ Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign } };
+use serde::{ Serialize, Deserialize, Deserializer, de::Error };
+use super::field;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// An element of the field used by the VM. Unlike a raw `u128`, a `Felt` is guaranteed to be a
+/// canonical field element (i.e. smaller than the field modulus): values can be constructed only
+/// via checked constructors, and all arithmetic operations are performed modulo the field modulus.
+///
+/// The public API of the VM takes and returns field elements as `u128` values; lists of elements
+/// can be converted to and from such values via `Felt::to_u128_vec()` and `Felt::from_u128_slice()`.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Felt(u128);
+
+// FELT IMPLEMENTATION
+// ================================================================================================
+impl Felt {
+
+    pub const ZERO: Felt = Felt(field::ZERO);
+    pub const ONE: Felt = Felt(field::ONE);
+
+    /// Returns a field element with the specified `value`, or None if the value is not smaller
+    /// than the field modulus.
+    pub fn new(value: u128) -> Option<Felt> {
+        return if value < field::MODULUS { Some(Felt(value)) } else { None };
+    }
+
+    /// Returns a field element equal to `value` reduced by the field modulus.
+    pub fn from_u128_reduced(value: u128) -> Felt {
+        return Felt(value % field::MODULUS);
+    }
+
+    /// Returns the value of this element as `u128`.
+    pub fn as_int(&self) -> u128 {
+        return self.0;
+    }
+
+    /// Returns the multiplicative inverse of this element; the inverse of zero is zero.
+    pub fn inv(self) -> Felt {
+        return Felt(field::inv(self.0));
+    }
+
+    /// Returns this element raised to the specified `power`.
+    pub fn exp(self, power: u128) -> Felt {
+        return Felt(field::exp(self.0, power));
+    }
+
+    // SLICE CONVERSIONS
+    // --------------------------------------------------------------------------------------------
+
+    /// Converts a list of `u128` values into field elements; returns an error if any of the
+    /// values is not a canonical field element.
+    pub fn from_u128_slice(values: &[u128]) -> Result<Vec<Felt>, String> {
+        return values.iter().enumerate().map(|(i, &value)| {
+            Felt::new(value).ok_or_else(|| format!("value {} at position {} is not a valid field element", value, i))
+        }).collect();
+    }
+
+    /// Converts a list of field elements into `u128` values.
+    pub fn to_u128_vec(elements: &[Felt]) -> Vec<u128> {
+        return elements.iter().map(|element| element.0).collect();
+    }
+}
+
+// CONVERSIONS
+// ================================================================================================
+
+impl TryFrom<u128> for Felt {
+    type Error = String;
+
+    fn try_from(value: u128) -> Result<Felt, String> {
+        return Felt::new(value).ok_or_else(|| format!("value {} is not a valid field element", value));
+    }
+}
+
+impl From<u64> for Felt {
+    fn from(value: u64) -> Felt {
+        return Felt(value as u128);
+    }
+}
+
+impl From<u32> for Felt {
+    fn from(value: u32) -> Felt {
+        return Felt(value as u128);
+    }
+}
+
+impl From<bool> for Felt {
+    fn from(value: bool) -> Felt {
+        return Felt(value as u128);
+    }
+}
+
+impl From<Felt> for u128 {
+    fn from(element: Felt) -> u128 {
+        return element.0;
+    }
+}
+
+impl<'de> Deserialize<'de> for Felt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Felt, D::Error> {
+        let value = u128::deserialize(deserializer)?;
+        return Felt::try_from(value).map_err(D::Error::custom);
+    }
+}
+
+impl fmt::Display for Felt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "{}", self.0);
+    }
+}
+
+// ARITHMETIC OPERATORS
+// ================================================================================================
+
+impl Add for Felt {
+    type Output = Felt;
+
+    fn add(self, rhs: Felt) -> Felt {
+        return Felt(field::add(self.0, rhs.0));
+    }
+}
+
+impl Sub for Felt {
+    type Output = Felt;
+
+    fn sub(self, rhs: Felt) -> Felt {
+        return Felt(field::sub(self.0, rhs.0));
+    }
+}
+
+impl Mul for Felt {
+    type Output = Felt;
+
+    fn mul(self, rhs: Felt) -> Felt {
+        return Felt(field::mul(self.0, rhs.0));
+    }
+}
+
+impl Div for Felt {
+    type Output = Felt;
+
+    fn div(self, rhs: Felt) -> Felt {
+        assert!(rhs.0 != field::ZERO, "cannot divide by zero");
+        return Felt(field::div(self.0, rhs.0));
+    }
+}
+
+impl Neg for Felt {
+    type Output = Felt;
+
+    fn neg(self) -> Felt {
+        return Felt(field::neg(self.0));
+    }
+}
+
+impl AddAssign for Felt {
+    fn add_assign(&mut self, rhs: Felt) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Felt {
+    fn sub_assign(&mut self, rhs: Felt) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Felt {
+    fn mul_assign(&mut self, rhs: Felt) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Felt {
+    fn div_assign(&mut self, rhs: Felt) {
+        *self = *self / rhs;
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use std::convert::TryFrom;
+    use super::{ Felt, field };
+
+    #[test]
+    fn construction() {
+        assert_eq!(Some(5), Felt::new(5).map(|e| e.as_int()));
+        assert_eq!(Some(field::MODULUS - 1), Felt::new(field::MODULUS - 1).map(|e| e.as_int()));
+        assert_eq!(None, Felt::new(field::MODULUS));
+        assert!(Felt::try_from(u128::MAX).is_err());
+
+        assert_eq!(Felt::ONE, Felt::from_u128_reduced(field::MODULUS + 1));
+        assert_eq!(Felt::from(7u64), Felt::new(7).unwrap());
+        assert_eq!(Felt::ONE, Felt::from(true));
+        assert_eq!(7u128, u128::from(Felt::from(7u32)));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Felt::new(field::MODULUS - 2).unwrap();
+        let b = Felt::from(5u64);
+
+        assert_eq!(Felt::from(3u64), a + b);
+        assert_eq!(Felt::new(field::MODULUS - 7).unwrap(), a - b);
+        assert_eq!(Felt::new(field::MODULUS - 10).unwrap(), a * b);
+        assert_eq!(a, (a / b) * b);
+        assert_eq!(Felt::ZERO, a + -a);
+        assert_eq!(Felt::ONE, b * b.inv());
+        assert_eq!(Felt::from(125u64), b.exp(3));
+
+        let mut c = a;
+        c += b;
+        c *= b;
+        assert_eq!(Felt::from(15u64), c);
+    }
+
+    #[test]
+    fn conversions() {
+        let elements = vec![Felt::from(1u64), Felt::from(2u64), Felt::new(field::MODULUS - 1).unwrap()];
+        let values = Felt::to_u128_vec(&elements);
+        assert_eq!(vec![1, 2, field::MODULUS - 1], values);
+        assert_eq!(Ok(elements.clone()), Felt::from_u128_slice(&values));
+
+        let err_msg = format!("value {} at position 1 is not a valid field element", field::MODULUS);
+        assert_eq!(Err(err_msg), Felt::from_u128_slice(&[1, field::MODULUS]));
+    }
+
+    #[test]
+    fn serialization() {
+        let element = Felt::from(42u64);
+        let bytes = bincode::serialize(&element).unwrap();
+        assert_eq!(bincode::serialize(&42u128).unwrap(), bytes);
+        assert_eq!(element, bincode::deserialize::<Felt>(&bytes).unwrap());
+
+        // non-canonical values are rejected
+        let bytes = bincode::serialize(&field::MODULUS).unwrap();
+        assert!(bincode::deserialize::<Felt>(&bytes).is_err());
+    }
+}
@@ -6,6 +6,98 @@ use crate::math::field;
 const USIZE_BITS: usize = 0_usize.count_zeros() as usize;
 const MAX_LOOP: usize = 256;
 
+/// Transforms smaller than this are always executed in a single thread.
+const MIN_PARALLEL_SIZE: usize = 4096;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Precomputed data for evaluating polynomials over (and interpolating polynomials from) the
+/// multiplicative subgroup of the specified size. A plan can be reused for any number of
+/// transforms of that size; both transforms take inputs and produce outputs in natural order.
+pub struct FftPlan {
+    size            : usize,
+    twiddles        : Vec<u128>,
+    inv_twiddles    : Vec<u128>,
+    permutation     : Vec<usize>,
+    inv_size        : u128,
+    num_threads     : usize,
+}
+
+// FFT PLAN IMPLEMENTATION
+// ================================================================================================
+impl FftPlan {
+
+    /// Creates a plan for transforms of the specified `size`; large transforms are executed in
+    /// as many threads as there are available CPU cores (rounded down to a power of 2).
+    pub fn new(size: usize) -> FftPlan {
+        let num_threads = if size >= MIN_PARALLEL_SIZE { get_num_threads() } else { 1 };
+        return FftPlan::with_threads(size, num_threads);
+    }
+
+    /// Creates a plan for transforms of the specified `size` which are executed in
+    /// `num_threads` threads; number of threads must be a power of 2.
+    pub fn with_threads(size: usize, num_threads: usize) -> FftPlan {
+        assert!(size.is_power_of_two() && size > 1, "transform size must be a power of 2 greater than 1");
+        assert!(num_threads.is_power_of_two(), "number of threads must be a power of 2");
+
+        let root = field::get_root_of_unity(size);
+        return FftPlan {
+            size            : size,
+            twiddles        : get_twiddles(root, size),
+            inv_twiddles    : get_inv_twiddles(root, size),
+            permutation     : (0..size).map(|i| permute_index(size, i)).collect(),
+            inv_size        : field::inv(size as u128),
+            num_threads     : num_threads,
+        };
+    }
+
+    pub fn size(&self) -> usize {
+        return self.size;
+    }
+
+    pub fn num_threads(&self) -> usize {
+        return self.num_threads;
+    }
+
+    pub fn twiddles(&self) -> &[u128] {
+        return &self.twiddles;
+    }
+
+    pub fn inv_twiddles(&self) -> &[u128] {
+        return &self.inv_twiddles;
+    }
+
+    /// Evaluates polynomial with coefficients `values` at all points of the domain; the
+    /// evaluation is done in-place, meaning `values` are replaced with the evaluations.
+    pub fn forward(&self, values: &mut [u128]) {
+        assert!(values.len() == self.size, "expected {} values, but received {}", self.size, values.len());
+        fft_in_place(values, &self.twiddles, 1, 1, 0, self.num_threads);
+        self.permute(values);
+    }
+
+    /// Interpolates a polynomial from its evaluations `values` at all points of the domain; the
+    /// interpolation is done in-place, meaning `values` are replaced with polynomial coefficients.
+    pub fn inverse(&self, values: &mut [u128]) {
+        assert!(values.len() == self.size, "expected {} values, but received {}", self.size, values.len());
+        fft_in_place(values, &self.inv_twiddles, 1, 1, 0, self.num_threads);
+        for value in values.iter_mut() {
+            *value = field::mul(*value, self.inv_size);
+        }
+        self.permute(values);
+    }
+
+    /// Puts `values` into bit-reversed order (or back into natural order) using the cached
+    /// permutation table.
+    pub fn permute(&self, values: &mut [u128]) {
+        for (i, &j) in self.permutation.iter().enumerate() {
+            if j > i {
+                values.swap(i, j);
+            }
+        }
+    }
+}
+
 // PUBLIC FUNCTIONS
 // ================================================================================================
 
@@ -80,6 +172,11 @@ pub fn permute(v: &mut [u128]) {
 
 // HELPER FUNCTIONS
 // ================================================================================================
+fn get_num_threads() -> usize {
+    let num_cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    return 1 << (USIZE_BITS - 1 - num_cores.leading_zeros() as usize);
+}
+
 fn permute_index(size: usize, index: usize) -> usize {
     debug_assert!(index < size);
     if size == 1 { return 0 }
@@ -109,7 +206,7 @@ fn butterfly_twiddle(values: &mut [u128], twiddle: u128, offset: usize, stride:
 
 // TESTS
 // ================================================================================================
-#[cfg(test)]
+#[cfg(all(test, feature = "std-rand"))]
 mod tests {
     use crate::math::{ field, polynom };
 
@@ -155,4 +252,36 @@ mod tests {
         super::permute(&mut p);
         assert_eq!(expected, p);
     }
+
+    #[test]
+    fn fft_plan() {
+        // forward transform evaluates the polynomial over the domain
+        let p = field::rand_vector(1024);
+        let g = field::get_root_of_unity(1024);
+        let roots = field::get_power_series(g, 1024);
+        let expected = roots.iter().map(|x| polynom::eval(&p, *x)).collect::<Vec<u128>>();
+
+        let plan = super::FftPlan::new(1024);
+        let mut values = p.clone();
+        plan.forward(&mut values);
+        assert_eq!(expected, values);
+
+        // inverse transform recovers the polynomial
+        plan.inverse(&mut values);
+        assert_eq!(p, values);
+
+        // multi-threaded transforms produce the same results
+        let p = field::rand_vector(1 << 13);
+        let plan1 = super::FftPlan::with_threads(p.len(), 1);
+        let plan4 = super::FftPlan::with_threads(p.len(), 4);
+
+        let mut values1 = p.clone();
+        let mut values4 = p.clone();
+        plan1.forward(&mut values1);
+        plan4.forward(&mut values4);
+        assert_eq!(values1, values4);
+
+        plan4.inverse(&mut values4);
+        assert_eq!(p, values4);
+    }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::ops::Range;
 use std::convert::TryInto;
 use rand::prelude::*;
 use rand::distributions::{ Distribution, Uniform };
-use crate::utils::{ uninit_vector };
+use crate::utils::{ zeroed_vector };
 
 // CONSTANTS
 // ================================================================================================
@@ -163,7 +163,7 @@ pub fn inv(x: u128) -> u128 {
 
 /// Computes multiplicative inverses of all slice elements using batch inversion method.
 pub fn inv_many(values: &[u128]) -> Vec<u128> {
-    let mut result = uninit_vector(values.len());
+    let mut result = zeroed_vector(values.len());
     inv_many_fill(values, &mut result);
     return result;
 }
@@ -235,7 +235,7 @@ pub fn get_root_of_unity(order: usize) -> u128 {
 
 /// Generates a vector with values [1, b, b^2, b^3, b^4, ..., b^length].
 pub fn get_power_series(b: u128, length: usize) -> Vec<u128> {
-    let mut result = uninit_vector(length);
+    let mut result = zeroed_vector(length);
     result[0] = ONE;
     for i in 1..result.len() {
         result[i] = mul(result[i - 1], b);
@@ -246,18 +246,28 @@ pub fn get_power_series(b: u128, length: usize) -> Vec<u128> {
 // RANDOMNESS
 // --------------------------------------------------------------------------------------------
 
-/// Generates a random field element.
+/// Generates a random field element using thread-local random number generator.
+#[cfg(feature = "std-rand")]
 pub fn rand() -> u128 {
-    let range = Uniform::from(RANGE);
-    let mut g = thread_rng();
-    return g.sample(range);
+    return rand_with(&mut thread_rng());
 }
 
-/// Generates a vector of random field elements.
+/// Generates a vector of random field elements using thread-local random number generator.
+#[cfg(feature = "std-rand")]
 pub fn rand_vector(length: usize) -> Vec<u128> {
+    return rand_vector_with(length, &mut thread_rng());
+}
+
+/// Generates a random field element using the provided random number generator.
+pub fn rand_with<R: RngCore + ?Sized>(rng: &mut R) -> u128 {
     let range = Uniform::from(RANGE);
-    let g = thread_rng();
-    return g.sample_iter(range).take(length).collect();
+    return range.sample(rng);
+}
+
+/// Generates a vector of random field elements using the provided random number generator.
+pub fn rand_vector_with<R: RngCore + ?Sized>(length: usize, rng: &mut R) -> Vec<u128> {
+    let range = Uniform::from(RANGE);
+    return (0..length).map(|_| range.sample(rng)).collect();
 }
 
 /// Generates a pseudo-random field element from a given `seed`.
@@ -340,9 +350,13 @@ pub const fn add64_with_carry(a: u64, b: u64, carry: u64) -> (u64, u64) {
 #[cfg(test)]
 mod tests {
 
+    #[cfg(feature = "std-rand")]
     use std::convert::TryInto;
+    #[cfg(feature = "std-rand")]
     use num_bigint::{ BigUint };
+    use rand::{ SeedableRng, rngs::StdRng };
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn add() {
         // identity
@@ -367,6 +381,7 @@ mod tests {
         assert_eq!(expected, super::add(r1, r2));
     }
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn sub() {
         // identity
@@ -381,6 +396,7 @@ mod tests {
         assert_eq!(m - 2, super::sub(3u128, 5));
     }
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn mul() {
         // identity
@@ -421,6 +437,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn inv() {
         // identity
@@ -446,4 +463,21 @@ mod tests {
         assert_eq!(expected, root_39);
         assert_eq!(1, super::exp(root_39, u128::pow(2, 39)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rand_with() {
+        // the same seed produces the same elements
+        let values1 = super::rand_vector_with(64, &mut StdRng::from_seed([1; 32]));
+        let values2 = super::rand_vector_with(64, &mut StdRng::from_seed([1; 32]));
+        assert_eq!(values1, values2);
+        assert!(values1.iter().all(|&v| v < super::MODULUS));
+
+        // elements are drawn from the generator in sequence
+        let mut rng = StdRng::from_seed([1; 32]);
+        assert_eq!(values1[0], super::rand_with(&mut rng));
+        assert_eq!(values1[1], super::rand_with(&mut rng));
+
+        let values3 = super::rand_vector_with(64, &mut StdRng::from_seed([2; 32]));
+        assert_ne!(values1, values3);
+    }
+}
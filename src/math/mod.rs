@@ -1,4 +1,8 @@
 pub mod field;
+
+mod felt;
+pub use felt::Felt;
+
 pub mod fft;
 pub mod polynom;
 pub mod quartic;
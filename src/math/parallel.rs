@@ -1,6 +1,6 @@
 use crossbeam_utils::thread;
 use crate::math::{ field };
-use crate::utils::{ uninit_vector };
+use crate::utils::{ zeroed_vector };
 
 // ADDITION
 // ================================================================================================
@@ -14,7 +14,7 @@ pub fn add(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
     let batch_size = n / num_threads;
 
     // allocate space for the results
-    let mut result = uninit_vector(n);
+    let mut result = zeroed_vector(n);
 
     // add batches of values in separate threads
     thread::scope(|s| {
@@ -88,7 +88,7 @@ pub fn mul(a: &[u128], b: &[u128], num_threads: usize) -> Vec<u128> {
     let batch_size = n / num_threads;
 
     // allocate space for the results
-    let mut result = uninit_vector(n);
+    let mut result = zeroed_vector(n);
 
     // multiply batches of values in separate threads
     thread::scope(|s| {
@@ -159,15 +159,11 @@ pub fn inv(values: &[u128], num_threads: usize) -> Vec<u128> {
     let batch_size = n / num_threads;
 
     // allocate space for the results
-    let result = uninit_vector(n);
+    let mut result = zeroed_vector(n);
 
     // break up the values into batches and invert each batch in a separate thread
     thread::scope(|s| {
-        for i in (0..n).step_by(batch_size) {
-            let values_slice = &values[i..(i + batch_size)];
-            let values_slice = unsafe { &*(values_slice as *const _ as *const [u128]) };
-            let result_slice = &result[i..(i + batch_size)];
-            let result_slice = unsafe { &mut *(result_slice as *const _ as *mut [u128]) };
+        for (values_slice, result_slice) in values.chunks(batch_size).zip(result.chunks_mut(batch_size)) {
             s.spawn(move |_| {
                 field::inv_many_fill(values_slice, result_slice);
             });
@@ -180,7 +176,7 @@ pub fn inv(values: &[u128], num_threads: usize) -> Vec<u128> {
 
 // TESTS
 // ================================================================================================
-#[cfg(test)]
+#[cfg(all(test, feature = "std-rand"))]
 mod tests {
     use crate::math::{ field };
 
@@ -1,6 +1,6 @@
 use std::mem;
 use crate::math::{ field, fft };
-use crate::utils::{ uninit_vector, filled_vector };
+use crate::utils::{ zeroed_vector, filled_vector };
 
 // POLYNOMIAL EVALUATION
 // ================================================================================================
@@ -218,7 +218,7 @@ pub fn syn_div_expanded_in_place(a: &mut [u128], degree: usize, exceptions: &[u1
         let exception = field::neg(exception);
 
         // extend length of result since we are raising degree
-        unsafe { result.set_len(result.len() + 1); }
+        result.push(field::ZERO);
 
         let mut next_term = result[0];
         result[0] = field::ZERO;
@@ -259,7 +259,7 @@ pub fn infer_degree(evaluations: &[u128]) -> usize {
 // ================================================================================================
 fn get_zero_roots(xs: &[u128]) -> Vec<u128> {
     let mut n = xs.len() + 1;
-    let mut result = uninit_vector(n);
+    let mut result = zeroed_vector(n);
     
     n -= 1;
     result[n] = field::ONE;
@@ -312,6 +312,7 @@ mod tests {
             field::mul(poly[3], x3)))), super::eval(&poly, x));
     }
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn eval_fft() {
         let n: usize = 1024;
@@ -1,5 +1,5 @@
 use crate::math::{ field };
-use crate::utils::uninit_vector;
+use crate::utils::zeroed_vector;
 
 /// Evaluates degree 3 polynomial `p` at coordinate `x`. This function is about 30% faster than
 /// the `polys::eval` function.
@@ -20,8 +20,7 @@ pub fn eval(p: &[u128], x: u128) -> u128 {
 pub fn evaluate_batch(polys: &[[u128; 4]], x: u128) -> Vec<u128> {
     let n = polys.len();
     
-    let mut result: Vec<u128> = Vec::with_capacity(n);
-    unsafe { result.set_len(n); }
+    let mut result: Vec<u128> = zeroed_vector(n);
 
     for i in 0..n {
         result[i] = eval(&polys[i], x);
@@ -38,12 +37,8 @@ pub fn interpolate_batch(xs: &[[u128; 4]], ys: &[[u128; 4]]) -> Vec<[u128; 4]> {
     debug_assert!(xs.len() == ys.len(), "number of X coordinates must be equal to number of Y coordinates");
 
     let n = xs.len();
-    let mut equations: Vec<[u128; 4]> = Vec::with_capacity(n * 4);
-    let mut inverses: Vec<u128> = Vec::with_capacity(n * 4);
-    unsafe { 
-        equations.set_len(n * 4);
-        inverses.set_len(n * 4);
-    }
+    let mut equations: Vec<[u128; 4]> = zeroed_vector(n * 4);
+    let mut inverses: Vec<u128> = zeroed_vector(n * 4);
 
     for (i, j) in (0..n).zip((0..equations.len()).step_by(4)) {
         
@@ -95,8 +90,7 @@ pub fn interpolate_batch(xs: &[[u128; 4]], ys: &[[u128; 4]]) -> Vec<[u128; 4]> {
 
     let inverses = field::inv_many(&inverses);
 
-    let mut result: Vec<[u128; 4]> = Vec::with_capacity(n);
-    unsafe { result.set_len(n); }
+    let mut result: Vec<[u128; 4]> = zeroed_vector(n);
 
     for (i, j) in (0..n).zip((0..equations.len()).step_by(4)) {
         
@@ -138,7 +132,7 @@ pub fn transpose(vector: &[u128], stride: usize) -> Vec<[u128; 4]> {
     assert!(vector.len() % (4 * stride) == 0, "vector length must be divisible by {}", 4 * stride);
     let row_count = vector.len() / (4 * stride);
 
-    let mut result = to_quartic_vec(uninit_vector(row_count * 4));
+    let mut result = to_quartic_vec(zeroed_vector(row_count * 4));
     for i in 0..row_count {
         result[i] = [
             vector[i * stride],
@@ -190,6 +184,7 @@ mod tests {
         assert_eq!(expected, super::interpolate_batch(&xs, &ys));
     }
 
+    #[cfg(feature = "std-rand")]
     #[test]
     fn evaluate_batch() {
         let x = field::rand();
@@ -0,0 +1,11 @@
+//! Types and functions needed by most users of the VM: compiling a program, executing it to
+//! generate a proof, and verifying the proof.
+//!
+//! Everything exported from this module is covered by semver guarantees, and can be imported
+//! with a single `use distaff::prelude::*;`. Paths of these items outside of the prelude (e.g.
+//! `distaff::Program`) remain valid as well.
+
+pub use crate::{
+    Program, ProgramInputs, ProgramInputsBuilder, ProofOptions, StarkProof, ProvingCancelled,
+    OpCode, OpHint, Felt, Execution, Verification, execute, execute_with, verify, verify_with,
+    assembly::{ self, AssemblyError } };
@@ -1,6 +1,6 @@
 use crate::{
     math::field,
-    utils::sponge,
+    crypto::rescue,
     MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH,
     NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
     SPONGE_WIDTH, BASE_CYCLE_LENGTH, PUSH_OP_ALIGNMENT,
@@ -78,6 +78,42 @@ impl Decoder {
         };
     }
 
+    /// Creates a new instance of instruction decoder positioned at the specified `step` with the
+    /// sponge set to `sponge`, and with context and loop stacks holding `ctx_stack` and
+    /// `loop_stack` values (the top of each stack comes first; the outer-most context, which is
+    /// always 0, is added below `ctx_stack` values). Register traces are all zeros before `step`.
+    ///
+    /// This is intended only for unit-testing decoding of operations at arbitrary points of
+    /// execution: traces which do not start from the initial state of the decoder cannot be
+    /// proven.
+    #[cfg(test)]
+    pub fn from_state(step: usize, sponge: [u128; SPONGE_WIDTH], ctx_stack: &[u128], loop_stack: &[u128], init_trace_length: usize) -> Decoder {
+        assert!(step < init_trace_length,
+            "step {} is outside of the trace of {} steps", step, init_trace_length);
+        assert!(ctx_stack.len() < MAX_CONTEXT_DEPTH, "context stack overflow at step {}", step);
+        assert!(loop_stack.len() <= MAX_LOOP_DEPTH, "loop stack overflow at step {}", step);
+
+        let mut decoder = Decoder::new(init_trace_length);
+        decoder.step = step;
+
+        decoder.sponge = sponge;
+        for (register, &value) in decoder.sponge_trace.iter_mut().zip(sponge.iter()) {
+            register[step] = value;
+        }
+
+        decoder.ctx_stack = ctx_stack.iter().chain([field::ZERO].iter())
+            .map(|&value| init_register(value, step, init_trace_length))
+            .collect();
+        decoder.ctx_depth = decoder.ctx_stack.len();
+
+        decoder.loop_stack = loop_stack.iter()
+            .map(|&value| init_register(value, step, init_trace_length))
+            .collect();
+        decoder.loop_depth = decoder.loop_stack.len();
+
+        return decoder;
+    }
+
     /// Returns trace length of register traces in the decoder.
     pub fn trace_length(&self) -> usize {
         return self.op_counter.len();
@@ -448,7 +484,7 @@ impl Decoder {
     fn apply_hacc_round(&mut self, op_code: UserOps, op_value: u128) {
 
         // apply single round of sponge function
-        sponge::apply_round(&mut self.sponge, op_code as u128, op_value, self.step - 1);
+        rescue::apply_hacc_round(&mut self.sponge, op_code as u128, op_value, self.step - 1);
 
         // copy the new sponge state into the sponge_trace registers
         for i in 0..SPONGE_WIDTH {
@@ -463,4 +499,11 @@ fn fill_register(register: &mut Vec<u128>, from: usize, value: u128) {
     let to = register.len();
     register.resize(from, field::ZERO);
     register.resize(to, value);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+fn init_register(value: u128, step: usize, trace_length: usize) -> Vec<u128> {
+    let mut register = vec![field::ZERO; trace_length];
+    register[step] = value;
+    return register;
+}
@@ -38,4 +38,19 @@ fn start_loop() {
     
     assert_eq!(1, 2);
 }
-*/
\ No newline at end of file
+*/
+#[test]
+fn from_state() {
+    // the decoder starts at the last step of a cycle inside a loop nested in a block
+    let mut decoder = super::Decoder::from_state(15, [1, 2, 3, 4], &[5], &[6], 32);
+    assert_eq!(15, decoder.current_step());
+    assert_eq!(1, decoder.max_ctx_stack_depth());
+    assert_eq!(1, decoder.max_loop_stack_depth());
+    assert_eq!(vec![0, 1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 5, 0, 6], decoder.get_state(15));
+
+    // a new block can be started right away; its parent is pushed onto the context stack
+    decoder.start_block();
+    let state = decoder.get_state(16);
+    assert_eq!(vec![0, 0, 0, 0], state[1..5].to_vec());
+    assert_eq!(vec![1, 5, 0, 6], state[15..].to_vec());
+}
@@ -0,0 +1,474 @@
+use std::collections::{ BTreeMap, HashMap };
+use crate::{
+    math::field,
+    crypto::rescue::HASHER,
+    programs::{ Program, ProgramInputs, TapePolicy, blocks::{ ProgramBlock, Span, Switch, Loop } },
+    HASH_STATE_WIDTH, HACC_NUM_ROUNDS, MAX_STACK_DEPTH,
+};
+use super::{ OpCode, OpHint };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// State of the VM after a program was executed by the interpreter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterpreterState {
+    /// Items on the stack at the end of execution; the first item is the top of the stack.
+    pub stack       : Vec<u128>,
+    /// Number of operations executed by the VM (not counting flow control operations).
+    pub op_count    : usize,
+    /// Number of values left unconsumed on secret input tapes A and B.
+    pub unconsumed  : [usize; 2],
+}
+
+/// A reference interpreter which executes programs directly against a list of stack items
+/// without building an execution trace. The interpreter is much simpler than the trace-building
+/// executor, and so, it is used to cross-check results of the executor.
+struct Interpreter {
+    stack       : Vec<u128>,
+    tape_a      : Vec<u128>,
+    tape_b      : Vec<u128>,
+    tape_policy : TapePolicy,
+    advice      : BTreeMap<u128, Vec<u128>>,
+    step        : usize,
+    op_count    : usize,
+    selector    : Option<BranchSelector>,
+}
+
+/// Determines which branches of Switch blocks are executed when a single path through a program
+/// is explored (see `processor::explore_paths()`). Branches are taken from the `prefix` for the
+/// first Switch blocks reached, and the true branch is taken for all others; if the condition at
+/// the top of the stack does not select the same branch, the condition is overridden, and the
+/// path is marked as infeasible for the provided inputs.
+pub(super) struct BranchSelector {
+    /// Maps addresses of Switch blocks to their indexes in the program.
+    pub switch_ids  : HashMap<usize, usize>,
+    pub prefix      : Vec<bool>,
+    /// Executed branches as (switch index, branch) tuples.
+    pub branches    : Vec<(usize, bool)>,
+    pub feasible    : bool,
+    /// Number of steps after which the path is abandoned; overridden conditions may lead to
+    /// loops which never exit.
+    pub max_steps   : usize,
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Executes the `program` against the specified inputs without building an execution trace,
+/// and returns the final state of the VM; returns an error if the program fails.
+pub fn interpret(program: &Program, inputs: &ProgramInputs) -> Result<InterpreterState, String>
+{
+    let (result, _) = run(program, inputs, None);
+    return result;
+}
+
+/// Executes a single path through the `program` with branches of Switch blocks chosen by the
+/// `selector`; returns the final state of the VM (or an error if the path fails) together with
+/// the selector recording the executed branches.
+pub(super) fn interpret_path(program: &Program, inputs: &ProgramInputs, selector: BranchSelector) -> (Result<InterpreterState, String>, BranchSelector)
+{
+    let (result, selector) = run(program, inputs, Some(selector));
+    return (result, selector.expect("branch selector must be returned"));
+}
+
+// INTERPRETER IMPLEMENTATION
+// ================================================================================================
+impl Interpreter {
+
+    // FLOW CONTROL
+    // --------------------------------------------------------------------------------------------
+
+    fn run_blocks(&mut self, blocks: &[ProgramBlock]) -> Result<(), String> {
+        match &blocks[0] {
+            ProgramBlock::Span(block) => self.run_span(block, true)?,
+            _ => return Err(String::from("first block in a sequence must be a Span block")),
+        }
+
+        for block in blocks.iter().skip(1) {
+            match block {
+                ProgramBlock::Span(block) => self.run_span(block, false)?,
+                ProgramBlock::Group(block) => {
+                    self.flow_op();
+                    self.run_blocks(block.body())?;
+                    self.close_block()?;
+                },
+                ProgramBlock::Switch(block) => {
+                    self.flow_op();
+                    match self.select_branch(block)? {
+                        false => self.run_blocks(block.false_branch())?,
+                        true  => self.run_blocks(block.true_branch())?,
+                    }
+                    self.close_block()?;
+                },
+                ProgramBlock::Loop(block) => {
+                    match self.condition("enter loop")? {
+                        false => {
+                            self.flow_op();
+                            self.run_blocks(block.skip())?;
+                            self.close_block()?;
+                        },
+                        true => self.run_loop(block)?,
+                    }
+                },
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn run_span(&mut self, block: &Span, is_first: bool) -> Result<(), String> {
+        if !is_first {
+            self.user_op(OpCode::Noop, OpHint::None)?;
+        }
+        for i in 0..block.length() {
+            let (op_code, op_hint) = block.get_op(i);
+            self.user_op(op_code, op_hint)?;
+        }
+        return Ok(());
+    }
+
+    fn run_loop(&mut self, block: &Loop) -> Result<(), String> {
+        self.flow_op();
+        loop {
+            self.run_blocks(block.body())?;
+            let condition = self.condition("exit loop")?;
+            self.flow_op();
+            if !condition { break; }
+        }
+
+        match &block.skip()[0] {
+            ProgramBlock::Span(block) => self.run_span(block, true)?,
+            _ => return Err(String::from("invalid skip block content: content must be a Span block")),
+        }
+        return self.close_block();
+    }
+
+    fn close_block(&mut self) -> Result<(), String> {
+        self.user_op(OpCode::Noop, OpHint::None)?;
+        self.flow_op();
+        for _ in 0..HACC_NUM_ROUNDS {
+            self.user_op(OpCode::Noop, OpHint::None)?;
+        }
+        return Ok(());
+    }
+
+    /// Reads a branching condition from the top of the stack without removing it.
+    fn condition(&self, action: &str) -> Result<bool, String> {
+        return match self.stack.first().copied().unwrap_or(field::ZERO) {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(format!("cannot {} based on a non-binary condition {}", action, value)),
+        };
+    }
+
+    /// Determines which branch of the Switch `block` is executed; unless a path is being explored,
+    /// this is the branch selected by the condition at the top of the stack.
+    fn select_branch(&mut self, block: &Switch) -> Result<bool, String> {
+        let condition = self.condition("select a branch");
+        let selector = match self.selector.as_mut() {
+            Some(selector) => selector,
+            None => return condition,
+        };
+
+        let branch = selector.prefix.get(selector.branches.len()).copied().unwrap_or(true);
+        if condition != Ok(branch) {
+            selector.feasible = false;
+            if let Some(top) = self.stack.first_mut() {
+                *top = branch as u128;
+            }
+        }
+        selector.branches.push((selector.switch_ids[&(block as *const Switch as usize)], branch));
+        return Ok(branch);
+    }
+
+    /// Executes a flow control operation; such operations leave the stack unchanged and are not
+    /// counted as executed operations.
+    fn flow_op(&mut self) {
+        self.step += 1;
+    }
+
+    // USER OPERATIONS
+    // --------------------------------------------------------------------------------------------
+
+    fn user_op(&mut self, op_code: OpCode, op_hint: OpHint) -> Result<(), String> {
+        self.step += 1;
+        self.op_count += 1;
+
+        if let Some(selector) = &self.selector {
+            if self.step > selector.max_steps {
+                return Err(format!("path was abandoned after {} steps", selector.max_steps));
+            }
+        }
+
+        let required = match (op_code, op_hint) {
+            (OpCode::Cmp, OpHint::CmpStart(_)) => 10,
+            (OpCode::BinAcc, OpHint::RcStart(_)) => 5,
+            (OpCode::Read, OpHint::EqStart) | (OpCode::Read, OpHint::DivModStart) => 2,
+            (OpCode::Read2, OpHint::PmpathStart(_)) => 3,
+            (OpCode::Read, OpHint::AdviceKey(_)) => 1,
+            _ => op_code.required_depth(),
+        };
+        if self.stack.len() < required {
+            return Err(format!("stack underflow at step {}", self.step));
+        }
+
+        let s = &mut self.stack;
+        match op_code {
+            OpCode::Begin | OpCode::Noop => (),
+
+            OpCode::Assert => {
+                if s[0] != field::ONE { return Err(format!("ASSERT failed at step {}", self.step)); }
+                s.remove(0);
+            },
+            OpCode::AssertEq => {
+                if s[0] != s[1] { return Err(format!("ASSERTEQ failed at step {}", self.step)); }
+                s.drain(..2);
+            },
+            OpCode::AssertBin => {
+                binary(s[0], "ASSERTBIN failed", self.step)?;
+            },
+
+            OpCode::Push => match op_hint {
+                OpHint::PushValue(value) => s.insert(0, value),
+                _ => return Err(format!("invalid value for PUSH operation at step {}", self.step)),
+            },
+            OpCode::Read => {
+                match op_hint {
+                    OpHint::EqStart => {
+                        let value = if s[0] == s[1] { field::ONE } else { field::inv(field::sub(s[0], s[1])) };
+                        self.tape_a.push(value);
+                    },
+                    OpHint::DivModStart => {
+                        let (b, a) = (s[0], s[1]);
+                        if b == field::ZERO {
+                            return Err(format!("cannot perform DIVMOD by {} at step {}", field::ZERO, self.step));
+                        }
+                        self.tape_a.push(a / b);
+                        self.tape_a.push(a % b);
+                    },
+                    OpHint::AdviceKey(n) => {
+                        let (key, step) = (s[0], self.step);
+                        let values = self.advice.get(&key)
+                            .ok_or_else(|| format!("no advice values for key {} at step {}", key, step))?;
+                        if values.len() != n as usize {
+                            return Err(format!("expected {} advice values for key {}, but found {}", n, key, values.len()));
+                        }
+                        self.tape_a.extend(values.iter().rev());
+                    },
+                    _ => (),
+                }
+                let value = self.read_tape_a()?;
+                self.stack.insert(0, value);
+            },
+            OpCode::Read2 => {
+                if let OpHint::PmpathStart(n) = op_hint {
+                    let n = (n - 1) as usize;
+                    if self.tape_a.len() < n || self.tape_b.len() < n {
+                        return Err(String::from("too few items on secret tapes for pmpath macro"));
+                    }
+                    let index = s[2];
+                    let nodes = self.tape_a.split_off(self.tape_a.len() - n);
+                    for i in 0..n {
+                        self.tape_a.push((index >> (n - i - 1)) & 1);
+                        self.tape_a.push(nodes[i]);
+                    }
+                }
+                let value_a = self.read_tape_a()?;
+                let value_b = self.read_tape_b()?;
+                self.stack.splice(0..0, [value_b, value_a].iter().cloned());
+            },
+
+            OpCode::Dup  => { let top = s[..1].to_vec(); s.splice(0..0, top); },
+            OpCode::Dup2 => { let top = s[..2].to_vec(); s.splice(0..0, top); },
+            OpCode::Dup4 => { let top = s[..4].to_vec(); s.splice(0..0, top); },
+            OpCode::Dup8 => { let top = s[..8].to_vec(); s.splice(0..0, top); },
+            OpCode::Pad2 => { s.splice(0..0, [field::ZERO, field::ZERO].iter().cloned()); },
+
+            OpCode::Drop  => { s.remove(0); },
+            OpCode::Drop4 => { s.drain(..4); },
+            OpCode::Drop8 => { s.drain(..8); },
+
+            OpCode::Swap  => s.swap(0, 1),
+            OpCode::Swap2 => { s[..4].rotate_left(2); },
+            OpCode::Swap4 => { s[..8].rotate_left(4); },
+            OpCode::Swap8 => { s[..16].rotate_left(8); },
+            OpCode::Roll4 => { s[..4].rotate_right(1); },
+            OpCode::Roll8 => { s[..8].rotate_right(1); },
+
+            OpCode::Choose => {
+                let condition = binary(s[2], "CHOOSE on a non-binary condition", self.step)?;
+                let value = if condition { s[0] } else { s[1] };
+                s.drain(..3);
+                s.insert(0, value);
+            },
+            OpCode::Choose2 => {
+                let condition = binary(s[4], "CHOOSE2 on a non-binary condition", self.step)?;
+                let values = if condition { [s[0], s[1]] } else { [s[2], s[3]] };
+                s.drain(..6);
+                s.splice(0..0, values.iter().cloned());
+            },
+            OpCode::CSwap2 => {
+                let condition = binary(s[4], "CSWAP2 on a non-binary condition", self.step)?;
+                if condition { s[..4].rotate_left(2); }
+                s.drain(4..6);
+            },
+
+            OpCode::Add => { let v = field::add(s[0], s[1]); s.drain(..2); s.insert(0, v); },
+            OpCode::Mul => { let v = field::mul(s[0], s[1]); s.drain(..2); s.insert(0, v); },
+            OpCode::Inv => {
+                if s[0] == field::ZERO {
+                    return Err(format!("cannot compute INV of {} at step {}", field::ZERO, self.step));
+                }
+                s[0] = field::inv(s[0]);
+            },
+            OpCode::Neg => s[0] = field::neg(s[0]),
+            OpCode::Not => {
+                let x = binary(s[0], "cannot compute NOT of a non-binary value", self.step)?;
+                s[0] = (!x) as u128;
+            },
+            OpCode::And | OpCode::Or => {
+                let x = binary(s[0], "cannot compute AND/OR for a non-binary value", self.step)?;
+                let y = binary(s[1], "cannot compute AND/OR for a non-binary value", self.step)?;
+                let v = if op_code == OpCode::And { x && y } else { x || y };
+                s.drain(..2);
+                s.insert(0, v as u128);
+            },
+
+            OpCode::Eq => {
+                let (aux, x, y) = (s[0], s[1], s[2]);
+                if x != y && aux != field::inv(field::sub(x, y)) {
+                    return Err(format!("invalid AUX value for EQ operation at step {}", self.step));
+                }
+                s.drain(..3);
+                s.insert(0, (x == y) as u128);
+            },
+            OpCode::Cmp => {
+                if let OpHint::CmpStart(n) = op_hint {
+                    for i in 0..n {
+                        self.tape_a.push((s[8] >> i) & 1);
+                        self.tape_b.push((s[9] >> i) & 1);
+                    }
+                }
+                let a_bit = self.read_tape_a()?;
+                let b_bit = self.read_tape_b()?;
+                let s = &mut self.stack;
+                binary(a_bit, "expected binary input", self.step)?;
+                binary(b_bit, "expected binary input", self.step)?;
+
+                let power_of_two = s[0];
+                if !power_of_two.is_power_of_two() {
+                    return Err(format!("expected top of the stack at step {} to be a power of 2, but received {}",
+                        self.step, power_of_two));
+                }
+
+                // once either gt or lt flag is set, the flags remain unchanged
+                let (gt, lt) = (s[4], s[5]);
+                let not_set = field::mul(field::sub(field::ONE, gt), field::sub(field::ONE, lt));
+                let bit_gt = field::mul(a_bit, field::sub(field::ONE, b_bit));
+                let bit_lt = field::mul(b_bit, field::sub(field::ONE, a_bit));
+                s[0] = if power_of_two == 1 { field::div(1, 2) } else { power_of_two >> 1 };
+                s[1] = a_bit;
+                s[2] = b_bit;
+                s[3] = not_set;
+                s[4] = field::add(gt, field::mul(bit_gt, not_set));
+                s[5] = field::add(lt, field::mul(bit_lt, not_set));
+                s[6] = field::add(s[6], field::mul(b_bit, power_of_two));
+                s[7] = field::add(s[7], field::mul(a_bit, power_of_two));
+            },
+            OpCode::BinAcc => {
+                if let OpHint::RcStart(n) = op_hint {
+                    for i in 0..n {
+                        self.tape_a.push((s[4] >> (n - i - 1)) & 1);
+                    }
+                }
+                let bit = self.read_tape_a()?;
+                let s = &mut self.stack;
+                binary(bit, "expected binary input", self.step)?;
+
+                let power_of_two = s[2];
+                if !power_of_two.is_power_of_two() {
+                    return Err(format!("expected 3rd value from the top of the stack at step {} to be a power of 2, but received {}",
+                        self.step, power_of_two));
+                }
+                s[0] = bit;
+                s[1] = field::ZERO;
+                s[2] = field::mul(power_of_two, 2);
+                s[3] = field::add(s[3], field::mul(bit, power_of_two));
+            },
+
+            OpCode::RescR => {
+                HASHER.apply_round(&mut s[..HASH_STATE_WIDTH], self.step - 1);
+            },
+        }
+
+        if self.stack.len() > MAX_STACK_DEPTH {
+            return Err(format!("stack overflow at step {}", self.step));
+        }
+        return Ok(());
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    fn read_tape_a(&mut self) -> Result<u128, String> {
+        return read_tape(&mut self.tape_a, self.tape_policy, "A", self.step);
+    }
+
+    fn read_tape_b(&mut self) -> Result<u128, String> {
+        return read_tape(&mut self.tape_b, self.tape_policy, "B", self.step);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn run(program: &Program, inputs: &ProgramInputs, selector: Option<BranchSelector>) -> (Result<InterpreterState, String>, Option<BranchSelector>)
+{
+    let [secret_a, secret_b] = inputs.get_secret_inputs();
+    let mut interpreter = Interpreter {
+        stack       : inputs.get_public_inputs().to_vec(),
+        tape_a      : secret_a.iter().rev().cloned().collect(),
+        tape_b      : secret_b.iter().rev().cloned().collect(),
+        tape_policy : inputs.get_tape_policy(),
+        advice      : inputs.get_advice_map().clone(),
+        step        : 0,
+        op_count    : 0,
+        selector    : selector,
+    };
+
+    let result = interpreter.run_blocks(program.root().body())
+        .and_then(|_| interpreter.close_block())
+        .and_then(|_| {
+            let unconsumed = [interpreter.tape_a.len(), interpreter.tape_b.len()];
+            if inputs.is_full_consumption_required() && unconsumed != [0, 0] {
+                return Err(format!("not all secret inputs have been consumed: {:?} values left on tapes A and B",
+                    unconsumed));
+            }
+            Ok(InterpreterState {
+                stack       : interpreter.stack.clone(),
+                op_count    : interpreter.op_count,
+                unconsumed  : unconsumed,
+            })
+        });
+
+    return (result, interpreter.selector);
+}
+
+fn read_tape(tape: &mut Vec<u128>, policy: TapePolicy, name: &str, step: usize) -> Result<u128, String> {
+    return match tape.pop() {
+        Some(value) => Ok(value),
+        None => match policy {
+            TapePolicy::ZeroFill => Ok(field::ZERO),
+            TapePolicy::Error => Err(format!("attempt to read from empty tape {} at step {}", name, step)),
+        }
+    };
+}
+
+fn binary(value: u128, message: &str, step: usize) -> Result<bool, String> {
+    return match value {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(format!("{} at step {}", message, step)),
+    };
+}
@@ -11,10 +11,86 @@ mod decoder;
 pub use decoder::{ Decoder };
 
 mod stack;
-pub use stack::{ Stack };
+pub use stack::{ Stack, SecretUse };
 
 pub mod opcodes;
-pub use opcodes::{ UserOps as OpCode, OpHint };
+pub use opcodes::{ UserOps as OpCode, OpHint, OpSpec };
+
+mod interpreter;
+pub use interpreter::{ interpret, InterpreterState };
+
+mod paths;
+pub use paths::{ explore_paths, ExecutionPath, PathReport };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Summary of secret input consumption during program execution.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ExecutionReport {
+    /// Number of values provided on secret input tapes A and B.
+    pub provided_inputs : [usize; 2],
+    /// Number of provided values consumed from secret input tapes A and B.
+    pub consumed_inputs : [usize; 2],
+}
+
+impl ExecutionReport {
+
+    /// Returns the number of values left unconsumed on secret input tapes A and B.
+    pub fn unconsumed_inputs(&self) -> [usize; 2] {
+        return [
+            self.provided_inputs[0] - self.consumed_inputs[0],
+            self.provided_inputs[1] - self.consumed_inputs[1],
+        ];
+    }
+
+    /// Returns true if all values provided on secret input tapes have been consumed.
+    pub fn is_fully_consumed(&self) -> bool {
+        return self.consumed_inputs == self.provided_inputs;
+    }
+}
+
+/// Receives notifications about operations executed by the VM. This can be used to implement
+/// watchpoints, coverage measurement etc. without modifying the VM. All methods do nothing by
+/// default, so implementations need to override only the methods they are interested in.
+///
+/// In addition to operations of the program itself, the observer is notified about NOOPs
+/// which the VM executes when entering and exiting program blocks.
+pub trait ExecutionObserver {
+
+    /// Called before `op_code` is executed at the specified `step`; `stack_top` contains
+    /// the top 8 items of the stack before the operation is executed.
+    fn before_op(&mut self, _step: usize, _op_code: OpCode, _stack_top: &[u128; 8]) { }
+
+    /// Called after `op_code` was executed at the specified `step`; `stack_top` contains
+    /// the top 8 items of the stack after the operation was executed.
+    fn after_op(&mut self, _step: usize, _op_code: OpCode, _stack_top: &[u128; 8]) { }
+}
+
+/// An observer which ignores all notifications.
+impl ExecutionObserver for () { }
+
+/// Supplies nondeterministic inputs for operations marked with `OpHint::Custom` hints. This can
+/// be used to experiment with new kinds of execution hints without modifying the VM: a program
+/// built with custom hints attached to `READ` or `READ2` operations (e.g. via `blocks::Span`)
+/// can be executed with a provider which computes the values these operations read.
+pub trait HintProvider {
+
+    /// Called before an operation marked with `OpHint::Custom(tag, payload)` is executed at the
+    /// specified `step`; `stack_top` contains the top 8 items of the stack. Returns values which
+    /// are placed at the front of input tapes A and B (in the order in which they will be read).
+    /// The values should be consumed by the program, otherwise the reported number of consumed
+    /// secret inputs will be inaccurate.
+    fn provide_hint(&mut self, step: usize, tag: u32, payload: u128, stack_top: &[u128; 8]) -> (Vec<u128>, Vec<u128>);
+}
+
+/// A provider which supplies no values; with it, operations marked with custom hints read values
+/// already present on the input tapes.
+impl HintProvider for () {
+    fn provide_hint(&mut self, _step: usize, _tag: u32, _payload: u128, _stack_top: &[u128; 8]) -> (Vec<u128>, Vec<u128>) {
+        return (Vec::new(), Vec::new());
+    }
+}
 
 // PUBLIC FUNCTIONS
 // ================================================================================================
@@ -22,13 +98,39 @@ pub use opcodes::{ UserOps as OpCode, OpHint };
 /// Returns register traces resulting from executing the `program` against the specified inputs.
 pub fn execute(program: &Program, inputs: &ProgramInputs) -> (Vec<Vec<u128>>, usize, usize)
 {
-    // initialize decoder and stack components
-    let mut decoder = Decoder::new(MIN_TRACE_LENGTH);
-    let mut stack = Stack::new(inputs, MIN_TRACE_LENGTH);
+    return execute_with_observer(program, inputs, &mut ());
+}
 
-    // execute body of the program
-    execute_blocks(program.root().body(), &mut decoder, &mut stack);
-    close_block(&mut decoder, &mut stack, field::ZERO, true);
+/// Returns register traces resulting from executing the `program` against the specified inputs;
+/// the `observer` is notified before and after every executed operation.
+pub fn execute_with_observer(program: &Program, inputs: &ProgramInputs, observer: &mut dyn ExecutionObserver) -> (Vec<Vec<u128>>, usize, usize)
+{
+    return execute_with_hints(program, inputs, observer, &mut ());
+}
+
+/// Returns register traces resulting from executing the `program` against the specified inputs;
+/// the `observer` is notified before and after every executed operation, and the `hints`
+/// provider supplies values for operations marked with `OpHint::Custom` hints.
+pub fn execute_with_hints(program: &Program, inputs: &ProgramInputs, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider) -> (Vec<Vec<u128>>, usize, usize)
+{
+    let (register_traces, context_depth, loop_depth, _) = execute_with_report(program, inputs, observer, hints);
+    return (register_traces, context_depth, loop_depth);
+}
+
+/// Returns register traces resulting from executing the `program` in the same way as
+/// `execute_with_hints()` does, together with a report describing how many secret inputs were
+/// consumed by the execution.
+pub fn execute_with_report(program: &Program, inputs: &ProgramInputs, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider) -> (Vec<Vec<u128>>, usize, usize, ExecutionReport)
+{
+    let (mut decoder, mut stack) = run(program, inputs, observer, hints);
+
+    // if required, make sure all secret inputs have been consumed
+    let report = build_report(inputs, &stack);
+    if inputs.is_full_consumption_required() {
+        assert!(report.is_fully_consumed(),
+            "not all secret inputs have been consumed: {:?} values left on tapes A and B",
+            report.unconsumed_inputs());
+    }
 
     // fill in remaining steps to make sure the length of the trace is a power of 2
     decoder.finalize_trace();
@@ -42,52 +144,98 @@ pub fn execute(program: &Program, inputs: &ProgramInputs) -> (Vec<Vec<u128>>, us
     let mut register_traces = decoder.into_register_traces();
     register_traces.append(&mut stack.into_register_traces());
 
-    return (register_traces, context_depth, loop_depth);
+    return (register_traces, context_depth, loop_depth, report);
+}
+
+/// Executes the `program` against the specified inputs and returns a list of places in the
+/// execution where values derived from secret inputs were used.
+pub fn analyze(program: &Program, inputs: &ProgramInputs) -> Vec<SecretUse>
+{
+    let (_, stack) = run(program, inputs, &mut (), &mut ());
+    return stack.secret_uses().to_vec();
+}
+
+/// Executes the `program` against the specified inputs and returns a report describing how many
+/// secret inputs were consumed by the program.
+pub fn report(program: &Program, inputs: &ProgramInputs) -> ExecutionReport
+{
+    let (_, stack) = run(program, inputs, &mut (), &mut ());
+    return build_report(inputs, &stack);
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut Stack)
+
+/// Executes the `program` against the specified inputs and returns decoder and stack components
+/// containing the resulting execution trace.
+fn run(program: &Program, inputs: &ProgramInputs, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider) -> (Decoder, Stack)
+{
+    // initialize decoder and stack components
+    let mut decoder = Decoder::new(MIN_TRACE_LENGTH);
+    let mut stack = Stack::new(inputs, MIN_TRACE_LENGTH);
+
+    // execute body of the program
+    execute_blocks(program.root().body(), &mut decoder, &mut stack, observer, hints);
+    close_block(&mut decoder, &mut stack, observer, hints, field::ZERO, true);
+
+    return (decoder, stack);
+}
+
+fn build_report(inputs: &ProgramInputs, stack: &Stack) -> ExecutionReport
+{
+    // values pushed onto the tapes by execution hints are always consumed by the operations
+    // which follow the hints; so, all values remaining on the tapes were provided by the user
+    let [secret_a, secret_b] = inputs.get_secret_inputs();
+    let unconsumed = stack.unconsumed_inputs();
+    return ExecutionReport {
+        provided_inputs : [secret_a.len(), secret_b.len()],
+        consumed_inputs : [secret_a.len() - unconsumed[0], secret_b.len() - unconsumed[1]],
+    };
+}
+
+fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider)
 {
     // execute first block in the sequence, which mast be a Span block
     match &blocks[0] {
-        ProgramBlock::Span(block) => execute_span(block, decoder, stack, true),
+        ProgramBlock::Span(block) => execute_span(block, decoder, stack, observer, hints, true),
         _ => panic!("first block in a sequence must be a Span block"),
     }
 
     // execute all other blocks in the sequence one after another
     for block in blocks.iter().skip(1) {
         match block {
-            ProgramBlock::Span(block) => execute_span(block, decoder, stack, false),
+            ProgramBlock::Span(block) => execute_span(block, decoder, stack, observer, hints, false),
             ProgramBlock::Group(block) => {
-                start_block(decoder, stack);
-                execute_blocks(block.body(), decoder, stack);
-                close_block(decoder, stack, field::ZERO, true);
+                start_block(decoder, stack, observer, hints);
+                execute_blocks(block.body(), decoder, stack, observer, hints);
+                close_block(decoder, stack, observer, hints, field::ZERO, true);
             },
             ProgramBlock::Switch(block) => {
-                start_block(decoder, stack);
+                start_block(decoder, stack, observer, hints);
+                stack.check_condition_secrecy(SecretUse::Branch(stack.current_step()));
                 let condition = stack.get_stack_top();
                 match condition {
                     0 => {
-                        execute_blocks(block.false_branch(), decoder, stack);
-                        close_block(decoder, stack, block.true_branch_hash(), false);
+                        execute_blocks(block.false_branch(), decoder, stack, observer, hints);
+                        close_block(decoder, stack, observer, hints, block.true_branch_hash(), false);
                     },
                     1 => {
-                        execute_blocks(block.true_branch(), decoder, stack);
-                        close_block(decoder, stack, block.false_branch_hash(), true);
+                        execute_blocks(block.true_branch(), decoder, stack, observer, hints);
+                        close_block(decoder, stack, observer, hints, block.false_branch_hash(), true);
                     },
                     _ => panic!("cannot select a branch based on a non-binary condition {}", condition)
                 };
             },
             ProgramBlock::Loop(block) => {
+                stack.check_condition_secrecy(SecretUse::Loop(stack.current_step()));
                 let condition = stack.get_stack_top();
                 match condition {
                     0 => {
-                        start_block(decoder, stack);
-                        execute_blocks(block.skip(), decoder, stack);
-                        close_block(decoder, stack, block.body_hash(), false);
+                        start_block(decoder, stack, observer, hints);
+                        execute_blocks(block.skip(), decoder, stack, observer, hints);
+                        close_block(decoder, stack, observer, hints, block.body_hash(), false);
                     },
-                    1 => execute_loop(block, decoder, stack),
+                    1 => execute_loop(block, decoder, stack, observer, hints),
                     _ => panic!("cannot enter loop based on a non-binary condition {}", condition)
                 }
             },
@@ -96,73 +244,89 @@ fn execute_blocks(blocks: &[ProgramBlock], decoder: &mut Decoder, stack: &mut St
 }
 
 /// Executes all instructions in a Span block.
-fn execute_span(block: &Span, decoder: &mut Decoder, stack: &mut Stack, is_first: bool)
+fn execute_span(block: &Span, decoder: &mut Decoder, stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider, is_first: bool)
 {
     // if this is the first Span block in a sequence of blocks, it needs to be
     // pre-padded with a NOOP to make sure the first instruction in the block
     // starts executing on a step which is a multiple of 16
     if !is_first {
         decoder.decode_op(OpCode::Noop, field::ZERO);
-        stack.execute(OpCode::Noop, OpHint::None);
+        execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
     }
 
     // execute all other instructions in the block
     for i in 0..block.length() {
         let (op_code, op_hint) = block.get_op(i);
         decoder.decode_op(op_code, op_hint.value());
-        stack.execute(op_code, op_hint);
+        execute_op(stack, observer, hints, op_code, op_hint);
+    }
+}
+
+/// Executes a single operation against the stack and notifies the observer about it; if the
+/// operation is marked with a custom hint, values supplied by the hint provider are put onto
+/// the input tapes first.
+fn execute_op(stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider, op_code: OpCode, op_hint: OpHint)
+{
+    let step = stack.current_step();
+    if let OpHint::Custom(tag, payload) = op_hint {
+        let (values_a, values_b) = hints.provide_hint(step, tag, payload, &stack.get_stack_top8());
+        stack.prepend_tapes(values_a, values_b);
     }
+    observer.before_op(step, op_code, &stack.get_stack_top8());
+    stack.execute(op_code, op_hint);
+    observer.after_op(step, op_code, &stack.get_stack_top8());
 }
 
 /// Starts executing a new program block.
-fn start_block(decoder: &mut Decoder, stack: &mut Stack)
+fn start_block(decoder: &mut Decoder, stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider)
 {
     decoder.start_block();
-    stack.execute(OpCode::Noop, OpHint::None);
+    execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
 }
 
 /// Closes the currently executing program block.
-fn close_block(decoder: &mut Decoder, stack: &mut Stack, sibling_hash: u128, is_true_branch: bool)
+fn close_block(decoder: &mut Decoder, stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider, sibling_hash: u128, is_true_branch: bool)
 {
     // a sequence of blocks always ends on a step which is one less than a multiple of 16;
     // all sequences end one operation short of multiple of 16 - so, we need to pad them
     // with a single NOOP ensure proper alignment
     decoder.decode_op(OpCode::Noop, field::ZERO);
-    stack.execute(OpCode::Noop, OpHint::None);
+    execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
 
     // end the block, this prepares decoder registers for merging block hash into
     // program hash
     decoder.end_block(sibling_hash, is_true_branch);
-    stack.execute(OpCode::Noop, OpHint::None);
+    execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
 
     // execute NOOPs to merge block hash into the program hash
     for _ in 0..HACC_NUM_ROUNDS {
         decoder.decode_op(OpCode::Noop, field::ZERO);
-        stack.execute(OpCode::Noop, OpHint::None);
+        execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
     }
 }
 
 /// Executes the specified loop.
-fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
+fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack, observer: &mut dyn ExecutionObserver, hints: &mut dyn HintProvider)
 {
     // mark the beginning of the loop block
     decoder.start_loop(block.image());
-    stack.execute(OpCode::Noop, OpHint::None);
+    execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
 
     // execute blocks in loop body until top of the stack becomes 0
     loop {
-        execute_blocks(block.body(), decoder, stack);
+        execute_blocks(block.body(), decoder, stack, observer, hints);
 
+        stack.check_condition_secrecy(SecretUse::Loop(stack.current_step()));
         let condition = stack.get_stack_top();
         match condition {
             0 => {
                 decoder.break_loop();
-                stack.execute(OpCode::Noop, OpHint::None);
+                execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
                 break;
             },
             1 => {
                 decoder.wrap_loop();
-                stack.execute(OpCode::Noop, OpHint::None);
+                execute_op(stack, observer, hints, OpCode::Noop, OpHint::None);
             },
             _ => panic!("cannot exit loop based on a non-binary condition {}", condition)
         };
@@ -170,12 +334,12 @@ fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
 
     // execute the contents of the skip block to make sure the loop was exited correctly
     match &block.skip()[0] {
-        ProgramBlock::Span(block) => execute_span(block, decoder, stack, true),
+        ProgramBlock::Span(block) => execute_span(block, decoder, stack, observer, hints, true),
         _ => panic!("invalid skip block content: content must be a Span block"),
     }
 
     // close block
-    close_block(decoder, stack, block.skip_hash(), true);
+    close_block(decoder, stack, observer, hints, block.skip_hash(), true);
 }
 
 // TESTS
@@ -185,7 +349,8 @@ fn execute_loop(block: &Loop, decoder: &mut Decoder, stack: &mut Stack)
 mod tests {
 
     use crate::{ programs::assembly, stark::TraceState, utils::as_bytes };
-    use super::{ ProgramInputs };
+    use crate::TapePolicy;
+    use super::{ ProgramInputs, SecretUse, OpCode, OpHint, ExecutionObserver, HintProvider };
 
     #[test]
     fn execute_span() {
@@ -196,7 +361,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(17, trace.len());
+        assert_eq!(30, trace.len());
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
 
@@ -206,6 +371,7 @@ mod tests {
         assert_eq!([1, 1, 1, 1, 1], state.ld_op_bits());
         assert_eq!([1, 1], state.hd_op_bits());
         assert_eq!([0], state.ctx_stack());
+        assert_eq!(2, state.depth());
         assert_eq!([7, 15, 0, 0, 0, 0, 0, 0], state.user_stack());
     }
 
@@ -218,7 +384,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(18, trace.len());
+        assert_eq!(31, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -244,7 +410,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(32, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -264,7 +430,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(32, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -290,7 +456,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(64, trace_length);
-        assert_eq!(18, trace.len());
+        assert_eq!(31, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -310,7 +476,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(128, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(32, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -330,7 +496,7 @@ mod tests {
         let trace_length = trace[0].len();
 
         assert_eq!(256, trace_length);
-        assert_eq!(19, trace.len());
+        assert_eq!(32, trace.len());
 
         let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth) ;
         state.update_from_trace(&trace, trace_length - 1);
@@ -345,9 +511,208 @@ mod tests {
         assert_eq!([43143988327398919500410556793212890625, 0, 0, 0, 0, 0, 0, 0], state.user_stack());
     }
 
+    #[test]
+    fn execute_with_observer() {
+        let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1, 2]);
+
+        struct Observer { steps: Vec<usize>, products: Vec<u128> }
+        impl ExecutionObserver for Observer {
+            fn before_op(&mut self, step: usize, _op_code: OpCode, _stack_top: &[u128; 8]) {
+                self.steps.push(step);
+            }
+            fn after_op(&mut self, _step: usize, op_code: OpCode, stack_top: &[u128; 8]) {
+                if op_code == OpCode::Mul { self.products.push(stack_top[0]); }
+            }
+        }
+
+        let mut observer = Observer { steps: Vec::new(), products: Vec::new() };
+        let (trace, ..) = super::execute_with_observer(&program, &inputs, &mut observer);
+
+        // the observer is notified about every executed operation
+        assert_eq!((0..47).collect::<Vec<_>>(), observer.steps);
+        assert_eq!(vec![15], observer.products);
+        assert_eq!(super::execute(&program, &inputs).0, trace);
+    }
+
+    #[test]
+    fn execute_with_hints() {
+        use std::collections::HashMap;
+        use crate::programs::{ Program, blocks::{ ProgramBlock, Span, Group } };
+
+        let mut instructions = vec![OpCode::Noop; 15];
+        instructions[0] = OpCode::Begin;
+        instructions[1] = OpCode::Read;
+        instructions[2] = OpCode::Read2;
+        let mut hints = HashMap::new();
+        hints.insert(1, OpHint::Custom(1, 5));
+        hints.insert(2, OpHint::Custom(2, 0));
+        let program = Program::new(Group::new(vec![ProgramBlock::Span(Span::new(instructions, hints))]));
+        let inputs = ProgramInputs::new(&[1, 2], &[3, 4], &[5]);
+
+        // tag 1 doubles the payload, and tag 2 increments the top of the stack
+        struct Provider { tags: Vec<(usize, u32)> }
+        impl HintProvider for Provider {
+            fn provide_hint(&mut self, step: usize, tag: u32, payload: u128, stack_top: &[u128; 8]) -> (Vec<u128>, Vec<u128>) {
+                self.tags.push((step, tag));
+                return match tag {
+                    1 => (vec![payload * 2], vec![]),
+                    _ => (vec![stack_top[0] + 1], vec![7]),
+                };
+            }
+        }
+
+        let mut provider = Provider { tags: Vec::new() };
+        let (trace, ..) = super::execute_with_hints(&program, &inputs, &mut (), &mut provider);
+        assert_eq!(vec![(1, 1), (2, 2)], provider.tags);
+        let mut state = build_trace_state(trace.len(), 0, 0);
+        state.update_from_trace(&trace, trace[0].len() - 1);
+        assert_eq!([7, 11, 10, 1, 2, 0, 0, 0], state.user_stack());
+
+        // without a provider, the same operations read values from the input tapes
+        let (trace, ..) = super::execute(&program, &inputs);
+        state.update_from_trace(&trace, trace[0].len() - 1);
+        assert_eq!([5, 4, 3, 1, 2, 0, 0, 0], state.user_stack());
+        assert_eq!(Ok(vec![5, 4, 3, 1, 2]), super::interpret(&program, &inputs).map(|state| state.stack));
+    }
+
+    #[test]
+    fn explore_paths() {
+        let program = assembly::compile("begin read if.true push.3 else push.5 end \
+            read if.true push.7 mul else push.11 mul end end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[1, 0], &[]);
+
+        // every combination of branches is executed, and every path reads its own copy of the tapes
+        let report = super::explore_paths(&program, &inputs, 16);
+        assert!(report.is_complete());
+        assert_eq!(2, report.num_switches());
+        let paths: Vec<(Vec<(usize, bool)>, bool, u128)> = report.paths().iter()
+            .map(|p| (p.branches.clone(), p.feasible, p.result.as_ref().unwrap().stack[0])).collect();
+        assert_eq!(vec![
+            (vec![(0, true), (1, true)], false, 21),
+            (vec![(0, false), (1, true)], false, 35),
+            (vec![(0, true), (1, false)], true, 33),
+            (vec![(0, false), (1, false)], false, 55),
+        ], paths);
+        assert_eq!(vec![[true, true], [true, true]], report.coverage());
+
+        let op_count = super::interpret(&program, &inputs).unwrap().op_count;
+        assert_eq!(Some(op_count), report.max_op_count());
+
+        // exploration stops after the specified number of paths
+        let report = super::explore_paths(&program, &inputs, 2);
+        assert!(!report.is_complete());
+        assert_eq!(2, report.paths().len());
+        assert_eq!(vec![[true, true], [true, false]], report.coverage());
+
+        // paths which fail are reported with their errors
+        let program = assembly::compile("begin read if.true push.1 assert else push.0 assert end end").unwrap();
+        let report = super::explore_paths(&program, &ProgramInputs::new(&[], &[1], &[]), 16);
+        assert!(report.paths()[0].result.is_ok());
+        assert!(report.paths()[1].result.is_err());
+        assert!(!report.paths()[1].feasible);
+    }
+
+    #[test]
+    fn analyze_secret_uses() {
+        let program = assembly::compile(
+            "begin push.3 swap read mul if.true push.2 add else push.7 end drop end").unwrap();
+        let inputs = ProgramInputs::new(&[1], &[1], &[]);
+
+        // only the value read from the secret tape and values derived from it are secret
+        let uses = super::analyze(&program, &inputs);
+        assert_eq!(vec![
+            SecretUse::Op(12, OpCode::Mul),
+            SecretUse::Branch(16),
+            SecretUse::Op(17, OpCode::Assert),
+        ], uses);
+
+        // without secret inputs, nothing is reported
+        let program = assembly::compile("begin push.3 swap push.1 mul add end").unwrap();
+        let uses = super::analyze(&program, &ProgramInputs::from_public(&[1]));
+        assert_eq!(Vec::<SecretUse>::new(), uses);
+
+        // moving secret values around the stack is not a use of the values
+        let program = assembly::compile("begin read push.3 swap dup drop roll.4 end").unwrap();
+        let uses = super::analyze(&program, &ProgramInputs::new(&[1, 2], &[7], &[]));
+        assert_eq!(Vec::<SecretUse>::new(), uses);
+    }
+
+    #[test]
+    fn execute_hardened() {
+        // hardened execution produces the same trace as regular execution for both values of
+        // every condition and for both equal and different values
+        let program = assembly::compile(
+            "begin read read read choose read read eq read inv read read read read read choose.2 sortnet.2 end").unwrap();
+        for tape in [[1, 5, 6, 4, 4, 3, 1, 7, 8, 9, 10], [0, 5, 6, 4, 9, 3, 0, 7, 8, 10, 9]].iter() {
+            let inputs = ProgramInputs::new(&[], tape, &[]);
+            let (trace, ..) = super::execute(&program, &inputs);
+            let (hardened, ..) = super::execute(&program, &inputs.with_hardened_execution());
+            assert_eq!(trace, hardened);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "hardened execution cannot select a branch based on a secret value at step")]
+    fn execute_hardened_secret_branch() {
+        let program = assembly::compile("begin read if.true push.1 else push.2 end end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[1], &[]).with_hardened_execution();
+        super::execute(&program, &inputs);
+    }
+
+    #[test]
+    #[should_panic(expected = "hardened execution cannot decide whether to execute a loop based on a secret value at step")]
+    fn execute_hardened_secret_loop() {
+        let program = assembly::compile("begin read while.true read end end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[1, 0], &[]).with_hardened_execution();
+        super::execute(&program, &inputs);
+    }
+
+    #[test]
+    fn report_inputs_consumption() {
+        let program = assembly::compile("begin read read.ab add add end").unwrap();
+
+        // all inputs consumed
+        let inputs = ProgramInputs::new(&[], &[1, 2], &[3]);
+        let report = super::report(&program, &inputs);
+        assert_eq!([2, 1], report.consumed_inputs);
+        assert_eq!([0, 0], report.unconsumed_inputs());
+        assert!(report.is_fully_consumed());
+
+        // leftover values on both tapes
+        let inputs = ProgramInputs::new(&[], &[1, 2, 3, 4], &[3, 4]);
+        let report = super::report(&program, &inputs);
+        assert_eq!([4, 2], report.provided_inputs);
+        assert_eq!([2, 1], report.unconsumed_inputs());
+        assert!(!report.is_fully_consumed());
+
+        // missing values are replaced with zeros
+        let inputs = ProgramInputs::new(&[], &[1], &[]).with_tape_policy(TapePolicy::ZeroFill);
+        assert_eq!([0, 0], super::report(&program, &inputs).unconsumed_inputs());
+        let (trace, ctx_depth, loop_depth) = super::execute(&program, &inputs);
+        let mut state = build_trace_state(trace.len(), ctx_depth, loop_depth);
+        state.update_from_trace(&trace, trace[0].len() - 1);
+        assert_eq!([1, 0, 0, 0, 0, 0, 0, 0], state.user_stack());
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to read from empty tape B")]
+    fn read_from_empty_tape() {
+        let program = assembly::compile("begin read read.ab add add end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[1, 2], &[]);
+        super::execute(&program, &inputs);
+    }
+
+    #[test]
+    #[should_panic(expected = "not all secret inputs have been consumed: [1, 0] values left on tapes A and B")]
+    fn require_full_consumption() {
+        let program = assembly::compile("begin read read.ab add add end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[1, 2, 3], &[3]).require_full_consumption();
+        super::execute(&program, &inputs);
+    }
+
     fn build_trace_state(num_registers: usize, ctx_depth: usize, loop_depth: usize) -> TraceState {
-        let decoder_width = TraceState::compute_decoder_width(ctx_depth, loop_depth);
-        let stack_depth = num_registers - decoder_width;
+        let stack_depth = TraceState::compute_stack_depth(num_registers, ctx_depth, loop_depth);
         return TraceState::new(ctx_depth, loop_depth, stack_depth);
     }
 }
\ No newline at end of file
@@ -41,8 +41,32 @@ impl std::fmt::Display for FlowOps {
 
 // USER OPERATIONS
 // ================================================================================================
+
+/// Static properties of a user operation. This is the single source of truth for these
+/// properties: the VM, stack depth constraints, the assembler, and program analysis all rely
+/// on it, and it can be used by external tools (e.g. to generate documentation).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpSpec {
+    /// Minimum number of items which must be on the stack for the operation to be executed.
+    pub required_depth  : usize,
+    /// Number of items by which the operation changes the depth of the stack; negative values
+    /// mean that the stack is shifted to the left.
+    pub depth_change    : isize,
+    /// The operation can be executed only on steps which are multiples of this value.
+    pub alignment       : usize,
+    /// Degree of transition constraints enforced for the operation, including the degree of
+    /// the operation flag.
+    pub degree          : usize,
+}
+
+impl OpSpec {
+    const fn new(required_depth: usize, depth_change: isize, alignment: usize, degree: usize) -> OpSpec {
+        return OpSpec { required_depth, depth_change, alignment, degree };
+    }
+}
+
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum UserOps {
     
     // low-degree operations
@@ -62,7 +86,7 @@ pub enum UserOps {
     Inv         = 0b0_11_01100,         // no shift
     Neg         = 0b0_11_01101,         // no shift
     Not         = 0b0_11_01110,         // no shift
-    //???       = 0b0_11_01111,
+    AssertBin   = 0b0_11_01111,         // no shift
 
     Read        = 0b0_11_10000,         // right shift: 1
     Read2       = 0b0_11_10001,         // right shift: 2
@@ -70,8 +94,8 @@ pub enum UserOps {
     Dup2        = 0b0_11_10011,         // right shift: 2
     Dup4        = 0b0_11_10100,         // right shift: 4
     Pad2        = 0b0_11_10101,         // right shift: 2
-    //???       = 0b0_11_10110,
-    //???       = 0b0_11_10111,
+    Dup8        = 0b0_11_10110,         // right shift: 8
+    Drop8       = 0b0_11_10111,         // left shift: 8
 
     Swap        = 0b0_11_11000,         // no shift
     Swap2       = 0b0_11_11001,         // no shift
@@ -79,7 +103,7 @@ pub enum UserOps {
     Roll4       = 0b0_11_11011,         // no shift
     Roll8       = 0b0_11_11100,         // no shift
     BinAcc      = 0b0_11_11101,         // no shift
-    //???       = 0b0_11_11110,
+    Swap8       = 0b0_11_11110,         // no shift
 
     // high-degree operations
     Push        = 0b0_00_11111,         // right shift: 1
@@ -114,6 +138,71 @@ impl UserOps {
             }
         };
     }
+
+    /// Returns the static properties of this operation; see `OpSpec` for details.
+    pub const fn spec(&self) -> OpSpec {
+        return match self {
+            //                                 depth  change  align  degree
+            UserOps::Begin      => OpSpec::new(0,      0,     1,     7),
+            UserOps::Noop       => OpSpec::new(0,      0,     1,     7),
+
+            UserOps::Assert     => OpSpec::new(1,     -1,     1,     7),
+            UserOps::AssertEq   => OpSpec::new(2,     -2,     1,     6),
+            UserOps::AssertBin  => OpSpec::new(1,      0,     1,     7),
+
+            UserOps::Push       => OpSpec::new(0,      1,     8,     4),
+            UserOps::Read       => OpSpec::new(0,      1,     1,     6),
+            UserOps::Read2      => OpSpec::new(0,      2,     1,     6),
+
+            UserOps::Dup        => OpSpec::new(1,      1,     1,     6),
+            UserOps::Dup2       => OpSpec::new(2,      2,     1,     6),
+            UserOps::Dup4       => OpSpec::new(4,      4,     1,     6),
+            UserOps::Dup8       => OpSpec::new(8,      8,     1,     6),
+            UserOps::Pad2       => OpSpec::new(0,      2,     1,     6),
+
+            UserOps::Drop       => OpSpec::new(1,     -1,     1,     6),
+            UserOps::Drop4      => OpSpec::new(4,     -4,     1,     6),
+            UserOps::Drop8      => OpSpec::new(8,     -8,     1,     6),
+
+            UserOps::Swap       => OpSpec::new(2,      0,     1,     6),
+            UserOps::Swap2      => OpSpec::new(4,      0,     1,     6),
+            UserOps::Swap4      => OpSpec::new(8,      0,     1,     6),
+            UserOps::Swap8      => OpSpec::new(16,     0,     1,     6),
+
+            UserOps::Roll4      => OpSpec::new(4,      0,     1,     6),
+            UserOps::Roll8      => OpSpec::new(8,      0,     1,     6),
+
+            UserOps::Choose     => OpSpec::new(3,     -2,     1,     7),
+            UserOps::Choose2    => OpSpec::new(6,     -4,     1,     7),
+            UserOps::CSwap2     => OpSpec::new(6,     -2,     1,     7),
+
+            UserOps::Add        => OpSpec::new(2,     -1,     1,     6),
+            UserOps::Mul        => OpSpec::new(2,     -1,     1,     7),
+            UserOps::And        => OpSpec::new(2,     -1,     1,     7),
+            UserOps::Or         => OpSpec::new(2,     -1,     1,     7),
+            UserOps::Inv        => OpSpec::new(1,      0,     1,     7),
+            UserOps::Neg        => OpSpec::new(1,      0,     1,     6),
+            UserOps::Not        => OpSpec::new(1,      0,     1,     7),
+
+            UserOps::Eq         => OpSpec::new(3,     -2,     1,     7),
+            UserOps::Cmp        => OpSpec::new(8,      0,     1,     5),
+            UserOps::BinAcc     => OpSpec::new(4,      0,     1,     7),
+
+            UserOps::RescR      => OpSpec::new(6,      0,     1,     5),
+        };
+    }
+
+    /// Returns the minimum number of items which must be on the stack for this operation
+    /// to be executed.
+    pub fn required_depth(&self) -> usize {
+        return self.spec().required_depth;
+    }
+
+    /// Returns the number of items by which this operation changes the depth of the stack;
+    /// negative values mean that the stack is shifted to the left.
+    pub fn depth_change(&self) -> isize {
+        return self.spec().depth_change;
+    }
 }
 
 impl std::fmt::Display for UserOps {
@@ -126,6 +215,7 @@ impl std::fmt::Display for UserOps {
 
             UserOps::Assert     => write!(f, "assert"),
             UserOps::AssertEq   => write!(f, "asserteq"),
+            UserOps::AssertBin  => write!(f, "assertbin"),
     
             UserOps::Push       => write!(f, "push"),
             UserOps::Read       => write!(f, "read"),
@@ -134,14 +224,17 @@ impl std::fmt::Display for UserOps {
             UserOps::Dup        => write!(f, "dup"),
             UserOps::Dup2       => write!(f, "dup2"),
             UserOps::Dup4       => write!(f, "dup4"),
+            UserOps::Dup8       => write!(f, "dup8"),
             UserOps::Pad2       => write!(f, "pad2"),
     
             UserOps::Drop       => write!(f, "drop"),
             UserOps::Drop4      => write!(f, "drop4"),
+            UserOps::Drop8      => write!(f, "drop8"),
     
             UserOps::Swap       => write!(f, "swap"),
             UserOps::Swap2      => write!(f, "swap2"),
             UserOps::Swap4      => write!(f, "swap4"),
+            UserOps::Swap8      => write!(f, "swap8"),
     
             UserOps::Roll4      => write!(f, "roll4"),
             UserOps::Roll8      => write!(f, "roll8"),
@@ -167,15 +260,76 @@ impl std::fmt::Display for UserOps {
     }
 }
 
+impl std::str::FromStr for UserOps {
+    type Err = String;
+
+    /// Parses an operation from its name as printed by `Display` (e.g. `read2` or `rescr`).
+    fn from_str(name: &str) -> Result<UserOps, String> {
+        return match name {
+
+            "begin"     => Ok(UserOps::Begin),
+            "noop"      => Ok(UserOps::Noop),
+
+            "assert"    => Ok(UserOps::Assert),
+            "asserteq"  => Ok(UserOps::AssertEq),
+            "assertbin" => Ok(UserOps::AssertBin),
+
+            "push"      => Ok(UserOps::Push),
+            "read"      => Ok(UserOps::Read),
+            "read2"     => Ok(UserOps::Read2),
+
+            "dup"       => Ok(UserOps::Dup),
+            "dup2"      => Ok(UserOps::Dup2),
+            "dup4"      => Ok(UserOps::Dup4),
+            "dup8"      => Ok(UserOps::Dup8),
+            "pad2"      => Ok(UserOps::Pad2),
+
+            "drop"      => Ok(UserOps::Drop),
+            "drop4"     => Ok(UserOps::Drop4),
+            "drop8"     => Ok(UserOps::Drop8),
+
+            "swap"      => Ok(UserOps::Swap),
+            "swap2"     => Ok(UserOps::Swap2),
+            "swap4"     => Ok(UserOps::Swap4),
+            "swap8"     => Ok(UserOps::Swap8),
+
+            "roll4"     => Ok(UserOps::Roll4),
+            "roll8"     => Ok(UserOps::Roll8),
+
+            "choose"    => Ok(UserOps::Choose),
+            "choose2"   => Ok(UserOps::Choose2),
+            "cswap2"    => Ok(UserOps::CSwap2),
+
+            "add"       => Ok(UserOps::Add),
+            "mul"       => Ok(UserOps::Mul),
+            "inv"       => Ok(UserOps::Inv),
+            "neg"       => Ok(UserOps::Neg),
+            "not"       => Ok(UserOps::Not),
+            "and"       => Ok(UserOps::And),
+            "or"        => Ok(UserOps::Or),
+
+            "eq"        => Ok(UserOps::Eq),
+            "cmp"       => Ok(UserOps::Cmp),
+            "binacc"    => Ok(UserOps::BinAcc),
+
+            "rescr"     => Ok(UserOps::RescR),
+            _ => Err(format!("operation '{}' does not exist", name)),
+        };
+    }
+}
+
 // OPERATION HINTS
 // ================================================================================================
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum OpHint {
     EqStart,
+    DivModStart,
     RcStart(u32),
     CmpStart(u32),
     PmpathStart(u32),
     PushValue(u128),
+    AdviceKey(u32),
+    Custom(u32, u128),
     None,
 }
 
@@ -192,10 +346,13 @@ impl std::fmt::Display for OpHint {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         return match self {
             OpHint::EqStart          => write!(f, "::eq"),
+            OpHint::DivModStart      => write!(f, "::divmod"),
             OpHint::RcStart(value)   => write!(f, ".{}", value),
             OpHint::CmpStart(value)     => write!(f, ".{}", value),
             OpHint::PmpathStart(value)  => write!(f, ".{}", value),
             OpHint::PushValue(value)    => write!(f, "({})", value),
+            OpHint::AdviceKey(n)        => write!(f, ".key.{}", n),
+            OpHint::Custom(tag, payload) => write!(f, "::custom({}, {})", tag, payload),
             OpHint::None             => Ok(()),
         };
     }
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use crossbeam_utils::thread;
+use crate::programs::{ Program, ProgramInputs, blocks::{ ProgramBlock, Switch } };
+use super::interpreter::{ interpret_path, BranchSelector, InterpreterState };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of steps after which exploration of a path is abandoned.
+const MAX_PATH_STEPS: usize = 1 << 24;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A single path through a program explored by `explore_paths()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionPath {
+    /// Branches executed along the path as (switch index, branch) tuples, where Switch blocks
+    /// are indexed in the order in which they appear in the program; a Switch block inside a
+    /// loop is listed once for every iteration.
+    pub branches    : Vec<(usize, bool)>,
+    /// True if all branches were selected by the provided inputs; false if the condition of at
+    /// least one Switch block had to be overridden to execute the path.
+    pub feasible    : bool,
+    /// Final state of the VM, or an error if the path failed.
+    pub result      : Result<InterpreterState, String>,
+}
+
+/// Paths through a program explored by `explore_paths()`, together with branch coverage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathReport {
+    paths           : Vec<ExecutionPath>,
+    num_switches    : usize,
+    is_complete     : bool,
+}
+
+// PATH EXPLORATION
+// ================================================================================================
+
+/// Executes all paths through the `program` which differ in the branches taken by Switch blocks,
+/// starting with the provided `inputs`; at most `max_paths` paths are explored. Whenever a Switch
+/// block is reached, the path is forked: both branches are executed with their own copies of the
+/// stack and the input tapes. Loops are executed as their conditions dictate.
+///
+/// Paths are executed by the reference interpreter, and independent paths are distributed across
+/// multiple threads.
+pub fn explore_paths(program: &Program, inputs: &ProgramInputs, max_paths: usize) -> PathReport
+{
+    assert!(max_paths > 0, "at least one path must be explored");
+
+    let mut switch_ids = HashMap::new();
+    index_switches(program.root().body(), &mut switch_ids);
+    let num_threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+    // every path is identified by the branches taken at the first Switch blocks it reaches;
+    // the true branch is taken at every Switch block after that
+    let mut pending: Vec<Vec<bool>> = vec![Vec::new()];
+    let mut paths = Vec::new();
+    while !pending.is_empty() && paths.len() < max_paths {
+        let batch_size = usize::min(pending.len(), max_paths - paths.len());
+        let batch: Vec<Vec<bool>> = pending.drain(..batch_size).collect();
+        let results = run_batch(program, inputs, &switch_ids, batch, num_threads);
+
+        // every Switch block at which the true branch was taken by default forks a new path
+        // which takes the false branch instead
+        for (prefix_length, path) in results {
+            for i in prefix_length..path.branches.len() {
+                let mut prefix: Vec<bool> = path.branches[..i].iter().map(|&(_, branch)| branch).collect();
+                prefix.push(false);
+                pending.push(prefix);
+            }
+            paths.push(path);
+        }
+    }
+
+    return PathReport { paths, num_switches: switch_ids.len(), is_complete: pending.is_empty() };
+}
+
+// PATH REPORT IMPLEMENTATION
+// ================================================================================================
+impl PathReport {
+
+    /// Returns all explored paths.
+    pub fn paths(&self) -> &[ExecutionPath] {
+        return &self.paths;
+    }
+
+    /// Returns the number of Switch blocks in the program.
+    pub fn num_switches(&self) -> usize {
+        return self.num_switches;
+    }
+
+    /// Returns true if all paths through the program were explored.
+    pub fn is_complete(&self) -> bool {
+        return self.is_complete;
+    }
+
+    /// Returns [true branch, false branch] flags for every Switch block in the program, where a
+    /// flag is set if the branch was executed on at least one explored path.
+    pub fn coverage(&self) -> Vec<[bool; 2]> {
+        let mut coverage = vec![[false, false]; self.num_switches];
+        for path in self.paths.iter() {
+            for &(switch, branch) in path.branches.iter() {
+                coverage[switch][!branch as usize] = true;
+            }
+        }
+        return coverage;
+    }
+
+    /// Returns the largest number of operations executed on a successful path; when all paths
+    /// were explored, this is the worst-case cost of executing the program.
+    pub fn max_op_count(&self) -> Option<usize> {
+        return self.paths.iter().filter_map(|path| path.result.as_ref().ok()).map(|state| state.op_count).max();
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Executes paths described by every prefix in the `batch`, and returns the results in the same
+/// order as the prefixes together with the lengths of the prefixes.
+fn run_batch(program: &Program, inputs: &ProgramInputs, switch_ids: &HashMap<usize, usize>, batch: Vec<Vec<bool>>, num_threads: usize) -> Vec<(usize, ExecutionPath)>
+{
+    let chunk_size = (batch.len() + num_threads - 1) / num_threads;
+    return thread::scope(|s| {
+        let handles: Vec<_> = batch.chunks(chunk_size).map(|chunk| {
+            s.spawn(move |_| {
+                chunk.iter().map(|prefix| (prefix.len(), run_path(program, inputs, switch_ids, prefix))).collect::<Vec<_>>()
+            })
+        }).collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("path exploration thread panicked")).collect()
+    }).expect("path exploration thread panicked");
+}
+
+fn run_path(program: &Program, inputs: &ProgramInputs, switch_ids: &HashMap<usize, usize>, prefix: &[bool]) -> ExecutionPath
+{
+    let selector = BranchSelector {
+        switch_ids  : switch_ids.clone(),
+        prefix      : prefix.to_vec(),
+        branches    : Vec::new(),
+        feasible    : true,
+        max_steps   : MAX_PATH_STEPS,
+    };
+    let (result, selector) = interpret_path(program, inputs, selector);
+    return ExecutionPath { branches: selector.branches, feasible: selector.feasible, result };
+}
+
+/// Assigns indexes to all Switch blocks in the `blocks` in the order in which they appear in
+/// the program; Switch blocks are identified by their addresses.
+fn index_switches(blocks: &[ProgramBlock], switch_ids: &mut HashMap<usize, usize>) {
+    for block in blocks.iter() {
+        match block {
+            ProgramBlock::Span(_) => (),
+            ProgramBlock::Group(block) => index_switches(block.body(), switch_ids),
+            ProgramBlock::Switch(block) => {
+                let id = switch_ids.len();
+                switch_ids.insert(block as *const Switch as usize, id);
+                index_switches(block.true_branch(), switch_ids);
+                index_switches(block.false_branch(), switch_ids);
+            },
+            ProgramBlock::Loop(block) => index_switches(block.body(), switch_ids),
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use std::collections::BTreeMap;
 use crate::{
     math::field,
-    utils::hasher,
-    ProgramInputs, OpCode, OpHint,
-    HASH_STATE_WIDTH, MIN_STACK_DEPTH, MAX_STACK_DEPTH,
+    crypto::rescue::HASHER,
+    ProgramInputs, TapePolicy, OpCode, OpHint,
+    HASH_STATE_WIDTH, MIN_STACK_DEPTH, MAX_STACK_DEPTH, NUM_DEPTH_BITS,
 };
 
+mod secrets;
+pub use secrets::{ SecretUse };
+
 #[cfg(test)]
 mod tests;
 
@@ -12,11 +16,18 @@ mod tests;
 // ================================================================================================
 pub struct Stack {
     registers   : Vec<Vec<u128>>,
+    depth_trace : Vec<u128>,
+    slack_trace : Vec<u128>,
     tape_a      : Vec<u128>,
     tape_b      : Vec<u128>,
+    tape_policy : TapePolicy,
+    advice      : BTreeMap<u128, Vec<u128>>,
     max_depth   : usize,
     depth       : usize,
     step        : usize,
+    secrets     : Vec<bool>,
+    secret_uses : Vec<SecretUse>,
+    hardened    : bool,
 }
 
 // STACK IMPLEMENTATION
@@ -47,22 +58,67 @@ impl Stack {
         let mut tape_b = secret_inputs_b.clone();
         tape_b.reverse();
 
+        // the stack starts out with public inputs on it
+        let mut depth_trace = vec![field::ZERO; init_trace_length];
+        depth_trace[0] = public_inputs.len() as u128;
+
         return Stack {
             registers,
+            depth_trace,
+            slack_trace: vec![field::ZERO; init_trace_length],
             tape_a,
             tape_b,
+            tape_policy: inputs.get_tape_policy(),
+            advice: inputs.get_advice_map().clone(),
             max_depth: public_inputs.len(),
             depth: public_inputs.len(),
             step: 0,
+            secrets: vec![false; public_inputs.len()],
+            secret_uses: Vec::new(),
+            hardened: inputs.is_hardened_execution(),
         };
     }
 
+    /// Returns a new Stack in the same way as `new()` does, but with the stack initialized to
+    /// `state` (the top of the stack comes first) instead of public inputs; secret input tapes
+    /// and advice are taken from `inputs`, which must not contain public inputs. Unlike public
+    /// inputs, the state can be as deep as MAX_STACK_DEPTH.
+    ///
+    /// This is intended only for unit-testing operations which need a deep stack: traces which
+    /// do not start from public inputs cannot be proven.
+    #[cfg(test)]
+    pub fn from_state(state: &[u128], inputs: &ProgramInputs, init_trace_length: usize) -> Stack {
+        assert!(inputs.get_public_inputs().is_empty(), "inputs for a stack initialized from a state cannot contain public inputs");
+        assert!(state.len() <= MAX_STACK_DEPTH, "stack overflow at step 0");
+
+        let mut stack = Stack::new(inputs, init_trace_length);
+        let init_stack_depth = std::cmp::max(state.len(), MIN_STACK_DEPTH);
+        stack.add_registers(init_stack_depth - stack.registers.len());
+        for (register, &value) in stack.registers.iter_mut().zip(state.iter()) {
+            register[0] = value;
+        }
+
+        stack.depth_trace[0] = state.len() as u128;
+        stack.max_depth = state.len();
+        stack.depth = state.len();
+        stack.secrets = vec![false; state.len()];
+        return stack;
+    }
+
     /// Executes `opcode` against the current state of the stack.
     pub fn execute(&mut self, op_code: OpCode, op_hint: OpHint) {
 
         // increment step pointer and make sure there is enough memory allocated to hold the trace
         self.advance_step();
 
+        // keep track of stack items derived from secret inputs
+        self.track_secrets(op_code);
+
+        // record by how many items the depth of the stack exceeds the depth required by the
+        // operation; if there are too few items, the operation itself will fail below
+        let slack = self.depth.saturating_sub(op_code.required_depth());
+        self.slack_trace[self.step - 1] = slack as u128;
+
         // execute the appropriate action against the current state of the stack
         match op_code {
 
@@ -71,6 +127,7 @@ impl Stack {
 
             OpCode::Assert      => self.op_assert(),
             OpCode::AssertEq    => self.op_asserteq(),
+            OpCode::AssertBin   => self.op_assertbin(),
 
             OpCode::Push        => self.op_push(op_hint),
             OpCode::Read        => self.op_read(op_hint),
@@ -79,14 +136,17 @@ impl Stack {
             OpCode::Dup         => self.op_dup(),
             OpCode::Dup2        => self.op_dup2(),
             OpCode::Dup4        => self.op_dup4(),
+            OpCode::Dup8        => self.op_dup8(),
             OpCode::Pad2        => self.op_pad2(),
 
             OpCode::Drop        => self.op_drop(),
             OpCode::Drop4       => self.op_drop4(),
+            OpCode::Drop8       => self.op_drop8(),
 
             OpCode::Swap        => self.op_swap(),
             OpCode::Swap2       => self.op_swap2(),
             OpCode::Swap4       => self.op_swap4(),
+            OpCode::Swap8       => self.op_swap8(),
 
             OpCode::Roll4       => self.op_roll4(),
             OpCode::Roll8       => self.op_roll8(),
@@ -109,6 +169,8 @@ impl Stack {
 
             OpCode::RescR       => self.op_rescr(),
         }
+
+        self.depth_trace[self.step] = self.depth as u128;
     }
 
     /// Returns trace length of register traces in the decoder.
@@ -117,7 +179,6 @@ impl Stack {
     }
 
     /// Returns value of the current step pointer.
-    #[cfg(test)]
     pub fn current_step(&self) -> usize {
         return self.step;
     }
@@ -127,6 +188,27 @@ impl Stack {
         return self.registers[0][self.step];
     }
 
+    /// Returns the top 8 values of the stack at the current step.
+    pub fn get_stack_top8(&self) -> [u128; MIN_STACK_DEPTH] {
+        let mut result = [field::ZERO; MIN_STACK_DEPTH];
+        for (i, register) in self.registers.iter().take(MIN_STACK_DEPTH).enumerate() {
+            result[i] = register[self.step];
+        }
+        return result;
+    }
+
+    /// Returns the number of values left unconsumed on secret input tapes A and B.
+    pub fn unconsumed_inputs(&self) -> [usize; 2] {
+        return [self.tape_a.len(), self.tape_b.len()];
+    }
+
+    /// Puts the specified values at the front of input tapes A and B such that they are read
+    /// before any other values on the tapes, and in the order in which they are provided.
+    pub fn prepend_tapes(&mut self, values_a: Vec<u128>, values_b: Vec<u128>) {
+        self.tape_a.extend(values_a.into_iter().rev());
+        self.tape_b.extend(values_b.into_iter().rev());
+    }
+
     /// Populate all register traces with values for steps between the current step
     /// and the end of the trace.
     pub fn finalize_trace(&mut self) {
@@ -136,14 +218,36 @@ impl Stack {
             register.resize(trace_length, register[self.step]);
         }
 
+        // the remaining steps are filled with NOOPs which require no items on the stack
+        self.depth_trace.resize(self.step + 1, field::ZERO);
+        self.depth_trace.resize(trace_length, self.depth as u128);
+        self.slack_trace.resize(self.step, field::ZERO);
+        self.slack_trace.resize(trace_length, self.depth as u128);
+
         // update the step pointer to point to the last step
         self.step = self.trace_length() - 1;
     }
 
-    /// Merges all register traces into a single vector of traces.
+    /// Merges all register traces into a single vector of traces. The depth register and its
+    /// range-check bits are placed in front of the user stack registers.
     pub fn into_register_traces(mut self) -> Vec<Vec<u128>> {
         self.registers.truncate(self.max_depth);
-        return self.registers;
+
+        // decompose depth slack into bits to prove that the stack never underflows; and decompose
+        // the difference between the number of user stack registers and the depth of the stack
+        // into bits to prove that the stack never overflows
+        let headroom = self.depth_trace.iter()
+            .map(|&depth| self.max_depth as u128 - depth)
+            .collect::<Vec<u128>>();
+        let underflow_bits = decompose_into_bits(&self.slack_trace);
+        let overflow_bits = decompose_into_bits(&headroom);
+
+        let mut result = Vec::with_capacity(1 + 2 * NUM_DEPTH_BITS + self.registers.len());
+        result.push(self.depth_trace);
+        result.extend(underflow_bits);
+        result.extend(overflow_bits);
+        result.append(&mut self.registers);
+        return result;
     }
 
     // FLOW CONTROL OPERATIONS
@@ -167,6 +271,13 @@ impl Stack {
         self.shift_left(2, 2);
     }
 
+    fn op_assertbin(&mut self) {
+        assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+        let x = self.registers[0][self.step - 1];
+        assert!(is_binary(x), "ASSERTBIN failed at step {}", self.step);
+        self.copy_state(0);
+    }
+
     // INPUT OPERATIONS
     // --------------------------------------------------------------------------------------------
     fn op_push(&mut self, hint: OpHint) {
@@ -195,14 +306,36 @@ impl Stack {
                     self.tape_a.push(field::inv(field::sub(x, y)));
                 }
             },
-            OpHint::None => {
-                assert!(self.tape_a.len() > 0, "attempt to read from empty tape A at step {}", self.step);
+            OpHint::DivModStart => {
+                // if we are about to start integer division, push the quotient and the remainder
+                // of dividing the second stack value by the top stack value onto secret tape A;
+                // the remainder is pushed last so that it is read first
+                assert!(self.depth >= 2, "stack underflow at step {}", self.step);
+                let b = self.registers[0][self.step - 1];
+                let a = self.registers[1][self.step - 1];
+                assert!(b != field::ZERO, "cannot perform DIVMOD by {} at step {}", field::ZERO, self.step);
+                self.tape_a.push(a / b);
+                self.tape_a.push(a % b);
             },
+            OpHint::AdviceKey(n) => {
+                // if we are about to read values from the advice map, put the values associated
+                // with the key at the top of the stack onto secret tape A
+                assert!(self.depth >= 1, "stack underflow at step {}", self.step);
+                let key = self.registers[0][self.step - 1];
+                let values = match self.advice.get(&key) {
+                    Some(values) => values,
+                    None => panic!("no advice values for key {} at step {}", key, self.step),
+                };
+                assert!(values.len() == n as usize, "expected {} advice values for key {}, but found {}",
+                    n, key, values.len());
+                self.tape_a.extend(values.iter().rev());
+            },
+            OpHint::None | OpHint::Custom(..) => (),
             _ => panic!("execution hint {:?} is not valid for READ operation", hint)
         }
 
         self.shift_right(0, 1);
-        let value = self.tape_a.pop().unwrap();
+        let value = self.read_tape_a();
         self.registers[0][self.step] = value;
     }
 
@@ -230,16 +363,13 @@ impl Stack {
                     self.tape_a.push(v_a[i]);
                 }
             },
-            OpHint::None => {
-                assert!(self.tape_a.len() > 0, "attempt to read from empty tape A at step {}", self.step);
-                assert!(self.tape_b.len() > 0, "attempt to read from empty tape B at step {}", self.step);
-            },
+            OpHint::None | OpHint::Custom(..) => (),
             _ => panic!("execution hint {:?} is not valid for READ2 operation", hint)
         }
 
         self.shift_right(0, 2);
-        let value_a = self.tape_a.pop().unwrap();
-        let value_b = self.tape_b.pop().unwrap();
+        let value_a = self.read_tape_a();
+        let value_b = self.read_tape_b();
         self.registers[0][self.step] = value_b;
         self.registers[1][self.step] = value_a;
     }
@@ -268,6 +398,14 @@ impl Stack {
         self.registers[3][self.step] = self.registers[3][self.step - 1];
     }
 
+    fn op_dup8(&mut self) {
+        assert!(self.depth >= 8, "stack underflow at step {}", self.step);
+        self.shift_right(0, 8);
+        for i in 0..8 {
+            self.registers[i][self.step] = self.registers[i][self.step - 1];
+        }
+    }
+
     fn op_pad2(&mut self) {
         self.shift_right(0, 2);
         self.registers[0][self.step] = field::ZERO;
@@ -284,6 +422,11 @@ impl Stack {
         self.shift_left(4, 4);
     }
 
+    fn op_drop8(&mut self) {
+        assert!(self.depth >= 8, "stack underflow at step {}", self.step);
+        self.shift_left(8, 8);
+    }
+
     fn op_swap(&mut self) {
         assert!(self.depth >= 2, "stack underflow at step {}", self.step);
         self.registers[0][self.step] = self.registers[1][self.step - 1];
@@ -313,6 +456,15 @@ impl Stack {
         self.copy_state(8);
     }
 
+    fn op_swap8(&mut self) {
+        assert!(self.depth >= 16, "stack underflow at step {}", self.step);
+        for i in 0..8 {
+            self.registers[i][self.step] = self.registers[i + 8][self.step - 1];
+            self.registers[i + 8][self.step] = self.registers[i][self.step - 1];
+        }
+        self.copy_state(16);
+    }
+
     fn op_roll4(&mut self) {
         assert!(self.depth >= 4, "stack underflow at step {}", self.step);
         self.registers[0][self.step] = self.registers[3][self.step - 1];
@@ -340,53 +492,38 @@ impl Stack {
     fn op_choose(&mut self) {
         assert!(self.depth >= 3, "stack underflow at step {}", self.step);
         let condition = self.registers[2][self.step - 1];
-        if condition == field::ONE {
-            self.registers[0][self.step] = self.registers[0][self.step - 1];
-        }
-        else if condition == field::ZERO {
-            self.registers[0][self.step] = self.registers[1][self.step - 1];
-        }
-        else {
-            assert!(false, "CHOOSE on a non-binary condition at step {}", self.step);
-        }
+        assert!(is_binary(condition), "CHOOSE on a non-binary condition at step {}", self.step);
+        let x = self.registers[0][self.step - 1];
+        let y = self.registers[1][self.step - 1];
+        self.registers[0][self.step] = self.select(condition, x, y);
         self.shift_left(3, 2);
     }
 
     fn op_choose2(&mut self) {
         assert!(self.depth >= 6, "stack underflow at step {}", self.step);
         let condition = self.registers[4][self.step - 1];
-        if condition == field::ONE {
-            self.registers[0][self.step] = self.registers[0][self.step - 1];
-            self.registers[1][self.step] = self.registers[1][self.step - 1];
-        }
-        else if condition == field::ZERO {
-            self.registers[0][self.step] = self.registers[2][self.step - 1];
-            self.registers[1][self.step] = self.registers[3][self.step - 1];
-        }
-        else {
-            assert!(false, "CHOOSE2 on a non-binary condition at step {}", self.step);
-        }
+        assert!(is_binary(condition), "CHOOSE2 on a non-binary condition at step {}", self.step);
+        let x0 = self.registers[0][self.step - 1];
+        let x1 = self.registers[1][self.step - 1];
+        let y0 = self.registers[2][self.step - 1];
+        let y1 = self.registers[3][self.step - 1];
+        self.registers[0][self.step] = self.select(condition, x0, y0);
+        self.registers[1][self.step] = self.select(condition, x1, y1);
         self.shift_left(6, 4);
     }
 
     fn op_cswap2(&mut self) {
         assert!(self.depth >= 6, "stack underflow at step {}", self.step);
         let condition = self.registers[4][self.step - 1];
-        if condition == field::ZERO {
-            self.registers[0][self.step] = self.registers[0][self.step - 1];
-            self.registers[1][self.step] = self.registers[1][self.step - 1];
-            self.registers[2][self.step] = self.registers[2][self.step - 1];
-            self.registers[3][self.step] = self.registers[3][self.step - 1];
-        }
-        else if condition == field::ONE {
-            self.registers[0][self.step] = self.registers[2][self.step - 1];
-            self.registers[1][self.step] = self.registers[3][self.step - 1];
-            self.registers[2][self.step] = self.registers[0][self.step - 1];
-            self.registers[3][self.step] = self.registers[1][self.step - 1];
-        }
-        else {
-            assert!(false, "CSWAP2 on a non-binary condition at step {}", self.step);
-        }
+        assert!(is_binary(condition), "CSWAP2 on a non-binary condition at step {}", self.step);
+        let x0 = self.registers[0][self.step - 1];
+        let x1 = self.registers[1][self.step - 1];
+        let y0 = self.registers[2][self.step - 1];
+        let y1 = self.registers[3][self.step - 1];
+        self.registers[0][self.step] = self.select(condition, y0, x0);
+        self.registers[1][self.step] = self.select(condition, y1, x1);
+        self.registers[2][self.step] = self.select(condition, x0, y0);
+        self.registers[3][self.step] = self.select(condition, x1, y1);
         self.shift_left(6, 2);
     }
 
@@ -412,7 +549,7 @@ impl Stack {
         assert!(self.depth >= 1, "stack underflow at step {}", self.step);
         let x = self.registers[0][self.step - 1];
         assert!(x != field::ZERO, "cannot compute INV of {} at step {}", field::ZERO, self.step);
-        self.registers[0][self.step] = field::inv(x);
+        self.registers[0][self.step] = self.inv(x);
         self.copy_state(1);
     }
 
@@ -438,7 +575,7 @@ impl Stack {
         assert!(is_binary(x), "cannot compute AND for a non-binary value at step {}", self.step);
         assert!(is_binary(y), "cannot compute AND for a non-binary value at step {}", self.step);
 
-        self.registers[0][self.step] = if x == field::ONE && y == field::ONE { field::ONE } else { field::ZERO };
+        self.registers[0][self.step] = field::mul(x, y);
         self.shift_left(2, 1);
     }
 
@@ -449,7 +586,7 @@ impl Stack {
         assert!(is_binary(x), "cannot compute OR for a non-binary value at step {}", self.step);
         assert!(is_binary(y), "cannot compute OR for a non-binary value at step {}", self.step);
 
-        self.registers[0][self.step] = if x == field::ONE || y == field::ONE { field::ONE } else { field::ZERO };
+        self.registers[0][self.step] = field::sub(field::add(x, y), field::mul(x, y));
         self.shift_left(2, 1);
     }
 
@@ -461,7 +598,16 @@ impl Stack {
         let aux = self.registers[0][self.step - 1];
         let x = self.registers[1][self.step - 1];
         let y = self.registers[2][self.step - 1];
-        if x == y {
+        if self.hardened {
+            // the inverse of 0 is 0, and so, AUX value can be checked without branching on
+            // whether the values are equal
+            let diff = field::sub(x, y);
+            let is_equal = (diff == field::ZERO) as u128;
+            let is_valid = (aux == inv_fixed(diff)) as u128 | is_equal;
+            assert!(is_valid == 1, "invalid AUX value for EQ operation at step {}", self.step);
+            self.registers[0][self.step] = is_equal;
+        }
+        else if x == y {
             self.registers[0][self.step] = field::ONE;
         } else {
             let diff = field::sub(x, y);
@@ -487,17 +633,15 @@ impl Stack {
             },
             OpHint::None => {
                 assert!(self.depth >= 8, "stack underflow at step {}", self.step);
-                assert!(self.tape_a.len() > 0, "attempt to read from empty tape A at step {}", self.step);
-                assert!(self.tape_b.len() > 0, "attempt to read from empty tape B at step {}", self.step);
             },
             _ => panic!("execution hint {:?} is not valid for CMP operation", hint)
         }
 
         // get next bits of a and b values from the tapes
-        let a_bit = self.tape_a.pop().unwrap();
+        let a_bit = self.read_tape_a();
         assert!(a_bit == field::ZERO || a_bit == field::ONE,
             "expected binary input at step {} but received: {}", self.step, a_bit);
-        let b_bit = self.tape_b.pop().unwrap();
+        let b_bit = self.read_tape_b();
         assert!(b_bit == field::ZERO || b_bit == field::ONE,
             "expected binary input at step {} but received: {}", self.step, b_bit);
 
@@ -549,13 +693,12 @@ impl Stack {
             },
             OpHint::None => {
                 assert!(self.depth >= 4, "stack underflow at step {}", self.step);
-                assert!(self.tape_a.len() > 0, "attempt to read from empty tape A at step {}", self.step);
             },
             _ => panic!("execution hint {:?} is not valid for BINACC operation", hint)
         }
 
         // get the next bit of the value from tape A
-        let bit = self.tape_a.pop().unwrap();
+        let bit = self.read_tape_a();
         assert!(bit == field::ZERO || bit == field::ONE,
             "expected binary input at step {} but received: {}", self.step, bit);
 
@@ -590,7 +733,7 @@ impl Stack {
             self.registers[5][self.step - 1],
         ];
 
-        hasher::apply_round(&mut state, self.step - 1);
+        HASHER.apply_round(&mut state, self.step - 1);
 
         self.registers[0][self.step] = state[0];
         self.registers[1][self.step] = state[1];
@@ -605,6 +748,48 @@ impl Stack {
     // HELPER METHODS
     // --------------------------------------------------------------------------------------------
 
+    /// Removes the next value from tape A and returns it; if the tape is empty, the result
+    /// depends on the tape policy.
+    fn read_tape_a(&mut self) -> u128 {
+        return match self.tape_a.pop() {
+            Some(value) => value,
+            None => match self.tape_policy {
+                TapePolicy::ZeroFill => field::ZERO,
+                TapePolicy::Error => panic!("attempt to read from empty tape A at step {}", self.step),
+            }
+        };
+    }
+
+    /// Removes the next value from tape B and returns it; if the tape is empty, the result
+    /// depends on the tape policy.
+    fn read_tape_b(&mut self) -> u128 {
+        return match self.tape_b.pop() {
+            Some(value) => value,
+            None => match self.tape_policy {
+                TapePolicy::ZeroFill => field::ZERO,
+                TapePolicy::Error => panic!("attempt to read from empty tape B at step {}", self.step),
+            }
+        };
+    }
+
+    /// Returns `x` if `condition` is 1, and `y` if `condition` is 0; in hardened mode, the value
+    /// is selected without branching on the condition.
+    fn select(&self, condition: u128, x: u128, y: u128) -> u128 {
+        if self.hardened {
+            return select(condition, x, y);
+        }
+        return if condition == field::ONE { x } else { y };
+    }
+
+    /// Computes multiplicative inverse of `x`; in hardened mode, the running time of the
+    /// computation does not depend on `x`.
+    fn inv(&self, x: u128) -> u128 {
+        if self.hardened {
+            return inv_fixed(x);
+        }
+        return field::inv(x);
+    }
+
     fn copy_state(&mut self, start: usize) {
         for i in start..self.depth {
             self.registers[i][self.step] = self.registers[i][self.step - 1];
@@ -660,6 +845,8 @@ impl Stack {
         if self.step >= self.trace_length() {
             let new_length = self.trace_length() * 2;
             for register in self.registers.iter_mut() { register.resize(new_length, field::ZERO); }
+            self.depth_trace.resize(new_length, field::ZERO);
+            self.slack_trace.resize(new_length, field::ZERO);
         }
     }
 }
@@ -668,4 +855,35 @@ impl Stack {
 // ================================================================================================
 fn is_binary(value: u128) -> bool {
     return value == field::ZERO || value == field::ONE;
+}
+
+/// Returns `NUM_DEPTH_BITS` register traces such that the i-th trace contains the i-th bit
+/// of the corresponding value in `values`.
+fn decompose_into_bits(values: &[u128]) -> Vec<Vec<u128>> {
+    let mut result = Vec::with_capacity(NUM_DEPTH_BITS);
+    for i in 0..NUM_DEPTH_BITS {
+        result.push(values.iter().map(|&value| (value >> i) & 1).collect());
+    }
+    return result;
+}
+
+/// Returns `x` if `condition` is 1, and `y` if `condition` is 0; the selection is done via
+/// field arithmetic to avoid branching on the value of the condition.
+fn select(condition: u128, x: u128, y: u128) -> u128 {
+    return field::add(y, field::mul(condition, field::sub(x, y)));
+}
+
+/// Computes multiplicative inverse of `x` by raising it to the power of `MODULUS - 2`; unlike
+/// `field::inv()`, the number of iterations depends only on the modulus, and not on `x`. The
+/// inverse of 0 is 0.
+fn inv_fixed(x: u128) -> u128 {
+    let mut result = field::ONE;
+    let mut base = x;
+    let mut power = field::MODULUS - 2;
+    for _ in 0..128 {
+        result = select(power & 1, field::mul(result, base), result);
+        base = field::mul(base, base);
+        power = power >> 1;
+    }
+    return result;
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+use super::{ Stack, OpCode };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Describes a place in program execution where a value derived from secret inputs was used.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SecretUse {
+    /// An operation executed at the specified step consumed a value derived from secret inputs.
+    Op(usize, OpCode),
+    /// A branch was selected at the specified step based on a value derived from secret inputs.
+    Branch(usize),
+    /// A loop was entered, repeated, or exited at the specified step based on a value derived
+    /// from secret inputs.
+    Loop(usize),
+}
+
+// SECRET INPUT TRACKING
+// ================================================================================================
+impl Stack {
+
+    /// Returns true if the value currently at the top of the stack was derived from secret inputs.
+    pub fn is_top_secret(&self) -> bool {
+        return self.secrets.last().copied().unwrap_or(false);
+    }
+
+    /// Records a use of a secret value by the flow control logic if the value at the top of the
+    /// stack, which decides the branch or the loop, was derived from secret inputs. In hardened
+    /// mode, execution fails instead: the branch taken and the number of loop iterations are
+    /// revealed by the length of the execution.
+    pub fn check_condition_secrecy(&mut self, secret_use: SecretUse) {
+        if !self.is_top_secret() { return; }
+        if self.hardened {
+            let action = match secret_use {
+                SecretUse::Branch(_) => "select a branch",
+                _ => "decide whether to execute a loop",
+            };
+            panic!("hardened execution cannot {} based on a secret value at step {}", action, self.step);
+        }
+        self.secret_uses.push(secret_use);
+    }
+
+    /// Returns a list of all places in the execution where values derived from secret inputs
+    /// were used.
+    pub fn secret_uses(&self) -> &[SecretUse] {
+        return &self.secret_uses;
+    }
+
+    /// Updates secret markers of stack items to reflect execution of `op_code`; this must be
+    /// called before the operation is applied to the stack. Markers are propagated conservatively:
+    /// if any of the operands of an operation is secret, all of its results are marked as secret.
+    /// Only operations which compute on secret values are recorded as secret uses; operations
+    /// which merely move values around the stack (e.g. `Swap` or `Dup`) are not.
+    pub(super) fn track_secrets(&mut self, op_code: OpCode) {
+        match op_code {
+            OpCode::Begin | OpCode::Noop => (),
+
+            OpCode::Assert      => self.mix_secrets(op_code, 1, 0, false),
+            OpCode::AssertEq    => self.mix_secrets(op_code, 2, 0, false),
+            OpCode::AssertBin   => self.mix_secrets(op_code, 1, 1, false),
+
+            OpCode::Push        => self.secrets.push(false),
+            OpCode::Read        => self.secrets.push(true),
+            OpCode::Read2       => { self.secrets.push(true); self.secrets.push(true); },
+
+            OpCode::Dup         => self.dup_secrets(1),
+            OpCode::Dup2        => self.dup_secrets(2),
+            OpCode::Dup4        => self.dup_secrets(4),
+            OpCode::Dup8        => self.dup_secrets(8),
+            OpCode::Pad2        => { self.secrets.push(false); self.secrets.push(false); },
+
+            OpCode::Drop        => self.drop_secrets(1),
+            OpCode::Drop4       => self.drop_secrets(4),
+            OpCode::Drop8       => self.drop_secrets(8),
+
+            OpCode::Swap        => self.permute_secrets(&[1, 0]),
+            OpCode::Swap2       => self.permute_secrets(&[2, 3, 0, 1]),
+            OpCode::Swap4       => self.permute_secrets(&[4, 5, 6, 7, 0, 1, 2, 3]),
+            OpCode::Swap8       => self.permute_secrets(&[8, 9, 10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 6, 7]),
+            OpCode::Roll4       => self.permute_secrets(&[3, 0, 1, 2]),
+            OpCode::Roll8       => self.permute_secrets(&[7, 0, 1, 2, 3, 4, 5, 6]),
+
+            OpCode::Choose      => self.mix_secrets(op_code, 3, 1, false),
+            OpCode::Choose2     => self.mix_secrets(op_code, 6, 2, false),
+            OpCode::CSwap2      => self.mix_secrets(op_code, 6, 4, false),
+
+            OpCode::Add         => self.mix_secrets(op_code, 2, 1, false),
+            OpCode::Mul         => self.mix_secrets(op_code, 2, 1, false),
+            OpCode::Inv         => self.mix_secrets(op_code, 1, 1, false),
+            OpCode::Neg         => self.mix_secrets(op_code, 1, 1, false),
+            OpCode::Not         => self.mix_secrets(op_code, 1, 1, false),
+            OpCode::And         => self.mix_secrets(op_code, 2, 1, false),
+            OpCode::Or          => self.mix_secrets(op_code, 2, 1, false),
+
+            OpCode::Eq          => self.mix_secrets(op_code, 3, 1, false),
+            // CMP and BINACC read values from secret tapes
+            OpCode::Cmp         => self.mix_secrets(op_code, 8, 8, true),
+            OpCode::BinAcc      => self.mix_secrets(op_code, 4, 4, true),
+
+            OpCode::RescR       => self.mix_secrets(op_code, 6, 6, false),
+        }
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Replaces top `num_inputs` markers with `num_outputs` markers which are secret if any of
+    /// the inputs are secret or if the operation reads from secret tapes.
+    fn mix_secrets(&mut self, op_code: OpCode, num_inputs: usize, num_outputs: usize, reads_tape: bool) {
+        let start = self.secrets.len().saturating_sub(num_inputs);
+        let uses_secret = self.secrets.drain(start..).any(|s| s);
+        if uses_secret || reads_tape {
+            self.secret_uses.push(SecretUse::Op(self.step, op_code));
+        }
+        for _ in 0..num_outputs {
+            self.secrets.push(uses_secret || reads_tape);
+        }
+    }
+
+    fn dup_secrets(&mut self, count: usize) {
+        let start = self.secrets.len().saturating_sub(count);
+        let copy = self.secrets[start..].to_vec();
+        self.secrets.extend(copy);
+    }
+
+    fn drop_secrets(&mut self, count: usize) {
+        let length = self.secrets.len().saturating_sub(count);
+        self.secrets.truncate(length);
+    }
+
+    /// Rearranges top markers such that item at position `i` (from the top of the stack) comes
+    /// from position `sources[i]`.
+    fn permute_secrets(&mut self, sources: &[usize]) {
+        let length = self.secrets.len();
+        if length < sources.len() { return; }
+
+        let top: Vec<bool> = (0..sources.len()).map(|i| self.secrets[length - 1 - i]).collect();
+        for (i, &source) in sources.iter().enumerate() {
+            self.secrets[length - 1 - i] = top[source];
+        }
+    }
+}
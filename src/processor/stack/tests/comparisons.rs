@@ -1,5 +1,6 @@
 use crate::math::{ field };
 use super::{ init_stack, get_stack_state, OpCode, OpHint, TRACE_LENGTH };
+#[cfg(feature = "std-rand")]
 use super::super::Stack;
 
 // EQUALITY OPERATION
@@ -47,6 +48,7 @@ fn eq_with_hint() {
 // COMPARISON OPERATION
 // ================================================================================================
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn cmp_128() {
 
@@ -81,6 +83,7 @@ fn cmp_128() {
     assert_eq!([gt, lt, b, a], state[4..8]);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn cmp_64() {
 
@@ -118,6 +121,7 @@ fn cmp_64() {
 // COMPARISON PROGRAMS
 // ================================================================================================
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn lt() {
 
@@ -144,6 +148,7 @@ fn lt() {
     assert_eq!(vec![expected, 7, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0], state);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn gt() {
 
@@ -173,6 +178,7 @@ fn gt() {
 // BINARY DECOMPOSITION
 // ================================================================================================
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn binacc_128() {
 
@@ -200,6 +206,7 @@ fn binacc_128() {
     assert_eq!(vec![x, x, 7, 11, 0, 0, 0, 0], state);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn binacc_64() {
 
@@ -227,6 +234,7 @@ fn binacc_64() {
     assert_eq!(vec![x, x, 7, 11, 0, 0, 0, 0], state);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn isodd_128() {
 
@@ -266,6 +274,7 @@ fn isodd_128() {
 
 // HELPER FUNCTIONS
 // ================================================================================================
+#[cfg(feature = "std-rand")]
 fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>) {
 
     let mut inputs_a = Vec::new();
@@ -280,6 +289,7 @@ fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>)
     return (inputs_a, inputs_b);
 }
 
+#[cfg(feature = "std-rand")]
 fn lt_finale(stack: &mut Stack) {
     stack.execute(OpCode::Drop4, OpHint::None);
     stack.execute(OpCode::Pad2, OpHint::None);
@@ -291,6 +301,7 @@ fn lt_finale(stack: &mut Stack) {
     stack.execute(OpCode::Drop4, OpHint::None);
 }
 
+#[cfg(feature = "std-rand")]
 fn gt_finale(stack: &mut Stack) {
     stack.execute(OpCode::Drop4, OpHint::None);
     stack.execute(OpCode::Pad2, OpHint::None);
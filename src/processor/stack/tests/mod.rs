@@ -1,5 +1,5 @@
 use crate::math::{ field };
-use crate::utils::{ hasher };
+use crate::crypto::rescue::HASHER;
 use super::{ Stack, super::ProgramInputs, OpHint, OpCode };
 use crate::{ HASH_STATE_WIDTH };
 
@@ -8,6 +8,30 @@ mod conditional;
 
 const TRACE_LENGTH: usize = 16;
 
+// INITIALIZATION
+// ================================================================================================
+
+#[test]
+fn from_state() {
+    let state: Vec<u128> = (1..=12).collect();
+    let inputs = ProgramInputs::new(&[], &[13], &[]);
+    let mut stack = Stack::from_state(&state, &inputs, TRACE_LENGTH);
+    assert_eq!(state, get_stack_state(&stack, 0));
+    assert_eq!(12, stack.depth);
+    assert_eq!(12, stack.max_depth);
+
+    // secret inputs are read from the tapes as usual
+    stack.execute(OpCode::Read, OpHint::None);
+    assert_eq!(13, get_stack_state(&stack, 1)[0]);
+    assert_eq!(state, get_stack_state(&stack, 1)[1..]);
+    assert_eq!(13, stack.depth);
+
+    // shallow states are padded with zeros to the minimum number of registers
+    let stack = Stack::from_state(&[1, 2], &ProgramInputs::none(), TRACE_LENGTH);
+    assert_eq!(vec![1, 2, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 0));
+    assert_eq!(2, stack.depth);
+}
+
 // FLOW CONTROL OPERATIONS
 // ================================================================================================
 
@@ -55,6 +79,27 @@ fn asserteq_fail() {
     stack.execute(OpCode::AssertEq, OpHint::None);
 }
 
+#[test]
+fn assertbin() {
+    let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::AssertBin, OpHint::None);
+    assert_eq!(vec![1, 2, 3, 4, 0, 0, 0, 0], get_stack_state(&stack, 1));
+
+    stack.execute(OpCode::Not, OpHint::None);
+    stack.execute(OpCode::AssertBin, OpHint::None);
+    assert_eq!(vec![0, 2, 3, 4, 0, 0, 0, 0], get_stack_state(&stack, 3));
+
+    assert_eq!(4, stack.depth);
+    assert_eq!(4, stack.max_depth);
+}
+
+#[test]
+#[should_panic(expected = "ASSERTBIN failed at step 1")]
+fn assertbin_fail() {
+    let mut stack = init_stack(&[2, 1, 0], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::AssertBin, OpHint::None);
+}
+
 // INPUT OPERATIONS
 // ================================================================================================
 
@@ -85,6 +130,25 @@ fn read() {
     assert_eq!(3, stack.max_depth);
 }
 
+#[test]
+fn read_with_divmod_hint() {
+    let mut stack = init_stack(&[5, 17, 1], &[], &[], TRACE_LENGTH);
+
+    stack.execute(OpCode::Read, OpHint::DivModStart);
+    stack.execute(OpCode::Read, OpHint::None);
+    assert_eq!(vec![3, 2, 5, 17, 1, 0, 0, 0], get_stack_state(&stack, 2));
+
+    assert_eq!(5, stack.depth);
+    assert_eq!(5, stack.max_depth);
+}
+
+#[test]
+#[should_panic(expected = "cannot perform DIVMOD by 0 at step 1")]
+fn read_with_divmod_hint_fail() {
+    let mut stack = init_stack(&[0, 17], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::Read, OpHint::DivModStart);
+}
+
 #[test]
 fn read2() {
     let mut stack = init_stack(&[1], &[2, 4], &[3, 5], TRACE_LENGTH);
@@ -135,6 +199,16 @@ fn dup4() {
     assert_eq!(8, stack.max_depth);
 }
 
+#[test]
+fn dup8() {
+    let mut stack = init_stack(&[1, 2, 3, 4, 5, 6, 7, 8], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::Dup8, OpHint::None);
+    assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3, 4, 5, 6, 7, 8], get_stack_state(&stack, 1));
+
+    assert_eq!(16, stack.depth);
+    assert_eq!(16, stack.max_depth);
+}
+
 #[test]
 fn pad2() {
     let mut stack = init_stack(&[1, 2], &[], &[], TRACE_LENGTH);
@@ -165,6 +239,17 @@ fn drop4() {
     assert_eq!(5, stack.max_depth);
 }
 
+#[test]
+fn drop8() {
+    let mut stack = init_stack(&[1, 2, 3, 4, 5, 6, 7, 8], &[], &[], TRACE_LENGTH);
+    stack.execute(OpCode::Dup8, OpHint::None);
+    stack.execute(OpCode::Drop8, OpHint::None);
+    assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 0, 0, 0, 0], get_stack_state(&stack, 2));
+
+    assert_eq!(8, stack.depth);
+    assert_eq!(16, stack.max_depth);
+}
+
 #[test]
 fn swap() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
@@ -195,6 +280,25 @@ fn swap4() {
     assert_eq!(8, stack.max_depth);
 }
 
+#[test]
+fn swap8() {
+    let state: Vec<u128> = (1..=17).collect();
+    let mut stack = Stack::from_state(&state, &ProgramInputs::none(), TRACE_LENGTH);
+    stack.execute(OpCode::Swap8, OpHint::None);
+    assert_eq!(vec![9, 10, 11, 12, 13, 14, 15, 16, 1, 2, 3, 4, 5, 6, 7, 8, 17], get_stack_state(&stack, 1));
+
+    assert_eq!(17, stack.depth);
+    assert_eq!(17, stack.max_depth);
+}
+
+#[test]
+#[should_panic(expected = "stack underflow at step 1")]
+fn swap8_underflow() {
+    let state: Vec<u128> = (1..=15).collect();
+    let mut stack = Stack::from_state(&state, &ProgramInputs::none(), TRACE_LENGTH);
+    stack.execute(OpCode::Swap8, OpHint::None);
+}
+
 #[test]
 fn roll4() {
     let mut stack = init_stack(&[1, 2, 3, 4], &[], &[], TRACE_LENGTH);
@@ -343,11 +447,11 @@ fn rescr() {
     let mut expected = vec![0, 0, 1, 2, 3, 4, 0, 0];
 
     stack.execute(OpCode::RescR, OpHint::None);
-    hasher::apply_round(&mut expected[..HASH_STATE_WIDTH], 0);
+    HASHER.apply_round(&mut expected[..HASH_STATE_WIDTH], 0);
     assert_eq!(expected, get_stack_state(&stack, 1));
 
     stack.execute(OpCode::RescR, OpHint::None);
-    hasher::apply_round(&mut expected[..HASH_STATE_WIDTH], 1);
+    HASHER.apply_round(&mut expected[..HASH_STATE_WIDTH], 1);
     assert_eq!(expected, get_stack_state(&stack, 2));
 
     assert_eq!(6, stack.depth);
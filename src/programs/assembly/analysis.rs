@@ -0,0 +1,120 @@
+use crate::math::field;
+use super::{ ProgramBlock, Span, OpCode };
+
+// CONDITION ANALYSIS
+// ================================================================================================
+
+/// Returns the value which will be at the top of the stack after the last block in `body` is
+/// executed, if this value can be determined without knowing the state of the stack before the
+/// block is executed. Only Span blocks are analyzed; for all other blocks None is returned.
+pub fn get_stack_top(body: &[ProgramBlock]) -> Option<u128> {
+    return match body.last() {
+        Some(ProgramBlock::Span(span)) => execute_span(span),
+        _ => None,
+    };
+}
+
+/// Performs constant propagation over the operations of the `span`, and returns the value at
+/// the top of the stack after the last operation, if it is known.
+fn execute_span(span: &Span) -> Option<u128> {
+
+    // the stack is modeled as a list of known (Some) and unknown (None) values with the top of
+    // the stack at index 0; the stack is assumed to be unknown before the span is executed
+    let mut stack: Vec<Option<u128>> = Vec::new();
+
+    for i in 0..span.length() {
+        let (op_code, op_hint) = span.get_op(i);
+        match op_code {
+            // ASSERTBIN leaves the stack unchanged, and if it fails, the program fails as well
+            OpCode::Begin | OpCode::Noop | OpCode::AssertBin => (),
+
+            OpCode::Push => stack.insert(0, Some(op_hint.value())),
+            OpCode::Pad2 => {
+                stack.insert(0, Some(field::ZERO));
+                stack.insert(0, Some(field::ZERO));
+            },
+
+            OpCode::Dup  => duplicate(&mut stack, 1),
+            OpCode::Dup2 => duplicate(&mut stack, 2),
+            OpCode::Dup4 => duplicate(&mut stack, 4),
+            OpCode::Dup8 => duplicate(&mut stack, 8),
+
+            OpCode::Drop => { pop(&mut stack); },
+            OpCode::Drop4 => for _ in 0..4 { pop(&mut stack); },
+            OpCode::Drop8 => for _ in 0..8 { pop(&mut stack); },
+
+            OpCode::Swap  => swap(&mut stack, 1),
+            OpCode::Swap2 => swap(&mut stack, 2),
+            OpCode::Swap4 => swap(&mut stack, 4),
+            OpCode::Swap8 => swap(&mut stack, 8),
+
+            OpCode::Roll4 => roll(&mut stack, 4),
+            OpCode::Roll8 => roll(&mut stack, 8),
+
+            OpCode::Not => {
+                let x = pop(&mut stack);
+                stack.insert(0, x.filter(|&x| x <= field::ONE).map(|x| field::sub(field::ONE, x)));
+            },
+            OpCode::Neg => {
+                let x = pop(&mut stack);
+                stack.insert(0, x.map(field::neg));
+            },
+            OpCode::Add | OpCode::Mul => {
+                let x = pop(&mut stack);
+                let y = pop(&mut stack);
+                let result = match (x, y) {
+                    (Some(x), Some(y)) if op_code == OpCode::Add => Some(field::add(x, y)),
+                    (Some(x), Some(y)) => Some(field::mul(x, y)),
+                    _ => None,
+                };
+                stack.insert(0, result);
+            },
+
+            // for all other operations, the values they consume are discarded and the values
+            // they produce are unknown
+            _ => {
+                let spec = op_code.spec();
+                let consumed = spec.required_depth;
+                let produced = (consumed as isize + spec.depth_change) as usize;
+                for _ in 0..consumed { pop(&mut stack); }
+                for _ in 0..produced { stack.insert(0, None); }
+            }
+        }
+    }
+
+    return pop(&mut stack);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure the stack model contains at least `n` values by appending unknown values to its
+/// bottom.
+fn ensure(stack: &mut Vec<Option<u128>>, n: usize) {
+    while stack.len() < n {
+        stack.push(None);
+    }
+}
+
+fn pop(stack: &mut Vec<Option<u128>>) -> Option<u128> {
+    return if stack.is_empty() { None } else { stack.remove(0) };
+}
+
+/// Copies the top `n` values and pushes the copies onto the stack.
+fn duplicate(stack: &mut Vec<Option<u128>>, n: usize) {
+    ensure(stack, n);
+    let top = stack[..n].to_vec();
+    stack.splice(0..0, top);
+}
+
+/// Swaps the top `n` values with the next `n` values.
+fn swap(stack: &mut Vec<Option<u128>>, n: usize) {
+    ensure(stack, 2 * n);
+    stack[..2 * n].rotate_left(n);
+}
+
+/// Moves the value at depth `n` to the top of the stack.
+fn roll(stack: &mut Vec<Option<u128>>, n: usize) {
+    ensure(stack, n);
+    stack[..n].rotate_right(1);
+}
@@ -0,0 +1,59 @@
+use super::AssemblyError;
+
+// CONDITIONAL ASSEMBLY
+// ================================================================================================
+
+/// Removes conditional sections from the `source` token stream. A conditional section starts
+/// with `ifdef.FLAG` and ends with a matching `endif`; the section is kept if `FLAG` is in the
+/// list of `flags`, and removed otherwise. Conditional sections can be nested.
+///
+/// Returns the remaining tokens together with a source map which contains the index of every
+/// remaining token in the `source`; the directives themselves are always removed.
+pub fn strip_conditionals<'a>(source: &[&'a str], flags: &[&str]) -> Result<(Vec<&'a str>, Vec<usize>), AssemblyError> {
+
+    let mut tokens = Vec::with_capacity(source.len());
+    let mut source_map = Vec::with_capacity(source.len());
+
+    // for each currently open section, keeps the step at which it was opened; the number of
+    // sections which were opened while all enclosing sections were enabled is tracked separately
+    let mut open_sections: Vec<(Vec<&str>, usize)> = Vec::new();
+    let mut enabled_depth = 0;
+
+    for (step, &token) in source.iter().enumerate() {
+        let op: Vec<&str> = token.split(".").collect();
+        match op[0] {
+            "ifdef" => {
+                if op.len() != 2 || op[1].is_empty() {
+                    return Err(AssemblyError::invalid_ifdef(&op, step));
+                }
+                if enabled_depth == open_sections.len() && flags.contains(&op[1]) {
+                    enabled_depth += 1;
+                }
+                open_sections.push((op, step));
+            },
+            "endif" => {
+                if op.len() != 1 {
+                    return Err(AssemblyError::extra_param(&op, step));
+                }
+                if open_sections.pop().is_none() {
+                    return Err(AssemblyError::dangling_endif(step));
+                }
+                if enabled_depth > open_sections.len() {
+                    enabled_depth -= 1;
+                }
+            },
+            _ => {
+                if enabled_depth == open_sections.len() {
+                    tokens.push(token);
+                    source_map.push(step);
+                }
+            }
+        }
+    }
+
+    if let Some((op, step)) = open_sections.pop() {
+        return Err(AssemblyError::unmatched_ifdef(&op, step));
+    }
+
+    return Ok((tokens, source_map));
+}
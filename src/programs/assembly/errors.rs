@@ -6,6 +6,12 @@ pub struct AssemblyError {
     op      : String
 }
 
+pub struct AssemblyWarning {
+    message : String,
+    step    : usize,
+    op      : String
+}
+
 // ASSEMBLY ERROR IMPLEMENTATION
 // ================================================================================================
 impl AssemblyError {
@@ -157,6 +163,151 @@ impl AssemblyError {
         };
     }
 
+    pub fn infinite_loop(step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("loop never terminates: loop body always leaves 1 at the top of the stack"),
+            step    : step,
+            op      : String::from("while.true"),
+        };
+    }
+
+    pub fn invalid_macro(op: &[&str], step: usize, reason: String) -> AssemblyError {
+        return AssemblyError {
+            message : format!("invalid macro definition '{}': {}", op.join("."), reason),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn duplicate_macro(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("macro {} is already defined", op[1]),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn unmatched_macro(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("macro without matching end"),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn misplaced_macro(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("macros must be defined before the program begins"),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn invalid_macro_args(op: &[&str], step: usize, expected: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("macro {} expects {} numeric arguments, but {} were provided",
+                op[0], expected, op.len() - 1),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn macro_too_deep(op: &[&str], step: usize, max_depth: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("expansion of macro {} is nested more than {} levels deep", op[0], max_depth),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn macro_too_large(op: &[&str], step: usize, max_tokens: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("program expands into more than {} instructions", max_tokens),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn invalid_proc(op: &[&str], step: usize, reason: String) -> AssemblyError {
+        return AssemblyError {
+            message : format!("invalid procedure definition '{}': {}", op.join("."), reason),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn duplicate_proc(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("procedure {} is already defined", op[1]),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn invalid_ifdef(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("invalid directive '{}': exactly one flag must be specified", op.join(".")),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn unmatched_ifdef(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("ifdef without matching endif"),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn dangling_endif(step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("endif without matching ifdef"),
+            step    : step,
+            op      : String::from("endif"),
+        };
+    }
+
+    pub fn unmatched_test(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("test without matching expect and end"),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn misplaced_test(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("'{}' is not allowed in a test or in a program", op[0]),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn misplaced_expect(op: &[&str], step: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("expect must be the last instruction of a test"),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    pub fn invalid_num_outputs(num_outputs: usize, min: usize, max: usize) -> AssemblyError {
+        return AssemblyError {
+            message : format!("number of outputs must be between {} and {}, but received {}", min, max, num_outputs),
+            step    : 0,
+            op      : String::from("begin"),
+        };
+    }
+
+    pub fn format_changed_program() -> AssemblyError {
+        return AssemblyError {
+            message : String::from("formatted program hash does not match the hash of the source program"),
+            step    : 0,
+            op      : String::from("begin"),
+        };
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
     pub fn message(&self) -> &String {
@@ -170,8 +321,79 @@ impl AssemblyError {
     pub fn step(&self) -> usize {
         return self.step;
     }
+
+    /// Maps the step of this error from a step in the expanded token stream to a step in the
+    /// original source code.
+    pub(super) fn map_step(mut self, source_map: &[usize]) -> AssemblyError {
+        if let Some(&step) = source_map.get(self.step) {
+            self.step = step;
+        }
+        return self;
+    }
 }
 
+// ASSEMBLY WARNING IMPLEMENTATION
+// ================================================================================================
+impl AssemblyWarning {
+
+    // CONSTRUCTORS
+    // --------------------------------------------------------------------------------------------
+
+    pub fn dead_true_branch(step: usize) -> AssemblyWarning {
+        return AssemblyWarning {
+            message : format!("condition is always 0: true branch is never executed"),
+            step    : step,
+            op      : String::from("if.true"),
+        };
+    }
+
+    pub fn dead_false_branch(step: usize) -> AssemblyWarning {
+        return AssemblyWarning {
+            message : format!("condition is always 1: false branch is never executed"),
+            step    : step,
+            op      : String::from("if.true"),
+        };
+    }
+
+    pub fn dead_loop(step: usize) -> AssemblyWarning {
+        return AssemblyWarning {
+            message : format!("condition is always 0: loop body is never executed"),
+            step    : step,
+            op      : String::from("while.true"),
+        };
+    }
+
+    pub fn non_binary_condition(op: &[&str], step: usize, value: u128) -> AssemblyWarning {
+        return AssemblyWarning {
+            message : format!("condition is always {}: program execution will always fail", value),
+            step    : step,
+            op      : op.join("."),
+        };
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+    pub fn message(&self) -> &String {
+        return &self.message;
+    }
+
+    pub fn operation(&self) -> &String {
+        return &self.op;
+    }
+
+    pub fn step(&self) -> usize {
+        return self.step;
+    }
+
+    /// Maps the step of this warning from a step in the expanded token stream to a step in the
+    /// original source code.
+    pub(super) fn map_step(mut self, source_map: &[usize]) -> AssemblyWarning {
+        if let Some(&step) = source_map.get(self.step) {
+            self.step = step;
+        }
+        return self;
+    }
+}
 
 // COMMON TRAIT IMPLEMENTATIONS
 // ================================================================================================
@@ -186,4 +408,16 @@ impl std::fmt::Display for AssemblyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "assembly error at {}: {}", self.step, self.message)
     }
+}
+
+impl std::fmt::Debug for AssemblyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "assembly warning at {}: {}", self.step, self.message)
+    }
+}
+
+impl std::fmt::Display for AssemblyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "assembly warning at {}: {}", self.step, self.message)
+    }
 }
\ No newline at end of file
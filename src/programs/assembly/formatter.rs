@@ -0,0 +1,182 @@
+use super::{ compile, compile_with_flags, AssemblyError };
+
+// CONSTANTS
+// ================================================================================================
+
+/// If the source references no more than this number of conditional assembly flags, formatted
+/// code is checked against every combination of these flags.
+const MAX_EXHAUSTIVE_FLAGS: usize = 8;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Options which control the layout of formatted assembly code.
+///
+/// * `indent` is the number of spaces by which the body of every block is indented;
+/// * `line_width` is the maximum width of a line; instructions are packed into a line until
+///   adding another instruction would exceed this width (a single instruction is never split);
+/// * `one_per_line` places every instruction on its own line and overrides `line_width`.
+#[derive(Copy, Clone, Debug)]
+pub struct FormatOptions {
+    pub indent          : usize,
+    pub line_width      : usize,
+    pub one_per_line    : bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        return FormatOptions { indent: 4, line_width: 80, one_per_line: false };
+    }
+}
+
+// ASSEMBLY FORMATTER
+// ================================================================================================
+
+/// Formats the provided assembly code using default formatting options.
+pub fn format(source: &str) -> Result<String, AssemblyError> {
+    return format_with_options(source, &FormatOptions::default());
+}
+
+/// Formats the provided assembly code into a canonical layout:
+/// * every block head (`macro`, `begin`, `test.begin`, `block`, `if.true`, `else`, `while.true`,
+///   `repeat.n`),
+///   every `ifdef` directive, and every `end` and `endif` is placed on its own line, and block
+///   bodies are indented;
+/// * numeric parameters of instructions are normalized: leading zeros are removed, and
+///   hexadecimal values are written in lower case.
+///
+/// The source must be a valid program. The formatted code is compiled again to make sure that
+/// it reduces to the same program hash as the source; this is checked with every combination of
+/// flags referenced by `ifdef` directives of the source (or, if the source references more than
+/// 8 flags, with no flags, with each flag alone, and with all flags), so that code inside
+/// conditional sections is checked as well.
+pub fn format_with_options(source: &str, options: &FormatOptions) -> Result<String, AssemblyError> {
+
+    // make sure the source is a valid program; this also guarantees that blocks are balanced
+    let program = compile(source)?;
+
+    let mut output = String::new();
+    let mut line = String::new();
+    let mut depth = 0;
+
+    for token in source.split_whitespace() {
+        // flag names are left as is, since they are not numeric parameters
+        let token = if token.starts_with("ifdef.") { token.to_string() } else { normalize_token(token) };
+        let head = token.split(".").next().unwrap_or("");
+        match head {
+            "ifdef" | "macro" | "begin" | "test" | "block" | "if" | "while" | "repeat" => {
+                flush_line(&mut output, &mut line, depth, options);
+                push_line(&mut output, &token, depth, options);
+                depth += 1;
+            },
+            "else" => {
+                flush_line(&mut output, &mut line, depth, options);
+                push_line(&mut output, &token, depth - 1, options);
+            },
+            "end" | "endif" => {
+                flush_line(&mut output, &mut line, depth, options);
+                depth -= 1;
+                push_line(&mut output, &token, depth, options);
+            },
+            _ => {
+                let line_start = depth * options.indent;
+                if options.one_per_line {
+                    push_line(&mut output, &token, depth, options);
+                }
+                else if line.is_empty() {
+                    line.push_str(&token);
+                }
+                else if line_start + line.len() + 1 + token.len() > options.line_width {
+                    flush_line(&mut output, &mut line, depth, options);
+                    line.push_str(&token);
+                }
+                else {
+                    line.push(' ');
+                    line.push_str(&token);
+                }
+            }
+        }
+    }
+
+    // formatting must not change the program for any set of flags; sets of flags for which the
+    // source itself does not compile are skipped
+    for flags in get_flag_sets(source) {
+        let original_hash = match flags.is_empty() {
+            true  => Some(*program.hash()),
+            false => compile_with_flags(source, &flags).ok().map(|p| *p.hash()),
+        };
+        if let Some(original_hash) = original_hash {
+            let formatted = compile_with_flags(&output, &flags)
+                .map_err(|_| AssemblyError::format_changed_program())?;
+            if *formatted.hash() != original_hash {
+                return Err(AssemblyError::format_changed_program());
+            }
+        }
+    }
+
+    return Ok(output);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Normalizes numeric parameters of an instruction; all other parts of the instruction are
+/// left as is.
+fn normalize_token(token: &str) -> String {
+    let parts: Vec<String> = token.split(".").enumerate().map(|(i, part)| {
+        if i == 0 {
+            part.to_string()
+        }
+        else if part.starts_with("0x") && part.len() > 2 {
+            match u128::from_str_radix(&part[2..], 16) {
+                Ok(value) => format!("0x{:x}", value),
+                Err(_) => part.to_string(),
+            }
+        }
+        else {
+            match u128::from_str_radix(part, 10) {
+                Ok(value) => value.to_string(),
+                Err(_) => part.to_string(),
+            }
+        }
+    }).collect();
+
+    return parts.join(".");
+}
+
+/// Returns sets of conditional assembly flags with which formatted code should be checked. The
+/// first set is always empty.
+fn get_flag_sets(source: &str) -> Vec<Vec<&str>> {
+    let mut flags: Vec<&str> = Vec::new();
+    for token in source.split_whitespace() {
+        if let Some(flag) = token.strip_prefix("ifdef.") {
+            if !flags.contains(&flag) {
+                flags.push(flag);
+            }
+        }
+    }
+
+    if flags.len() <= MAX_EXHAUSTIVE_FLAGS {
+        return (0..(1usize << flags.len())).map(|mask| {
+            flags.iter().enumerate().filter(|(i, _)| mask & (1 << i) != 0).map(|(_, &flag)| flag).collect()
+        }).collect();
+    }
+
+    let mut sets = vec![Vec::new()];
+    sets.extend(flags.iter().map(|&flag| vec![flag]));
+    sets.push(flags);
+    return sets;
+}
+
+fn flush_line(output: &mut String, line: &mut String, depth: usize, options: &FormatOptions) {
+    if !line.is_empty() {
+        push_line(output, line, depth, options);
+        line.clear();
+    }
+}
+
+fn push_line(output: &mut String, line: &str, depth: usize, options: &FormatOptions) {
+    output.push_str(&" ".repeat(depth * options.indent));
+    output.push_str(line);
+    output.push('\n');
+}
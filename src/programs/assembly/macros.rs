@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use super::AssemblyError;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Maximum number of macro invocations which can be nested within each other.
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// Maximum number of tokens a program can expand into.
+const MAX_EXPANDED_TOKENS: usize = 1 << 20;
+
+/// Names which cannot be used as macro names because they are used by block heads and
+/// assembly instructions.
+const RESERVED_NAMES: [&str; 48] = [
+    "begin", "end", "block", "if", "else", "while", "repeat", "macro", "proc", "test", "expect",
+    "noop", "assert", "push", "read", "dup", "pad", "pick", "drop", "swap", "roll", "perm",
+    "add", "sub", "mul", "div", "divmod", "umod", "split", "join", "neg", "inv", "not", "and", "or",
+    "eq", "ne", "gt", "lt", "rc", "isodd", "choose", "sortnet",
+    "hash", "smpath", "mpath_update", "pmpath", "prng",
+];
+
+// TYPES AND INTERFACES
+// ================================================================================================
+struct Macro {
+    params      : Vec<String>,
+    body        : Vec<String>,
+}
+
+// MACRO EXPANSION
+// ================================================================================================
+
+/// Reads macro definitions from the start of the `source` token stream, and expands all macro
+/// invocations in the remaining tokens. Returns the expanded token stream together with a source
+/// map which contains, for every expanded token, the index of the source token it came from;
+/// tokens produced by a macro invocation are mapped to the invocation.
+///
+/// A macro is defined as `macro.name.param1.param2 <instructions> end`, and parameters are
+/// referenced in the instructions as `$param1`, `$param2` etc. (e.g. `push.$param1`). A macro is
+/// invoked as `name.arg1.arg2`, where all arguments are numeric.
+pub fn expand(source: &[&str]) -> Result<(Vec<String>, Vec<usize>), AssemblyError> {
+
+    // read macro definitions; these must come before the program
+    let mut macros = HashMap::new();
+    let mut i = 0;
+    while i < source.len() && source[i].split(".").next() == Some("macro") {
+        i = read_macro(source, i, &mut macros)?;
+    }
+
+    // copy the remaining tokens into the result expanding all macro invocations
+    let mut tokens = Vec::with_capacity(source.len() - i);
+    let mut source_map = Vec::with_capacity(source.len() - i);
+    for step in i..source.len() {
+        let op: Vec<&str> = source[step].split(".").collect();
+        if op[0] == "macro" {
+            return Err(AssemblyError::misplaced_macro(&op, step));
+        }
+        else if macros.contains_key(op[0]) {
+            expand_macro(&op, &macros, step, 0, &mut tokens)?;
+        }
+        else {
+            tokens.push(source[step].to_string());
+        }
+        source_map.resize(tokens.len(), step);
+    }
+
+    return Ok((tokens, source_map));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads a macro definition starting at token `i`, adds it to `macros`, and returns the index
+/// of the token following the definition.
+fn read_macro(source: &[&str], mut i: usize, macros: &mut HashMap<String, Macro>) -> Result<usize, AssemblyError> {
+
+    // validate macro head
+    let head: Vec<&str> = source[i].split(".").collect();
+    let step = i;
+    if head.len() < 2 {
+        return Err(AssemblyError::invalid_macro(&head, step, format!("macro name is missing")));
+    }
+    for (j, &name) in head[1..].iter().enumerate() {
+        if !is_identifier(name) {
+            return Err(AssemblyError::invalid_macro(&head, step, format!("'{}' is not a valid name", name)));
+        }
+        if head[1..(j + 1)].contains(&name) {
+            return Err(AssemblyError::invalid_macro(&head, step, format!("'{}' is used more than once", name)));
+        }
+    }
+    if RESERVED_NAMES.contains(&head[1]) {
+        return Err(AssemblyError::invalid_macro(&head, step, format!("'{}' is a reserved name", head[1])));
+    }
+    if macros.contains_key(head[1]) {
+        return Err(AssemblyError::duplicate_macro(&head, step));
+    }
+    let params: Vec<String> = head[2..].iter().map(|&p| p.to_string()).collect();
+
+    // read macro body until the matching end; nested blocks are tracked so that their ends
+    // are not mistaken for the end of the macro
+    let mut body = Vec::new();
+    let mut depth = 0;
+    i += 1;
+    loop {
+        if i == source.len() {
+            return Err(AssemblyError::unmatched_macro(&head, step));
+        }
+
+        let op: Vec<&str> = source[i].split(".").collect();
+        match op[0] {
+            "begin" | "macro" | "proc" | "test" => {
+                return Err(AssemblyError::invalid_macro(&head, step,
+                    format!("'{}' is not allowed in a macro", op[0])));
+            },
+            "block" | "if" | "while" | "repeat" => depth += 1,
+            "end" if depth == 0 => break,
+            "end" => depth -= 1,
+            _ => (),
+        }
+
+        // make sure all referenced parameters are defined
+        for part in op[1..].iter().filter(|p| p.starts_with("$")) {
+            if !params.iter().any(|p| p == &part[1..]) {
+                return Err(AssemblyError::invalid_macro(&head, step,
+                    format!("parameter '{}' is not defined", &part[1..])));
+            }
+        }
+
+        body.push(source[i].to_string());
+        i += 1;
+    }
+
+    if body.is_empty() {
+        return Err(AssemblyError::invalid_macro(&head, step, format!("macro body is empty")));
+    }
+
+    macros.insert(head[1].to_string(), Macro { params, body });
+    return Ok(i + 1);
+}
+
+/// Appends the body of the macro invoked by `op` to `tokens`, substituting the macro parameters
+/// with the arguments of the invocation; macros invoked from the body are expanded recursively.
+fn expand_macro(op: &[&str], macros: &HashMap<String, Macro>, step: usize, depth: usize, tokens: &mut Vec<String>) -> Result<(), AssemblyError> {
+
+    if depth == MAX_EXPANSION_DEPTH {
+        return Err(AssemblyError::macro_too_deep(op, step, MAX_EXPANSION_DEPTH));
+    }
+
+    let definition = &macros[op[0]];
+    let args = &op[1..];
+    if args.len() != definition.params.len() || !args.iter().all(|a| is_number(a)) {
+        return Err(AssemblyError::invalid_macro_args(op, step, definition.params.len()));
+    }
+
+    for token in definition.body.iter() {
+
+        // replace references to parameters with the corresponding arguments
+        let parts: Vec<&str> = token.split(".").map(|part| {
+            match definition.params.iter().position(|p| part.starts_with("$") && p == &part[1..]) {
+                Some(idx) => args[idx],
+                None => part,
+            }
+        }).collect();
+
+        if macros.contains_key(parts[0]) {
+            expand_macro(&parts, macros, step, depth + 1, tokens)?;
+        }
+        else {
+            tokens.push(parts.join("."));
+        }
+
+        if tokens.len() > MAX_EXPANDED_TOKENS {
+            return Err(AssemblyError::macro_too_large(op, step, MAX_EXPANDED_TOKENS));
+        }
+    }
+
+    return Ok(());
+}
+
+pub fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    return match chars.next() {
+        Some(c) if c.is_ascii_lowercase() || c == '_' => {
+            chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        },
+        _ => false,
+    };
+}
+
+fn is_number(value: &str) -> bool {
+    return if value.starts_with("0x") {
+        value.len() > 2 && value[2..].chars().all(|c| c.is_ascii_hexdigit())
+    }
+    else {
+        !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+    };
+}
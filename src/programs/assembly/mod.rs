@@ -1,11 +1,27 @@
 use std::collections::HashMap;
-use super::{ Program, ProgramBlock, Span, Group, Switch, Loop, OpCode, OpHint, BASE_CYCLE_LENGTH };
+use super::{ Program, ProcedureSet, ProgramBlock, Span, Group, Switch, Loop, OpCode, OpHint, BASE_CYCLE_LENGTH };
+use crate::{ MAX_OUTPUTS, MAX_STACK_DEPTH, MAX_DIRECT_OUTPUTS, processor, programs::ProgramInputs };
 
 mod parsers;
 use parsers::*;
 
 mod errors;
-use errors::{ AssemblyError };
+pub use errors::{ AssemblyError, AssemblyWarning };
+
+mod padding;
+pub use padding::{ PaddingReport, Padding, PaddingCause, BLOCK_CLOSE_NOOPS };
+
+mod analysis;
+mod directives;
+mod macros;
+
+mod testing;
+pub use testing::TestResult;
+
+pub mod stdlib;
+
+mod formatter;
+pub use formatter::{ format, format_with_options, FormatOptions };
 
 #[cfg(test)]
 mod tests;
@@ -17,9 +33,178 @@ type HintMap = HashMap<usize, OpHint>;
 
 /// Compiles provided assembly code into a program.
 pub fn compile(source: &str) -> Result<Program, AssemblyError> {
+    let (program, _) = compile_with_warnings(source)?;
+    return Ok(program);
+}
+
+/// Compiles provided assembly code into a program, and returns the program together with a list
+/// of warnings about code which can never be executed. Macros defined at the start of the source
+/// code are expanded before the program is parsed. Conditions of `if.true` and `while.true`
+/// blocks which are pushed onto the stack as constants right before the block are detected, and
+/// a loop whose body always leaves 1 at the top of the stack results in an error.
+pub fn compile_with_warnings(source: &str) -> Result<(Program, Vec<AssemblyWarning>), AssemblyError> {
+    let (program, warnings, _) = compile_source(source, &[], &[])?;
+    return Ok((program, warnings));
+}
+
+/// Compiles provided assembly code into a program, and returns the program together with a
+/// report listing every sequence of NOOPs which was added to the program to keep its operations
+/// aligned, as well as the NOOPs the VM executes when closing nested blocks. Positions in the
+/// report refer to instructions in the source code.
+pub fn compile_with_padding_report(source: &str) -> Result<(Program, PaddingReport), AssemblyError> {
+    let (program, _, report) = compile_source(source, &[], &[])?;
+    return Ok((program, report));
+}
+
+/// Compiles provided assembly code into a program with the specified compile-time `flags` set.
+/// Sections of the source code enclosed in `ifdef.FLAG ... endif` are compiled only if `FLAG` is
+/// one of the `flags`; thus, programs compiled with different flags may have different hashes.
+pub fn compile_with_flags(source: &str, flags: &[&str]) -> Result<Program, AssemblyError> {
+    let (program, _, _) = compile_source(source, flags, &[])?;
+    return Ok(program);
+}
+
+/// Compiles provided assembly code into a program which returns more outputs than can be bound
+/// by boundary constraints directly. The program is expected to leave `num_outputs` items at the
+/// top of the stack; an epilogue appended to the program keeps the top 4 of these items, and
+/// replaces the remaining ones with their digest computed as `crypto::digest_outputs()` does.
+/// Thus, after the program is executed, the digest is at the top of the stack, and it is followed
+/// by the 4 direct outputs; this can be verified using `distaff::verify_with_output_digest()`.
+///
+/// Returns an error if `num_outputs` is not greater than `MAX_OUTPUTS` or is greater than
+/// `MAX_STACK_DEPTH`, or if the program does not end with an `end` instruction.
+pub fn compile_with_output_digest(source: &str, num_outputs: usize) -> Result<Program, AssemblyError> {
+    if num_outputs <= MAX_OUTPUTS || num_outputs > MAX_STACK_DEPTH {
+        return Err(AssemblyError::invalid_num_outputs(num_outputs, MAX_OUTPUTS + 1, MAX_STACK_DEPTH));
+    }
+    let epilogue = build_output_digest_epilogue(num_outputs - MAX_DIRECT_OUTPUTS);
+    let (program, _, _) = compile_source(source, &[], &epilogue)?;
+    return Ok(program);
+}
+
+/// Compiles and runs test sections of provided assembly code, and returns the results of all
+/// tests in the order in which they appear in the code. A test section is written as
+/// `test.begin <instructions> expect.<v1>,<v2>,... end`; the instructions are compiled into a
+/// standalone program, executed by the interpreter with no inputs, and the test passes if the
+/// top items of the stack are equal to the expected values (the first value is the top item).
+///
+/// Test sections can be placed anywhere outside of programs and procedures (e.g. after the
+/// definitions of the macros they test), and they are ignored by all other compile functions;
+/// thus, a library of macros can carry its own tests. An error is returned only if a test cannot
+/// be compiled; failures of tests are reported in their results.
+pub fn test(source: &str) -> Result<Vec<TestResult>, AssemblyError> {
+    let (_, _, tests) = expand_source(source, &[])?;
+
+    let mut results = Vec::with_capacity(tests.len());
+    for test in tests {
+        let tokens: Vec<&str> = test.tokens.iter().map(|t| t.as_str()).collect();
+        let (program, _, _) = compile_tokens(&tokens).map_err(|e| e.map_step(&test.source_map))?;
+
+        let num_expected = test.expected.len();
+        let outcome = processor::interpret(&program, &ProgramInputs::none()).map(|state| {
+            let mut outputs = state.stack;
+            outputs.resize(std::cmp::max(outputs.len(), num_expected), 0);
+            outputs.truncate(num_expected);
+            outputs
+        });
+        results.push(TestResult { step: test.step, expected: test.expected, outcome });
+    }
+
+    return Ok(results);
+}
+
+/// Compiles provided assembly code into a set of procedures. Instead of a single `begin ... end`
+/// block, the source code consists of one or more `proc.<name> ... end` blocks (optionally
+/// preceded by macro definitions shared by all procedures); every procedure is compiled into a
+/// standalone program with the same hash as `begin ... end` with the same body would have.
+pub fn compile_procedures(source: &str) -> Result<ProcedureSet, AssemblyError> {
+    let (tokens, source_map, _) = expand_source(source, &[])?;
+    let mut tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    if tokens.len() == 0 {
+        return Err(AssemblyError::empty_program());
+    }
+
+    let mut procedures: Vec<(Option<String>, Program)> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let step = i;
+        let name = read_proc_head(&tokens, step, &procedures).map_err(|e| e.map_step(&source_map))?;
+
+        // a procedure is compiled as if it were a program starting at the procedure head
+        tokens[step] = "begin";
+        let mut blocks = Vec::new();
+        i = parse_branch(&mut blocks, &tokens, step, &mut Vec::new(), &mut PaddingReport::default())
+            .map_err(|e| e.map_step(&source_map))? + 1;
+        procedures.push((Some(name), Program::new(Group::new(blocks))));
+    }
+
+    return Ok(ProcedureSet::new(procedures));
+}
+
+fn compile_source(source: &str, flags: &[&str], epilogue: &[&str]) -> Result<(Program, Vec<AssemblyWarning>, PaddingReport), AssemblyError> {
+
+    let (tokens, mut source_map, _) = expand_source(source, flags)?;
+    let mut tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+
+    // insert epilogue instructions (if any) right before the last `end` of the program; errors
+    // in the epilogue are reported at the position of this `end`
+    if epilogue.len() > 0 {
+        match tokens.last() {
+            Some(&"end") => (),
+            Some(&op) => return Err(AssemblyError::invalid_program_end(op)),
+            None => return Err(AssemblyError::empty_program()),
+        }
+        let end = tokens.len() - 1;
+        tokens.splice(end..end, epilogue.iter().cloned());
+        source_map.splice(end..end, epilogue.iter().map(|_| source_map[end]).collect::<Vec<_>>());
+    }
+
+    return match compile_tokens(&tokens) {
+        Ok((program, warnings, report)) => {
+            let warnings = warnings.into_iter().map(|w| w.map_step(&source_map)).collect();
+            Ok((program, warnings, report.map_steps(&source_map)))
+        },
+        Err(error) => Err(error.map_step(&source_map)),
+    };
+}
 
-    // break assembly string into tokens
-    let tokens: Vec<&str> = source.split_whitespace().collect();
+/// Breaks assembly string into tokens, removes disabled conditional sections, expands macros,
+/// and removes test sections; the returned source maps are used to report errors and warnings
+/// at their positions in the source code.
+fn expand_source(source: &str, flags: &[&str]) -> Result<(Vec<String>, Vec<usize>, Vec<testing::TestSection>), AssemblyError> {
+    let source_tokens: Vec<&str> = source.split_whitespace().collect();
+    let (tokens, conditional_map) = directives::strip_conditionals(&source_tokens, flags)?;
+    let (tokens, macro_map) = macros::expand(&tokens).map_err(|e| e.map_step(&conditional_map))?;
+    let macro_map: Vec<usize> = macro_map.iter().map(|&i| conditional_map[i]).collect();
+    let (tokens, test_map, mut tests) = testing::strip_tests(&tokens).map_err(|e| e.map_step(&macro_map))?;
+
+    // steps of test sections refer to expanded tokens; map them to the source code as well
+    for test in tests.iter_mut() {
+        test.step = macro_map[test.step];
+        test.source_map.iter_mut().for_each(|i| *i = macro_map[*i]);
+    }
+    let source_map = test_map.iter().map(|&i| macro_map[i]).collect();
+    return Ok((tokens, source_map, tests));
+}
+
+/// Reads the name of the procedure whose head is at the specified `step`, and makes sure that
+/// no procedure with the same name was defined before.
+fn read_proc_head(tokens: &[&str], step: usize, procedures: &[(Option<String>, Program)]) -> Result<String, AssemblyError> {
+    let head: Vec<&str> = tokens[step].split(".").collect();
+    if head[0] != "proc" {
+        return Err(AssemblyError::invalid_proc(&head, step, format!("expected a procedure head")));
+    }
+    if head.len() != 2 || !macros::is_identifier(head[1]) {
+        return Err(AssemblyError::invalid_proc(&head, step, format!("procedure must have a valid name")));
+    }
+    if procedures.iter().any(|(name, _)| name.as_deref() == Some(head[1])) {
+        return Err(AssemblyError::duplicate_proc(&head, step));
+    }
+    return Ok(head[1].to_string());
+}
+
+/// Compiles a stream of tokens with all macros expanded into a program.
+fn compile_tokens(tokens: &[&str]) -> Result<(Program, Vec<AssemblyWarning>, PaddingReport), AssemblyError> {
 
     // perform basic validation
     if tokens.len() == 0 {
@@ -34,7 +219,9 @@ pub fn compile(source: &str) -> Result<Program, AssemblyError> {
 
     // read the program from the token stream
     let mut root_blocks = Vec::new();
-    let i = parse_branch(&mut root_blocks, &tokens, 0)?;
+    let mut warnings = Vec::new();
+    let mut report = PaddingReport::default();
+    let i = parse_branch(&mut root_blocks, tokens, 0, &mut warnings, &mut report)?;
     let root = Group::new(root_blocks);
 
     // make sure there is nothing left after the last token
@@ -43,7 +230,25 @@ pub fn compile(source: &str) -> Result<Program, AssemblyError> {
     }
 
     // build and return the program
-    return Ok(Program::new(root));
+    return Ok((Program::new(root), warnings, report));
+}
+
+/// Builds instructions which replace `num_digested` stack items located right below the top 4
+/// items with their digest, and move the digest to the top of the stack. Digested items are
+/// absorbed 2 at a time into a digest which starts out as 2 zeros: on every iteration, the digest
+/// is located right above the 4 direct outputs, and the next 2 items are right below them.
+fn build_output_digest_epilogue(num_digested: usize) -> Vec<&'static str> {
+    let mut epilogue = vec!["pad.2"];
+    for _ in 0..(num_digested / 2) {
+        epilogue.extend_from_slice(&["roll.8", "roll.8", "swap.2", "hash.4"]);
+    }
+    if num_digested % 2 == 1 {
+        // the last item is absorbed alone; a zero is pushed onto the stack to make sure the
+        // item can be reached by roll.8 (the stack may be exactly 7 items deep at this point),
+        // and the zero is dropped before hashing
+        epilogue.extend_from_slice(&["pad.1", "roll.8", "swap.2", "roll.4", "drop", "hash.3"]);
+    }
+    return epilogue;
 }
 
 // PARSER FUNCTIONS
@@ -51,10 +256,11 @@ pub fn compile(source: &str) -> Result<Program, AssemblyError> {
 
 /// Parses a single program block from the `token` stream, and appends this block to the `parent`
 /// list of blocks.
-fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) -> Result<usize, AssemblyError> {
+fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize, warnings: &mut Vec<AssemblyWarning>, report: &mut PaddingReport) -> Result<usize, AssemblyError> {
 
     // read the block header
     let head: Vec<&str> = tokens[i].split(".").collect();
+    let head_step = i;
 
     // based on the block header, figure out what type of a block we are dealing with
     match head[0] {
@@ -65,8 +271,9 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
             }
             // then parse the body of the block, add the new block to the parent, and return
             let mut body = Vec::new();
-            i = parse_branch(&mut body, tokens, i)?;
+            i = parse_branch(&mut body, tokens, i, warnings, report)?;
             parent.push(Group::new_block(body));
+            report.add(i, head_step, PaddingCause::BlockClose, BLOCK_CLOSE_NOOPS);
             return Ok(i + 1);
         },
         "if" => {
@@ -75,17 +282,23 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
                 return Err(AssemblyError::invalid_block_head(&head, i));
             }
 
+            // check whether the condition is known at compile time
+            let step = i;
+            let condition = analysis::get_stack_top(parent);
+
             // parse the body of the true branch
             let mut t_branch = Vec::new();
-            i = parse_branch(&mut t_branch, tokens, i)?;
+            i = parse_branch(&mut t_branch, tokens, i, warnings, report)?;
 
             // if the false branch is present, parse it as well; otherwise
             // create an empty false branch
             let mut f_branch = Vec::new();
-            if tokens[i] == "else" {
-                i = parse_branch(&mut f_branch, tokens, i)?;
+            let has_else = tokens[i] == "else";
+            if has_else {
+                i = parse_branch(&mut f_branch, tokens, i, warnings, report)?;
             }
             else {
+                report.add(i, head_step, PaddingCause::SpanAlignment, BASE_CYCLE_LENGTH - 3);
                 f_branch.push(Span::new_block(vec![
                     OpCode::Not,  OpCode::Assert, OpCode::Noop, OpCode::Noop,
                     OpCode::Noop, OpCode::Noop,   OpCode::Noop, OpCode::Noop,
@@ -94,8 +307,18 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
                 ]));
             }
 
+            // warn about branches which can never be executed
+            match condition {
+                Some(0) => warnings.push(AssemblyWarning::dead_true_branch(step)),
+                Some(1) if has_else => warnings.push(AssemblyWarning::dead_false_branch(step)),
+                Some(1) => (),
+                Some(value) => warnings.push(AssemblyWarning::non_binary_condition(&head, step, value)),
+                None => (),
+            }
+
             // create a Switch block, add it to the parent, and return
             parent.push(Switch::new_block(t_branch, f_branch));
+            report.add(i, head_step, PaddingCause::BlockClose, BLOCK_CLOSE_NOOPS);
             return Ok(i + 1);
         },
         "repeat" => {
@@ -106,14 +329,19 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
             }
 
             // parse loop body
+            let first_padding = report.len();
             let mut body_template = Vec::new();
-            i = parse_branch(&mut body_template, tokens, i)?;
+            i = parse_branch(&mut body_template, tokens, i, warnings, report)?;
 
-            // duplicate loop body as many times as needed
+            // duplicate loop body as many times as needed; padding of the body is repeated in
+            // every iteration, and a NOOP is added at the boundary of every two iterations
             let body = repeat_block_sequence(body_template, num_iterations);
+            report.repeat(first_padding, num_iterations);
+            report.add(head_step, head_step, PaddingCause::SpanAlignment, num_iterations - 1);
 
             // create a Group block with all iterations expanded, and return
             parent.push(Group::new_block(body));
+            report.add(i, head_step, PaddingCause::BlockClose, BLOCK_CLOSE_NOOPS);
             return Ok(i + 1);
         },
         "while" => {
@@ -121,10 +349,25 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
             if head.len() == 1 || head[1] != "true" {
                 return Err(AssemblyError::invalid_block_head(&head, i));
             }
-            // then parse the body of the block, add the new block to the parent, and return
+            // warn if the loop can never be entered
+            let step = i;
+            match analysis::get_stack_top(parent) {
+                Some(0) => warnings.push(AssemblyWarning::dead_loop(step)),
+                Some(1) | None => (),
+                Some(value) => warnings.push(AssemblyWarning::non_binary_condition(&head, step, value)),
+            }
+
+            // then parse the body of the block; if the body always leaves 1 at the top of
+            // the stack, the loop can never be exited
             let mut body = Vec::new();
-            i = parse_branch(&mut body, tokens, i)?;
+            i = parse_branch(&mut body, tokens, i, warnings, report)?;
+            if analysis::get_stack_top(&body) == Some(1) {
+                return Err(AssemblyError::infinite_loop(step));
+            }
+
+            // add the new block to the parent, and return
             parent.push(Loop::new_block(body));
+            report.add(i, head_step, PaddingCause::BlockClose, BLOCK_CLOSE_NOOPS);
             return Ok(i + 1);
         },
         _ => return Err(AssemblyError::invalid_block_head(&head, i)),
@@ -133,7 +376,7 @@ fn parse_block(parent: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
 
 /// Builds a body of a program block by parsing tokens from the stream and transforming
 /// them into program blocks.
-fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) -> Result<usize, AssemblyError> {
+fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize, warnings: &mut Vec<AssemblyWarning>, report: &mut PaddingReport) -> Result<usize, AssemblyError> {
 
     // determine starting instructions of the branch based on branch head
     let mut head: Vec<&str> = tokens[i].split(".").collect();
@@ -163,8 +406,14 @@ fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
         i = match op[0] {
             "block" | "if" | "repeat" | "while" => {
                 let force_span = body.len() == 0;
+                if force_span && op_codes.len() == 0 {
+                    report.add(i, first_step, PaddingCause::EmptySpan, BASE_CYCLE_LENGTH - 1);
+                }
+                else {
+                    add_span_padding(body, &op_codes, i, first_step, report);
+                }
                 add_span(body, &mut op_codes, &mut op_hints, force_span);
-                parse_block(body, tokens, i)?
+                parse_block(body, tokens, i, warnings, report)?
             },
             "else" => {
                 if head[0] != "if" {
@@ -173,6 +422,7 @@ fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
                 else if i - first_step < 2 {
                     return Err(AssemblyError::empty_block(&head, first_step));
                 }
+                add_span_padding(body, &op_codes, i, first_step, report);
                 add_span(body, &mut op_codes, &mut op_hints, false);
                 return Ok(i);
             },
@@ -180,10 +430,16 @@ fn parse_branch(body: &mut Vec<ProgramBlock>, tokens: &[&str], mut i: usize) ->
                 if i - first_step < 2 {
                     return Err(AssemblyError::empty_block(&head, first_step));
                 }
+                add_span_padding(body, &op_codes, i, first_step, report);
                 add_span(body, &mut op_codes, &mut op_hints, false);
                 return Ok(i);
             },
-            _ => parse_op_token(op, &mut op_codes, &mut op_hints, i)?
+            _ => {
+                let start = op_codes.len();
+                let next = parse_op_token(op, &mut op_codes, &mut op_hints, i)?;
+                add_op_padding(&op_codes, start, i, first_step, report);
+                next
+            }
         };
     }
 
@@ -207,7 +463,7 @@ fn parse_op_token(op: Vec<&str>, op_codes: &mut Vec<OpCode>, op_hints: &mut Hint
         "assert" => parse_assert(op_codes, &op, step),
 
         "push"   => parse_push(op_codes, op_hints, &op, step),
-        "read"   => parse_read(op_codes, &op, step),
+        "read"   => parse_read(op_codes, op_hints, &op, step),
 
         "dup"    => parse_dup(op_codes, &op, step),
         "pad"    => parse_pad(op_codes, &op, step),
@@ -215,11 +471,16 @@ fn parse_op_token(op: Vec<&str>, op_codes: &mut Vec<OpCode>, op_hints: &mut Hint
         "drop"   => parse_drop(op_codes, &op, step),
         "swap"   => parse_swap(op_codes, &op, step),
         "roll"   => parse_roll(op_codes, &op, step),
+        "perm"   => parse_perm(op_codes, &op, step),
 
         "add"    => parse_add(op_codes, &op, step),
         "sub"    => parse_sub(op_codes, &op, step),
         "mul"    => parse_mul(op_codes, &op, step),
         "div"    => parse_div(op_codes, &op, step),
+        "divmod" => parse_divmod(op_codes, op_hints, &op, step),
+        "umod"   => parse_umod(op_codes, op_hints, &op, step),
+        "split"  => parse_split(op_codes, op_hints, &op, step),
+        "join"   => parse_join(op_codes, op_hints, &op, step),
         "neg"    => parse_neg(op_codes, &op, step),
         "inv"    => parse_inv(op_codes, &op, step),
         "not"    => parse_not(op_codes, &op, step),
@@ -234,10 +495,13 @@ fn parse_op_token(op: Vec<&str>, op_codes: &mut Vec<OpCode>, op_hints: &mut Hint
         "isodd"  => parse_isodd(op_codes, op_hints, &op, step),
 
         "choose" => parse_choose(op_codes, &op, step),
+        "sortnet" => parse_sortnet(op_codes, op_hints, &op, step),
 
         "hash"   => parse_hash(op_codes, &op, step),
         "smpath" => parse_smpath(op_codes, &op, step),
+        "mpath_update" => parse_mpath_update(op_codes, &op, step),
         "pmpath" => parse_pmpath(op_codes, op_hints, &op, step),
+        "prng"   => parse_prng(op_codes, &op, step),
 
         _ => return Err(AssemblyError::invalid_op(&op, step))
     }?;
@@ -268,6 +532,37 @@ fn add_span(body: &mut Vec<ProgramBlock>, op_codes: &mut Vec<OpCode>, op_hints:
     op_hints.clear();
 }
 
+/// Records NOOPs which `add_span()` adds to the `op_codes` to make sure the Span block ends on a
+/// step which is one less than a multiple of 16; if the span is not the first block in the
+/// `body`, the VM also executes a NOOP before the span.
+fn add_span_padding(body: &[ProgramBlock], op_codes: &[OpCode], step: usize, block: usize, report: &mut PaddingReport) {
+    if op_codes.len() == 0 { return };
+    let pad_length = BASE_CYCLE_LENGTH - (op_codes.len() % BASE_CYCLE_LENGTH) - 1;
+    let pre_pad = if body.len() > 0 { 1 } else { 0 };
+    report.add(step, block, PaddingCause::SpanAlignment, pad_length + pre_pad);
+}
+
+/// Records NOOPs which the parser of an instruction inserted into `op_codes` starting at index
+/// `start`: a sequence of NOOPs is considered padding if it ends right before a PUSH operation
+/// on a step which is a multiple of 8, or right before a step which is a multiple of 16 (where
+/// hashing starts). NOOPs emitted by the `noop` instruction do not satisfy either condition.
+fn add_op_padding(op_codes: &[OpCode], start: usize, step: usize, block: usize, report: &mut PaddingReport) {
+    let mut num_noops = 0;
+    for i in start..op_codes.len() {
+        if op_codes[i] == OpCode::Noop {
+            num_noops += 1;
+            continue;
+        }
+        if op_codes[i] == OpCode::Push && i % OpCode::Push.spec().alignment == 0 {
+            report.add(step, block, PaddingCause::PushAlignment, num_noops);
+        }
+        else if i % BASE_CYCLE_LENGTH == 0 {
+            report.add(step, block, PaddingCause::HashAlignment, num_noops);
+        }
+        num_noops = 0;
+    }
+}
+
 fn repeat_block_sequence(template: Vec<ProgramBlock>, num_iterations: usize) -> Vec<ProgramBlock> {
     let mut body = Vec::with_capacity(template.len() * num_iterations);
 
@@ -0,0 +1,123 @@
+use crate::HACC_NUM_ROUNDS;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of NOOPs executed by the VM when a nested block is closed: a NOOP which aligns the end
+/// of the block, the operation which ends the block, and the rounds merging the hash of the block
+/// into the hash of its parent.
+pub const BLOCK_CLOSE_NOOPS: usize = HACC_NUM_ROUNDS + 2;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Reason for which NOOP operations were added to a program.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PaddingCause {
+    /// Span blocks are padded so that their length is one less than a multiple of 16; this
+    /// includes the implicit false branch of an `if.true` block without `else`.
+    SpanAlignment,
+    /// PUSH operations must be executed on steps which are multiples of 8.
+    PushAlignment,
+    /// Hashing sequences (e.g. in `hash`, `smpath`, `pmpath` instructions) must start on steps
+    /// which are multiples of 16.
+    HashAlignment,
+    /// A body which starts with a nested block is preceded by a Span block consisting of NOOPs.
+    EmptySpan,
+    /// The VM executes NOOPs when a nested block is closed (see `BLOCK_CLOSE_NOOPS`); for loops,
+    /// these are executed once when the loop is exited.
+    BlockClose,
+}
+
+/// A sequence of NOOPs added to a program; `step` is the position of the instruction which
+/// caused the padding, and `block` is the position of the head of the block which contains it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Padding {
+    pub step        : usize,
+    pub block       : usize,
+    pub cause       : PaddingCause,
+    pub num_noops   : usize,
+}
+
+/// Lists all NOOPs which the compiler added to a program, as returned by
+/// `assembly::compile_with_padding_report()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PaddingReport {
+    paddings    : Vec<Padding>,
+}
+
+// PADDING REPORT IMPLEMENTATION
+// ================================================================================================
+impl PaddingReport {
+
+    /// Returns all paddings in the order in which they were added to the program.
+    pub fn paddings(&self) -> &[Padding] {
+        return &self.paddings;
+    }
+
+    /// Returns the total number of NOOPs added to the program.
+    pub fn total(&self) -> usize {
+        return self.paddings.iter().map(|p| p.num_noops).sum();
+    }
+
+    /// Returns the total number of NOOPs added for the specified `cause`.
+    pub fn total_for(&self, cause: PaddingCause) -> usize {
+        return self.paddings.iter().filter(|p| p.cause == cause).map(|p| p.num_noops).sum();
+    }
+
+    /// Returns the total number of NOOPs added to every block, sorted by the position of the
+    /// head of the block; paddings of nested blocks are not included in the totals of their
+    /// parents.
+    pub fn block_totals(&self) -> Vec<(usize, usize)> {
+        let mut totals: Vec<(usize, usize)> = Vec::new();
+        for padding in self.paddings.iter() {
+            match totals.iter_mut().find(|(block, _)| *block == padding.block) {
+                Some(total) => total.1 += padding.num_noops,
+                None => totals.push((padding.block, padding.num_noops)),
+            }
+        }
+        totals.sort();
+        return totals;
+    }
+
+    pub(super) fn add(&mut self, step: usize, block: usize, cause: PaddingCause, num_noops: usize) {
+        if num_noops > 0 {
+            self.paddings.push(Padding { step, block, cause, num_noops });
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        return self.paddings.len();
+    }
+
+    /// Multiplies the number of NOOPs in all paddings starting with the one at index `first` by
+    /// `num_iterations`; this is used when the body of a `repeat` block is expanded.
+    pub(super) fn repeat(&mut self, first: usize, num_iterations: usize) {
+        for padding in self.paddings[first..].iter_mut() {
+            padding.num_noops *= num_iterations;
+        }
+    }
+
+    /// Maps steps of all paddings from steps in the expanded token stream to steps in the
+    /// original source code.
+    pub(super) fn map_steps(mut self, source_map: &[usize]) -> PaddingReport {
+        for padding in self.paddings.iter_mut() {
+            padding.step = source_map.get(padding.step).copied().unwrap_or(padding.step);
+            padding.block = source_map.get(padding.block).copied().unwrap_or(padding.block);
+        }
+        return self;
+    }
+}
+
+impl std::fmt::Display for PaddingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for padding in self.paddings.iter() {
+            writeln!(f, "{} NOOPs at {} in block at {}: {:?}",
+                padding.num_noops, padding.step, padding.block, padding.cause)?;
+        }
+        for (block, total) in self.block_totals() {
+            writeln!(f, "block at {}: {} NOOPs", block, total)?;
+        }
+        write!(f, "total: {} NOOPs", self.total())
+    }
+}
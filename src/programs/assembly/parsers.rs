@@ -1,11 +1,25 @@
-use crate::{ math::field };
+use std::collections::{ HashMap, VecDeque };
+use crate::{ math::field, utils::codec, HASH_NUM_ROUNDS };
 use super::{ AssemblyError, HintMap, OpCode, OpHint };
 
 // CONSTANTS
 // ================================================================================================
-const PUSH_OP_ALIGNMENT: usize = 8;
+const PUSH_OP_ALIGNMENT: usize = OpCode::Push.spec().alignment;
 const HASH_OP_ALIGNMENT: usize = 16;
 
+/// Maximum number of bytes read.bytes can read; the chunks of the string must fit on the stack.
+const MAX_READ_BYTES: usize = 16 * codec::BYTES_PER_ELEMENT;
+const MAX_READ_KEY_VALUES: usize = 16;
+
+/// Maximum number of values sortnet.n can sort; values beyond the top 16 items of the stack
+/// cannot be reached by stack manipulation operations.
+const MAX_SORTNET_VALUES: usize = 16;
+
+/// Field elements are split into 64-bit halves as x = hi * 2^64 + lo.
+const HALF_BASE: u128 = 1 << 64;
+const MODULUS_HI: u128 = field::MODULUS >> 64;
+const MODULUS_LO: u128 = field::MODULUS & (HALF_BASE - 1);
+
 // CONTROL FLOW OPERATIONS
 // ================================================================================================
 
@@ -29,9 +43,12 @@ pub fn parse_assert(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Resu
     else if op[1] == "eq" {
         program.push(OpCode::AssertEq);
     }
+    else if op[1] == "bin" {
+        program.push(OpCode::AssertBin);
+    }
     else {
         return Err(AssemblyError::invalid_param_reason(op, step,
-            format!("parameter {} is invalid; allowed values are: [eq]", op[1])));
+            format!("parameter {} is invalid; allowed values are: [eq, bin]", op[1])));
     }
     
     return Ok(true);
@@ -62,7 +79,16 @@ fn append_push_op(program: &mut Vec<OpCode>, hints: &mut HintMap, value: u128) {
 }
 
 /// Appends either READ or READ2 operation to the program.
-pub fn parse_read(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+pub fn parse_read(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    // read.bytes.n reads a byte string encoded with utils::codec::bytes_to_elements()
+    if op.len() > 1 && op[1] == "bytes" {
+        return parse_read_bytes(program, hints, op, step);
+    }
+    // read.key.n reads values associated with the key at the top of the stack
+    if op.len() > 1 && op[1] == "key" {
+        return parse_read_key(program, hints, op, step);
+    }
+
     if op.len() > 2 {
         return Err(AssemblyError::extra_param(op, step));
     }
@@ -74,7 +100,67 @@ pub fn parse_read(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
     }
     else {
         return Err(AssemblyError::invalid_param_reason(op, step,
-            format!("parameter {} is invalid; allowed values are: [a, ab]", op[1])));
+            format!("parameter {} is invalid; allowed values are: [a, ab, bytes, key]", op[1])));
+    }
+
+    return Ok(true);
+}
+
+/// Appends a sequence of n READ operations to the program; the first operation is marked with
+/// a hint which puts values associated with the key at the top of the stack in the advice map
+/// of program inputs onto input tape A. The key is left on the stack below the values, and the
+/// values are pushed onto the stack in the order in which they were associated with the key.
+fn parse_read_key(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 3 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 3 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let n = match op[2].parse::<usize>() {
+        Ok(n) if n > 0 && n <= MAX_READ_KEY_VALUES => n,
+        _ => return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 1 and {}", op[2], MAX_READ_KEY_VALUES)))
+    };
+
+    hints.insert(program.len(), OpHint::AdviceKey(n as u32));
+    program.resize(program.len() + n, OpCode::Read);
+
+    return Ok(true);
+}
+
+/// Appends a sequence of operations to the program to read a byte string of n bytes from input
+/// tape A. The string must be encoded as described in `utils::codec::bytes_to_elements()`: the
+/// length element is checked against n and is dropped, and every chunk is range-checked so that
+/// it cannot contain more bytes than it should. The chunks are left on the stack in the order
+/// in which they were read (i.e. the last chunk ends up at the top of the stack).
+fn parse_read_bytes(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 3 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 3 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let n = match op[2].parse::<usize>() {
+        Ok(n) if n > 0 && n <= MAX_READ_BYTES => n,
+        _ => return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 1 and {}", op[2], MAX_READ_BYTES)))
+    };
+
+    // read the length of the string and make sure it is equal to n
+    program.push(OpCode::Read);
+    append_push_op(program, hints, n as u128);
+    program.push(OpCode::AssertEq);
+
+    // read the chunks making sure each chunk fits into the expected number of bytes
+    for i in 0..codec::num_chunks(n) {
+        let chunk_bytes = std::cmp::min(codec::BYTES_PER_ELEMENT, n - i * codec::BYTES_PER_ELEMENT);
+        let num_bits = (chunk_bytes * 8).to_string();
+        program.extend_from_slice(&[OpCode::Read, OpCode::Dup]);
+        parse_rc(program, hints, &["rc", &num_bits], step)?;
+        program.push(OpCode::Assert);
     }
 
     return Ok(true);
@@ -91,8 +177,9 @@ pub fn parse_dup(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<
         2 => program.push(OpCode::Dup2),
         3 => program.extend_from_slice(&[OpCode::Dup4, OpCode::Roll4, OpCode::Drop]),
         4 => program.push(OpCode::Dup4),
+        8 => program.push(OpCode::Dup8),
         _ => return Err(AssemblyError::invalid_param_reason(op, step,
-            format!("parameter {} is invalid; allowed values are: [1, 2, 3, 4]", n)))
+            format!("parameter {} is invalid; allowed values are: [1, 2, 3, 4, 8]", n)))
     };
 
     return Ok(true);
@@ -143,8 +230,8 @@ pub fn parse_drop(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
         4 => program.push(OpCode::Drop4),
         5 => program.extend_from_slice(&[OpCode::Drop, OpCode::Drop4]),
         6 => program.extend_from_slice(&[OpCode::Drop, OpCode::Drop, OpCode::Drop4]),
-        7 => program.extend_from_slice(&[OpCode::Dup, OpCode::Drop4, OpCode::Drop4]),
-        8 => program.extend_from_slice(&[OpCode::Drop4, OpCode::Drop4]),
+        7 => program.extend_from_slice(&[OpCode::Dup, OpCode::Drop8]),
+        8 => program.push(OpCode::Drop8),
         _ => return Err(AssemblyError::invalid_param_reason(op, step,
             format!("parameter {} is invalid; allowed values are: [1, 2, 3, 4, 5, 6, 7, 8]", n)))
     }
@@ -160,8 +247,9 @@ pub fn parse_swap(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
         1 => program.push(OpCode::Swap),
         2 => program.push(OpCode::Swap2),
         4 => program.push(OpCode::Swap4),
+        8 => program.push(OpCode::Swap8),
         _ => return Err(AssemblyError::invalid_param_reason(op, step,
-            format!("parameter {} is invalid; allowed values are: [1, 2, 4]", n)))
+            format!("parameter {} is invalid; allowed values are: [1, 2, 4, 8]", n)))
     }
 
     return Ok(true);
@@ -180,6 +268,49 @@ pub fn parse_roll(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result
     return Ok(true);
 }
 
+/// Appends a sequence of SWAP, SWAP2, SWAP4, ROLL4, and ROLL8 operations to the program to
+/// rearrange the top k values of the stack; the parameters specify which of the top values ends
+/// up at each of the top k positions. For example, `perm.3.0.1.2` is equivalent to `roll.4`.
+/// The sequence is the shortest one found by a breadth-first search over all arrangements of
+/// the top 4 values (if k is at most 4) or of the top 8 values.
+pub fn parse_perm(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 3 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 9 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    // parse the permutation and make sure every position is used exactly once
+    let k = op.len() - 1;
+    let mut permutation = [0u8, 1, 2, 3, 4, 5, 6, 7];
+    let mut used = [false; 8];
+    for i in 0..k {
+        let index = match op[i + 1].parse::<usize>() {
+            Ok(index) if index < k => index,
+            Ok(_) => return Err(AssemblyError::invalid_param_reason(op, step,
+                format!("parameter {} is invalid; allowed values are between 0 and {}", op[i + 1], k - 1))),
+            Err(_) => return Err(AssemblyError::invalid_param(op, step)),
+        };
+        if used[index] {
+            return Err(AssemblyError::invalid_param_reason(op, step,
+                format!("parameter {} is used more than once", index)));
+        }
+        used[index] = true;
+        permutation[i] = index as u8;
+    }
+
+    let op_codes = find_permutation_ops(if k <= 4 { 4 } else { 8 }, |state| *state == permutation);
+    if op_codes.is_empty() {
+        program.push(OpCode::Noop);
+    }
+    else {
+        program.extend_from_slice(&op_codes);
+    }
+
+    return Ok(true);
+}
+
 // ARITHMETIC AND BOOLEAN OPERATIONS
 // ================================================================================================
 
@@ -211,6 +342,122 @@ pub fn parse_div(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<
     return Ok(true);
 }
 
+/// Appends a sequence of operations to the program to perform integer division of the 2nd item
+/// on the stack by the 1st item; the quotient and the remainder are provided by the prover via
+/// input tape A, and are then verified on the stack.
+pub fn parse_divmod(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    // n is the number of bits sufficient to represent each value; n cannot exceed 64 to make
+    // sure that q * b + r cannot overflow the field modulus
+    let n = read_param(op, step)?;
+    if n < 4 || n > 64 {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 4 and 64", n)))
+    }
+
+    // read the remainder and the quotient from tape A
+    hints.insert(program.len(), OpHint::DivModStart);
+    program.extend_from_slice(&[OpCode::Read, OpCode::Read]);
+
+    // make sure q * b + r = a
+    program.extend_from_slice(&[
+        OpCode::Dup4,  OpCode::Roll4, OpCode::Roll4, OpCode::Roll4, OpCode::Swap,
+        OpCode::Roll4, OpCode::Mul,   OpCode::Add,   OpCode::AssertEq
+    ]);
+
+    // make sure r < b; this also makes sure that r and b fit into n bits
+    program.extend_from_slice(&[OpCode::Roll4, OpCode::Roll4, OpCode::Roll4, OpCode::Dup2]);
+    parse_lt(program, hints, op, step)?;
+    program.push(OpCode::Assert);
+
+    // make sure q fits into n bits
+    program.extend_from_slice(&[OpCode::Roll4, OpCode::Dup]);
+    parse_rc(program, hints, op, step)?;
+    program.push(OpCode::Assert);
+
+    // drop the original values from the stack
+    program.extend_from_slice(&[OpCode::Swap2, OpCode::Drop, OpCode::Drop]);
+    return Ok(true);
+}
+
+/// Appends a sequence of operations to the program to reduce the top item on the stack modulo
+/// the constant m; the quotient and the remainder are provided by the prover via input tape A
+/// in the same way as for divmod, and only the remainder is left on the stack.
+pub fn parse_umod(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    let m = read_value(op, step)?;
+    if m < 2 || m > u64::MAX as u128 {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 2 and {}", m, u64::MAX)));
+    }
+
+    // divide the value by m using 64-bit division; this also makes sure that the value fits
+    // into 64 bits, and then drop the quotient
+    append_push_op(program, hints, m);
+    parse_divmod(program, hints, &["divmod", "64"], step)?;
+    program.push(OpCode::Drop);
+    return Ok(true);
+}
+
+/// Appends a sequence of operations to the program to split the top item on the stack into its
+/// high and low 64-bit halves; the low half ends up on the top of the stack. The halves are
+/// provided by the prover via input tape A in the same way as for divmod with divisor 2^64.
+pub fn parse_split(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() > 1 { return Err(AssemblyError::extra_param(op, step)); }
+
+    // read hi and lo from tape A
+    append_push_op(program, hints, HALF_BASE);
+    hints.insert(program.len(), OpHint::DivModStart);
+    program.extend_from_slice(&[OpCode::Read, OpCode::Read]);
+
+    // make sure hi * 2^64 + lo = x
+    program.extend_from_slice(&[
+        OpCode::Dup4,  OpCode::Roll4, OpCode::Roll4, OpCode::Roll4, OpCode::Swap,
+        OpCode::Roll4, OpCode::Mul,   OpCode::Add,   OpCode::AssertEq
+    ]);
+
+    // make sure the halves are valid, and drop 2^64 and x from the stack
+    program.push(OpCode::Swap);
+    append_halves_check(program, hints, step)?;
+    program.extend_from_slice(&[OpCode::Swap2, OpCode::Drop, OpCode::Drop]);
+    return Ok(true);
+}
+
+/// Appends a sequence of operations to the program to combine the top two items on the stack
+/// into a single value computed as hi * 2^64 + lo, where lo is the item at the top of the stack.
+pub fn parse_join(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() > 1 { return Err(AssemblyError::extra_param(op, step)); }
+
+    append_halves_check(program, hints, step)?;
+    program.push(OpCode::Swap);
+    append_push_op(program, hints, HALF_BASE);
+    program.extend_from_slice(&[OpCode::Mul, OpCode::Add]);
+    return Ok(true);
+}
+
+/// Appends a sequence of operations which makes sure that the top two items on the stack, lo and
+/// hi, are 64-bit values and that hi * 2^64 + lo does not exceed the field modulus; thus, every
+/// field element has exactly one valid pair of halves. The stack is left unchanged.
+fn append_halves_check(program: &mut Vec<OpCode>, hints: &mut HintMap, step: usize) -> Result<(), AssemblyError> {
+    // make sure hi fits into 64 bits
+    program.extend_from_slice(&[OpCode::Dup2, OpCode::Swap, OpCode::Dup]);
+    parse_rc(program, hints, &["rc", "64"], step)?;
+    program.push(OpCode::Assert);
+
+    // lo must be smaller than 2^64, unless hi is the high half of the modulus, in which case lo
+    // must be smaller than the low half of the modulus
+    append_push_op(program, hints, MODULUS_HI);
+    parse_eq(program, hints, &["eq"], step)?;
+    append_push_op(program, hints, HALF_BASE - MODULUS_LO);
+    program.extend_from_slice(&[OpCode::Mul, OpCode::Neg]);
+    append_push_op(program, hints, HALF_BASE);
+    program.push(OpCode::Add);
+
+    // make sure lo is smaller than the bound computed above
+    program.push(OpCode::Swap);
+    parse_lt(program, hints, &["lt", "65"], step)?;
+    program.push(OpCode::Assert);
+    return Ok(());
+}
+
 /// Appends NEG operation to the program.
 pub fn parse_neg(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
     if op.len() > 1 { return Err(AssemblyError::extra_param(op, step)); }
@@ -218,10 +465,27 @@ pub fn parse_neg(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<
     return Ok(true);
 }
 
-/// Appends INV operation to the program.
+/// Appends INV operation to the program; if a parameter n is provided, appends a sequence of
+/// operations which replaces each of the top n items on the stack with its multiplicative inverse.
 pub fn parse_inv(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
-    if op.len() > 1 { return Err(AssemblyError::extra_param(op, step)); }
-    program.push(OpCode::Inv);
+    if op.len() == 1 {
+        program.push(OpCode::Inv);
+        return Ok(true);
+    }
+
+    // inv.n inverts each of the top n items in place; INV is a single-cycle operation, and so,
+    // rotating the items through the top of the stack is cheaper than batch inversion via
+    // running products (which would require 3 multiplications per item)
+    let n = read_param(op, step)?;
+    match n {
+        1 => program.push(OpCode::Inv),
+        2 => program.extend_from_slice(&[OpCode::Inv, OpCode::Swap, OpCode::Inv, OpCode::Swap]),
+        4 => for _ in 0..4 { program.extend_from_slice(&[OpCode::Inv, OpCode::Roll4]); },
+        8 => for _ in 0..8 { program.extend_from_slice(&[OpCode::Inv, OpCode::Roll8]); },
+        _ => return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; allowed values are: [1, 2, 4, 8]", n)))
+    }
+
     return Ok(true);
 }
 
@@ -333,6 +597,11 @@ pub fn parse_lt(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], ste
 /// Appends a sequence of operations to the program to determine whether the top value on the 
 /// stack can be represented with n bits.
 pub fn parse_rc(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    // rc.range.lo.hi checks the value against an arbitrary interval
+    if op.len() > 1 && op[1] == "range" {
+        return parse_rc_range(program, hints, op, step);
+    }
+
     // n is the number of bits against which to test the binary decomposition
     let n = read_param(op, step)?;
     if n < 4 || n > 128 {
@@ -358,6 +627,41 @@ pub fn parse_rc(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], ste
     return Ok(true);
 }
 
+/// Appends a sequence of operations to the program to determine whether the top value on the
+/// stack is within the closed interval [lo, hi].
+fn parse_rc_range(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 4 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 4 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let lo = parse_value(op, 2, step)?;
+    let hi = parse_value(op, 3, step)?;
+    if lo > hi {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("lower bound {} cannot be greater than upper bound {}", lo, hi)));
+    }
+
+    // comparisons are done using 128 bits so that they cannot fail for any field element
+    let cmp_op = ["lt", "128"];
+
+    // compute x >= lo as NOT(x < lo)
+    program.push(OpCode::Dup);
+    append_push_op(program, hints, lo);
+    program.push(OpCode::Swap);
+    parse_lt(program, hints, &cmp_op, step)?;
+    program.extend_from_slice(&[OpCode::Not, OpCode::Swap]);
+
+    // compute x <= hi as NOT(x > hi), and combine the results
+    append_push_op(program, hints, hi);
+    program.push(OpCode::Swap);
+    parse_gt(program, hints, &cmp_op, step)?;
+    program.extend_from_slice(&[OpCode::Not, OpCode::And]);
+    return Ok(true);
+}
+
 /// Appends a sequence of operations to the program to determine whether the top value on the 
 /// stack is odd.
 pub fn parse_isodd(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
@@ -407,6 +711,119 @@ pub fn parse_choose(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Resu
     return Ok(true);
 }
 
+// SORTING OPERATIONS
+// ================================================================================================
+
+/// Appends a sequence of operations to the program to sort the top n values of the stack in
+/// ascending order (the smallest value ends up at the top of the stack). The values are sorted
+/// by Batcher's odd-even merge sorting network; every comparator of the network moves a pair of
+/// values to the top of the stack, checks whether they are in order using the same sequence of
+/// CMP operations as `lt.b` does (binary decompositions of the values are supplied as hints and
+/// are validated by CMP constraints), and puts them in order with CSWAP2 operation.
+///
+/// n can be between 2 and 16; optional parameter b is the number of bits sufficient to represent
+/// each of the values (128 by default). Since only the top 8 items of the stack can be rearranged
+/// directly, sorting more than 8 values is done over 16 items: the values are padded with zeros
+/// at the top of the stack, the halves of the 16 items are exchanged with SWAP8 operation, and
+/// values in different halves are brought together by shifting the stack by one item.
+pub fn parse_sortnet(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 2 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 3 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    let n = match op[1].parse::<usize>() {
+        Ok(n) if n >= 2 && n <= MAX_SORTNET_VALUES => n,
+        Ok(n) => return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 2 and {}", n, MAX_SORTNET_VALUES))),
+        Err(_) => return Err(AssemblyError::invalid_param(op, step)),
+    };
+    let num_bits = if op.len() == 3 {
+        match op[2].parse::<u32>() {
+            Ok(b) if b >= 4 && b <= 128 => b,
+            Ok(b) => return Err(AssemblyError::invalid_param_reason(op, step,
+                format!("parameter {} is invalid; value must be between 4 and 128", b))),
+            Err(_) => return Err(AssemblyError::invalid_param(op, step)),
+        }
+    }
+    else {
+        128
+    };
+    let num_bits = num_bits.to_string();
+    let lt_op = ["lt", num_bits.as_str()];
+
+    // the values are padded with zeros to 4, 8, or 16 items so that all stack manipulation
+    // operations have enough items to work with; the layout lists labels of these items: the
+    // values are labeled by their positions in the sorted order, and the zeros by labels
+    // starting with n
+    let width = if n <= 4 { 4 } else if n <= 8 { 8 } else { 16 };
+    let num_pads = width - n;
+    let mut layout: Vec<usize> = (0..width).map(|p| if p < num_pads { n + p } else { p - num_pads }).collect();
+    if num_pads > 0 {
+        parse_pad(program, &["pad", &num_pads.to_string()], step)?;
+    }
+
+    for (i, j) in get_sorting_network(n) {
+        let pi = layout.iter().position(|&label| label == i).unwrap();
+        let pj = layout.iter().position(|&label| label == j).unwrap();
+        if width <= 8 || (pi < 8) == (pj < 8) {
+            // both values are in the same half; move them to the top of the stack with the
+            // smaller label first, so that the smaller value ends up under the smaller label
+            if pi >= 8 {
+                append_swap8(program, &mut layout);
+            }
+            append_arrangement(program, &mut layout, |labels| labels[0] == i && labels[1] == j);
+            append_compare_exchange(program, hints, &lt_op, step)?;
+        }
+        else {
+            // the values are in different halves; move the value from the upper half to
+            // position 7, and the value from the lower half to position 8
+            let (x, y) = if pi < 8 { (i, j) } else { (j, i) };
+            append_arrangement(program, &mut layout, |labels| labels[7] == x);
+            append_swap8(program, &mut layout);
+            append_arrangement(program, &mut layout, |labels| labels[0] == y);
+            append_swap8(program, &mut layout);
+
+            // shifting the stack by one item moves both values to the lower half; thus, they
+            // end up at the top of the stack after the halves are exchanged
+            program.extend_from_slice(&[OpCode::Dup, OpCode::Swap8]);
+            append_compare_exchange(program, hints, &lt_op, step)?;
+            program.extend_from_slice(&[OpCode::Swap8, OpCode::Drop]);
+            layout[7] = i;
+            layout[8] = j;
+        }
+    }
+
+    // put the sorted values in order, and remove the padding
+    let is_sorted = |p: usize, label: usize| if p < num_pads { label >= n } else { label == p - num_pads };
+    if width == 16 {
+        // exchange values between the halves until every value is in the right half
+        let is_upper = |label: usize| label >= n || label + num_pads < 8;
+        while let Some(x) = layout[..8].iter().copied().find(|&label| !is_upper(label)) {
+            let y = layout[8..].iter().copied().find(|&label| is_upper(label)).unwrap();
+            append_arrangement(program, &mut layout, |labels| labels[7] == x);
+            append_swap8(program, &mut layout);
+            append_arrangement(program, &mut layout, |labels| labels[0] == y);
+            append_swap8(program, &mut layout);
+            program.extend_from_slice(&[OpCode::Dup, OpCode::Swap8, OpCode::Swap, OpCode::Swap8, OpCode::Drop]);
+            layout.swap(7, 8);
+        }
+
+        // put the values in the lower half in order
+        append_swap8(program, &mut layout);
+        append_arrangement(program, &mut layout, |labels| labels.iter().enumerate().all(|(p, &l)| is_sorted(p + 8, l)));
+        append_swap8(program, &mut layout);
+    }
+    append_arrangement(program, &mut layout, |labels| labels.iter().enumerate().all(|(p, &l)| is_sorted(p, l)));
+    if num_pads > 0 {
+        parse_drop(program, &["drop", &num_pads.to_string()], step)?;
+    }
+
+    return Ok(true);
+}
+
 // CRYPTO OPERATIONS
 // ================================================================================================
 
@@ -483,6 +900,54 @@ pub fn parse_smpath(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Resu
     return Ok(true);
 }
 
+/// Appends a sequence of operations to the program to compute roots of Merkle authentication
+/// path for a tree of depth n for two leaves located at the same position in the tree. The old
+/// leaf is expected to be at the top of the stack followed by the new leaf; after the operations
+/// are executed, the stack will contain the old root followed by the new root.
+pub fn parse_mpath_update(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    let n = read_param(op, step)?;
+    if n < 2 || n > 256 {
+        return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; value must be between 2 and 256", n)))
+    }
+
+    // read the index bit and the sibling node from the tapes, and arrange the stack so that
+    // the sibling and the bit are positioned for hashing with both the old and the new nodes:
+    // [o1, o0, n1, n0] -> [s1, s0, o1, o0, b, 0, b, 0, s1, s0, n1, n0]
+    const READ_CYCLE: [OpCode; 9] = [
+        OpCode::Read2, OpCode::Read2, OpCode::Dup2,   OpCode::Swap4, OpCode::Dup2,
+        OpCode::Swap4, OpCode::Swap2, OpCode::CSwap2, OpCode::Pad2,
+    ];
+
+    // repeat the following cycle of operations once for each node in the path:
+    // 1. compute hash of the old node and its sibling
+    // 2. move the sibling and the index bit over the new node (the bit is the same for both)
+    // 3. compute hash of the new node and its sibling
+    // 4. read the next sibling and index bit, and prepare the stack for the next cycle
+    const HASH_CYCLE: [OpCode; 28] = [
+        OpCode::RescR, OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR, OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR, OpCode::RescR, OpCode::Drop4,  OpCode::Swap2,
+        OpCode::Swap4, OpCode::CSwap2, OpCode::Pad2,  OpCode::Noop,
+        OpCode::RescR, OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR, OpCode::RescR, OpCode::RescR,  OpCode::RescR,
+        OpCode::RescR, OpCode::RescR, OpCode::Drop4,  OpCode::Swap2,
+    ];
+
+    for _ in 0..(n - 1) {
+        program.extend_from_slice(&READ_CYCLE);
+
+        // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+        let alignment = program.len() % HASH_OP_ALIGNMENT;
+        let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+        program.resize(program.len() + pad_length, OpCode::Noop);
+
+        program.extend_from_slice(&HASH_CYCLE);
+    }
+
+    return Ok(true);
+}
+
 /// Appends a sequence of operations to the program to compute the root of Merkle authentication
 /// path for a tree of depth n. Leaf index is expected to be 3rd item from the top of the stack.
 pub fn parse_pmpath(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
@@ -537,9 +1002,169 @@ pub fn parse_pmpath(program: &mut Vec<OpCode>, hints: &mut HintMap, op: &[&str],
     return Ok(true);
 }
 
+/// Appends a sequence of operations to the program to either seed a pseudo-random number
+/// generator or to draw values from it. The generator is a Rescue sponge whose 6-element state
+/// is kept at the top of the stack:
+/// * prng.seed turns the top 2 values of the stack into the initial state of the sponge;
+/// * prng.next permutes the state and places the first 2 elements of the permuted state right
+///   below it, leaving the state at the top of the stack so that more values can be drawn.
+///
+/// The same sequence of values can be computed outside of the VM using `crypto::Prng`.
+pub fn parse_prng(program: &mut Vec<OpCode>, op: &[&str], step: usize) -> Result<bool, AssemblyError> {
+    if op.len() < 2 {
+        return Err(AssemblyError::missing_param(op, step));
+    }
+    else if op.len() > 2 {
+        return Err(AssemblyError::extra_param(op, step));
+    }
+
+    match op[1] {
+        "seed" => program.extend_from_slice(&[OpCode::Pad2, OpCode::Pad2]),
+        "next" => {
+            // pad with NOOPs to make sure hashing starts on a step which is a multiple of 16
+            let alignment = program.len() % HASH_OP_ALIGNMENT;
+            let pad_length = (HASH_OP_ALIGNMENT - alignment) % HASH_OP_ALIGNMENT;
+            program.resize(program.len() + pad_length, OpCode::Noop);
+
+            // permute the state with 10 rounds of Rescue
+            program.resize(program.len() + HASH_NUM_ROUNDS, OpCode::RescR);
+
+            // copy the first 2 elements of the state and move them below the state
+            program.push(OpCode::Dup2);
+            program.resize(program.len() + 6, OpCode::Roll8);
+        },
+        _ => return Err(AssemblyError::invalid_param_reason(op, step,
+            format!("parameter {} is invalid; allowed values are: [seed, next]", op[1])))
+    }
+
+    return Ok(true);
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 
+/// Returns the shortest sequence of operations which rearranges the top `width` items of the
+/// stack into an arrangement accepted by `is_target`; an arrangement lists for every position i
+/// the position at which the item now at position i was originally. `width` must be either 4
+/// or 8, and for width 4 only operations which affect the top 4 items are used.
+fn find_permutation_ops<F: Fn(&[u8; 8]) -> bool>(width: usize, is_target: F) -> Vec<OpCode> {
+    let op_codes: &[OpCode] = if width == 4 {
+        &[OpCode::Swap, OpCode::Swap2, OpCode::Roll4]
+    }
+    else {
+        &[OpCode::Swap, OpCode::Swap2, OpCode::Swap4, OpCode::Roll4, OpCode::Roll8]
+    };
+
+    // every state lists the original positions of the items currently at the top of the stack
+    let initial = [0u8, 1, 2, 3, 4, 5, 6, 7];
+    let mut visited: HashMap<[u8; 8], ([u8; 8], OpCode)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(initial);
+    let mut target = initial;
+    while let Some(state) = queue.pop_front() {
+        if is_target(&state) {
+            target = state;
+            break;
+        }
+        for &op_code in op_codes.iter() {
+            let next = apply_stack_op(state, op_code);
+            if next != initial && !visited.contains_key(&next) {
+                visited.insert(next, (state, op_code));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // walk back from the target to the initial state to recover the sequence
+    let mut result = Vec::new();
+    let mut state = target;
+    while state != initial {
+        let (previous, op_code) = visited[&state];
+        result.push(op_code);
+        state = previous;
+    }
+    result.reverse();
+    return result;
+}
+
+/// Applies a stack manipulation operation to the top 8 items of the stack.
+fn apply_stack_op(mut state: [u8; 8], op_code: OpCode) -> [u8; 8] {
+    match op_code {
+        OpCode::Swap  => state.swap(0, 1),
+        OpCode::Swap2 => { state.swap(0, 2); state.swap(1, 3); },
+        OpCode::Swap4 => { for i in 0..4 { state.swap(i, i + 4); } },
+        OpCode::Roll4 => state[..4].rotate_right(1),
+        OpCode::Roll8 => state.rotate_right(1),
+        _ => unreachable!("{} is not a stack permutation operation", op_code),
+    }
+    return state;
+}
+
+/// Appends a sequence of operations which puts the top 2 values of the stack in order: the
+/// smaller value ends up at the top, and the larger value right below it.
+fn append_compare_exchange(program: &mut Vec<OpCode>, hints: &mut HintMap, lt_op: &[&str], step: usize) -> Result<(), AssemblyError> {
+    // determine whether the values are in order: [a, b] -> [a < b, a, b, a, b]
+    program.extend_from_slice(&[OpCode::Dup2, OpCode::Dup2]);
+    parse_lt(program, hints, lt_op, step)?;
+
+    // arrange the stack for CSWAP2 as [a, b, b, a, a < b, a < b]; if the values are in order,
+    // the pairs are swapped, and in both cases the ordered pair ends up below the other one
+    program.push(OpCode::Dup);
+    const KINDS: [u8; 8] = [0, 0, 1, 2, 1, 2, 3, 4];
+    let op_codes = find_permutation_ops(8, |state| {
+        state.iter().map(|&p| KINDS[p as usize]).eq([1, 2, 2, 1, 0, 0, 3, 4].iter().copied())
+    });
+    program.extend_from_slice(&op_codes);
+    program.extend_from_slice(&[OpCode::CSwap2, OpCode::Drop, OpCode::Drop]);
+    return Ok(());
+}
+
+/// Appends the shortest sequence of operations which rearranges the top items of the stack so
+/// that their labels are accepted by `is_target`, and updates the `layout` accordingly; at most
+/// 8 items (or 4 items if the layout has only 4 labels) are rearranged.
+fn append_arrangement<F: Fn(&[usize]) -> bool>(program: &mut Vec<OpCode>, layout: &mut [usize], is_target: F) {
+    let width = usize::min(layout.len(), 8);
+    let op_codes = find_permutation_ops(width, |state| {
+        let labels: Vec<usize> = state[..width].iter().map(|&p| layout[p as usize]).collect();
+        is_target(&labels)
+    });
+
+    let state = op_codes.iter().fold([0u8, 1, 2, 3, 4, 5, 6, 7], |state, &op_code| apply_stack_op(state, op_code));
+    let labels: Vec<usize> = state[..width].iter().map(|&p| layout[p as usize]).collect();
+    layout[..width].copy_from_slice(&labels);
+    program.extend_from_slice(&op_codes);
+}
+
+/// Appends SWAP8 operation to the program, and exchanges the halves of the `layout`.
+fn append_swap8(program: &mut Vec<OpCode>, layout: &mut [usize]) {
+    program.push(OpCode::Swap8);
+    layout.rotate_left(8);
+}
+
+/// Returns comparators of Batcher's odd-even merge sorting network for n values; the network
+/// is built for the next power of two, and comparators of values beyond n are dropped (such
+/// values can be thought of as infinitely large, and so, these comparators never swap them).
+fn get_sorting_network(n: usize) -> Vec<(usize, usize)> {
+    let size = n.next_power_of_two();
+    let mut comparators = Vec::new();
+    let mut p = 1;
+    while p < size {
+        let mut k = p;
+        while k >= 1 {
+            for j in (k % p..size - k).step_by(2 * k) {
+                for i in 0..usize::min(k, size - j - k) {
+                    if (i + j) / (2 * p) == (i + j + k) / (2 * p) && i + j + k < n {
+                        comparators.push((i + j, i + j + k));
+                    }
+                }
+            }
+            k /= 2;
+        }
+        p *= 2;
+    }
+    return comparators;
+}
+
 fn read_param(op: &[&str], step: usize) -> Result<u32, AssemblyError> {
     if op.len() == 1 {
         // if no parameters were provided, assume parameter value 1
@@ -572,16 +1197,21 @@ fn read_value(op: &[&str], step: usize) -> Result<u128, AssemblyError> {
         return Err(AssemblyError::extra_param(op, step));
     }
 
-    let result = if op[1].starts_with("0x") {
+    return parse_value(op, 1, step);
+}
+
+/// Parses parameter at the specified `index` of the `op` into a field element.
+pub(super) fn parse_value(op: &[&str], index: usize, step: usize) -> Result<u128, AssemblyError> {
+    let result = if op[index].starts_with("0x") {
         // parse hexadecimal number
-        match u128::from_str_radix(&op[1][2..], 16) {
+        match u128::from_str_radix(&op[index][2..], 16) {
             Ok(i) => i,
             Err(_) => return Err(AssemblyError::invalid_param(op, step))
         }
     }
     else {
         // parse decimal number
-        match u128::from_str_radix(&op[1], 10) {
+        match u128::from_str_radix(&op[index], 10) {
             Ok(i) => i,
             Err(_) => return Err(AssemblyError::invalid_param(op, step))
         }
@@ -594,4 +1224,4 @@ fn read_value(op: &[&str], step: usize) -> Result<u128, AssemblyError> {
     }
 
     return Ok(result);
-}
\ No newline at end of file
+}
@@ -0,0 +1,47 @@
+// SEARCH MACROS
+// ================================================================================================
+
+/// Definition of `bsearch.n.d` macro which looks up a value in a sorted table committed to by a
+/// Merkle tree built with `utils::table::SortedTable`. Since macros must be defined at the start
+/// of a program, this source should be prepended to programs which use the macro.
+///
+/// The macro expects the stack to be `[x, r0, r1]`, where x is the value being searched for, and
+/// `[r0, r1]` is the root of the table as returned by `SortedTable::root()`. The index i of the
+/// table entry such that `a[i] <= x < a[i + 1]` is supplied by the prover via input tape `A`,
+/// together with `a[i]` and `a[i + 1]` (the latter on tape `B`) and the Merkle path for the pair;
+/// these inputs can be built using `SortedTable::build_tapes()`. The macro checks that x falls
+/// between the pair of values, and that the pair is the leaf at position i of the table; then it
+/// replaces x and the root with i.
+///
+/// Parameter n is the number of bits sufficient to represent x and all values in the table, and
+/// d is the depth of the table as returned by `SortedTable::depth()`.
+pub const BSEARCH: &str = "
+macro.bsearch.n.d
+    read.a swap read.ab perm.2.0.1
+    dup.3 lt.$n assert
+    swap lt.$n not assert
+    pick.2 perm.1.2.0 pmpath.$d
+    roll.4 perm.0.2.1 assert.eq
+    swap pad swap.2 assert.eq drop
+end
+";
+
+// COMMITMENT MACROS
+// ================================================================================================
+
+/// Definition of `commit_tape.n` macro which commits to n secret values so that any subset of
+/// them can later be revealed against the proof of execution; this source should be prepended
+/// to programs which use the macro.
+///
+/// The macro reads n values from input tape `A` and n salts from input tape `B`, and pushes the
+/// digest of the salted values onto the stack; the tapes and the digest can be computed using
+/// `utils::disclosure::TapeCommitment`. For the values to be bound by the proof, the digest must
+/// be among the outputs of the program. Parameter n must be at least 2.
+pub const COMMIT_TAPE: &str = "
+macro.commit_tape.n
+    pad.2
+    repeat.$n
+        read.ab swap hash.2 hash.4
+    end
+end
+";
@@ -0,0 +1,137 @@
+use super::{ AssemblyError, parsers::parse_value };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A test section of assembly source code; the body of the section is stored as a program, i.e.
+/// `begin <instructions> end`.
+pub struct TestSection {
+    pub step        : usize,
+    pub tokens      : Vec<String>,
+    pub source_map  : Vec<usize>,
+    pub expected    : Vec<u128>,
+}
+
+/// Result of running a single test section of assembly source code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    /// Position of the `test.begin` instruction in the source code.
+    pub step        : usize,
+    /// Values which the test expects at the top of the stack; the first value is the top item.
+    pub expected    : Vec<u128>,
+    /// As many items from the top of the stack as there are expected values, or an error if
+    /// the test body failed to execute.
+    pub outcome     : Result<Vec<u128>, String>,
+}
+
+// TEST RESULT IMPLEMENTATION
+// ================================================================================================
+impl TestResult {
+
+    /// Returns true if the test body was executed and left the expected values on the stack.
+    pub fn passed(&self) -> bool {
+        return self.outcome.as_ref() == Ok(&self.expected);
+    }
+}
+
+// TEST SECTIONS
+// ================================================================================================
+
+/// Removes test sections from the `source` token stream. A test section is written as
+/// `test.begin <instructions> expect.<v1>,<v2>,... end`, and it cannot be placed inside a
+/// program, a procedure, or another test section.
+///
+/// Returns the remaining tokens together with a source map which contains the index of every
+/// remaining token in the `source`, and the removed test sections; steps of the test sections
+/// are indexes in the `source` as well.
+pub fn strip_tests(source: &[String]) -> Result<(Vec<String>, Vec<usize>, Vec<TestSection>), AssemblyError> {
+
+    let mut tokens = Vec::with_capacity(source.len());
+    let mut source_map = Vec::with_capacity(source.len());
+    let mut tests = Vec::new();
+
+    let mut depth = 0;
+    let mut i = 0;
+    while i < source.len() {
+        let op: Vec<&str> = source[i].split(".").collect();
+        match op[0] {
+            "test" if depth > 0 => return Err(AssemblyError::misplaced_test(&op, i)),
+            "test" => {
+                let test = read_test(source, i)?;
+                i = test.source_map[test.source_map.len() - 1] + 1;
+                tests.push(test);
+                continue;
+            },
+            "expect" => return Err(AssemblyError::misplaced_expect(&op, i)),
+            "begin" | "proc" | "block" | "if" | "while" | "repeat" => depth += 1,
+            "end" if depth > 0 => depth -= 1,
+            _ => (),
+        }
+        tokens.push(source[i].clone());
+        source_map.push(i);
+        i += 1;
+    }
+
+    return Ok((tokens, source_map, tests));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Reads a test section starting at token `i`; nested blocks are tracked so that an `expect`
+/// instruction inside them is not mistaken for the end of the test body.
+fn read_test(source: &[String], mut i: usize) -> Result<TestSection, AssemblyError> {
+
+    // validate test head
+    let head: Vec<&str> = source[i].split(".").collect();
+    let step = i;
+    if head.len() != 2 || head[1] != "begin" {
+        return Err(AssemblyError::invalid_block_head(&head, step));
+    }
+
+    // read test body until expect instruction
+    let mut tokens = vec![String::from("begin")];
+    let mut source_map = vec![step];
+    let mut depth = 0;
+    i += 1;
+    let expect = loop {
+        if i == source.len() {
+            return Err(AssemblyError::unmatched_test(&head, step));
+        }
+
+        let op: Vec<&str> = source[i].split(".").collect();
+        match op[0] {
+            "begin" | "proc" | "test" => return Err(AssemblyError::misplaced_test(&op, i)),
+            "block" | "if" | "while" | "repeat" => depth += 1,
+            "expect" if depth == 0 => break op,
+            "expect" => return Err(AssemblyError::misplaced_expect(&op, i)),
+            "end" if depth == 0 => return Err(AssemblyError::unmatched_test(&head, step)),
+            "end" => depth -= 1,
+            _ => (),
+        }
+        tokens.push(source[i].clone());
+        source_map.push(i);
+        i += 1;
+    };
+
+    // parse expected values; these are separated by commas
+    if expect.len() == 1 {
+        return Err(AssemblyError::missing_param(&expect, i));
+    }
+    else if expect.len() > 2 {
+        return Err(AssemblyError::extra_param(&expect, i));
+    }
+    let mut expected = Vec::new();
+    for value in expect[1].split(",") {
+        expected.push(parse_value(&[expect[0], value], 1, i)?);
+    }
+
+    // the expect instruction must be followed by the end of the test
+    if source.get(i + 1).map(|t| t.as_str()) != Some("end") {
+        return Err(AssemblyError::unmatched_test(&head, step));
+    }
+    tokens.push(String::from("end"));
+    source_map.push(i + 1);
+
+    return Ok(TestSection { step, tokens, source_map, expected });
+}
@@ -400,4 +400,485 @@ fn repeat_2_blocks_with_suffix() {
     end";
 
     assert_eq!(expected, format!("{:?}", program));
-}
\ No newline at end of file
+}
+
+// FORMATTING
+// ================================================================================================
+#[test]
+fn format_blocks() {
+    let source = "begin  push.0x0A push.007 add if.true   mul else repeat.02 inv end end while.true drop end end";
+    let formatted = super::format(source).unwrap();
+
+    let expected = "\
+begin
+    push.0xa push.7 add
+    if.true
+        mul
+    else
+        repeat.2
+            inv
+        end
+    end
+    while.true
+        drop
+    end
+end
+";
+    assert_eq!(expected, formatted);
+
+    // formatting preserves the program and is idempotent
+    let program = super::compile(source).unwrap();
+    assert_eq!(program.hash(), super::compile(&formatted).unwrap().hash());
+    assert_eq!(formatted, super::format(&formatted).unwrap());
+}
+
+#[test]
+fn format_with_options() {
+    let source = "begin push.1 push.2 add push.3 mul end";
+
+    let options = super::FormatOptions { one_per_line: true, ..Default::default() };
+    let formatted = super::format_with_options(source, &options).unwrap();
+    assert_eq!("begin\n    push.1\n    push.2\n    add\n    push.3\n    mul\nend\n", formatted);
+
+    let options = super::FormatOptions { indent: 2, line_width: 15, one_per_line: false };
+    let formatted = super::format_with_options(source, &options).unwrap();
+    assert_eq!("begin\n  push.1 push.2\n  add push.3\n  mul\nend\n", formatted);
+
+    // invalid programs cannot be formatted
+    assert!(super::format("begin push.1 add").is_err());
+}
+
+#[test]
+fn format_conditional_sections() {
+    let source = "begin push.1 ifdef.007 push.02 ifdef.DEBUG push.0x0A endif endif add end";
+    let formatted = super::format(source).unwrap();
+
+    // flag names are not normalized, while instructions inside conditional sections are
+    let expected = "\
+begin
+    push.1
+    ifdef.007
+        push.2
+        ifdef.DEBUG
+            push.0xa
+        endif
+    endif
+    add
+end
+";
+    assert_eq!(expected, formatted);
+
+    // formatted code reduces to the same program for every combination of flags
+    for flags in [vec![], vec!["007"], vec!["DEBUG"], vec!["007", "DEBUG"]].iter() {
+        let original = super::compile_with_flags(source, flags).unwrap();
+        let formatted = super::compile_with_flags(&formatted, flags).unwrap();
+        assert_eq!(original.hash(), formatted.hash());
+    }
+}
+
+// CONSTANT CONDITIONS
+// ================================================================================================
+#[test]
+fn constant_if_conditions() {
+    // condition depends on the inputs
+    let (_, warnings) = super::compile_with_warnings("begin read if.true add else mul end end").unwrap();
+    assert!(warnings.is_empty());
+
+    // condition is always 0
+    let (_, warnings) = super::compile_with_warnings("begin push.0 if.true add else mul end end").unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!(2, warnings[0].step());
+    assert_eq!("condition is always 0: true branch is never executed", warnings[0].message());
+
+    // condition is always 1; constants are propagated through stack manipulations
+    let source = "begin push.1 push.0 swap dup drop if.true add else mul end end";
+    let (_, warnings) = super::compile_with_warnings(source).unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!("condition is always 1: false branch is never executed", warnings[0].message());
+
+    // without else, a condition which is always 1 is not reported
+    let (_, warnings) = super::compile_with_warnings("begin push.0 not if.true add end end").unwrap();
+    assert!(warnings.is_empty());
+
+    // non-binary condition
+    let (_, warnings) = super::compile_with_warnings("begin push.3 if.true add end end").unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!("if.true", warnings[0].operation());
+
+    // warnings are reported for nested blocks as well
+    let source = "begin read if.true push.0 if.true add end else mul end end";
+    let (_, warnings) = super::compile_with_warnings(source).unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!(4, warnings[0].step());
+}
+
+#[test]
+fn assert_bin() {
+    let program = super::compile("begin read assert.bin not end").unwrap();
+    let expected = "\
+        begin read assertbin not noop noop noop noop \
+        noop noop noop noop noop noop noop end";
+    assert_eq!(expected, format!("{:?}", program));
+
+    // constants are propagated through ASSERTBIN
+    let (_, warnings) = super::compile_with_warnings("begin push.0 assert.bin if.true add else mul end end").unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!("condition is always 0: true branch is never executed", warnings[0].message());
+
+    let err = super::compile("begin read assert.foo end").unwrap_err();
+    assert_eq!("malformed instruction assert: parameter foo is invalid; allowed values are: [eq, bin]", err.message());
+}
+
+#[test]
+fn constant_loop_conditions() {
+    // loop condition and loop body depend on the inputs
+    let (_, warnings) = super::compile_with_warnings("begin read while.true add read end end").unwrap();
+    assert!(warnings.is_empty());
+
+    // loop is never entered
+    let (_, warnings) = super::compile_with_warnings("begin push.0 while.true add read end end").unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!("condition is always 0: loop body is never executed", warnings[0].message());
+
+    // loop is never exited
+    let error = super::compile("begin read while.true add push.1 end end").err().unwrap();
+    assert_eq!(2, error.step());
+
+    // loop body which leaves 0 at the top of the stack is fine
+    assert!(super::compile("begin read while.true push.1 neg push.1 add end end").is_ok());
+}
+
+// MACROS
+// ================================================================================================
+#[test]
+fn macros() {
+    // macros are expanded in place, and their parameters are substituted with arguments
+    let source = "\
+        macro.add_n.n push.$n add end \
+        macro.double_add.a.b add_n.$a add_n.$b end \
+        begin double_add.3.0x5 repeat.2 add_n.7 end end";
+    let expected = super::compile("begin push.3 add push.0x5 add repeat.2 push.7 add end end").unwrap();
+    let program = super::compile(source).unwrap();
+    assert_eq!(expected.hash(), program.hash());
+    assert!(super::format(source).is_ok());
+
+    // macros can contain nested blocks
+    let source = "macro.select if.true add else mul end end begin read select end";
+    let expected = super::compile("begin read if.true add else mul end end").unwrap();
+    assert_eq!(expected.hash(), super::compile(source).unwrap().hash());
+
+    // warnings are reported at macro invocations
+    let source = "macro.skip push.0 if.true add end end begin add skip end";
+    let (_, warnings) = super::compile_with_warnings(source).unwrap();
+    assert_eq!(1, warnings.len());
+    assert_eq!(8, warnings[0].step());
+}
+
+#[test]
+fn macro_errors() {
+    // errors in expanded code are reported at macro invocations
+    let error = super::compile("macro.bad push.1 foo end begin add bad end").err().unwrap();
+    assert_eq!(6, error.step());
+    assert_eq!("instruction foo is invalid", error.message());
+
+    // invalid definitions
+    let error = super::compile("macro.add push.1 end begin add end").err().unwrap();
+    assert_eq!("invalid macro definition 'macro.add': 'add' is a reserved name", error.message());
+
+    let error = super::compile("macro.inc.n push.$m add end begin inc.1 end").err().unwrap();
+    assert_eq!("invalid macro definition 'macro.inc.n': parameter 'm' is not defined", error.message());
+
+    let error = super::compile("macro.inc push.1 add begin inc end").err().unwrap();
+    assert_eq!("invalid macro definition 'macro.inc': 'begin' is not allowed in a macro", error.message());
+
+    let error = super::compile("macro.inc push.1 add end macro.inc add end begin inc end").err().unwrap();
+    assert_eq!(4, error.step());
+
+    let error = super::compile("begin add end macro.inc push.1 add end").err().unwrap();
+    assert_eq!("macros must be defined before the program begins", error.message());
+
+    // invalid invocations
+    let error = super::compile("macro.inc.n push.$n add end begin inc end").err().unwrap();
+    assert_eq!("macro inc expects 1 numeric arguments, but 0 were provided", error.message());
+
+    let error = super::compile("macro.inc.n push.$n add end begin inc.x end").err().unwrap();
+    assert_eq!(5, error.step());
+
+    // recursive macros are rejected
+    let error = super::compile("macro.a b end macro.b a end begin a end").err().unwrap();
+    assert_eq!(7, error.step());
+    assert_eq!("expansion of macro a is nested more than 16 levels deep", error.message());
+}
+
+// CONDITIONAL ASSEMBLY
+// ================================================================================================
+#[test]
+fn conditional_assembly() {
+    let source = "begin push.1 ifdef.TESTING push.2 ifdef.DEBUG push.3 endif endif add end";
+
+    // without flags, all conditional sections are removed
+    let expected = super::compile("begin push.1 add end").unwrap();
+    assert_eq!(expected.hash(), super::compile(source).unwrap().hash());
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &[]).unwrap().hash());
+
+    // nested sections are kept only if all enclosing sections are kept
+    let expected = super::compile("begin push.1 push.2 add end").unwrap();
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &["TESTING"]).unwrap().hash());
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &["TESTING", "OTHER"]).unwrap().hash());
+
+    let expected = super::compile("begin push.1 push.2 push.3 add end").unwrap();
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &["DEBUG", "TESTING"]).unwrap().hash());
+
+    let expected = super::compile("begin push.1 add end").unwrap();
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &["DEBUG"]).unwrap().hash());
+
+    // conditional sections can contain macro definitions
+    let source = "ifdef.TESTING macro.check assert end endif begin read ifdef.TESTING check endif end";
+    let expected = super::compile("begin read assert end").unwrap();
+    assert_eq!(expected.hash(), super::compile_with_flags(source, &["TESTING"]).unwrap().hash());
+
+    // errors are reported at their positions in the source code
+    let error = super::compile("begin ifdef.TESTING push.1 endif foo end").err().unwrap();
+    assert_eq!(4, error.step());
+
+    let error = super::compile("begin ifdef.TESTING push.1 add end").err().unwrap();
+    assert_eq!("ifdef without matching endif", error.message());
+    assert_eq!(1, error.step());
+
+    let error = super::compile("begin push.1 endif add end").err().unwrap();
+    assert_eq!("endif without matching ifdef", error.message());
+
+    let error = super::compile("begin ifdef push.1 endif add end").err().unwrap();
+    assert_eq!(1, error.step());
+}
+
+#[test]
+fn procedures() {
+    let source = "macro.double dup add end \
+        proc.inc push.1 add end \
+        proc.double_inc double if.true push.1 add else push.2 add end end \
+        proc.square dup mul end";
+    let procedures = super::compile_procedures(source).unwrap();
+    assert_eq!(3, procedures.len());
+
+    // every procedure is the same as a program with the same body
+    let expected = [
+        super::compile("begin push.1 add end").unwrap(),
+        super::compile("begin dup add if.true push.1 add else push.2 add end end").unwrap(),
+        super::compile("begin dup mul end").unwrap(),
+    ];
+    let names = ["inc", "double_inc", "square"];
+    for (i, name, hash) in procedures.procedures() {
+        assert_eq!(Some(names[i]), name);
+        assert_eq!(expected[i].hash(), hash);
+    }
+
+    let (index, program) = procedures.find_by_name("square").unwrap();
+    assert_eq!(2, index);
+    assert_eq!(expected[2].hash(), program.hash());
+    assert_eq!(1, procedures.find_by_hash(expected[1].hash()).unwrap().0);
+    assert!(procedures.find_by_name("cube").is_none());
+    assert!(procedures.get(3).is_none());
+
+    // errors are reported at their positions in the source code
+    let error = super::compile_procedures("proc.inc push.1 add end begin push.1 end").err().unwrap();
+    assert_eq!("invalid procedure definition 'begin': expected a procedure head", error.message());
+    assert_eq!(4, error.step());
+
+    let error = super::compile_procedures("proc.inc push.1 add end proc.inc push.2 add end").err().unwrap();
+    assert_eq!("procedure inc is already defined", error.message());
+    assert_eq!(4, error.step());
+
+    let error = super::compile_procedures("proc push.1 end").err().unwrap();
+    assert_eq!("invalid procedure definition 'proc': procedure must have a valid name", error.message());
+
+    let error = super::compile_procedures("proc.inc push.1 proc.dec end").err().unwrap();
+    assert_eq!(2, error.step());
+
+    assert!(super::compile("macro.proc push.1 end begin proc end").is_err());
+}
+
+#[test]
+fn inline_tests() {
+    let source = "macro.inc.n push.$n add end \
+        test.begin push.2 inc.3 expect.5 end \
+        test.begin push.1 push.1 if.true inc.1 else inc.2 end expect.2,0 end \
+        begin inc.1 end \
+        test.begin push.7 dup mul expect.48 end \
+        test.begin push.0 assert expect.1 end";
+
+    // test sections are run in order, and their results are reported at test heads
+    let results = super::test(source).unwrap();
+    assert_eq!(vec![4, 9, 22, 28], results.iter().map(|r| r.step).collect::<Vec<_>>());
+    assert_eq!(vec![true, true, false, false], results.iter().map(|r| r.passed()).collect::<Vec<_>>());
+    assert_eq!(Ok(vec![2, 0]), results[1].outcome);
+    assert_eq!(Ok(vec![49]), results[2].outcome);
+    assert!(results[3].outcome.is_err());
+
+    // test sections are ignored when the program is compiled and formatted
+    let expected = super::compile("begin push.1 add end").unwrap();
+    assert_eq!(expected.hash(), super::compile(source).unwrap().hash());
+    assert!(super::format(source).is_ok());
+
+    let procedures = super::compile_procedures("proc.sq dup mul end test.begin push.3 dup mul expect.9 end").unwrap();
+    assert_eq!(1, procedures.len());
+
+    // errors are reported at their positions in the source code
+    let error = super::test("test.begin push.1 foo expect.1 end").err().unwrap();
+    assert_eq!("instruction foo is invalid", error.message());
+    assert_eq!(2, error.step());
+
+    let error = super::test("test.begin push.1 end").err().unwrap();
+    assert_eq!("test without matching expect and end", error.message());
+    assert_eq!(0, error.step());
+
+    let error = super::test("test.begin push.1 expect.1 add end").err().unwrap();
+    assert_eq!("test without matching expect and end", error.message());
+
+    let error = super::test("test.begin push.1 expect end").err().unwrap();
+    assert_eq!(2, error.step());
+
+    let error = super::test("test.begin push.1 expect.1,x end").err().unwrap();
+    assert_eq!(2, error.step());
+
+    let error = super::compile("begin push.1 test.begin push.1 expect.1 end end").err().unwrap();
+    assert_eq!("'test' is not allowed in a test or in a program", error.message());
+    assert_eq!(2, error.step());
+
+    let error = super::compile("begin push.1 expect.1 end").err().unwrap();
+    assert_eq!("expect must be the last instruction of a test", error.message());
+
+    let error = super::test("test.begin push.1 if.true push.1 expect.1 end expect.1 end").err().unwrap();
+    assert_eq!(4, error.step());
+}
+
+#[test]
+fn output_digest_epilogue() {
+    // the epilogue is appended to the program right before the last end
+    let source = "begin push.1 push.2 push.3 push.4 push.5 push.6 push.7 push.8 push.9 end";
+    let expected = super::compile("begin push.1 push.2 push.3 push.4 push.5 push.6 push.7 push.8 push.9
+        pad.2 roll.8 roll.8 swap.2 hash.4 roll.8 roll.8 swap.2 hash.4
+        pad.1 roll.8 swap.2 roll.4 drop hash.3 end").unwrap();
+    assert_eq!(expected.hash(), super::compile_with_output_digest(source, 9).unwrap().hash());
+
+    // errors in the source code are reported at their positions
+    let error = super::compile_with_output_digest("begin push.1 foo end", 9).err().unwrap();
+    assert_eq!(2, error.step());
+
+    // the epilogue is never skipped: programs which do not end with an end are rejected
+    let error = super::compile_with_output_digest("begin push.1", 9).err().unwrap();
+    assert_eq!("a program must end with an 'end' instruction", error.message());
+    assert_eq!("push.1", error.operation());
+
+    let error = super::compile_with_output_digest("", 9).err().unwrap();
+    assert_eq!("a program must contain at least one instruction", error.message());
+}
+
+#[test]
+fn output_digest_invalid_num_outputs() {
+    let error = super::compile_with_output_digest("begin push.1 end", 8).err().unwrap();
+    assert_eq!("number of outputs must be between 9 and 32, but received 8", error.message());
+
+    let error = super::compile_with_output_digest("begin push.1 end", 33).err().unwrap();
+    assert_eq!("number of outputs must be between 9 and 32, but received 33", error.message());
+}
+
+#[test]
+fn wide_stack_instructions() {
+    let program = super::compile("begin dup.8 swap.8 drop.8 drop.7 end").unwrap();
+    let expected = "\
+        begin dup8 swap8 drop8 dup drop8 noop noop noop \
+        noop noop noop noop noop noop end";
+    assert_eq!(expected, format!("{:?}", program));
+
+    let err = super::compile("begin swap.3 end").unwrap_err();
+    assert_eq!("malformed instruction swap: parameter 3 is invalid; allowed values are: [1, 2, 4, 8]", err.message());
+}
+
+#[test]
+fn perm_instruction() {
+    // permutations are compiled into the shortest sequences of existing instructions
+    let expected = super::compile("begin roll.4 end").unwrap();
+    assert_eq!(expected.hash(), super::compile("begin perm.3.0.1.2 end").unwrap().hash());
+
+    let expected = super::compile("begin swap.4 end").unwrap();
+    assert_eq!(expected.hash(), super::compile("begin perm.4.5.6.7.0.1.2.3 end").unwrap().hash());
+
+    let expected = super::compile("begin noop end").unwrap();
+    assert_eq!(expected.hash(), super::compile("begin perm.0.1.2 end").unwrap().hash());
+
+    // parameters must form a permutation of 2 to 8 positions
+    assert!(super::compile("begin perm end").is_err());
+    assert!(super::compile("begin perm.0 end").is_err());
+    assert!(super::compile("begin perm.0.0 end").is_err());
+    assert!(super::compile("begin perm.0.2 end").is_err());
+    assert!(super::compile("begin perm.0.a end").is_err());
+    assert!(super::compile("begin perm.0.1.2.3.4.5.6.7.8 end").is_err());
+}
+
+#[test]
+fn sortnet_instruction() {
+    // values of every supported count are sorted, and the items below them are not affected
+    let mut seed = 7u128;
+    for n in 2..=16 {
+        let mut values: Vec<u128> = (0..n).map(|_| { seed = (seed * 1103515245 + 12345) % 1000; seed }).collect();
+        values[n - 1] = values[0];
+        let mut inputs = values.clone();
+        inputs.extend_from_slice(&[1001, 1002]);
+
+        let program = super::compile(&format!("begin sortnet.{}.10 end", n)).unwrap();
+        let state = crate::processor::interpret(&program, &crate::ProgramInputs::from_public(&inputs)).unwrap();
+        values.sort();
+        assert_eq!(values, state.stack[..n].to_vec(), "failed to sort {} values", n);
+        assert_eq!(vec![1001, 1002], state.stack[n..(n + 2)].to_vec());
+    }
+
+    // the program executed by the VM produces the same result
+    let program = super::compile("begin repeat.10 read end sortnet.10.8 end").unwrap();
+    let inputs = crate::ProgramInputs::new(&[], &[9, 3, 200, 3, 0, 17, 255, 1, 42, 8], &[]);
+    let (outputs, proof) = crate::execute(&program, &inputs, 8, &crate::ProofOptions::default());
+    assert_eq!(vec![0, 1, 3, 3, 8, 9, 17, 42], outputs);
+    assert_eq!(Ok(true), crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+
+    // values which do not fit into the specified number of bits cannot be sorted
+    let program = super::compile("begin sortnet.3.4 end").unwrap();
+    assert!(crate::processor::interpret(&program, &crate::ProgramInputs::from_public(&[3, 16, 1])).is_err());
+
+    let err = super::compile("begin sortnet.17 end").unwrap_err();
+    assert_eq!("malformed instruction sortnet: parameter 17 is invalid; value must be between 2 and 16", err.message());
+    assert!(super::compile("begin sortnet end").is_err());
+    assert!(super::compile("begin sortnet.4.129 end").is_err());
+    assert!(super::compile("begin sortnet.4.8.1 end").is_err());
+}
+
+#[test]
+fn padding_report() {
+    use super::{ Padding, PaddingCause::* };
+
+    // every inserted sequence of NOOPs is reported at the instruction which caused it
+    let source = "begin push.3 push.5 add if.true hash.2 end end";
+    let (_, report) = super::compile_with_padding_report(source).unwrap();
+    let expected = vec![
+        Padding { step: 1, block: 0, cause: PushAlignment, num_noops: 7 },
+        Padding { step: 2, block: 0, cause: PushAlignment, num_noops: 7 },
+        Padding { step: 4, block: 0, cause: SpanAlignment, num_noops: 13 },
+        Padding { step: 5, block: 4, cause: HashAlignment, num_noops: 13 },
+        Padding { step: 6, block: 4, cause: SpanAlignment, num_noops: 4 },
+        Padding { step: 6, block: 4, cause: SpanAlignment, num_noops: 13 },
+        Padding { step: 6, block: 4, cause: BlockClose,    num_noops: 16 },
+    ];
+    assert_eq!(expected, report.paddings());
+    assert_eq!(vec![(0, 27), (4, 46)], report.block_totals());
+    assert_eq!(73, report.total());
+
+    // padding of a repeat block body is counted once for every iteration
+    let (program, report) = super::compile_with_padding_report("begin repeat.3 push.1 end end").unwrap();
+    assert_eq!(vec![(0, 14), (1, 60)], report.block_totals());
+    assert_eq!(14 + 3 * 14 + 2, report.total_for(SpanAlignment));
+    assert_eq!(super::compile("begin repeat.3 push.1 end end").unwrap().hash(), program.hash());
+
+    // steps are reported at their positions in the source code
+    let source = "macro.two push.1 push.2 end begin add two end";
+    let (_, report) = super::compile_with_padding_report(source).unwrap();
+    assert_eq!(vec![6, 6, 7], report.paddings().iter().map(|p| p.step).collect::<Vec<_>>());
+}
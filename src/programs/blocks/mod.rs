@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::hash::{ Hash, Hasher };
+use crate::math::field;
 use super::{ OpCode, OpHint, hash_seq, hash_op, BASE_CYCLE_LENGTH };
 
 #[cfg(test)]
@@ -26,7 +28,7 @@ const LOOP_BLOCK_SUFFIX: [u8; 16] = [
 // TYPES AND INTERFACES
 // ================================================================================================
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum ProgramBlock {
     Span(Span),
     Group(Group),
@@ -40,18 +42,18 @@ pub struct Span {
     op_hints    : HashMap<usize, OpHint>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Group {
     body        : Vec<ProgramBlock>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Switch {
     t_branch    : Vec<ProgramBlock>,
     f_branch    : Vec<ProgramBlock>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Loop {
     body        : Vec<ProgramBlock>,
     skip        : Vec<ProgramBlock>,
@@ -96,12 +98,15 @@ impl Span {
         // make sure all instructions are valid
         for i in 0..instructions.len() {
             let op_code = instructions[i];
+            let alignment = op_code.spec().alignment;
+            assert!(i % alignment == 0, "{} is not allowed on step {}, must be on step which is a multiple of {}",
+                op_code, i, alignment);
             if op_code == OpCode::Push {
-                assert!(i % 8 == 0, "PUSH is not allowed on step {}, must be on step which is a multiple of 8", i);
                 let hint = hints.get(&i);
                 assert!(hint.is_some(), "invalid PUSH operation on step {}: operation value is missing", i);
                 match hint.unwrap() {
-                    OpHint::PushValue(_) => (),
+                    OpHint::PushValue(value) => assert!(*value < field::MODULUS,
+                        "invalid PUSH operation on step {}: value {} is not a valid field element", i, value),
                     _ => panic!("invalid PUSH operation on step {}: operation value is of wrong type", i)
                 }
             }
@@ -160,6 +165,17 @@ impl Span {
         return state;
     }
 
+    /// Updates the `hasher` with op codes of this block and values of its PUSH operations.
+    pub fn fingerprint(&self, hasher: &mut blake3::Hasher) {
+        hasher.update(&(self.op_codes.len() as u64).to_le_bytes());
+        for (i, &op_code) in self.op_codes.iter().enumerate() {
+            hasher.update(&[op_code as u8]);
+            if let OpHint::PushValue(op_value) = self.get_hint(i) {
+                hasher.update(&op_value.to_le_bytes());
+            }
+        }
+    }
+
     pub fn merge(span1: &Span, span2: &Span) -> Span {
         // merge op codes
         let mut new_op_codes = span1.op_codes.clone();
@@ -178,6 +194,26 @@ impl Span {
     }
 }
 
+// two spans are equal if they have the same op codes and the same hints; a missing hint is the
+// same as OpHint::None
+impl PartialEq for Span {
+    fn eq(&self, other: &Span) -> bool {
+        return self.op_codes == other.op_codes
+            && (0..self.length()).all(|i| self.get_hint(i) == other.get_hint(i));
+    }
+}
+
+impl Eq for Span {}
+
+impl Hash for Span {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.op_codes.hash(state);
+        for i in 0..self.length() {
+            self.get_hint(i).hash(state);
+        }
+    }
+}
+
 impl std::fmt::Debug for Span {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let (op_code, op_hint) = self.get_op(0);
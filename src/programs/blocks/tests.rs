@@ -47,4 +47,16 @@ fn span_hash() {
         238085520613464573032580920836572617149,  98362585914038709664139524327351111560,
         159064915881679512167348007665307977960, 152057468867502483682425300737565245134,
     ], hash);
-}
\ No newline at end of file
+}
+#[test]
+#[should_panic(expected = "invalid PUSH operation on step 8: value 340282366920938463463374557953744961537 is not a valid field element")]
+fn span_push_value_not_reduced() {
+    let mut hints = HashMap::new();
+    hints.insert(8, OpHint::PushValue(340282366920938463463374557953744961537));
+    Span::new(vec![
+        OpCode::Noop, OpCode::Noop, OpCode::Noop, OpCode::Noop,
+        OpCode::Noop, OpCode::Noop, OpCode::Noop, OpCode::Noop,
+        OpCode::Push, OpCode::Noop, OpCode::Noop, OpCode::Noop,
+        OpCode::Noop, OpCode::Noop, OpCode::Noop
+    ], hints);
+}
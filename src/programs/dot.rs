@@ -0,0 +1,63 @@
+use std::fmt::Write;
+use super::{ Program, ProgramBlock };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of leading hex digits of block hashes shown in node labels.
+const HASH_DIGITS: usize = 16;
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Returns a Graphviz description of the block tree of the `program`. Every block is a node: Group,
+/// Switch, and Loop nodes are labeled with the first element of their hash, and Span nodes are
+/// labeled with the number of operations in the span. Edges lead from every block to the blocks
+/// in its body in execution order; edges of Switch blocks are labeled with their branch.
+pub fn to_dot(program: &Program) -> String {
+    let mut dot = String::from("digraph program {\n    node [shape=box, fontname=\"monospace\"];\n");
+    writeln!(dot, "    n0 [label=\"program\\n{}\"];", &hex::encode(program.hash())[..HASH_DIGITS]).unwrap();
+
+    let mut next_id = 1;
+    add_blocks(&mut dot, 0, program.root().body(), None, &mut next_id);
+    dot.push_str("}\n");
+    return dot;
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn add_blocks(dot: &mut String, parent: usize, blocks: &[ProgramBlock], edge_label: Option<&str>, next_id: &mut usize) {
+    for block in blocks.iter() {
+        let id = *next_id;
+        *next_id += 1;
+
+        match edge_label {
+            Some(label) => writeln!(dot, "    n{} -> n{} [label=\"{}\"];", parent, id, label).unwrap(),
+            None => writeln!(dot, "    n{} -> n{};", parent, id).unwrap(),
+        }
+
+        match block {
+            ProgramBlock::Span(span) => {
+                writeln!(dot, "    n{} [label=\"span\\nlength: {}\"];", id, span.length()).unwrap();
+            },
+            ProgramBlock::Group(group) => {
+                writeln!(dot, "    n{} [label=\"group\\n{}\"];", id, format_hash(group.get_hash())).unwrap();
+                add_blocks(dot, id, group.body(), None, next_id);
+            },
+            ProgramBlock::Switch(switch) => {
+                writeln!(dot, "    n{} [label=\"switch\\n{}\", shape=diamond];", id, format_hash(switch.get_hash())).unwrap();
+                add_blocks(dot, id, switch.true_branch(), Some("true"), next_id);
+                add_blocks(dot, id, switch.false_branch(), Some("false"), next_id);
+            },
+            ProgramBlock::Loop(block) => {
+                writeln!(dot, "    n{} [label=\"loop\\n{}\", shape=ellipse];", id, format_hash(block.get_hash())).unwrap();
+                add_blocks(dot, id, block.body(), None, next_id);
+            },
+        }
+    }
+}
+
+fn format_hash(hash: (u128, u128)) -> String {
+    return format!("{:032x}", hash.0)[..HASH_DIGITS].to_string();
+}
@@ -0,0 +1,42 @@
+use crate::{ ProgramInputs, assembly };
+use super::Example;
+
+/// Returns an example which proves that the average of the specified `values` is below the
+/// `threshold`, and that every value fits into 32 bits. The values are supplied via secret input
+/// tape A, while the threshold is a public input. The program outputs two values: `1` if the
+/// average (rounded down) is below the threshold and `0` otherwise, followed by the average
+/// itself; a verifier which needs only the former can request a single output.
+pub fn average_check(values: &[u128], threshold: u128) -> Example {
+    assert!(!values.is_empty(), "at least one value must be provided");
+    assert!(threshold <= u64::MAX as u128, "threshold {} cannot be greater than {}", threshold, u64::MAX);
+    let p32 = u128::pow(2, 32);
+    for &value in values.iter() {
+        assert!(value < p32, "value {} does not fit into 32 bits", value);
+    }
+
+    // for each value: read it from secret tape A, make sure it fits into 32 bits, and add it
+    // into the running sum; the sum of fewer than 2^32 such values fits into 64 bits
+    let mut source = String::with_capacity(values.len() * 32 + 64);
+    source.push_str("begin ");
+    for _ in 0..values.len() {
+        source.push_str("read dup rc.32 assert add ");
+    }
+
+    // divide the sum by the number of values and drop the remainder; the quotient and the
+    // remainder are supplied by the prover via tape A
+    source.push_str(&format!("push.{} divmod.64 swap drop ", values.len()));
+
+    // compare a copy of the average with the threshold; this leaves the result of the
+    // comparison at the top of the stack, followed by the average
+    source.push_str("dup.2 lt.64 end");
+
+    let average = values.iter().sum::<u128>() / values.len() as u128;
+    let is_below = if average < threshold { 1 } else { 0 };
+
+    return Example {
+        program         : assembly::compile(&source).unwrap(),
+        inputs          : ProgramInputs::new(&[0, threshold], values, &[]),
+        num_outputs     : 2,
+        expected_outputs: vec![is_below, average],
+    };
+}
@@ -0,0 +1,42 @@
+use crate::{ ProgramInputs, assembly, math::field };
+use super::Example;
+
+/// Returns an example which computes the `n`-th term of Fibonacci sequence; the stack is
+/// initialized with values 1 and 0, and the program outputs a single value.
+pub fn fibonacci(n: usize) -> Example {
+    assert!(n > 0, "Fibonacci term must be at least 1, but received {}", n);
+
+    // the program is a simple repetition of 4 stack operations:
+    // the first operation moves the 2nd stack item to the top,
+    // the second operation duplicates the top 2 stack items,
+    // the third operation removes the top item from the stack
+    // the last operation pops top 2 stack items, adds them, and pushes
+    // the result back onto the stack
+    let source = format!("
+    begin 
+        repeat.{}
+            swap dup.2 drop add
+        end
+    end", n - 1);
+
+    return Example {
+        program         : assembly::compile(&source).unwrap(),
+        inputs          : ProgramInputs::from_public(&[1, 0]),
+        num_outputs     : 1,
+        expected_outputs: vec![compute_fibonacci(n)],
+    };
+}
+
+/// Computes the `n`-th term of Fibonacci sequence
+fn compute_fibonacci(n: usize) -> u128 {
+    let mut n1 = 0;
+    let mut n2 = 1;
+
+    for _ in 0..(n - 1) {
+        let n3 = field::add(n1, n2);
+        n1 = n2;
+        n2 = n3;
+    }
+
+    return n2;
+}
@@ -0,0 +1,59 @@
+use crate::programs::{ Program, ProgramInputs };
+
+mod aggregate;
+pub use aggregate::average_check;
+
+mod fibonacci;
+pub use fibonacci::fibonacci;
+
+mod merkle;
+pub use merkle::merkle;
+
+mod range;
+pub use range::{ range_check, interval_check };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A program together with inputs for its execution and the outputs which the execution is
+/// expected to produce; examples can be used to test or benchmark the VM.
+pub struct Example {
+    pub program         : Program,
+    pub inputs          : ProgramInputs,
+    pub num_outputs     : usize,
+    pub expected_outputs: Vec<u128>,
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ ProofOptions, math::field };
+    use super::Example;
+
+    #[test]
+    fn execute_examples() {
+        let values = [1, 5, u64::MAX as u128, field::exp(2, 63), field::exp(2, 63) - 1];
+        let examples = vec![
+            super::fibonacci(6),
+            super::merkle(4),
+            super::range_check(&values),
+            super::interval_check(&values, 2, field::exp(2, 63)),
+            super::average_check(&[7, 12, u32::MAX as u128], 1431655772),
+            super::average_check(&[7, 12, u32::MAX as u128], 1431655771),
+        ];
+
+        let expected = [vec![8], vec![], vec![3], vec![3], vec![1, 1431655771], vec![0, 1431655771]];
+        for (example, expected) in examples.into_iter().zip(expected.iter()) {
+            let Example { program, inputs, num_outputs, expected_outputs } = example;
+            if !expected.is_empty() {
+                assert_eq!(expected, &expected_outputs);
+            }
+
+            let (outputs, proof) = crate::execute(&program, &inputs, num_outputs, &ProofOptions::default());
+            assert_eq!(expected_outputs, outputs);
+            assert_eq!(Ok(true), crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{ ProgramInputs, assembly, math::field };
+use super::Example;
+
+/// Returns an example which counts how many of the specified `values` fit into 63 bits; the
+/// values are supplied via secret input tape A, and the program outputs a single value.
+pub fn range_check(values: &[u128]) -> Example {
+
+    // repeat the cycle of the following operations:
+    // 1. read a value from secret tape A
+    // 2. check if it fits into 63 bits (result is 1 if true, 0 otherwise)
+    // 3. add the result into the running sum
+    let mut source = String::with_capacity(values.len() * 80);
+    source.push_str("begin ");
+    for _ in 0..values.len() {
+        source.push_str("read rc.63 add ");
+    }
+    source.push_str("end");
+
+    let p63: u128 = field::exp(2, 63);
+    let count = values.iter().filter(|&&v| v < p63).count() as u128;
+
+    return Example {
+        program         : assembly::compile(&source).unwrap(),
+        inputs          : ProgramInputs::new(&[0], values, &[]),
+        num_outputs     : 1,
+        expected_outputs: vec![count],
+    };
+}
+
+/// Returns an example which counts how many of the specified `values` fall into the [lo, hi]
+/// interval; the values are supplied via secret input tape A, and the program outputs a single
+/// value.
+pub fn interval_check(values: &[u128], lo: u128, hi: u128) -> Example {
+    assert!(lo <= hi, "lower bound {} cannot be greater than upper bound {}", lo, hi);
+
+    // for each value: read it from secret tape A, check if it is in the interval, and add
+    // the result into the running sum
+    let mut source = String::with_capacity(values.len() * 80);
+    source.push_str("begin ");
+    for _ in 0..values.len() {
+        source.push_str(&format!("read rc.range.{}.{} add ", lo, hi));
+    }
+    source.push_str("end");
+
+    let count = values.iter().filter(|&&v| v >= lo && v <= hi).count() as u128;
+
+    return Example {
+        program         : assembly::compile(&source).unwrap(),
+        inputs          : ProgramInputs::new(&[0], values, &[]),
+        num_outputs     : 1,
+        expected_outputs: vec![count],
+    };
+}
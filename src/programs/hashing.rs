@@ -1,4 +1,4 @@
-use crate::{ utils::sponge };
+use crate::{ crypto::rescue };
 use super::{
     ProgramBlock, OpCode, BASE_CYCLE_LENGTH, SPONGE_WIDTH as STATE_WIDTH, HACC_NUM_ROUNDS,
 };
@@ -8,6 +8,11 @@ use super::{
 pub const HACC_ROUND_OFFSET: usize = 1;
 pub const NOOP_VALUE: u8 = OpCode::Noop as u8;
 
+const SPAN_TAG  : u8 = 0;
+const GROUP_TAG : u8 = 1;
+const SWITCH_TAG: u8 = 2;
+const LOOP_TAG  : u8 = 3;
+
 // PUBLIC FUNCTIONS
 // ================================================================================================
 
@@ -59,10 +64,38 @@ pub fn hash_seq(blocks: &Vec<ProgramBlock>, suffix: &[u8], suffix_offset: usize)
     return state[0];
 }
 
+/// Updates the `hasher` with a BLAKE3 fingerprint of a sequence of program blocks. The fingerprint
+/// covers the same data as the program hash (block structure, op codes, and PUSH values), but
+/// it cannot be computed inside the VM, and thus, cannot be used as a program hash.
+pub fn fingerprint_seq(blocks: &[ProgramBlock], hasher: &mut blake3::Hasher) {
+    hasher.update(&(blocks.len() as u64).to_le_bytes());
+    for block in blocks.iter() {
+        match block {
+            ProgramBlock::Span(block) => {
+                hasher.update(&[SPAN_TAG]);
+                block.fingerprint(hasher);
+            },
+            ProgramBlock::Group(block) => {
+                hasher.update(&[GROUP_TAG]);
+                fingerprint_seq(block.body(), hasher);
+            },
+            ProgramBlock::Switch(block) => {
+                hasher.update(&[SWITCH_TAG]);
+                fingerprint_seq(block.true_branch(), hasher);
+                fingerprint_seq(block.false_branch(), hasher);
+            },
+            ProgramBlock::Loop(block) => {
+                hasher.update(&[LOOP_TAG]);
+                fingerprint_seq(block.body(), hasher);
+            },
+        }
+    }
+}
+
 /// Merges an operation with the state of the sponge.
 pub fn hash_op(state: &mut [u128; STATE_WIDTH], op_code: u8, op_value: u128, step: usize)
 {
-    sponge::apply_round(state, op_code as u128, op_value, step);
+    rescue::apply_hacc_round(state, op_code as u128, op_value, step);
 }
 
 /// Merges hash of a control block (v0, v1) into the hash of the parent block.
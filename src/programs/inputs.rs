@@ -1,27 +1,83 @@
-use crate::{ MAX_PUBLIC_INPUTS };
+use std::collections::BTreeMap;
+use crate::{ math::{ field, Felt }, utils::{ codec, merkle::{ MerklePath, build_path_tapes } }, MAX_PUBLIC_INPUTS };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ProgramInputs {
     public: Vec<u128>,
     secret: [Vec<u128>; 2],
+    tape_policy: TapePolicy,
+    full_consumption: bool,
+    hardened: bool,
+    advice: BTreeMap<u128, Vec<u128>>,
+}
+
+/// Determines what happens when a program tries to read from an empty secret input tape.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TapePolicy {
+    /// Reading from an empty tape causes execution to fail.
+    Error,
+    /// Reading from an empty tape returns `0`.
+    ZeroFill,
+}
+
+/// Incrementally builds `ProgramInputs` taking care of the order in which values are consumed
+/// by a program.
+#[derive(Clone, Debug, Default)]
+pub struct ProgramInputsBuilder {
+    public  : Vec<u128>,
+    tape_a  : Vec<u128>,
+    tape_b  : Vec<u128>,
+    advice  : BTreeMap<u128, Vec<u128>>,
 }
 
 impl ProgramInputs {
 
-    /// Returns `ProgramInputs` initialized with the provided public and secret inputs.
+    /// Returns `ProgramInputs` initialized with the provided public and secret inputs. Inputs
+    /// which are not valid field elements are reduced by the field modulus; use `try_new()` to
+    /// get an error for such inputs instead, or `from_felts()` to construct inputs from values
+    /// which are guaranteed to be valid.
+    ///
+    /// Panics if the number of inputs is invalid.
     pub fn new(public: &[u128], secret_a: &[u128], secret_b: &[u128]) -> ProgramInputs {
+        let (public, secret_a, secret_b) = (reduce_elements(public), reduce_elements(secret_a), reduce_elements(secret_b));
+        return match ProgramInputs::try_new(&public, &secret_a, &secret_b) {
+            Ok(inputs) => inputs,
+            Err(message) => panic!("{}", message),
+        };
+    }
 
-        assert!(public.len() <= MAX_PUBLIC_INPUTS,
-            "expected no more than {} public inputs, but received {}",
-            MAX_PUBLIC_INPUTS,
-            public.len());
-        assert!(secret_a.len() >= secret_b.len(), 
-            "number of primary secret inputs cannot be smaller than the number of secondary secret inputs");
+    /// Returns `ProgramInputs` initialized with the provided public and secret inputs, or an
+    /// error if the inputs are invalid (e.g. if any of the inputs is not a valid field element).
+    pub fn try_new(public: &[u128], secret_a: &[u128], secret_b: &[u128]) -> Result<ProgramInputs, String> {
 
-        return ProgramInputs {
+        if public.len() > MAX_PUBLIC_INPUTS {
+            return Err(format!("expected no more than {} public inputs, but received {}",
+                MAX_PUBLIC_INPUTS, public.len()));
+        }
+        if secret_a.len() < secret_b.len() {
+            return Err(format!("number of primary secret inputs cannot be smaller than the number of secondary secret inputs"));
+        }
+        validate_elements(public, "public")?;
+        validate_elements(secret_a, "secret tape A")?;
+        validate_elements(secret_b, "secret tape B")?;
+
+        return Ok(ProgramInputs {
             public  : public.to_vec(),
-            secret  : [secret_a.to_vec(), secret_b.to_vec()]
-        };
+            secret  : [secret_a.to_vec(), secret_b.to_vec()],
+            tape_policy : TapePolicy::Error,
+            full_consumption : false,
+            hardened : false,
+            advice  : BTreeMap::new(),
+        });
+    }
+
+    /// Returns `ProgramInputs` initialized with the provided public and secret inputs specified
+    /// as field elements.
+    pub fn from_felts(public: &[Felt], secret_a: &[Felt], secret_b: &[Felt]) -> ProgramInputs {
+        return ProgramInputs::new(
+            &Felt::to_u128_vec(public),
+            &Felt::to_u128_vec(secret_a),
+            &Felt::to_u128_vec(secret_b));
     }
 
     /// Returns `ProgramInputs` with public and secret input tapes set to empty vectors.
@@ -29,18 +85,80 @@ impl ProgramInputs {
         return ProgramInputs {
             public  : Vec::new(),
             secret  : [Vec::new(), Vec::new()],
+            tape_policy : TapePolicy::Error,
+            full_consumption : false,
+            hardened : false,
+            advice  : BTreeMap::new(),
         };
     }
 
     /// Returns `ProgramInputs` initialized with the provided public inputs and secret
-    /// input tapes set to empty vectors.
+    /// input tapes set to empty vectors. Inputs which are not valid field elements are reduced
+    /// by the field modulus.
     pub fn from_public(public: &[u128]) -> ProgramInputs {
         return ProgramInputs {
-            public: public.to_vec(),
-            secret: [vec![], vec![]]
+            public: reduce_elements(public),
+            secret: [vec![], vec![]],
+            tape_policy: TapePolicy::Error,
+            full_consumption: false,
+            hardened: false,
+            advice: BTreeMap::new(),
         };
     }
 
+    /// Returns `ProgramInputs` with the tape policy set to the specified value; by default, the
+    /// policy is set to `TapePolicy::Error`.
+    pub fn with_tape_policy(mut self, policy: TapePolicy) -> ProgramInputs {
+        self.tape_policy = policy;
+        return self;
+    }
+
+    /// Returns `ProgramInputs` which require all values on secret input tapes to be consumed
+    /// by a program; if any values are left unconsumed, program execution fails.
+    pub fn require_full_consumption(mut self) -> ProgramInputs {
+        self.full_consumption = true;
+        return self;
+    }
+
+    /// Returns `ProgramInputs` which execute programs in hardened mode. In this mode, operations
+    /// which compute on stack values (e.g. `CHOOSE`, `EQ`, `INV`) avoid branching on the values
+    /// in the processor, and execution fails if a branch or a loop is decided by a value derived
+    /// from secret inputs, since the path taken is revealed by the length of the execution.
+    /// Programs which process a secret number of values should instead use loops with a fixed
+    /// number of iterations (e.g. `repeat.n`) over inputs padded via `ProgramInputsBuilder`.
+    pub fn with_hardened_execution(mut self) -> ProgramInputs {
+        self.hardened = true;
+        return self;
+    }
+
+    /// Returns `ProgramInputs` with the `values` associated with the `key` in the advice map;
+    /// the values are put onto secret tape A by `read.key.n` instruction when the key is at the
+    /// top of the stack. Unlike values on the tapes, advice values do not need to be ordered in
+    /// the way a program consumes them, and values for the same key can be read any number of
+    /// times.
+    ///
+    /// Panics if the key or any of the values is not a valid field element.
+    pub fn with_advice(mut self, key: u128, values: &[u128]) -> ProgramInputs {
+        let result = validate_elements(&[key], "advice key").and_then(|_| validate_elements(values, "advice"));
+        if let Err(message) = result {
+            panic!("{}", message);
+        }
+        self.advice.insert(key, values.to_vec());
+        return self;
+    }
+
+    /// Returns a copy of these inputs with public inputs replaced by the specified values.
+    pub(crate) fn with_public_inputs(&self, public: &[u128]) -> ProgramInputs {
+        assert!(public.len() <= MAX_PUBLIC_INPUTS,
+            "expected no more than {} public inputs, but received {}",
+            MAX_PUBLIC_INPUTS,
+            public.len());
+
+        let mut inputs = self.clone();
+        inputs.public = public.to_vec();
+        return inputs;
+    }
+
     pub fn get_public_inputs(&self) -> &[u128] {
         return &self.public;
     }
@@ -48,4 +166,204 @@ impl ProgramInputs {
     pub fn get_secret_inputs(&self) -> &[Vec<u128>; 2] {
         return &self.secret;
     }
-}
\ No newline at end of file
+
+    pub fn get_tape_policy(&self) -> TapePolicy {
+        return self.tape_policy;
+    }
+
+    pub fn is_full_consumption_required(&self) -> bool {
+        return self.full_consumption;
+    }
+
+    pub fn is_hardened_execution(&self) -> bool {
+        return self.hardened;
+    }
+
+    /// Returns the values associated with the `key` in the advice map, if any.
+    pub fn get_advice(&self, key: u128) -> Option<&[u128]> {
+        return self.advice.get(&key).map(|values| values.as_slice());
+    }
+
+    pub(crate) fn get_advice_map(&self) -> &BTreeMap<u128, Vec<u128>> {
+        return &self.advice;
+    }
+}
+
+// PROGRAM INPUTS BUILDER
+// ================================================================================================
+impl ProgramInputsBuilder {
+
+    /// Returns a builder with no public or secret inputs.
+    pub fn new() -> ProgramInputsBuilder {
+        return ProgramInputsBuilder::default();
+    }
+
+    /// Pushes the `value` onto the initial stack; the value pushed last ends up at the top of
+    /// the stack.
+    pub fn push_public(&mut self, value: u128) -> &mut ProgramInputsBuilder {
+        self.public.push(value);
+        return self;
+    }
+
+    /// Appends the `value` to secret tape A; values are read from the tape in the order in
+    /// which they were appended.
+    pub fn push_tape_a(&mut self, value: u128) -> &mut ProgramInputsBuilder {
+        self.tape_a.push(value);
+        return self;
+    }
+
+    /// Appends the byte string `bytes` to secret tape A in the format expected by `read.bytes`
+    /// instruction (see `utils::codec::bytes_to_elements()`).
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> &mut ProgramInputsBuilder {
+        self.tape_a.extend_from_slice(&codec::bytes_to_elements(bytes));
+        return self;
+    }
+
+    /// Appends `a` to secret tape A and `b` to secret tape B; when both values are read by a
+    /// single `read.ab` instruction, `b` ends up at the top of the stack and `a` right below it.
+    pub fn push_tape_ab(&mut self, a: u128, b: u128) -> &mut ProgramInputsBuilder {
+        self.tape_a.push(a);
+        self.tape_b.push(b);
+        return self;
+    }
+
+    /// Appends zeros to secret tape A until it contains `length` values. This way, a program can
+    /// read a fixed number of values from the tape regardless of how many of them are actual
+    /// inputs, which is how hardened programs (see `ProgramInputs::with_hardened_execution()`)
+    /// process a secret number of values.
+    ///
+    /// Panics if the tape already contains more than `length` values.
+    pub fn pad_tape_a(&mut self, length: usize) -> &mut ProgramInputsBuilder {
+        pad_tape(&mut self.tape_a, length, "A");
+        return self;
+    }
+
+    /// Appends zeros to secret tape B until it contains `length` values; see `pad_tape_a()`.
+    ///
+    /// Panics if the tape already contains more than `length` values.
+    pub fn pad_tape_b(&mut self, length: usize) -> &mut ProgramInputsBuilder {
+        pad_tape(&mut self.tape_b, length, "B");
+        return self;
+    }
+
+    /// Associates the `values` with the `key` in the advice map; see `ProgramInputs::with_advice()`.
+    pub fn push_advice(&mut self, key: u128, values: &[u128]) -> &mut ProgramInputsBuilder {
+        self.advice.insert(key, values.to_vec());
+        return self;
+    }
+
+    /// Appends the Merkle authentication `path` to secret tapes A and B in the format expected
+    /// by `smpath` and `mpath_update` instructions: for each node of the path, the next bit
+    /// of the leaf index is followed by the node itself.
+    pub fn push_merkle_path(&mut self, path: &MerklePath) -> &mut ProgramInputsBuilder {
+        let (a, b) = build_path_tapes(path.nodes(), path.index());
+        self.tape_a.extend_from_slice(&a);
+        self.tape_b.extend_from_slice(&b);
+        return self;
+    }
+
+    /// Returns `ProgramInputs` containing the values pushed into this builder.
+    ///
+    /// Panics if any of the values is not a valid field element, or if there are more than
+    /// `MAX_PUBLIC_INPUTS` public inputs.
+    pub fn build(&self) -> ProgramInputs {
+        return match self.try_build() {
+            Ok(inputs) => inputs,
+            Err(message) => panic!("{}", message),
+        };
+    }
+
+    /// Builds `ProgramInputs` from the values pushed so far, or returns an error if the values
+    /// are invalid (e.g. if any of the values is not a valid field element).
+    pub fn try_build(&self) -> Result<ProgramInputs, String> {
+        let public: Vec<u128> = self.public.iter().rev().cloned().collect();
+        let mut inputs = ProgramInputs::try_new(&public, &self.tape_a, &self.tape_b)?;
+        for (&key, values) in self.advice.iter() {
+            validate_elements(&[key], "advice key")?;
+            validate_elements(values, "advice")?;
+        }
+        inputs.advice = self.advice.clone();
+        return Ok(inputs);
+    }
+}
+
+// SEALED INPUTS
+// ================================================================================================
+
+/// Version of the sealed inputs format; this is the first byte of every sealed message.
+#[cfg(feature = "sealed-inputs")]
+const SEALED_INPUTS_VERSION: u8 = 1;
+
+/// Secret part of program inputs as it is serialized before being sealed.
+#[cfg(feature = "sealed-inputs")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SecretRecord {
+    secret  : [Vec<u128>; 2],
+    advice  : BTreeMap<u128, Vec<u128>>,
+}
+
+#[cfg(feature = "sealed-inputs")]
+impl ProgramInputs {
+
+    /// Encrypts secret input tapes and the advice map of these inputs to the `public_key` of a
+    /// prover (see `crypto::box_` module), and returns the result as a hex string. This way, a
+    /// client can delegate proof generation without sending its secret inputs in plaintext.
+    ///
+    /// Public inputs are not sealed because the verifier needs them anyway; tape policy, full
+    /// consumption flag, and hardened mode are not sealed either, and should be set by the prover.
+    pub fn seal(&self, public_key: &[u8; 32]) -> String {
+        let record = SecretRecord { secret: self.secret.clone(), advice: self.advice.clone() };
+        let payload = bincode::serialize(&record).expect("failed to serialize secret inputs");
+
+        let mut bytes = vec![SEALED_INPUTS_VERSION];
+        bytes.extend_from_slice(&crate::crypto::box_::seal(&payload, public_key));
+        return hex::encode(bytes);
+    }
+
+    /// Restores program inputs from the `public` inputs and the secret inputs sealed with
+    /// `seal()` function using the `secret_key` of the prover; returns an error if the inputs
+    /// were sealed to a different key, were modified, or are invalid.
+    pub fn unseal(public: &[u128], sealed: &str, secret_key: &[u8; 32]) -> Result<ProgramInputs, String> {
+        let bytes = hex::decode(sealed.trim())
+            .map_err(|err| format!("sealed inputs are not a valid hex string: {}", err))?;
+        match bytes.first() {
+            Some(&SEALED_INPUTS_VERSION) => (),
+            Some(&version) => return Err(format!("sealed inputs version {} is not supported; expected version {}",
+                version, SEALED_INPUTS_VERSION)),
+            None => return Err(format!("sealed inputs are empty")),
+        }
+
+        let payload = crate::crypto::box_::unseal(&bytes[1..], secret_key)?;
+        let record: SecretRecord = bincode::deserialize(&payload)
+            .map_err(|err| format!("failed to deserialize secret inputs: {}", err))?;
+
+        let mut inputs = ProgramInputs::try_new(public, &record.secret[0], &record.secret[1])?;
+        for (&key, values) in record.advice.iter() {
+            validate_elements(&[key], "advice key")?;
+            validate_elements(values, "advice")?;
+        }
+        inputs.advice = record.advice;
+        return Ok(inputs);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn validate_elements(values: &[u128], kind: &str) -> Result<(), String> {
+    for (i, &value) in values.iter().enumerate() {
+        if value >= field::MODULUS {
+            return Err(format!("{} input {} at position {} is not a valid field element", kind, value, i));
+        }
+    }
+    return Ok(());
+}
+
+fn pad_tape(tape: &mut Vec<u128>, length: usize, name: &str) {
+    assert!(tape.len() <= length, "cannot pad tape {} to {} values since it already contains {} values",
+        name, length, tape.len());
+    tape.resize(length, field::ZERO);
+}
+
+fn reduce_elements(values: &[u128]) -> Vec<u128> {
+    return values.iter().map(|&value| value % field::MODULUS).collect();
+}
@@ -0,0 +1,157 @@
+use std::{ collections::HashMap, panic::{ self, AssertUnwindSafe } };
+use serde_json::{ Map, Value };
+use super::{ Program, ProgramBlock, Span, Group, Switch, Loop, OpCode, OpHint };
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Builds a program from a structured description of its block tree; this way, programs can be
+/// generated by other tools without going through the assembler. The description is a JSON
+/// document of the following form:
+/// ```json
+/// { "root": [
+///     { "span": ["begin", "noop", ..., "push", ...], "hints": { "8": { "push": 3 } } },
+///     { "switch": { "true": [ ... ], "false": [ ... ] } },
+///     { "loop": [ ... ] },
+///     { "group": [ ... ] }
+/// ] }
+/// ```
+/// Operations are specified by the names printed by `OpCode`'s `Display` implementation, and
+/// hints are keyed by the index of the operation in the span; a hint is one of `"eq"`,
+/// `"divmod"`, `{ "rc": n }`, `{ "cmp": n }`, `{ "pmpath": n }`, `{ "push": v }`, `{ "key": n }`
+/// or `{ "custom": [tag, v] }`, where values can be either numbers or decimal strings (for
+/// values which do not fit into 64 bits).
+///
+/// Blocks are built using the same constructors as the ones used by the assembler, and so, the
+/// description must satisfy all of their requirements (e.g. the length of every span must be one
+/// less than a multiple of 16, and PUSH operations must be aligned). Instead of panicking, an
+/// error naming the offending block is returned if any of the requirements is violated.
+pub fn from_json(def: &str) -> Result<Program, String> {
+    let def: Value = serde_json::from_str(def)
+        .map_err(|err| format!("invalid program description: {}", err))?;
+    let root = match def.get("root") {
+        Some(root) => parse_blocks(root, "root")?,
+        None => return Err(format!("program description must contain a root block")),
+    };
+    return build("root", || Program::new(Group::new(root)));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn parse_blocks(def: &Value, path: &str) -> Result<Vec<ProgramBlock>, String> {
+    let blocks = def.as_array().ok_or(format!("{}: expected a list of blocks", path))?;
+    let mut result = Vec::with_capacity(blocks.len());
+    for (i, block) in blocks.iter().enumerate() {
+        result.push(parse_block(block, &format!("{}[{}]", path, i))?);
+    }
+    return Ok(result);
+}
+
+fn parse_block(def: &Value, path: &str) -> Result<ProgramBlock, String> {
+    let def = def.as_object().ok_or(format!("{}: expected a block", path))?;
+
+    if let Some(ops) = def.get("span") {
+        let op_codes = parse_ops(ops, path)?;
+        let op_hints = match def.get("hints") {
+            Some(hints) => parse_hints(hints, path)?,
+            None => HashMap::new(),
+        };
+        return build(path, || ProgramBlock::Span(Span::new(op_codes, op_hints)));
+    }
+    else if let Some(body) = def.get("group") {
+        let body = parse_blocks(body, &format!("{}.group", path))?;
+        return build(path, || Group::new_block(body));
+    }
+    else if let Some(branches) = def.get("switch") {
+        let branches = branches.as_object().ok_or(format!("{}: expected true and false branches", path))?;
+        let t_branch = parse_blocks(get_field(branches, "true", path)?, &format!("{}.true", path))?;
+        let f_branch = parse_blocks(get_field(branches, "false", path)?, &format!("{}.false", path))?;
+        return build(path, || Switch::new_block(t_branch, f_branch));
+    }
+    else if let Some(body) = def.get("loop") {
+        let body = parse_blocks(body, &format!("{}.loop", path))?;
+        return build(path, || Loop::new_block(body));
+    }
+
+    return Err(format!("{}: block type must be one of: span, group, switch, loop", path));
+}
+
+fn parse_ops(def: &Value, path: &str) -> Result<Vec<OpCode>, String> {
+    let ops = def.as_array().ok_or(format!("{}: expected a list of operations", path))?;
+    let mut result = Vec::with_capacity(ops.len());
+    for (i, op) in ops.iter().enumerate() {
+        let op = op.as_str().ok_or(format!("{}.span[{}]: expected an operation name", path, i))?;
+        result.push(op.parse::<OpCode>().map_err(|err| format!("{}.span[{}]: {}", path, i, err))?);
+    }
+    return Ok(result);
+}
+
+fn parse_hints(def: &Value, path: &str) -> Result<HashMap<usize, OpHint>, String> {
+    let hints = def.as_object().ok_or(format!("{}: expected a map of hints", path))?;
+    let mut result = HashMap::with_capacity(hints.len());
+    for (step, hint) in hints.iter() {
+        let path = format!("{}.hints[{}]", path, step);
+        let step = step.parse::<usize>().map_err(|_| format!("{}: hint must be keyed by an operation index", path))?;
+        result.insert(step, parse_hint(hint, &path)?);
+    }
+    return Ok(result);
+}
+
+fn parse_hint(def: &Value, path: &str) -> Result<OpHint, String> {
+    if let Some(name) = def.as_str() {
+        return match name {
+            "eq"     => Ok(OpHint::EqStart),
+            "divmod" => Ok(OpHint::DivModStart),
+            _ => Err(format!("{}: hint '{}' does not exist", path, name)),
+        };
+    }
+
+    let hint = def.as_object().filter(|hint| hint.len() == 1)
+        .ok_or(format!("{}: expected a hint name or a single-entry map", path))?;
+    let (name, value) = hint.iter().next().unwrap();
+    return match name.as_str() {
+        "rc"     => Ok(OpHint::RcStart(parse_u32(value, path)?)),
+        "cmp"    => Ok(OpHint::CmpStart(parse_u32(value, path)?)),
+        "pmpath" => Ok(OpHint::PmpathStart(parse_u32(value, path)?)),
+        "key"    => Ok(OpHint::AdviceKey(parse_u32(value, path)?)),
+        "push"   => Ok(OpHint::PushValue(parse_value(value, path)?)),
+        "custom" => match value.as_array().map(|v| v.as_slice()) {
+            Some([tag, payload]) => Ok(OpHint::Custom(parse_u32(tag, path)?, parse_value(payload, path)?)),
+            _ => Err(format!("{}: custom hint must consist of a tag and a payload", path)),
+        },
+        _ => Err(format!("{}: hint '{}' does not exist", path, name)),
+    };
+}
+
+fn parse_value(def: &Value, path: &str) -> Result<u128, String> {
+    let value = match def {
+        Value::Number(value) => value.as_u64().map(|v| v as u128),
+        Value::String(value) => value.parse::<u128>().ok(),
+        _ => None,
+    };
+    return value.ok_or(format!("{}: {} is not a valid value", path, def));
+}
+
+fn parse_u32(def: &Value, path: &str) -> Result<u32, String> {
+    let value = parse_value(def, path)?;
+    return if value <= u32::MAX as u128 { Ok(value as u32) } else {
+        Err(format!("{}: {} is not a valid parameter", path, value))
+    };
+}
+
+fn get_field<'a>(def: &'a Map<String, Value>, name: &str, path: &str) -> Result<&'a Value, String> {
+    return def.get(name).ok_or(format!("{}: {} branch is missing", path, name));
+}
+
+/// Invokes the `constructor` of a block, and converts a panic raised by the constructor (when
+/// the block is invalid) into an error.
+fn build<T, F: FnOnce() -> T>(path: &str, constructor: F) -> Result<T, String> {
+    return panic::catch_unwind(AssertUnwindSafe(constructor)).map_err(|err| {
+        let message = match err.downcast_ref::<String>() {
+            Some(msg) => msg.clone(),
+            None => err.downcast_ref::<&str>().map(|msg| msg.to_string()).unwrap_or_default(),
+        };
+        format!("{}: {}", path, message)
+    });
+}
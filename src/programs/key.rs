@@ -0,0 +1,83 @@
+use serde::{ Serialize, Deserialize };
+use crate::{ math::field, stark::ProofOptions, MAX_PUBLIC_INPUTS, MAX_OUTPUTS };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Everything a verifier needs to know about a program in order to verify proofs of its
+/// execution. A verification key can be serialized and distributed to verifiers separately from
+/// the program; thus, verifiers do not need access to the program itself.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationKey {
+    program_hash    : [u8; 32],
+    field_modulus   : u128,
+    num_inputs      : usize,
+    num_outputs     : usize,
+    options         : ProofOptions,
+}
+
+// VERIFICATION KEY IMPLEMENTATION
+// ================================================================================================
+impl VerificationKey {
+
+    /// Returns a verification key for a program with the specified `program_hash` which accepts
+    /// `num_inputs` public inputs and returns `num_outputs` outputs, and proofs of which are
+    /// generated using the specified `options`.
+    pub fn new(program_hash: &[u8; 32], num_inputs: usize, num_outputs: usize, options: &ProofOptions) -> VerificationKey {
+        assert!(num_inputs <= MAX_PUBLIC_INPUTS,
+            "expected no more than {} public inputs, but received {}", MAX_PUBLIC_INPUTS, num_inputs);
+        assert!(num_outputs > 0 && num_outputs <= MAX_OUTPUTS,
+            "number of outputs must be between 1 and {}, but received {}", MAX_OUTPUTS, num_outputs);
+
+        return VerificationKey {
+            program_hash    : *program_hash,
+            field_modulus   : field::MODULUS,
+            num_inputs      : num_inputs,
+            num_outputs     : num_outputs,
+            options         : options.clone(),
+        };
+    }
+
+    /// Returns hash of the program.
+    pub fn program_hash(&self) -> &[u8; 32] {
+        return &self.program_hash;
+    }
+
+    /// Returns modulus of the field in which the program is executed.
+    pub fn field_modulus(&self) -> u128 {
+        return self.field_modulus;
+    }
+
+    /// Returns the number of public inputs accepted by the program.
+    pub fn num_inputs(&self) -> usize {
+        return self.num_inputs;
+    }
+
+    /// Returns the number of outputs returned by the program.
+    pub fn num_outputs(&self) -> usize {
+        return self.num_outputs;
+    }
+
+    /// Returns options with which proofs of program execution must be generated.
+    pub fn options(&self) -> &ProofOptions {
+        return &self.options;
+    }
+
+    // SERIALIZATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Serializes this key into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        return bincode::serialize(self).expect("failed to serialize verification key");
+    }
+
+    /// Deserializes a verification key from the specified `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<VerificationKey, String> {
+        let key: VerificationKey = bincode::deserialize(bytes)
+            .map_err(|err| format!("failed to deserialize verification key: {}", err))?;
+        if key.num_inputs > MAX_PUBLIC_INPUTS || key.num_outputs == 0 || key.num_outputs > MAX_OUTPUTS {
+            return Err(String::from("verification key specifies an invalid number of inputs or outputs"));
+        }
+        return Ok(key);
+    }
+}
@@ -1,8 +1,11 @@
+use std::sync::OnceLock;
 use crate::{
     math::field,
+    crypto::HashFunction,
+    stark::ProofOptions,
     processor::{ OpCode, OpHint },
     utils::{ as_bytes },
-    SPONGE_WIDTH, PROGRAM_DIGEST_SIZE, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS,
+    SPONGE_WIDTH, PROGRAM_DIGEST_SIZE, BASE_CYCLE_LENGTH, HACC_NUM_ROUNDS, MIN_TRACE_LENGTH,
 };
 
 pub mod assembly;
@@ -11,10 +14,27 @@ pub mod blocks;
 use blocks::{ ProgramBlock, Span, Group, Switch, Loop };
 
 mod inputs;
-pub use inputs::{ ProgramInputs };
+pub use inputs::{ ProgramInputs, ProgramInputsBuilder, TapePolicy };
+
+mod key;
+pub use key::VerificationKey;
+
+mod registry;
+pub use registry::{ Registry, MembershipProof, BatchMembershipProof };
+
+#[cfg(feature = "examples")]
+pub mod examples;
+
+mod json;
+pub use json::from_json;
+
+mod procedures;
+pub use procedures::ProcedureSet;
+
+mod dot;
 
 mod hashing;
-use hashing::{ hash_op, hash_acc, hash_seq };
+use hashing::{ hash_op, hash_acc, hash_seq, fingerprint_seq };
 
 #[cfg(test)]
 mod tests;
@@ -24,7 +44,7 @@ mod tests;
 #[derive(Clone)]
 pub struct Program {
     root    : Group,
-    hash    : [u8; 32],
+    hash    : OnceLock<[u8; 32]>,
 }
 
 // PROGRAM IMPLEMENTATION
@@ -43,22 +63,103 @@ impl Program {
             _ => panic!("a program must start with a Span block")
         }
 
-        // compute program hash
-        let (v0, v1) = root.get_hash();
-        let hash = hash_acc(field::ZERO, v0, v1);
-        let mut hash_bytes = [0u8; 32];
-        hash_bytes.copy_from_slice(as_bytes(&hash[..PROGRAM_DIGEST_SIZE]));
+        // program hash is computed on first use; this keeps compilation of large programs fast
+        // when only the program structure or its fingerprint is needed
+        return Program { root, hash: OnceLock::new() };
+    }
 
-        return Program { root, hash: hash_bytes };
+    /// Builds a program from a JSON description of its block tree; see `programs::from_json()`
+    /// for the format of the description.
+    pub fn from_json(def: &str) -> Result<Program, String> {
+        return from_json(def);
     }
+
     /// Returns the root block of the program.
     pub fn root(&self) -> &Group {
         return &self.root;
     }
 
-    /// Returns hash of the program.
+    /// Returns hash of the program. The hash is computed using the same Rescue-based sponge
+    /// which the VM uses to hash executed operations, and so, it is the value against which
+    /// proofs of execution are verified. The hash is computed the first time this function is
+    /// called.
     pub fn hash(&self) -> &[u8; 32] {
-        return &self.hash;
+        return self.hash.get_or_init(|| {
+            let (v0, v1) = self.root.get_hash();
+            let hash = hash_acc(field::ZERO, v0, v1);
+            let mut hash_bytes = [0u8; 32];
+            hash_bytes.copy_from_slice(as_bytes(&hash[..PROGRAM_DIGEST_SIZE]));
+            hash_bytes
+        });
+    }
+
+    /// Returns a BLAKE3 fingerprint of the program. The fingerprint covers the same data as the
+    /// program hash and is much faster to compute, which makes it suitable for identifying
+    /// programs off-chain (e.g. as keys in program registries or compilation caches). However,
+    /// the fingerprint cannot be computed by the VM, and thus, proofs of execution cannot be
+    /// verified against it; `hash()` must be used for that.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        fingerprint_seq(self.root.body(), &mut hasher);
+        return *hasher.finalize().as_bytes();
+    }
+
+    /// Returns a commitment to the program computed as hash(program_hash || salt) using the
+    /// provided hash function. The commitment can be published instead of the program hash to
+    /// keep the program confidential; see `get_program_commitment()`.
+    pub fn commitment(&self, salt: &[u8; 32], hash_fn: HashFunction) -> [u8; 32] {
+        return get_program_commitment(self.hash(), salt, hash_fn);
+    }
+
+    /// Returns a Graphviz description of the block tree of the program; the description can be
+    /// rendered using `dot` tool (e.g. `dot -Tsvg program.dot -o program.svg`). Control blocks
+    /// are labeled with their hashes, and Span blocks are labeled with their lengths.
+    pub fn to_dot(&self) -> String {
+        return dot::to_dot(self);
+    }
+
+    /// Returns a verification key for this program; the key can be used to verify proofs of
+    /// execution of the program with `num_inputs` public inputs and `num_outputs` outputs
+    /// generated using the specified `options`.
+    pub fn to_verification_key(&self, num_inputs: usize, num_outputs: usize, options: &ProofOptions) -> VerificationKey {
+        return VerificationKey::new(self.hash(), num_inputs, num_outputs, options);
+    }
+
+    /// Returns the length of the longest execution trace the program can produce when no loop
+    /// in it runs for more than `max_loop_iterations` iterations; for nested loops, the bound
+    /// applies to every execution of an inner loop. The length is computed from the block tree
+    /// alone: the longer branch of every Switch block is taken, and steps needed to start and
+    /// close blocks and to align Span blocks are accounted for. Thus, the returned value is the
+    /// exact trace length the VM would produce on the worst-case path, and can be used to
+    /// choose proof options and to estimate memory requirements before the program is executed.
+    ///
+    /// The result saturates at `usize::MAX` if the trace length does not fit into `usize`.
+    pub fn max_trace_length(&self, max_loop_iterations: usize) -> usize {
+        let num_steps = get_max_seq_steps(self.root.body(), max_loop_iterations)
+            .saturating_add(CLOSE_BLOCK_STEPS);
+
+        // the trace must include the state after the last step, and its length is doubled
+        // starting from MIN_TRACE_LENGTH until it does
+        return match num_steps.checked_add(1).and_then(usize::checked_next_power_of_two) {
+            Some(trace_length) => usize::max(trace_length, MIN_TRACE_LENGTH),
+            None => usize::MAX,
+        };
+    }
+}
+
+// programs are compared structurally; since structurally equal programs have the same program
+// hash, the program hash is used to hash programs
+impl PartialEq for Program {
+    fn eq(&self, other: &Program) -> bool {
+        return self.root == other.root;
+    }
+}
+
+impl Eq for Program {}
+
+impl std::hash::Hash for Program {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hash().hash(state);
     }
 }
 
@@ -72,4 +173,65 @@ impl std::fmt::Debug for Program {
 
         return Ok(());
     }
-}
\ No newline at end of file
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Number of steps needed to close a block: a NOOP for alignment, a step to end the block, and
+/// rounds of merging the block hash into the parent hash.
+const CLOSE_BLOCK_STEPS: usize = 2 + HACC_NUM_ROUNDS;
+
+/// Returns the maximum number of steps needed to execute a sequence of `blocks`; this mirrors
+/// how the processor executes blocks (see processor::execute_blocks()).
+fn get_max_seq_steps(blocks: &[ProgramBlock], max_loop_iterations: usize) -> usize {
+    let mut num_steps: usize = 0;
+    for (i, block) in blocks.iter().enumerate() {
+        let block_steps = match block {
+            // all Span blocks but the first one in a sequence are prefixed with a NOOP
+            ProgramBlock::Span(block) => if i == 0 { block.length() } else { block.length() + 1 },
+            ProgramBlock::Group(block) => {
+                get_max_seq_steps(block.body(), max_loop_iterations)
+                    .saturating_add(1 + CLOSE_BLOCK_STEPS)
+            },
+            ProgramBlock::Switch(block) => {
+                let true_steps = get_max_seq_steps(block.true_branch(), max_loop_iterations);
+                let false_steps = get_max_seq_steps(block.false_branch(), max_loop_iterations);
+                usize::max(true_steps, false_steps).saturating_add(1 + CLOSE_BLOCK_STEPS)
+            },
+            ProgramBlock::Loop(block) => {
+                // a loop which is not entered executes only its skip block; otherwise, every
+                // iteration is followed by a step to either wrap or break the loop
+                let skip_steps = get_max_seq_steps(block.skip(), max_loop_iterations)
+                    .saturating_add(1 + CLOSE_BLOCK_STEPS);
+                if max_loop_iterations == 0 {
+                    skip_steps
+                }
+                else {
+                    let iteration_steps = get_max_seq_steps(block.body(), max_loop_iterations)
+                        .saturating_add(1);
+                    let loop_steps = iteration_steps.saturating_mul(max_loop_iterations)
+                        .saturating_add(skip_steps);
+                    usize::max(loop_steps, skip_steps)
+                }
+            },
+        };
+        num_steps = num_steps.saturating_add(block_steps);
+    }
+    return num_steps;
+}
+
+/// Returns a commitment to the program with the specified `program_hash` computed as
+/// hash(program_hash || salt) using the provided hash function. As long as the salt is random
+/// and kept secret, the commitment does not reveal which program it commits to; proofs of
+/// execution generated with the salt can be verified against the commitment by anyone who
+/// also knows the program hash.
+pub fn get_program_commitment(program_hash: &[u8; 32], salt: &[u8; 32], hash_fn: HashFunction) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(program_hash);
+    buf[32..].copy_from_slice(salt);
+
+    let mut commitment = [0u8; 32];
+    hash_fn(&buf, &mut commitment);
+    return commitment;
+}
@@ -0,0 +1,72 @@
+use crate::crypto::HashFunction;
+use super::{ Program, Registry };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A list of programs which are distributed together as procedures, e.g. entry points of a single
+/// application. Every procedure is a standalone program with its own hash, and can be identified
+/// by its index in the list, by its hash, or by its name (if it has one).
+///
+/// Procedures can be compiled from assembly source code using `assembly::compile_procedures()`,
+/// in which case their names are taken from the `proc.<name>` heads.
+#[derive(Clone)]
+pub struct ProcedureSet {
+    procedures  : Vec<(Option<String>, Program)>,
+}
+
+// PROCEDURE SET IMPLEMENTATION
+// ================================================================================================
+impl ProcedureSet {
+
+    /// Builds a procedure set from the specified (name, program) tuples; procedures keep the
+    /// order in which they were provided.
+    ///
+    /// Panics if the list is empty or if any name is used more than once.
+    pub fn new(procedures: Vec<(Option<String>, Program)>) -> ProcedureSet {
+        assert!(procedures.len() > 0, "a procedure set must contain at least one procedure");
+        for (i, (name, _)) in procedures.iter().enumerate() {
+            if let Some(name) = name {
+                assert!(!procedures[..i].iter().any(|(other, _)| other.as_ref() == Some(name)),
+                    "procedure {} is defined more than once", name);
+            }
+        }
+        return ProcedureSet { procedures };
+    }
+
+    /// Returns the number of procedures in the set.
+    pub fn len(&self) -> usize {
+        return self.procedures.len();
+    }
+
+    /// Returns an iterator over (index, name, hash) tuples of all procedures.
+    pub fn procedures(&self) -> impl Iterator<Item = (usize, Option<&str>, &[u8; 32])> {
+        return self.procedures.iter().enumerate()
+            .map(|(i, (name, program))| (i, name.as_deref(), program.hash()));
+    }
+
+    /// Returns the procedure at the specified `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&Program> {
+        return self.procedures.get(index).map(|(_, program)| program);
+    }
+
+    /// Returns the index of the procedure with the specified `name` together with the procedure.
+    pub fn find_by_name(&self, name: &str) -> Option<(usize, &Program)> {
+        return self.procedures.iter().position(|(n, _)| n.as_deref() == Some(name))
+            .map(|i| (i, &self.procedures[i].1));
+    }
+
+    /// Returns the index of the first procedure with the specified `hash` together with the
+    /// procedure.
+    pub fn find_by_hash(&self, hash: &[u8; 32]) -> Option<(usize, &Program)> {
+        return self.procedures.iter().position(|(_, p)| p.hash() == hash)
+            .map(|i| (i, &self.procedures[i].1));
+    }
+
+    /// Builds a registry of all procedures; positions of procedures in the registry are the same
+    /// as their indexes in the set.
+    pub fn to_registry(&self, hash_fn: HashFunction) -> Registry {
+        let programs: Vec<&Program> = self.procedures.iter().map(|(_, program)| program).collect();
+        return Registry::from_programs(&programs, hash_fn);
+    }
+}
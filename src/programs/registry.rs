@@ -0,0 +1,205 @@
+use serde::{ Serialize, Deserialize };
+use crate::crypto::{ MerkleTree, BatchMerkleProof, HashFunction, ct_eq };
+use super::Program;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A set of approved programs committed to by a Merkle tree of program hashes. The registry is
+/// identified by a root which commits to both the root of the tree and its depth, and so, nodes
+/// at other levels of the tree cannot be passed off as program hashes. A membership proof shows
+/// that a given program hash is one of the leaves of the tree.
+///
+/// Membership proofs reveal the program hash and its position in the registry. Hiding which of
+/// the programs was executed would require salted leaves as well as a proof of execution which
+/// does not expose the program hash; execution proofs always bind the program hash as a public
+/// value, and so, the registry does not attempt to hide it.
+pub struct Registry {
+    root        : [u8; 32],
+    tree        : MerkleTree,
+    num_programs: usize,
+    hash_fn     : HashFunction,
+}
+
+/// A proof that a program hash is included in a registry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MembershipProof {
+    index       : usize,
+    path        : Vec<[u8; 32]>,
+    tree_root   : [u8; 32],
+}
+
+/// A proof that several program hashes are included in a registry. Merkle paths of all programs
+/// are compressed into a single batch proof, and so, the proof is smaller than a set of individual
+/// membership proofs for the same programs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BatchMembershipProof {
+    indexes     : Vec<usize>,
+    proof       : BatchMerkleProof,
+    tree_root   : [u8; 32],
+}
+
+// REGISTRY IMPLEMENTATION
+// ================================================================================================
+impl Registry {
+
+    /// Builds a registry from the specified program hashes using the provided hash function.
+    /// If the number of programs is not a power of two, the tree is padded with zero leaves.
+    pub fn new(program_hashes: &[[u8; 32]], hash_fn: HashFunction) -> Registry {
+        assert!(!program_hashes.is_empty(), "a registry must contain at least one program");
+        assert!(!program_hashes.contains(&[0u8; 32]), "a registry cannot contain a zero program hash");
+
+        let num_leaves = usize::max(program_hashes.len().next_power_of_two(), 2);
+        let mut leaves = program_hashes.to_vec();
+        leaves.resize(num_leaves, [0u8; 32]);
+
+        let tree = MerkleTree::new(leaves, hash_fn);
+        return Registry {
+            root        : commit_to_depth(tree.root(), num_leaves.trailing_zeros() as usize, hash_fn),
+            tree        : tree,
+            num_programs: program_hashes.len(),
+            hash_fn     : hash_fn,
+        };
+    }
+
+    /// Builds a registry from the hashes of the specified programs.
+    pub fn from_programs(programs: &[&Program], hash_fn: HashFunction) -> Registry {
+        let program_hashes: Vec<[u8; 32]> = programs.iter().map(|p| *p.hash()).collect();
+        return Registry::new(&program_hashes, hash_fn);
+    }
+
+    /// Returns the root of the registry; this is a commitment to the root of the Merkle tree of
+    /// program hashes and to the depth of the tree.
+    pub fn root(&self) -> &[u8; 32] {
+        return &self.root;
+    }
+
+    /// Returns the number of programs in the registry.
+    pub fn num_programs(&self) -> usize {
+        return self.num_programs;
+    }
+
+    /// Returns hashes of all programs in the registry.
+    pub fn program_hashes(&self) -> &[[u8; 32]] {
+        return &self.tree.leaves()[..self.num_programs];
+    }
+
+    /// Returns true if a program with the specified hash is in the registry.
+    pub fn contains(&self, program_hash: &[u8; 32]) -> bool {
+        return self.program_hashes().contains(program_hash);
+    }
+
+    /// Returns a proof that a program with the specified hash is in the registry, or None if
+    /// the registry does not contain such a program.
+    pub fn prove(&self, program_hash: &[u8; 32]) -> Option<MembershipProof> {
+        let index = self.program_hashes().iter().position(|h| h == program_hash)?;
+        return Some(MembershipProof { index, path: self.tree.prove(index), tree_root: *self.tree.root() });
+    }
+
+    /// Checks whether the `proof` shows that a program with the specified `program_hash` is
+    /// in the registry with the specified `root`.
+    pub fn verify(root: &[u8; 32], program_hash: &[u8; 32], proof: &MembershipProof, hash_fn: HashFunction) -> bool {
+        if proof.path.len() < 2 || proof.path.len() > usize::BITS as usize {
+            return false;
+        }
+        let depth = proof.path.len() - 1;
+        if proof.index >> depth != 0 || !ct_eq(&proof.path[0], program_hash) {
+            return false;
+        }
+        if !ct_eq(root, &commit_to_depth(&proof.tree_root, depth, hash_fn)) {
+            return false;
+        }
+        return MerkleTree::verify(&proof.tree_root, proof.index, &proof.path, hash_fn);
+    }
+
+    /// Returns a single proof that all programs with the specified hashes are in the registry,
+    /// or None if the registry does not contain any of the programs.
+    pub fn prove_batch(&self, program_hashes: &[[u8; 32]]) -> Option<BatchMembershipProof> {
+        assert!(!program_hashes.is_empty(), "at least one program hash must be provided");
+
+        let mut indexes = Vec::with_capacity(program_hashes.len());
+        for program_hash in program_hashes.iter() {
+            let index = self.program_hashes().iter().position(|h| h == program_hash)?;
+            assert!(!indexes.contains(&index), "program hash {:?} is provided more than once", program_hash);
+            indexes.push(index);
+        }
+
+        let proof = self.tree.prove_batch(&indexes);
+        return Some(BatchMembershipProof { indexes, proof, tree_root: *self.tree.root() });
+    }
+
+    /// Checks whether the `proof` shows that programs with the specified `program_hashes` are
+    /// in the registry with the specified `root`; program hashes must be listed in the same
+    /// order in which they were passed to `prove_batch()`.
+    pub fn verify_batch(root: &[u8; 32], program_hashes: &[[u8; 32]], proof: &BatchMembershipProof, hash_fn: HashFunction) -> bool {
+        // make sure the proof is well-formed before passing it to the Merkle tree
+        let depth = proof.proof.depth as u32;
+        if depth == 0 || depth >= usize::BITS || proof.indexes.is_empty() {
+            return false;
+        }
+        for (i, &index) in proof.indexes.iter().enumerate() {
+            if index >> depth != 0 || proof.indexes[..i].contains(&index) {
+                return false;
+            }
+        }
+        if proof.proof.values.len() != program_hashes.len()
+            || !ct_eq(proof.proof.values.concat().as_slice(), program_hashes.concat().as_slice())
+        {
+            return false;
+        }
+        if !ct_eq(root, &commit_to_depth(&proof.tree_root, depth as usize, hash_fn)) {
+            return false;
+        }
+        return MerkleTree::verify_batch(&proof.tree_root, &proof.indexes, &proof.proof, hash_fn);
+    }
+
+    /// Returns the hash function used to build the registry.
+    pub fn hash_fn(&self) -> HashFunction {
+        return self.hash_fn;
+    }
+}
+
+// MEMBERSHIP PROOF IMPLEMENTATION
+// ================================================================================================
+impl MembershipProof {
+
+    /// Returns hash of the program this proof was generated for, or None if the proof is
+    /// malformed.
+    pub fn program_hash(&self) -> Option<&[u8; 32]> {
+        return self.path.first();
+    }
+
+    /// Returns position of the program in the registry.
+    pub fn index(&self) -> usize {
+        return self.index;
+    }
+}
+
+// BATCH MEMBERSHIP PROOF IMPLEMENTATION
+// ================================================================================================
+impl BatchMembershipProof {
+
+    /// Returns hashes of the programs this proof was generated for.
+    pub fn program_hashes(&self) -> &[[u8; 32]] {
+        return &self.proof.values;
+    }
+
+    /// Returns positions of the programs in the registry.
+    pub fn indexes(&self) -> &[usize] {
+        return &self.indexes;
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Hashes the root of a Merkle tree together with the depth of the tree.
+fn commit_to_depth(tree_root: &[u8; 32], depth: usize, hash_fn: HashFunction) -> [u8; 32] {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(tree_root);
+    buf[32..40].copy_from_slice(&(depth as u64).to_le_bytes());
+
+    let mut result = [0u8; 32];
+    hash_fn(&buf, &mut result);
+    return result;
+}
@@ -1,6 +1,8 @@
+use std::collections::HashSet;
 use crate::utils::{ as_bytes };
 use crate::processor::{ OpCode };
-use super::{ Program, ProgramBlock, Span, Group, Switch, Loop };
+use crate::crypto::hash;
+use super::{ Program, ProgramBlock, ProgramInputs, ProgramInputsBuilder, TapePolicy, Registry, MembershipProof, Span, Group, Switch, Loop, assembly };
 
 mod utils;
 use utils::{ traverse, close_block };
@@ -145,6 +147,262 @@ fn simple_loop() {
     assert_eq!(111, step);
 }
 
+#[test]
+fn fingerprint() {
+    // the same programs have the same fingerprint
+    let program1 = assembly::compile("begin add if.true mul else inv end end").unwrap();
+    let program2 = assembly::compile("begin add if.true mul else inv end end").unwrap();
+    assert_eq!(program1.fingerprint(), program2.fingerprint());
+
+    // different op codes result in different fingerprints
+    let program3 = assembly::compile("begin add if.true mul else neg end end").unwrap();
+    assert_ne!(program1.fingerprint(), program3.fingerprint());
+
+    // different block structure results in different fingerprints
+    let program4 = assembly::compile("begin add if.true mul end end").unwrap();
+    assert_ne!(program1.fingerprint(), program4.fingerprint());
+
+    let program5 = assembly::compile("begin add while.true mul end end").unwrap();
+    assert_ne!(program4.fingerprint(), program5.fingerprint());
+
+    // different PUSH values result in different fingerprints
+    let program6 = assembly::compile("begin push.1 end").unwrap();
+    let program7 = assembly::compile("begin push.2 end").unwrap();
+    assert_ne!(program6.fingerprint(), program7.fingerprint());
+    assert_ne!(program6.hash(), program7.hash());
+}
+
+#[test]
+fn program_equality() {
+    // the same programs are equal and have the same hash
+    let program1 = assembly::compile("begin push.3 add if.true mul else inv end end").unwrap();
+    let program2 = assembly::compile("begin push.3 add if.true mul else inv end end").unwrap();
+    assert_eq!(program1, program2);
+    assert_eq!(program1.root().body(), program2.root().body());
+
+    // different PUSH values and different block structure make programs different
+    let program3 = assembly::compile("begin push.4 add if.true mul else inv end end").unwrap();
+    assert_ne!(program1, program3);
+    let program4 = assembly::compile("begin push.3 add if.true mul end end").unwrap();
+    assert_ne!(program1, program4);
+
+    // programs can be deduplicated
+    let programs: HashSet<Program> = vec![program1, program2, program3, program4].into_iter().collect();
+    assert_eq!(3, programs.len());
+
+    // inputs are compared by value
+    let inputs1 = ProgramInputs::new(&[1, 2], &[3], &[]);
+    let inputs2 = ProgramInputs::new(&[1, 2], &[3], &[]);
+    assert_eq!(inputs1, inputs2);
+    assert_ne!(inputs1, inputs2.with_tape_policy(TapePolicy::ZeroFill));
+}
+
+#[test]
+fn inputs_builder() {
+    let path = crate::utils::merkle::MerklePath::new(vec![[11, 12], [13, 14]], 2);
+    let inputs = ProgramInputsBuilder::new()
+        .push_public(1).push_public(2).push_public(3)
+        .push_tape_a(4)
+        .push_tape_ab(5, 6)
+        .push_merkle_path(&path)
+        .build();
+
+    // public inputs pushed last are at the top of the stack
+    assert_eq!(&[3, 2, 1], inputs.get_public_inputs());
+
+    // tape B is aligned with tape A when values are read via READ2
+    let [tape_a, tape_b] = inputs.get_secret_inputs();
+    assert_eq!(&vec![4, 5, 0, 11, 0, 13], tape_a);
+    assert_eq!(&vec![6, 0, 12, 1, 14], tape_b);
+
+    // tapes can be padded with zeros to a fixed length
+    let inputs = ProgramInputsBuilder::new().push_tape_ab(1, 2).pad_tape_a(4).pad_tape_b(3).build();
+    let [tape_a, tape_b] = inputs.get_secret_inputs();
+    assert_eq!(&vec![1, 0, 0, 0], tape_a);
+    assert_eq!(&vec![2, 0, 0], tape_b);
+}
+
+#[test]
+#[cfg(feature = "sealed-inputs")]
+fn sealed_inputs() {
+    use crate::crypto::box_;
+
+    let (secret_key, public_key) = box_::generate_keys();
+    let inputs = ProgramInputs::new(&[1, 2], &[3, 4, 5], &[6]).with_advice(7, &[8, 9]);
+    let sealed = inputs.seal(&public_key);
+
+    // the prover restores secret inputs using its secret key
+    let unsealed = ProgramInputs::unseal(&[1, 2], &sealed, &secret_key).unwrap();
+    assert_eq!(inputs, unsealed);
+
+    // sealed inputs cannot be restored using a different key or after being modified
+    let (other_key, _) = box_::generate_keys();
+    assert!(ProgramInputs::unseal(&[1, 2], &sealed, &other_key).is_err());
+    let modified = format!("{}{}", &sealed[..sealed.len() - 1], if sealed.ends_with('0') { '1' } else { '0' });
+    assert!(ProgramInputs::unseal(&[1, 2], &modified, &secret_key).is_err());
+    assert!(ProgramInputs::unseal(&[1, 2], &format!("02{}", &sealed[2..]), &secret_key).is_err());
+}
+
+#[test]
+fn registry() {
+    let hashes: Vec<[u8; 32]> = (1..=5u8).map(|i| [i; 32]).collect();
+    let registry = Registry::new(&hashes, hash::blake3);
+    assert_eq!(5, registry.num_programs());
+    assert!(registry.contains(&[3; 32]));
+    assert!(!registry.contains(&[0; 32]));
+
+    // every program in the registry can be proven to be a member
+    for program_hash in hashes.iter() {
+        let proof = registry.prove(program_hash).unwrap();
+        assert_eq!(Some(program_hash), proof.program_hash());
+        assert!(Registry::verify(registry.root(), program_hash, &proof, hash::blake3));
+    }
+
+    // a proof does not verify for a different program or a different registry
+    let proof = registry.prove(&[2; 32]).unwrap();
+    assert!(!Registry::verify(registry.root(), &[3; 32], &proof, hash::blake3));
+    let other = Registry::new(&hashes[..4], hash::blake3);
+    assert!(!Registry::verify(other.root(), &[2; 32], &proof, hash::blake3));
+
+    // padding leaves cannot be proven
+    assert_eq!(None, registry.prove(&[0; 32]));
+
+    // a registry with a single program is padded to two leaves
+    let registry = Registry::new(&hashes[..1], hash::blake3);
+    let proof = registry.prove(&[1; 32]).unwrap();
+    assert!(Registry::verify(registry.root(), &[1; 32], &proof, hash::blake3));
+
+    // an internal node of the tree cannot be passed off as a program hash with a shorter path
+    let registry = Registry::new(&hashes, hash::blake3);
+    let proof = registry.prove(&[1; 32]).unwrap();
+    let mut node = [0u8; 32];
+    hash::blake3(&[hashes[0], hashes[1]].concat(), &mut node);
+    let mut value = serde_json::to_value(&proof).unwrap();
+    let path = value["path"].clone();
+    value["path"] = serde_json::json!([node, path[2], path[3]]);
+    let forged: MembershipProof = serde_json::from_value(value).unwrap();
+    assert!(!Registry::verify(registry.root(), &node, &forged, hash::blake3));
+}
+
+#[test]
+fn registry_batch() {
+    let hashes: Vec<[u8; 32]> = (1..=5u8).map(|i| [i; 32]).collect();
+    let registry = Registry::new(&hashes, hash::blake3);
+
+    // several programs can be proven to be members with a single proof
+    let members = [[4; 32], [1; 32], [5; 32]];
+    let proof = registry.prove_batch(&members).unwrap();
+    assert_eq!(&members, proof.program_hashes());
+    assert_eq!(&[3, 0, 4], proof.indexes());
+    assert!(Registry::verify_batch(registry.root(), &members, &proof, hash::blake3));
+
+    // the proof does not verify for a different set of programs or a different registry
+    assert!(!Registry::verify_batch(registry.root(), &[[4; 32], [1; 32], [3; 32]], &proof, hash::blake3));
+    assert!(!Registry::verify_batch(registry.root(), &members[..2], &proof, hash::blake3));
+    let other = Registry::new(&hashes[..4], hash::blake3);
+    assert!(!Registry::verify_batch(other.root(), &members, &proof, hash::blake3));
+
+    // the proof cannot be generated if any of the programs is not in the registry
+    assert_eq!(None, registry.prove_batch(&[[1; 32], [0; 32]]));
+
+    // a batch proof for a single program works as well
+    let proof = registry.prove_batch(&[[2; 32]]).unwrap();
+    assert!(Registry::verify_batch(registry.root(), &[[2; 32]], &proof, hash::blake3));
+}
+
+#[test]
+fn program_to_dot() {
+    let program = assembly::compile("begin push.1 if.true push.2 else push.3 end while.true push.0 end end").unwrap();
+    let dot = program.to_dot();
+    assert!(dot.starts_with("digraph program {\n"));
+    assert!(dot.ends_with("}\n"));
+
+    // the root is labeled with the program hash, and every block of the tree is a node
+    assert!(dot.contains(&format!("n0 [label=\"program\\n{}\"];", &hex::encode(program.hash())[..16])));
+    assert_eq!(4, dot.matches("[label=\"span\\nlength: ").count());
+    assert_eq!(1, dot.matches("[label=\"switch\\n").count());
+    assert_eq!(1, dot.matches("[label=\"loop\\n").count());
+
+    // edges of switch blocks are labeled with their branch
+    assert_eq!(1, dot.matches("[label=\"true\"]").count());
+    assert_eq!(1, dot.matches("[label=\"false\"]").count());
+}
+
+#[test]
+fn program_from_json() {
+    // spans are described by operation names and hints at operation indexes
+    let mut root_ops = vec!["begin"; 1];
+    root_ops.resize(8, "noop");
+    root_ops.push("push");
+    root_ops.resize(16, "noop");
+    root_ops.extend_from_slice(&["push", "add"]);
+    root_ops.resize(31, "noop");
+    let def = format!(r#"{{ "root": [
+        {{ "span": {:?}, "hints": {{ "8": {{ "push": 3 }}, "16": {{ "push": "5" }} }} }}
+    ] }}"#, root_ops);
+    let expected = assembly::compile("begin push.3 push.5 add end").unwrap();
+    assert_eq!(expected, Program::from_json(&def).unwrap());
+
+    // nested blocks are described by lists of their child blocks
+    let mut t_ops = vec!["assert", "add"];
+    t_ops.resize(15, "noop");
+    let mut f_ops = vec!["not", "assert", "mul"];
+    f_ops.resize(15, "noop");
+    let mut root_ops = vec!["begin"; 1];
+    root_ops.resize(15, "noop");
+    let def = format!(r#"{{ "root": [
+        {{ "span": {:?} }},
+        {{ "switch": {{ "true": [{{ "span": {:?} }}], "false": [{{ "span": {:?} }}] }} }}
+    ] }}"#, root_ops, t_ops, f_ops);
+    let expected = assembly::compile("begin noop if.true add else mul end end").unwrap();
+    assert_eq!(expected.hash(), Program::from_json(&def).unwrap().hash());
+
+    // invalid descriptions are reported as errors naming the offending block
+    let def = def.replace(r#""not", "assert""#, r#""assert", "not""#);
+    let error = Program::from_json(&def).err().unwrap();
+    assert!(error.starts_with("root[1]: the first block does not start"), "{}", error);
+
+    let def = r#"{ "root": [{ "span": ["begin", "push", "noop"] }] }"#;
+    let error = Program::from_json(def).err().unwrap();
+    assert!(error.starts_with("root[0]: invalid number of instructions"), "{}", error);
+
+    let def = r#"{ "root": [{ "span": ["begin", "foo"] }] }"#;
+    assert_eq!(Err(String::from("root[0].span[1]: operation 'foo' does not exist")), Program::from_json(def));
+    assert!(Program::from_json(r#"{ "blocks": [] }"#).is_err());
+    assert!(Program::from_json("root").is_err());
+}
+
+#[test]
+fn max_trace_length() {
+    let get_trace_length = |program: &Program, inputs: &ProgramInputs| crate::generate_trace(program, inputs).0[0].len();
+
+    // a linear program has a single path
+    let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    assert_eq!(get_trace_length(&program, &ProgramInputs::none()), program.max_trace_length(0));
+
+    // the longer branch of a switch determines the worst case
+    let source = format!("begin if.true {}else push.3 end end", "push.1 drop ".repeat(40));
+    let program = assembly::compile(&source).unwrap();
+    let max_length = program.max_trace_length(0);
+    assert_eq!(get_trace_length(&program, &ProgramInputs::from_public(&[1, 5])), max_length);
+    assert!(get_trace_length(&program, &ProgramInputs::from_public(&[0, 5])) < max_length);
+
+    // loops are bounded by the number of iterations; a loop which is not entered is also covered
+    let program = assembly::compile("begin read while.true push.2 push.3 mul drop read end end").unwrap();
+    for &iterations in [0, 1, 5, 20].iter() {
+        let mut tape = vec![1; iterations];
+        tape.push(0);
+        let inputs = ProgramInputs::new(&[], &tape, &[]);
+        assert_eq!(get_trace_length(&program, &inputs), program.max_trace_length(iterations));
+    }
+    assert!(program.max_trace_length(100) >= program.max_trace_length(20));
+
+    // bounds of nested loops multiply, and the length saturates instead of overflowing
+    let program = assembly::compile("begin read while.true read while.true read end read end end").unwrap();
+    assert!(program.max_trace_length(64) > program.max_trace_length(8) * 8);
+    assert_eq!(usize::MAX, program.max_trace_length(usize::MAX));
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
 fn build_first_block(op_code: OpCode, length: usize) -> ProgramBlock {
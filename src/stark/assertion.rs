@@ -0,0 +1,57 @@
+use serde::{ Serialize, Deserialize };
+use crate::MAX_ASSERTIONS;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// An assertion that user stack register `register` holds `value` at the specified `step` of the
+/// execution trace. Assertions are enforced as additional boundary constraints, and can be used
+/// to bind intermediate states of an execution (e.g. a checkpoint in the middle of a program).
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Assertion {
+    pub register    : usize,
+    pub step        : usize,
+    pub value       : u128,
+}
+
+// ASSERTION IMPLEMENTATION
+// ================================================================================================
+impl Assertion {
+
+    pub fn new(register: usize, step: usize, value: u128) -> Assertion {
+        return Assertion { register, step, value };
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure that the `assertions` can be enforced against an execution trace of the specified
+/// `trace_length` with `stack_depth` user stack registers.
+pub fn validate_assertions(assertions: &[Assertion], trace_length: usize, stack_depth: usize) -> Result<(), String> {
+    if assertions.len() > MAX_ASSERTIONS {
+        return Err(format!("no more than {} assertions can be made, but {} were provided",
+            MAX_ASSERTIONS, assertions.len()));
+    }
+
+    for assertion in assertions.iter() {
+        if assertion.register >= stack_depth {
+            return Err(format!("cannot make an assertion about register {}: trace has only {} user stack registers",
+                assertion.register, stack_depth));
+        }
+        if assertion.step >= trace_length {
+            return Err(format!("cannot make an assertion about step {}: trace has only {} steps",
+                assertion.step, trace_length));
+        }
+    }
+
+    return Ok(());
+}
+
+/// Returns a sorted list of distinct steps at which the `assertions` are made.
+pub fn get_assertion_steps(assertions: &[Assertion]) -> Vec<usize> {
+    let mut steps: Vec<usize> = assertions.iter().map(|a| a.step).collect();
+    steps.sort_unstable();
+    steps.dedup();
+    return steps;
+}
@@ -0,0 +1,170 @@
+use crate::{ math::field, utils::as_bytes, PROGRAM_DIGEST_SIZE };
+use super::{ TraceTable, Assertion, validate_assertions };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Public values which boundary constraints bind to the execution trace:
+///
+/// * the operation sponge at the last step of the trace must contain `program_hash`; this is
+///   what binds a proof to a specific program;
+/// * the user stack at the first step must start with `inputs`, and at the last step - with
+///   `outputs`;
+/// * user stack registers must hold asserted values at the steps of `assertions`.
+///
+/// The prover checks that the trace satisfies these constraints before generating a proof, and
+/// the verifier builds the same constraints from the public values it is given; thus, neither of
+/// them can be invoked without a program hash.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundaryConstraints {
+    program_hash    : [u8; 32],
+    inputs          : Vec<u128>,
+    outputs         : Vec<u128>,
+    assertions      : Vec<Assertion>,
+}
+
+// BOUNDARY CONSTRAINTS IMPLEMENTATION
+// ================================================================================================
+impl BoundaryConstraints {
+
+    /// Returns boundary constraints which require the operation sponge at the last step of the
+    /// execution trace to contain the specified `program_hash`; inputs, outputs, and assertions
+    /// can be added using `with_inputs()`, `with_outputs()`, and `with_assertions()` methods.
+    pub fn program_hash(program_hash: &[u8; 32]) -> BoundaryConstraints {
+        return BoundaryConstraints {
+            program_hash    : *program_hash,
+            inputs          : Vec::new(),
+            outputs         : Vec::new(),
+            assertions      : Vec::new(),
+        };
+    }
+
+    /// Requires the user stack at the first step of the execution trace to start with `inputs`.
+    pub fn with_inputs(mut self, inputs: &[u128]) -> BoundaryConstraints {
+        self.inputs = inputs.to_vec();
+        return self;
+    }
+
+    /// Requires the user stack at the last step of the execution trace to start with `outputs`.
+    pub fn with_outputs(mut self, outputs: &[u128]) -> BoundaryConstraints {
+        self.outputs = outputs.to_vec();
+        return self;
+    }
+
+    /// Requires the `assertions` about intermediate states of the execution trace to hold.
+    pub fn with_assertions(mut self, assertions: &[Assertion]) -> BoundaryConstraints {
+        self.assertions = assertions.to_vec();
+        return self;
+    }
+
+    // PUBLIC ACCESSORS
+    // --------------------------------------------------------------------------------------------
+
+    pub fn get_program_hash(&self) -> &[u8; 32] {
+        return &self.program_hash;
+    }
+
+    pub fn get_inputs(&self) -> &[u128] {
+        return &self.inputs;
+    }
+
+    pub fn get_outputs(&self) -> &[u128] {
+        return &self.outputs;
+    }
+
+    pub fn get_assertions(&self) -> &[Assertion] {
+        return &self.assertions;
+    }
+
+    /// Returns the program hash as it appears in the operation sponge: as field elements built
+    /// from 16-byte halves of the hash.
+    pub(crate) fn get_program_digest(&self) -> [u128; PROGRAM_DIGEST_SIZE] {
+        return [
+            field::from_bytes(&self.program_hash[..16]),
+            field::from_bytes(&self.program_hash[16..]),
+        ];
+    }
+
+    /// Returns the number of boundary constraints enforced against the execution trace.
+    pub(crate) fn constraint_count(&self) -> usize {
+        return PROGRAM_DIGEST_SIZE
+            + self.inputs.len() + self.outputs.len() + self.assertions.len()
+            + 1 /* for op_count */
+            + 1 /* for initial stack depth */;
+    }
+
+    // TRACE VALIDATION
+    // --------------------------------------------------------------------------------------------
+
+    /// Checks whether the execution `trace` satisfies these boundary constraints; returns an
+    /// error describing the first constraint which does not hold.
+    pub fn check_trace(&self, trace: &TraceTable) -> Result<(), String> {
+
+        // make sure program hash generated by the VM matches the claimed program hash
+        let last_state = trace.get_last_state();
+        if last_state.program_hash() != self.get_program_digest() {
+            return Err(format!("expected program hash {} does not match trace hash {}",
+                hex::encode(self.program_hash), hex::encode(as_bytes(last_state.program_hash()))));
+        }
+
+        // make sure the stack starts with the inputs and ends with the outputs; if the trace has
+        // been extended already, steps of the original trace are spread out over the domain
+        let stride = if trace.is_extended() { trace.extension_factor() } else { 1 };
+        let first_state = trace.get_state(0);
+        if !first_state.user_stack().starts_with(&self.inputs) {
+            return Err(format!("inputs {:?} do not match the initial state of the stack", self.inputs));
+        }
+        if !last_state.user_stack().starts_with(&self.outputs) {
+            return Err(format!("outputs {:?} do not match the final state of the stack", self.outputs));
+        }
+
+        // make sure all assertions hold
+        validate_assertions(&self.assertions, trace.unextended_length(), trace.stack_depth())?;
+        for assertion in self.assertions.iter() {
+            let value = trace.get_state(assertion.step * stride).user_stack()[assertion.register];
+            if value != assertion.value {
+                return Err(format!("assertion failed: register {} at step {} is {}, but expected {}",
+                    assertion.register, assertion.step, value, assertion.value));
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, stark::{ TraceTable, Assertion }, MIN_EXTENSION_FACTOR };
+    use super::BoundaryConstraints;
+
+    #[test]
+    fn check_trace() {
+        let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1]);
+        let (registers, ctx_depth, loop_depth) = crate::generate_trace(&program, &inputs);
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+
+        let boundary = BoundaryConstraints::program_hash(program.hash())
+            .with_inputs(&[1])
+            .with_outputs(&[8, 1])
+            .with_assertions(&[Assertion::new(0, 1, 1)]);
+        assert_eq!(Ok(()), boundary.check_trace(&trace));
+        assert_eq!(boundary.get_program_digest().to_vec(), trace.get_last_state().program_hash().to_vec());
+
+        // a different program hash is rejected even though the trace is otherwise valid
+        let other = assembly::compile("begin push.3 push.5 mul end").unwrap();
+        let result = BoundaryConstraints::program_hash(other.hash()).check_trace(&trace);
+        assert!(result.unwrap_err().starts_with("expected program hash"));
+
+        let result = BoundaryConstraints::program_hash(program.hash()).with_outputs(&[15]).check_trace(&trace);
+        assert_eq!(Err(String::from("outputs [15] do not match the final state of the stack")), result);
+
+        let result = BoundaryConstraints::program_hash(program.hash())
+            .with_assertions(&[Assertion::new(0, 1, 2)])
+            .check_trace(&trace);
+        assert_eq!(Err(String::from("assertion failed: register 0 at step 1 is 1, but expected 2")), result);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{ math::field, stark::TraceTable };
+use super::{ decoder::Decoder, stack::Stack, description::build_description };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Result of checking a single group of transition constraints (e.g. `op_bits` or `stack_depth`)
+/// against an execution trace; groups are the same as the ones listed by `describe_constraints()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintGroupCheck {
+    pub group           : &'static str,
+    pub num_constraints : usize,
+    pub failure         : Option<ConstraintFailure>,
+}
+
+/// The first transition constraint of a group which did not hold for an execution trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintFailure {
+    pub step            : usize,
+    pub index           : usize,
+    pub description     : String,
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Evaluates all transition constraints of the VM over consecutive states of the un-extended
+/// `trace`, and returns the result of the check for every constraint group. For every group
+/// which does not hold, the first failing step and the first failing constraint at that step
+/// are reported. Unlike proof generation, this does not require extending the trace, and so,
+/// it is a quick way to find out which constraints an execution trace violates.
+pub fn check_constraints(trace: &TraceTable) -> Vec<ConstraintGroupCheck> {
+
+    let trace_length = trace.unextended_length();
+    let decoder = Decoder::new(trace_length, 1, trace.ctx_depth(), trace.loop_depth());
+    let stack = Stack::new(trace_length, 1, trace.stack_depth());
+    let description = build_description(trace.ctx_depth(), trace.loop_depth(), trace.stack_depth());
+
+    // group constraints in the order in which the groups first appear in the description
+    let mut result: Vec<ConstraintGroupCheck> = Vec::new();
+    let mut group_indexes = Vec::with_capacity(description.constraints.len());
+    for constraint in description.constraints.iter() {
+        let index = match result.iter().position(|check| check.group == constraint.group) {
+            Some(index) => index,
+            None => {
+                result.push(ConstraintGroupCheck { group: constraint.group, num_constraints: 0, failure: None });
+                result.len() - 1
+            }
+        };
+        result[index].num_constraints += 1;
+        group_indexes.push(index);
+    }
+
+    // evaluate constraints at every step except for the last one
+    let num_decoder_constraints = decoder.constraint_degrees().len();
+    let mut evaluations = vec![field::ZERO; description.constraints.len()];
+    let mut current = trace.get_state(0);
+    let mut next = trace.get_state(0);
+    for step in 0..(trace_length - 1) {
+        trace.fill_state(&mut next, step + 1);
+        decoder.evaluate(&current, &next, step, &mut evaluations[..num_decoder_constraints]);
+        stack.evaluate(&current, &next, step, &mut evaluations[num_decoder_constraints..]);
+
+        for (i, &evaluation) in evaluations.iter().enumerate() {
+            let check = &mut result[group_indexes[i]];
+            if evaluation != field::ZERO && check.failure.is_none() {
+                check.failure = Some(ConstraintFailure {
+                    step, index: i, description: description.constraints[i].description.clone()
+                });
+            }
+        }
+
+        std::mem::swap(&mut current, &mut next);
+    }
+
+    return result;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, stark::TraceTable, MIN_EXTENSION_FACTOR };
+    use super::check_constraints;
+
+    #[test]
+    fn check_valid_and_tampered_traces() {
+        let program = assembly::compile("begin push.3 push.5 add read.a mul end").unwrap();
+        let inputs = ProgramInputs::new(&[], &[7], &[]);
+        let (registers, ctx_depth, loop_depth) = crate::generate_trace(&program, &inputs);
+
+        // all constraints hold for a trace generated by the VM
+        let trace = TraceTable::new(registers.clone(), ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let checks = check_constraints(&trace);
+        assert!(checks.iter().all(|check| check.failure.is_none()));
+        assert_eq!(checks[0].group, "op_bits");
+
+        // changing the value at the top of the stack breaks only user stack constraints
+        let mut registers = registers;
+        let top_register = registers.len() - trace.stack_depth();
+        registers[top_register][20] = 42;
+        let trace = TraceTable::new(registers, ctx_depth, loop_depth, MIN_EXTENSION_FACTOR);
+        let checks = check_constraints(&trace);
+        for check in checks.iter() {
+            match check.failure {
+                Some(ref failure) => {
+                    assert_eq!("user_stack", check.group);
+                    assert_eq!(19, failure.step);
+                },
+                None => assert_ne!("user_stack", check.group),
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::math::{ field, polynom, parallel };
+use crate::math::{ field, polynom, parallel, fft::FftPlan };
 use crate::stark::{ MAX_CONSTRAINT_DEGREE, utils::CompositionCoefficients };
 
 // TYPES AND INTERFACES
@@ -25,13 +25,13 @@ impl ConstraintPoly {
         return get_expected_degree(&self.poly);
     }
 
-    pub fn eval(&self, twiddles: &[u128]) -> Vec<u128> {
-        let domain_size = twiddles.len() * 2;
+    pub fn eval(&self, plan: &FftPlan) -> Vec<u128> {
+        let domain_size = plan.size();
         assert!(domain_size > self.poly.len(), "domain size must be greater than poly length");
 
         let mut evaluations = vec![field::ZERO; domain_size];
         evaluations[..self.poly.len()].copy_from_slice(&self.poly);
-        polynom::eval_fft_twiddles(&mut evaluations, twiddles, true);
+        plan.forward(&mut evaluations);
 
         return evaluations;
     }
@@ -1,28 +1,34 @@
-use crate::math::{ field, parallel, fft, polynom };
-use crate::stark::{ TraceTable, TraceState };
-use crate::utils::{ uninit_vector };
+use crate::math::{ field, parallel, fft::FftPlan, polynom };
+use crate::stark::{ TraceTable, TraceState, BoundaryConstraints, AirExtension, ProverSetup };
+use crate::utils::{ zeroed_vector };
 use super::{ ConstraintEvaluator, ConstraintPoly };
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct ConstraintTable {
-    evaluator       : ConstraintEvaluator,
+pub struct ConstraintTable<'a> {
+    evaluator       : ConstraintEvaluator<'a>,
     i_evaluations   : Vec<u128>,    // combined evaluations of boundary constraints at the first step
     f_evaluations   : Vec<u128>,    // combined evaluations of boundary constraints at the last step
+    a_evaluations   : Vec<Vec<u128>>, // combined evaluations of user assertions for each asserted step
     t_evaluations   : Vec<u128>,    // combined evaluations of transition constraints
 }
 
 // CONSTRAINT TABLE IMPLEMENTATION
 // ================================================================================================
-impl ConstraintTable {
-    pub fn new(trace: &TraceTable, trace_root: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> ConstraintTable {
-        let evaluator = ConstraintEvaluator::from_trace(trace, trace_root, inputs, outputs);
+impl<'a> ConstraintTable<'a> {
+    pub fn new(trace: &TraceTable, setup: &ProverSetup, trace_root: &[u8; 32], boundary: &BoundaryConstraints, extension: Option<&'a dyn AirExtension>) -> ConstraintTable<'a> {
+        let evaluator = ConstraintEvaluator::from_trace(trace, setup.decoder.clone(), setup.stack.clone(),
+            trace_root, boundary, extension);
         let evaluation_domain_size = evaluator.domain_size();
+        let a_evaluations = evaluator.assertion_steps().iter()
+            .map(|_| zeroed_vector(evaluation_domain_size))
+            .collect();
         return ConstraintTable {
             evaluator       : evaluator,
-            i_evaluations   : uninit_vector(evaluation_domain_size),
-            f_evaluations   : uninit_vector(evaluation_domain_size),
-            t_evaluations   : uninit_vector(evaluation_domain_size),
+            i_evaluations   : zeroed_vector(evaluation_domain_size),
+            f_evaluations   : zeroed_vector(evaluation_domain_size),
+            a_evaluations   : a_evaluations,
+            t_evaluations   : zeroed_vector(evaluation_domain_size),
         };
     }
 
@@ -46,41 +52,54 @@ impl ConstraintTable {
         let (init_bound, last_bound) = self.evaluator.evaluate_boundaries(current, x);
         self.i_evaluations[step] = init_bound;
         self.f_evaluations[step] = last_bound;
+        let assertions = self.evaluator.evaluate_assertions(current, x);
+        for (evaluations, value) in self.a_evaluations.iter_mut().zip(assertions) {
+            evaluations[step] = value;
+        }
         self.t_evaluations[step] = self.evaluator.evaluate_transition(current, next, x, step);
     }
 
     /// Interpolates all constraint evaluations into polynomials and combines all these 
-    /// polynomials into a single polynomial using pseudo-random linear combination.
-    pub fn combine_polys(mut self) -> ConstraintPoly
+    /// polynomials into a single polynomial using pseudo-random linear combination; `plan` must
+    /// be an FFT plan for the constraint evaluation domain.
+    pub fn combine_polys(mut self, plan: &FftPlan) -> ConstraintPoly
     {
-        let combination_root = field::get_root_of_unity(self.evaluation_domain_size());
-        let inv_twiddles = fft::get_inv_twiddles(combination_root, self.evaluation_domain_size());
+        assert!(plan.size() == self.evaluation_domain_size(), "invalid FFT plan size");
      
         #[cfg(debug_assertions)]
         self.validate_transition_degrees();
         
-        let mut combined_poly = uninit_vector(self.evaluation_domain_size());
+        let mut combined_poly = zeroed_vector(self.evaluation_domain_size());
         
         // 1 ----- boundary constraints for the initial step --------------------------------------
         // interpolate initial step boundary constraint combination into a polynomial, divide the 
         // polynomial by Z(x) = (x - 1), and add it to the result
-        polynom::interpolate_fft_twiddles(&mut self.i_evaluations, &inv_twiddles, true);
+        plan.inverse(&mut self.i_evaluations);
         polynom::syn_div_in_place(&mut self.i_evaluations, field::ONE);
         combined_poly.copy_from_slice(&self.i_evaluations);
 
         // 2 ----- boundary constraints for the final step ----------------------------------------
         // interpolate final step boundary constraint combination into a polynomial, divide the 
         // polynomial by Z(x) = (x - x_at_last_step), and add it to the result
-        polynom::interpolate_fft_twiddles(&mut self.f_evaluations, &inv_twiddles, true);
+        plan.inverse(&mut self.f_evaluations);
         let x_at_last_step = self.evaluator.get_x_at_last_step();
         polynom::syn_div_in_place(&mut self.f_evaluations, x_at_last_step);
         parallel::add_in_place(&mut combined_poly, &self.f_evaluations, 1);
 
-        // 3 ----- transition constraints ---------------------------------------------------------
+        // 3 ----- user assertions ----------------------------------------------------------------
+        // interpolate combination of assertions made at each asserted step into a polynomial,
+        // divide the polynomial by Z(x) = (x - x_at_step), and add it to the result
+        for (evaluations, &step) in self.a_evaluations.iter_mut().zip(self.evaluator.assertion_steps()) {
+            plan.inverse(evaluations);
+            polynom::syn_div_in_place(evaluations, self.evaluator.get_x_at_step(step));
+            parallel::add_in_place(&mut combined_poly, evaluations, 1);
+        }
+
+        // 4 ----- transition constraints ---------------------------------------------------------
         // interpolate transition constraint combination into a polynomial, divide the polynomial
         // by Z(x) = (x^steps - 1) / (x - x_at_last_step), and add it to the result
         let trace_length = self.trace_length();
-        polynom::interpolate_fft_twiddles(&mut self.t_evaluations, &inv_twiddles, true);
+        plan.inverse(&mut self.t_evaluations);
         polynom::syn_div_expanded_in_place(&mut self.t_evaluations, trace_length, &[x_at_last_step]);
         parallel::add_in_place(&mut combined_poly, &self.t_evaluations, 1);
 
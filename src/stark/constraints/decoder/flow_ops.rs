@@ -170,6 +170,7 @@ pub fn enforce_void(result: &mut [u128], current: &TraceState, next: &TraceState
 mod tests {
     
     use crate::processor::opcodes::{ FlowOps, UserOps };
+    use crate::NUM_DEPTH_REGISTERS;
     use super::{ TraceState, are_equal };
 
     #[test]
@@ -456,6 +457,7 @@ mod tests {
 
         state.extend_from_slice(ctx_stack);
         state.extend_from_slice(loop_stack);
+        state.extend_from_slice(&[0; NUM_DEPTH_REGISTERS]);
         state.push(101); // single value for user stack
 
         return TraceState::from_vec(ctx_depth, loop_depth, 1, &state);
@@ -3,7 +3,7 @@ use crate::{
     math::{ field, polynom },
     processor::opcodes::{ FlowOps, UserOps },
     stark::trace::TraceState,
-    utils::sponge::ARK, SPONGE_WIDTH, BASE_CYCLE_LENGTH, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH,
+    crypto::rescue::SPONGE, SPONGE_WIDTH, BASE_CYCLE_LENGTH, MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH,
 };
 use super::utils::{
     are_equal, is_zero, is_binary, binary_not, extend_constants, EvaluationResult,
@@ -27,7 +27,7 @@ mod tests;
 
 // CONSTANTS
 // ================================================================================================
-const NUM_OP_CONSTRAINTS: usize = 15;
+pub(super) const NUM_OP_CONSTRAINTS: usize = 15;
 const OP_CONSTRAINT_DEGREES: [usize; NUM_OP_CONSTRAINTS] = [
     2, 2, 2, 2, 2, 2, 2, 2, 2, 2,   // all op bits are binary
     3,                              // op_counter should be incremented for HACC operations
@@ -37,7 +37,7 @@ const OP_CONSTRAINT_DEGREES: [usize; NUM_OP_CONSTRAINTS] = [
     4,                              // operations happen on allowed step multiples
 ];
 
-const NUM_SPONGE_CONSTRAINTS: usize = 4;
+pub(super) const NUM_SPONGE_CONSTRAINTS: usize = 4;
 const SPONGE_CONSTRAINT_DEGREES: [usize; NUM_SPONGE_CONSTRAINTS] = [
     6, 7, 6, 6,                     // sponge transition constraints
 ];
@@ -87,7 +87,7 @@ impl Decoder {
         let cycle_length = BASE_CYCLE_LENGTH * extension_factor;
 
         // extend rounds constants by the specified extension factor
-        let (ark_polys, ark_evaluations) = extend_constants(&ARK, extension_factor);
+        let (ark_polys, ark_evaluations) = extend_constants(SPONGE.ark(), extension_factor);
         let ark_values = transpose_ark_constants(ark_evaluations, cycle_length);
 
         // extend mask constants by the specified extension factor
@@ -216,7 +216,7 @@ fn transpose_mask_constants(constants: Vec<Vec<u128>>, cycle_length: usize) -> V
 
 // CYCLE MASKS
 // ================================================================================================
-const MASKS: [[u128; BASE_CYCLE_LENGTH]; 3] = [
+pub(super) const MASKS: [[u128; BASE_CYCLE_LENGTH]; 3] = [
     [0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],   // multiples of 16
     [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0],   // one less than multiple of 16
     [0, 1, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1],   // multiples of 8
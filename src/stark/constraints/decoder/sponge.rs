@@ -1,6 +1,6 @@
 use crate::{
     math::field::{ self, add, sub, mul },
-    utils::sponge::{ apply_sbox, apply_mds, apply_inv_mds },
+    crypto::rescue::SPONGE,
 };
 use super::{ TraceState, UserOps, are_equal, EvaluationResult, SPONGE_WIDTH };
 
@@ -20,8 +20,8 @@ pub fn enforce_hacc(result: &mut [u128], current: &TraceState, next: &TraceState
     for i in 0..SPONGE_WIDTH {
         old_sponge[i] = add(old_sponge[i], ark[i]);
     }
-    apply_sbox(&mut old_sponge);
-    apply_mds(&mut old_sponge);
+    SPONGE.apply_sbox(&mut old_sponge);
+    SPONGE.apply_mds(&mut old_sponge);
 
     // op_code injection
     old_sponge[0] = add(old_sponge[0], current.op_code());
@@ -30,8 +30,8 @@ pub fn enforce_hacc(result: &mut [u128], current: &TraceState, next: &TraceState
     // evaluate inverse of the second half of Rescue round
     let mut new_sponge = [field::ZERO; SPONGE_WIDTH];
     new_sponge.copy_from_slice(next.sponge());
-    apply_inv_mds(&mut new_sponge);
-    apply_sbox(&mut new_sponge);
+    SPONGE.apply_inv_mds(&mut new_sponge);
+    SPONGE.apply_sbox(&mut new_sponge);
     for i in 0..SPONGE_WIDTH {
         new_sponge[i] = sub(new_sponge[i], ark[SPONGE_WIDTH + i]);
     }
@@ -47,19 +47,19 @@ pub fn enforce_hacc(result: &mut [u128], current: &TraceState, next: &TraceState
 #[cfg(test)]
 mod tests {
     
-    use crate::{ SPONGE_WIDTH, BASE_CYCLE_LENGTH };
-    use crate::utils::sponge::{ apply_round as apply_hacc_round, ARK };
+    use crate::{ SPONGE_WIDTH, BASE_CYCLE_LENGTH, NUM_DEPTH_REGISTERS };
+    use crate::crypto::rescue::{ apply_hacc_round, SPONGE };
     use super::{ TraceState, super::transpose_ark_constants };
 
     #[test]
     fn op_hacc() {
 
-        let ark: Vec<Vec<u128>> = ARK.iter().map(|row| row.to_vec()).collect();
+        let ark: Vec<Vec<u128>> = SPONGE.ark().iter().map(|row| row.to_vec()).collect();
         let ark = transpose_ark_constants(ark, BASE_CYCLE_LENGTH);
 
         // correct transition, push.7
         let push_value = 7;
-        let state1 = TraceState::from_vec(1, 0, 1, &vec![0,  1, 2, 3, 4,  0, 0, 0,  1, 1, 1, 1, 1,  0, 0,  0,  0]);
+        let state1 = new_state(vec![0,  1, 2, 3, 4,  0, 0, 0,  1, 1, 1, 1, 1,  0, 0,  0,  0]);
 
         let mut sponge = [1, 2, 3, 4];
         apply_hacc_round(&mut sponge, state1.op_code(), push_value, 0);
@@ -71,7 +71,7 @@ mod tests {
         assert_eq!(vec![0, 0, 0, 0], evaluations);
 
         // correct transition, non-push op
-        let state1 = TraceState::from_vec(1, 0, 1, &vec![0,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  1, 1,  0,  0]);
+        let state1 = new_state(vec![0,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  1, 1,  0,  0]);
 
         let mut sponge = [1, 2, 3, 4];
         apply_hacc_round(&mut sponge, state1.op_code(), 0, 0);
@@ -83,7 +83,7 @@ mod tests {
 
         // incorrect transition, push.7
         let push_value = 7;
-        let state1 = TraceState::from_vec(1, 0, 1, &vec![0,  1, 2, 3, 4,  0, 0, 0,  1, 1, 1, 1, 1,  0, 0,  0,  0]);
+        let state1 = new_state(vec![0,  1, 2, 3, 4,  0, 0, 0,  1, 1, 1, 1, 1,  0, 0,  0,  0]);
 
         let mut sponge = [1, 2, 3, 4];
         apply_hacc_round(&mut sponge, state1.op_code(), push_value, 0);
@@ -95,7 +95,7 @@ mod tests {
         assert_eq!(vec![0, 340282366920938463463374557953744961536, 0, 0], evaluations);
 
         // incorrect transition, non-push op
-        let state1 = TraceState::from_vec(1, 0, 1, &vec![0,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  1, 1,  0,  0]);
+        let state1 = new_state(vec![0,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  1, 1,  0,  0]);
 
         let mut sponge = [1, 2, 3, 4];
         apply_hacc_round(&mut sponge, state1.op_code(), 9, 0);
@@ -112,6 +112,14 @@ mod tests {
         let state = vec![
             0, sponge[0], sponge[1], sponge[2], sponge[3],  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  0,  push_value
         ];
+        return new_state(state);
+    }
+
+    /// Builds a trace state from a row which contains no stack depth registers; stack depth
+    /// registers are inserted (set to 0s) in front of the last register of the row.
+    fn new_state(mut state: Vec<u128>) -> TraceState {
+        let user_stack_start = state.len() - 1;
+        state.splice(user_stack_start..user_stack_start, vec![0; NUM_DEPTH_REGISTERS]);
         return TraceState::from_vec(1, 0, 1, &state);
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
-use crate::utils::sponge::{ apply_round as apply_hacc_round };
+use crate::crypto::rescue::{ apply_hacc_round };
+use crate::NUM_DEPTH_REGISTERS;
 use super::{ Decoder, TraceState, UserOps };
 
 // CONSTANTS
@@ -108,10 +109,18 @@ fn new_decoder(ctx_depth: usize, loop_depth: usize) -> Decoder {
 
 fn evaluate_transition(decoder: &Decoder, step: usize, state1: Vec<u128>, state2: Vec<u128>) -> Vec<u128>
 {
-    let state1 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &state1);
-    let state2 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &state2);
+    let state1 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &add_depth_registers(state1));
+    let state2 = TraceState::from_vec(decoder.ctx_depth(), decoder.loop_depth(), 1, &add_depth_registers(state2));
 
     let mut evaluations = vec![0; decoder.constraint_degrees().len()];
     decoder.evaluate(&state1, &state2, step, &mut evaluations);
     return evaluations
-}
\ No newline at end of file
+}
+
+/// Inserts stack depth registers (set to 0s) in front of the last register of the `state`; stack
+/// depth registers are not used by decoder constraints.
+fn add_depth_registers(mut state: Vec<u128>) -> Vec<u128> {
+    let user_stack_start = state.len() - 1;
+    state.splice(user_stack_start..user_stack_start, vec![0; NUM_DEPTH_REGISTERS]);
+    return state;
+}
@@ -0,0 +1,458 @@
+use std::cmp;
+use serde::Serialize;
+use crate::{
+    math::field,
+    processor::opcodes::{ FlowOps, UserOps },
+    stark::TraceState,
+    BASE_CYCLE_LENGTH, SPONGE_WIDTH, HASH_STATE_WIDTH, NUM_DEPTH_BITS,
+    MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH,
+};
+use super::{
+    decoder::{ Decoder, NUM_OP_CONSTRAINTS, NUM_SPONGE_CONSTRAINTS, MASKS },
+    stack::{ Stack, NUM_AUX_CONSTRAINTS, NUM_DEPTH_CONSTRAINTS },
+};
+
+// CONSTANTS
+// ================================================================================================
+const MASK_NAMES: [&str; 3] = [ "cycle_mask", "prefix_mask", "push_mask" ];
+const SPONGE_ARK: &str = "sponge_ark";
+const HASH_ARK: &str = "hash_ark";
+
+/// Step of the constant cycle at which all cycle masks are set to 1; constraints are evaluated
+/// at this step when determining involved registers so that no mask hides a dependency.
+const PROBE_STEP: usize = 1;
+const PROBE_SEED: [u8; 32] = [42; 32];
+
+const USER_OPS: [UserOps; 36] = [
+    UserOps::Assert, UserOps::AssertEq, UserOps::AssertBin, UserOps::Eq, UserOps::Drop, UserOps::Drop4, UserOps::Drop8,
+    UserOps::Choose, UserOps::Choose2, UserOps::CSwap2,
+    UserOps::Add, UserOps::Mul, UserOps::And, UserOps::Or, UserOps::Inv, UserOps::Neg, UserOps::Not,
+    UserOps::Read, UserOps::Read2, UserOps::Dup, UserOps::Dup2, UserOps::Dup4, UserOps::Dup8, UserOps::Pad2,
+    UserOps::Swap, UserOps::Swap2, UserOps::Swap4, UserOps::Swap8, UserOps::Roll4, UserOps::Roll8, UserOps::BinAcc,
+    UserOps::Push, UserOps::Cmp, UserOps::RescR,
+    UserOps::Begin, UserOps::Noop,
+];
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Machine-readable description of all transition constraints for a given trace layout.
+#[derive(Serialize)]
+pub struct ConstraintSystem {
+    pub ctx_depth   : usize,
+    pub loop_depth  : usize,
+    pub stack_depth : usize,
+    pub registers   : Vec<String>,
+    pub masks       : Vec<CycleMask>,
+    pub constraints : Vec<ConstraintDescription>,
+}
+
+/// A periodic column with a period of 16 steps used to restrict when a constraint applies.
+#[derive(Serialize)]
+pub struct CycleMask {
+    pub name        : &'static str,
+    pub values      : Vec<u128>,
+}
+
+/// Description of a single transition constraint.
+///
+/// * `registers` lists all trace registers on which the constraint depends; registers in the
+///   next step of the trace are marked with `'` (e.g. `sponge[0]'`);
+/// * `selectors` lists operation flags which gate the constraint (e.g. `cf_op_flags[hacc]`);
+///   constraints without selectors are enforced at every step;
+/// * `masks` lists cycle masks used by the constraint;
+/// * `round_constants` names the set of periodic round constants used by the constraint, if any.
+#[derive(Serialize)]
+pub struct ConstraintDescription {
+    pub index           : usize,
+    pub group           : &'static str,
+    pub description     : String,
+    pub degree          : usize,
+    pub registers       : Vec<String>,
+    pub selectors       : Vec<String>,
+    pub masks           : Vec<&'static str>,
+    pub round_constants : Option<&'static str>,
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Returns a JSON description of every transition constraint enforced for an execution trace
+/// with the specified context stack, loop stack, and user stack depths. Constraints are listed
+/// in the same order in which they are evaluated by the constraint evaluator.
+pub fn describe(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> String {
+    let system = build_description(ctx_depth, loop_depth, stack_depth);
+    return serde_json::to_string_pretty(&system).expect("failed to serialize constraint description");
+}
+
+/// Builds a description of all transition constraints; degrees are taken directly from
+/// constraint evaluators, and involved registers are determined by evaluating each constraint
+/// against pseudo-random trace states.
+pub fn build_description(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> ConstraintSystem {
+
+    let decoder = Decoder::new(BASE_CYCLE_LENGTH, 1, ctx_depth, loop_depth);
+    let stack = Stack::new(BASE_CYCLE_LENGTH, 1, stack_depth);
+
+    let degrees = [decoder.constraint_degrees(), stack.constraint_degrees()].concat();
+    let mut constraints = describe_decoder(ctx_depth, loop_depth);
+    constraints.append(&mut describe_stack(stack_depth));
+    assert!(constraints.len() == degrees.len(),
+        "expected {} constraint descriptions, but {} were built", degrees.len(), constraints.len());
+
+    let registers = get_register_names(ctx_depth, loop_depth, stack_depth);
+    let dependencies = find_dependencies(&decoder, &stack, ctx_depth, loop_depth, stack_depth);
+
+    for (i, constraint) in constraints.iter_mut().enumerate() {
+        constraint.index = i;
+        constraint.degree = degrees[i];
+        constraint.registers = dependencies[i].iter()
+            .map(|&(r, is_next)| if is_next { format!("{}'", registers[r]) } else { registers[r].clone() })
+            .collect();
+    }
+
+    let masks = MASKS.iter().zip(MASK_NAMES.iter())
+        .map(|(values, &name)| CycleMask { name, values: values.to_vec() })
+        .collect();
+
+    return ConstraintSystem { ctx_depth, loop_depth, stack_depth, registers, masks, constraints };
+}
+
+// DECODER CONSTRAINTS
+// ================================================================================================
+fn describe_decoder(ctx_depth: usize, loop_depth: usize) -> Vec<ConstraintDescription> {
+    let mut result = Vec::new();
+
+    // op bit constraints
+    for (name, count) in [("cf_op_bits", 3), ("ld_op_bits", 5), ("hd_op_bits", 2)].iter() {
+        for i in 0..*count {
+            result.push(new_constraint("op_bits", format!("{}[{}] is binary", name, i), vec![], vec![]));
+        }
+    }
+    result.push(new_constraint("op_bits",
+        "op_counter is incremented by HACC and remains the same otherwise".to_string(),
+        cf_flags(&[FlowOps::Hacc]), vec![]));
+    result.push(new_constraint("op_bits",
+        "ld_op_bits and hd_op_bits cannot be all 0s after the first step".to_string(),
+        vec![], vec![]));
+    result.push(new_constraint("op_bits",
+        "when cf_op_bits are not all 0s, ld_op_bits and hd_op_bits must be all 1s".to_string(),
+        vec![], vec![]));
+    result.push(new_constraint("op_bits",
+        "VOID can be followed only by VOID".to_string(),
+        cf_flags(&[FlowOps::Void]), vec![]));
+
+    let mut selectors = cf_flags(&[
+        FlowOps::Begin, FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Tend, FlowOps::Fend,
+    ]);
+    selectors.push(hd_flag(UserOps::Push));
+    result.push(new_constraint("op_bits",
+        "BEGIN, LOOP, WRAP, BREAK happen one step before a multiple of 16, TEND and FEND \
+        happen on multiples of 16, and PUSH happens on multiples of 8".to_string(),
+        selectors, vec![MASK_NAMES[1], MASK_NAMES[0], MASK_NAMES[2]]));
+    debug_assert!(result.len() == NUM_OP_CONSTRAINTS, "wrong number of op bit constraints");
+
+    // sponge constraints; TEND does not constrain the 3rd element of the sponge, and FEND does
+    // not constrain the 2nd element of the sponge
+    for i in 0..NUM_SPONGE_CONSTRAINTS {
+        let mut ops = vec![FlowOps::Hacc, FlowOps::Begin];
+        if i != 2 { ops.push(FlowOps::Tend); }
+        if i != 1 { ops.push(FlowOps::Fend); }
+        ops.extend_from_slice(&[FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Void]);
+
+        let mut selectors = cf_flags(&ops);
+        if i == 1 { selectors.push(hd_flag(UserOps::Push)); }
+
+        let mut constraint = new_constraint("sponge",
+            format!("sponge[{}] is updated according to the executed flow control operation", i),
+            selectors, vec![]);
+        constraint.round_constants = Some(SPONGE_ARK);
+        result.push(constraint);
+    }
+
+    // loop image constraint
+    result.push(new_constraint("loop_image",
+        "loop image in the sponge matches the top of the loop stack".to_string(),
+        cf_flags(&[FlowOps::Wrap, FlowOps::Break]), vec![]));
+
+    // context and loop stack constraints
+    let stack_ops = [
+        FlowOps::Begin, FlowOps::Tend, FlowOps::Fend,
+        FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Void,
+    ];
+    for i in 0..cmp::max(ctx_depth, MIN_CONTEXT_DEPTH) {
+        result.push(new_constraint("ctx_stack",
+            format!("ctx_stack[{}] is updated according to the executed flow control operation", i),
+            cf_flags(&stack_ops), vec![]));
+    }
+
+    for i in 0..cmp::max(loop_depth, MIN_LOOP_DEPTH) {
+        // LOOP does not constrain the top of the loop stack since it holds the new loop image
+        let ops: Vec<FlowOps> = stack_ops.iter()
+            .filter(|&&op| i != 0 || op != FlowOps::Loop).cloned().collect();
+        result.push(new_constraint("loop_stack",
+            format!("loop_stack[{}] is updated according to the executed flow control operation", i),
+            cf_flags(&ops), vec![]));
+    }
+
+    return result;
+}
+
+// STACK CONSTRAINTS
+// ================================================================================================
+fn describe_stack(stack_depth: usize) -> Vec<ConstraintDescription> {
+    let mut result = Vec::new();
+
+    // auxiliary constraints
+    result.push(new_constraint("stack_aux",
+        "first auxiliary constraint of assertion, boolean, comparison, and selection operations"
+        .to_string(),
+        user_flags(&[
+            UserOps::Assert, UserOps::AssertEq, UserOps::AssertBin, UserOps::Not, UserOps::And, UserOps::Or,
+            UserOps::Eq, UserOps::Choose, UserOps::Choose2, UserOps::CSwap2,
+        ]), vec![]));
+    result.push(new_constraint("stack_aux",
+        "second auxiliary constraint of boolean operations".to_string(),
+        user_flags(&[UserOps::And, UserOps::Or]), vec![]));
+    debug_assert!(result.len() == NUM_AUX_CONSTRAINTS, "wrong number of aux constraints");
+
+    // depth constraints; BEGIN and NOOP are not included because they don't affect the depth
+    let depth_ops: Vec<UserOps> = USER_OPS[..34].to_vec();
+    result.push(new_constraint("stack_depth",
+        "depth changes by the number of items pushed or removed by the executed operation"
+        .to_string(), user_flags(&depth_ops), vec![]));
+    result.push(new_constraint("stack_depth",
+        "underflow bits decompose the difference between depth and the depth required by the \
+        executed operation".to_string(), user_flags(&depth_ops), vec![]));
+    result.push(new_constraint("stack_depth",
+        "overflow bits decompose the difference between stack register count and depth"
+        .to_string(), vec![], vec![]));
+    for name in ["underflow_bits", "overflow_bits"].iter() {
+        for i in 0..NUM_DEPTH_BITS {
+            result.push(new_constraint("stack_depth", format!("{}[{}] is binary", name, i),
+                vec![], vec![]));
+        }
+    }
+    debug_assert!(result.len() == NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS,
+        "wrong number of depth constraints");
+
+    // user stack constraints; values of the top slots written by PUSH, READ, and READ2 are not
+    // constrained by the stack (for PUSH, the value is constrained by the decoder); SWAP8 is
+    // constrained only when there are at least 16 stack registers (otherwise, it underflows)
+    for i in 0..stack_depth {
+        let ops: Vec<UserOps> = USER_OPS.iter().filter(|&&op| match op {
+            UserOps::Push | UserOps::Read => i != 0,
+            UserOps::Read2 => i > 1,
+            UserOps::Swap8 => stack_depth >= 16,
+            _ => true,
+        }).cloned().collect();
+
+        let mut constraint = new_constraint("user_stack",
+            format!("user_stack[{}] is updated according to the executed user operation", i),
+            user_flags(&ops), vec![]);
+        if i < HASH_STATE_WIDTH {
+            constraint.round_constants = Some(HASH_ARK);
+        }
+        result.push(constraint);
+    }
+
+    return result;
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn new_constraint(group: &'static str, description: String, selectors: Vec<String>, masks: Vec<&'static str>)
+    -> ConstraintDescription
+{
+    return ConstraintDescription {
+        index: 0, group, description, degree: 0, registers: Vec::new(), selectors, masks,
+        round_constants: None,
+    };
+}
+
+fn cf_flags(ops: &[FlowOps]) -> Vec<String> {
+    return ops.iter().map(|op| format!("cf_op_flags[{}]", op)).collect();
+}
+
+fn hd_flag(op: UserOps) -> String {
+    return format!("hd_op_flags[{}]", op);
+}
+
+fn user_flags(ops: &[UserOps]) -> Vec<String> {
+    return ops.iter().map(|&op| match op {
+        UserOps::Begin => "begin_flag".to_string(),
+        UserOps::Noop => "noop_flag".to_string(),
+        UserOps::Push | UserOps::Cmp | UserOps::RescR => hd_flag(op),
+        _ => format!("ld_op_flags[{}]", op),
+    }).collect();
+}
+
+/// Returns names of all registers in the execution trace in the order in which they appear
+/// in the trace.
+fn get_register_names(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> Vec<String> {
+    let mut names = vec!["op_counter".to_string()];
+    let groups = [
+        ("sponge", SPONGE_WIDTH), ("cf_op_bits", 3), ("ld_op_bits", 5), ("hd_op_bits", 2),
+        ("ctx_stack", ctx_depth), ("loop_stack", loop_depth),
+    ];
+    for (name, count) in groups.iter() {
+        names.extend((0..*count).map(|i| format!("{}[{}]", name, i)));
+    }
+    names.push("depth".to_string());
+    for name in ["underflow_bits", "overflow_bits"].iter() {
+        names.extend((0..NUM_DEPTH_BITS).map(|i| format!("{}[{}]", name, i)));
+    }
+    names.extend((0..stack_depth).map(|i| format!("user_stack[{}]", i)));
+    return names;
+}
+
+/// Determines which registers of the current and the next step each constraint depends on. This
+/// is done by perturbing one register at a time in a pseudo-random pair of states and checking
+/// which constraint evaluations change as the result. Since register values are random, all
+/// operation flags are non-zero, and so, constraints of all operations are taken into account.
+fn find_dependencies(decoder: &Decoder, stack: &Stack, ctx_depth: usize, loop_depth: usize, stack_depth: usize)
+    -> Vec<Vec<(usize, bool)>>
+{
+    let width = TraceState::new(ctx_depth, loop_depth, stack_depth).to_vec().len();
+    let values = field::prng_vector(PROBE_SEED, 2 * width);
+    let current = values[..width].to_vec();
+    let next = values[width..].to_vec();
+
+    let evaluate = |current: &Vec<u128>, next: &Vec<u128>| {
+        let current = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, current);
+        let next = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, next);
+        let mut decoder_result = vec![field::ZERO; decoder.constraint_count()];
+        decoder.evaluate(&current, &next, PROBE_STEP, &mut decoder_result);
+        let mut stack_result = vec![field::ZERO; stack.constraint_degrees().len()];
+        stack.evaluate(&current, &next, PROBE_STEP, &mut stack_result);
+        return [decoder_result, stack_result].concat();
+    };
+
+    let base = evaluate(&current, &next);
+    let mut dependencies = vec![Vec::new(); base.len()];
+
+    for &is_next in [false, true].iter() {
+        for r in 0..width {
+            let (mut current, mut next) = (current.clone(), next.clone());
+            let state = if is_next { &mut next } else { &mut current };
+            state[r] = field::add(state[r], field::ONE);
+
+            let evaluations = evaluate(&current, &next);
+            for (i, (&a, &b)) in base.iter().zip(evaluations.iter()).enumerate() {
+                if a != b { dependencies[i].push((r, is_next)); }
+            }
+        }
+    }
+
+    return dependencies;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ math::field, stark::TraceState };
+    use super::{
+        Decoder, Stack, FlowOps, UserOps, USER_OPS, BASE_CYCLE_LENGTH, PROBE_STEP, PROBE_SEED,
+    };
+
+    const FLOW_OPS: [FlowOps; 8] = [
+        FlowOps::Hacc, FlowOps::Begin, FlowOps::Tend, FlowOps::Fend,
+        FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Void,
+    ];
+
+    #[test]
+    fn describe() {
+        let system = super::build_description(2, 1, 10);
+        let decoder = Decoder::new(BASE_CYCLE_LENGTH, 1, 2, 1);
+        let stack = Stack::new(BASE_CYCLE_LENGTH, 1, 10);
+
+        // degrees match the ones used by constraint evaluators
+        let degrees = [decoder.constraint_degrees(), stack.constraint_degrees()].concat();
+        let described: Vec<usize> = system.constraints.iter().map(|c| c.degree).collect();
+        assert_eq!(degrees, described);
+
+        // involved registers are detected in current and next states
+        let sponge = &system.constraints[15];
+        assert!(sponge.registers.contains(&"sponge[0]".to_string()));
+        assert!(sponge.registers.contains(&"sponge[0]'".to_string()));
+        assert!(sponge.registers.contains(&"cf_op_bits[0]".to_string()));
+        assert!(!sponge.registers.contains(&"depth".to_string()));
+
+        let alignment = &system.constraints[14];
+        assert_eq!(vec!["prefix_mask", "cycle_mask", "push_mask"], alignment.masks);
+
+        // output is valid JSON
+        let json = super::describe(2, 1, 10);
+        assert!(json.starts_with("{") && json.contains("\"constraints\""));
+    }
+
+    #[test]
+    fn describe_selectors() {
+        let depths = (2, 2, 10);
+        let (ctx_depth, loop_depth, stack_depth) = depths;
+        let system = super::build_description(ctx_depth, loop_depth, stack_depth);
+        let decoder = Decoder::new(BASE_CYCLE_LENGTH, 1, ctx_depth, loop_depth);
+        let stack = Stack::new(BASE_CYCLE_LENGTH, 1, stack_depth);
+        let width = TraceState::new(ctx_depth, loop_depth, stack_depth).to_vec().len();
+        let values = field::prng_vector(PROBE_SEED, 2 * width);
+
+        // a constraint is gated by an operation if it is non-zero for random register values
+        // when op bits are set to that operation; flow control operations are checked for
+        // sponge, loop image, ctx stack and loop stack constraints, and user operations are
+        // checked for aux and user stack constraints
+        let decoder_count = decoder.constraint_count();
+        let mut expected = vec![Vec::new(); system.constraints.len()];
+        for &op in FLOW_OPS.iter() {
+            let evaluations = evaluate(&decoder, &stack, &values, op, UserOps::Noop, depths);
+            for i in 15..decoder_count {
+                if evaluations[i] != 0 { expected[i].push(format!("cf_op_flags[{}]", op)); }
+            }
+        }
+
+        for &op in USER_OPS.iter() {
+            let evaluations = evaluate(&decoder, &stack, &values, FlowOps::Hacc, op, depths);
+            for i in decoder_count..system.constraints.len() {
+                if evaluations[i] != 0 { expected[i].extend(super::user_flags(&[op])); }
+            }
+        }
+
+        for (i, constraint) in system.constraints.iter().enumerate() {
+            if i < 15 || constraint.group == "stack_depth" { continue; }
+            let mut expected = expected[i].clone();
+            let mut selectors = constraint.selectors.clone();
+            if i == 16 {
+                // PUSH selector for sponge[1] is combined with HACC
+                expected.push("hd_op_flags[push]".to_string());
+            }
+            expected.sort();
+            selectors.sort();
+            assert_eq!(expected, selectors, "selectors of constraint {}", i);
+        }
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+    fn evaluate(decoder: &Decoder, stack: &Stack, values: &[u128], flow_op: FlowOps, user_op: UserOps,
+        depths: (usize, usize, usize)) -> Vec<u128>
+    {
+        let (ctx_depth, loop_depth, stack_depth) = depths;
+        let width = values.len() / 2;
+        let mut current = values[..width].to_vec();
+        let next = values[width..].to_vec();
+        for i in 0..3 {
+            current[5 + i] = ((flow_op as u128) >> i) & 1;
+        }
+        for i in 0..7 {
+            current[8 + i] = ((user_op as u128) >> i) & 1;
+        }
+
+        let current = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, &current);
+        let next = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, &next);
+        let mut decoder_result = vec![0; decoder.constraint_count()];
+        decoder.evaluate(&current, &next, PROBE_STEP, &mut decoder_result);
+        let mut stack_result = vec![0; stack.constraint_degrees().len()];
+        stack.evaluate(&current, &next, PROBE_STEP, &mut stack_result);
+        return [decoder_result, stack_result].concat();
+    }
+}
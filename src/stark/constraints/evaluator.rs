@@ -1,16 +1,20 @@
+use std::sync::Arc;
+use std::cell::{ Ref, RefCell };
 use crate::{
     math::field,
-    utils::uninit_vector,
-    stark::{ StarkProof, TraceTable, TraceState, ConstraintCoefficients },
-    PROGRAM_DIGEST_SIZE,
+    utils::zeroed_vector,
+    stark::{
+        StarkProof, TraceTable, TraceState, ConstraintCoefficients, BoundaryConstraints, AirExtension,
+        get_assertion_steps, get_extension_constraint_num },
 };
 use super::{ decoder::Decoder, stack::Stack, super::MAX_CONSTRAINT_DEGREE };
 
 // TYPES AND INTERFACES
 // ================================================================================================
-pub struct Evaluator {
-    decoder         : Decoder,
-    stack           : Stack,
+pub struct Evaluator<'a> {
+    decoder         : Arc<Decoder>,
+    stack           : Arc<Stack>,
+    extension       : Option<&'a dyn AirExtension>,
 
     coefficients    : ConstraintCoefficients,
     domain_size     : usize,
@@ -18,21 +22,24 @@ pub struct Evaluator {
 
     t_constraint_num: usize,
     t_degree_groups : Vec<(u128, Vec<usize>)>,
-    t_evaluations   : Vec<Vec<u128>>,
+    t_evaluations   : RefCell<Vec<Vec<u128>>>,
 
     b_constraint_num: usize,
+    boundary        : BoundaryConstraints,
     program_hash    : Vec<u128>,
     op_count        : u128,
-    inputs          : Vec<u128>,
-    outputs         : Vec<u128>,
+    a_steps         : Vec<usize>,
     b_degree_adj    : u128,
 }
 
 // EVALUATOR IMPLEMENTATION
 // ================================================================================================
-impl Evaluator {
+impl<'a> Evaluator<'a> {
 
-    pub fn from_trace(trace: &TraceTable, trace_root: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> Evaluator
+    /// Creates an evaluator for the constraints of the `trace`; `decoder` and `stack` must be
+    /// built for the shape of the trace with extension factor equal to MAX_CONSTRAINT_DEGREE.
+    /// Boundary constraints are taken from `boundary` rather than from the trace itself.
+    pub fn from_trace(trace: &TraceTable, decoder: Arc<Decoder>, stack: Arc<Stack>, trace_root: &[u8; 32], boundary: &BoundaryConstraints, extension: Option<&'a dyn AirExtension>) -> Evaluator<'a>
     {
         let last_state = trace.get_last_state();
         let ctx_depth = trace.ctx_depth();
@@ -41,20 +48,14 @@ impl Evaluator {
         let trace_length = trace.unextended_length();
         let extension_factor = MAX_CONSTRAINT_DEGREE;
 
-        // instantiate decoder and stack constraint evaluators 
-        let decoder = Decoder::new(trace_length, extension_factor, ctx_depth, loop_depth);
-        let stack = Stack::new(trace_length, extension_factor, stack_depth);
-
         // build a list of transition constraint degrees
-        let t_constraint_degrees = [
-            decoder.constraint_degrees(), stack.constraint_degrees()
-        ].concat();
+        let t_constraint_degrees = get_transition_degrees(&decoder, &stack, extension);
 
         // if we are in debug mode, initialize vectors to hold individual evaluations
         // of transition constraints
         let domain_size = trace_length * extension_factor;
         let t_evaluations = if cfg!(debug_assertions) {
-            t_constraint_degrees.iter().map(|_| uninit_vector(domain_size)).collect()
+            t_constraint_degrees.iter().map(|_| zeroed_vector(domain_size)).collect()
         }
         else {
             Vec::new()
@@ -63,22 +64,24 @@ impl Evaluator {
         return Evaluator {
             decoder         : decoder,
             stack           : stack,
-            coefficients    : ConstraintCoefficients::new(*trace_root, ctx_depth, loop_depth, stack_depth),
+            extension       : extension,
+            coefficients    : ConstraintCoefficients::new(*trace_root, ctx_depth, loop_depth, stack_depth,
+                get_extension_constraint_num(extension)),
             domain_size     : domain_size,
             extension_factor: extension_factor,
             t_constraint_num: t_constraint_degrees.len(),
             t_degree_groups : group_transition_constraints(t_constraint_degrees, trace_length),
-            t_evaluations   : t_evaluations,
-            b_constraint_num: get_boundary_constraint_num(&inputs, &outputs),
-            program_hash    : last_state.program_hash().to_vec(),
+            t_evaluations   : RefCell::new(t_evaluations),
+            b_constraint_num: boundary.constraint_count(),
+            boundary        : boundary.clone(),
+            program_hash    : boundary.get_program_digest().to_vec(),
             op_count        : last_state.op_counter(),
-            inputs          : inputs.to_vec(),
-            outputs         : outputs.to_vec(),
+            a_steps         : get_assertion_steps(boundary.get_assertions()),
             b_degree_adj    : get_boundary_constraint_adjustment_degree(trace_length),
         };
     }
 
-    pub fn from_proof(proof: &StarkProof, program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128]) -> Evaluator
+    pub fn from_proof(proof: &StarkProof, boundary: &BoundaryConstraints, extension: Option<&'a dyn AirExtension>) -> Evaluator<'a>
     {
         let ctx_depth = proof.ctx_depth();
         let loop_depth = proof.loop_depth();
@@ -87,28 +90,28 @@ impl Evaluator {
         let extension_factor = proof.options().extension_factor();
         
         // instantiate decoder and stack constraint evaluators 
-        let decoder = Decoder::new(trace_length, extension_factor, ctx_depth, loop_depth);
-        let stack = Stack::new(trace_length, extension_factor, stack_depth);
+        let decoder = Arc::new(Decoder::new(trace_length, extension_factor, ctx_depth, loop_depth));
+        let stack = Arc::new(Stack::new(trace_length, extension_factor, stack_depth));
 
         // build a list of transition constraint degrees
-        let t_constraint_degrees = [
-            decoder.constraint_degrees(), stack.constraint_degrees()
-        ].concat();
+        let t_constraint_degrees = get_transition_degrees(&decoder, &stack, extension);
 
         return Evaluator {
             decoder         : decoder,
             stack           : stack,
-            coefficients    : ConstraintCoefficients::new(*proof.trace_root(), ctx_depth, loop_depth, stack_depth),
+            extension       : extension,
+            coefficients    : ConstraintCoefficients::new(*proof.trace_root(), ctx_depth, loop_depth, stack_depth,
+                get_extension_constraint_num(extension)),
             domain_size     : proof.domain_size(),
             extension_factor: extension_factor,
             t_constraint_num: t_constraint_degrees.len(),
             t_degree_groups : group_transition_constraints(t_constraint_degrees, trace_length),
-            t_evaluations   : Vec::new(),
-            b_constraint_num: get_boundary_constraint_num(&inputs, &outputs),
-            program_hash    : parse_program_hash(program_hash),
+            t_evaluations   : RefCell::new(Vec::new()),
+            b_constraint_num: boundary.constraint_count(),
+            boundary        : boundary.clone(),
+            program_hash    : boundary.get_program_digest().to_vec(),
             op_count        : proof.op_count(),
-            inputs          : inputs.to_vec(),
-            outputs         : outputs.to_vec(),
+            a_steps         : get_assertion_steps(boundary.get_assertions()),
             b_degree_adj    : get_boundary_constraint_adjustment_degree(trace_length),
         };
     }
@@ -126,8 +129,17 @@ impl Evaluator {
     }
 
     pub fn get_x_at_last_step(&self) -> u128 {
+        return self.get_x_at_step(self.trace_length() - 1);
+    }
+
+    pub fn get_x_at_step(&self, step: usize) -> u128 {
         let trace_root = field::get_root_of_unity(self.trace_length());
-        return field::exp(trace_root, (self.trace_length() - 1) as u128);
+        return field::exp(trace_root, step as u128);
+    }
+
+    /// Returns a sorted list of distinct steps at which user assertions are made.
+    pub fn assertion_steps(&self) -> &[usize] {
+        return &self.a_steps;
     }
 
     // CONSTRAINT EVALUATORS
@@ -142,6 +154,7 @@ impl Evaluator {
         let mut evaluations = vec![field::ZERO; self.t_constraint_num];
         self.decoder.evaluate(&current, &next, step, &mut evaluations);
         self.stack.evaluate(&current, &next, step, &mut evaluations[self.decoder.constraint_count()..]);
+        self.evaluate_extension(&current, &next, &mut evaluations);
 
         // when in debug mode, save transition evaluations before they are combined
         #[cfg(debug_assertions)]
@@ -169,6 +182,7 @@ impl Evaluator {
         let mut evaluations = vec![field::ZERO; self.t_constraint_num];
         self.decoder.evaluate_at(&current, &next, x, &mut evaluations);
         self.stack.evaluate_at(&current, &next, x, &mut evaluations[self.decoder.constraint_count()..]);
+        self.evaluate_extension(&current, &next, &mut evaluations);
 
         // compute a pseudo-random linear combination of all transition constraints
         return self.combine_transition_constraints(&evaluations, x);
@@ -240,10 +254,16 @@ impl Evaluator {
             result_adj = field::add(result_adj, field::mul(loop_stack[i], cc.loop_stack[i * 2 + 1]));
         }
 
+        // make sure stack depth is set to the number of inputs
+        let inputs = self.boundary.get_inputs();
+        let val = field::sub(current.depth(), inputs.len() as u128);
+        i_result = field::add(i_result, field::mul(val, cc.depth[0]));
+        result_adj = field::add(result_adj, field::mul(val, cc.depth[1]));
+
         // make sure stack registers are set to inputs
         let user_stack = current.user_stack();
-        for i in 0..self.inputs.len() {
-            let val = field::sub(user_stack[i], self.inputs[i]);
+        for i in 0..inputs.len() {
+            let val = field::sub(user_stack[i], inputs[i]);
             i_result = field::add(i_result, field::mul(val, cc.user_stack[i * 2]));
             result_adj = field::add(result_adj, field::mul(val, cc.user_stack[i * 2 + 1]));
         }
@@ -313,8 +333,9 @@ impl Evaluator {
         }
 
         // make sure user stack registers are set to outputs
-        for i in 0..self.outputs.len() {
-            let val = field::sub(user_stack[i], self.outputs[i]);
+        let outputs = self.boundary.get_outputs();
+        for i in 0..outputs.len() {
+            let val = field::sub(user_stack[i], outputs[i]);
             f_result = field::add(f_result, field::mul(val, cc.user_stack[i * 2]));
             result_adj = field::add(result_adj, field::mul(val, cc.user_stack[i * 2 + 1]));
         }        
@@ -325,8 +346,46 @@ impl Evaluator {
         return (i_result, f_result);
     }
 
+    /// Computes pseudo-random linear combinations of user assertions A_i at point x; assertions
+    /// made at the same step are combined together, and a separate combination is returned for
+    /// every step in `assertion_steps()`. The combinations are computed in the same way as for
+    /// boundary constraints.
+    pub fn evaluate_assertions(&self, current: &TraceState, x: u128) -> Vec<u128> {
+
+        // compute degree adjustment factor
+        let xp = field::exp(x, self.b_degree_adj);
+
+        let cc = &self.coefficients.assertions;
+        let user_stack = current.user_stack();
+
+        let mut result = Vec::with_capacity(self.a_steps.len());
+        for &step in self.a_steps.iter() {
+            let mut a_result = field::ZERO;
+            let mut result_adj = field::ZERO;
+            for (i, assertion) in self.boundary.get_assertions().iter().enumerate() {
+                if assertion.step != step { continue; }
+                let val = field::sub(user_stack[assertion.register], assertion.value);
+                a_result = field::add(a_result, field::mul(val, cc[i * 2]));
+                result_adj = field::add(result_adj, field::mul(val, cc[i * 2 + 1]));
+            }
+            result.push(field::add(a_result, field::mul(result_adj, xp)));
+        }
+
+        return result;
+    }
+
     // HELPER METHODS
     // -------------------------------------------------------------------------------------------
+
+    /// Evaluates AIR extension constraints (if any); evaluations of extension constraints are
+    /// placed after evaluations of decoder and stack constraints.
+    fn evaluate_extension(&self, current: &TraceState, next: &TraceState, evaluations: &mut [u128]) {
+        if let Some(extension) = self.extension {
+            let offset = self.decoder.constraint_count() + self.stack.constraint_degrees().len();
+            extension.evaluate(current, next, &mut evaluations[offset..]);
+        }
+    }
+
     fn should_evaluate_to_zero_at(&self, step: usize) -> bool {
         return (step & (self.extension_factor - 1) == 0) // same as: step % extension_factor == 0
             && (step != self.domain_size - self.extension_factor);
@@ -359,29 +418,36 @@ impl Evaluator {
 
     #[cfg(debug_assertions)]
     fn save_transition_evaluations(&self, evaluations: &[u128], step: usize) {
-        unsafe {
-            let mutable_self = &mut *(self as *const _ as *mut Evaluator);
-            for i in 0..evaluations.len() {
-                mutable_self.t_evaluations[i][step] = evaluations[i];
-            }
+        let mut t_evaluations = self.t_evaluations.borrow_mut();
+        for i in 0..evaluations.len() {
+            t_evaluations[i][step] = evaluations[i];
         }
     }
 
     #[cfg(debug_assertions)]
-    pub fn get_transition_evaluations(&self) -> &Vec<Vec<u128>> {
-        return &self.t_evaluations;
+    pub fn get_transition_evaluations(&self) -> Ref<'_, Vec<Vec<u128>>> {
+        return self.t_evaluations.borrow();
     }
 
     #[cfg(debug_assertions)]
     pub fn get_transition_degrees(&self) -> Vec<usize> {
-        return [
-            self.decoder.constraint_degrees(), self.stack.constraint_degrees()
-        ].concat();
+        return get_transition_degrees(&self.decoder, &self.stack, self.extension);
     }
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
+
+/// Returns degrees of all transition constraints in the order in which they are evaluated:
+/// decoder constraints, stack constraints, and then AIR extension constraints.
+fn get_transition_degrees(decoder: &Decoder, stack: &Stack, extension: Option<&dyn AirExtension>) -> Vec<usize> {
+    let mut result = [decoder.constraint_degrees(), stack.constraint_degrees()].concat();
+    if let Some(extension) = extension {
+        result.extend(extension.constraint_degrees());
+    }
+    return result;
+}
+
 fn group_transition_constraints(degrees: Vec<usize>, trace_length: usize) -> Vec<(u128, Vec<usize>)> {
     let mut groups = [
         Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(),
@@ -428,17 +494,3 @@ fn get_transition_constraint_target_degree(trace_length: usize) -> usize {
     let divisor_degree = trace_length - 1;
     return combination_degree + divisor_degree;
 }
-
-fn parse_program_hash(program_hash: &[u8; 32]) -> Vec<u128> {
-    return vec![
-        field::from_bytes(&program_hash[..16]),
-        field::from_bytes(&program_hash[16..]),
-    ];
-}
-
-fn get_boundary_constraint_num(inputs: &[u128], outputs: &[u128]) -> usize {
-    return
-        PROGRAM_DIGEST_SIZE 
-        + inputs.len() + outputs.len()
-        + 1 /* for op_count */;
-}
\ No newline at end of file
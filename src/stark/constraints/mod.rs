@@ -4,9 +4,15 @@ mod stack;
 mod constraint_table;
 mod constraint_poly;
 mod utils;
+mod description;
+mod checker;
 
 pub use decoder::{ NUM_STATIC_DECODER_CONSTRAINTS };
-pub use stack::{ NUM_AUX_CONSTRAINTS as NUM_AUX_STACK_CONSTRAINTS };
+pub use stack::{ NUM_AUX_CONSTRAINTS as NUM_AUX_STACK_CONSTRAINTS, NUM_DEPTH_CONSTRAINTS };
+pub use decoder::{ Decoder };
+pub use stack::{ Stack };
 pub use evaluator::{ Evaluator as ConstraintEvaluator};
 pub use constraint_table::{ ConstraintTable };
-pub use constraint_poly::{ ConstraintPoly };
\ No newline at end of file
+pub use constraint_poly::{ ConstraintPoly };
+pub use description::{ describe };
+pub use checker::{ check_constraints, ConstraintGroupCheck, ConstraintFailure };
\ No newline at end of file
@@ -35,6 +35,14 @@ pub fn enforce_asserteq(result: &mut [u128], aux: &mut [u128], old_stack: &[u128
     aux.agg_constraint(0, op_flag, are_equal(old_stack[0], old_stack[1]));
 }
 
+/// Enforces constraints for ASSERTBIN operation. The stack remains unchanged and an auxiliary
+/// constraint enforces that x * (1 - x) = 0, where x is the top of the stack.
+pub fn enforce_assertbin(result: &mut [u128], aux: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
+{
+    enforce_stack_copy(result, old_stack, new_stack, 0, op_flag);
+    aux.agg_constraint(0, op_flag, is_binary(old_stack[0]));
+}
+
 // EQUALITY
 // ================================================================================================
 
@@ -0,0 +1,164 @@
+use crate::{
+    math::field,
+    processor::OpCode,
+    stark::TraceState,
+    NUM_DEPTH_BITS,
+};
+use super::{ are_equal, is_binary };
+
+// CONSTANTS
+// ================================================================================================
+pub const NUM_DEPTH_CONSTRAINTS: usize = 3 + 2 * NUM_DEPTH_BITS;
+pub const DEPTH_CONSTRAINT_DEGREES: [usize; NUM_DEPTH_CONSTRAINTS] = [
+    6,                                  // depth changes as specified by the executed operation
+    6,                                  // underflow bits decompose depth slack
+    1,                                  // overflow bits decompose stack headroom
+    2, 2, 2, 2, 2, 2,                   // all underflow bits are binary
+    2, 2, 2, 2, 2, 2,                   // all overflow bits are binary
+];
+
+const LD_OPS: [OpCode; 31] = [
+    OpCode::Assert, OpCode::AssertEq, OpCode::AssertBin, OpCode::Eq, OpCode::Drop, OpCode::Drop4, OpCode::Drop8,
+    OpCode::Choose, OpCode::Choose2, OpCode::CSwap2,
+    OpCode::Add, OpCode::Mul, OpCode::And, OpCode::Or, OpCode::Inv, OpCode::Neg, OpCode::Not,
+    OpCode::Read, OpCode::Read2, OpCode::Dup, OpCode::Dup2, OpCode::Dup4, OpCode::Dup8, OpCode::Pad2,
+    OpCode::Swap, OpCode::Swap2, OpCode::Swap4, OpCode::Swap8, OpCode::Roll4, OpCode::Roll8, OpCode::BinAcc,
+];
+
+const HD_OPS: [OpCode; 3] = [ OpCode::Push, OpCode::Cmp, OpCode::RescR ];
+
+// STACK DEPTH CONSTRAINTS
+// ================================================================================================
+
+/// Enforces constraints for the stack depth register. The depth must change by the number of items
+/// pushed onto (or removed from) the stack by the executed operation; the depth must be at least
+/// as big as the number of items required by the operation (no underflow); and the depth must not
+/// exceed the number of user stack registers in the trace (no overflow). The last two constraints
+/// are enforced by decomposing the respective differences into bits.
+pub fn enforce_depth(result: &mut [u128], current: &TraceState, next: &TraceState, stack_depth: usize)
+{
+    let ld_flags = current.ld_op_flags();
+    let hd_flags = current.hd_op_flags();
+
+    // compute the expected change in depth and the number of items required by the operation;
+    // only the flag of the executed operation is set to 1, and so, only one term of each sum
+    // is non-zero; BEGIN and NOOP are not included because they don't affect the depth
+    let mut depth_change = field::ZERO;
+    let mut required_depth = field::ZERO;
+    for op in LD_OPS.iter() {
+        let flag = ld_flags[op.ld_index()];
+        depth_change = field::add(depth_change, field::mul(flag, to_field(op.depth_change())));
+        required_depth = field::add(required_depth, field::mul(flag, op.required_depth() as u128));
+    }
+
+    for op in HD_OPS.iter() {
+        let flag = hd_flags[op.hd_index()];
+        depth_change = field::add(depth_change, field::mul(flag, to_field(op.depth_change())));
+        required_depth = field::add(required_depth, field::mul(flag, op.required_depth() as u128));
+    }
+
+    // depth transition
+    let depth = current.depth();
+    result[0] = are_equal(next.depth(), field::add(depth, depth_change));
+
+    // underflow bits must decompose (depth - required_depth), and overflow bits must decompose
+    // (stack_depth - depth); since both values are decomposed into a small number of bits, this
+    // ensures that neither of them is negative
+    let underflow_bits = current.underflow_bits();
+    let overflow_bits = current.overflow_bits();
+
+    let slack = field::sub(depth, required_depth);
+    result[1] = are_equal(slack, combine_bits(underflow_bits));
+
+    let headroom = field::sub(stack_depth as u128, depth);
+    result[2] = are_equal(headroom, combine_bits(overflow_bits));
+
+    for i in 0..NUM_DEPTH_BITS {
+        result[3 + i] = is_binary(underflow_bits[i]);
+        result[3 + NUM_DEPTH_BITS + i] = is_binary(overflow_bits[i]);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn to_field(value: isize) -> u128 {
+    return if value < 0 { field::neg(-value as u128) } else { value as u128 };
+}
+
+fn combine_bits(bits: &[u128]) -> u128 {
+    let mut result = field::ZERO;
+    for i in 0..bits.len() {
+        result = field::add(result, field::mul(bits[i], 1 << i));
+    }
+    return result;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ math::field, processor::OpCode, stark::TraceState, NUM_DEPTH_BITS };
+    use super::{ NUM_DEPTH_CONSTRAINTS };
+
+    #[test]
+    fn enforce_depth() {
+        let success_result = vec![0; NUM_DEPTH_CONSTRAINTS];
+
+        // correct transition: DROP with 2 items on the stack
+        let state1 = build_state(OpCode::Drop, 2, 1, 0);
+        let state2 = build_state(OpCode::Noop, 1, 1, 1);
+        assert_eq!(success_result, evaluate(&state1, &state2));
+
+        // correct transition: DUP with 1 item on the stack
+        let state1 = build_state(OpCode::Dup, 1, 0, 1);
+        let state2 = build_state(OpCode::Noop, 2, 2, 0);
+        assert_eq!(success_result, evaluate(&state1, &state2));
+
+        // incorrect transition: depth not reduced by DROP
+        let state1 = build_state(OpCode::Drop, 2, 1, 0);
+        let state2 = build_state(OpCode::Noop, 2, 2, 0);
+        assert_ne!(success_result, evaluate(&state1, &state2));
+
+        // incorrect transition: DROP with an empty stack; the slack cannot be decomposed into bits
+        let state1 = build_state(OpCode::Drop, 0, field::neg(1), 2);
+        let state2 = build_state(OpCode::Noop, field::neg(1), 0, 0);
+        let evaluations = evaluate(&state1, &state2);
+        assert_eq!(0, evaluations[0]);
+        assert_ne!(0, evaluations[1]);
+
+        // incorrect state: depth exceeds the number of user stack registers; the headroom cannot
+        // be decomposed into bits
+        let state = build_state(OpCode::Noop, 3, 3, field::neg(1));
+        let evaluations = evaluate(&state, &state);
+        assert_eq!(0, evaluations[1]);
+        assert_ne!(0, evaluations[2]);
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+    fn evaluate(current: &TraceState, next: &TraceState) -> Vec<u128> {
+        let mut evaluations = vec![0; NUM_DEPTH_CONSTRAINTS];
+        super::enforce_depth(&mut evaluations, current, next, 2);
+        return evaluations;
+    }
+
+    /// Builds a trace state with a single context register and two user stack registers;
+    /// slack and headroom values are decomposed into bits only if they are small enough.
+    fn build_state(op_code: OpCode, depth: u128, slack: u128, headroom: u128) -> TraceState {
+        let mut state = vec![0,  0, 0, 0, 0,  0, 0, 0];
+        for i in 0..7 {
+            state.push(((op_code as u128) >> i) & 1);
+        }
+        state.push(0); // context stack
+        state.push(depth);
+        for value in [slack, headroom].iter() {
+            for i in 0..NUM_DEPTH_BITS {
+                state.push(if *value < 64 { (value >> i) & 1 } else { 0 });
+            }
+        }
+        state.extend_from_slice(&[0, 0]); // user stack
+
+        return TraceState::from_vec(1, 0, 2, &state);
+    }
+}
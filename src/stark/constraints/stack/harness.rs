@@ -0,0 +1,89 @@
+use crate::{
+    processor::{ self, OpCode, OpHint },
+    stark::TraceState,
+    ProgramInputs, NUM_DEPTH_BITS, MIN_TRACE_LENGTH,
+};
+use super::{ Stack, NUM_AUX_CONSTRAINTS, NUM_DEPTH_CONSTRAINTS };
+
+// CONSTANTS
+// ================================================================================================
+pub const STACK_DEPTH: usize = 8;
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Executes `op_code` with the specified hint against a stack initialized with `inputs`, and
+/// checks the transition made by the processor using `check_transition()`. This way, constraints
+/// of an operation can be tested against the processor without building an execution trace.
+pub fn check_op(op_code: OpCode, op_hint: OpHint, inputs: &ProgramInputs) {
+    let mut stack = processor::Stack::new(inputs, MIN_TRACE_LENGTH);
+    let old_stack = stack.get_stack_top8();
+    stack.execute(op_code, op_hint);
+    check_transition(op_code, &old_stack, &stack.get_stack_top8());
+}
+
+/// Checks that stack transition constraints of `op_code` hold for the transition from
+/// `old_stack` to `new_stack`, and that changing any item of the new stack which the operation
+/// defines breaks the constraints. Items at the bottom of the new stack which are shifted in from
+/// beyond the top 8 items are not perturbed, since they are not visible here; neither are items
+/// which are not constrained by stack constraints (e.g. values read from input tapes).
+pub fn check_transition(op_code: OpCode, old_stack: &[u128; STACK_DEPTH], new_stack: &[u128; STACK_DEPTH]) {
+
+    // all constraints must hold for the valid transition
+    let (aux, result) = evaluate(op_code, old_stack, new_stack);
+    assert!(aux.iter().all(|&v| v == 0), "auxiliary constraints of {} do not hold: {:?}", op_code, aux);
+    assert!(result.iter().all(|&v| v == 0), "stack constraints of {} do not hold: {:?}", op_code, result);
+
+    // changing any of the defined items must break the constraints
+    let num_defined = STACK_DEPTH - std::cmp::max(0, -op_code.depth_change()) as usize;
+    for i in num_unconstrained_items(op_code)..num_defined {
+        let mut perturbed = *new_stack;
+        perturbed[i] = perturbed[i] + 1;
+        let (_, result) = evaluate(op_code, old_stack, &perturbed);
+        assert!(result.iter().any(|&v| v != 0), "changing stack item {} does not break constraints of {}", i, op_code);
+    }
+}
+
+/// Builds a trace state with a single context register and a full user stack; depth
+/// registers are set to zeros since depth constraints are not checked here.
+pub fn build_state(op_code: OpCode, stack: &[u128; STACK_DEPTH]) -> TraceState {
+    let mut state = vec![0,  0, 0, 0, 0,  0, 0, 0];
+    for i in 0..7 {
+        state.push(((op_code as u128) >> i) & 1);
+    }
+    state.push(0); // context stack
+    state.push(STACK_DEPTH as u128);
+    state.resize(state.len() + 2 * NUM_DEPTH_BITS, 0);
+    state.extend_from_slice(stack);
+
+    return TraceState::from_vec(1, 0, STACK_DEPTH, &state);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of items at the top of the new stack which are not constrained by stack
+/// constraints: values read from input tapes are not constrained at all, while values pushed
+/// onto the stack are constrained by the decoder.
+fn num_unconstrained_items(op_code: OpCode) -> usize {
+    return match op_code {
+        OpCode::Read | OpCode::Push => 1,
+        OpCode::Read2 => 2,
+        _ => 0,
+    };
+}
+
+/// Evaluates stack transition constraints at the first step of the execution trace, and returns
+/// evaluations of auxiliary constraints and of user stack constraints.
+fn evaluate(op_code: OpCode, old_stack: &[u128; STACK_DEPTH], new_stack: &[u128; STACK_DEPTH]) -> (Vec<u128>, Vec<u128>) {
+    let current = build_state(op_code, old_stack);
+    let next = build_state(OpCode::Noop, new_stack);
+
+    let stack = Stack::new(MIN_TRACE_LENGTH, 1, STACK_DEPTH);
+    let mut result = vec![0; NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS + STACK_DEPTH];
+    stack.evaluate(&current, &next, 0, &mut result);
+
+    let aux = result[..NUM_AUX_CONSTRAINTS].to_vec();
+    let stack_result = result[(NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS)..].to_vec();
+    return (aux, stack_result);
+}
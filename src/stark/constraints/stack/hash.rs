@@ -1,4 +1,4 @@
-use crate::utils::hasher::{ apply_sbox, apply_mds, apply_inv_mds };
+use crate::crypto::rescue::HASHER;
 use super::{
     field, are_equal, EvaluationResult, enforce_stack_copy,
     HASH_STATE_WIDTH
@@ -14,14 +14,14 @@ pub fn enforce_rescr(result: &mut [u128], old_stack: &[u128], new_stack: &[u128]
     for i in 0..HASH_STATE_WIDTH {
         old_state[i] = field::add(old_state[i], ark[i]);
     }
-    apply_sbox(&mut old_state);
-    apply_mds(&mut old_state);
+    HASHER.apply_sbox(&mut old_state);
+    HASHER.apply_mds(&mut old_state);
 
     // evaluate inverse of the second half of Rescue round
     let mut new_state = [field::ZERO; HASH_STATE_WIDTH];
     new_state.copy_from_slice(&new_stack[..HASH_STATE_WIDTH]);
-    apply_inv_mds(&mut new_state);
-    apply_sbox(&mut new_state);
+    HASHER.apply_inv_mds(&mut new_state);
+    HASHER.apply_sbox(&mut new_state);
     for i in 0..HASH_STATE_WIDTH {
         new_state[i] = field::sub(new_state[i], ark[HASH_STATE_WIDTH + i]);
     }
@@ -35,6 +35,16 @@ pub fn enforce_dup4(result: &mut [u128], old_stack: &[u128], new_stack: &[u128],
     enforce_right_shift(result, old_stack, new_stack, 4, op_flag);
 }
 
+/// Enforces constraints for DUP8 operation. The constraints are based on the first 8 element
+/// of the stack; the old stack is shifted right by 8 element.
+pub fn enforce_dup8(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
+{
+    for i in 0..8 {
+        result.agg_constraint(i, op_flag, are_equal(new_stack[i], old_stack[i]));
+    }
+    enforce_right_shift(result, old_stack, new_stack, 8, op_flag);
+}
+
 /// Enforces constraints for PAD2 operation. The constraints are based on the first 2 element
 /// of the stack; the old stack is shifted right by 2 element.
 pub fn enforce_pad2(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
@@ -56,12 +66,18 @@ pub fn enforce_drop4(result: &mut [u128], old_stack: &[u128], new_stack: &[u128]
     enforce_left_shift(result, old_stack, new_stack, 4, 4, op_flag);
 }
 
+// Enforces constraints for DROP8 operation. The stack is simply shifted left by 8 element.
+pub fn enforce_drop8(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
+{
+    enforce_left_shift(result, old_stack, new_stack, 8, 8, op_flag);
+}
+
 /// Enforces constraints for SWAP operation. The constraints are based on the first 2 element
 /// of the stack; the rest of the stack is unaffected.
 pub fn enforce_swap(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
 {
     result.agg_constraint(0, op_flag, are_equal(new_stack[0], old_stack[1]));
-    result.agg_constraint(0, op_flag, are_equal(new_stack[1], old_stack[0]));
+    result.agg_constraint(1, op_flag, are_equal(new_stack[1], old_stack[0]));
     enforce_stack_copy(result, old_stack, new_stack, 2, op_flag);
 }
 
@@ -91,6 +107,20 @@ pub fn enforce_swap4(result: &mut [u128], old_stack: &[u128], new_stack: &[u128]
     enforce_stack_copy(result, old_stack, new_stack, 8, op_flag);
 }
 
+/// Enforces constraints for SWAP8 operation. The constraints are based on the first 16 element
+/// of the stack; the rest of the stack is unaffected. If the trace has fewer than 16 stack
+/// registers, SWAP8 cannot be executed without underflowing the stack, and this is enforced
+/// by the stack depth constraints.
+pub fn enforce_swap8(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
+{
+    if result.len() < 16 { return; }
+    for i in 0..8 {
+        result.agg_constraint(i, op_flag, are_equal(new_stack[i], old_stack[i + 8]));
+        result.agg_constraint(i + 8, op_flag, are_equal(new_stack[i + 8], old_stack[i]));
+    }
+    enforce_stack_copy(result, old_stack, new_stack, 16, op_flag);
+}
+
 /// Enforces constraints for ROLL4 operation. The constraints are based on the first 4 element
 /// of the stack; the rest of the stack is unaffected.
 pub fn enforce_roll4(result: &mut [u128], old_stack: &[u128], new_stack: &[u128], op_flag: u128)
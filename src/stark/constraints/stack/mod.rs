@@ -2,7 +2,7 @@ use crate::{
     math::{ field, polynom },
     processor::OpCode,
     stark::TraceState,
-    utils::hasher::ARK,
+    crypto::rescue::HASHER,
     BASE_CYCLE_LENGTH, HASH_STATE_WIDTH
 };
 use super::utils::{
@@ -21,12 +21,12 @@ use arithmetic::{
 
 mod manipulation;
 use manipulation::{
-    enforce_dup, enforce_dup2, enforce_dup4, enforce_pad2, enforce_drop, enforce_drop4,
-    enforce_swap, enforce_swap2, enforce_swap4, enforce_roll4, enforce_roll8,
+    enforce_dup, enforce_dup2, enforce_dup4, enforce_dup8, enforce_pad2, enforce_drop, enforce_drop4,
+    enforce_drop8, enforce_swap, enforce_swap2, enforce_swap4, enforce_swap8, enforce_roll4, enforce_roll8,
 };
 
 mod comparison;
-use comparison::{ enforce_assert, enforce_asserteq, enforce_eq, enforce_cmp, enforce_binacc };
+use comparison::{ enforce_assert, enforce_asserteq, enforce_assertbin, enforce_eq, enforce_cmp, enforce_binacc };
 
 mod conditional;
 use conditional::{ enforce_choose, enforce_choose2, enforce_cswap2 };
@@ -34,6 +34,13 @@ use conditional::{ enforce_choose, enforce_choose2, enforce_cswap2 };
 mod hash;
 use hash::{ enforce_rescr };
 
+mod depth;
+use depth::{ enforce_depth, DEPTH_CONSTRAINT_DEGREES };
+pub use depth::{ NUM_DEPTH_CONSTRAINTS };
+
+#[cfg(test)]
+mod harness;
+
 // CONSTANTS
 // ================================================================================================
 pub const NUM_AUX_CONSTRAINTS: usize = 2;
@@ -43,6 +50,7 @@ const STACK_TRANSITION_DEGREE: usize = 7; // degree for all stack register trans
 // TYPES AND INTERFACES
 // ================================================================================================
 pub struct Stack {
+    stack_depth         : usize,
     trace_length        : usize,
     cycle_length        : usize,
     ark_values          : Vec<[u128; 2 * HASH_STATE_WIDTH]>,
@@ -58,17 +66,18 @@ impl Stack {
     {
         // build an array of constraint degrees for the stack
         let mut degrees = Vec::from(&AUX_CONSTRAINT_DEGREES[..]);
-        degrees.resize(stack_depth + NUM_AUX_CONSTRAINTS, STACK_TRANSITION_DEGREE);
+        degrees.extend_from_slice(&DEPTH_CONSTRAINT_DEGREES[..]);
+        degrees.resize(stack_depth + NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS, STACK_TRANSITION_DEGREE);
 
         // determine extended cycle length
         let cycle_length = BASE_CYCLE_LENGTH * extension_factor;
 
         // extend rounds constants by the specified extension factor
-        let (ark_polys, ark_evaluations) = extend_constants(&ARK, extension_factor);
+        let (ark_polys, ark_evaluations) = extend_constants(HASHER.ark(), extension_factor);
         let ark_values = transpose_ark_constants(ark_evaluations, cycle_length);
 
         return Stack {
-            trace_length, cycle_length,
+            stack_depth, trace_length, cycle_length,
             ark_values, ark_polys,
             constraint_degrees: degrees,
         };
@@ -89,7 +98,7 @@ impl Stack {
         let ark = self.ark_values[step % self.cycle_length];
 
         // evaluate transition constraints for the stack
-        enforce_constraints(current, next, &ark, result);
+        enforce_constraints(current, next, &ark, self.stack_depth, result);
     }
 
     /// Evaluates stack transition constraints at the specified x coordinate and saves the
@@ -108,16 +117,21 @@ impl Stack {
         }
 
         // evaluate transition constraints for the stack
-        enforce_constraints(current, next, &ark, result);
+        enforce_constraints(current, next, &ark, self.stack_depth, result);
     }
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], result: &mut [u128])
+fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], stack_depth: usize, result: &mut [u128])
 {
-    // split constraint evaluation result into aux constraints and stack constraints
+    // split constraint evaluation result into aux constraints, depth constraints, and
+    // stack constraints
     let (aux, result) = result.split_at_mut(NUM_AUX_CONSTRAINTS);
+    let (depth, result) = result.split_at_mut(NUM_DEPTH_CONSTRAINTS);
+
+    // enforce constraints for the stack depth register
+    enforce_depth(depth, current, next, stack_depth);
 
     // get user stack registers from current and next steps
     let old_stack = current.user_stack();
@@ -134,6 +148,7 @@ fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], re
     // assertion operations
     enforce_assert  (&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::Assert.ld_index()]);
     enforce_asserteq(&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::AssertEq.ld_index()]);
+    enforce_assertbin(&mut evaluations, aux, old_stack, new_stack, ld_flags[OpCode::AssertBin.ld_index()]);
 
     // input operations
     enforce_read    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Read.ld_index()]);
@@ -143,14 +158,17 @@ fn enforce_constraints(current: &TraceState, next: &TraceState, ark: &[u128], re
     enforce_dup     (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Dup.ld_index()]);
     enforce_dup2    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Dup2.ld_index()]);
     enforce_dup4    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Dup4.ld_index()]);
+    enforce_dup8    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Dup8.ld_index()]);
     enforce_pad2    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Pad2.ld_index()]);
 
     enforce_drop    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Drop.ld_index()]);
     enforce_drop4   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Drop4.ld_index()]);
+    enforce_drop8   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Drop8.ld_index()]);
     
     enforce_swap    (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Swap.ld_index()]);
     enforce_swap2   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Swap2.ld_index()]);
     enforce_swap4   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Swap4.ld_index()]);
+    enforce_swap8   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Swap8.ld_index()]);
 
     enforce_roll4   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Roll4.ld_index()]);
     enforce_roll8   (&mut evaluations,      old_stack, new_stack, ld_flags[OpCode::Roll8.ld_index()]);
@@ -204,4 +222,136 @@ fn transpose_ark_constants(constants: Vec<Vec<u128>>, cycle_length: usize) -> Ve
         }
     }
     return values;
-}
\ No newline at end of file
+}
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ processor::{ OpCode, OpHint }, math::field, ProgramInputs, HASH_STATE_WIDTH };
+    use super::{ NUM_AUX_CONSTRAINTS, NUM_DEPTH_CONSTRAINTS, STACK_TRANSITION_DEGREE };
+    use super::harness::{ check_op, check_transition, build_state, STACK_DEPTH };
+
+    const ALL_OPS: [OpCode; 36] = [
+        OpCode::Assert, OpCode::AssertEq, OpCode::AssertBin, OpCode::Eq, OpCode::Drop, OpCode::Drop4, OpCode::Drop8,
+        OpCode::Choose, OpCode::Choose2, OpCode::CSwap2,
+        OpCode::Add, OpCode::Mul, OpCode::And, OpCode::Or, OpCode::Inv, OpCode::Neg, OpCode::Not,
+        OpCode::Read, OpCode::Read2, OpCode::Dup, OpCode::Dup2, OpCode::Dup4, OpCode::Dup8, OpCode::Pad2,
+        OpCode::Swap, OpCode::Swap2, OpCode::Swap4, OpCode::Swap8, OpCode::Roll4, OpCode::Roll8, OpCode::BinAcc,
+        OpCode::Push, OpCode::Cmp, OpCode::RescR,
+        OpCode::Begin, OpCode::Noop,
+    ];
+
+    #[test]
+    fn all_ops_constrained() {
+        // for every operation, an arbitrary change of the stack must violate stack constraints;
+        // this makes sure that no operation executed by the processor is left unconstrained
+        let current_stack = [1, 0, 1, 1, 0, 1, 0, 1];
+        let next_stack = [101, 102, 103, 104, 105, 106, 107, 108];
+        for &op_code in ALL_OPS.iter() {
+            // SWAP8 cannot be executed on a stack with 8 registers; see swap8_transition
+            if op_code.required_depth() > STACK_DEPTH { continue; }
+            let current = build_state(op_code, &current_stack);
+            let next = build_state(OpCode::Noop, &next_stack);
+
+            let mut result = vec![0; NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS + STACK_DEPTH];
+            let ark = [0; 2 * HASH_STATE_WIDTH];
+            super::enforce_constraints(&current, &next, &ark, STACK_DEPTH, &mut result);
+
+            let stack_result = &result[(NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS)..];
+            assert!(stack_result.iter().any(|&v| v != 0), "{} is not constrained", op_code);
+        }
+    }
+
+    #[test]
+    fn eq_constrained_within_span() {
+        // a forged result of EQ executed within a span must violate stack constraints; this did
+        // not hold while the flag of EQ was selected by a control flow op bit
+        let current = build_state(OpCode::Eq, &[5, 5, 0, 0, 0, 0, 0, 0]);
+        for &forged in [0, 2].iter() {
+            let next = build_state(OpCode::Noop, &[forged, 0, 0, 0, 0, 0, 0, 0]);
+
+            let mut result = vec![0; NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS + STACK_DEPTH];
+            let ark = [0; 2 * HASH_STATE_WIDTH];
+            super::enforce_constraints(&current, &next, &ark, STACK_DEPTH, &mut result);
+
+            let stack_result = &result[(NUM_AUX_CONSTRAINTS + NUM_DEPTH_CONSTRAINTS)..];
+            assert!(stack_result.iter().any(|&v| v != 0), "forged result {} of eq is accepted", forged);
+        }
+    }
+
+    #[test]
+    fn op_transitions() {
+        // constraints of every operation hold for the transition made by the processor, and
+        // break when any item defined by the operation is changed; the stack is chosen so that
+        // binary conditions and operands of assertions are valid for all operations, except for
+        // CMP and BINACC which expect powers of 2 at specific positions
+        let inputs = ProgramInputs::new(&[1, 1, 0, 1, 1, 0, 1, 1], &[1, 0], &[1]);
+        for &op_code in ALL_OPS.iter() {
+            match op_code {
+                OpCode::Swap8 => continue,
+                OpCode::Push => check_op(op_code, OpHint::PushValue(42), &inputs),
+                OpCode::Cmp => check_op(op_code, OpHint::None,
+                    &ProgramInputs::new(&[8, 1, 0, 1, 0, 0, 3, 2], &[1], &[0])),
+                OpCode::BinAcc => check_op(op_code, OpHint::None,
+                    &ProgramInputs::new(&[1, 0, 4, 3, 1, 0, 1, 1], &[1], &[])),
+                _ => check_op(op_code, OpHint::None, &inputs),
+            }
+        }
+    }
+
+    #[test]
+    fn swap_constraints_not_aggregated() {
+        // each swapped item must be constrained by its own constraint; when both constraints were
+        // aggregated into the same slot, offsetting both items by opposite amounts was accepted
+        let old_stack = [1, 2, 3, 4];
+        let new_stack = [2 + 5, field::sub(1, 5), 3, 4];
+        let mut result = vec![0; old_stack.len()];
+        super::enforce_swap(&mut result, &old_stack, &new_stack, 1);
+        assert!(result.iter().any(|&v| v != 0));
+
+        let mut result = vec![0; old_stack.len()];
+        super::enforce_swap(&mut result, &old_stack, &[2, 1, 3, 4], 1);
+        assert!(result.iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn swap8_transition() {
+        // SWAP8 needs 16 stack registers, and so it is checked outside of the test harness
+        let old_stack: Vec<u128> = (1..=20).collect();
+        let mut new_stack = old_stack.clone();
+        new_stack[..16].rotate_left(8);
+
+        let mut result = vec![0; old_stack.len()];
+        super::enforce_swap8(&mut result, &old_stack, &new_stack, 1);
+        assert!(result.iter().all(|&v| v == 0));
+
+        for i in 0..new_stack.len() {
+            let mut perturbed = new_stack.clone();
+            perturbed[i] = perturbed[i] + 1;
+            let mut result = vec![0; old_stack.len()];
+            super::enforce_swap8(&mut result, &old_stack, &perturbed, 1);
+            assert!(result.iter().any(|&v| v != 0), "changing stack item {} does not break constraints", i);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "stack constraints of add do not hold")]
+    fn invalid_transition() {
+        check_transition(OpCode::Add, &[1, 2, 3, 4, 5, 6, 7, 8], &[4, 3, 4, 5, 6, 7, 8, 0]);
+    }
+
+    #[test]
+    fn op_specs() {
+        // constraints of every operation fit into the degree of stack transition constraints,
+        // and no operation removes more items from the stack than it requires
+        for &op_code in ALL_OPS.iter() {
+            let spec = op_code.spec();
+            assert!(spec.degree <= STACK_TRANSITION_DEGREE, "degree of {} is too high", op_code);
+            assert!(spec.required_depth as isize + spec.depth_change >= 0, "{} underflows the stack", op_code);
+            assert_eq!(spec.required_depth, op_code.required_depth());
+            assert_eq!(spec.depth_change, op_code.depth_change());
+        }
+        assert_eq!(8, OpCode::Push.spec().alignment);
+    }
+}
@@ -84,6 +84,12 @@ impl EvaluationResult for Vec<u128> {
 
 // CONSTANT INTERPOLATION AND EXTENSIONS
 // ================================================================================================
+
+/// Interpolates cyclic constants (e.g. round constants and masks) and evaluates them over a
+/// domain `extension_factor` times larger than the cycle. The extended domain contains the
+/// cycle domain as a subgroup, and so, every extension factor supported by `ProofOptions`
+/// must reproduce the original constants at every `extension_factor`-th step; this is checked
+/// for every extended constant.
 pub fn extend_constants(constants: &[[u128; BASE_CYCLE_LENGTH]], extension_factor: usize) -> (Vec<Vec<u128>>, Vec<Vec<u128>>)
 {
     let root = field::get_root_of_unity(BASE_CYCLE_LENGTH);
@@ -103,9 +109,14 @@ pub fn extend_constants(constants: &[[u128; BASE_CYCLE_LENGTH]], extension_facto
         polynom::interpolate_fft_twiddles(&mut extended_constant, &inv_twiddles, true);
         polys.push(extended_constant.clone());
 
-        unsafe { extended_constant.set_len(extended_constant.capacity()); }
+        extended_constant.resize(domain_size, field::ZERO);
         polynom::eval_fft_twiddles(&mut extended_constant, &twiddles, true);
 
+        for (i, &value) in constant.iter().enumerate() {
+            assert!(extended_constant[i * extension_factor] == value,
+                "extension of constant at step {} is inconsistent for extension factor {}", i, extension_factor);
+        }
+
         evaluations.push(extended_constant);
     }
 
@@ -117,6 +128,33 @@ pub fn extend_constants(constants: &[[u128; BASE_CYCLE_LENGTH]], extension_facto
 #[cfg(test)]
 mod tests {
 
+    use crate::{ math::{ field, polynom }, crypto::rescue::{ HASHER, SPONGE }, BASE_CYCLE_LENGTH };
+    use super::super::decoder::MASKS;
+
+    #[test]
+    fn extend_constants() {
+        for &extension_factor in [16, 32, 64, 128, 256].iter() {
+            for constants in [HASHER.ark(), SPONGE.ark(), &MASKS[..]].iter() {
+                let (polys, evaluations) = super::extend_constants(constants, extension_factor);
+                assert_eq!(constants.len(), polys.len());
+                assert_eq!(constants.len(), evaluations.len());
+
+                let domain_size = BASE_CYCLE_LENGTH * extension_factor;
+                let domain_root = field::get_root_of_unity(domain_size);
+                for (i, (poly, evaluation)) in polys.iter().zip(evaluations.iter()).enumerate() {
+                    assert_eq!(BASE_CYCLE_LENGTH, poly.len());
+                    assert_eq!(domain_size, evaluation.len());
+
+                    // spot-check evaluations at points outside of the cycle domain
+                    for &step in [1, extension_factor + 3, domain_size - 1].iter() {
+                        let x = field::exp(domain_root, step as u128);
+                        assert_eq!(polynom::eval(poly, x), evaluation[step], "constant {}, step {}", i, step);
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn enforce_left_shift() {
 
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::{ Arc, Mutex };
+use crate::math::{ field, fft::FftPlan };
+use super::{ constraints::{ Decoder, Stack }, MAX_CONSTRAINT_DEGREE };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Number of setups kept by a context created via `ProverContext::new()`.
+const DEFAULT_CAPACITY: usize = 16;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A cache of prover setups which depend only on the shape of the execution trace. Building a
+/// setup requires extending round and mask constants of the constraint evaluators, and computing
+/// LDE domain and FFT twiddles; when many programs with the same shape are proven, reusing a
+/// context avoids repeating this work for every proof.
+///
+/// A context can be shared between threads; setups are built the first time a shape is
+/// requested, and at most `capacity` setups are kept: when a setup for a new shape is added to
+/// a full cache, the least recently used setup is evicted.
+pub struct ProverContext {
+    cache   : Mutex<SetupCache>,
+}
+
+struct SetupCache {
+    setups      : HashMap<TraceShape, (Arc<ProverSetup>, u64)>,
+    capacity    : usize,
+    clock       : u64,
+}
+
+/// Parameters of the execution trace which define a prover setup.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TraceShape {
+    pub trace_length    : usize,
+    pub extension_factor: usize,
+    pub ctx_depth       : usize,
+    pub loop_depth      : usize,
+    pub stack_depth     : usize,
+}
+
+/// Constants and FFT plans reused across proofs of execution traces with the same shape.
+pub struct ProverSetup {
+    pub decoder         : Arc<Decoder>,
+    pub stack           : Arc<Stack>,
+    pub lde_domain      : Vec<u128>,
+    pub lde_plan        : FftPlan,
+    pub trace_plan      : FftPlan,
+    pub constraint_plan : FftPlan,
+}
+
+// PROVER CONTEXT IMPLEMENTATION
+// ================================================================================================
+impl ProverContext {
+
+    pub fn new() -> ProverContext {
+        return ProverContext::with_capacity(DEFAULT_CAPACITY);
+    }
+
+    /// Returns a context which keeps at most `capacity` setups.
+    pub fn with_capacity(capacity: usize) -> ProverContext {
+        assert!(capacity > 0, "capacity must be greater than zero");
+        let cache = SetupCache { setups: HashMap::new(), capacity, clock: 0 };
+        return ProverContext { cache: Mutex::new(cache) };
+    }
+
+    /// Returns the setup for execution traces of the specified `shape`, building it if the
+    /// setup is not in the cache yet. The setup is built without holding the lock, and so,
+    /// requests for other shapes are not blocked while it is built; if two threads build the
+    /// same setup at the same time, the setup built first is kept.
+    pub fn get_setup(&self, shape: TraceShape) -> Arc<ProverSetup> {
+        if let Some(setup) = self.lock().get(&shape) {
+            return setup;
+        }
+
+        let setup = Arc::new(ProverSetup::new(shape));
+        return self.lock().insert(shape, setup);
+    }
+
+    /// Returns the number of setups in the cache.
+    pub fn num_setups(&self) -> usize {
+        return self.lock().setups.len();
+    }
+
+    /// Removes all setups from the cache.
+    pub fn clear(&self) {
+        self.lock().setups.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SetupCache> {
+        return self.cache.lock().expect("prover context lock is poisoned");
+    }
+}
+
+impl Default for ProverContext {
+    fn default() -> ProverContext {
+        return ProverContext::new();
+    }
+}
+
+// SETUP CACHE IMPLEMENTATION
+// ================================================================================================
+impl SetupCache {
+
+    /// Returns the setup for the `shape` and marks it as the most recently used one.
+    fn get(&mut self, shape: &TraceShape) -> Option<Arc<ProverSetup>> {
+        self.clock += 1;
+        let clock = self.clock;
+        return self.setups.get_mut(shape).map(|(setup, last_used)| {
+            *last_used = clock;
+            Arc::clone(setup)
+        });
+    }
+
+    /// Adds the `setup` for the `shape` unless a setup for the shape is already in the cache,
+    /// evicting the least recently used setup if the cache is full; returns the cached setup.
+    fn insert(&mut self, shape: TraceShape, setup: Arc<ProverSetup>) -> Arc<ProverSetup> {
+        if let Some(setup) = self.get(&shape) {
+            return setup;
+        }
+
+        if self.setups.len() >= self.capacity {
+            let evicted = self.setups.iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(&shape, _)| shape);
+            if let Some(evicted) = evicted {
+                self.setups.remove(&evicted);
+            }
+        }
+
+        self.clock += 1;
+        self.setups.insert(shape, (Arc::clone(&setup), self.clock));
+        return setup;
+    }
+}
+
+// PROVER SETUP IMPLEMENTATION
+// ================================================================================================
+impl ProverSetup {
+
+    fn new(shape: TraceShape) -> ProverSetup {
+        let domain_size = shape.trace_length * shape.extension_factor;
+
+        // constraints are evaluated over a domain extended by the max constraint degree
+        let ce_factor = MAX_CONSTRAINT_DEGREE;
+        let lde_root = field::get_root_of_unity(domain_size);
+
+        return ProverSetup {
+            decoder         : Arc::new(Decoder::new(shape.trace_length, ce_factor, shape.ctx_depth, shape.loop_depth)),
+            stack           : Arc::new(Stack::new(shape.trace_length, ce_factor, shape.stack_depth)),
+            lde_domain      : field::get_power_series(lde_root, domain_size),
+            lde_plan        : FftPlan::new(domain_size),
+            trace_plan      : FftPlan::new(shape.trace_length),
+            constraint_plan : FftPlan::new(shape.trace_length * ce_factor),
+        };
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use super::{ ProverContext, TraceShape };
+
+    #[test]
+    fn evict_least_recently_used() {
+        let context = ProverContext::with_capacity(2);
+        let a = context.get_setup(build_shape(16));
+        let b = context.get_setup(build_shape(32));
+        assert_eq!(2, context.num_setups());
+
+        // using `a` makes `b` the least recently used setup, and so, `b` is evicted
+        assert!(Arc::ptr_eq(&a, &context.get_setup(build_shape(16))));
+        context.get_setup(build_shape(64));
+        assert_eq!(2, context.num_setups());
+        assert!(Arc::ptr_eq(&a, &context.get_setup(build_shape(16))));
+        assert!(!Arc::ptr_eq(&b, &context.get_setup(build_shape(32))));
+    }
+
+    fn build_shape(trace_length: usize) -> TraceShape {
+        return TraceShape { trace_length, extension_factor: 16, ctx_depth: 1, loop_depth: 0, stack_depth: 8 };
+    }
+}
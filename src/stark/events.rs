@@ -0,0 +1,124 @@
+use std::fmt;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Levels of detail at which prover events are reported; every event has a level, and a logger
+/// receives only the events with levels not exceeding its verbosity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verbosity {
+    /// No events are reported.
+    Quiet,
+    /// Events marking completion of proof generation stages are reported.
+    Stages,
+    /// In addition to stage events, events describing every FRI layer are reported.
+    Details,
+}
+
+/// Structured events emitted by the prover as proof generation progresses. Events carry only
+/// values which are fully determined by the program, its inputs, and proof options (e.g. there
+/// are no timings); thus, proving the same program twice produces the same sequence of events.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProverEvent {
+    /// The execution trace handed to the prover has `registers` registers of `steps` steps each.
+    TraceBuilt { registers: usize, steps: usize },
+    /// The execution trace was extended to the LDE domain of `domain` elements.
+    LdeDone { domain: usize },
+    /// A Merkle tree with `leaves` leaves was built from the extended execution trace.
+    TraceCommitted { leaves: usize },
+    /// `constraints` constraints were evaluated over the domain of `domain` elements.
+    ConstraintsEvaluated { constraints: usize, domain: usize },
+    /// Constraint evaluations were combined into a single polynomial of degree `degree`.
+    ConstraintsCombined { degree: usize },
+    /// A Merkle tree with `leaves` leaves was built from constraint polynomial evaluations.
+    ConstraintsCommitted { leaves: usize },
+    /// The composition polynomial was evaluated over the domain of `domain` elements.
+    CompositionBuilt { domain: usize },
+    /// FRI layer `index` of `size` evaluations was committed to; the last layer is the remainder.
+    FriLayer { index: usize, size: usize },
+    /// `count` query positions were derived from the commitments.
+    QueriesSelected { count: usize },
+    /// All sections of the proof were built.
+    ProofBuilt,
+}
+
+// PROVER EVENT IMPLEMENTATION
+// ================================================================================================
+impl ProverEvent {
+
+    /// Returns the lowest verbosity at which this event is reported.
+    pub fn verbosity(&self) -> Verbosity {
+        return match self {
+            ProverEvent::FriLayer { .. } => Verbosity::Details,
+            _ => Verbosity::Stages,
+        };
+    }
+
+    /// Returns the name of this event in snake case (e.g. `trace_built`).
+    pub fn name(&self) -> &'static str {
+        return match self {
+            ProverEvent::TraceBuilt { .. }              => "trace_built",
+            ProverEvent::LdeDone { .. }                 => "lde_done",
+            ProverEvent::TraceCommitted { .. }          => "trace_committed",
+            ProverEvent::ConstraintsEvaluated { .. }    => "constraints_evaluated",
+            ProverEvent::ConstraintsCombined { .. }     => "constraints_combined",
+            ProverEvent::ConstraintsCommitted { .. }    => "constraints_committed",
+            ProverEvent::CompositionBuilt { .. }        => "composition_built",
+            ProverEvent::FriLayer { .. }                => "fri_layer",
+            ProverEvent::QueriesSelected { .. }         => "queries_selected",
+            ProverEvent::ProofBuilt                     => "proof_built",
+        };
+    }
+
+    /// Returns names and values of the fields of this event.
+    pub fn fields(&self) -> Vec<(&'static str, usize)> {
+        return match *self {
+            ProverEvent::TraceBuilt { registers, steps }    => vec![("registers", registers), ("steps", steps)],
+            ProverEvent::LdeDone { domain }                 => vec![("domain", domain)],
+            ProverEvent::TraceCommitted { leaves }          => vec![("leaves", leaves)],
+            ProverEvent::ConstraintsEvaluated { constraints, domain }
+                => vec![("constraints", constraints), ("domain", domain)],
+            ProverEvent::ConstraintsCombined { degree }     => vec![("degree", degree)],
+            ProverEvent::ConstraintsCommitted { leaves }    => vec![("leaves", leaves)],
+            ProverEvent::CompositionBuilt { domain }        => vec![("domain", domain)],
+            ProverEvent::FriLayer { index, size }           => vec![("i", index), ("size", size)],
+            ProverEvent::QueriesSelected { count }          => vec![("count", count)],
+            ProverEvent::ProofBuilt                         => vec![],
+        };
+    }
+}
+
+impl fmt::Display for ProverEvent {
+    /// Formats the event as its name followed by `key=value` pairs of its fields, for example:
+    /// `trace_built registers=20 steps=256`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        for (name, value) in self.fields() {
+            write!(f, " {}={}", name, value)?;
+        }
+        return Ok(());
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use super::{ ProverEvent, Verbosity };
+
+    #[test]
+    fn display() {
+        let event = ProverEvent::TraceBuilt { registers: 20, steps: 256 };
+        assert_eq!("trace_built registers=20 steps=256", event.to_string());
+        assert_eq!("fri_layer i=2 size=64", ProverEvent::FriLayer { index: 2, size: 64 }.to_string());
+        assert_eq!("proof_built", ProverEvent::ProofBuilt.to_string());
+    }
+
+    #[test]
+    fn verbosity() {
+        assert!(Verbosity::Quiet < Verbosity::Stages && Verbosity::Stages < Verbosity::Details);
+        assert_eq!(Verbosity::Stages, ProverEvent::LdeDone { domain: 1024 }.verbosity());
+        assert_eq!(Verbosity::Details, ProverEvent::FriLayer { index: 0, size: 1024 }.verbosity());
+    }
+}
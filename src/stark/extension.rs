@@ -0,0 +1,67 @@
+use crate::{ MAX_EXTENSION_CONSTRAINTS, stark::{ TraceState, MAX_CONSTRAINT_DEGREE } };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Auxiliary transition constraints which are enforced in addition to the constraints of the VM.
+///
+/// Extension constraints are evaluated over consecutive states of the execution trace, and can
+/// refer to any register of the trace; this makes it possible to enforce application-specific
+/// invariants over registers which a program reserves for its own use (e.g. an accumulator kept
+/// at a fixed position of the user stack). Extensions cannot add columns to the execution trace:
+/// all values referred to by extension constraints must be kept in registers of the VM. Same as
+/// all other transition constraints, extension constraints must evaluate to zero at every step of
+/// the trace except for the last one.
+///
+/// The identifier of an extension is stored in the proof, and a proof can be verified only against
+/// an extension with the same identifier; thus, the identifier should change whenever constraints
+/// of the extension change.
+pub trait AirExtension {
+
+    /// Returns the identifier of this extension.
+    fn id(&self) -> &str;
+
+    /// Returns degrees of the extension constraints; the degree of a constraint is the degree
+    /// of its polynomial expressed in terms of trace registers (e.g. a * b - c has degree 2).
+    fn constraint_degrees(&self) -> Vec<usize>;
+
+    /// Evaluates extension constraints against the `current` and `next` states of the execution
+    /// trace, and writes the results into `result`; there is one result per constraint.
+    fn evaluate(&self, current: &TraceState, next: &TraceState, result: &mut [u128]);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure that constraints of the `extension` can be combined with the constraints of the VM.
+pub fn validate_extension(extension: &dyn AirExtension) -> Result<(), String> {
+    if extension.id().is_empty() {
+        return Err(String::from("AIR extension identifier cannot be empty"));
+    }
+
+    let degrees = extension.constraint_degrees();
+    if degrees.is_empty() {
+        return Err(format!("AIR extension {} must define at least one constraint", extension.id()));
+    }
+    if degrees.len() > MAX_EXTENSION_CONSTRAINTS {
+        return Err(format!("AIR extension {} cannot define more than {} constraints, but defines {}",
+            extension.id(), MAX_EXTENSION_CONSTRAINTS, degrees.len()));
+    }
+
+    for (i, &degree) in degrees.iter().enumerate() {
+        if degree == 0 || degree > MAX_CONSTRAINT_DEGREE {
+            return Err(format!("degree of constraint {} of AIR extension {} must be between 1 and {}, but was {}",
+                i, extension.id(), MAX_CONSTRAINT_DEGREE, degree));
+        }
+    }
+
+    return Ok(());
+}
+
+/// Returns the number of constraints defined by the `extension`, or 0 if there is no extension.
+pub fn get_extension_constraint_num(extension: Option<&dyn AirExtension>) -> usize {
+    return match extension {
+        Some(extension) => extension.constraint_degrees().len(),
+        None => 0,
+    };
+}
@@ -3,12 +3,13 @@ use serde::{ Serialize, Deserialize };
 // RE-EXPORTS
 // ================================================================================================
 mod utils;
+pub(crate) use utils::get_augmented_positions;
 
 mod prover;
-pub use prover::{ reduce, build_proof };
+pub use prover::{ reduce, build_proof, build_layers, build_remainder, LayerProofs };
 
 mod verifier;
-pub use verifier::{ verify };
+pub use verifier::{ verify, verify_stream, check_first_layer, check_structure };
 
 const MAX_REMAINDER_LENGTH: usize = 256;
 
@@ -31,10 +32,10 @@ pub struct FriLayer {
 
 // TESTS
 // ================================================================================================
-#[cfg(test)]
+#[cfg(all(test, feature = "std-rand"))]
 mod tests {
     use crate::math::{ field, polynom };
-    use crate::stark::{ ProofOptions, utils::compute_query_positions };
+    use crate::stark::{ ProofOptions, ProgressHandle, ProvingCancelled, Transcript, utils::compute_query_positions };
 
     #[test]
     fn prove_verify() {
@@ -47,14 +48,19 @@ mod tests {
         let evaluations = build_random_poly_evaluations(domain_size, degree);
 
         // generate proof
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options, &ProgressHandle::new()).unwrap();
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
         let proof = super::build_proof(fri_trees, fri_values, &positions);
 
         // verify proof
         let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
+        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options, None);
         assert_eq!(Ok(true), result);
+
+        // a cancelled handle stops layer construction
+        let progress = ProgressHandle::new();
+        progress.cancel();
+        assert_eq!(Some(ProvingCancelled), super::reduce(&evaluations, &domain, &options, &progress).err());
     }
 
     #[test]
@@ -67,33 +73,71 @@ mod tests {
 
         // degree too low 1
         let evaluations = build_random_poly_evaluations(domain_size, degree);
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options, &ProgressHandle::new()).unwrap();
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
         let proof = super::build_proof(fri_trees, fri_values, &positions);
 
         let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree - 1, &options);
-        let err_msg = format!("remainder is not a valid degree {} polynomial", 14);
-        assert_eq!(Err(err_msg), result);
+        let result = super::verify(&proof, &sampled_evaluations, &positions, degree - 1, &options, None);
+        assert!(result.unwrap_err().starts_with("remainder is not a valid degree"));
 
         // degree too low 2
         let evaluations = build_random_poly_evaluations(domain_size, degree + 1);
-        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options);
+        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options, &ProgressHandle::new()).unwrap();
         let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
         let proof = super::build_proof(fri_trees, fri_values, &positions);
 
         let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
-        let err_msg = format!("remainder is not a valid degree {} polynomial", 15);
-        assert_eq!(Err(err_msg), result);
+        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options, None);
+        assert!(result.unwrap_err().starts_with("remainder is not a valid degree"));
 
         // invalid evaluations
         let sampled_evaluations = sampled_evaluations[1..].to_vec();
-        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options);
-        let err_msg = format!("evaluations did not match column value at depth 0");
+        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options, None);
+        assert!(result.unwrap_err().starts_with("evaluations did not match column value"));
+
+        // proofs without layers are rejected
+        let mut proof = proof;
+        proof.layers.clear();
+        let result = super::verify(&proof, &sampled_evaluations, &positions, degree, &options, None);
+        let err_msg = format!("FRI proof must contain at least one layer");
         assert_eq!(Err(err_msg), result);
     }
 
+    #[test]
+    fn verify_stream() {
+        let degree: usize = 63;
+        let domain_size: usize = 4096;
+        let root = field::get_root_of_unity(domain_size);
+        let domain = field::get_power_series(root, domain_size);
+        let options = ProofOptions::default();
+
+        let evaluations = build_random_poly_evaluations(domain_size, degree);
+        let (fri_trees, fri_values) = super::reduce(&evaluations, &domain, &options, &ProgressHandle::new()).unwrap();
+        let positions = compute_query_positions(fri_trees[fri_trees.len() - 1].root(), domain_size, &options);
+        let proof = super::build_proof(fri_trees, fri_values, &positions);
+        assert!(proof.layers.len() > 1);
+
+        // streamed proof is verified in the same way as the deserialized proof
+        let bytes = bincode::serialize(&proof).unwrap();
+        let sampled_evaluations = positions.iter().map(|&i| evaluations[i]).collect::<Vec<u128>>();
+        let mut transcript = Transcript::new();
+        let result = super::verify_stream(&mut &bytes[..], &sampled_evaluations, &positions, degree, &options, Some(&mut transcript));
+        assert_eq!(Ok(true), result);
+
+        let mut expected_transcript = Transcript::new();
+        super::verify(&proof, &sampled_evaluations, &positions, degree, &options, Some(&mut expected_transcript)).unwrap();
+        assert_eq!(expected_transcript, transcript);
+
+        // degree is checked
+        let result = super::verify_stream(&mut &bytes[..], &sampled_evaluations, &positions, degree - 1, &options, None);
+        assert!(result.is_err());
+
+        // truncated proofs are rejected
+        let result = super::verify_stream(&mut &bytes[..bytes.len() - 1], &sampled_evaluations, &positions, degree, &options, None);
+        assert!(result.unwrap_err().starts_with("failed to read remainder values"));
+    }
+
     // TODO: add more tests
 
     fn build_random_poly_evaluations(domain_size: usize, degree: usize) -> Vec<u128> {
@@ -1,14 +1,25 @@
 use std::mem;
 use crate::math::{ field, quartic };
 use crate::crypto::{ MerkleTree };
-use crate::stark::{ ProofOptions };
+use crate::stark::{ ProofOptions, ProgressHandle, ProvingCancelled };
 
 use super::{ FriProof, FriLayer, utils, MAX_REMAINDER_LENGTH};
 
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Iterator over proofs of FRI layers built by `build_layers()` function.
+pub struct LayerProofs {
+    trees       : std::vec::IntoIter<MerkleTree>,
+    values      : std::vec::IntoIter<Vec<[u128; 4]>>,
+    positions   : Vec<usize>,
+    domain_size : usize,
+}
+
 // PROVER FUNCTIONS
 // ================================================================================================
 
-pub fn reduce(evaluations: &[u128], domain: &[u128], options: &ProofOptions) -> (Vec<MerkleTree>, Vec<Vec<[u128; 4]>>) {
+pub fn reduce(evaluations: &[u128], domain: &[u128], options: &ProofOptions, progress: &ProgressHandle) -> Result<(Vec<MerkleTree>, Vec<Vec<[u128; 4]>>), ProvingCancelled> {
     let mut tree_results: Vec<MerkleTree> = Vec::new();
     let mut value_results: Vec<Vec<[u128; 4]>> = Vec::new();
 
@@ -19,6 +30,7 @@ pub fn reduce(evaluations: &[u128], domain: &[u128], options: &ProofOptions) ->
 
     // reduce the degree by 4 at each iteration until the remaining polynomial is small enough
     while p_tree.leaves().len() * 4 > MAX_REMAINDER_LENGTH {
+        progress.check()?;
 
         // build polynomials from each row of the polynomial value matrix
         let depth = tree_results.len() as u32;
@@ -49,40 +61,37 @@ pub fn reduce(evaluations: &[u128], domain: &[u128], options: &ProofOptions) ->
     tree_results.push(p_tree);
     value_results.push(p_values);
 
-    return (tree_results, value_results);
+    return Ok((tree_results, value_results));
 }
 
 pub fn build_proof(trees: Vec<MerkleTree>, values: Vec<Vec<[u128; 4]>>, positions: &[usize]) -> FriProof {
-    let mut positions = positions.to_vec();
-    let mut domain_size = trees[0].leaves().len() * 4;
-
-    // for all trees, except the last one, record tree root, authentication paths
-    // to row evaluations, and values for row evaluations
-    let mut layers = Vec::with_capacity(trees.len());
-    for i in 0..(trees.len() - 1) {
-        
-        positions = utils::get_augmented_positions(&positions, domain_size);
-
-        let tree = &trees[i];
-        let proof = tree.prove_batch(&positions);
-        
-        let mut queried_values: Vec<[u128; 4]> = Vec::with_capacity(positions.len());
-        for &position in positions.iter() {
-            queried_values.push(values[i][position]);
-        }
-
-        layers.push(FriLayer {
-            root    : *tree.root(),
-            values  : queried_values,
-            nodes   : proof.nodes,
-            depth   : proof.depth
-        });
-        domain_size = domain_size / 4;
-    }
+    let (layers, rem_root, rem_values) = build_layers(trees, values, positions);
+    return FriProof { layers: layers.collect(), rem_root, rem_values };
+}
+
+/// Splits the FRI `trees` and `values` into an iterator over proofs of all layers except for the
+/// last one, and the root and the remainder of the last layer; layer proofs are built lazily, and
+/// the tree and the values of every layer are dropped as soon as the proof of the layer is built.
+pub fn build_layers(mut trees: Vec<MerkleTree>, mut values: Vec<Vec<[u128; 4]>>, positions: &[usize]) -> (LayerProofs, [u8; 32], Vec<u128>) {
+    let domain_size = trees[0].leaves().len() * 4;
 
     // use the remaining polynomial values directly as proof
-    let last_tree = &trees[trees.len() - 1];
-    let last_values = &values[values.len() - 1];
+    let last_tree = trees.pop().expect("FRI proof must contain at least one layer");
+    let last_values = values.pop().expect("FRI proof must contain at least one layer");
+    let remainder = build_remainder(&last_values);
+
+    let layers = LayerProofs {
+        trees       : trees.into_iter(),
+        values      : values.into_iter(),
+        positions   : positions.to_vec(),
+        domain_size : domain_size,
+    };
+    return (layers, *last_tree.root(), remainder);
+}
+
+/// Converts values of the last FRI layer into the remainder included in the proof; the remainder
+/// lists evaluations of the last layer polynomial column by column.
+pub fn build_remainder(last_values: &[[u128; 4]]) -> Vec<u128> {
     let n = last_values.len();
     let mut remainder = vec![field::ZERO; n * 4];
     for i in 0..last_values.len() {
@@ -91,6 +100,36 @@ pub fn build_proof(trees: Vec<MerkleTree>, values: Vec<Vec<[u128; 4]>>, position
         remainder[i + n * 2] = last_values[i][2];
         remainder[i + n * 3] = last_values[i][3];
     }
+    return remainder;
+}
+
+// LAYER PROOFS IMPLEMENTATION
+// ================================================================================================
+impl Iterator for LayerProofs {
+    type Item = FriLayer;
+
+    fn next(&mut self) -> Option<FriLayer> {
+        let tree = self.trees.next()?;
+        let values = self.values.next()?;
+
+        // record tree root, authentication paths to row evaluations, and values for row
+        // evaluations
+        self.positions = utils::get_augmented_positions(&self.positions, self.domain_size);
+        let proof = tree.prove_batch(&self.positions);
+        let queried_values = self.positions.iter().map(|&p| values[p]).collect();
+        self.domain_size = self.domain_size / 4;
+
+        return Some(FriLayer {
+            root    : *tree.root(),
+            values  : queried_values,
+            nodes   : proof.nodes,
+            depth   : proof.depth
+        });
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        return self.trees.size_hint();
+    }
+}
 
-    return FriProof { layers, rem_root: *last_tree.root(), rem_values: remainder };
-}
\ No newline at end of file
+impl ExactSizeIterator for LayerProofs {}
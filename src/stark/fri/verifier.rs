@@ -1,57 +1,183 @@
-use std::mem;
+use std::{ mem, io::Read };
 use crate::math::{ field, polynom, quartic };
-use crate::crypto::{ MerkleTree, BatchMerkleProof };
-use crate::stark::{ ProofOptions };
+use crate::crypto::{ MerkleTree, BatchMerkleProof, ct_eq_elements };
+use crate::stark::{ ProofOptions, Transcript, record_check };
 
-use super::{ FriProof, FriLayer, utils };
+use super::{ FriProof, FriLayer, utils, MAX_REMAINDER_LENGTH };
 
 // VERIFIER
 // ================================================================================================
 
+/// Verifies that the `evaluations` at the specified `positions` belong to a polynomial of degree
+/// at most `max_degree` using the FRI `proof`.
 pub fn verify(
-    proof       : &FriProof,
-    evaluations : &[u128],
-    positions   : &[usize],
-    max_degree  : usize,
-    options     : &ProofOptions) -> Result<bool, String>
+    proof          : &FriProof,
+    evaluations    : &[u128],
+    positions      : &[usize],
+    max_degree     : usize,
+    options        : &ProofOptions,
+    mut transcript : Option<&mut Transcript>) -> Result<bool, String>
 {
+    // 1 ----- verify the recursive components of the FRI proof -----------------------------------
+    let first_layer = proof.layers.first().ok_or_else(|| String::from("FRI proof must contain at least one layer"))?;
+    let mut verifier = LayerVerifier::new(first_layer.depth, evaluations, positions, max_degree);
+    for (depth, layer) in proof.layers.iter().enumerate() {
+        verifier.verify_layer(depth, layer, options, transcript.as_deref_mut())?;
+    }
 
-    let domain_size = usize::pow(2, proof.layers[0].depth as u32) * 4;
-    let domain_root = field::get_root_of_unity(domain_size);
-
-    // powers of the given root of unity 1, p, p^2, p^3 such that p^4 = 1
-    let quartic_roots = [
-        1u128,
-        field::exp(domain_root, (domain_size / 4) as u128),
-        field::exp(domain_root, (domain_size / 2) as u128),
-        field::exp(domain_root, (domain_size * 3 / 4) as u128),
-    ];
+    // 2 ----- verify the remainder of the FRI proof ----------------------------------------------
+    return verifier.verify_remainder(&proof.rem_values, options, transcript);
+}
 
+/// Verifies a FRI proof in the same way as `verify()` function does, but reads the proof from
+/// the `reader`; the reader must supply bincode serialization of a `FriProof`. Layers of the
+/// proof are deserialized and verified one at a time, and every layer is dropped as soon as it
+/// is verified; thus, at most one layer is kept in memory at any point in time.
+pub fn verify_stream<R: Read>(
+    reader         : &mut R,
+    evaluations    : &[u128],
+    positions      : &[usize],
+    max_degree     : usize,
+    options        : &ProofOptions,
+    mut transcript : Option<&mut Transcript>) -> Result<bool, String>
+{
     // 1 ----- verify the recursive components of the FRI proof -----------------------------------
-    let mut domain_root = domain_root;
-    let mut domain_size = domain_size;
-    let mut max_degree_plus_1 = max_degree + 1;
-    let mut positions = positions.to_vec();
-    let mut evaluations = evaluations.to_vec();
+    let num_layers: u64 = read_section(reader, "number of FRI layers")?;
+    let mut verifier = None;
+    for depth in 0..(num_layers as usize) {
+        let layer: FriLayer = read_section(reader, "FRI layer")?;
+        let verifier = verifier.get_or_insert_with(||
+            LayerVerifier::new(layer.depth, evaluations, positions, max_degree));
+        verifier.verify_layer(depth, &layer, options, transcript.as_deref_mut())?;
+    }
+    let verifier = verifier.ok_or_else(|| String::from("FRI proof must contain at least one layer"))?;
 
-    for (depth, layer) in proof.layers.iter().enumerate() {
+    // 2 ----- verify the remainder of the FRI proof ----------------------------------------------
+    let _rem_root: [u8; 32] = read_section(reader, "remainder root")?;
+    let rem_values: Vec<u128> = read_section(reader, "remainder values")?;
+    return verifier.verify_remainder(&rem_values, options, transcript);
+}
+
+/// Checks that the `evaluations` at the specified `positions` match the values of the first
+/// layer of the FRI `proof`. Neither the Merkle proof of the layer nor the degree of the
+/// polynomial is verified, and so, this is a quick pre-check rather than a substitute for
+/// `verify()`.
+pub fn check_first_layer(proof: &FriProof, evaluations: &[u128], positions: &[usize]) -> bool {
+    let layer = match proof.layers.first() {
+        Some(layer) => layer,
+        None => return false,
+    };
+    let domain_size = usize::pow(2, layer.depth as u32) * 4;
+    if positions.iter().any(|&p| p >= domain_size) {
+        return false;
+    }
+    let augmented_positions = utils::get_augmented_positions(positions, domain_size);
+    if layer.values.len() != augmented_positions.len() {
+        return false;
+    }
+    return ct_eq_elements(evaluations, &get_column_values(&layer.values, positions, &augmented_positions, domain_size));
+}
+
+/// Returns an error if the number of layers of the FRI `proof`, depths of the layers, or the
+/// number of remainder values differ from the ones produced by the prover for a domain of
+/// 2^`domain_depth` elements. Numbers of layer values depend on query positions, and so, they
+/// are checked while the layers are verified.
+pub fn check_structure(proof: &FriProof, domain_depth: usize) -> Result<(), String> {
+    let mut domain_size = usize::pow(2, domain_depth as u32);
+    let mut layer_depths = Vec::new();
+    while domain_size > MAX_REMAINDER_LENGTH {
+        domain_size = domain_size / 4;
+        layer_depths.push(domain_size.trailing_zeros() as usize);
+    }
+
+    if proof.layers.len() != layer_depths.len() {
+        return Err(format!("FRI proof must contain {} layers, but contained {}",
+            layer_depths.len(), proof.layers.len()));
+    }
+    for (i, (layer, &depth)) in proof.layers.iter().zip(layer_depths.iter()).enumerate() {
+        if layer.depth as usize != depth {
+            return Err(format!("FRI layer {} must have depth {}, but had depth {}", i, depth, layer.depth));
+        }
+    }
+    if proof.rem_values.len() != domain_size {
+        return Err(format!("FRI remainder must contain {} values, but contained {}",
+            domain_size, proof.rem_values.len()));
+    }
+    return Ok(());
+}
+
+// LAYER VERIFIER
+// ================================================================================================
+
+/// Keeps track of the values which are carried over between consecutive FRI layers, so that the
+/// layers can be verified one at a time.
+struct LayerVerifier {
+    quartic_roots       : [u128; 4],
+    domain_root         : u128,
+    domain_size         : usize,
+    max_degree_plus_1   : usize,
+    positions      : Vec<usize>,
+    evaluations    : Vec<u128>,
+}
+
+impl LayerVerifier {
+
+    /// Creates a verifier for a FRI proof with the first layer of the specified `depth`.
+    fn new(depth: u8, evaluations: &[u128], positions: &[usize], max_degree: usize) -> LayerVerifier {
+        let domain_size = usize::pow(2, depth as u32) * 4;
+        let domain_root = field::get_root_of_unity(domain_size);
+
+        // powers of the given root of unity 1, p, p^2, p^3 such that p^4 = 1
+        let quartic_roots = [
+            1u128,
+            field::exp(domain_root, (domain_size / 4) as u128),
+            field::exp(domain_root, (domain_size / 2) as u128),
+            field::exp(domain_root, (domain_size * 3 / 4) as u128),
+        ];
+
+        return LayerVerifier {
+            quartic_roots, domain_root, domain_size,
+            max_degree_plus_1   : max_degree + 1,
+            positions           : positions.to_vec(),
+            evaluations         : evaluations.to_vec(),
+        };
+    }
+
+    /// Verifies a single FRI layer, and computes evaluations to be checked against the next layer.
+    fn verify_layer(&mut self, depth: usize, layer: &FriLayer, options: &ProofOptions, mut transcript: Option<&mut Transcript>) -> Result<(), String> {
 
-        let mut augmented_positions = utils::get_augmented_positions(&positions, domain_size);
-        let column_values = get_column_values(&layer.values, &positions, &augmented_positions, domain_size);
-        if evaluations != column_values {
+        // make sure the layer was built for the current domain, and that it contains a row for
+        // every augmented position; otherwise, the values could not be looked up
+        let mut augmented_positions = utils::get_augmented_positions(&self.positions, self.domain_size);
+        if layer.depth as u32 + 2 != self.domain_size.trailing_zeros() {
+            return Err(format!("FRI layer {} must have depth {}, but had depth {}",
+                depth, self.domain_size.trailing_zeros().saturating_sub(2), layer.depth));
+        }
+        if layer.values.len() != augmented_positions.len() {
+            return Err(format!("FRI layer {} must contain {} values, but contained {}",
+                depth, augmented_positions.len(), layer.values.len()));
+        }
+        let column_values = get_column_values(&layer.values, &self.positions, &augmented_positions, self.domain_size);
+        if !record_check(&mut transcript, "fri_layer_values", ct_eq_elements(&self.evaluations, &column_values),
+            || format!("layer: {}, domain size: {}, values: {}", depth, self.domain_size, self.evaluations.len()))
+        {
             return Err(format!("evaluations did not match column value at depth {}", depth));
         }
 
         // verify Merkle proof for the layer
         let merkle_proof = build_layer_merkle_proof(&layer, options);
-        if !MerkleTree::verify_batch(&layer.root, &augmented_positions, &merkle_proof, options.hash_fn()) {
+        if !record_check(&mut transcript, "fri_layer_merkle_proof",
+            MerkleTree::verify_batch(&layer.root, &augmented_positions, &merkle_proof, options.hash_fn()),
+            || format!("layer: {}, root: {}, rows: {}", depth, hex::encode(layer.root), augmented_positions.len()))
+        {
             return Err(format!("verification of Merkle proof failed at layer {}", depth));
         }
 
         // build a set of x for each row polynomial
+        let quartic_roots = &self.quartic_roots;
         let mut xs = Vec::with_capacity(augmented_positions.len());
         for &i in augmented_positions.iter() {
-            let xe = field::exp(domain_root, i as u128);
+            let xe = field::exp(self.domain_root, i as u128);
             xs.push([
                 field::mul(quartic_roots[0], xe),
                 field::mul(quartic_roots[1], xe),
@@ -67,25 +193,36 @@ pub fn verify(
         let special_x = field::prng(layer.root);
 
         // check that when the polynomials are evaluated at x, the result is equal to the corresponding column value
-        evaluations = quartic::evaluate_batch(&row_polys, special_x);
+        self.evaluations = quartic::evaluate_batch(&row_polys, special_x);
 
-        // update variables for the next iteration of the loop
-        domain_root = field::exp(domain_root, 4);
-        max_degree_plus_1 = max_degree_plus_1 / 4;
-        domain_size = domain_size / 4;
-        mem::swap(&mut positions, &mut augmented_positions);
+        // update variables for the next layer
+        self.domain_root = field::exp(self.domain_root, 4);
+        self.max_degree_plus_1 = self.max_degree_plus_1 / 4;
+        self.domain_size = self.domain_size / 4;
+        mem::swap(&mut self.positions, &mut augmented_positions);
+
+        return Ok(());
     }
 
-    // 2 ----- verify the remainder of the FRI proof ----------------------------------------------
-    
-    for (&position, evaluation) in positions.iter().zip(evaluations) {
-        if proof.rem_values[position] != evaluation {
+    /// Verifies that the remainder is consistent with the last layer, and that it satisfies
+    /// the degree.
+    fn verify_remainder(self, rem_values: &[u128], options: &ProofOptions, mut transcript: Option<&mut Transcript>) -> Result<bool, String> {
+
+        let in_bounds = self.positions.iter().all(|&position| position < rem_values.len());
+        let consistent = in_bounds && ct_eq_elements(&self.evaluations,
+            &self.positions.iter().map(|&position| rem_values[position]).collect::<Vec<_>>());
+        if !record_check(&mut transcript, "fri_remainder_values", consistent,
+            || format!("remainder size: {}, values: {}", rem_values.len(), self.positions.len()))
+        {
             return Err(String::from("remainder values are inconsistent with values of the last column"));
         }
-    }
 
-    // make sure the remainder values satisfy the degree
-    return verify_remainder(&proof.rem_values, max_degree_plus_1, domain_root, options.extension_factor());
+        // make sure the remainder values satisfy the degree
+        let result = verify_remainder(rem_values, self.max_degree_plus_1, self.domain_root, options.extension_factor());
+        record_check(&mut transcript, "fri_remainder_degree", result.is_ok(),
+            || format!("max degree: {}", self.max_degree_plus_1 as isize - 1));
+        return result;
+    }
 }
 
 fn verify_remainder(remainder: &[u128], max_degree_plus_1: usize, domain_root: u128, extension_factor: usize) -> Result<bool, String> {
@@ -101,6 +238,10 @@ fn verify_remainder(remainder: &[u128], max_degree_plus_1: usize, domain_root: u
         }
     }
 
+    if max_degree_plus_1 > positions.len() {
+        return Err(String::from("remainder degree is greater than number of remainder values"));
+    }
+
     // pick a subset of points from the remainder and interpolate them into a polynomial
     let domain = field::get_power_series(domain_root, remainder.len());
     let mut xs = Vec::with_capacity(max_degree_plus_1);
@@ -113,11 +254,11 @@ fn verify_remainder(remainder: &[u128], max_degree_plus_1: usize, domain_root: u
     let poly = polynom::interpolate(&xs, &ys);
 
     // check that polynomial evaluates correctly for all other points in the remainder
-    for i in max_degree_plus_1..positions.len() {
-        let p = positions[i];
-        if polynom::eval(&poly, domain[p]) != remainder[p] {
-            return Err(format!("remainder is not a valid degree {} polynomial", max_degree_plus_1 - 1));
-        }
+    let other_positions = &positions[max_degree_plus_1..];
+    let evaluations: Vec<u128> = other_positions.iter().map(|&p| polynom::eval(&poly, domain[p])).collect();
+    let values: Vec<u128> = other_positions.iter().map(|&p| remainder[p]).collect();
+    if !ct_eq_elements(&evaluations, &values) {
+        return Err(format!("remainder is not a valid degree {} polynomial", max_degree_plus_1 - 1));
     }
 
     return Ok(true);
@@ -138,6 +279,10 @@ fn get_column_values(values: &Vec<[u128; 4]>, positions: &[usize], augmented_pos
     return result;
 }
 
+fn read_section<R: Read, T: serde::de::DeserializeOwned>(reader: &mut R, name: &str) -> Result<T, String> {
+    return bincode::deserialize_from(reader).map_err(|err| format!("failed to read {}: {}", name, err));
+}
+
 fn build_layer_merkle_proof(layer: &FriLayer, options: &ProofOptions) -> BatchMerkleProof {
     return BatchMerkleProof {
         values  : utils::hash_values(&layer.values, options.hash_fn()),
@@ -148,7 +293,7 @@ fn build_layer_merkle_proof(layer: &FriLayer, options: &ProofOptions) -> BatchMe
 
 // TESTS
 // ================================================================================================
-#[cfg(test)]
+#[cfg(all(test, feature = "std-rand"))]
 mod tests {
     
     use crate::math::{ field, polynom };
@@ -174,8 +319,7 @@ mod tests {
         // check against lower degree
         let degree_plus_1 = degree_plus_1 - 1;
         let result = super::verify_remainder(&remainder, degree_plus_1, root, extension_factor);
-        let err_msg = format!("remainder is not a valid degree {} polynomial", degree_plus_1 - 1);
-        assert_eq!(Err(err_msg), result);
+        assert!(result.unwrap_err().starts_with("remainder is not a valid degree"));
     }
 
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+use super::StarkProof;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Limits on the size and the structure of proofs accepted by the verifier. Services which
+/// verify proofs received from untrusted parties can use these limits to reject oversized proofs
+/// before any expensive work (e.g. deserialization of large vectors, or hashing) is done.
+///
+/// The default limits are the largest values the verifier can handle, and thus, do not reject
+/// any proof; tighter limits can be set like so:
+/// ```
+/// # use distaff::VerifierLimits;
+/// let limits = VerifierLimits { max_proof_bytes: 1 << 20, ..VerifierLimits::default() };
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierLimits {
+    pub max_proof_bytes : usize,
+    pub max_trace_len   : usize,
+    pub max_queries     : usize,
+    pub max_fri_layers  : usize,
+}
+
+// VERIFIER LIMITS IMPLEMENTATION
+// ================================================================================================
+impl VerifierLimits {
+
+    /// Returns an error if a serialized proof of `num_bytes` bytes exceeds these limits.
+    pub fn check_size(&self, num_bytes: usize) -> Result<(), String> {
+        if num_bytes > self.max_proof_bytes {
+            return Err(format!("proof size of {} bytes exceeds the limit of {} bytes",
+                num_bytes, self.max_proof_bytes));
+        }
+        return Ok(());
+    }
+
+    /// Returns an error if the structure of the `proof` exceeds these limits, or if lengths of
+    /// vectors in the proof do not match the dimensions of the execution trace. Only the header
+    /// of the proof and lengths of its vectors are examined; so, the check is cheap enough to be
+    /// done before any hashing.
+    pub fn check_proof(&self, proof: &StarkProof) -> Result<(), String> {
+        // the trace length is computed from the depth of the domain to make sure that a malformed
+        // depth cannot overflow the computation
+        let ext_depth = proof.options().extension_factor().trailing_zeros() as usize;
+        let trace_depth = proof.domain_depth().saturating_sub(ext_depth);
+        if trace_depth >= usize::BITS as usize || (1 << trace_depth) > self.max_trace_len {
+            return Err(format!("trace length of 2^{} steps exceeds the limit of {} steps",
+                trace_depth, self.max_trace_len));
+        }
+
+        let num_queries = proof.options().num_queries();
+        if num_queries > self.max_queries {
+            return Err(format!("number of queries {} exceeds the limit of {} queries",
+                num_queries, self.max_queries));
+        }
+
+        let num_layers = proof.degree_proof().layers.len();
+        if num_layers > self.max_fri_layers {
+            return Err(format!("number of FRI layers {} exceeds the limit of {} layers",
+                num_layers, self.max_fri_layers));
+        }
+
+        // make sure the verifier can read the proof without going out of bounds
+        return proof.check_structure();
+    }
+}
+
+impl Default for VerifierLimits {
+
+    fn default() -> VerifierLimits {
+        return VerifierLimits {
+            max_proof_bytes : usize::MAX,
+            max_trace_len   : usize::MAX,
+            max_queries     : usize::MAX,
+            max_fri_layers  : usize::MAX,
+        };
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, ProofOptions };
+    use super::VerifierLimits;
+
+    #[test]
+    fn check_proof() {
+        let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+        let (_, proof) = crate::execute(&program, &ProgramInputs::from_public(&[1]), 1, &ProofOptions::default());
+        let proof_bytes = proof.to_bytes();
+
+        // default limits accept any proof
+        let limits = VerifierLimits::default();
+        assert_eq!(Ok(()), limits.check_size(proof_bytes.len()));
+        assert_eq!(Ok(()), limits.check_proof(&proof));
+
+        // limits which match the proof exactly accept it as well
+        let limits = VerifierLimits {
+            max_proof_bytes : proof_bytes.len(),
+            max_trace_len   : proof.trace_length(),
+            max_queries     : proof.options().num_queries(),
+            max_fri_layers  : proof.degree_proof().layers.len(),
+        };
+        assert_eq!(Ok(()), limits.check_size(proof_bytes.len()));
+        assert_eq!(Ok(()), limits.check_proof(&proof));
+
+        // every limit is enforced
+        let tight = VerifierLimits { max_proof_bytes: proof_bytes.len() - 1, ..limits.clone() };
+        assert!(tight.check_size(proof_bytes.len()).is_err());
+
+        let tight = VerifierLimits { max_trace_len: proof.trace_length() - 1, ..limits.clone() };
+        assert!(tight.check_proof(&proof).is_err());
+
+        let tight = VerifierLimits { max_queries: proof.options().num_queries() - 1, ..limits.clone() };
+        assert!(tight.check_proof(&proof).is_err());
+
+        let tight = VerifierLimits { max_fri_layers: proof.degree_proof().layers.len() - 1, ..limits.clone() };
+        assert!(tight.check_proof(&proof).is_err());
+    }
+}
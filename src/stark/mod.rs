@@ -4,23 +4,45 @@ mod options;
 mod prover;
 mod verifier;
 mod proof;
-mod fri;
+mod assertion;
+mod boundary;
+mod limits;
+mod extension;
+mod context;
+mod transcript;
+mod progress;
+mod events;
+mod openings;
+mod sharding;
+pub mod fri;
 mod utils;
 
-pub use trace::{ TraceTable, TraceState };
+pub use trace::{ TraceTable, TraceState, OpFlags };
 
 pub use constraints::{
     ConstraintEvaluator,
     ConstraintTable,
-    ConstraintPoly };
+    ConstraintPoly,
+    describe as describe_constraints,
+    check_constraints, ConstraintGroupCheck, ConstraintFailure };
 
 pub use utils::{
     ConstraintCoefficients,
     CompositionCoefficients };
 
 pub use options::ProofOptions;
-pub use proof::{ StarkProof, DeepValues };
-pub use prover::{ prove };
-pub use verifier::{ verify };
+pub use proof::{ StarkProof, FieldMismatch, DeepValues, PROOF_VERSION };
+pub use assertion::{ Assertion, validate_assertions, get_assertion_steps };
+pub use boundary::BoundaryConstraints;
+pub use limits::VerifierLimits;
+pub use extension::{ AirExtension, validate_extension, get_extension_constraint_num };
+pub use context::{ ProverContext, ProverSetup, TraceShape };
+pub use progress::{ ProgressHandle, ProvingStage, ProvingCancelled };
+pub use events::{ ProverEvent, Verbosity };
+pub use prover::{ prove, prove_committed, prove_to_writer };
+pub use openings::{ CommittedTrace, CellOpening };
+pub use sharding::{ prove_sharded, ShardedProver, ProofHeader, QueryTask, ProofPart };
+pub use verifier::{ verify, verify_program_binding, get_query_positions };
+pub use transcript::{ Transcript, TranscriptEntry, record_check };
 
 const MAX_CONSTRAINT_DEGREE : usize = 8;
\ No newline at end of file
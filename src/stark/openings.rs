@@ -0,0 +1,163 @@
+use serde::{ Serialize, Deserialize };
+use crate::crypto::{ MerkleTree, BatchMerkleProof, HashFunction };
+use crate::utils::as_bytes;
+use super::{ TraceTable, StarkProof };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// An extended execution trace together with the Merkle tree committing to it, as retained by
+/// the prover after a proof was generated (see `execute_with_trace()`). Since the root of the
+/// tree is included in the proof, cells of the trace can be opened against the proof at any
+/// point after the proof was published.
+pub struct CommittedTrace {
+    trace   : TraceTable,
+    tree    : MerkleTree,
+}
+
+/// Values of execution trace cells together with a Merkle proof binding them to the trace root
+/// of a `StarkProof`. Cells are specified as (register, step) tuples, where `register` is an
+/// index into the full trace state (decoder, stacks, and stack depth registers), and `step` is a
+/// step of the un-extended execution trace.
+///
+/// Every leaf of the trace commitment is a hash of an entire trace row, and so, an opening
+/// reveals values of all registers at the opened steps, and not just the requested cells.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellOpening {
+    cells   : Vec<(usize, usize)>,
+    steps   : Vec<usize>,
+    rows    : Vec<Vec<u128>>,
+    nodes   : Vec<Vec<[u8; 32]>>,
+    depth   : u8,
+}
+
+// COMMITTED TRACE IMPLEMENTATION
+// ================================================================================================
+impl CommittedTrace {
+
+    /// Wraps an extended `trace` and the Merkle tree built from it.
+    pub fn new(trace: TraceTable, tree: MerkleTree) -> CommittedTrace {
+        assert!(trace.is_extended(), "trace table has not been extended yet");
+        assert!(tree.leaves().len() == trace.domain_size(), "Merkle tree does not match the trace");
+        return CommittedTrace { trace, tree };
+    }
+
+    /// Returns the root of the trace commitment; this is the same as `StarkProof::trace_root()`.
+    pub fn root(&self) -> &[u8; 32] {
+        return self.tree.root();
+    }
+
+    /// Returns the number of steps in the un-extended execution trace.
+    pub fn trace_length(&self) -> usize {
+        return self.trace.unextended_length();
+    }
+
+    /// Returns the number of registers in the execution trace.
+    pub fn register_count(&self) -> usize {
+        return self.trace.register_count();
+    }
+
+    /// Opens the specified `cells` of the execution trace; every cell is a (register, step)
+    /// tuple. Panics if the list of cells is empty or if any of the cells is out of bounds.
+    pub fn open_cells(&self, cells: &[(usize, usize)]) -> CellOpening {
+        assert!(!cells.is_empty(), "at least one cell must be opened");
+        for &(register, step) in cells.iter() {
+            assert!(register < self.register_count(), "register {} is out of bounds; trace has only {} registers",
+                register, self.register_count());
+            assert!(step < self.trace_length(), "step {} is out of bounds; trace has only {} steps",
+                step, self.trace_length());
+        }
+
+        let steps = get_opened_steps(cells);
+        let positions = map_steps_to_positions(&steps, self.trace.extension_factor());
+        let rows = self.trace.get_register_values_at(&positions);
+        let proof = self.tree.prove_batch(&positions);
+
+        return CellOpening {
+            cells   : cells.to_vec(),
+            steps   : steps,
+            rows    : rows,
+            nodes   : proof.nodes,
+            depth   : proof.depth,
+        };
+    }
+}
+
+// CELL OPENING IMPLEMENTATION
+// ================================================================================================
+impl CellOpening {
+
+    /// Returns the opened (register, step) cells in the order in which they were requested.
+    pub fn cells(&self) -> &[(usize, usize)] {
+        return &self.cells;
+    }
+
+    /// Checks the opening against the trace root of the `proof`, and returns values of the
+    /// opened cells in the order in which they were requested. The proof itself is not verified
+    /// by this function, and must be verified separately.
+    pub fn verify(&self, proof: &StarkProof) -> Result<Vec<u128>, String> {
+        let register_count = TraceTable::check_dimensions(proof.ctx_depth(), proof.loop_depth(), proof.stack_depth())?;
+
+        // make sure the opening is well-formed
+        if self.cells.is_empty() {
+            return Err(format!("opening does not contain any cells"));
+        }
+        for &(register, step) in self.cells.iter() {
+            if register >= register_count {
+                return Err(format!("register {} is out of bounds; trace has only {} registers", register, register_count));
+            }
+            if step >= proof.trace_length() {
+                return Err(format!("step {} is out of bounds; trace has only {} steps", step, proof.trace_length()));
+            }
+        }
+        if self.steps != get_opened_steps(&self.cells) {
+            return Err(format!("opened steps do not match the opened cells"));
+        }
+        if self.rows.len() != self.steps.len() || self.rows.iter().any(|row| row.len() != register_count) {
+            return Err(format!("opening must contain {} rows of {} values", self.steps.len(), register_count));
+        }
+        if self.depth as u32 != proof.domain_size().trailing_zeros() {
+            return Err(format!("opening depth {} does not match trace domain depth {}",
+                self.depth, proof.domain_size().trailing_zeros()));
+        }
+
+        // verify the rows against the trace root
+        let hash_fn = proof.options().hash_fn();
+        let positions = map_steps_to_positions(&self.steps, proof.options().extension_factor());
+        let merkle_proof = BatchMerkleProof {
+            values  : self.rows.iter().map(|row| hash_row(row, hash_fn)).collect(),
+            nodes   : self.nodes.clone(),
+            depth   : self.depth,
+        };
+        if !MerkleTree::verify_batch(proof.trace_root(), &positions, &merkle_proof, hash_fn) {
+            return Err(format!("opened cells do not match the trace root"));
+        }
+
+        return Ok(self.cells.iter().map(|&(register, step)| {
+            let row = self.steps.binary_search(&step).unwrap();
+            self.rows[row][register]
+        }).collect());
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Returns a sorted list of distinct steps referenced by the `cells`.
+fn get_opened_steps(cells: &[(usize, usize)]) -> Vec<usize> {
+    let mut steps: Vec<usize> = cells.iter().map(|&(_, step)| step).collect();
+    steps.sort();
+    steps.dedup();
+    return steps;
+}
+
+/// Maps steps of the execution trace to positions in the extended execution trace.
+fn map_steps_to_positions(steps: &[usize], extension_factor: usize) -> Vec<usize> {
+    return steps.iter().map(|&step| step * extension_factor).collect();
+}
+
+fn hash_row(row: &[u128], hash_fn: HashFunction) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    hash_fn(as_bytes(row), &mut result);
+    return result;
+}
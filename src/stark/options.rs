@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use serde::{ Serialize, Deserialize };
 use crate::crypto::{ HashFunction, hash };
 use super::MAX_CONSTRAINT_DEGREE;
@@ -8,11 +9,25 @@ const DEFAULT_EXTENSION_FACTOR: u8 = (MAX_CONSTRAINT_DEGREE * 4) as u8;
 const DEFAULT_NUM_QUERIES     : u8 = 50;
 const DEFAULT_GRINDING_FACTOR : u8 = 20;
 
+const DEV_EXTENSION_FACTOR    : u8 = (MAX_CONSTRAINT_DEGREE * 2) as u8;
+const DEV_NUM_QUERIES         : u8 = 4;
+const DEV_GRINDING_FACTOR     : u8 = 0;
+
+const MIN_EXTENSION_FACTOR    : usize = 16;
+const MAX_EXTENSION_FACTOR    : usize = 256;
+const MAX_NUM_QUERIES         : usize = 128;
+const MAX_GRINDING_FACTOR     : u32 = 32;
+
+/// Proofs with lower conjectured security level (in bits) are treated as insecure by the verifier.
+const MIN_SECURITY_LEVEL      : u32 = 80;
+
 // TYPES AND INTERFACES
 // ================================================================================================
 
-// TODO: validate field values on de-serialization
+// options are validated on de-serialization, and so, options of a deserialized proof are always
+// within the same bounds as the options created via ProofOptions::new()
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "ProofOptionsData")]
 pub struct ProofOptions {
     extension_factor    : u8,   // stored as power of 2
     num_queries         : u8,
@@ -22,10 +37,24 @@ pub struct ProofOptions {
     hash_fn: HashFunction,
 }
 
+/// Unvalidated fields of `ProofOptions` as they appear in a serialized proof.
+#[derive(Deserialize)]
+struct ProofOptionsData {
+    extension_factor    : u8,
+    num_queries         : u8,
+    grinding_factor     : u8,
+
+    #[serde(with = "hash_fn_serialization")]
+    hash_fn: HashFunction,
+}
+
 // PROOF OPTIONS IMPLEMENTATION
 // ================================================================================================
 impl ProofOptions {
 
+    /// Creates proof options with the specified parameters. `extension_factor` can be any power
+    /// of 2 between 16 and 256; larger factors improve soundness per query at the cost of a
+    /// proportionally larger LDE domain, which is negligible for short execution traces.
     pub fn new(
         extension_factor : usize,
         num_queries      : usize,
@@ -33,13 +62,13 @@ impl ProofOptions {
         hash_fn          : HashFunction) -> ProofOptions
     {
         assert!(extension_factor.is_power_of_two(), "extension_factor must be a power of 2");
-        assert!(extension_factor >= 16, "extension_factor cannot be smaller than 16");
-        assert!(extension_factor <= 256, "extension_factor cannot be greater than 256");
+        assert!(extension_factor >= MIN_EXTENSION_FACTOR, "extension_factor cannot be smaller than {}", MIN_EXTENSION_FACTOR);
+        assert!(extension_factor <= MAX_EXTENSION_FACTOR, "extension_factor cannot be greater than {}", MAX_EXTENSION_FACTOR);
 
         assert!(num_queries > 0, "num_queries must be greater than 0");
-        assert!(num_queries <= 128, "num_queries cannot be greater than 128");
+        assert!(num_queries <= MAX_NUM_QUERIES, "num_queries cannot be greater than {}", MAX_NUM_QUERIES);
 
-        assert!(grinding_factor <= 32, "grinding factor cannot be greater than 32");
+        assert!(grinding_factor <= MAX_GRINDING_FACTOR, "grinding factor cannot be greater than {}", MAX_GRINDING_FACTOR);
 
         return ProofOptions {
             extension_factor    : extension_factor.trailing_zeros() as u8,
@@ -49,6 +78,49 @@ impl ProofOptions {
         };
     }
 
+    /// Creates proof options in the same way as `new()` does, but returns an error instead of
+    /// panicking if any of the parameters is out of bounds.
+    pub(crate) fn try_new(
+        extension_factor : usize,
+        num_queries      : usize,
+        grinding_factor  : u32,
+        hash_fn          : HashFunction) -> Result<ProofOptions, String>
+    {
+        if !extension_factor.is_power_of_two() || extension_factor > MAX_EXTENSION_FACTOR {
+            return Err(format!("extension factor {} must be a power of 2 between {} and {}",
+                extension_factor, MIN_EXTENSION_FACTOR, MAX_EXTENSION_FACTOR));
+        }
+        if num_queries > MAX_NUM_QUERIES {
+            return Err(format!("number of queries {} must be between 1 and {}",
+                num_queries, MAX_NUM_QUERIES));
+        }
+        if grinding_factor > MAX_GRINDING_FACTOR {
+            return Err(format!("grinding factor {} cannot be greater than {}",
+                grinding_factor, MAX_GRINDING_FACTOR));
+        }
+
+        // the remaining bounds are checked in the same way as for deserialized options
+        return ProofOptions::try_from(ProofOptionsData {
+            extension_factor    : extension_factor.trailing_zeros() as u8,
+            num_queries         : num_queries as u8,
+            grinding_factor     : grinding_factor as u8,
+            hash_fn             : hash_fn,
+        });
+    }
+
+    /// Returns proof options which make proof generation and verification as fast as possible
+    /// at the expense of security. Proofs generated with these options are insecure (see
+    /// `is_insecure()`), and are rejected by the verifier unless insecure proofs are explicitly
+    /// allowed. These options are intended only for use during program development.
+    pub fn dev() -> ProofOptions {
+        return ProofOptions {
+            extension_factor: DEV_EXTENSION_FACTOR.trailing_zeros() as u8,
+            num_queries     : DEV_NUM_QUERIES,
+            grinding_factor : DEV_GRINDING_FACTOR,
+            hash_fn         : hash::blake3,
+        };
+    }
+
     pub fn extension_factor(&self) -> usize {
         return 1 << (self.extension_factor as usize)
     }
@@ -65,6 +137,13 @@ impl ProofOptions {
         return self.hash_fn;
     }
 
+    /// Returns true if proofs generated with these options provide less than 80 bits of
+    /// conjectured security (e.g. options created via `ProofOptions::dev()`). This is computed
+    /// from the parameters themselves, and so, the prover cannot make weak options look secure.
+    pub fn is_insecure(&self) -> bool {
+        return self.security_level(true) < MIN_SECURITY_LEVEL;
+    }
+
     pub fn security_level(&self, optimistic: bool) -> u32 {
         let one_over_rho = (self.extension_factor() / MAX_CONSTRAINT_DEGREE) as u32;
         let security_factor = 31 - one_over_rho.leading_zeros(); // same as log2(one_over_rho)
@@ -92,6 +171,58 @@ impl Default for ProofOptions {
 
 }
 
+impl TryFrom<ProofOptionsData> for ProofOptions {
+    type Error = String;
+
+    /// Checks the raw fields before any arithmetic is done on them; the extension factor is
+    /// stored as a power of 2, and so, must be checked as an exponent.
+    fn try_from(data: ProofOptionsData) -> Result<ProofOptions, String> {
+        let min_ext_bits = MIN_EXTENSION_FACTOR.trailing_zeros() as u8;
+        let max_ext_bits = MAX_EXTENSION_FACTOR.trailing_zeros() as u8;
+        if data.extension_factor < min_ext_bits || data.extension_factor > max_ext_bits {
+            return Err(format!("extension factor 2^{} must be between {} and {}",
+                data.extension_factor, MIN_EXTENSION_FACTOR, MAX_EXTENSION_FACTOR));
+        }
+        if data.num_queries == 0 || data.num_queries as usize > MAX_NUM_QUERIES {
+            return Err(format!("number of queries {} must be between 1 and {}",
+                data.num_queries, MAX_NUM_QUERIES));
+        }
+        if data.grinding_factor as u32 > MAX_GRINDING_FACTOR {
+            return Err(format!("grinding factor {} cannot be greater than {}",
+                data.grinding_factor, MAX_GRINDING_FACTOR));
+        }
+
+        return Ok(ProofOptions {
+            extension_factor    : data.extension_factor,
+            num_queries         : data.num_queries,
+            grinding_factor     : data.grinding_factor,
+            hash_fn             : data.hash_fn,
+        });
+    }
+}
+
+// hash functions are compared by address; this is consistent with how hash functions are
+// serialized
+impl PartialEq for ProofOptions {
+    fn eq(&self, other: &ProofOptions) -> bool {
+        return self.extension_factor == other.extension_factor
+            && self.num_queries == other.num_queries
+            && self.grinding_factor == other.grinding_factor
+            && self.hash_fn as usize == other.hash_fn as usize;
+    }
+}
+
+impl Eq for ProofOptions {}
+
+impl std::hash::Hash for ProofOptions {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.extension_factor.hash(state);
+        self.num_queries.hash(state);
+        self.grinding_factor.hash(state);
+        (self.hash_fn as usize).hash(state);
+    }
+}
+
 // HASH FUNCTION SERIALIZATION / DE-SERIALIZATION
 // ================================================================================================
 mod hash_fn_serialization {
@@ -0,0 +1,123 @@
+use std::fmt;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use log::{ debug, trace };
+use super::{ ProverEvent, Verbosity };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Stages of proof generation in the order in which they are executed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ProvingStage {
+    TraceExtension,
+    TraceCommitment,
+    ConstraintEvaluation,
+    ConstraintCommitment,
+    Composition,
+    FriReduction,
+    QuerySelection,
+    ProofConstruction,
+}
+
+/// Error returned when proof generation is cancelled via a `ProgressHandle`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProvingCancelled;
+
+/// A handle which can be used to observe and cancel proof generation. The handle can be shared
+/// between threads (e.g. via an `Arc`): calling `cancel()` from any thread makes the prover stop
+/// at the next progress report, and the progress callback is invoked whenever the prover reaches
+/// a new stage or makes progress within a stage.
+///
+/// Prover events (see `ProverEvent`) are delivered to the logger of the handle; if the handle has
+/// no logger, events are forwarded to the `log` crate: stage events at debug level, and detailed
+/// events at trace level.
+pub struct ProgressHandle {
+    cancelled   : AtomicBool,
+    callback    : Option<Box<dyn Fn(ProvingStage, u8) + Send + Sync>>,
+    logger      : Option<(Verbosity, Box<dyn Fn(&ProverEvent) + Send + Sync>)>,
+}
+
+// PROGRESS HANDLE IMPLEMENTATION
+// ================================================================================================
+impl ProgressHandle {
+
+    /// Returns a handle without a progress callback; such a handle can be used only to cancel
+    /// proof generation.
+    pub fn new() -> ProgressHandle {
+        return ProgressHandle { cancelled: AtomicBool::new(false), callback: None, logger: None };
+    }
+
+    /// Returns a handle which invokes the `callback` with the current stage and the percentage
+    /// of the stage completed (0 - 100) as proof generation progresses.
+    pub fn with_callback<F>(callback: F) -> ProgressHandle
+        where F: Fn(ProvingStage, u8) + Send + Sync + 'static
+    {
+        return ProgressHandle {
+            cancelled   : AtomicBool::new(false),
+            callback    : Some(Box::new(callback)),
+            logger      : None,
+        };
+    }
+
+    /// Returns a handle which invokes the `logger` with every prover event at or below the
+    /// specified `verbosity`.
+    pub fn with_logger<F>(verbosity: Verbosity, logger: F) -> ProgressHandle
+        where F: Fn(&ProverEvent) + Send + Sync + 'static
+    {
+        return ProgressHandle {
+            cancelled   : AtomicBool::new(false),
+            callback    : None,
+            logger      : Some((verbosity, Box::new(logger))),
+        };
+    }
+
+    /// Requests proof generation to be cancelled.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancelled.load(Ordering::Relaxed);
+    }
+
+    /// Reports progress to the callback (if any), and returns an error if proof generation
+    /// should be stopped.
+    pub fn report(&self, stage: ProvingStage, percent: u8) -> Result<(), ProvingCancelled> {
+        if let Some(callback) = &self.callback {
+            callback(stage, percent);
+        }
+        return if self.is_cancelled() { Err(ProvingCancelled) } else { Ok(()) };
+    }
+
+    /// Returns an error if proof generation should be stopped; unlike `report()`, progress is not
+    /// reported to the callback. This is cheap enough to be called inside of long-running loops.
+    pub(super) fn check(&self) -> Result<(), ProvingCancelled> {
+        return if self.is_cancelled() { Err(ProvingCancelled) } else { Ok(()) };
+    }
+
+    /// Delivers the `event` to the logger (if any) or to the `log` crate.
+    pub(super) fn log(&self, event: ProverEvent) {
+        match &self.logger {
+            Some((verbosity, logger)) => if event.verbosity() <= *verbosity { logger(&event) },
+            None => match event.verbosity() {
+                Verbosity::Details => trace!("{}", event),
+                _ => debug!("{}", event),
+            },
+        }
+    }
+}
+
+impl Default for ProgressHandle {
+    fn default() -> ProgressHandle {
+        return ProgressHandle::new();
+    }
+}
+
+impl fmt::Display for ProvingCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "proof generation was cancelled");
+    }
+}
+
+impl std::error::Error for ProvingCancelled {}
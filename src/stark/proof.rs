@@ -1,14 +1,53 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::Write;
 use serde::{ Serialize, Deserialize };
-use crate::crypto::{ BatchMerkleProof };
-use crate::stark::{ fri::FriProof, TraceState, ProofOptions };
-use crate::utils::{ uninit_vector, as_bytes };
+use bincode::Options;
+use serde_json::{ json, Value };
+use crate::crypto::{ BatchMerkleProof, hash };
+use crate::stark::{ fri::{ self, FriProof, FriLayer }, TraceState, TraceTable, ProofOptions, Assertion, VerifierLimits };
+use crate::math::field;
+use crate::utils::{ zeroed_vector, as_bytes };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Version of the JSON document produced by `StarkProof::to_json()`; this must be incremented
+/// whenever the schema of the document changes. Version 2 added `program_salt`.
+const JSON_SCHEMA_VERSION: u32 = 2;
+
+/// Names of hash functions used in JSON documents produced by `StarkProof::to_json()`.
+const HASH_FUNCTIONS: [(&str, crate::crypto::HashFunction); 5] = [
+    ("blake3", hash::blake3), ("sha3", hash::sha3),
+    ("poseidon", hash::poseidon), ("rescue", hash::rescue), ("gmimc", hash::gmimc),
+];
+
+/// Every serialized proof starts with these bytes followed by the version of the proof layout;
+/// proofs serialized before the layout was versioned start directly with the trace root.
+pub const PROOF_MAGIC: [u8; 4] = *b"DSPF";
+
+/// Version of the proof layout produced by the prover; this must be incremented whenever the
+/// fields of `StarkProof` change, and the previous layout must remain readable by
+/// `StarkProof::from_bytes()`.
+pub const PROOF_VERSION: u8 = 2;
+
+/// Version assigned to proofs serialized before the layout was versioned.
+const UNVERSIONED_PROOF_VERSION: u8 = 1;
+
+/// The field has roots of unity of orders up to 2^40, and so, evaluation domains cannot be
+/// larger than that.
+pub(super) const MAX_DOMAIN_DEPTH: usize = 40;
 
 // TYPES AND INTERFACES
 // ================================================================================================
 
 // TODO: custom serialization should reduce size by 5% - 10%
+// NOTE: the order of fields defines the serialized layout of the proof; ProofWriter writes proof
+// sections in the same order, and must be updated if the fields are changed
 #[derive(Clone, Serialize, Deserialize)]
 pub struct StarkProof {
+    magic               : [u8; 4],
+    version             : u8,
     trace_root          : [u8; 32],
     trace_info          : TraceInfo,
     trace_nodes         : Vec<Vec<[u8; 32]>>,
@@ -18,17 +57,81 @@ pub struct StarkProof {
     deep_values         : DeepValues,
     degree_proof        : FriProof,
     pow_nonce           : u64,
+    outputs             : Vec<u128>,
+    assertions          : Vec<Assertion>,
+    extension           : Option<String>,
+    program_salt        : Option<[u8; 32]>,
+    options             : ProofOptions
+}
+
+/// Writes a proof section by section, so that the proof can be streamed out while it is being
+/// built. Sections must be written in the order of the `write_*()` methods, which is the order of
+/// `StarkProof` fields; this way, the written bytes are the same as bincode serialization of a
+/// `StarkProof` with the same contents. Writing a section out of order panics.
+pub(super) struct ProofWriter<'a, W: Write> {
+    writer              : &'a mut W,
+    next                : ProofSection,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ProofSection {
+    TraceCommitment,
+    TraceQueries,
+    ConstraintQueries,
+    DegreeProof,
+    PublicData,
+    Done,
+}
+
+/// Layout of proofs serialized before the layout was versioned (version 1), i.e. proofs produced
+/// by distaff v0.5.1; such proofs carry neither outputs nor the field modulus, and cannot bind
+/// assertions, AIR extensions, or program commitments.
+#[derive(Deserialize)]
+struct StarkProofV1 {
+    trace_root          : [u8; 32],
+    trace_info          : TraceInfoV1,
+    trace_nodes         : Vec<Vec<[u8; 32]>>,
+    trace_evaluations   : Vec<Vec<u128>>,
+    constraint_root     : [u8; 32],
+    constraint_proof    : BatchMerkleProof,
+    deep_values         : DeepValues,
+    degree_proof        : FriProof,
+    pow_nonce           : u64,
     options             : ProofOptions
 }
 
+#[derive(Deserialize)]
+struct TraceInfoV1 {
+    domain_depth        : u8,
+    ctx_depth           : u8,
+    loop_depth          : u8,
+    stack_depth         : u8,
+    op_count            : u32,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeepValues {
     pub trace_at_z1     : Vec<u128>,
     pub trace_at_z2     : Vec<u128>,
 }
 
+/// Commitments to the execution trace and to the constraint evaluations together with their
+/// openings at the queried positions, and the proof of low degree of the composition
+/// polynomial; these are combined with the public data of the execution into a `StarkProof`.
+pub struct ProofComponents {
+    pub trace_root          : [u8; 32],
+    pub trace_proof         : BatchMerkleProof,
+    pub trace_evaluations   : Vec<Vec<u128>>,
+    pub constraint_root     : [u8; 32],
+    pub constraint_proof    : BatchMerkleProof,
+    pub deep_values         : DeepValues,
+    pub degree_proof        : FriProof,
+    pub pow_nonce           : u64,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TraceInfo {
+    pub field_modulus   : u128,
     pub domain_depth    : u8,
     pub ctx_depth       : u8,
     pub loop_depth      : u8,
@@ -36,46 +139,170 @@ pub struct TraceInfo {
     pub op_count        : u32,
 }
 
-// STARK PROOF IMPLEMENTATION
+/// Error returned when a proof was generated over a field other than the field of this verifier.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FieldMismatch {
+    pub proof_modulus       : u128,
+    pub verifier_modulus    : u128,
+}
+
+// TRACE INFO IMPLEMENTATION
 // ================================================================================================
-impl StarkProof {
-    pub fn new(
-        trace_root          : &[u8; 32],
-        trace_proof         : BatchMerkleProof,
-        trace_evaluations   : Vec<Vec<u128>>,
-        constraint_root     : &[u8; 32],
-        constraint_proof    : BatchMerkleProof,
-        deep_values         : DeepValues,
-        degree_proof        : FriProof,
-        pow_nonce           : u64,
-        op_count            : u128,
-        ctx_depth           : usize,
-        loop_depth          : usize,
-        stack_depth         : usize,
-        options             : &ProofOptions ) -> StarkProof
-    {
-        let trace_info = TraceInfo {
-            domain_depth        : trace_proof.depth,
+impl TraceInfo {
+    pub fn new(domain_depth: u8, op_count: u128, ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> TraceInfo {
+        return TraceInfo {
+            field_modulus       : field::MODULUS,
+            domain_depth        : domain_depth,
             ctx_depth           : ctx_depth as u8,
             loop_depth          : loop_depth as u8,
             stack_depth         : stack_depth as u8,
             op_count            : op_count as u32,
         };
+    }
+}
 
+// STARK PROOF IMPLEMENTATION
+// ================================================================================================
+impl StarkProof {
+    pub fn new(
+        components          : ProofComponents,
+        trace_info          : TraceInfo,
+        outputs             : &[u128],
+        assertions          : &[Assertion],
+        extension           : Option<&str>,
+        options             : &ProofOptions ) -> StarkProof
+    {
         return StarkProof {
-            trace_root          : *trace_root,
+            magic               : PROOF_MAGIC,
+            version             : PROOF_VERSION,
+            trace_root          : components.trace_root,
             trace_info          : trace_info,
-            trace_nodes         : trace_proof.nodes,
-            trace_evaluations   : trace_evaluations,
-            constraint_root     : *constraint_root,
-            constraint_proof    : constraint_proof,
-            deep_values         : deep_values,
-            degree_proof        : degree_proof,
-            pow_nonce           : pow_nonce,
+            trace_nodes         : components.trace_proof.nodes,
+            trace_evaluations   : components.trace_evaluations,
+            constraint_root     : components.constraint_root,
+            constraint_proof    : components.constraint_proof,
+            deep_values         : components.deep_values,
+            degree_proof        : components.degree_proof,
+            pow_nonce           : components.pow_nonce,
+            outputs             : outputs.to_vec(),
+            assertions          : assertions.to_vec(),
+            extension           : extension.map(String::from),
+            program_salt        : None,
             options             : options.clone()
         };
     }
 
+    /// Deserializes a proof from the specified `bytes`. The bytes can be a serialization of a
+    /// proof of the current layout (e.g. produced by `to_bytes()` or written by
+    /// `execute_to_writer()`), or of any earlier layout which is still supported; proofs of
+    /// earlier layouts are upgraded to the current one. This way, archived proofs remain
+    /// readable after the layout changes; note that proofs produced by v0.5.1 can be read, but
+    /// not verified (see `check_version()`).
+    ///
+    /// Bytes which start with `PROOF_MAGIC` are always read as a versioned proof. An unversioned
+    /// proof could start with these bytes only if its trace root did, and the chance of this is
+    /// negligible.
+    pub fn from_bytes(bytes: &[u8]) -> Result<StarkProof, String> {
+        return read_proof(bytes, u64::MAX);
+    }
+
+    /// Deserializes a proof from the specified `bytes` in the same way as `from_bytes()` does,
+    /// but rejects the proof if its size or structure exceeds the specified `limits`. The size
+    /// is checked before the bytes are deserialized, the deserializer never reads more than
+    /// `limits.max_proof_bytes` bytes, and the structure is checked right after.
+    pub fn from_bytes_with_limits(bytes: &[u8], limits: &VerifierLimits) -> Result<StarkProof, String> {
+        limits.check_size(bytes.len())?;
+        let proof = read_proof(bytes, limits.max_proof_bytes as u64)?;
+        limits.check_proof(&proof)?;
+        return Ok(proof);
+    }
+
+    /// Serializes this proof with bincode; the serialization starts with `PROOF_MAGIC` followed
+    /// by `PROOF_VERSION`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        return bincode::serialize(self).expect("failed to serialize proof");
+    }
+
+    /// Returns the version of the prover which produced this proof. Proofs of earlier versions
+    /// are upgraded to the current layout by `from_bytes()`, but keep their version; a proof
+    /// deserialized directly with bincode may have been produced by a newer version of the prover.
+    pub fn version(&self) -> u8 {
+        return self.version;
+    }
+
+    /// Returns an error if this proof cannot be verified by this verifier. Proofs produced by
+    /// v0.5.1 (version 1) can be read, but the execution trace of v0.5.1 has no stack depth
+    /// registers, and so, the constraints of the current VM cannot be checked against it.
+    pub(crate) fn check_version(&self) -> Result<(), String> {
+        return match self.version {
+            PROOF_VERSION => Ok(()),
+            UNVERSIONED_PROOF_VERSION => Err(format!("Proof version {} was produced by distaff v0.5.1 and cannot be verified; the verifier supports version {}",
+                UNVERSIONED_PROOF_VERSION, PROOF_VERSION)),
+            version => Err(format!("Proof version {} is not supported; the verifier supports version {}",
+                version, PROOF_VERSION)),
+        };
+    }
+
+    /// Returns an error if this proof was generated over a field other than the field supported
+    /// by this verifier.
+    pub fn check_field(&self) -> Result<(), FieldMismatch> {
+        if self.trace_info.field_modulus != field::MODULUS {
+            return Err(FieldMismatch {
+                proof_modulus       : self.trace_info.field_modulus,
+                verifier_modulus    : field::MODULUS,
+            });
+        }
+        return Ok(());
+    }
+
+    /// Sets the field modulus recorded in this proof; used to test rejection of proofs generated
+    /// over other fields.
+    #[cfg(test)]
+    pub(crate) fn set_field_modulus(&mut self, modulus: u128) {
+        self.trace_info.field_modulus = modulus;
+    }
+
+    /// Returns an error if lengths of the vectors in this proof do not match the dimensions of
+    /// the execution trace and the options of the proof. This is checked before the verifier
+    /// reads any of these vectors; counts which depend on query positions (i.e. numbers of
+    /// constraint and FRI layer values) are checked by the verifier once the positions are known.
+    pub(crate) fn check_structure(&self) -> Result<(), String> {
+        self.check_version()?;
+
+        let ext_depth = self.options.extension_factor().trailing_zeros() as usize;
+        let min_depth = ext_depth + crate::MIN_TRACE_LENGTH.trailing_zeros() as usize;
+        if self.domain_depth() < min_depth || self.domain_depth() > MAX_DOMAIN_DEPTH {
+            return Err(format!("domain depth must be between {} and {}, but was {}",
+                min_depth, MAX_DOMAIN_DEPTH, self.domain_depth()));
+        }
+
+        let register_count = TraceTable::check_dimensions(self.ctx_depth(), self.loop_depth(), self.stack_depth())?;
+        let deep_values = [("trace_at_z1", &self.deep_values.trace_at_z1), ("trace_at_z2", &self.deep_values.trace_at_z2)];
+        for (name, values) in deep_values.iter() {
+            if values.len() != register_count {
+                return Err(format!("{} must contain {} values, but contained {}", name, register_count, values.len()));
+            }
+        }
+
+        let num_queries = self.options.num_queries();
+        if self.trace_evaluations.len() != num_queries {
+            return Err(format!("trace evaluations must contain {} rows, but contained {}",
+                num_queries, self.trace_evaluations.len()));
+        }
+        if let Some(row) = self.trace_evaluations.iter().find(|row| row.len() != register_count) {
+            return Err(format!("trace evaluation rows must contain {} values, but a row contained {}",
+                register_count, row.len()));
+        }
+
+        // every leaf of the constraint tree holds two evaluations
+        if self.constraint_proof.depth as usize != self.domain_depth() - 1 {
+            return Err(format!("constraint Merkle proof must have depth {}, but had depth {}",
+                self.domain_depth() - 1, self.constraint_proof.depth));
+        }
+
+        return fri::check_structure(&self.degree_proof, self.domain_depth());
+    }
+
     pub fn trace_root(&self) -> &[u8; 32] {
         return &self.trace_root;
     }
@@ -84,6 +311,10 @@ impl StarkProof {
         return &self.options;
     }
 
+    pub(crate) fn domain_depth(&self) -> usize {
+        return self.trace_info.domain_depth as usize;
+    }
+
     pub fn domain_size(&self) -> usize {
         return usize::pow(2, self.trace_info.domain_depth as u32);
     }
@@ -91,7 +322,7 @@ impl StarkProof {
     pub fn trace_proof(&self) -> BatchMerkleProof {
 
         let hash = self.options.hash_fn();
-        let mut hashed_states = uninit_vector::<[u8; 32]>(self.trace_evaluations.len());
+        let mut hashed_states = zeroed_vector::<[u8; 32]>(self.trace_evaluations.len());
         for i in 0..self.trace_evaluations.len() {
             hash(as_bytes(&self.trace_evaluations[i]), &mut hashed_states[i]);
         }
@@ -123,8 +354,236 @@ impl StarkProof {
         return self.pow_nonce;
     }
 
+    /// Returns outputs of the program bound by the boundary constraints of this proof.
+    pub fn outputs(&self) -> &[u128] {
+        return &self.outputs;
+    }
+
+    /// Returns user assertions about intermediate states of the execution trace bound by the
+    /// boundary constraints of this proof.
+    pub fn assertions(&self) -> &[Assertion] {
+        return &self.assertions;
+    }
+
+    /// Returns the identifier of the AIR extension the proof was generated with, if any.
+    pub fn extension_id(&self) -> Option<&str> {
+        return self.extension.as_deref();
+    }
+
+    /// Returns the salt of the program commitment this proof was generated for, if any.
+    pub fn program_salt(&self) -> Option<&[u8; 32]> {
+        return self.program_salt.as_ref();
+    }
+
+    /// Sets the salt of the program commitment this proof can be verified against; see
+    /// `execute_blinded()`. The salt is not bound by the proof itself: it only opens the
+    /// commitment to the program hash against which the proof is verified.
+    pub fn set_program_salt(&mut self, salt: &[u8; 32]) {
+        self.program_salt = Some(*salt);
+    }
+
+    /// Returns a JSON document containing this proof together with the public data against
+    /// which it can be verified (`program_hash` and `public_inputs`); program outputs and
+    /// assertions are included in the proof. The document is intended for verifiers written in
+    /// other languages: all fields of the proof are included, hashes are encoded as hex strings
+    /// of 32 bytes, and field elements are encoded as hex strings of their 16-byte little-endian
+    /// representation (the same representation which is hashed into Merkle tree leaves). The
+    /// schema of the document is described in docs/proof_json.md.
+    pub fn to_json(&self, program_hash: &[u8; 32], public_inputs: &[u128]) -> String {
+        let document = json!({
+            "version": JSON_SCHEMA_VERSION,
+            "program_hash": hex::encode(program_hash),
+            "public_inputs": elements_to_json(public_inputs),
+            "outputs": elements_to_json(&self.outputs),
+            "assertions": self.assertions.iter().map(|a| json!({
+                "register": a.register,
+                "step": a.step,
+                "value": element_to_json(a.value),
+            })).collect::<Vec<Value>>(),
+            "extension": self.extension,
+            "program_salt": self.program_salt.map(hex::encode),
+            "options": json!({
+                "extension_factor": self.options.extension_factor(),
+                "num_queries": self.options.num_queries(),
+                "grinding_factor": self.options.grinding_factor(),
+                "insecure": self.options.is_insecure(),
+                "hash_fn": hash_fn_name(self.options.hash_fn()),
+            }),
+            "trace_info": json!({
+                "field_modulus": element_to_json(self.trace_info.field_modulus),
+                "domain_depth": self.trace_info.domain_depth,
+                "ctx_depth": self.trace_info.ctx_depth,
+                "loop_depth": self.trace_info.loop_depth,
+                "stack_depth": self.trace_info.stack_depth,
+                "op_count": self.trace_info.op_count,
+            }),
+            "trace_root": hex::encode(self.trace_root),
+            "trace_nodes": nodes_to_json(&self.trace_nodes),
+            "trace_evaluations": self.trace_evaluations.iter()
+                .map(|row| elements_to_json(row)).collect::<Vec<Value>>(),
+            "constraint_root": hex::encode(self.constraint_root),
+            "constraint_proof": json!({
+                "values": self.constraint_proof.values.iter().map(hex::encode).collect::<Vec<String>>(),
+                "nodes": nodes_to_json(&self.constraint_proof.nodes),
+                "depth": self.constraint_proof.depth,
+            }),
+            "deep_values": json!({
+                "trace_at_z1": elements_to_json(&self.deep_values.trace_at_z1),
+                "trace_at_z2": elements_to_json(&self.deep_values.trace_at_z2),
+            }),
+            "degree_proof": json!({
+                "layers": self.degree_proof.layers.iter().map(|layer| json!({
+                    "root": hex::encode(layer.root),
+                    "values": layer.values.iter().map(|v| elements_to_json(v)).collect::<Vec<Value>>(),
+                    "nodes": nodes_to_json(&layer.nodes),
+                    "depth": layer.depth,
+                })).collect::<Vec<Value>>(),
+                "rem_root": hex::encode(self.degree_proof.rem_root),
+                "rem_values": elements_to_json(&self.degree_proof.rem_values),
+            }),
+            "pow_nonce": self.pow_nonce,
+        });
+
+        return serde_json::to_string_pretty(&document).expect("failed to serialize proof into JSON");
+    }
+
+    /// Reads a proof from a JSON document produced by `to_json()`, and returns the proof together
+    /// with the program hash and the public inputs included in the document. Documents of all
+    /// schema versions up to the current one are accepted; version 1 documents predate program
+    /// commitments, and so, proofs read from them carry no program salt.
+    pub fn from_json(json: &str) -> Result<(StarkProof, [u8; 32], Vec<u128>), String> {
+        let document: Value = serde_json::from_str(json)
+            .map_err(|err| format!("failed to parse proof JSON: {}", err))?;
+
+        let version = number_from_json(&document["version"], "version")?;
+        if version == 0 || version > JSON_SCHEMA_VERSION as u64 {
+            return Err(format!("JSON schema version {} is not supported; supported versions are 1 through {}",
+                version, JSON_SCHEMA_VERSION));
+        }
+
+        let program_hash = hash_from_json(&document["program_hash"], "program_hash")?;
+        let public_inputs = elements_from_json(&document["public_inputs"], "public_inputs")?;
+
+        let assertions = array_from_json(&document["assertions"], "assertions")?.iter().enumerate()
+            .map(|(i, a)| Ok(Assertion::new(
+                number_from_json(&a["register"], &format!("assertions[{}].register", i))? as usize,
+                number_from_json(&a["step"], &format!("assertions[{}].step", i))? as usize,
+                element_from_json(&a["value"], &format!("assertions[{}].value", i))?)))
+            .collect::<Result<Vec<Assertion>, String>>()?;
+
+        let extension = match &document["extension"] {
+            Value::Null => None,
+            Value::String(id) => Some(id.clone()),
+            _ => return Err(String::from("extension must be a string or null")),
+        };
+
+        // program salt was added in version 2 of the schema
+        let program_salt = match &document["program_salt"] {
+            _ if version < 2 => None,
+            Value::Null => None,
+            salt => Some(hash_from_json(salt, "program_salt")?),
+        };
+
+        let options = &document["options"];
+        let hash_fn_name = options["hash_fn"].as_str().unwrap_or_default();
+        let hash_fn = match HASH_FUNCTIONS.iter().find(|(name, _)| *name == hash_fn_name) {
+            Some((_, hash_fn)) => *hash_fn,
+            None => return Err(format!("hash function '{}' is not supported", hash_fn_name)),
+        };
+        let options = ProofOptions::try_new(
+            number_from_json(&options["extension_factor"], "options.extension_factor")? as usize,
+            number_from_json(&options["num_queries"], "options.num_queries")? as usize,
+            number_from_json(&options["grinding_factor"], "options.grinding_factor")? as u32,
+            hash_fn)?;
+
+        let trace_info = &document["trace_info"];
+        let trace_info = TraceInfo {
+            field_modulus   : element_from_json(&trace_info["field_modulus"], "trace_info.field_modulus")?,
+            domain_depth    : byte_from_json(&trace_info["domain_depth"], "trace_info.domain_depth")?,
+            ctx_depth       : byte_from_json(&trace_info["ctx_depth"], "trace_info.ctx_depth")?,
+            loop_depth      : byte_from_json(&trace_info["loop_depth"], "trace_info.loop_depth")?,
+            stack_depth     : byte_from_json(&trace_info["stack_depth"], "trace_info.stack_depth")?,
+            op_count        : number_from_json(&trace_info["op_count"], "trace_info.op_count")? as u32,
+        };
+
+        let trace_evaluations = array_from_json(&document["trace_evaluations"], "trace_evaluations")?.iter()
+            .enumerate()
+            .map(|(i, row)| elements_from_json(row, &format!("trace_evaluations[{}]", i)))
+            .collect::<Result<Vec<Vec<u128>>, String>>()?;
+
+        let constraint_proof = &document["constraint_proof"];
+        let constraint_proof = BatchMerkleProof {
+            values  : array_from_json(&constraint_proof["values"], "constraint_proof.values")?.iter()
+                .enumerate()
+                .map(|(i, v)| hash_from_json(v, &format!("constraint_proof.values[{}]", i)))
+                .collect::<Result<Vec<[u8; 32]>, String>>()?,
+            nodes   : nodes_from_json(&constraint_proof["nodes"], "constraint_proof.nodes")?,
+            depth   : byte_from_json(&constraint_proof["depth"], "constraint_proof.depth")?,
+        };
+
+        let deep_values = &document["deep_values"];
+        let deep_values = DeepValues {
+            trace_at_z1     : elements_from_json(&deep_values["trace_at_z1"], "deep_values.trace_at_z1")?,
+            trace_at_z2     : elements_from_json(&deep_values["trace_at_z2"], "deep_values.trace_at_z2")?,
+        };
+
+        let degree_proof = &document["degree_proof"];
+        let layers = array_from_json(&degree_proof["layers"], "degree_proof.layers")?.iter().enumerate()
+            .map(|(i, layer)| {
+                let path = format!("degree_proof.layers[{}]", i);
+                let values = array_from_json(&layer["values"], &format!("{}.values", path))?.iter().enumerate()
+                    .map(|(j, v)| {
+                        let values = elements_from_json(v, &format!("{}.values[{}]", path, j))?;
+                        let mut result = [0u128; 4];
+                        if values.len() != result.len() {
+                            return Err(format!("{}.values[{}] must contain {} elements", path, j, result.len()));
+                        }
+                        result.copy_from_slice(&values);
+                        Ok(result)
+                    })
+                    .collect::<Result<Vec<[u128; 4]>, String>>()?;
+                Ok(FriLayer {
+                    root    : hash_from_json(&layer["root"], &format!("{}.root", path))?,
+                    values  : values,
+                    nodes   : nodes_from_json(&layer["nodes"], &format!("{}.nodes", path))?,
+                    depth   : byte_from_json(&layer["depth"], &format!("{}.depth", path))?,
+                })
+            })
+            .collect::<Result<Vec<FriLayer>, String>>()?;
+        let degree_proof = FriProof {
+            layers      : layers,
+            rem_root    : hash_from_json(&degree_proof["rem_root"], "degree_proof.rem_root")?,
+            rem_values  : elements_from_json(&degree_proof["rem_values"], "degree_proof.rem_values")?,
+        };
+
+        let proof = StarkProof {
+            magic               : PROOF_MAGIC,
+            version             : PROOF_VERSION,
+            trace_root          : hash_from_json(&document["trace_root"], "trace_root")?,
+            trace_info          : trace_info,
+            trace_nodes         : nodes_from_json(&document["trace_nodes"], "trace_nodes")?,
+            trace_evaluations   : trace_evaluations,
+            constraint_root     : hash_from_json(&document["constraint_root"], "constraint_root")?,
+            constraint_proof    : constraint_proof,
+            deep_values         : deep_values,
+            degree_proof        : degree_proof,
+            pow_nonce           : number_from_json(&document["pow_nonce"], "pow_nonce")?,
+            outputs             : elements_from_json(&document["outputs"], "outputs")?,
+            assertions          : assertions,
+            extension           : extension,
+            program_salt        : program_salt,
+            options             : options
+        };
+        return Ok((proof, program_hash, public_inputs));
+    }
+
     // TRACE INFO
     // -------------------------------------------------------------------------------------------
+    /// Returns modulus of the field over which the execution trace of this proof was built.
+    pub fn field_modulus(&self) -> u128 {
+        return self.trace_info.field_modulus;
+    }
+
     pub fn trace_length(&self) -> usize {
         return self.domain_size() / self.options.extension_factor();
     }
@@ -162,4 +621,206 @@ impl StarkProof {
             self.stack_depth(),
             &self.deep_values.trace_at_z2);
     }
-}
\ No newline at end of file
+}
+
+// PROOF WRITER IMPLEMENTATION
+// ================================================================================================
+impl<'a, W: Write> ProofWriter<'a, W> {
+
+    pub fn new(writer: &'a mut W) -> ProofWriter<'a, W> {
+        return ProofWriter { writer, next: ProofSection::TraceCommitment };
+    }
+
+    /// Writes the magic bytes, the proof version, the trace root, and the trace info.
+    pub fn write_trace_commitment(&mut self, trace_root: &[u8; 32], trace_info: &TraceInfo) -> Result<(), String> {
+        self.begin(ProofSection::TraceCommitment, ProofSection::TraceQueries);
+        self.write(&PROOF_MAGIC)?;
+        self.write(&PROOF_VERSION)?;
+        self.write(trace_root)?;
+        return self.write(trace_info);
+    }
+
+    /// Writes the trace Merkle proof nodes and the trace evaluations at queried positions.
+    pub fn write_trace_queries(&mut self, trace_nodes: &[Vec<[u8; 32]>], trace_evaluations: &[Vec<u128>]) -> Result<(), String> {
+        self.begin(ProofSection::TraceQueries, ProofSection::ConstraintQueries);
+        self.write(trace_nodes)?;
+        return self.write(trace_evaluations);
+    }
+
+    /// Writes the constraint root, the constraint Merkle proof, and the DEEP values.
+    pub fn write_constraint_queries(&mut self, constraint_root: &[u8; 32], constraint_proof: &BatchMerkleProof, deep_values: &DeepValues) -> Result<(), String> {
+        self.begin(ProofSection::ConstraintQueries, ProofSection::DegreeProof);
+        self.write(constraint_root)?;
+        self.write(constraint_proof)?;
+        return self.write(deep_values);
+    }
+
+    /// Writes the FRI proof; every layer is written as soon as it is produced by the `layers`
+    /// iterator, and so, the layers do not need to be kept in memory at the same time.
+    pub fn write_degree_proof<I>(&mut self, layers: I, rem_root: &[u8; 32], rem_values: &[u128]) -> Result<(), String>
+        where I: ExactSizeIterator<Item = FriLayer>
+    {
+        self.begin(ProofSection::DegreeProof, ProofSection::PublicData);
+        self.write(&(layers.len() as u64))?;
+        for layer in layers {
+            self.write(&layer)?;
+        }
+        self.write(rem_root)?;
+        return self.write(rem_values);
+    }
+
+    /// Writes the proof-of-work nonce, the public data of the proof, and the proof options.
+    pub fn write_public_data(&mut self, pow_nonce: u64, outputs: &[u128], assertions: &[Assertion], extension: Option<&str>, program_salt: Option<&[u8; 32]>, options: &ProofOptions) -> Result<(), String> {
+        self.begin(ProofSection::PublicData, ProofSection::Done);
+        self.write(&pow_nonce)?;
+        self.write(outputs)?;
+        self.write(assertions)?;
+        self.write(&extension)?;
+        self.write(&program_salt)?;
+        return self.write(options);
+    }
+
+    fn begin(&mut self, section: ProofSection, next: ProofSection) {
+        assert!(self.next == section, "cannot write {:?} section: expected {:?} section", section, self.next);
+        self.next = next;
+    }
+
+    fn write<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), String> {
+        return bincode::serialize_into(&mut *self.writer, value)
+            .map_err(|err| format!("failed to write proof section: {}", err));
+    }
+}
+
+impl From<StarkProofV1> for StarkProof {
+    fn from(proof: StarkProofV1) -> StarkProof {
+        // v0.5.1 supported only the field which is still the only supported field
+        let trace_info = TraceInfo {
+            field_modulus       : field::MODULUS,
+            domain_depth        : proof.trace_info.domain_depth,
+            ctx_depth           : proof.trace_info.ctx_depth,
+            loop_depth          : proof.trace_info.loop_depth,
+            stack_depth         : proof.trace_info.stack_depth,
+            op_count            : proof.trace_info.op_count,
+        };
+
+        return StarkProof {
+            magic               : PROOF_MAGIC,
+            version             : UNVERSIONED_PROOF_VERSION,
+            trace_root          : proof.trace_root,
+            trace_info          : trace_info,
+            trace_nodes         : proof.trace_nodes,
+            trace_evaluations   : proof.trace_evaluations,
+            constraint_root     : proof.constraint_root,
+            constraint_proof    : proof.constraint_proof,
+            deep_values         : proof.deep_values,
+            degree_proof        : proof.degree_proof,
+            pow_nonce           : proof.pow_nonce,
+            outputs             : Vec::new(),
+            assertions          : Vec::new(),
+            extension           : None,
+            program_salt        : None,
+            options             : proof.options
+        };
+    }
+}
+
+impl fmt::Display for FieldMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return write!(f, "Proof was generated over a field with modulus {}, but the verifier supports only modulus {}",
+            self.proof_modulus, self.verifier_modulus);
+    }
+}
+
+impl std::error::Error for FieldMismatch {}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+fn read_proof(bytes: &[u8], byte_limit: u64) -> Result<StarkProof, String> {
+    let version = match bytes.get(PROOF_MAGIC.len()) {
+        Some(&version) if bytes.starts_with(&PROOF_MAGIC) => Some(version),
+        _ => None,
+    };
+
+    let result = match version {
+        None => deserialize::<StarkProofV1>(bytes, byte_limit).map(StarkProof::from),
+        // upgraded v0.5.1 proofs keep their version when they are serialized again
+        Some(PROOF_VERSION) | Some(UNVERSIONED_PROOF_VERSION) => deserialize::<StarkProof>(bytes, byte_limit),
+        Some(version) => Err(format!("proof version {} is not supported; supported versions are {} through {}",
+            version, UNVERSIONED_PROOF_VERSION, PROOF_VERSION)),
+    };
+
+    let version = version.unwrap_or(UNVERSIONED_PROOF_VERSION);
+    return result.map_err(|err| format!("failed to deserialize proof of version {}: {}", version, err));
+}
+
+/// Deserializes bytes in the same way as `bincode::deserialize()` does, but fails as soon as more
+/// than `byte_limit` bytes are read.
+fn deserialize<'a, T: Deserialize<'a>>(bytes: &'a [u8], byte_limit: u64) -> Result<T, String> {
+    return bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(byte_limit)
+        .deserialize(bytes)
+        .map_err(|err| err.to_string());
+}
+
+fn element_to_json(value: u128) -> Value {
+    return Value::String(hex::encode(value.to_le_bytes()));
+}
+
+fn elements_to_json(values: &[u128]) -> Value {
+    return Value::Array(values.iter().map(|&v| element_to_json(v)).collect());
+}
+
+fn nodes_to_json(nodes: &[Vec<[u8; 32]>]) -> Value {
+    return nodes.iter().map(|path| path.iter().map(hex::encode).collect::<Vec<String>>()).collect();
+}
+
+fn array_from_json<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, String> {
+    return value.as_array().ok_or_else(|| format!("{} must be an array", path));
+}
+
+fn number_from_json(value: &Value, path: &str) -> Result<u64, String> {
+    return value.as_u64().ok_or_else(|| format!("{} must be a non-negative integer", path));
+}
+
+fn byte_from_json(value: &Value, path: &str) -> Result<u8, String> {
+    let number = number_from_json(value, path)?;
+    return u8::try_from(number).map_err(|_| format!("{} cannot be greater than {}", path, u8::MAX));
+}
+
+fn bytes_from_json<const N: usize>(value: &Value, path: &str) -> Result<[u8; N], String> {
+    let bytes = value.as_str().and_then(|v| hex::decode(v).ok())
+        .ok_or_else(|| format!("{} must be a hex string", path))?;
+    return <[u8; N]>::try_from(&bytes[..])
+        .map_err(|_| format!("{} must encode {} bytes, but encodes {}", path, N, bytes.len()));
+}
+
+fn hash_from_json(value: &Value, path: &str) -> Result<[u8; 32], String> {
+    return bytes_from_json::<32>(value, path);
+}
+
+fn element_from_json(value: &Value, path: &str) -> Result<u128, String> {
+    return Ok(u128::from_le_bytes(bytes_from_json::<16>(value, path)?));
+}
+
+fn elements_from_json(value: &Value, path: &str) -> Result<Vec<u128>, String> {
+    return array_from_json(value, path)?.iter().enumerate()
+        .map(|(i, v)| element_from_json(v, &format!("{}[{}]", path, i)))
+        .collect();
+}
+
+fn nodes_from_json(value: &Value, path: &str) -> Result<Vec<Vec<[u8; 32]>>, String> {
+    return array_from_json(value, path)?.iter().enumerate()
+        .map(|(i, nodes)| array_from_json(nodes, &format!("{}[{}]", path, i))?.iter().enumerate()
+            .map(|(j, node)| hash_from_json(node, &format!("{}[{}][{}]", path, i, j)))
+            .collect())
+        .collect();
+}
+
+fn hash_fn_name(hash_fn: crate::crypto::HashFunction) -> &'static str {
+    return match HASH_FUNCTIONS.iter().find(|(_, f)| *f as usize == hash_fn as usize) {
+        Some((name, _)) => name,
+        None => "unknown",
+    };
+}
@@ -1,46 +1,212 @@
-use std::time::Instant;
-use log::debug;
+use std::io::Write;
+use std::sync::Arc;
 use crate::{
-    math::{ field, polynom, fft },
+    math::{ field, polynom },
     crypto::MerkleTree,
 };
 use super::{
-    ProofOptions, StarkProof, CompositionCoefficients, DeepValues, fri, utils,
+    ProofOptions, StarkProof, CompositionCoefficients, DeepValues, BoundaryConstraints, AirExtension,
+    ProverContext, ProverSetup, TraceShape, ProgressHandle, ProvingStage, ProvingCancelled, ProverEvent, CommittedTrace, fri, utils,
+    proof::{ TraceInfo, ProofComponents, ProofWriter },
     trace::{ TraceTable, TraceState },
     constraints::{ ConstraintTable, ConstraintPoly },
     MAX_CONSTRAINT_DEGREE,
 };
 
-// PROVER FUNCTION
+// TYPES AND INTERFACES
 // ================================================================================================
 
-pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options: &ProofOptions) -> StarkProof {
-    // 1 ----- extend execution trace -------------------------------------------------------------
-    let now = Instant::now();
+/// Commitments to the execution trace, constraint evaluations, and FRI layers, together with
+/// query positions derived from them; these are all that is needed to build a proof.
+pub(super) struct Commitments {
+    pub trace_tree      : MerkleTree,
+    pub constraint_tree : MerkleTree,
+    pub deep_values     : DeepValues,
+    pub fri_trees       : Vec<MerkleTree>,
+    pub fri_values      : Vec<Vec<[u128; 4]>>,
+    pub positions       : Vec<usize>,
+    pub pow_nonce       : u64,
+}
+
+// PROVER FUNCTIONS
+// ================================================================================================
+
+/// Generates a proof of the execution `trace`; returns an error if proof generation is cancelled
+/// via the `progress` handle.
+pub fn prove(trace: &mut TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<StarkProof, ProvingCancelled> {
+    let (proof, _) = build_proof(trace, boundary, extension, options, context, progress)?;
+    return Ok(proof);
+}
+
+/// Generates a proof in the same way as `prove()` function does, but also returns the extended
+/// execution trace together with the Merkle tree committing to it; this way, cells of the trace
+/// can be opened against the proof after the proof has been generated.
+pub fn prove_committed(mut trace: TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<(StarkProof, CommittedTrace), ProvingCancelled> {
+    let (proof, trace_tree) = build_proof(&mut trace, boundary, extension, options, context, progress)?;
+    return Ok((proof, CommittedTrace::new(trace, trace_tree)));
+}
+
+fn build_proof(trace: &mut TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<(StarkProof, MerkleTree), ProvingCancelled> {
+
+    let Commitments {
+        trace_tree, constraint_tree, deep_values, fri_trees, fri_values, positions, pow_nonce
+    } = commit(trace, boundary, extension, options, context, progress)?;
+
+    // 9 ----- build proof object -----------------------------------------------------------------
+    progress.report(ProvingStage::ProofConstruction, 0)?;
+
+    // generate FRI proof
+    let fri_proof = fri::build_proof(fri_trees, fri_values, &positions);
+
+    // built a list of trace evaluations at queried positions
+    let trace_evaluations = trace.get_register_values_at(&positions);
+
+    // build a list of constraint positions
+    let constraint_positions = utils::map_trace_to_constraint_positions(&positions);
+
+    // build the proof object
+    let components = ProofComponents {
+        trace_root          : *trace_tree.root(),
+        trace_proof         : trace_tree.prove_batch(&positions),
+        trace_evaluations   : trace_evaluations,
+        constraint_root     : *constraint_tree.root(),
+        constraint_proof    : constraint_tree.prove_batch(&constraint_positions),
+        deep_values         : deep_values,
+        degree_proof        : fri_proof,
+        pow_nonce           : pow_nonce,
+    };
+    let trace_info = TraceInfo::new(
+        components.trace_proof.depth,
+        trace.get_last_state().op_counter(),
+        trace.ctx_depth(),
+        trace.loop_depth(),
+        trace.stack_depth());
+    let proof = StarkProof::new(
+        components,
+        trace_info,
+        boundary.get_outputs(),
+        boundary.get_assertions(),
+        extension.map(|e| e.id()),
+        &options);
+
+    progress.log(ProverEvent::ProofBuilt);
+    progress.report(ProvingStage::ProofConstruction, 100)?;
+    return Ok((proof, trace_tree));
+}
+
+/// Generates a proof in the same way as `prove()` function does, but instead of building a proof
+/// object, writes the proof to the `writer` section by section as the sections are produced: the
+/// trace commitment is written as soon as the trace is committed to, and the queried values of
+/// every commitment are written as soon as query positions are known, after which the commitment
+/// is dropped. The written bytes are the same as bincode serialization of the proof returned by
+/// `prove()`, with the program commitment `salt` (if any) set via `set_program_salt()`.
+pub fn prove_to_writer<W: Write>(trace: &mut TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, salt: Option<&[u8; 32]>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle, writer: &mut W) -> Result<(), String> {
+    let mut writer = ProofWriter::new(writer);
+
+    let (setup, trace_tree) = commit_trace(trace, boundary, options, context, progress)
+        .map_err(|e| e.to_string())?;
+    let trace_info = TraceInfo::new(
+        trace.domain_size().trailing_zeros() as u8,
+        trace.get_last_state().op_counter(),
+        trace.ctx_depth(),
+        trace.loop_depth(),
+        trace.stack_depth());
+    writer.write_trace_commitment(trace_tree.root(), &trace_info)?;
+
+    let Commitments {
+        trace_tree, constraint_tree, deep_values, fri_trees, fri_values, positions, pow_nonce
+    } = commit_constraints(trace, trace_tree, &setup, boundary, extension, options, progress)
+        .map_err(|e| e.to_string())?;
+
+    // 9 ----- write queried values ---------------------------------------------------------------
+    progress.report(ProvingStage::ProofConstruction, 0).map_err(|e| e.to_string())?;
+
+    let trace_proof = trace_tree.prove_batch(&positions);
+    drop(trace_tree);
+    writer.write_trace_queries(&trace_proof.nodes, &trace.get_register_values_at(&positions))?;
+    drop(trace_proof);
+
+    let constraint_positions = utils::map_trace_to_constraint_positions(&positions);
+    let constraint_proof = constraint_tree.prove_batch(&constraint_positions);
+    writer.write_constraint_queries(constraint_tree.root(), &constraint_proof, &deep_values)?;
+    drop(constraint_tree);
+
+    let (fri_layers, rem_root, rem_values) = fri::build_layers(fri_trees, fri_values, &positions);
+    writer.write_degree_proof(fri_layers, &rem_root, &rem_values)?;
+
+    writer.write_public_data(
+        pow_nonce,
+        boundary.get_outputs(),
+        boundary.get_assertions(),
+        extension.map(|e| e.id()),
+        salt,
+        options)?;
+
+    progress.log(ProverEvent::ProofBuilt);
+    progress.report(ProvingStage::ProofConstruction, 100).map_err(|e| e.to_string())?;
+    return Ok(());
+}
 
-    // build LDE domain and LDE twiddles (for FFT evaluation over LDE domain)
-    let lde_root = field::get_root_of_unity(trace.domain_size());
-    let lde_domain = field::get_power_series(lde_root, trace.domain_size());
-    let lde_twiddles = twiddles_from_domain(&lde_domain);
+// COMMITMENTS
+// ================================================================================================
+
+/// Extends the execution trace, evaluates constraints, builds the composition polynomial and its
+/// FRI layers, and determines query positions. Constants, domains, and FFT plans which depend
+/// only on the shape of the trace are taken from the prover `context`. Progress is reported to
+/// the `progress` handle at the start of every stage, and periodically during constraint
+/// evaluation; an error is returned as soon as cancellation is detected.
+///
+/// Panics if the trace does not satisfy the `boundary` constraints (e.g. if the trace was
+/// generated by a program with a different hash); a proof of such a trace would not verify.
+pub(super) fn commit(trace: &mut TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<Commitments, ProvingCancelled> {
+    let (setup, trace_tree) = commit_trace(trace, boundary, options, context, progress)?;
+    return commit_constraints(trace, trace_tree, &setup, boundary, extension, options, progress);
+}
+
+/// Extends the execution trace and commits to it; this is the first part of `commit()`.
+fn commit_trace(trace: &mut TraceTable, boundary: &BoundaryConstraints, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<(Arc<ProverSetup>, MerkleTree), ProvingCancelled> {
+    // 1 ----- extend execution trace -------------------------------------------------------------
+    if let Err(message) = boundary.check_trace(trace) {
+        panic!("{}", message);
+    }
+    progress.log(ProverEvent::TraceBuilt {
+        registers   : trace.register_count(),
+        steps       : trace.unextended_length(),
+    });
+    progress.report(ProvingStage::TraceExtension, 0)?;
+
+    // get LDE domain, FFT plans, and extended constants for traces of this shape
+    let setup = context.get_setup(TraceShape {
+        trace_length    : trace.unextended_length(),
+        extension_factor: trace.extension_factor(),
+        ctx_depth       : trace.ctx_depth(),
+        loop_depth      : trace.loop_depth(),
+        stack_depth     : trace.stack_depth(),
+    });
 
     // extend the execution trace registers to LDE domain
-    trace.extend(&lde_twiddles);
-    debug!("Extended execution trace from {} to {} steps in {} ms",
-        trace.unextended_length(),
-        trace.domain_size(), 
-        now.elapsed().as_millis());
+    trace.extend(&setup.trace_plan, &setup.lde_plan, progress)?;
+    progress.log(ProverEvent::LdeDone { domain: trace.domain_size() });
 
     // 2 ----- build Merkle tree from the extended execution trace ------------------------------------
-    let now = Instant::now();
+    progress.report(ProvingStage::TraceCommitment, 0)?;
     let trace_tree = trace.build_merkle_tree(options.hash_fn());
-    debug!("Built trace Merkle tree in {} ms", 
-        now.elapsed().as_millis());
+    progress.log(ProverEvent::TraceCommitted { leaves: trace_tree.leaves().len() });
+
+    return Ok((setup, trace_tree));
+}
+
+/// Evaluates constraints over the extended execution `trace`, builds the composition polynomial
+/// and its FRI layers, and determines query positions; this is the second part of `commit()`.
+fn commit_constraints(trace: &TraceTable, trace_tree: MerkleTree, setup: &ProverSetup, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, progress: &ProgressHandle) -> Result<Commitments, ProvingCancelled> {
+    let lde_domain = &setup.lde_domain;
+    let lde_plan = &setup.lde_plan;
 
     // 3 ----- evaluate constraints ---------------------------------------------------------------
-    let now = Instant::now();
+    progress.report(ProvingStage::ConstraintEvaluation, 0)?;
     
     // initialize constraint evaluation table
-    let mut constraints = ConstraintTable::new(&trace, trace_tree.root(), inputs, outputs);
+    let mut constraints = ConstraintTable::new(&trace, &setup, trace_tree.root(), boundary, extension);
     
     // allocate space to hold current and next states for constraint evaluations
     let mut current = TraceState::new(trace.ctx_depth(), trace.loop_depth(), trace.stack_depth());
@@ -50,7 +216,12 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // to evaluate them over the domain extended to match max constraint degree - thus, we can
     // skip most trace states for the purposes of constraint evaluation.
     let stride = trace.extension_factor() / MAX_CONSTRAINT_DEGREE;
+    let report_interval = usize::max(trace.domain_size() / 100, stride);
     for i in (0..trace.domain_size()).step_by(stride) {
+        if i > 0 && i % report_interval == 0 {
+            progress.report(ProvingStage::ConstraintEvaluation, (i * 100 / trace.domain_size()) as u8)?;
+        }
+
         // TODO: this loop should be parallelized and also potentially optimized to avoid copying
         // next state from the trace table twice
 
@@ -63,32 +234,27 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
         constraints.evaluate(&current, &next, lde_domain[i], i / stride);
     }
 
-    debug!("Evaluated {} constraints over domain of {} elements in {} ms",
-        constraints.constraint_count(),
-        constraints.evaluation_domain_size(),
-        now.elapsed().as_millis());
+    progress.log(ProverEvent::ConstraintsEvaluated {
+        constraints : constraints.constraint_count(),
+        domain      : constraints.evaluation_domain_size(),
+    });
 
     // 4 ----- convert constraint evaluations into a polynomial -----------------------------------
-    let now = Instant::now();
-    let constraint_poly = constraints.combine_polys();
-    debug!("Converted constraint evaluations into a single polynomial of degree {} in {} ms",
-        constraint_poly.degree(),
-        now.elapsed().as_millis());
+    progress.report(ProvingStage::ConstraintCommitment, 0)?;
+    let constraint_poly = constraints.combine_polys(&setup.constraint_plan);
+    progress.log(ProverEvent::ConstraintsCombined { degree: constraint_poly.degree() });
 
     // 5 ----- build Merkle tree from constraint polynomial evaluations ---------------------------
-    let now = Instant::now();
-    
     // evaluate constraint polynomial over the evaluation domain
-    let constraint_evaluations = constraint_poly.eval(&lde_twiddles);
+    let constraint_evaluations = constraint_poly.eval(lde_plan);
 
     // put evaluations into a Merkle tree; 4 evaluations per leaf
     let constraint_evaluations = evaluations_to_leaves(constraint_evaluations);
     let constraint_tree = MerkleTree::new(constraint_evaluations, options.hash_fn());
-    debug!("Evaluated constraint polynomial and built constraint Merkle tree in {} ms",
-        now.elapsed().as_millis());
+    progress.log(ProverEvent::ConstraintsCommitted { leaves: constraint_tree.leaves().len() });
 
     // 6 ----- build and evaluate deep composition polynomial -------------------------------------
-    let now = Instant::now();
+    progress.report(ProvingStage::Composition, 0)?;
 
     // combine trace and constraint polynomials into the final deep composition polynomial
     let seed = constraint_tree.root();
@@ -97,24 +263,22 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
     // evaluate the composition polynomial over LDE domain
     let mut composed_evaluations = composition_poly;
     debug_assert!(composed_evaluations.capacity() == lde_domain.len(), "invalid composition polynomial capacity");
-    unsafe { composed_evaluations.set_len(composed_evaluations.capacity()); }
-    polynom::eval_fft_twiddles(&mut composed_evaluations, &lde_twiddles, true);
+    composed_evaluations.resize(lde_domain.len(), field::ZERO);
+    lde_plan.forward(&mut composed_evaluations);
 
-    debug!("Built composition polynomial and evaluated it over domain of {} elements in {} ms",
-        composed_evaluations.len(),
-        now.elapsed().as_millis());
+    progress.log(ProverEvent::CompositionBuilt { domain: composed_evaluations.len() });
 
     // 7 ----- compute FRI layers for the composition polynomial ----------------------------------
-    let now = Instant::now();
+    progress.report(ProvingStage::FriReduction, 0)?;
     let composition_degree = utils::get_composition_degree(trace.unextended_length());
     debug_assert!(composition_degree == polynom::infer_degree(&composed_evaluations));
-    let (fri_trees, fri_values) = fri::reduce(&composed_evaluations, &lde_domain, options);
-    debug!("Computed {} FRI layers from composition polynomial evaluations in {} ms",
-    fri_trees.len(),
-        now.elapsed().as_millis());
+    let (fri_trees, fri_values) = fri::reduce(&composed_evaluations, lde_domain, options, progress)?;
+    for (index, values) in fri_values.iter().enumerate() {
+        progress.log(ProverEvent::FriLayer { index, size: values.len() * 4 });
+    }
 
     // 8 ----- determine query positions -----------------------------------------------------------
-    let now = Instant::now();
+    progress.report(ProvingStage::QuerySelection, 0)?;
 
     // combine all FRI layer roots into a single vector
     let mut fri_roots: Vec<u8> = Vec::new();
@@ -131,51 +295,15 @@ pub fn prove(trace: &mut TraceTable, inputs: &[u128], outputs: &[u128], options:
 
     // generate pseudo-random query positions
     let positions = utils::compute_query_positions(&seed, lde_domain.len(), options);
-    debug!("Determined {} query positions from seed {} in {} ms",
-        positions.len(),
-        hex::encode(seed),
-        now.elapsed().as_millis());
-
-    // 9 ----- build proof object -----------------------------------------------------------------
-    let now = Instant::now();
+    progress.log(ProverEvent::QueriesSelected { count: positions.len() });
 
-    // generate FRI proof
-    let fri_proof = fri::build_proof(fri_trees, fri_values, &positions);
-
-    // built a list of trace evaluations at queried positions
-    let trace_evaluations = trace.get_register_values_at(&positions);
-
-    // build a list of constraint positions
-    let constraint_positions = utils::map_trace_to_constraint_positions(&positions);
-
-    // build the proof object
-    let proof = StarkProof::new(
-        trace_tree.root(),
-        trace_tree.prove_batch(&positions),
-        trace_evaluations,
-        constraint_tree.root(),
-        constraint_tree.prove_batch(&constraint_positions),
-        deep_values,
-        fri_proof,
-        pow_nonce,
-        trace.get_last_state().op_counter(),
-        trace.ctx_depth(),
-        trace.loop_depth(),
-        trace.stack_depth(),
-        &options);
-
-    debug!("Built proof object in {} ms", now.elapsed().as_millis());
-    return proof;
+    return Ok(Commitments {
+        trace_tree, constraint_tree, deep_values, fri_trees, fri_values, positions, pow_nonce
+    });
 }
 
 // HELPER FUNCTIONS
 // ================================================================================================
-fn twiddles_from_domain(domain: &[u128]) -> Vec<u128> {
-    let mut twiddles = domain[..(domain.len() / 2)].to_vec();
-    fft::permute(&mut twiddles);
-    return twiddles;
-}
-
 /// Re-interpret vector of 16-byte values as a vector of 32-byte arrays
 fn evaluations_to_leaves(evaluations: Vec<u128>) -> Vec<[u8; 32]> {
     assert!(evaluations.len() % 2 == 0, "number of values must be divisible by 2");
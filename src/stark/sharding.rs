@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{ Serialize, Deserialize };
+use crate::crypto::{ MerkleTree, BatchMerkleProof, HashFunction, ct_eq };
+use crate::utils::as_bytes;
+use super::{
+    ProofOptions, StarkProof, DeepValues, Assertion, BoundaryConstraints, AirExtension, ProverContext, ProgressHandle,
+    ProvingCancelled, fri, utils,
+    fri::{ FriProof, FriLayer },
+    proof::{ TraceInfo, ProofComponents, MAX_DOMAIN_DEPTH },
+    prover::{ commit, Commitments },
+    trace::TraceTable,
+};
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// The prover of a proof whose query openings are produced by multiple workers. The prover holds
+/// the extended execution trace and all commitments to it; the query positions of the proof are
+/// split into `QueryTask`'s, every task is opened independently via `open()`, and the resulting
+/// `ProofPart`'s are merged into a proof via `StarkProof::assemble()`.
+///
+/// The extended trace and the commitments are built only once, and are shared by all clones of
+/// the prover; cloning is cheap, and so, every worker thread can open its tasks using its own
+/// clone of the prover without repeating any of the commitment work.
+///
+/// The prover itself is not serializable: it holds the entire extended trace, which is far larger
+/// than the proof. Only tasks, parts, and the header are meant to cross process boundaries; a
+/// worker in another process recomputes the prover by executing the same program with the same
+/// inputs and options, which always leads to the same commitments.
+#[derive(Clone)]
+pub struct ShardedProver {
+    state       : Arc<CommittedState>,
+    header      : ProofHeader,
+}
+
+/// The extended execution trace together with commitments to it.
+struct CommittedState {
+    trace       : TraceTable,
+    commitments : Commitments,
+}
+
+/// Everything a proof contains except for query openings: commitment roots, DEEP values, the FRI
+/// remainder, public data, and the list of query positions which must be opened.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProofHeader {
+    trace_root      : [u8; 32],
+    trace_info      : TraceInfo,
+    constraint_root : [u8; 32],
+    deep_values     : DeepValues,
+    fri_roots       : Vec<[u8; 32]>,
+    rem_root        : [u8; 32],
+    rem_values      : Vec<u128>,
+    pow_nonce       : u64,
+    outputs         : Vec<u128>,
+    assertions      : Vec<Assertion>,
+    extension       : Option<String>,
+    options         : ProofOptions,
+    program_salt    : Option<[u8; 32]>,
+    positions       : Vec<usize>,
+}
+
+/// A subset of query positions of a proof to be opened by a single worker.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryTask {
+    shard       : usize,
+    positions   : Vec<usize>,
+}
+
+/// Openings of the trace, constraint, and FRI commitments at query positions of a single task;
+/// every opened value comes with its own Merkle path, so that parts can be checked and merged
+/// independently of each other.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProofPart {
+    shard               : usize,
+    positions           : Vec<usize>,
+    trace_rows          : Vec<Vec<u128>>,
+    trace_paths         : Vec<Vec<[u8; 32]>>,
+    constraint_positions: Vec<usize>,
+    constraint_paths    : Vec<Vec<[u8; 32]>>,
+    fri_layers          : Vec<FriLayerPart>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct FriLayerPart {
+    positions   : Vec<usize>,
+    values      : Vec<[u128; 4]>,
+    paths       : Vec<Vec<[u8; 32]>>,
+}
+
+// PROVER FUNCTIONS
+// ================================================================================================
+
+/// Commits to the execution `trace` in the same way as `prove()` function does, but instead of
+/// building a proof, returns a prover whose query openings can be distributed across workers.
+pub fn prove_sharded(mut trace: TraceTable, boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, options: &ProofOptions, context: &ProverContext, progress: &ProgressHandle) -> Result<ShardedProver, ProvingCancelled> {
+    let commitments = commit(&mut trace, boundary, extension, options, context, progress)?;
+
+    let last_values = &commitments.fri_values[commitments.fri_values.len() - 1];
+    let header = ProofHeader {
+        trace_root      : *commitments.trace_tree.root(),
+        trace_info      : TraceInfo::new(
+            trace.domain_size().trailing_zeros() as u8,
+            trace.get_last_state().op_counter(),
+            trace.ctx_depth(),
+            trace.loop_depth(),
+            trace.stack_depth()),
+        constraint_root : *commitments.constraint_tree.root(),
+        deep_values     : commitments.deep_values.clone(),
+        fri_roots       : commitments.fri_trees.iter().map(|tree| *tree.root()).collect(),
+        rem_root        : *commitments.fri_trees[commitments.fri_trees.len() - 1].root(),
+        rem_values      : fri::build_remainder(last_values),
+        pow_nonce       : commitments.pow_nonce,
+        outputs         : boundary.get_outputs().to_vec(),
+        assertions      : boundary.get_assertions().to_vec(),
+        extension       : extension.map(|e| e.id().to_string()),
+        options         : options.clone(),
+        program_salt    : None,
+        positions       : commitments.positions.clone(),
+    };
+
+    return Ok(ShardedProver { state: Arc::new(CommittedState { trace, commitments }), header });
+}
+
+// SHARDED PROVER IMPLEMENTATION
+// ================================================================================================
+impl ShardedProver {
+
+    /// Returns the header of the proof; the header must be passed to `StarkProof::assemble()`
+    /// together with the opened parts.
+    pub fn header(&self) -> &ProofHeader {
+        return &self.header;
+    }
+
+    /// Sets the salt of the program commitment which the assembled proof can be verified against;
+    /// see `execute_blinded()`.
+    pub fn set_program_salt(&mut self, salt: &[u8; 32]) {
+        self.header.program_salt = Some(*salt);
+    }
+
+    /// Splits query positions of the proof into `num_shards` tasks of roughly equal size.
+    pub fn tasks(&self, num_shards: usize) -> Vec<QueryTask> {
+        let positions = &self.header.positions;
+        assert!(num_shards > 0, "number of shards must be greater than zero");
+        assert!(num_shards <= positions.len(), "cannot split {} query positions into {} shards",
+            positions.len(), num_shards);
+
+        return (0..num_shards).map(|shard| {
+            let start = shard * positions.len() / num_shards;
+            let end = (shard + 1) * positions.len() / num_shards;
+            QueryTask { shard, positions: positions[start..end].to_vec() }
+        }).collect();
+    }
+
+    /// Opens all commitments at the query positions of the `task`. Panics if the task contains
+    /// positions which are not query positions of the proof.
+    pub fn open(&self, task: &QueryTask) -> ProofPart {
+        assert!(!task.positions.is_empty(), "task must contain at least one position");
+        for position in task.positions.iter() {
+            assert!(self.header.positions.contains(position), "position {} is not a query position", position);
+        }
+
+        let CommittedState { trace, commitments } = self.state.as_ref();
+        let Commitments { trace_tree, constraint_tree, fri_trees, fri_values, .. } = commitments;
+        let positions = &task.positions;
+
+        // open trace and constraint commitments
+        let trace_paths = positions.iter().map(|&p| trace_tree.prove(p)).collect();
+        let constraint_positions = utils::map_trace_to_constraint_positions(positions);
+        let constraint_paths = constraint_positions.iter().map(|&p| constraint_tree.prove(p)).collect();
+
+        // open all FRI layers except for the last one, which is included in the header; this
+        // mirrors fri::build_proof()
+        let mut layer_positions = positions.clone();
+        let mut domain_size = trace.domain_size();
+        let mut fri_layers = Vec::with_capacity(fri_trees.len() - 1);
+        for i in 0..(fri_trees.len() - 1) {
+            layer_positions = fri::get_augmented_positions(&layer_positions, domain_size);
+            fri_layers.push(FriLayerPart {
+                positions   : layer_positions.clone(),
+                values      : layer_positions.iter().map(|&p| fri_values[i][p]).collect(),
+                paths       : layer_positions.iter().map(|&p| fri_trees[i].prove(p)).collect(),
+            });
+            domain_size = domain_size / 4;
+        }
+
+        return ProofPart {
+            shard               : task.shard,
+            positions           : positions.clone(),
+            trace_rows          : trace.get_register_values_at(positions),
+            trace_paths         : trace_paths,
+            constraint_positions: constraint_positions,
+            constraint_paths    : constraint_paths,
+            fri_layers          : fri_layers,
+        };
+    }
+}
+
+// PROOF HEADER IMPLEMENTATION
+// ================================================================================================
+impl ProofHeader {
+
+    /// Returns the positions at which commitments must be opened.
+    pub fn positions(&self) -> &[usize] {
+        return &self.positions;
+    }
+}
+
+// QUERY TASK IMPLEMENTATION
+// ================================================================================================
+impl QueryTask {
+
+    /// Returns the index of this task among the tasks of the proof.
+    pub fn shard(&self) -> usize {
+        return self.shard;
+    }
+
+    /// Returns the query positions to be opened by this task.
+    pub fn positions(&self) -> &[usize] {
+        return &self.positions;
+    }
+}
+
+// PROOF PART IMPLEMENTATION
+// ================================================================================================
+impl ProofPart {
+
+    /// Returns the index of the task from which this part was produced.
+    pub fn shard(&self) -> usize {
+        return self.shard;
+    }
+
+    /// Returns the query positions opened by this part.
+    pub fn positions(&self) -> &[usize] {
+        return &self.positions;
+    }
+}
+
+// PROOF ASSEMBLY
+// ================================================================================================
+impl StarkProof {
+
+    /// Merges `parts` opened from the tasks of a `ShardedProver` into a proof; the result is the
+    /// same as the proof which `prove()` would generate for the same execution. Every opening is
+    /// checked against the commitments in the `header`, and every query position must be opened
+    /// by exactly one part; an error is returned otherwise.
+    pub fn assemble(header: &ProofHeader, parts: &[ProofPart]) -> Result<StarkProof, String> {
+        let hash = header.options.hash_fn();
+        if header.fri_roots.is_empty() {
+            return Err(String::from("header must contain at least one FRI root"));
+        }
+        if header.trace_info.domain_depth as usize > MAX_DOMAIN_DEPTH {
+            return Err(format!("header domain depth must be at most {}, but was {}",
+                MAX_DOMAIN_DEPTH, header.trace_info.domain_depth));
+        }
+        let num_layers = header.fri_roots.len() - 1;
+        let domain_size = usize::pow(2, header.trace_info.domain_depth as u32);
+        if let Some(position) = header.positions.iter().find(|&&p| p >= domain_size) {
+            return Err(format!("header query position {} is outside of the evaluation domain", position));
+        }
+
+        // make sure every query position is opened exactly once
+        let mut part_index = HashMap::new();
+        for (i, part) in parts.iter().enumerate() {
+            for &position in part.positions.iter() {
+                if !header.positions.contains(&position) {
+                    return Err(format!("part {} opens position {} which is not a query position", part.shard, position));
+                }
+                if part_index.insert(position, i).is_some() {
+                    return Err(format!("position {} is opened by more than one part", position));
+                }
+            }
+        }
+        if let Some(position) = header.positions.iter().find(|p| !part_index.contains_key(p)) {
+            return Err(format!("position {} is not opened by any part", position));
+        }
+
+        // check openings of every part against the commitments
+        let mut trace_openings = HashMap::new();
+        let mut constraint_openings = HashMap::new();
+        let mut fri_openings = vec![HashMap::new(); num_layers];
+        for part in parts.iter() {
+            if part.trace_rows.len() != part.positions.len() || part.trace_paths.len() != part.positions.len() {
+                return Err(format!("part {} must contain {} trace rows and paths", part.shard, part.positions.len()));
+            }
+            for ((&position, row), path) in part.positions.iter().zip(part.trace_rows.iter()).zip(part.trace_paths.iter()) {
+                if !check_path(&header.trace_root, position, path, as_bytes(row), hash) {
+                    return Err(format!("part {} has an invalid trace opening at position {}", part.shard, position));
+                }
+                trace_openings.insert(position, (row, path));
+            }
+
+            if part.constraint_positions != utils::map_trace_to_constraint_positions(&part.positions)
+                || part.constraint_paths.len() != part.constraint_positions.len()
+            {
+                return Err(format!("part {} does not open constraints at its positions", part.shard));
+            }
+            for (&position, path) in part.constraint_positions.iter().zip(part.constraint_paths.iter()) {
+                if !is_valid_path_length(path) || !MerkleTree::verify(&header.constraint_root, position, path, hash) {
+                    return Err(format!("part {} has an invalid constraint opening at position {}", part.shard, position));
+                }
+                constraint_openings.insert(position, path);
+            }
+
+            if part.fri_layers.len() != num_layers {
+                return Err(format!("part {} must open {} FRI layers", part.shard, num_layers));
+            }
+            let mut layer_positions = part.positions.clone();
+            let mut layer_domain_size = domain_size;
+            for (i, layer) in part.fri_layers.iter().enumerate() {
+                layer_positions = fri::get_augmented_positions(&layer_positions, layer_domain_size);
+                if layer.positions != layer_positions
+                    || layer.values.len() != layer_positions.len() || layer.paths.len() != layer_positions.len()
+                {
+                    return Err(format!("part {} does not open FRI layer {} at its positions", part.shard, i));
+                }
+                for ((&position, values), path) in layer.positions.iter().zip(layer.values.iter()).zip(layer.paths.iter()) {
+                    if !check_path(&header.fri_roots[i], position, path, as_bytes(values), hash) {
+                        return Err(format!("part {} has an invalid opening of FRI layer {} at position {}", part.shard, i, position));
+                    }
+                    fri_openings[i].insert(position, (values, path));
+                }
+                layer_domain_size = layer_domain_size / 4;
+            }
+        }
+
+        // merge the openings in the order in which a single prover would have made them
+        let positions = &header.positions;
+        let trace_paths: Vec<_> = positions.iter().map(|p| trace_openings[p].1.clone()).collect();
+        let trace_rows = positions.iter().map(|p| trace_openings[p].0.clone()).collect();
+
+        let constraint_positions = utils::map_trace_to_constraint_positions(positions);
+        let constraint_paths: Vec<_> = constraint_positions.iter().map(|p| constraint_openings[p].clone()).collect();
+
+        let mut layers = Vec::with_capacity(num_layers);
+        let mut layer_positions = positions.clone();
+        let mut layer_domain_size = domain_size;
+        for i in 0..num_layers {
+            layer_positions = fri::get_augmented_positions(&layer_positions, layer_domain_size);
+            let paths: Vec<_> = layer_positions.iter().map(|p| fri_openings[i][p].1.clone()).collect();
+            let proof = BatchMerkleProof::from_paths(&layer_positions, &paths);
+            layers.push(FriLayer {
+                root    : header.fri_roots[i],
+                values  : layer_positions.iter().map(|p| *fri_openings[i][p].0).collect(),
+                nodes   : proof.nodes,
+                depth   : proof.depth,
+            });
+            layer_domain_size = layer_domain_size / 4;
+        }
+        let degree_proof = FriProof { layers, rem_root: header.rem_root, rem_values: header.rem_values.clone() };
+
+        let components = ProofComponents {
+            trace_root          : header.trace_root,
+            trace_proof         : BatchMerkleProof::from_paths(positions, &trace_paths),
+            trace_evaluations   : trace_rows,
+            constraint_root     : header.constraint_root,
+            constraint_proof    : BatchMerkleProof::from_paths(&constraint_positions, &constraint_paths),
+            deep_values         : header.deep_values.clone(),
+            degree_proof        : degree_proof,
+            pow_nonce           : header.pow_nonce,
+        };
+        let mut proof = StarkProof::new(
+            components,
+            header.trace_info.clone(),
+            &header.outputs,
+            &header.assertions,
+            header.extension.as_deref(),
+            &header.options);
+        if let Some(salt) = &header.program_salt {
+            proof.set_program_salt(salt);
+        }
+        return Ok(proof);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Checks that the first node of the Merkle `path` is a hash of `leaf_bytes`, and that the path
+/// leads from the `position` to the `root`.
+fn check_path(root: &[u8; 32], position: usize, path: &[[u8; 32]], leaf_bytes: &[u8], hash: HashFunction) -> bool {
+    if !is_valid_path_length(path) { return false; }
+    let mut leaf = [0u8; 32];
+    hash(leaf_bytes, &mut leaf);
+    return ct_eq(&leaf, &path[0]) && MerkleTree::verify(root, position, path, hash);
+}
+
+/// Checks that the `path` has a leaf and a sibling, and is short enough for all positions in the
+/// tree to fit into `usize`.
+fn is_valid_path_length(path: &[[u8; 32]]) -> bool {
+    return path.len() >= 2 && path.len() <= MAX_DOMAIN_DEPTH + 1;
+}
+
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, Execution, ProgramInputs, ProofOptions, StarkProof };
+
+    #[test]
+    fn assemble_malformed_header() {
+        let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+        let (_, prover) = crate::execute_sharded(Execution::new(&program, &ProgramInputs::none(), 1, &ProofOptions::default())).unwrap();
+        let parts: Vec<_> = prover.tasks(2).iter().map(|task| prover.open(task)).collect();
+
+        // malformed headers are rejected instead of causing a panic
+        let mut header = prover.header().clone();
+        header.fri_roots.clear();
+        let result = StarkProof::assemble(&header, &parts).map(|_| ());
+        assert_eq!(Err(String::from("header must contain at least one FRI root")), result);
+
+        let mut header = prover.header().clone();
+        header.trace_info.domain_depth = 64;
+        let result = StarkProof::assemble(&header, &parts).map(|_| ());
+        assert_eq!(Err(String::from("header domain depth must be at most 40, but was 64")), result);
+
+        let mut header = prover.header().clone();
+        header.positions[0] = usize::MAX;
+        let result = StarkProof::assemble(&header, &parts).map(|_| ());
+        assert_eq!(Err(format!("header query position {} is outside of the evaluation domain", usize::MAX)), result);
+
+        // paths which are too long to describe a position in the domain are rejected
+        let mut malformed = parts.clone();
+        malformed[0].trace_paths[0] = vec![[0u8; 32]; 100];
+        let result = StarkProof::assemble(prover.header(), &malformed).map(|_| ());
+        assert!(result.unwrap_err().contains("invalid trace opening"));
+    }
+}
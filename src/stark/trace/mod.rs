@@ -1,5 +1,5 @@
 mod trace_state;
 mod trace_table;
 
-pub use trace_state::TraceState;
+pub use trace_state::{ TraceState, OpFlags };
 pub use trace_table::TraceTable;
\ No newline at end of file
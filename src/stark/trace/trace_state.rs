@@ -8,6 +8,7 @@ use crate::{
     NUM_CF_OPS, NUM_LD_OPS, NUM_HD_OPS,
     NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
     CF_OP_BITS_RANGE, LD_OP_BITS_RANGE, HD_OP_BITS_RANGE,
+    NUM_DEPTH_BITS, NUM_DEPTH_REGISTERS,
 };
 
 // CONSTANTS
@@ -17,6 +18,12 @@ const NUM_STATIC_DECODER_REGISTERS: usize = 1 + SPONGE_WIDTH + NUM_OP_BITS; // 1
 
 // TYPES AND INTERFACES
 // ================================================================================================
+
+/// State of the execution trace at a single step. Registers of the trace are laid out as follows:
+/// operation counter, operation sponge, control flow / low-degree / high-degree operation bits,
+/// context stack, loop stack, stack depth with its underflow / overflow bits, and user stack.
+/// Widths of the context stack, loop stack, and user stack sections depend on the context depth,
+/// loop depth, and stack depth of a given execution.
 #[derive(PartialEq)]
 pub struct TraceState {
     op_counter  : u128,
@@ -26,18 +33,29 @@ pub struct TraceState {
     hd_op_bits  : [u128; NUM_HD_OP_BITS],
     ctx_stack   : Vec<u128>,
     loop_stack  : Vec<u128>,
+    depth       : u128,
+    underflow_bits: [u128; NUM_DEPTH_BITS],
+    overflow_bits : [u128; NUM_DEPTH_BITS],
     user_stack  : Vec<u128>,
 
     ctx_depth   : usize,
     loop_depth  : usize,
     stack_depth : usize,
 
+    op_flags    : OpFlags,
+}
+
+/// Operation flags computed from op bits of a trace state. Every flag is a product of op bits
+/// (or their binary inverses), and so, for valid op bits, only the flag of the executed operation
+/// is set to 1. Flags are computed once whenever op bits of a trace state change, so that
+/// constraint evaluators can use them without recomputing the products.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OpFlags {
     cf_op_flags : [u128; NUM_CF_OPS],
     ld_op_flags : [u128; NUM_LD_OPS],
     hd_op_flags : [u128; NUM_HD_OPS],
     begin_flag  : u128,
     noop_flag   : u128,
-    op_flags_set: bool,
 }
 
 // TRACE STATE IMPLEMENTATION
@@ -47,6 +65,8 @@ impl TraceState {
     // CONSTRUCTORS
     // --------------------------------------------------------------------------------------------
 
+    /// Returns a trace state with all registers set to zeros for an execution trace with the
+    /// specified context depth, loop depth, and stack depth.
     pub fn new(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> TraceState {
         
         return TraceState {
@@ -57,19 +77,19 @@ impl TraceState {
             hd_op_bits  : [0; NUM_HD_OP_BITS],
             ctx_stack   : vec![0; cmp::max(ctx_depth, MIN_CONTEXT_DEPTH)],
             loop_stack  : vec![0; cmp::max(loop_depth, MIN_LOOP_DEPTH)],
+            depth       : 0,
+            underflow_bits: [0; NUM_DEPTH_BITS],
+            overflow_bits : [0; NUM_DEPTH_BITS],
             user_stack  : vec![0; cmp::max(stack_depth, MIN_STACK_DEPTH)],
             ctx_depth   : ctx_depth,
             loop_depth  : loop_depth,
             stack_depth : stack_depth,
-            cf_op_flags : [0; NUM_CF_OPS],
-            ld_op_flags : [0; NUM_LD_OPS],
-            hd_op_flags : [0; NUM_HD_OPS],
-            begin_flag  : 0,
-            noop_flag   : 0,
-            op_flags_set: false,
+            op_flags    : OpFlags::new(&[0; NUM_CF_OP_BITS], &[0; NUM_LD_OP_BITS], &[0; NUM_HD_OP_BITS]),
         };
     }
 
+    /// Returns a trace state built from a raw row of an execution trace with the specified
+    /// context depth, loop depth, and stack depth.
     pub fn from_vec(ctx_depth: usize, loop_depth: usize, stack_depth: usize, state: &Vec<u128>) -> TraceState {
 
         let op_counter = state[OP_COUNTER_IDX];
@@ -94,41 +114,70 @@ impl TraceState {
         let loop_stack_end = ctx_stack_end + loop_depth;
         loop_stack[..loop_depth].copy_from_slice(&state[ctx_stack_end..loop_stack_end]);
 
+        let depth = state[loop_stack_end];
+        let underflow_start = loop_stack_end + 1;
+
+        let mut underflow_bits = [0; NUM_DEPTH_BITS];
+        underflow_bits.copy_from_slice(&state[underflow_start..(underflow_start + NUM_DEPTH_BITS)]);
+
+        let mut overflow_bits = [0; NUM_DEPTH_BITS];
+        let overflow_start = underflow_start + NUM_DEPTH_BITS;
+        overflow_bits.copy_from_slice(&state[overflow_start..(overflow_start + NUM_DEPTH_BITS)]);
+
         let mut user_stack = vec![0; cmp::max(stack_depth, MIN_STACK_DEPTH)];
-        user_stack[..stack_depth].copy_from_slice(&state[loop_stack_end..]);
+        user_stack[..stack_depth].copy_from_slice(&state[(loop_stack_end + NUM_DEPTH_REGISTERS)..]);
 
         return TraceState {
             op_counter, sponge,
             cf_op_bits, ld_op_bits, hd_op_bits,
-            ctx_stack, loop_stack, user_stack,
+            ctx_stack, loop_stack,
+            depth, underflow_bits, overflow_bits,
+            user_stack,
             ctx_depth, loop_depth, stack_depth,
-            cf_op_flags : [0; NUM_CF_OPS],
-            ld_op_flags : [0; NUM_LD_OPS],
-            hd_op_flags : [0; NUM_HD_OPS],
-            begin_flag  : 0,
-            noop_flag   : 0,
-            op_flags_set: false,
+            op_flags: OpFlags::new(&cf_op_bits, &ld_op_bits, &hd_op_bits),
         };
     }
 
     // STATIC FUNCTIONS
     // --------------------------------------------------------------------------------------------
+    /// Returns the number of decoder registers in an execution trace with the specified
+    /// context depth and loop depth; user stack registers start right after these registers.
     pub fn compute_decoder_width(ctx_depth: usize, loop_depth: usize) -> usize {
         return NUM_STATIC_DECODER_REGISTERS + ctx_depth + loop_depth;
     }
 
+    /// Returns the number of user stack registers in an execution trace with the specified
+    /// number of registers, context depth, and loop depth.
+    pub fn compute_stack_depth(trace_width: usize, ctx_depth: usize, loop_depth: usize) -> usize {
+        return trace_width - Self::compute_decoder_width(ctx_depth, loop_depth) - NUM_DEPTH_REGISTERS;
+    }
+
     // PUBLIC ACCESSORS
     // --------------------------------------------------------------------------------------------
+    /// Returns the total number of registers in the trace state.
     pub fn width(&self) -> usize {
-        return HD_OP_BITS_RANGE.end + self.ctx_depth + self.loop_depth + self.stack_depth;
+        return HD_OP_BITS_RANGE.end + self.ctx_depth + self.loop_depth
+            + NUM_DEPTH_REGISTERS + self.stack_depth;
+    }
+
+    /// Returns the number of context stack registers in the trace state.
+    pub fn ctx_depth(&self) -> usize {
+        return self.ctx_depth;
     }
 
+    /// Returns the number of loop stack registers in the trace state.
+    pub fn loop_depth(&self) -> usize {
+        return self.loop_depth;
+    }
+
+    /// Returns the number of user stack registers in the trace state.
     pub fn stack_depth(&self) -> usize {
         return self.stack_depth;
     }
 
     // OPERATION COUNTER
     // --------------------------------------------------------------------------------------------
+    /// Returns the number of operations executed before this state was reached.
     pub fn op_counter(&self) -> u128 {
         return self.op_counter;
     }
@@ -140,28 +189,35 @@ impl TraceState {
 
     // SPONGE
     // --------------------------------------------------------------------------------------------
+    /// Returns the state of the sponge which accumulates hash of the executed program.
     pub fn sponge(&self) -> &[u128] {
         return &self.sponge;
     }
 
+    /// Returns the part of the sponge state which holds the program hash at the end of execution.
     pub fn program_hash(&self) -> &[u128] {
         return &self.sponge[..PROGRAM_DIGEST_SIZE];
     }
 
     // OP BITS
     // --------------------------------------------------------------------------------------------
+    /// Returns binary decomposition of the control flow operation executed at this step.
     pub fn cf_op_bits(&self) -> &[u128] {
         return &self.cf_op_bits;
     }
 
+    /// Returns the low-degree bits of the user operation executed at this step.
     pub fn ld_op_bits(&self) -> &[u128] {
         return &self.ld_op_bits;
     }
 
+    /// Returns the high-degree bits of the user operation executed at this step.
     pub fn hd_op_bits(&self) -> &[u128] {
         return &self.hd_op_bits;
     }
 
+    /// Returns the op code of the user operation executed at this step; values of op codes are
+    /// defined by the `OpCode` enum.
     pub fn op_code(&self) -> u128 {
         let mut result = self.ld_op_bits[0];
         result = field::add(result, field::mul(self.ld_op_bits[1], 2));
@@ -173,68 +229,87 @@ impl TraceState {
         return result;
     }
 
-    pub fn set_op_bits(&mut self, bits: [u128; NUM_OP_BITS]) {
+    #[cfg(test)]
+    pub(crate) fn set_op_bits(&mut self, bits: [u128; NUM_OP_BITS]) {
         self.cf_op_bits.copy_from_slice(&bits[..3]);
         self.ld_op_bits.copy_from_slice(&bits[3..8]);
         self.hd_op_bits.copy_from_slice(&bits[8..]);
+        self.op_flags = OpFlags::new(&self.cf_op_bits, &self.ld_op_bits, &self.hd_op_bits);
     }
 
     // OP FLAGS
     // --------------------------------------------------------------------------------------------
+    /// Returns flags of all operations computed from op bits of this state.
+    pub fn op_flags(&self) -> &OpFlags {
+        return &self.op_flags;
+    }
+
+    /// Returns flags for all control flow operations; only the flag of the operation executed
+    /// at this step is set to 1.
     pub fn cf_op_flags(&self) -> [u128; NUM_CF_OPS] {
-        if !self.op_flags_set {
-            unsafe {
-                let mutable_self = &mut *(self as *const _ as *mut TraceState);
-                mutable_self.set_op_flags();
-            }
-        }
-        return self.cf_op_flags;
+        return self.op_flags.cf_op_flags;
     }
 
+    /// Returns flags for all low-degree user operations; only the flag of the operation executed
+    /// at this step is set to 1.
     pub fn ld_op_flags(&self) -> [u128; NUM_LD_OPS] {
-        if !self.op_flags_set {
-            unsafe {
-                let mutable_self = &mut *(self as *const _ as *mut TraceState);
-                mutable_self.set_op_flags();
-            }
-        }
-        return self.ld_op_flags;
+        return self.op_flags.ld_op_flags;
     }
 
+    /// Returns flags for all high-degree user operations; only the flag of the operation
+    /// executed at this step is set to 1.
     pub fn hd_op_flags(&self) -> [u128; NUM_HD_OPS] {
-        if !self.op_flags_set {
-            unsafe {
-                let mutable_self = &mut *(self as *const _ as *mut TraceState);
-                mutable_self.set_op_flags();
-            }
-        }
-        return self.hd_op_flags;
+        return self.op_flags.hd_op_flags;
     }
 
+    /// Returns 1 if BEGIN operation was executed at this step, and 0 otherwise.
     pub fn begin_flag(&self) -> u128 {
-        return self.begin_flag;
+        return self.op_flags.begin_flag;
     }
 
+    /// Returns 1 if NOOP operation was executed at this step, and 0 otherwise.
     pub fn noop_flag(&self) -> u128 {
-        return self.noop_flag;
+        return self.op_flags.noop_flag;
     }
 
     // STACKS
     // --------------------------------------------------------------------------------------------
+    /// Returns the state of the context stack; the stack holds hashes of parent blocks.
     pub fn ctx_stack(&self) -> &[u128] {
         return &self.ctx_stack;
     }
 
+    /// Returns the state of the loop stack; the stack holds images of active loop bodies.
     pub fn loop_stack(&self) -> &[u128] {
         return &self.loop_stack;
     }
 
+    /// Returns the number of items on the user stack at this step. Unlike `stack_depth()`, which
+    /// is the number of user stack registers in the trace, this value can change with every step.
+    pub fn depth(&self) -> u128 {
+        return self.depth;
+    }
+
+    /// Returns binary decomposition of the difference between the depth of the stack and
+    /// the number of items required by the operation executed at this step.
+    pub fn underflow_bits(&self) -> &[u128] {
+        return &self.underflow_bits;
+    }
+
+    /// Returns binary decomposition of the difference between the number of user stack registers
+    /// and the depth of the stack at this step.
+    pub fn overflow_bits(&self) -> &[u128] {
+        return &self.overflow_bits;
+    }
+
+    /// Returns the state of the user stack with the top of the stack in the first position.
     pub fn user_stack(&self) -> &[u128] {
         return &self.user_stack;
     }
 
     // RAW STATE
     // --------------------------------------------------------------------------------------------
+    /// Returns the trace state as a raw row of the execution trace.
     pub fn to_vec(&self) -> Vec<u128> {
         let mut result = Vec::with_capacity(self.width());
         result.push(self.op_counter);
@@ -244,10 +319,14 @@ impl TraceState {
         result.extend_from_slice(&self.hd_op_bits);
         result.extend_from_slice(&self.ctx_stack[..self.ctx_depth]);
         result.extend_from_slice(&self.loop_stack[..self.loop_depth]);
+        result.push(self.depth);
+        result.extend_from_slice(&self.underflow_bits);
+        result.extend_from_slice(&self.overflow_bits);
         result.extend_from_slice(&self.user_stack[..self.stack_depth]);
         return result;
     }
 
+    /// Updates the trace state with values from the specified `step` of the execution trace.
     pub fn update_from_trace(&mut self, trace: &Vec<Vec<u128>>, step: usize) {
 
         self.op_counter = trace[OP_COUNTER_IDX][step];
@@ -268,91 +347,131 @@ impl TraceState {
             self.loop_stack[i] = trace[j][step];
         }
 
-        let user_stack_end = loop_stack_end + self.stack_depth;
-        for (i, j) in (loop_stack_end..user_stack_end).enumerate() {
+        self.depth = trace[loop_stack_end][step];
+        let underflow_start = loop_stack_end + 1;
+        for i in 0..NUM_DEPTH_BITS {
+            self.underflow_bits[i] = trace[underflow_start + i][step];
+            self.overflow_bits[i] = trace[underflow_start + NUM_DEPTH_BITS + i][step];
+        }
+
+        let user_stack_start = loop_stack_end + NUM_DEPTH_REGISTERS;
+        let user_stack_end = user_stack_start + self.stack_depth;
+        for (i, j) in (user_stack_start..user_stack_end).enumerate() {
             self.user_stack[i] = trace[j][step];
         }
-        
-        self.op_flags_set = false;
+
+        self.op_flags = OpFlags::new(&self.cf_op_bits, &self.ld_op_bits, &self.hd_op_bits);
     }
+}
 
-    // HELPER METHODS
-    // --------------------------------------------------------------------------------------------
-    fn set_op_flags(&mut self) {
+// OP FLAGS IMPLEMENTATION
+// ================================================================================================
+impl OpFlags {
+
+    /// Computes flags of all operations from control flow, low-degree, and high-degree op bits.
+    pub fn new(cf_op_bits: &[u128; NUM_CF_OP_BITS], ld_op_bits: &[u128; NUM_LD_OP_BITS], hd_op_bits: &[u128; NUM_HD_OP_BITS]) -> OpFlags {
+
+        let mut cf_op_flags = [0; NUM_CF_OPS];
+        let mut ld_op_flags = [0; NUM_LD_OPS];
+        let mut hd_op_flags = [0; NUM_HD_OPS];
 
         // set control flow flags
-        let not_0 = binary_not(self.cf_op_bits[0]);
-        let not_1 = binary_not(self.cf_op_bits[1]);
-        self.cf_op_flags[0] = field::mul(not_0, not_1);
-        self.cf_op_flags[1] = field::mul(self.cf_op_bits[0], not_1);
-        self.cf_op_flags[2] = field::mul(not_0, self.cf_op_bits[1]);
-        self.cf_op_flags[3] = field::mul(self.cf_op_bits[0], self.cf_op_bits[1]);
-        self.cf_op_flags.copy_within(0..4, 4);
-
-        let not_2 = binary_not(self.cf_op_bits[2]);
-        for i in 0..4 { self.cf_op_flags[i] = field::mul(self.cf_op_flags[i], not_2); }
-        for i in 4..8 { self.cf_op_flags[i] = field::mul(self.cf_op_flags[i], self.cf_op_bits[2]); }
+        let not_0 = binary_not(cf_op_bits[0]);
+        let not_1 = binary_not(cf_op_bits[1]);
+        cf_op_flags[0] = field::mul(not_0, not_1);
+        cf_op_flags[1] = field::mul(cf_op_bits[0], not_1);
+        cf_op_flags[2] = field::mul(not_0, cf_op_bits[1]);
+        cf_op_flags[3] = field::mul(cf_op_bits[0], cf_op_bits[1]);
+        cf_op_flags.copy_within(0..4, 4);
+
+        let not_2 = binary_not(cf_op_bits[2]);
+        for i in 0..4 { cf_op_flags[i] = field::mul(cf_op_flags[i], not_2); }
+        for i in 4..8 { cf_op_flags[i] = field::mul(cf_op_flags[i], cf_op_bits[2]); }
 
         // set low-degree operation flags
-        let not_0 = binary_not(self.ld_op_bits[0]);
-        let not_1 = binary_not(self.ld_op_bits[1]);
-        self.ld_op_flags[0] = field::mul(not_0, not_1);
-        self.ld_op_flags[1] = field::mul(self.ld_op_bits[0], not_1);
-        self.ld_op_flags[2] = field::mul(not_0, self.cf_op_bits[1]);
-        self.ld_op_flags[3] = field::mul(self.ld_op_bits[0], self.ld_op_bits[1]);
-        self.ld_op_flags.copy_within(0..4, 4);
-
-        let not_2 = binary_not(self.ld_op_bits[2]);
-        for i in 0..4 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_2); }
-        for i in 4..8 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[2]); }
-        self.ld_op_flags.copy_within(0..8, 8);
-
-        let not_3 = binary_not(self.ld_op_bits[3]);
-        for i in 0..8  { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_3); }
-        for i in 8..16 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[3]); }
-        self.ld_op_flags.copy_within(0..16, 16);
-
-        let not_4 = binary_not(self.ld_op_bits[4]);
-        for i in 0..16  { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], not_4); }
-        for i in 16..32 { self.ld_op_flags[i] = field::mul(self.ld_op_flags[i], self.ld_op_bits[4]); }
+        let not_0 = binary_not(ld_op_bits[0]);
+        let not_1 = binary_not(ld_op_bits[1]);
+        ld_op_flags[0] = field::mul(not_0, not_1);
+        ld_op_flags[1] = field::mul(ld_op_bits[0], not_1);
+        ld_op_flags[2] = field::mul(not_0, ld_op_bits[1]);
+        ld_op_flags[3] = field::mul(ld_op_bits[0], ld_op_bits[1]);
+        ld_op_flags.copy_within(0..4, 4);
+
+        let not_2 = binary_not(ld_op_bits[2]);
+        for i in 0..4 { ld_op_flags[i] = field::mul(ld_op_flags[i], not_2); }
+        for i in 4..8 { ld_op_flags[i] = field::mul(ld_op_flags[i], ld_op_bits[2]); }
+        ld_op_flags.copy_within(0..8, 8);
+
+        let not_3 = binary_not(ld_op_bits[3]);
+        for i in 0..8  { ld_op_flags[i] = field::mul(ld_op_flags[i], not_3); }
+        for i in 8..16 { ld_op_flags[i] = field::mul(ld_op_flags[i], ld_op_bits[3]); }
+        ld_op_flags.copy_within(0..16, 16);
+
+        let not_4 = binary_not(ld_op_bits[4]);
+        for i in 0..16  { ld_op_flags[i] = field::mul(ld_op_flags[i], not_4); }
+        for i in 16..32 { ld_op_flags[i] = field::mul(ld_op_flags[i], ld_op_bits[4]); }
 
         // set high-degree operation flags
-        let not_0 = binary_not(self.hd_op_bits[0]);
-        let not_1 = binary_not(self.hd_op_bits[1]);
-        self.hd_op_flags[0] = field::mul(not_0, not_1);
-        self.hd_op_flags[1] = field::mul(self.hd_op_bits[0], not_1);
-        self.hd_op_flags[2] = field::mul(not_0, self.hd_op_bits[1]);
-        self.hd_op_flags[3] = field::mul(self.hd_op_bits[0], self.hd_op_bits[1]);
+        let not_0 = binary_not(hd_op_bits[0]);
+        let not_1 = binary_not(hd_op_bits[1]);
+        hd_op_flags[0] = field::mul(not_0, not_1);
+        hd_op_flags[1] = field::mul(hd_op_bits[0], not_1);
+        hd_op_flags[2] = field::mul(not_0, hd_op_bits[1]);
+        hd_op_flags[3] = field::mul(hd_op_bits[0], hd_op_bits[1]);
 
         // compute flag for BEGIN operation which is just 0000000; the below is equivalent
         // to multiplying binary inverses of all op bits together.
-        self.begin_flag = field::mul(
-            self.ld_op_flags[OpCode::Begin.ld_index()], 
-            self.hd_op_flags[OpCode::Begin.hd_index()]);
+        let begin_flag = field::mul(
+            ld_op_flags[OpCode::Begin.ld_index()],
+            hd_op_flags[OpCode::Begin.hd_index()]);
 
         // compute flag for NOOP operation which is just 1111111; the below is equivalent to
         // multiplying all op bits together.
-        self.noop_flag = field::mul(
-            self.ld_op_flags[OpCode::Noop.ld_index()], 
-            self.hd_op_flags[OpCode::Noop.hd_index()]);
+        let noop_flag = field::mul(
+            ld_op_flags[OpCode::Noop.ld_index()],
+            hd_op_flags[OpCode::Noop.hd_index()]);
 
         // we need to make special adjustments for PUSH and ASSERT op flags so that they
         // don't coincide with BEGIN operation; we do this by multiplying each flag by a
         // single op_bit from another op bank; this increases degree of each flag by 1
         debug_assert!(OpCode::Push.hd_index() == 0, "PUSH index is not 0!");
-        self.hd_op_flags[0] = field::mul(self.hd_op_flags[0], self.ld_op_bits[0]);
+        hd_op_flags[0] = field::mul(hd_op_flags[0], ld_op_bits[0]);
 
         debug_assert!(OpCode::Assert.ld_index() == 0, "ASSERT index is not 0!");
-        self.ld_op_flags[0] = field::mul(self.ld_op_flags[0], self.hd_op_bits[0]);
+        ld_op_flags[0] = field::mul(ld_op_flags[0], hd_op_bits[0]);
 
-        // mark flags as set
-        self.op_flags_set = true;
+        return OpFlags { cf_op_flags, ld_op_flags, hd_op_flags, begin_flag, noop_flag };
+    }
+
+    /// Returns flags for all control flow operations.
+    pub fn cf_op_flags(&self) -> &[u128; NUM_CF_OPS] {
+        return &self.cf_op_flags;
+    }
+
+    /// Returns flags for all low-degree user operations.
+    pub fn ld_op_flags(&self) -> &[u128; NUM_LD_OPS] {
+        return &self.ld_op_flags;
+    }
+
+    /// Returns flags for all high-degree user operations.
+    pub fn hd_op_flags(&self) -> &[u128; NUM_HD_OPS] {
+        return &self.hd_op_flags;
+    }
+
+    /// Returns the flag of BEGIN operation.
+    pub fn begin_flag(&self) -> u128 {
+        return self.begin_flag;
+    }
+
+    /// Returns the flag of NOOP operation.
+    pub fn noop_flag(&self) -> u128 {
+        return self.noop_flag;
     }
 }
 
 impl fmt::Debug for TraceState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{:>4}] {:>32X?} {:?} {:?} {:?} {:>32X?} {:>32X?} {:?}",
+        write!(f, "[{:>4}] {:>32X?} {:?} {:?} {:?} {:>32X?} {:>32X?} {} {:?}",
             self.op_counter,
             self.sponge, 
             self.cf_op_bits,
@@ -360,6 +479,7 @@ impl fmt::Debug for TraceState {
             self.hd_op_bits,
             self.ctx_stack,
             self.loop_stack,
+            self.depth,
             self.user_stack
         )
     }
@@ -367,7 +487,7 @@ impl fmt::Debug for TraceState {
 
 impl fmt::Display for TraceState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[{:>4}] {:>16X?} {:?} {:?} {:?} {:>16X?} {:>16X?} {:?}",
+        write!(f, "[{:>4}] {:>16X?} {:?} {:?} {:?} {:>16X?} {:>16X?} {} {:?}",
             self.op_counter,
             self.sponge.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
             self.cf_op_bits,
@@ -375,6 +495,7 @@ impl fmt::Display for TraceState {
             self.hd_op_bits,
             self.ctx_stack.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
             self.loop_stack.iter().map(|x| x >> 64).collect::<Vec<u128>>(),
+            self.depth,
             &self.user_stack[..self.stack_depth]
         )
     }
@@ -392,14 +513,19 @@ fn binary_not(v: u128) -> u128 {
 #[cfg(test)]
 mod tests {
 
-    use super::{ TraceState };
+    use crate::{
+        OpCode, processor::opcodes::FlowOps,
+        NUM_CF_OPS, NUM_LD_OPS, NUM_HD_OPS, NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
+    };
+    use super::{ TraceState, OpFlags, NUM_OP_BITS };
 
     #[test]
     fn from_vec() {
 
         // empty context and loop stacks
         let state = TraceState::from_vec(0, 0, 2, &vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,
+            2,  1, 0, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0,  15, 16
         ]);
 
         assert_eq!(101, state.op_counter());
@@ -409,16 +535,21 @@ mod tests {
         assert_eq!([13, 14], state.hd_op_bits());
         assert_eq!([0], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
+        assert_eq!(2, state.depth());
+        assert_eq!([1, 0, 0, 0, 0, 0], state.underflow_bits());
+        assert_eq!([0, 0, 0, 0, 0, 0], state.overflow_bits());
         assert_eq!([15, 16, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(17, state.width());
+        assert_eq!(30, state.width());
         assert_eq!(2, state.stack_depth());
         assert_eq!(vec![
-            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16
+            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14,
+            2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 15, 16
         ], state.to_vec());
 
         // 1 item on context stack, empty loop stack
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15,  16, 17
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15,
+            1,  0, 0, 0, 0, 0, 0,  1, 0, 0, 0, 0, 0,  16, 17
         ]);
 
         assert_eq!(101, state.op_counter());
@@ -428,16 +559,21 @@ mod tests {
         assert_eq!([13, 14], state.hd_op_bits());
         assert_eq!([15], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
+        assert_eq!(1, state.depth());
+        assert_eq!([0, 0, 0, 0, 0, 0], state.underflow_bits());
+        assert_eq!([1, 0, 0, 0, 0, 0], state.overflow_bits());
         assert_eq!([16, 17, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(18, state.width());
+        assert_eq!(31, state.width());
         assert_eq!(2, state.stack_depth());
         assert_eq!(vec![
-            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17
+            101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+            1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 16, 17
         ], state.to_vec());
 
         // non-empty loop stack
         let state = TraceState::from_vec(2, 1, 9, &vec![
             101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16,  17,
+            6,  0, 1, 1, 0, 0, 0,  1, 1, 0, 0, 0, 0,
             18, 19, 20, 21, 22, 23, 24, 25, 26,
         ]);
 
@@ -448,11 +584,15 @@ mod tests {
         assert_eq!([13, 14], state.hd_op_bits());
         assert_eq!([15, 16], state.ctx_stack());
         assert_eq!([17], state.loop_stack());
+        assert_eq!(6, state.depth());
+        assert_eq!([0, 1, 1, 0, 0, 0], state.underflow_bits());
+        assert_eq!([1, 1, 0, 0, 0, 0], state.overflow_bits());
         assert_eq!([18, 19, 20, 21, 22, 23, 24, 25, 26], state.user_stack());
-        assert_eq!(27, state.width());
+        assert_eq!(40, state.width());
         assert_eq!(9, state.stack_depth());
         assert_eq!(vec![
             101, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17,
+            6, 0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0,
             18, 19, 20, 21, 22, 23, 24, 25, 26,
         ], state.to_vec());
     }
@@ -460,7 +600,8 @@ mod tests {
     #[test]
     fn update_from_trace() {
         let data = vec![
-            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16,  17,  18, 19, 20
+            101,  1, 2, 3, 4,  5, 6, 7,  8, 9, 10, 11, 12,  13, 14,  15, 16,  17,
+            3,  1, 1, 0, 0, 0, 0,  0, 0, 0, 0, 0, 0,  18, 19, 20
         ];
         let mut trace = Vec::with_capacity(data.len());
         for i in 0..data.len() {
@@ -478,8 +619,9 @@ mod tests {
         assert_eq!([0, 0], state.hd_op_bits());
         assert_eq!([0, 0], state.ctx_stack());
         assert_eq!([0], state.loop_stack());
+        assert_eq!(0, state.depth());
         assert_eq!([0, 0, 0, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(21, state.width());
+        assert_eq!(34, state.width());
         assert_eq!(3, state.stack_depth());
 
         // second row
@@ -492,8 +634,11 @@ mod tests {
         assert_eq!([13, 14], state.hd_op_bits());
         assert_eq!([15, 16], state.ctx_stack());
         assert_eq!([17], state.loop_stack());
+        assert_eq!(3, state.depth());
+        assert_eq!([1, 1, 0, 0, 0, 0], state.underflow_bits());
+        assert_eq!([0, 0, 0, 0, 0, 0], state.overflow_bits());
         assert_eq!([18, 19, 20, 0, 0, 0, 0, 0], state.user_stack());
-        assert_eq!(21, state.width());
+        assert_eq!(34, state.width());
         assert_eq!(3, state.stack_depth());
     }
 
@@ -502,7 +647,7 @@ mod tests {
 
         // all zeros
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  0, 0,  15, 16, 17
+            101,  1, 2, 3, 4,  0, 0, 0,  0, 0, 0, 0, 0,  0, 0,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
 
         assert_eq!([1, 0, 0, 0, 0, 0, 0, 0], state.cf_op_flags());
@@ -516,7 +661,7 @@ mod tests {
 
         // all ones
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
 
         assert_eq!([0, 0, 0, 0, 0, 0, 0, 1], state.cf_op_flags());
@@ -530,7 +675,7 @@ mod tests {
 
         // mixed 1
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 0, 0,  1, 0, 0, 0, 0,  1, 0,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 0, 0,  1, 0, 0, 0, 0,  1, 0,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
 
         assert_eq!([0, 1, 0, 0, 0, 0, 0, 0], state.cf_op_flags());
@@ -544,7 +689,7 @@ mod tests {
 
         // mixed 2
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101, 1, 2, 3, 4, 1, 1, 0, 1, 1, 0, 0, 0, 0, 1, 15, 16, 17
+            101, 1, 2, 3, 4, 1, 1, 0, 1, 1, 0, 0, 0, 0, 1, 15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16, 17
         ]);
 
         assert_eq!([0, 0, 0, 1, 0, 0, 0, 0], state.cf_op_flags());
@@ -558,23 +703,122 @@ mod tests {
     #[test]
     fn op_code() {
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 1, 1,  0, 0, 0, 0, 0,  0, 0,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 1, 1,  0, 0, 0, 0, 0,  0, 0,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
         assert_eq!(0, state.op_code());
 
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 1,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
         assert_eq!(127, state.op_code());
 
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 0,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 1, 1,  1, 1, 1, 1, 1,  1, 0,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
         assert_eq!(63, state.op_code());
 
         let state = TraceState::from_vec(1, 0, 2, &vec![
-            101,  1, 2, 3, 4,  1, 1, 1,  1, 0, 0, 0, 0,  1, 1,  15, 16, 17
+            101,  1, 2, 3, 4,  1, 1, 1,  1, 0, 0, 0, 0,  1, 1,  15,  0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,  16, 17
         ]);
         assert_eq!(97, state.op_code());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn op_flags_of_each_operation() {
+        let ld_ops = [
+            OpCode::Assert, OpCode::AssertEq, OpCode::AssertBin, OpCode::Eq, OpCode::Drop, OpCode::Drop4,
+            OpCode::Drop8, OpCode::Choose, OpCode::Choose2, OpCode::CSwap2, OpCode::Add, OpCode::Mul, OpCode::And,
+            OpCode::Or, OpCode::Inv, OpCode::Neg, OpCode::Not, OpCode::Read, OpCode::Read2,
+            OpCode::Dup, OpCode::Dup2, OpCode::Dup4, OpCode::Dup8, OpCode::Pad2, OpCode::Swap, OpCode::Swap2,
+            OpCode::Swap4, OpCode::Swap8, OpCode::Roll4, OpCode::Roll8, OpCode::BinAcc,
+        ];
+        let hd_ops = [OpCode::Push, OpCode::Cmp, OpCode::RescR];
+
+        // only the flag of the executed low-degree operation is set
+        for &op in ld_ops.iter() {
+            let flags = build_op_flags(FlowOps::Hacc, op);
+            assert_eq!(one_hot::<NUM_LD_OPS>(op.ld_index()), *flags.ld_op_flags(), "{}", op);
+            assert_eq!(one_hot::<NUM_HD_OPS>(OpCode::Noop.hd_index()), *flags.hd_op_flags(), "{}", op);
+            assert_eq!((0, 0), (flags.begin_flag(), flags.noop_flag()), "{}", op);
+        }
+
+        // only the flag of the executed high-degree operation is set
+        for &op in hd_ops.iter() {
+            let flags = build_op_flags(FlowOps::Hacc, op);
+            assert_eq!(one_hot::<NUM_LD_OPS>(OpCode::Noop.ld_index()), *flags.ld_op_flags(), "{}", op);
+            assert_eq!(one_hot::<NUM_HD_OPS>(op.hd_index()), *flags.hd_op_flags(), "{}", op);
+            assert_eq!((0, 0), (flags.begin_flag(), flags.noop_flag()), "{}", op);
+        }
+
+        // BEGIN and NOOP do not coincide with PUSH or ASSERT
+        let flags = build_op_flags(FlowOps::Hacc, OpCode::Begin);
+        assert_eq!((1, 0), (flags.begin_flag(), flags.noop_flag()));
+        assert_eq!(0, flags.ld_op_flags()[OpCode::Assert.ld_index()]);
+        assert_eq!(0, flags.hd_op_flags()[OpCode::Push.hd_index()]);
+
+        let flags = build_op_flags(FlowOps::Hacc, OpCode::Noop);
+        assert_eq!((0, 1), (flags.begin_flag(), flags.noop_flag()));
+
+        // only the flag of the executed control flow operation is set
+        let cf_ops = [
+            FlowOps::Hacc, FlowOps::Begin, FlowOps::Tend, FlowOps::Fend,
+            FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Void,
+        ];
+        for &op in cf_ops.iter() {
+            let flags = build_op_flags(op, OpCode::Noop);
+            assert_eq!(one_hot::<NUM_CF_OPS>(op.op_index()), *flags.cf_op_flags(), "{}", op);
+        }
+
+        // flags cached in a trace state are updated together with op bits
+        let mut state = TraceState::new(1, 0, 8);
+        let flags = build_op_flags(FlowOps::Void, OpCode::Swap);
+        let mut op_bits = [0; NUM_OP_BITS];
+        op_bits[..NUM_CF_OP_BITS].copy_from_slice(&[1, 1, 1]);
+        op_bits[NUM_CF_OP_BITS..].copy_from_slice(&to_bits::<7>(OpCode::Swap as usize));
+        state.set_op_bits(op_bits);
+        assert_eq!(&flags, state.op_flags());
+        assert_eq!(*flags.ld_op_flags(), state.ld_op_flags());
+    }
+
+    #[test]
+    fn ld_op_flags_ignore_cf_op_bits() {
+        // low-degree flags must be selected by low-degree op bits only; when the flag of EQ was
+        // selected by cf_op_bits[1], it was never set within spans (where the control flow
+        // operation is HACC), and was set for ASSERT during TEND and similar operations
+        let cf_ops = [
+            FlowOps::Hacc, FlowOps::Begin, FlowOps::Tend, FlowOps::Fend,
+            FlowOps::Loop, FlowOps::Wrap, FlowOps::Break, FlowOps::Void,
+        ];
+        for &cf_op in cf_ops.iter() {
+            let flags = build_op_flags(cf_op, OpCode::Eq);
+            assert_eq!(1, flags.ld_op_flags()[OpCode::Eq.ld_index()], "{}", cf_op);
+
+            let flags = build_op_flags(cf_op, OpCode::Assert);
+            assert_eq!(0, flags.ld_op_flags()[OpCode::Eq.ld_index()], "{}", cf_op);
+        }
+    }
+
+    // HELPER FUNCTIONS
+    // --------------------------------------------------------------------------------------------
+    fn build_op_flags(flow_op: FlowOps, user_op: OpCode) -> OpFlags {
+        let cf_bits = to_bits::<NUM_CF_OP_BITS>(flow_op as usize);
+        let user_bits = to_bits::<7>(user_op as usize);
+        let mut ld_bits = [0; NUM_LD_OP_BITS];
+        ld_bits.copy_from_slice(&user_bits[..NUM_LD_OP_BITS]);
+        let mut hd_bits = [0; NUM_HD_OP_BITS];
+        hd_bits.copy_from_slice(&user_bits[NUM_LD_OP_BITS..]);
+        return OpFlags::new(&cf_bits, &ld_bits, &hd_bits);
+    }
+
+    fn to_bits<const N: usize>(value: usize) -> [u128; N] {
+        let mut result = [0; N];
+        for i in 0..N { result[i] = ((value >> i) & 1) as u128; }
+        return result;
+    }
+
+    fn one_hot<const N: usize>(index: usize) -> [u128; N] {
+        let mut result = [0; N];
+        result[index] = 1;
+        return result;
+    }
+}
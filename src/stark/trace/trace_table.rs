@@ -1,7 +1,7 @@
-use crate::math::{ field, fft, polynom, parallel };
+use crate::math::{ field, fft::FftPlan, polynom, parallel };
 use crate::crypto::{ MerkleTree, HashFunction };
-use crate::stark::{ CompositionCoefficients, utils };
-use crate::utils::{ uninit_vector, filled_vector, as_bytes };
+use crate::stark::{ CompositionCoefficients, ProgressHandle, ProvingStage, ProvingCancelled, utils };
+use crate::utils::{ zeroed_vector, filled_vector, as_bytes };
 use super::{ TraceState };
 
 // TYPES AND INTERFACES
@@ -27,22 +27,18 @@ impl TraceTable {
         assert!(extension_factor >= crate::MIN_EXTENSION_FACTOR,
             "extension factor must be at least {}", crate::MIN_EXTENSION_FACTOR);
 
-        // validate context depth
-        assert!(ctx_depth <= crate::MAX_CONTEXT_DEPTH,
-            "context depth cannot be greater than {}", crate::MAX_CONTEXT_DEPTH);
-
-        // validate loop depth
-        assert!(loop_depth <= crate::MAX_LOOP_DEPTH,
-            "loop depth cannot be greater than {}", crate::MAX_LOOP_DEPTH);
-
         // compute stack depth
         let decoder_width = TraceState::compute_decoder_width(ctx_depth, loop_depth);
-        assert!(registers.len() > decoder_width, "user stack must consist of at least one register");
-        let stack_depth = registers.len() - decoder_width;
+        assert!(registers.len() > decoder_width + crate::NUM_DEPTH_REGISTERS,
+            "user stack must consist of at least one register");
+        let stack_depth = TraceState::compute_stack_depth(registers.len(), ctx_depth, loop_depth);
+
+        // validate trace dimensions
+        if let Err(msg) = Self::check_dimensions(ctx_depth, loop_depth, stack_depth) {
+            panic!("{}", msg);
+        }
 
         // validate register traces
-        assert!(registers.len() < crate::MAX_REGISTER_COUNT,
-            "execution trace cannot have more than {} registers", crate::MAX_REGISTER_COUNT);
         let trace_length = registers[0].len();
         assert!(trace_length.is_power_of_two(), "execution trace length must be a power of 2");
         for register in registers.iter() {
@@ -57,11 +53,73 @@ impl TraceTable {
         };
     }
 
-    /// Returns state of the trace table at the specified `step`.
+    /// Checks whether an execution trace with the specified context depth, loop depth, and stack
+    /// depth can be proven, and returns the number of registers such a trace would have. This is
+    /// cheap to call, and thus, can be used to reject executions before any expensive work starts.
+    pub fn check_dimensions(ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> Result<usize, String>
+    {
+        if ctx_depth > crate::MAX_CONTEXT_DEPTH {
+            return Err(format!("context depth cannot be greater than {}, but was {}",
+                crate::MAX_CONTEXT_DEPTH, ctx_depth));
+        }
+
+        if loop_depth > crate::MAX_LOOP_DEPTH {
+            return Err(format!("loop depth cannot be greater than {}, but was {}",
+                crate::MAX_LOOP_DEPTH, loop_depth));
+        }
+
+        // add up register counts of all trace components, and determine which of the components
+        // (if any) pushed the total over the limit
+        let components = [
+            ("static decoder registers", TraceState::compute_decoder_width(0, 0)),
+            ("context stack", ctx_depth),
+            ("loop stack", loop_depth),
+            ("stack depth registers", crate::NUM_DEPTH_REGISTERS),
+            ("user stack", stack_depth),
+        ];
+
+        let mut register_count = 0;
+        let mut culprit = None;
+        for &(name, width) in components.iter() {
+            register_count += width;
+            if culprit.is_none() && register_count >= crate::MAX_REGISTER_COUNT {
+                culprit = Some(name);
+            }
+        }
+
+        if let Some(culprit) = culprit {
+            return Err(format!("execution trace must have fewer than {} registers, but requires {} \
+                (context depth: {}, loop depth: {}, stack depth: {}); the limit was exceeded by the {}",
+                crate::MAX_REGISTER_COUNT, register_count, ctx_depth, loop_depth, stack_depth, culprit));
+        }
+
+        return Ok(register_count);
+    }
+
+    /// Returns state of the trace table at the specified `step`; panics if the step is outside
+    /// of the trace table.
     pub fn get_state(&self, step: usize) -> TraceState {
+        return self.try_get_state(step).unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    /// Returns state of the trace table at the specified `step`, or an error if the step is
+    /// outside of the trace table. Once the trace table has been extended, steps of the extended
+    /// trace are valid.
+    pub fn try_get_state(&self, step: usize) -> Result<TraceState, String> {
+        if step >= self.length() {
+            return Err(format!("step {} is outside of the trace table of {} steps", step, self.length()));
+        }
         let mut result = TraceState::new(self.ctx_depth, self.loop_depth, self.stack_depth);
         self.fill_state(&mut result, step);
-        return result;
+        return Ok(result);
+    }
+
+    /// Returns an iterator over states of the execution trace in the order of execution. Once
+    /// the trace table has been extended, only the states of the original execution trace are
+    /// visited (i.e. every `extension_factor`-th state of the extended trace).
+    pub fn states(&self) -> impl Iterator<Item = TraceState> + '_ {
+        let stride = if self.is_extended() { self.extension_factor } else { 1 };
+        return (0..self.trace_length).map(move |step| self.get_state(step * stride));
     }
 
     /// Returns state of the trace table at the last step.
@@ -115,6 +173,18 @@ impl TraceTable {
         return self.stack_depth;
     }
 
+    /// Returns values of the register at the specified `index`, or `None` if there is no such
+    /// register. Once the trace table has been extended, the values are taken from the extended
+    /// trace.
+    pub fn register(&self, index: usize) -> Option<&[u128]> {
+        return self.registers.get(index).map(|register| register.as_slice());
+    }
+
+    /// Returns an iterator over values of all registers in the order of register indexes.
+    pub fn registers(&self) -> impl Iterator<Item = &[u128]> {
+        return self.registers.iter().map(|register| register.as_slice());
+    }
+
     /// Returns polynomial of the register at the specified `index`; can be called only
     /// after the trace table has been extended.
     #[cfg(test)]
@@ -138,34 +208,42 @@ impl TraceTable {
         return self.registers[0].len() > self.trace_length;
     }
 
+    /// Returns the number of states in the trace table; this is the length of the extended
+    /// trace once the trace table has been extended.
+    fn length(&self) -> usize {
+        return self.registers[0].len();
+    }
+
     /// Extends all registers of the trace table by the `extension_factor` specified during
     /// trace table construction. A trace table can be extended only once.
-    pub fn extend(&mut self, twiddles: &[u128]) {
+    ///
+    /// `trace_plan` and `lde_plan` must be FFT plans for the trace domain and the LDE domain.
+    pub fn extend(&mut self, trace_plan: &FftPlan, lde_plan: &FftPlan, progress: &ProgressHandle) -> Result<(), ProvingCancelled> {
         assert!(!self.is_extended(), "trace table has already been extended");
-        assert!(twiddles.len() * 2 == self.domain_size(), "invalid number of twiddles");
+        assert!(trace_plan.size() == self.unextended_length(), "invalid FFT plan size");
+        assert!(lde_plan.size() == self.domain_size(), "invalid FFT plan size");
 
-        // build inverse twiddles needed for FFT interpolation
-        let root = field::get_root_of_unity(self.unextended_length());
-        let inv_twiddles = fft::get_inv_twiddles(root, self.unextended_length());
-        
         // move register traces into polys
         std::mem::swap(&mut self.registers, &mut self.polys);
 
-        // extend all registers
+        // extend all registers; cancellation is checked after every register
         let domain_size = self.domain_size();
-        for poly in self.polys.iter_mut() {
+        let register_count = self.polys.len();
+        for (i, poly) in self.polys.iter_mut().enumerate() {
+            progress.report(ProvingStage::TraceExtension, (i * 100 / register_count) as u8)?;
 
             // interpolate register trace into a polynomial
-            polynom::interpolate_fft_twiddles(poly, &inv_twiddles, true);
+            trace_plan.inverse(poly);
             
             // allocate space to hold extended evaluations and copy the polynomial into it
             let mut register = vec![field::ZERO; domain_size];
             register[..poly.len()].copy_from_slice(&poly);
             
             // evaluate the polynomial over extended domain
-            polynom::eval_fft_twiddles(&mut register, &twiddles, true);
+            lde_plan.forward(&mut register);
             self.registers.push(register);
         }
+        return Ok(());
     }
 
     /// Puts the trace table into a Merkle tree such that each state of the table becomes
@@ -173,7 +251,7 @@ impl TraceTable {
     /// form a single leaf value.
     pub fn build_merkle_tree(&self, hash: HashFunction) -> MerkleTree {
         let mut trace_state = vec![field::ZERO; self.register_count()];
-        let mut hashed_states = uninit_vector::<[u8; 32]>(self.domain_size());
+        let mut hashed_states = zeroed_vector::<[u8; 32]>(self.domain_size());
         // TODO: this loop should be parallelized
         for i in 0..self.domain_size() {
             for j in 0..trace_state.len() {
@@ -269,11 +347,12 @@ mod tests {
 
     use std::collections::HashMap;
     use crate::{
-        math::{ field, polynom, parallel, fft },
-        crypto::hash::blake3,
+        math::{ field, polynom, parallel, fft::FftPlan },
+        crypto::hash::blake3, ProgressHandle,
         programs::{ Program, ProgramInputs, blocks::{ ProgramBlock, Span, Group } },
         processor::{ execute, OpCode },
-        stark::{ TraceTable, CompositionCoefficients, utils::get_composition_degree }
+        stark::{ TraceTable, CompositionCoefficients, utils::get_composition_degree },
+        utils::as_bytes,
     };
     
     const EXT_FACTOR: usize = 32;
@@ -281,8 +360,7 @@ mod tests {
     #[test]
     fn eval_polys_at() {
         let mut trace = build_trace_table();
-        let lde_root = field::get_root_of_unity(trace.domain_size());
-        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()));
+        trace.extend(&FftPlan::new(trace.unextended_length()), &FftPlan::new(trace.domain_size()), &ProgressHandle::new()).unwrap();
 
         let g = field::get_root_of_unity(trace.unextended_length());
 
@@ -295,12 +373,76 @@ mod tests {
         assert_eq!(v2, s2.to_vec());
     }
 
+    #[test]
+    fn build_merkle_tree() {
+        let mut trace = build_trace_table();
+        trace.extend(&FftPlan::new(trace.unextended_length()), &FftPlan::new(trace.domain_size()), &ProgressHandle::new()).unwrap();
+        let tree = trace.build_merkle_tree(blake3);
+
+        // every leaf commits to an entire row of the extended trace, and so, a single Merkle
+        // path authenticates values of all registers at a queried position
+        assert_eq!(trace.domain_size(), tree.leaves().len());
+        let positions = [0, 7, trace.domain_size() - 1];
+        let rows = trace.get_register_values_at(&positions);
+        for (row, &position) in rows.iter().zip(positions.iter()) {
+            let mut expected_leaf = [0u8; 32];
+            blake3(as_bytes(row), &mut expected_leaf);
+            assert_eq!(expected_leaf, tree.leaves()[position]);
+        }
+
+        // a batch proof contains one hashed row per queried position
+        let proof = tree.prove_batch(&positions);
+        assert_eq!(positions.len(), proof.values.len());
+    }
+
+    #[test]
+    fn accessors() {
+        let mut trace = build_trace_table();
+        let length = trace.unextended_length();
+        let states: Vec<Vec<u128>> = trace.states().map(|state| state.to_vec()).collect();
+        assert_eq!(length, states.len());
+        assert_eq!(trace.get_last_state().to_vec(), states[length - 1]);
+
+        // registers and states outside of the trace table are reported as errors
+        assert_eq!(trace.register_count(), trace.registers().count());
+        assert_eq!(Some(trace.registers().last().unwrap()), trace.register(trace.register_count() - 1));
+        assert_eq!(None, trace.register(trace.register_count()));
+        assert_eq!(states[1], trace.try_get_state(1).unwrap().to_vec());
+        let err_msg = format!("step {} is outside of the trace table of {} steps", length, length);
+        assert_eq!(Some(err_msg), trace.try_get_state(length).err());
+
+        // once the table is extended, states of the execution trace are still visited in order
+        trace.extend(&FftPlan::new(trace.unextended_length()), &FftPlan::new(trace.domain_size()), &ProgressHandle::new()).unwrap();
+        let extended_states: Vec<Vec<u128>> = trace.states().map(|state| state.to_vec()).collect();
+        assert_eq!(states, extended_states);
+        assert_eq!(trace.domain_size(), trace.register(0).unwrap().len());
+        assert!(trace.try_get_state(trace.domain_size() - 1).is_ok());
+        assert!(trace.try_get_state(trace.domain_size()).is_err());
+    }
+
+    #[test]
+    fn check_dimensions() {
+        // 15 decoder registers + 1 context register + 1 loop register + 13 stack depth registers
+        // + 8 user stack registers
+        assert_eq!(Ok(38), TraceTable::check_dimensions(1, 1, 8));
+
+        let err_msg = format!("context depth cannot be greater than 16, but was 17");
+        assert_eq!(Err(err_msg), TraceTable::check_dimensions(17, 1, 8));
+
+        let err_msg = format!("loop depth cannot be greater than 8, but was 9");
+        assert_eq!(Err(err_msg), TraceTable::check_dimensions(1, 9, 8));
+
+        assert_eq!(Ok(127), TraceTable::check_dimensions(16, 8, 75));
+        let err_msg = format!("execution trace must have fewer than 128 registers, but requires 128 \
+            (context depth: 16, loop depth: 8, stack depth: 76); the limit was exceeded by the user stack");
+        assert_eq!(Err(err_msg), TraceTable::check_dimensions(16, 8, 76));
+    }
+
     #[test]
     fn get_composition_poly() {
 
         let mut trace = build_trace_table();
-        let lde_root = field::get_root_of_unity(trace.domain_size());
-        trace.extend(&fft::get_twiddles(lde_root, trace.domain_size()));
+        trace.extend(&FftPlan::new(trace.unextended_length()), &FftPlan::new(trace.domain_size()), &ProgressHandle::new()).unwrap();
 
         // compute trace composition polynomial
         let t_tree = trace.build_merkle_tree(blake3);
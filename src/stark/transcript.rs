@@ -0,0 +1,83 @@
+use serde::{ Serialize, Deserialize };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A record of the checks performed while verifying a proof. Every check is recorded together
+/// with its outcome and the values it was performed against (e.g. Merkle roots, query counts,
+/// the DEEP point); verification stops at the first failed check, and so, a failed check, if
+/// any, is always the last entry of the transcript.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    entries : Vec<TranscriptEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub check   : String,
+    pub passed  : bool,
+    pub details : String,
+}
+
+// TRANSCRIPT IMPLEMENTATION
+// ================================================================================================
+impl Transcript {
+
+    pub fn new() -> Transcript {
+        return Transcript { entries: Vec::new() };
+    }
+
+    /// Appends a check with the specified outcome to the transcript and returns the outcome.
+    pub fn record(&mut self, check: &str, passed: bool, details: String) -> bool {
+        self.entries.push(TranscriptEntry { check: String::from(check), passed, details });
+        return passed;
+    }
+
+    /// Returns all checks recorded in the transcript in the order in which they were performed.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        return &self.entries;
+    }
+
+    /// Returns true if the transcript is not empty and all recorded checks passed.
+    pub fn passed(&self) -> bool {
+        return !self.entries.is_empty() && self.entries.iter().all(|e| e.passed);
+    }
+
+    /// Returns the transcript serialized into a JSON document.
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).expect("failed to serialize transcript into JSON");
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Records a check into the `transcript` if one is supplied, and returns the outcome of the
+/// check; `details` are formatted only if they are recorded.
+pub fn record_check<F: FnOnce() -> String>(transcript: &mut Option<&mut Transcript>, check: &str, passed: bool, details: F) -> bool {
+    if let Some(transcript) = transcript {
+        transcript.record(check, passed, details());
+    }
+    return passed;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use super::{ Transcript, record_check };
+
+    #[test]
+    fn record_check_without_transcript() {
+        // details are not formatted when no transcript is supplied
+        let passed = record_check(&mut None, "check", true, || panic!("details must not be formatted"));
+        assert!(passed);
+
+        let mut transcript = Transcript::new();
+        let passed = record_check(&mut Some(&mut transcript), "check", false, || String::from("details"));
+        assert!(!passed);
+        assert_eq!(1, transcript.entries().len());
+        assert_eq!("details", transcript.entries()[0].details);
+    }
+}
@@ -2,12 +2,12 @@ use std::{ ops::Range };
 use crate::{
     math::field,
     utils::RangeSlider,
-    MAX_REGISTER_COUNT, MAX_PUBLIC_INPUTS,
+    MAX_REGISTER_COUNT, MAX_PUBLIC_INPUTS, MAX_ASSERTIONS, MAX_EXTENSION_CONSTRAINTS,
     SPONGE_WIDTH,
     MAX_CONTEXT_DEPTH, MAX_LOOP_DEPTH, MAX_STACK_DEPTH,
     MIN_CONTEXT_DEPTH, MIN_LOOP_DEPTH, MIN_STACK_DEPTH,
     NUM_CF_OP_BITS, NUM_LD_OP_BITS, NUM_HD_OP_BITS,
-    stark::constraints::{ NUM_STATIC_DECODER_CONSTRAINTS, NUM_AUX_STACK_CONSTRAINTS },
+    stark::constraints::{ NUM_STATIC_DECODER_CONSTRAINTS, NUM_AUX_STACK_CONSTRAINTS, NUM_DEPTH_CONSTRAINTS },
 };
 
 // CONSTANTS
@@ -20,6 +20,7 @@ const NUM_BOUNDARY_CONSTRAINTS: usize =
     + NUM_OP_BITS
     + MAX_CONTEXT_DEPTH
     + MAX_LOOP_DEPTH
+    + 1 // for stack depth
     + MAX_USER_STACK_IO_CONSTRAINTS;
 
 const NUM_TRANSITION_CONSTRAINTS: usize =
@@ -28,9 +29,11 @@ const NUM_TRANSITION_CONSTRAINTS: usize =
     + MAX_CONTEXT_DEPTH
     + MAX_LOOP_DEPTH
     + MAX_STACK_DEPTH
-    + NUM_AUX_STACK_CONSTRAINTS;
+    + NUM_AUX_STACK_CONSTRAINTS
+    + NUM_DEPTH_CONSTRAINTS;
 
-const NUM_CONSTRAINTS: usize = NUM_TRANSITION_CONSTRAINTS + 2 * NUM_BOUNDARY_CONSTRAINTS;
+const NUM_CONSTRAINTS: usize = NUM_TRANSITION_CONSTRAINTS + 2 * NUM_BOUNDARY_CONSTRAINTS
+    + MAX_ASSERTIONS + MAX_EXTENSION_CONSTRAINTS;
 
 // TYPES AND INTERFACES
 // ================================================================================================
@@ -38,6 +41,7 @@ pub struct ConstraintCoefficients {
     pub i_boundary  : BoundaryCoefficients,
     pub f_boundary  : BoundaryCoefficients,
     pub transition  : Vec<u128>,
+    pub assertions  : [u128; MAX_ASSERTIONS * 2],
 }
 
 pub struct BoundaryCoefficients {
@@ -46,6 +50,7 @@ pub struct BoundaryCoefficients {
     pub op_bits     : [u128; NUM_OP_BITS * 2],
     pub ctx_stack   : [u128; MAX_CONTEXT_DEPTH * 2],
     pub loop_stack  : [u128; MAX_LOOP_DEPTH * 2],
+    pub depth       : [u128; 2],
     pub user_stack  : [u128; MAX_USER_STACK_IO_CONSTRAINTS * 2],
 }
 
@@ -60,7 +65,10 @@ pub struct CompositionCoefficients {
 // IMPLEMENTATIONS
 // ================================================================================================
 impl ConstraintCoefficients {
-    pub fn new(seed: [u8; 32], ctx_depth: usize, loop_depth: usize, stack_depth: usize) -> ConstraintCoefficients {
+    /// Generates coefficients for all constraints from the `seed`; coefficients for the
+    /// `extension_constraints` constraints of an AIR extension are appended to the coefficients
+    /// for transition constraints.
+    pub fn new(seed: [u8; 32], ctx_depth: usize, loop_depth: usize, stack_depth: usize, extension_constraints: usize) -> ConstraintCoefficients {
 
         // generate a pseudo-random list of coefficients
         let coefficients = field::prng_vector(seed, 2 * NUM_CONSTRAINTS);
@@ -70,9 +78,19 @@ impl ConstraintCoefficients {
         let (f_boundary, i) = build_boundary_coefficients(&coefficients[i..]);
 
         // copy coefficients for transition constraints
-        let transition = build_transition_coefficients(&coefficients[i..], ctx_depth, loop_depth, stack_depth);
+        let mut transition = build_transition_coefficients(&coefficients[i..], ctx_depth, loop_depth, stack_depth);
 
-        return ConstraintCoefficients { i_boundary, f_boundary, transition };
+        // copy coefficients for user assertions
+        let i = i + NUM_TRANSITION_CONSTRAINTS * 2;
+        let mut assertions = [field::ZERO; MAX_ASSERTIONS * 2];
+        assertions.copy_from_slice(&coefficients[i..(i + MAX_ASSERTIONS * 2)]);
+
+        // copy coefficients for AIR extension constraints
+        assert!(extension_constraints <= MAX_EXTENSION_CONSTRAINTS, "too many AIR extension constraints");
+        let i = i + MAX_ASSERTIONS * 2;
+        transition.extend_from_slice(&coefficients[i..(i + extension_constraints * 2)]);
+
+        return ConstraintCoefficients { i_boundary, f_boundary, transition, assertions };
     }
 }
 
@@ -113,6 +131,7 @@ fn build_boundary_coefficients(coefficients: &[u128]) -> (BoundaryCoefficients,
         op_bits     : [0; NUM_OP_BITS * 2],
         ctx_stack   : [0; MAX_CONTEXT_DEPTH * 2],
         loop_stack  : [0; MAX_LOOP_DEPTH * 2],
+        depth       : [0; 2],
         user_stack  : [0; MAX_USER_STACK_IO_CONSTRAINTS * 2],
     };
 
@@ -131,6 +150,9 @@ fn build_boundary_coefficients(coefficients: &[u128]) -> (BoundaryCoefficients,
     range = range.slide(MAX_LOOP_DEPTH * 2);
     result.loop_stack.copy_from_slice(&coefficients[range.clone()]);
 
+    range = range.slide(2);
+    result.depth.copy_from_slice(&coefficients[range.clone()]);
+
     range = range.slide(MAX_USER_STACK_IO_CONSTRAINTS * 2);
     result.user_stack.copy_from_slice(&coefficients[range.clone()]);
 
@@ -148,7 +170,8 @@ fn build_transition_coefficients(coefficients: &[u128], ctx_depth: usize, loop_d
         + ctx_depth
         + loop_depth
         + stack_depth
-        + NUM_AUX_STACK_CONSTRAINTS;
+        + NUM_AUX_STACK_CONSTRAINTS
+        + NUM_DEPTH_CONSTRAINTS;
 
     // we need 2 coefficients per constraint
     let mut result = vec![0; num_constraints * 2];
@@ -159,7 +182,8 @@ fn build_transition_coefficients(coefficients: &[u128], ctx_depth: usize, loop_d
     // 2. context stack constraints - the number depends on the actual context depth
     // 3. loop stack constraints - the number depends on the actual loop depth
     // 4. aux stack constraints
-    // 5. user stack constraints - the number depends on the actual stack depth
+    // 5. stack depth constraints
+    // 6. user stack constraints - the number depends on the actual stack depth
 
     let mut s_range = new_range(0, NUM_STATIC_DECODER_CONSTRAINTS * 2);
     let mut t_range = new_range(0, NUM_STATIC_DECODER_CONSTRAINTS * 2);
@@ -177,6 +201,10 @@ fn build_transition_coefficients(coefficients: &[u128], ctx_depth: usize, loop_d
     t_range = t_range.slide(NUM_AUX_STACK_CONSTRAINTS * 2);
     result[t_range.clone()].copy_from_slice(&coefficients[s_range.clone()]);
 
+    s_range = s_range.slide(NUM_DEPTH_CONSTRAINTS * 2);
+    t_range = t_range.slide(NUM_DEPTH_CONSTRAINTS * 2);
+    result[t_range.clone()].copy_from_slice(&coefficients[s_range.clone()]);
+
     s_range = s_range.slide(stack_depth * 2);
     t_range = t_range.slide(stack_depth * 2);
     result[t_range.clone()].copy_from_slice(&coefficients[s_range.clone()]);
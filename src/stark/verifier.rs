@@ -3,87 +3,252 @@ use crate::{
     crypto::{ MerkleTree },
     MIN_TRACE_LENGTH
 };
-use super::{ StarkProof, TraceState, ConstraintEvaluator, CompositionCoefficients, fri, utils };
+use super::{
+    StarkProof, PROOF_VERSION, TraceState, ConstraintEvaluator, CompositionCoefficients, BoundaryConstraints, AirExtension,
+    VerifierLimits, Transcript, record_check, validate_assertions, validate_extension, fri, utils };
 
 // VERIFIER FUNCTION
 // ================================================================================================
 
-pub fn verify(program_hash: &[u8; 32], inputs: &[u128], outputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+/// Verifies the `proof` against the `boundary` constraints and, if a `transcript` is supplied,
+/// records every check performed during verification into it. The proof is rejected before any
+/// hashing is done if its structure exceeds the specified `limits`.
+pub fn verify(boundary: &BoundaryConstraints, extension: Option<&dyn AirExtension>, proof: &StarkProof, allow_insecure: bool, limits: &VerifierLimits, mut transcript: Option<&mut Transcript>) -> Result<bool, String>
 {
+    // 0 ----- Make sure the proof can be read by this verifier -----------------------------------
+    check_proof(boundary, proof, allow_insecure, limits, &mut transcript)?;
     let options = proof.options();
     let hash_fn = options.hash_fn();
 
     // 1 ----- Verify proof of work and determine query positions ---------------------------------
     let degree_proof = proof.degree_proof();
-    let mut fri_roots: Vec<u8> = Vec::new();
-    for layer in degree_proof.layers.iter() {
-        layer.root.iter().for_each(|&v| fri_roots.push(v));
-    }
-    degree_proof.rem_root.iter().for_each(|&v| fri_roots.push(v));
-
-    let mut seed = [0u8; 32];
-    hash_fn(&fri_roots, &mut seed);
-    let seed = match utils::verify_pow_nonce(seed, proof.pow_nonce(), &options) {
-        Ok(seed) => seed,
-        Err(msg) => return Err(msg)
-    };
+    let pow_result = utils::verify_pow_nonce(get_fri_seed(proof), proof.pow_nonce(), &options);
+    record_check(&mut transcript, "proof_of_work", pow_result.is_ok(),
+        || format!("nonce: {}, grinding factor: {}", proof.pow_nonce(), options.grinding_factor()));
+    let seed = pow_result?;
 
     let t_positions = utils::compute_query_positions(&seed, proof.domain_size(), options);
     let c_positions = utils::map_trace_to_constraint_positions(&t_positions);
+    check_constraint_values(proof, &c_positions)?;
 
     // 2 ----- Verify number of operations in the program -----------------------------------------
-    if proof.op_count() < MIN_TRACE_LENGTH as u128 {
+    if !record_check(&mut transcript, "op_count", proof.op_count() >= MIN_TRACE_LENGTH as u128,
+        || format!("operations: {}, minimum: {}", proof.op_count(), MIN_TRACE_LENGTH))
+    {
         return Err(String::from("Verification of minimum operation count failed"));
     }
 
+    // make sure assertions refer to registers and steps which exist in the trace
+    let assertions = boundary.get_assertions();
+    let result = validate_assertions(assertions, proof.trace_length(), proof.stack_depth());
+    record_check(&mut transcript, "assertions", result.is_ok(), || format!("assertions: {}", assertions.len()));
+    result?;
+
+    // make sure the proof was generated with the same AIR extension (if any)
+    let result = match (extension, proof.extension_id()) {
+        (None, None) => Ok(()),
+        (Some(extension), Some(id)) if extension.id() == id => validate_extension(extension),
+        (Some(extension), Some(id)) => Err(format!(
+            "proof was generated with AIR extension {}, but extension {} was provided", id, extension.id())),
+        (None, Some(id)) => Err(format!(
+            "proof was generated with AIR extension {}, but no extension was provided", id)),
+        (Some(extension), None) => Err(format!(
+            "proof was generated without an AIR extension, but extension {} was provided", extension.id())),
+    };
+    record_check(&mut transcript, "air_extension", result.is_ok(),
+        || format!("extension: {}", proof.extension_id().unwrap_or("none")));
+    result?;
+
     // 3 ----- Verify trace and constraint Merkle proofs ------------------------------------------
-    if !MerkleTree::verify_batch(proof.trace_root(), &t_positions, &proof.trace_proof(), hash_fn) {
+    if !record_check(&mut transcript, "trace_merkle_proof",
+        MerkleTree::verify_batch(proof.trace_root(), &t_positions, &proof.trace_proof(), hash_fn),
+        || format!("root: {}, queries: {}", hex::encode(proof.trace_root()), t_positions.len()))
+    {
         return Err(String::from("verification of trace Merkle proof failed"));
     }
 
-    if !MerkleTree::verify_batch(proof.constraint_root(), &c_positions, &proof.constraint_proof(), hash_fn) {
+    if !record_check(&mut transcript, "constraint_merkle_proof",
+        MerkleTree::verify_batch(proof.constraint_root(), &c_positions, &proof.constraint_proof(), hash_fn),
+        || format!("root: {}, queries: {}", hex::encode(proof.constraint_root()), c_positions.len()))
+    {
         return Err(String::from("verification of constraint Merkle proof failed"));
     }
 
-    // 4 ----- Compute constraint evaluations at DEEP point z -------------------------------------
+    // 4 ----- Compute constraint evaluations at z and composition polynomial evaluations --------
+    let evaluator = ConstraintEvaluator::from_proof(proof, boundary, extension);
+    let evaluations = compose(proof, evaluator, &t_positions, &c_positions);
+
+    // 5 ----- Verify low-degree proof -------------------------------------------------------------
+    let max_degree = utils::get_composition_degree(proof.trace_length());
+    return match fri::verify(&degree_proof, &evaluations, &t_positions, max_degree, options, transcript) {
+        Ok(result) => Ok(result),
+        Err(msg) => Err(format!("verification of low-degree proof failed: {}", msg))
+    }
+}
+
+/// Checks whether the `proof` could have been generated for a program with the specified
+/// `program_hash` executed with the provided public `inputs`; the outputs and assertions are
+/// taken from the proof. This is much cheaper than `verify()`: no Merkle proofs are verified,
+/// and only the first layer of the FRI proof is examined. Specifically, composition polynomial
+/// evaluations are computed at the query positions in the same way as during verification, and
+/// are compared against the values committed to by the first FRI layer; since the program hash
+/// enters these evaluations via boundary constraints on the sponge registers, a proof generated
+/// for a different program fails this check.
+///
+/// A passing check does not mean that the proof is valid; the proof must still be verified
+/// using `verify()`.
+pub fn verify_program_binding(program_hash: &[u8; 32], inputs: &[u128], proof: &StarkProof) -> Result<bool, String>
+{
+    // the same checks as the ones done by verify() make sure that a malformed proof is rejected
+    // rather than read out of bounds; security of the options is left to verify()
+    let boundary = BoundaryConstraints::program_hash(program_hash)
+        .with_inputs(inputs)
+        .with_outputs(proof.outputs())
+        .with_assertions(proof.assertions());
+    check_proof(&boundary, proof, true, &VerifierLimits::default(), &mut None)?;
+
+    if let Some(id) = proof.extension_id() {
+        return Err(format!("proof was generated with AIR extension {}; program binding of such proofs can be checked only by full verification", id));
+    }
+    if proof.degree_proof().layers.is_empty() {
+        return Err(String::from("FRI proof must contain at least one layer"));
+    }
+    validate_assertions(proof.assertions(), proof.trace_length(), proof.stack_depth())?;
+
+    // determine query positions in the same way as the verifier does
+    let t_positions = get_query_positions(proof)?;
+    let c_positions = utils::map_trace_to_constraint_positions(&t_positions);
+    check_constraint_values(proof, &c_positions)?;
+
+    // compute composition polynomial evaluations and check them against the first FRI layer
+    let evaluator = ConstraintEvaluator::from_proof(proof, &boundary, None);
+    let evaluations = compose(proof, evaluator, &t_positions, &c_positions);
+    if !fri::check_first_layer(proof.degree_proof(), &evaluations, &t_positions) {
+        return Err(String::from("proof is not bound to the specified program hash and public inputs"));
+    }
+
+    return Ok(true);
+}
+
+/// Returns positions of the extended execution trace at which the `proof` is queried; positions
+/// are derived from the roots of all FRI layers and the proof-of-work nonce, and an error is
+/// returned if the nonce is not valid.
+pub fn get_query_positions(proof: &StarkProof) -> Result<Vec<usize>, String> {
+    let seed = utils::verify_pow_nonce(get_fri_seed(proof), proof.pow_nonce(), proof.options())?;
+    return Ok(utils::compute_query_positions(&seed, proof.domain_size(), proof.options()));
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Makes sure the `proof` can be read by this verifier: the layout of the proof and its field are
+/// supported, its options are secure (unless `allow_insecure` is set), its structure does not
+/// exceed the `limits`, and public values of the `boundary` constraints are valid field elements.
+/// Nothing is hashed or read from the vectors of the proof before these checks pass.
+fn check_proof(boundary: &BoundaryConstraints, proof: &StarkProof, allow_insecure: bool, limits: &VerifierLimits, transcript: &mut Option<&mut Transcript>) -> Result<(), String>
+{
+    // proofs of earlier layouts are upgraded to the current layout when they are deserialized
+    // (see StarkProof::from_bytes()), but proofs of earlier versions of the VM are rejected
+    let result = proof.check_version();
+    record_check(transcript, "proof_version", result.is_ok(),
+        || format!("proof version: {}, verifier version: {}", proof.version(), PROOF_VERSION));
+    result?;
+
+    let result = proof.check_field();
+    record_check(transcript, "field_modulus", result.is_ok(),
+        || format!("proof modulus: {}, verifier modulus: {}", proof.field_modulus(), field::MODULUS));
+    result.map_err(|err| err.to_string())?;
+
+    let options = proof.options();
+    if !record_check(transcript, "proof_options", !options.is_insecure() || allow_insecure,
+        || format!("extension factor: {}, queries: {}, grinding factor: {}, security level: {}",
+            options.extension_factor(), options.num_queries(), options.grinding_factor(), options.security_level(true)))
+    {
+        return Err(format!("Proof was generated using insecure options providing only {} bits of security",
+            options.security_level(true)));
+    }
+
+    // make sure the proof is not larger than the verifier is willing to handle
+    let result = limits.check_proof(proof);
+    record_check(transcript, "proof_limits", result.is_ok(),
+        || format!("domain depth: {}, queries: {}, FRI layers: {}",
+            proof.domain_depth(), options.num_queries(), proof.degree_proof().layers.len()));
+    result?;
+
+    // make sure public inputs and outputs are valid field elements; otherwise, boundary
+    // constraints would be evaluated against values which cannot appear in the trace
+    let inputs = boundary.get_inputs();
+    let outputs = boundary.get_outputs();
+    let invalid_value = inputs.iter().chain(outputs).find(|&&v| v >= field::MODULUS);
+    if !record_check(transcript, "public_values", invalid_value.is_none(),
+        || format!("inputs: {}, outputs: {}", inputs.len(), outputs.len()))
+    {
+        return Err(format!("public value {} is not a valid field element", invalid_value.unwrap()));
+    }
+
+    return Ok(());
+}
+
+/// Returns the seed for query positions, derived from the roots of all FRI layers.
+fn get_fri_seed(proof: &StarkProof) -> [u8; 32] {
+    let degree_proof = proof.degree_proof();
+    let mut fri_roots: Vec<u8> = Vec::new();
+    for layer in degree_proof.layers.iter() {
+        layer.root.iter().for_each(|&v| fri_roots.push(v));
+    }
+    degree_proof.rem_root.iter().for_each(|&v| fri_roots.push(v));
+
+    let mut seed = [0u8; 32];
+    proof.options().hash_fn()(&fri_roots, &mut seed);
+    return seed;
+}
+
+/// Makes sure the constraint Merkle proof contains a leaf for every queried position of the
+/// constraint tree; the leaves are read while composition polynomial evaluations are computed.
+fn check_constraint_values(proof: &StarkProof, c_positions: &[usize]) -> Result<(), String> {
+    let num_values = proof.constraint_proof().values.len();
+    if num_values != c_positions.len() {
+        return Err(format!("constraint Merkle proof must contain {} values, but contained {}",
+            c_positions.len(), num_values));
+    }
+    return Ok(());
+}
+
+/// Evaluates constraints at DEEP point z, and computes composition polynomial evaluations at the
+/// specified positions; returns z, constraint evaluation at z, and the composition evaluations.
+fn compose(proof: &StarkProof, evaluator: ConstraintEvaluator, t_positions: &[usize], c_positions: &[usize]) -> Vec<u128> {
     // derive DEEP point z from the root of the constraint tree
     let z = field::prng(*proof.constraint_root());
 
     // evaluate constraints at z
-    let constraint_evaluation_at_z = evaluate_constraints(
-        ConstraintEvaluator::from_proof(proof, program_hash, inputs, outputs),
-        proof.get_state_at_z1(),
-        proof.get_state_at_z2(),
-        z
-    );
-
-    // 5 ----- Compute composition polynomial evaluations -----------------------------------------
+    let constraint_evaluation_at_z = evaluate_constraints(evaluator, proof.get_state_at_z1(), proof.get_state_at_z2(), z);
+
     // derive coefficient for linear combination from the root of constraint tree
     let coefficients = CompositionCoefficients::new(*proof.constraint_root());
 
     // compute composition values separately for trace and constraints, and then add them together
-    let t_composition = compose_registers(&proof, &t_positions, z, &coefficients);
-    let c_composition = compose_constraints(&proof, &t_positions, &c_positions, z, constraint_evaluation_at_z, &coefficients);
+    let t_composition = compose_registers(&proof, t_positions, z, &coefficients);
+    let c_composition = compose_constraints(&proof, t_positions, c_positions, z, constraint_evaluation_at_z, &coefficients);
     let evaluations = t_composition.iter().zip(c_composition).map(|(&t, c)| field::add(t, c)).collect::<Vec<u128>>();
-    
-    // 6 ----- Verify low-degree proof -------------------------------------------------------------
-    let max_degree = utils::get_composition_degree(proof.trace_length());
-    return match fri::verify(&degree_proof, &evaluations, &t_positions, max_degree, options) {
-        Ok(result) => Ok(result),
-        Err(msg) => Err(format!("verification of low-degree proof failed: {}", msg))
-    }
+
+    return evaluations;
 }
 
-// HELPER FUNCTIONS
-// ================================================================================================
 fn evaluate_constraints(evaluator: ConstraintEvaluator, state1: TraceState, state2: TraceState, x: u128) -> u128 {
     let (i_value, f_value) = evaluator.evaluate_boundaries(&state1, x);
+    let a_values = evaluator.evaluate_assertions(&state1, x);
     let t_value = evaluator.evaluate_transition_at(&state1, &state2, x);
 
     // Z(x) = x - 1
     let z = field::sub(x, field::ONE);
     let mut result = field::div(i_value, z);
 
+    // Z(x) = x - x_at_step
+    for (&a_value, &step) in a_values.iter().zip(evaluator.assertion_steps()) {
+        let z = field::sub(x, evaluator.get_x_at_step(step));
+        result = field::add(result, field::div(a_value, z));
+    }
+
     // Z(x) = x - x_at_last_step
     let z = field::sub(x, evaluator.get_x_at_last_step());
     result = field::add(result, field::div(f_value, z));
@@ -0,0 +1,172 @@
+use arbitrary::{ Arbitrary, Unstructured, Result };
+use crate::{ Program, ProgramInputs, assembly, MAX_PUBLIC_INPUTS };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Maximum number of instructions generated for a single program body.
+const MAX_BODY_LENGTH       : usize = 16;
+
+/// Maximum nesting depth of generated control flow blocks.
+const MAX_NESTING_DEPTH     : usize = 2;
+
+/// Maximum number of iterations of a generated repeat block.
+const MAX_REPEAT_COUNT      : usize = 4;
+
+/// Generated programs never grow the stack beyond this depth.
+const MAX_GENERATED_DEPTH   : usize = 16;
+
+/// Maximum number of values generated for each of the secret input tapes.
+const MAX_TAPE_LENGTH       : usize = 16;
+
+// ARBITRARY IMPLEMENTATIONS
+// ================================================================================================
+
+/// Generates a valid program which can be executed with any set of inputs. Generated programs
+/// never read from the secret tapes and never touch stack items they did not put there, and
+/// thus cannot underflow the stack or fail an assertion.
+impl Arbitrary for Program {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        let source = arbitrary_source(u)?;
+        let program = assembly::compile(&source)
+            .unwrap_or_else(|err| panic!("generated program failed to compile: {}\n{}", err, source));
+        return Ok(program);
+    }
+}
+
+impl Arbitrary for ProgramInputs {
+    fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
+        let public_length = u.int_in_range(0..=MAX_PUBLIC_INPUTS)?;
+        let public = arbitrary_values(u, public_length)?;
+
+        // secondary tape can never be longer than the primary tape
+        let secret_a_length = u.int_in_range(0..=MAX_TAPE_LENGTH)?;
+        let secret_a = arbitrary_values(u, secret_a_length)?;
+        let secret_b_length = u.int_in_range(0..=secret_a_length)?;
+        let secret_b = arbitrary_values(u, secret_b_length)?;
+
+        return Ok(ProgramInputs::new(&public, &secret_a, &secret_b));
+    }
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Generates assembly source for a valid program; the source is guaranteed to compile, and the
+/// resulting program is guaranteed to execute successfully with any inputs.
+pub fn arbitrary_source(u: &mut Unstructured<'_>) -> Result<String> {
+    // the trace must contain at least one user stack register, so the program always
+    // starts by pushing a value onto the stack
+    let mut source = format!("begin push.{}", u64::arbitrary(u)?);
+    let mut depth = 1;
+    append_body(u, &mut source, &mut depth, 0)?;
+    source.push_str(" end");
+    return Ok(source);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+/// Appends a sequence of instructions to the `source` while keeping track of the number of
+/// stack items put onto the stack by the program itself.
+fn append_body(u: &mut Unstructured<'_>, source: &mut String, depth: &mut usize, nesting: usize) -> Result<()> {
+
+    let length = u.int_in_range(1..=MAX_BODY_LENGTH)?;
+    for _ in 0..length {
+        match u.int_in_range(0u8..=9)? {
+            0 | 1 if *depth < MAX_GENERATED_DEPTH => {
+                source.push_str(&format!(" push.{}", u64::arbitrary(u)?));
+                *depth += 1;
+            },
+            2 if *depth >= 1 && *depth < MAX_GENERATED_DEPTH => {
+                source.push_str(" dup");
+                *depth += 1;
+            },
+            3 if *depth >= 2 => source.push_str(" swap"),
+            4 if *depth >= 1 => {
+                source.push_str(" drop");
+                *depth -= 1;
+            },
+            5 if *depth >= 2 => {
+                source.push_str(*u.choose(&[" add", " sub", " mul"])?);
+                *depth -= 1;
+            },
+            6 if *depth >= 1 => source.push_str(" neg"),
+            7 if nesting < MAX_NESTING_DEPTH => {
+                // the condition is consumed by the branch; both branches start at the same
+                // depth, and the code after the branch can rely only on the smaller result
+                source.push_str(&format!(" push.{} if.true", u.int_in_range(0u8..=1)?));
+                let mut true_depth = *depth;
+                append_body(u, source, &mut true_depth, nesting + 1)?;
+                source.push_str(" else");
+                let mut false_depth = *depth;
+                append_body(u, source, &mut false_depth, nesting + 1)?;
+                source.push_str(" end");
+                *depth = std::cmp::min(true_depth, false_depth);
+            },
+            8 if nesting < MAX_NESTING_DEPTH && *depth >= 1 => {
+                // a repeated body must leave the stack depth unchanged, so only in-place
+                // operations are used inside of it
+                let count = u.int_in_range(2..=MAX_REPEAT_COUNT)?;
+                source.push_str(&format!(" repeat.{}", count));
+                append_in_place(u, source, *depth)?;
+                source.push_str(" end");
+            },
+            _ => source.push_str(" noop"),
+        }
+    }
+
+    return Ok(());
+}
+
+/// Appends a sequence of instructions which do not change stack depth.
+fn append_in_place(u: &mut Unstructured<'_>, source: &mut String, depth: usize) -> Result<()> {
+    let length = u.int_in_range(1..=MAX_BODY_LENGTH)?;
+    for _ in 0..length {
+        match u.int_in_range(0u8..=2)? {
+            0 if depth >= 2 => source.push_str(" swap"),
+            1 => source.push_str(" neg"),
+            _ => source.push_str(" noop"),
+        }
+    }
+    return Ok(());
+}
+
+fn arbitrary_values(u: &mut Unstructured<'_>, length: usize) -> Result<Vec<u128>> {
+    let mut result = Vec::with_capacity(length);
+    for _ in 0..length {
+        result.push(u64::arbitrary(u)? as u128);
+    }
+    return Ok(result);
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use rand::prelude::*;
+    use arbitrary::{ Arbitrary, Unstructured };
+    use crate::{ Program, ProgramInputs, ProofOptions, MAX_OUTPUTS };
+
+    #[test]
+    fn execute_verify_arbitrary_programs() {
+        let options = ProofOptions::default();
+        let mut rng = thread_rng();
+        for _ in 0..8 {
+            let mut data = vec![0u8; 4096];
+            rng.fill_bytes(&mut data);
+            let mut u = Unstructured::new(&data);
+
+            // skip the case if random data ran out before generation was complete
+            let (program, inputs) = match (Program::arbitrary(&mut u), ProgramInputs::arbitrary(&mut u)) {
+                (Ok(program), Ok(inputs)) => (program, inputs),
+                _ => continue,
+            };
+
+            let (outputs, proof) = crate::execute(&program, &inputs, MAX_OUTPUTS, &options);
+            let result = crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+            assert_eq!(Ok(true), result, "failed to verify program: {:?}", program);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! Helpers for property-based testing and fuzzing of the VM.
+//!
+//! This module is available only when the `arbitrary` feature is enabled.
+
+mod arbitrary;
+pub use self::arbitrary::{ arbitrary_source };
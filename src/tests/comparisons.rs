@@ -1,4 +1,4 @@
-use crate::{ ProofOptions, math::field };
+use crate::{ ProofOptions, math::field, assembly };
 use super::{
     build_program, OpCode,
     super::{ execute, verify, ProgramInputs }
@@ -27,6 +27,7 @@ fn eq_operations() {
     assert_eq!(Ok(true), result);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn cmp_operation() {
 
@@ -66,6 +67,7 @@ fn cmp_operation() {
     assert_eq!(Ok(true), result);
 }
 
+#[cfg(feature = "std-rand")]
 #[test]
 fn binacc_operation() {
 
@@ -104,8 +106,31 @@ fn binacc_operation() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn rc_range_operation() {
+    let program = assembly::compile("
+    begin
+        read rc.range.10.20
+        read rc.range.10.20
+        read rc.range.10.20
+        read rc.range.10.20
+        read rc.range.10.20
+    end").unwrap();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::new(&[], &[5, 10, 15, 20, field::MODULUS - 1], &[]);
+    let num_outputs = 5;
+
+    let (outputs, proof) = execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![0, 1, 1, 1, 0], outputs);
+
+    let result = verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
 // HELPER FUNCTIONS
 // ================================================================================================
+#[cfg(feature = "std-rand")]
 fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>) {
 
     let mut inputs_a = Vec::new();
@@ -118,4 +143,4 @@ fn build_inputs_for_cmp(a: u128, b: u128, size: usize) -> (Vec<u128>, Vec<u128>)
     inputs_b.reverse();
 
     return (inputs_a, inputs_b);
-}
\ No newline at end of file
+}
@@ -1,67 +1,1378 @@
 use std::collections::HashMap;
+use std::sync::{ Arc, Mutex, Weak };
 use crate::{
-    ProofOptions, Program, ProgramInputs, OpCode, OpHint,
+    Execution, Verification, ProofOptions, ProverContext, ProgressHandle, ProvingStage, ProvingCancelled, ProverEvent, Verbosity, Program, ProgramInputs, ProgramInputsBuilder, VerificationKey, Registry, Felt, OpCode, OpHint, TraceState, Assertion, AirExtension, assembly,
     blocks::{ ProgramBlock, Span, Group },
-    math::field, utils::hasher
+    math::field, crypto::{ rescue, Prng, digest_outputs }, utils::{ merkle, codec }
 };
+#[cfg(feature = "std-rand")]
+use crate::utils::disclosure::TapeCommitment;
 
 mod branches;
 mod comparisons;
 
 #[test]
 fn execute_verify() {
-    let program = build_program(vec![
-        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Noop, OpCode::Noop,
-    ], &[]);
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_verify_fail() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3]);
+
+    // wrong inputs
+    let result = super::verify(program.hash(), &[1, 1], &outputs, &proof);
+    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
+    assert_eq!(Err(err_msg), result);
+
+    // wrong outputs
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &[5], &proof);
+    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
+    assert_eq!(Err(err_msg), result);
+
+    // wrong program hash
+    let mut program_hash2 = program.hash().clone();
+    program_hash2[0] = 1;
+    let result = super::verify(&program_hash2, inputs.get_public_inputs(), &outputs, &proof);
+    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
+    assert_eq!(Err(err_msg), result);
+}
+
+#[test]
+fn execute_verify_prelude() {
+    use crate::prelude::*;
+
+    let program: Program = assembly::compile("begin push.3 push.5 add end").unwrap();
+    let error: AssemblyError = assembly::compile("begin foo end").unwrap_err();
+    assert_eq!(1, error.step());
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::none();
+    let (outputs, proof): (Vec<u128>, StarkProof) = execute(&program, &inputs, 1, &options);
+    assert_eq!(vec![8], outputs);
+    assert_eq!(Ok(true), verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+}
+
+#[test]
+fn execute_verify_large_extension_factors() {
+    let program = assembly::compile("begin push.3 push.5 add push.7 mul end").unwrap();
+    let inputs = ProgramInputs::none();
+
+    // small traces can afford larger blow-up factors; all supported factors must round-trip
+    for &extension_factor in [16, 32, 64, 128, 256].iter() {
+        let options = ProofOptions::new(extension_factor, 80, 0, crate::crypto::hash::blake3);
+        let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+        assert_eq!(outputs, [56]);
+        assert_eq!(extension_factor, proof.options().extension_factor());
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result, "extension factor {}", extension_factor);
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &[57], &proof);
+        assert!(result.is_err(), "extension factor {}", extension_factor);
+    }
+}
+
+#[test]
+fn execute_verify_wrong_field() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+
+    let (outputs, mut proof) = super::execute(&program, &inputs, 1, &options);
+    assert_eq!(field::MODULUS, proof.field_modulus());
+    assert_eq!(Ok(()), proof.check_field());
+
+    proof.set_field_modulus(0xFFFFFFFF00000001);
+    let mismatch = super::FieldMismatch { proof_modulus: 0xFFFFFFFF00000001, verifier_modulus: field::MODULUS };
+    assert_eq!(Err(mismatch), proof.check_field());
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(mismatch.to_string()), result);
+}
+
+#[test]
+fn execute_verify_masked() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 2;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3, 2]);
+    assert_eq!(outputs, proof.outputs());
+
+    // check only the first output; the second output is committed to by the digest
+    let digest = digest_outputs(&[2]);
+    let result = super::verify_masked(program.hash(), inputs.get_public_inputs(), &[(0, 3)], &digest, &proof);
+    assert_eq!(Ok(true), result);
+
+    // the digest must match the outputs which are not checked directly
+    let result = super::verify_masked(program.hash(), inputs.get_public_inputs(), &[(0, 3)], &digest_outputs(&[5]), &proof);
+    assert_eq!(Err(format!("masked outputs do not match the expected digest")), result);
+
+    // wrong value for the second output
+    let result = super::verify_masked(program.hash(), inputs.get_public_inputs(), &[(1, 5)], &digest_outputs(&[3]), &proof);
+    assert_eq!(Err(format!("output at position 1 does not match the expected value")), result);
+
+    // position out of bounds or specified more than once
+    let result = super::verify_masked(program.hash(), inputs.get_public_inputs(), &[(2, 0)], &digest, &proof);
+    assert_eq!(Err(format!("output position 2 is out of bounds; proof contains only 2 outputs")), result);
+    let result = super::verify_masked(program.hash(), inputs.get_public_inputs(), &[(0, 3), (0, 3)], &digest, &proof);
+    assert_eq!(Err(format!("output position 0 is specified more than once")), result);
+}
+
+#[test]
+fn execute_verify_and_extract() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 2;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3, 2]);
+
+    // outputs are recovered from the proof
+    let result = super::verify_and_extract(program.hash(), inputs.get_public_inputs(), &proof);
+    assert_eq!(Ok(outputs.clone()), result);
+
+    // verification fails for wrong public inputs
+    let result = super::verify_and_extract(program.hash(), &[2, 0], &proof);
+    assert!(result.is_err());
+
+    // verification fails if outputs in the serialized proof were tampered with
+    let mut proof_bytes = bincode::serialize(&proof).unwrap();
+    let original = bincode::serialize(&outputs).unwrap();
+    let position = proof_bytes.windows(original.len()).position(|w| w == &original[..]).unwrap();
+    let tampered = bincode::serialize(&vec![4u128, 2]).unwrap();
+    proof_bytes[position..(position + tampered.len())].copy_from_slice(&tampered);
+    let proof = bincode::deserialize::<super::StarkProof>(&proof_bytes).unwrap();
+    assert_eq!(&[4, 2], proof.outputs());
+
+    let result = super::verify_and_extract(program.hash(), inputs.get_public_inputs(), &proof);
+    assert!(result.is_err());
+}
+
+#[test]
+fn execute_verify_output_digest() {
+    let options = ProofOptions::default();
+    for &num_outputs in [12, 11].iter() {
+        let pushes: Vec<String> = (1..=num_outputs).map(|i| format!("push.{}", i)).collect();
+        let source = format!("begin {} end", pushes.join(" "));
+        let program = assembly::compile_with_output_digest(&source, num_outputs).unwrap();
+        let inputs = ProgramInputs::none();
+
+        // the first 4 outputs are returned directly, and the rest are bound by the digest
+        let outputs: Vec<u128> = (1..=num_outputs as u128).rev().collect();
+        let (direct_outputs, overflow_outputs) = outputs.split_at(4);
+        let digest = digest_outputs(overflow_outputs);
+
+        let (result, proof) = super::execute(&program, &inputs, 6, &options);
+        assert_eq!(&digest, &result[..2]);
+        assert_eq!(direct_outputs, &result[2..]);
+
+        let result = super::verify_with_output_digest(program.hash(), &[], direct_outputs, &digest, &proof);
+        assert_eq!(Ok(true), result);
+
+        // verification fails if any of the digested outputs is different
+        let mut wrong_outputs = overflow_outputs.to_vec();
+        wrong_outputs[overflow_outputs.len() - 1] = 0;
+        let wrong_digest = digest_outputs(&wrong_outputs);
+        let result = super::verify_with_output_digest(program.hash(), &[], direct_outputs, &wrong_digest, &proof);
+        assert!(result.is_err());
+    }
+}
+
+#[cfg(feature = "std-rand")]
+#[test]
+fn execute_verify_disclosure() {
+    // the program commits to 4 secret values, and adds up the public inputs
+    let commitment = TapeCommitment::new(&[5, 6, 7, 8], &[field::rand(), field::rand(), field::rand(), field::rand()]);
+    let source = format!("{} begin commit_tape.4 roll.4 roll.4 add end", assembly::stdlib::COMMIT_TAPE);
+    let program = assembly::compile(&source).unwrap();
+    let (a, b) = commitment.build_tapes();
+    let inputs = ProgramInputs::new(&[1, 2], &a, &b);
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 3, &options);
+    assert_eq!(3, outputs[0]);
+
+    // values can be revealed selectively after the proof was generated
+    let disclosure = commitment.open(&[1, 3]);
+    let result = super::verify_disclosure(program.hash(), &[1, 2], 1, &disclosure, &proof);
+    assert_eq!(Ok(vec![(1, 6), (3, 8)]), result);
+
+    // the disclosure must match the digest in the proof
+    assert!(super::verify_disclosure(program.hash(), &[1, 2], 0, &disclosure, &proof).is_err());
+    assert!(super::verify_disclosure(program.hash(), &[1, 2], 2, &disclosure, &proof).is_err());
+    let other = TapeCommitment::new(&[5, 6, 7, 9], &[1, 2, 3, 4]);
+    assert!(super::verify_disclosure(program.hash(), &[1, 2], 1, &other.open(&[1]), &proof).is_err());
+}
+
+#[test]
+fn execute_verify_cells() {
+    let program = assembly::compile("begin push.3 push.5 add push.7 mul end").unwrap();
+    let inputs = ProgramInputs::none();
+    let options = ProofOptions::default();
+
+    let (outputs, proof, trace) = super::execute_with_trace(Execution::new(&program, &inputs, 1, &options)).unwrap();
+    assert_eq!(outputs, [56]);
+    assert_eq!(proof.trace_root(), trace.root());
+
+    // cells can be opened in any order, and multiple cells can be opened at the same step
+    let (registers, _, _) = super::generate_trace(&program, &inputs);
+    let cells = [(registers.len() - 1, 9), (0, 3), (registers.len() - 2, 9), (5, 14)];
+    let opening = trace.open_cells(&cells);
+    let expected: Vec<u128> = cells.iter().map(|&(register, step)| registers[register][step]).collect();
+    let result = super::verify_cells(program.hash(), inputs.get_public_inputs(), &outputs, &opening, &proof);
+    assert_eq!(Ok(expected), result);
+
+    // openings survive serialization, and are bound to the trace root of the proof
+    let opening: super::CellOpening = bincode::deserialize(&bincode::serialize(&opening).unwrap()).unwrap();
+    assert_eq!(&cells, opening.cells());
+
+    let (_, other_proof) = super::execute(&assembly::compile("begin push.3 push.5 add push.8 mul end").unwrap(), &inputs, 1, &options);
+    let result = opening.verify(&other_proof);
+    assert_eq!(Err(format!("opened cells do not match the trace root")), result);
+
+    // the verifier rejects cells which do not exist in the trace
+    let program = assembly::compile("begin push.3 push.5 add push.7 mul repeat.16 dup drop end end").unwrap();
+    let (_, _, long_trace) = super::execute_with_trace(Execution::new(&program, &inputs, 1, &options)).unwrap();
+    let opening = long_trace.open_cells(&[(0, proof.trace_length())]);
+    assert!(opening.verify(&proof).unwrap_err().contains("out of bounds"));
+}
+
+#[test]
+fn execute_verify_sharded() {
+    let program = assembly::compile("begin push.3 push.5 add push.7 mul repeat.16 dup drop end end").unwrap();
+    let inputs = ProgramInputs::none();
+    let options = ProofOptions::default();
+
+    let (outputs, prover) = super::execute_sharded(Execution::new(&program, &inputs, 1, &options)).unwrap();
+    assert_eq!(outputs, [56]);
+
+    // tasks and parts survive serialization, and can be opened in any order by worker threads
+    // sharing the committed state of the prover
+    let tasks = prover.tasks(3);
+    assert_eq!(3, tasks.len());
+    assert_eq!(prover.header().positions().len(), tasks.iter().map(|t| t.positions().len()).sum::<usize>());
+    let workers: Vec<_> = tasks.iter().rev().map(|task| {
+        let task: super::QueryTask = bincode::deserialize(&bincode::serialize(task).unwrap()).unwrap();
+        let prover = prover.clone();
+        std::thread::spawn(move || bincode::serialize(&prover.open(&task)).unwrap())
+    }).collect();
+    let parts: Vec<super::ProofPart> = workers.into_iter()
+        .map(|worker| bincode::deserialize(&worker.join().unwrap()).unwrap())
+        .collect();
+
+    // the assembled proof is the same as the proof generated by a single prover
+    let proof = super::StarkProof::assemble(prover.header(), &parts).unwrap();
+    let (_, expected) = super::execute(&program, &inputs, 1, &options);
+    assert_eq!(bincode::serialize(&expected).unwrap(), bincode::serialize(&proof).unwrap());
+    assert_eq!(Ok(true), super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+
+    // every position must be opened exactly once
+    let result = super::StarkProof::assemble(prover.header(), &parts[1..]);
+    assert!(result.err().unwrap().contains("is not opened by any part"));
+    let mut duplicated = parts.clone();
+    duplicated.push(parts[0].clone());
+    let result = super::StarkProof::assemble(prover.header(), &duplicated);
+    assert!(result.err().unwrap().contains("is opened by more than one part"));
+
+    // modified openings are rejected; the first value of the first trace row follows the shard
+    // index, the positions, and the lengths of the row list and of the row
+    let mut bytes = bincode::serialize(&parts[0]).unwrap();
+    bytes[32 + 8 * parts[0].positions().len()] ^= 1;
+    let mut modified = parts.clone();
+    modified[0] = bincode::deserialize(&bytes).unwrap();
+    let result = super::StarkProof::assemble(prover.header(), &modified);
+    assert!(result.err().unwrap().contains("invalid trace opening"));
+
+    // parts opened by a prover of a different execution are rejected
+    let other_program = assembly::compile("begin push.3 push.5 add push.8 mul repeat.16 dup drop end end").unwrap();
+    let (_, other_prover) = super::execute_sharded(Execution::new(&other_program, &inputs, 1, &options)).unwrap();
+    let result = super::StarkProof::assemble(other_prover.header(), &parts);
+    assert!(result.is_err());
+
+    // a worker in another process recomputes the prover by executing the program again
+    let (_, worker_prover) = super::execute_sharded(Execution::new(&program, &inputs, 1, &options)).unwrap();
+    let worker_parts: Vec<_> = tasks.iter().map(|task| worker_prover.open(task)).collect();
+    let proof = super::StarkProof::assemble(prover.header(), &worker_parts).unwrap();
+    assert_eq!(bincode::serialize(&expected).unwrap(), bincode::serialize(&proof).unwrap());
+
+    // the salt of the program commitment is carried by the header into the assembled proof
+    let salt = [7u8; 32];
+    let mut prover = prover;
+    prover.set_program_salt(&salt);
+    let proof = super::StarkProof::assemble(prover.header(), &parts).unwrap();
+    assert_eq!(Some(&salt), proof.program_salt());
+    let commitment = program.commitment(&salt, options.hash_fn());
+    let result = super::verify_blinded(&commitment, program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_verify_assertions() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    // assert intermediate values of the first two stack registers
+    let assertions = [Assertion::new(0, 2, 0), Assertion::new(1, 2, 1), Assertion::new(0, 5, 1)];
+    let (outputs, proof) = super::execute_with_assertions(&program, &inputs, num_outputs, &assertions, &options);
+    assert_eq!(outputs, [3]);
+    assert_eq!(&assertions, proof.assertions());
+
+    let result = super::verify_with_assertions(program.hash(), inputs.get_public_inputs(), &outputs, &assertions, &proof);
+    assert_eq!(Ok(true), result);
+
+    // assertions bound by the proof are verified by plain verification as well
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // verification fails for different assertions
+    let wrong_assertions = [Assertion::new(0, 2, 0), Assertion::new(1, 2, 1), Assertion::new(0, 5, 2)];
+    let result = super::verify_with_assertions(program.hash(), inputs.get_public_inputs(), &outputs, &wrong_assertions, &proof);
+    assert!(result.is_err());
+
+    // verification fails if assertions in the serialized proof were tampered with
+    let mut proof_bytes = bincode::serialize(&proof).unwrap();
+    let original = bincode::serialize(&assertions[2]).unwrap();
+    let position = proof_bytes.windows(original.len()).rposition(|w| w == &original[..]).unwrap();
+    let tampered = bincode::serialize(&wrong_assertions[2]).unwrap();
+    proof_bytes[position..(position + tampered.len())].copy_from_slice(&tampered);
+    let proof = bincode::deserialize::<super::StarkProof>(&proof_bytes).unwrap();
+    assert_eq!(&wrong_assertions, proof.assertions());
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "assertion failed: register 0 at step 5 is 1, but expected 2")]
+fn execute_assertions_fail() {
+    let program = add_program();
+
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let assertions = [Assertion::new(0, 5, 2)];
+    super::execute_with_assertions(&program, &inputs, 1, &assertions, &ProofOptions::default());
+}
+
+#[test]
+fn execute_verify_extension() {
+    let program = not_program(&[]);
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let num_outputs = 1;
+
+    let extension = BinaryTopExtension { id: "binary_top" };
+    let (outputs, proof) = super::execute_with_extension(&program, &inputs, num_outputs, &extension, &options);
+    assert_eq!(outputs, [1]);
+    assert_eq!(Some("binary_top"), proof.extension_id());
+
+    let result = super::verify_with_extension(program.hash(), inputs.get_public_inputs(), &outputs, &extension, &proof);
+    assert_eq!(Ok(true), result);
+
+    // verification fails without the extension or with a different extension
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
+
+    let other = BinaryTopExtension { id: "binary_top_v2" };
+    let result = super::verify_with_extension(program.hash(), inputs.get_public_inputs(), &outputs, &other, &proof);
+    assert!(result.is_err());
+
+    // proofs generated without an extension cannot be verified with an extension
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let result = super::verify_with_extension(program.hash(), inputs.get_public_inputs(), &outputs, &extension, &proof);
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "constraint 1 of AIR extension binary_top did not hold at step 1")]
+fn execute_extension_fail() {
+    let program = not_program(&[OpCode::Swap]);
+
+    // SWAP changes the value of the second stack register
+    let inputs = ProgramInputs::from_public(&[0, 1]);
+    let extension = BinaryTopExtension { id: "binary_top" };
+    super::execute_with_extension(&program, &inputs, 1, &extension, &ProofOptions::default());
+}
+
+#[test]
+fn execute_verify_combined() {
+    let program = not_program(&[]);
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::new(&[1], &[1, 2], &[]);
+    let num_outputs = 1;
+
+    // optional parameters of an execution can be combined
+    let assertions = [Assertion::new(0, 5, 1)];
+    let extension = BinaryTopExtension { id: "binary_top" };
+    let salt = [3u8; 32];
+    let context = ProverContext::new();
+    let progress = ProgressHandle::new();
+    let execution = Execution::new(&program, &inputs, num_outputs, &options)
+        .with_assertions(&assertions)
+        .with_extension(&extension)
+        .with_salt(&salt)
+        .with_context(&context)
+        .with_progress(&progress);
+    let (outputs, proof, report) = super::execute_with(execution).unwrap();
+    assert_eq!(outputs, [1]);
+    assert_eq!(&assertions, proof.assertions());
+    assert_eq!(Some("binary_top"), proof.extension_id());
+    assert_eq!(Some(&salt), proof.program_salt());
+    assert_eq!(1, context.num_setups());
+    assert_eq!([2, 0], report.provided_inputs);
+
+    // and so can optional parameters of a verification
+    let commitment = program.commitment(&salt, options.hash_fn());
+    let mut transcript = super::Transcript::new();
+    let verification = Verification::new(program.hash(), inputs.get_public_inputs(), &outputs)
+        .with_assertions(&assertions)
+        .with_extension(&extension)
+        .with_commitment(&commitment)
+        .with_transcript(&mut transcript);
+    assert_eq!(Ok(true), super::verify_with(verification, &proof));
+    assert!(transcript.passed());
+
+    // verification fails if any of the parameters does not match the proof
+    let other_commitment = program.commitment(&[4u8; 32], options.hash_fn());
+    let verification = Verification::new(program.hash(), inputs.get_public_inputs(), &outputs)
+        .with_extension(&extension)
+        .with_commitment(&other_commitment);
+    let err_msg = String::from("program hash does not match the program commitment");
+    assert_eq!(Err(err_msg), super::verify_with(verification, &proof));
+
+    let verification = Verification::new(program.hash(), inputs.get_public_inputs(), &outputs)
+        .with_commitment(&commitment);
+    assert!(super::verify_with(verification, &proof).is_err());
+}
+
+#[test]
+fn execute_to_writer() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let num_outputs = 1;
+
+    let mut proof_bytes = Vec::new();
+    let outputs = super::execute_to_writer(Execution::new(&program, &inputs, num_outputs, &options), &mut proof_bytes).unwrap();
+    assert_eq!(outputs, [3]);
+
+    // written proof is the same as serialized proof object
+    let (_, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(bincode::serialize(&proof).unwrap(), proof_bytes);
+
+    let proof = bincode::deserialize::<super::StarkProof>(&proof_bytes).unwrap();
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // salt of the program commitment is written into the proof
+    let salt = [7u8; 32];
+    let mut proof_bytes = Vec::new();
+    super::execute_to_writer(Execution::new(&program, &inputs, num_outputs, &options).with_salt(&salt), &mut proof_bytes).unwrap();
+    let (_, proof) = super::execute_blinded(&program, &inputs, num_outputs, &salt, &options);
+    assert_eq!(bincode::serialize(&proof).unwrap(), proof_bytes);
+
+    let proof = super::StarkProof::from_bytes(&proof_bytes).unwrap();
+    let commitment = program.commitment(&salt, options.hash_fn());
+    let result = super::verify_blinded(&commitment, program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn try_execute() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+
+    let (outputs, proof) = super::try_execute(&program, &inputs, 1, &options).unwrap();
+    assert_eq!(outputs, [20]);
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // executions which cannot be proven are rejected before proof generation starts
+    let err_msg = format!("cannot produce more than {} outputs, but requested {}", super::MAX_OUTPUTS, super::MAX_OUTPUTS + 1);
+    let result = super::try_execute(&program, &inputs, super::MAX_OUTPUTS + 1, &options);
+    assert_eq!(Some(err_msg.clone()), result.err());
+
+    let mut proof_bytes = Vec::new();
+    let result = super::execute_to_writer(Execution::new(&program, &inputs, super::MAX_OUTPUTS + 1, &options), &mut proof_bytes);
+    assert_eq!(Err(err_msg), result);
+    assert!(proof_bytes.is_empty());
+}
+
+#[test]
+fn verify_proof_versions() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+
+    // serialized proof starts with the magic bytes and the version
+    let proof_bytes = proof.to_bytes();
+    assert_eq!(super::PROOF_VERSION, proof.version());
+    assert_eq!(&b"DSPF"[..], &proof_bytes[..4]);
+    assert_eq!(super::PROOF_VERSION, proof_bytes[4]);
+    let result = super::verify_bytes(program.hash(), inputs.get_public_inputs(), &outputs, &proof_bytes);
+    assert_eq!(Ok(true), result);
+
+    // proofs produced by v0.5.1, before the layout was versioned, are upgraded to the current
+    // layout; the proof was generated for `begin push.3 add push.5 mul end` with 16x extension
+    // factor and 2 queries
+    let v1_bytes = include_bytes!("data/proof_v1.bin");
+    let upgraded = super::StarkProof::from_bytes(v1_bytes).unwrap();
+    assert_eq!(1, upgraded.version());
+    assert_eq!(&v1_bytes[..32], upgraded.trace_root());
+    assert_eq!(field::MODULUS, upgraded.field_modulus());
+    assert_eq!(64, upgraded.trace_length());
+    assert_eq!(16, upgraded.options().extension_factor());
+    assert_eq!(2, upgraded.options().num_queries());
+    assert!(upgraded.outputs().is_empty());
+    let reread = super::StarkProof::from_bytes(&upgraded.to_bytes()).unwrap();
+    assert_eq!(upgraded.to_bytes(), reread.to_bytes());
+
+    // such proofs cannot be verified since the execution trace of v0.5.1 has a different layout;
+    // verification returns an error instead of reading the trace with the current layout
+    let err_msg = format!("Proof version 1 was produced by distaff v0.5.1 and cannot be verified; the verifier supports version {}",
+        super::PROOF_VERSION);
+    let result = super::verify_with_options(program.hash(), &[1], &[20], &upgraded, true);
+    assert_eq!(Err(err_msg.clone()), result);
+    let result = super::verify_bytes(program.hash(), &[1], &[20], v1_bytes);
+    assert_eq!(Err(err_msg.clone()), result);
+    let result = super::verify_with_limits(program.hash(), &[1], &[20], v1_bytes, &super::VerifierLimits::default());
+    assert_eq!(Err(err_msg), result);
+
+    // versioned proofs which cannot be read are not retried as unversioned ones
+    let err_msg = super::StarkProof::from_bytes(&proof_bytes[..proof_bytes.len() - 1]).map(|_| ()).unwrap_err();
+    assert!(err_msg.starts_with(&format!("failed to deserialize proof of version {}: ", super::PROOF_VERSION)));
+
+    // proofs of unknown versions are rejected
+    let mut future_bytes = proof_bytes.clone();
+    future_bytes[4] = super::PROOF_VERSION + 1;
+    let err_msg = format!("failed to deserialize proof of version {}: proof version {} is not supported; supported versions are 1 through {}",
+        super::PROOF_VERSION + 1, super::PROOF_VERSION + 1, super::PROOF_VERSION);
+    assert_eq!(Err(err_msg), super::StarkProof::from_bytes(&future_bytes).map(|_| ()));
+
+    // when deserialized directly, such proofs are rejected by the verifier
+    let proof = bincode::deserialize::<super::StarkProof>(&future_bytes).unwrap();
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    let err_msg = format!("Proof version {} is not supported; the verifier supports version {}",
+        super::PROOF_VERSION + 1, super::PROOF_VERSION);
+    assert_eq!(Err(err_msg), result);
+}
+
+#[test]
+fn verify_with_limits() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+    let proof_bytes = proof.to_bytes();
+
+    // proofs within the limits are verified
+    let limits = super::VerifierLimits {
+        max_proof_bytes : proof_bytes.len(),
+        max_trace_len   : proof.trace_length(),
+        max_queries     : options.num_queries(),
+        max_fri_layers  : proof.degree_proof().layers.len(),
+    };
+    let result = super::verify_with_limits(program.hash(), inputs.get_public_inputs(), &outputs, &proof_bytes, &limits);
+    assert_eq!(Ok(true), result);
+
+    // oversized proofs are rejected before they are deserialized
+    let mut padded_bytes = proof_bytes.clone();
+    padded_bytes.push(0);
+    let result = super::verify_with_limits(program.hash(), inputs.get_public_inputs(), &outputs, &padded_bytes, &limits);
+    let err_msg = format!("proof size of {} bytes exceeds the limit of {} bytes", padded_bytes.len(), proof_bytes.len());
+    assert_eq!(Err(err_msg), result);
+
+    // proofs with too many queries are rejected before any hashing
+    let limits = super::VerifierLimits { max_queries: options.num_queries() - 1, ..limits };
+    let result = super::verify_with_limits(program.hash(), inputs.get_public_inputs(), &outputs, &proof_bytes, &limits);
+    let err_msg = format!("number of queries {} exceeds the limit of {} queries", options.num_queries(), options.num_queries() - 1);
+    assert_eq!(Err(err_msg), result);
+
+    // a malformed domain depth cannot overflow the computation of the trace length
+    let mut deep_bytes = proof_bytes.clone();
+    deep_bytes[5 + 32 + 16] = 255;
+    let limits = super::VerifierLimits { max_trace_len: 1 << 20, ..super::VerifierLimits::default() };
+    let result = super::verify_with_limits(program.hash(), inputs.get_public_inputs(), &outputs, &deep_bytes, &limits);
+    assert!(result.unwrap_err().starts_with("trace length of 2^"));
+
+    // malformed options are rejected when the proof is deserialized, before any arithmetic is
+    // done on them; options are the last 4 bytes of the proof
+    let options_offset = proof_bytes.len() - 4;
+    for &(offset, value) in [(0, 200), (0, 3), (0, 9), (1, 0), (1, 129), (2, 33)].iter() {
+        let mut bad_bytes = proof_bytes.clone();
+        bad_bytes[options_offset + offset] = value;
+        let result = super::verify_with_limits(program.hash(), inputs.get_public_inputs(), &outputs, &bad_bytes, &limits);
+        assert!(result.unwrap_err().starts_with("failed to deserialize proof of version"));
+    }
+}
+
+#[test]
+fn verify_malformed_proofs() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+    let document: serde_json::Value = serde_json::from_str(&proof.to_json(program.hash(), inputs.get_public_inputs())).unwrap();
+
+    let malform = |update: &dyn Fn(&mut serde_json::Value)| {
+        let mut document = document.clone();
+        update(&mut document);
+        let (proof, _, _) = super::StarkProof::from_json(&document.to_string()).unwrap();
+        proof
+    };
+    let truncate = |path: &[&str], length: usize| {
+        malform(&|document| {
+            let mut value = document;
+            for &key in path { value = &mut value[key]; }
+            value.as_array_mut().unwrap().truncate(length);
+        })
+    };
+
+    // vectors which depend only on the trace dimensions and proof options are checked when the
+    // proof is read with limits, and again by the verifier
+    let num_queries = options.num_queries();
+    let malformed = vec![
+        (truncate(&["deep_values", "trace_at_z1"], 2), "trace_at_z1 must contain"),
+        (truncate(&["deep_values", "trace_at_z2"], 2), "trace_at_z2 must contain"),
+        (truncate(&["trace_evaluations"], num_queries - 1), "trace evaluations must contain"),
+        (malform(&|d| { d["trace_evaluations"][0].as_array_mut().unwrap().pop(); }), "trace evaluation rows must contain"),
+        (truncate(&["degree_proof", "layers"], 1), "FRI proof must contain"),
+        (truncate(&["degree_proof", "rem_values"], 1), "FRI remainder must contain"),
+        (malform(&|d| d["trace_info"]["domain_depth"] = serde_json::json!(3)), "domain depth must be between"),
+        (malform(&|d| d["trace_info"]["stack_depth"] = serde_json::json!(200)), "execution trace must have fewer than"),
+    ];
+    for (proof, err_prefix) in malformed.iter() {
+        let result = super::StarkProof::from_bytes_with_limits(&proof.to_bytes(), &super::VerifierLimits::default());
+        assert!(result.err().unwrap().starts_with(err_prefix), "{}", err_prefix);
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, proof);
+        assert!(result.unwrap_err().starts_with(err_prefix), "{}", err_prefix);
+        let result = super::verify_program_binding(program.hash(), inputs.get_public_inputs(), proof);
+        assert!(result.unwrap_err().starts_with(err_prefix), "{}", err_prefix);
+    }
+
+    // counts which depend on query positions are checked by the verifier
+    let malformed = vec![
+        (malform(&|d| { d["constraint_proof"]["values"].as_array_mut().unwrap().pop(); }), "constraint Merkle proof must contain"),
+        (malform(&|d| { d["degree_proof"]["layers"][0]["values"].as_array_mut().unwrap().pop(); }), "verification of low-degree proof failed: FRI layer 0 must contain"),
+    ];
+    for (proof, err_prefix) in malformed.iter() {
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, proof);
+        assert!(result.unwrap_err().starts_with(err_prefix), "{}", err_prefix);
+        assert!(super::verify_program_binding(program.hash(), inputs.get_public_inputs(), proof).is_err());
+    }
+}
+
+#[test]
+fn proof_to_json() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let assertions = [Assertion::new(0, 5, 1)];
+    let (outputs, proof) = super::execute_with_assertions(&program, &inputs, 1, &assertions, &options);
+
+    let json = proof.to_json(program.hash(), inputs.get_public_inputs());
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    // public data is included in the document
+    assert_eq!(2, document["version"]);
+    assert_eq!(hex::encode(program.hash()), document["program_hash"]);
+    assert_eq!(hex::encode(1u128.to_le_bytes()), document["public_inputs"][0]);
+    assert_eq!(hex::encode(outputs[0].to_le_bytes()), document["outputs"][0]);
+    assert_eq!(5, document["assertions"][0]["step"]);
+    assert_eq!("blake3", document["options"]["hash_fn"]);
+    assert_eq!(options.extension_factor(), document["options"]["extension_factor"]);
+
+    // proof fields are hex-encoded
+    assert_eq!(hex::encode(field::MODULUS.to_le_bytes()), document["trace_info"]["field_modulus"]);
+    assert_eq!(hex::encode(proof.trace_root()), document["trace_root"]);
+    assert_eq!(hex::encode(proof.constraint_root()), document["constraint_root"]);
+    let row = &document["trace_evaluations"][0];
+    assert_eq!(proof.trace_evaluations()[0].len(), row.as_array().unwrap().len());
+    assert_eq!(hex::encode(proof.trace_evaluations()[0][0].to_le_bytes()), row[0]);
+    assert_eq!(proof.degree_proof().layers.len(), document["degree_proof"]["layers"].as_array().unwrap().len());
+    assert_eq!(proof.pow_nonce(), document["pow_nonce"]);
+
+    // the document can be read back into the same proof
+    let (read_proof, program_hash, public_inputs) = super::StarkProof::from_json(&json).unwrap();
+    assert_eq!(proof.to_bytes(), read_proof.to_bytes());
+    assert_eq!(program.hash(), &program_hash);
+    assert_eq!(inputs.get_public_inputs(), &public_inputs[..]);
+}
+
+#[test]
+fn proof_from_json_versions() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let salt = [7u8; 32];
+    let (outputs, proof) = super::execute_blinded(&program, &inputs, 1, &salt, &options);
+    let mut document: serde_json::Value = serde_json::from_str(&proof.to_json(program.hash(), inputs.get_public_inputs())).unwrap();
+
+    let (read_proof, _, _) = super::StarkProof::from_json(&document.to_string()).unwrap();
+    assert_eq!(Some(&salt), read_proof.program_salt());
+
+    // version 1 documents have no program salt
+    document["version"] = serde_json::json!(1);
+    document.as_object_mut().unwrap().remove("program_salt");
+    let (read_proof, program_hash, public_inputs) = super::StarkProof::from_json(&document.to_string()).unwrap();
+    assert_eq!(None, read_proof.program_salt());
+    let result = super::verify(&program_hash, &public_inputs, &outputs, &read_proof);
+    assert_eq!(Ok(true), result);
+
+    // documents of unknown versions are rejected
+    document["version"] = serde_json::json!(3);
+    let err_msg = "JSON schema version 3 is not supported; supported versions are 1 through 2";
+    assert_eq!(Some(String::from(err_msg)), super::StarkProof::from_json(&document.to_string()).err());
+
+    // malformed fields are reported by their path
+    document["version"] = serde_json::json!(2);
+    document["degree_proof"]["rem_root"] = serde_json::json!("00");
+    let err_msg = "degree_proof.rem_root must encode 32 bytes, but encodes 1";
+    assert_eq!(Some(String::from(err_msg)), super::StarkProof::from_json(&document.to_string()).err());
+}
+
+#[test]
+fn execute_verify_registry() {
+    let program = add_program();
+    let other = build_program(vec![OpCode::Begin; 15], &[]);
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let (outputs, proof) = super::execute(&program, &inputs, 2, &options);
+
+    let registry = Registry::from_programs(&[&other, &program], options.hash_fn());
+    let membership = registry.prove(program.hash()).unwrap();
+    let result = super::verify_with_registry(registry.root(), &membership, inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // verification fails if the program is not the one which was executed
+    let membership = registry.prove(other.hash()).unwrap();
+    let result = super::verify_with_registry(registry.root(), &membership, inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
+
+    // verification fails if the program is not in the registry
+    let registry = Registry::from_programs(&[&other], options.hash_fn());
+    let result = super::verify_with_registry(registry.root(), &membership, inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(String::from("program is not a member of the registry")), result);
+}
+
+#[test]
+fn execute_verify_batch_registry() {
+    let program1 = assembly::compile("begin push.3 add end").unwrap();
+    let program2 = assembly::compile("begin push.5 mul end").unwrap();
+    let program3 = assembly::compile("begin push.7 sub end").unwrap();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[2]);
+    let (outputs1, proof1) = super::execute(&program1, &inputs, 1, &options);
+    let (outputs2, proof2) = super::execute(&program2, &inputs, 1, &options);
+
+    let registry = Registry::from_programs(&[&program1, &program2, &program3], options.hash_fn());
+    let membership = registry.prove_batch(&[*program2.hash(), *program1.hash()]).unwrap();
+    let result = super::verify_batch_with_registry(registry.root(), &membership, &[
+        (inputs.get_public_inputs(), &outputs2, &proof2),
+        (inputs.get_public_inputs(), &outputs1, &proof1),
+    ]);
+    assert_eq!(Ok(true), result);
+
+    // verification fails if executions are not in the same order as programs in the proof
+    let result = super::verify_batch_with_registry(registry.root(), &membership, &[
+        (inputs.get_public_inputs(), &outputs1, &proof1),
+        (inputs.get_public_inputs(), &outputs2, &proof2),
+    ]);
+    assert!(result.is_err());
+
+    // verification fails if the number of executions does not match the proof
+    let result = super::verify_batch_with_registry(registry.root(), &membership, &[
+        (inputs.get_public_inputs(), &outputs2, &proof2),
+    ]);
+    assert_eq!(Err(String::from("expected 2 executions for the registry membership proof, but received 1")), result);
+
+    // verification fails if the programs are not in the registry
+    let registry = Registry::from_programs(&[&program2, &program3], options.hash_fn());
+    let result = super::verify_batch_with_registry(registry.root(), &membership, &[
+        (inputs.get_public_inputs(), &outputs2, &proof2),
+        (inputs.get_public_inputs(), &outputs1, &proof1),
+    ]);
+    assert_eq!(Err(String::from("programs are not members of the registry")), result);
+}
+
+#[test]
+fn execute_verify_blinded() {
+    let program = add_program();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let salt = [7u8; 32];
+    let (outputs, proof) = super::execute_blinded(&program, &inputs, 2, &salt, &options);
+    assert_eq!(Some(&salt), proof.program_salt());
+
+    let commitment = program.commitment(&salt, options.hash_fn());
+    assert_ne!(program.hash(), &commitment);
+    let result = super::verify_blinded(&commitment, program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // verification fails against a commitment with a different salt
+    let commitment = program.commitment(&[8u8; 32], options.hash_fn());
+    let result = super::verify_blinded(&commitment, program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(String::from("program hash does not match the program commitment")), result);
+
+    // verification fails for proofs without a salt
+    let (outputs, proof) = super::execute(&program, &inputs, 2, &options);
+    let result = super::verify_blinded(&commitment, program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(String::from("proof does not contain a program commitment salt")), result);
+}
+
+#[test]
+fn execute_with_context() {
+    let program1 = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let program2 = assembly::compile("begin push.4 mul push.6 add end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+
+    // programs of the same shape share the same prover setup
+    let context = ProverContext::new();
+    let (outputs1, proof1) = super::execute_with_context(&program1, &inputs, 1, &options, &context);
+    let (outputs2, proof2) = super::execute_with_context(&program2, &inputs, 1, &options, &context);
+    assert_eq!(1, context.num_setups());
+
+    // proofs are the same as the ones generated without a context
+    let (_, expected1) = super::execute(&program1, &inputs, 1, &options);
+    assert_eq!(bincode::serialize(&expected1).unwrap(), bincode::serialize(&proof1).unwrap());
+    let result = super::verify(program2.hash(), inputs.get_public_inputs(), &outputs2, &proof2);
+    assert_eq!(Ok(true), result);
+    assert_eq!(vec![20], outputs1);
+
+    // a program with a different shape requires a new setup
+    let program3 = assembly::compile("begin repeat.20 push.1 add end end").unwrap();
+    super::execute_with_context(&program3, &inputs, 1, &options, &context);
+    assert_eq!(2, context.num_setups());
+
+    context.clear();
+    assert_eq!(0, context.num_setups());
+
+    // a context never keeps more setups than its capacity
+    let context = ProverContext::with_capacity(1);
+    super::execute_with_context(&program1, &inputs, 1, &options, &context);
+    super::execute_with_context(&program3, &inputs, 1, &options, &context);
+    assert_eq!(1, context.num_setups());
+}
+
+#[test]
+fn execute_verify_with_key() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+
+    // the key can be serialized and used without the program
+    let key = program.to_verification_key(1, 1, &options);
+    let key = VerificationKey::from_bytes(&key.to_bytes()).unwrap();
+    assert_eq!(program.hash(), key.program_hash());
+    assert_eq!(Ok(true), super::verify_with_key(&key, inputs.get_public_inputs(), &outputs, &proof));
+
+    // verification fails if arity of inputs or outputs does not match the key
+    let result = super::verify_with_key(&key, &[1, 0], &outputs, &proof);
+    assert_eq!(Err(String::from("expected 1 public inputs, but received 2")), result);
+    let key = program.to_verification_key(1, 2, &options);
+    let result = super::verify_with_key(&key, inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(String::from("expected 2 outputs, but received 1")), result);
+
+    // verification fails if the proof was generated with different options
+    let key = program.to_verification_key(1, 1, &ProofOptions::new(32, 48, 0, crate::crypto::hash::blake3));
+    let result = super::verify_with_key(&key, inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Err(String::from("proof options do not match the options specified by the verification key")), result);
+
+    // malformed keys are rejected
+    assert!(VerificationKey::from_bytes(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn verify_with_transcript() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+
+    // all checks are recorded when verification succeeds
+    let (result, transcript) = super::verify_with_transcript(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+    assert!(transcript.passed());
+    let checks: Vec<&str> = transcript.entries().iter().map(|e| e.check.as_str()).collect();
+    assert_eq!(&checks[..10], &[
+        "proof_version", "field_modulus", "proof_options", "proof_limits", "public_values", "proof_of_work",
+        "op_count", "assertions", "air_extension", "trace_merkle_proof",
+    ]);
+    assert!(transcript.entries()[9].details.contains(&hex::encode(proof.trace_root())));
+    let fri_layers = checks.iter().filter(|&&c| c == "fri_layer_merkle_proof").count();
+    assert_eq!(proof.degree_proof().layers.len(), fri_layers);
+    assert_eq!(Some(&"fri_remainder_degree"), checks.last());
+
+    // the transcript can be archived as JSON
+    let document: serde_json::Value = serde_json::from_str(&transcript.to_json()).unwrap();
+    assert_eq!(checks.len(), document["entries"].as_array().unwrap().len());
+
+    // when verification fails, the failed check is the last entry
+    let (result, transcript) = super::verify_with_transcript(program.hash(), inputs.get_public_inputs(), &[21], &proof);
+    assert!(result.is_err());
+    assert!(!transcript.passed());
+    assert!(!transcript.entries().last().unwrap().passed);
+}
+
+#[test]
+fn verify_program_binding() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+    assert_eq!(Ok(true), super::verify_program_binding(program.hash(), inputs.get_public_inputs(), &proof));
+
+    // proofs of other programs, or of executions with other inputs, are rejected
+    let other = assembly::compile("begin push.3 add push.6 mul end").unwrap();
+    let err_msg = format!("proof is not bound to the specified program hash and public inputs");
+    assert_eq!(Err(err_msg.clone()), super::verify_program_binding(other.hash(), inputs.get_public_inputs(), &proof));
+    assert_eq!(Err(err_msg), super::verify_program_binding(program.hash(), &[2], &proof));
+
+    // public inputs must be valid field elements
+    let err_msg = format!("public value {} is not a valid field element", field::MODULUS + 1);
+    assert_eq!(Err(err_msg), super::verify_program_binding(program.hash(), &[field::MODULUS + 1], &proof));
+
+    // passing the check does not replace full verification
+    assert!(super::verify(program.hash(), inputs.get_public_inputs(), &[outputs[0] + 1], &proof).is_err());
+}
+
+#[test]
+fn execute_verify_felts() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+
+    // inputs can be specified as field elements, and outputs converted into field elements
+    let inputs = ProgramInputs::from_felts(&[Felt::from(1u64)], &[], &[]);
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+    assert_eq!(Ok(vec![Felt::from(20u64)]), Felt::from_u128_slice(&outputs));
+
+    // outputs which are not valid field elements are rejected by the verifier
+    let outputs = [20 + field::MODULUS];
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    let err_msg = format!("public value {} is not a valid field element", outputs[0]);
+    assert_eq!(Err(err_msg), result);
+}
+
+#[test]
+fn execute_with_report() {
+    let program = assembly::compile("begin read read.ab add add push.3 mul end").unwrap();
+    let options = ProofOptions::default();
+
+    // the report describes the execution which was proven
+    let inputs = ProgramInputs::new(&[], &[1, 2, 4], &[3, 5]);
+    let (outputs, proof, report) = super::execute_with_report(&program, &inputs, 1, &options);
+    assert_eq!(vec![18], outputs);
+    assert_eq!([2, 1], report.consumed_inputs);
+    assert_eq!([1, 1], report.unconsumed_inputs());
+    assert_eq!(super::report(&program, &inputs), report);
+    assert_eq!(Ok(true), super::verify(program.hash(), &[], &outputs, &proof));
+}
+
+#[test]
+fn execute_reduced_inputs() {
+    // strict construction returns an error for values which are not valid field elements
+    let err_msg = format!("secret tape A input {} at position 1 is not a valid field element", field::MODULUS);
+    assert_eq!(Err(err_msg), ProgramInputs::try_new(&[1], &[2, field::MODULUS], &[]));
+    let err_msg = format!("secret tape B input {} at position 0 is not a valid field element", field::MODULUS);
+    assert_eq!(Err(err_msg), ProgramInputs::try_new(&[1], &[2, 3], &[field::MODULUS]));
+    let err_msg = format!("public input {} at position 0 is not a valid field element", field::MODULUS + 3);
+    assert_eq!(Err(err_msg), ProgramInputsBuilder::new().push_public(field::MODULUS + 3).try_build());
+
+    // default construction does not panic, but canonicalizes the values instead
+    let inputs = ProgramInputs::new(&[field::MODULUS + 3], &[field::MODULUS + 2], &[]);
+    assert_eq!(ProgramInputs::new(&[3], &[2], &[]), inputs);
+    assert_eq!(ProgramInputs::from_public(&[1]), ProgramInputs::from_public(&[field::MODULUS + 1]));
+
+    let program = assembly::compile("begin read add end").unwrap();
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 1, &options);
+    assert_eq!(vec![5], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn execute_with_progress() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+
+    // all stages are reported in order
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_copy = reports.clone();
+    let progress = ProgressHandle::with_callback(move |stage, percent| {
+        reports_copy.lock().unwrap().push((stage, percent));
+    });
+    let (outputs, proof) = super::execute_with_progress(&program, &inputs, 1, &options, &progress).unwrap();
+    assert_eq!(Ok(true), super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+
+    let reports = reports.lock().unwrap();
+    let mut stages: Vec<ProvingStage> = reports.iter().map(|&(stage, _)| stage).collect();
+    stages.dedup();
+    assert_eq!(vec![
+        ProvingStage::TraceExtension, ProvingStage::TraceCommitment, ProvingStage::ConstraintEvaluation,
+        ProvingStage::ConstraintCommitment, ProvingStage::Composition, ProvingStage::FriReduction,
+        ProvingStage::QuerySelection, ProvingStage::ProofConstruction,
+    ], stages);
+    assert!(reports.iter().any(|&(stage, percent)| stage == ProvingStage::ConstraintEvaluation && percent > 0));
+    assert_eq!(Some(&(ProvingStage::ProofConstruction, 100)), reports.last());
+
+    // a cancelled handle stops proof generation at the first report
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_copy = reports.clone();
+    let progress = ProgressHandle::with_callback(move |stage, percent| {
+        reports_copy.lock().unwrap().push((stage, percent));
+    });
+    progress.cancel();
+    let result = super::execute_with_progress(&program, &inputs, 1, &options, &progress);
+    assert_eq!(Some(ProvingCancelled), result.err());
+    assert_eq!(vec![(ProvingStage::TraceExtension, 0)], *reports.lock().unwrap());
 
+    // cancellation is checked while the trace is extended, after every register
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reports_copy = reports.clone();
+    let handle: Arc<Mutex<Weak<ProgressHandle>>> = Arc::new(Mutex::new(Weak::new()));
+    let handle_copy = handle.clone();
+    let progress = Arc::new(ProgressHandle::with_callback(move |stage, percent| {
+        reports_copy.lock().unwrap().push((stage, percent));
+        if stage == ProvingStage::TraceExtension && percent > 0 {
+            handle_copy.lock().unwrap().upgrade().unwrap().cancel();
+        }
+    }));
+    *handle.lock().unwrap() = Arc::downgrade(&progress);
+    let result = super::execute_with_progress(&program, &inputs, 1, &options, &progress);
+    assert_eq!(Some(ProvingCancelled), result.err());
+    let reports = reports.lock().unwrap();
+    assert_eq!(ProvingStage::TraceExtension, reports.last().unwrap().0);
+    assert!(reports.last().unwrap().1 > 0);
+}
+
+#[test]
+fn execute_with_logger() {
+    let program = assembly::compile("begin push.3 add push.5 mul end").unwrap();
     let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1]);
+
+    let prove = |verbosity: Verbosity| {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_copy = events.clone();
+        let progress = ProgressHandle::with_logger(verbosity, move |event: &ProverEvent| {
+            events_copy.lock().unwrap().push(*event);
+        });
+        super::execute_with_progress(&program, &inputs, 1, &options, &progress).unwrap();
+        let events = events.lock().unwrap().clone();
+        events
+    };
+
+    // events are reported in order, and describe the trace and FRI layers
+    let events = prove(Verbosity::Details);
+    let steps = match events[0] {
+        ProverEvent::TraceBuilt { steps, .. } => steps,
+        event => panic!("unexpected first event: {}", event),
+    };
+    let domain = steps * options.extension_factor();
+    assert_eq!(ProverEvent::LdeDone { domain }, events[1]);
+    assert_eq!(Some(&ProverEvent::ProofBuilt), events.last());
+
+    let fri_layers: Vec<&ProverEvent> = events.iter().filter(|e| e.name() == "fri_layer").collect();
+    assert_eq!(ProverEvent::FriLayer { index: 0, size: domain }, *fri_layers[0]);
+
+    // the same events are reported every time the program is proven
+    assert_eq!(events, prove(Verbosity::Details));
+
+    // lower verbosity levels leave out detailed events
+    let stage_events: Vec<ProverEvent> = events.iter().filter(|e| e.verbosity() == Verbosity::Stages).cloned().collect();
+    assert_eq!(stage_events, prove(Verbosity::Stages));
+    assert!(prove(Verbosity::Quiet).is_empty());
+}
+
+#[test]
+fn selftest() {
+    // the executor and the interpreter agree on programs using all kinds of blocks and operations
+    let sources = [
+        "begin add push.5 mul push.7 end",
+        "begin read if.true add else mul end end",
+        "begin repeat.20 push.1 add end end",
+        "begin push.1 while.true push.3 add dup push.20 lt.8 end end",
+        "begin lt.64 eq gt.64 rc.32 divmod.64 end",
+        "begin push.1 push.2 push.3 choose push.7 isodd.8 hash.2 end",
+        "begin mpath_update.4 end",
+    ];
+    let path = merkle::MerklePath::new(vec![[1, 2], [3, 4], [5, 6]], 5);
+    let inputs = ProgramInputsBuilder::new()
+        .push_public(9).push_public(10).push_public(7).push_public(8).push_public(1)
+        .push_tape_a(1)
+        .push_merkle_path(&path)
+        .build()
+        .with_tape_policy(crate::TapePolicy::ZeroFill);
+    for source in sources.iter() {
+        let program = assembly::compile(source).unwrap();
+        assert_eq!(Ok(()), super::selftest(&program, &inputs), "{}", source);
+    }
+
+    // failures are reported
+    let program = assembly::compile("begin push.2 assert end").unwrap();
+    let errors = super::selftest(&program, &inputs).unwrap_err();
+    assert_eq!(1, errors.len());
+    assert!(errors[0].starts_with("program execution failed"));
+}
+
+#[test]
+fn execute_verify_insecure() {
+    let program = add_program();
+
+    let options = ProofOptions::dev();
     let inputs = ProgramInputs::from_public(&[1, 0]);
     let num_outputs = 1;
 
     let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
     assert_eq!(outputs, [3]);
+    assert!(proof.options().is_insecure());
+
+    // insecure proofs are rejected by default
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    let err_msg = format!("Proof was generated using insecure options providing only 4 bits of security");
+    assert_eq!(Err(err_msg), result);
+
+    let result = super::verify_with_options(program.hash(), inputs.get_public_inputs(), &outputs, &proof, true);
+    assert_eq!(Ok(true), result);
+
+    // insecurity is determined by the parameters of the proof, regardless of how the options
+    // were created
+    let options = ProofOptions::new(16, 4, 0, crate::crypto::hash::blake3);
+    assert!(ProofOptions::dev() == options);
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert!(result.is_err());
 
+    let options = ProofOptions::new(32, 40, 0, crate::crypto::hash::blake3);
+    assert!(!options.is_insecure());
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
     let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
     assert_eq!(Ok(true), result);
 }
 
 #[test]
-fn execute_verify_fail() {
-    let program = build_program(vec![
-        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
-        OpCode::Add,   OpCode::Noop, OpCode::Noop,
-    ], &[]);
+fn execute_verify_trivial() {
+    // the program is padded automatically to satisfy the minimum trace length
+    let program = assembly::compile("begin assert end").unwrap();
 
     let options = ProofOptions::default();
-    let inputs = ProgramInputs::from_public(&[1, 0]);
+    let inputs = ProgramInputs::from_public(&[1, 2]);
     let num_outputs = 1;
 
     let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
-    assert_eq!(outputs, [3]);
+    assert_eq!(outputs, [2]);
 
-    // wrong inputs
-    let result = super::verify(program.hash(), &[1, 1], &outputs, &proof);
-    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
-    assert_eq!(Err(err_msg), result);
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
 
-    // wrong outputs
-    let result = super::verify(program.hash(), inputs.get_public_inputs(), &[5], &proof);
-    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
-    assert_eq!(Err(err_msg), result);
+#[test]
+fn execute_verify_chain() {
+    let program1 = assembly::compile("begin add push.3 mul end").unwrap();
+    let program2 = assembly::compile("begin read add dup mul end").unwrap();
 
-    // wrong program hash
-    let mut program_hash2 = program.hash().clone();
-    program_hash2[0] = 1;
-    let result = super::verify(&program_hash2, inputs.get_public_inputs(), &outputs, &proof);
-    let err_msg = format!("verification of low-degree proof failed: evaluations did not match column value at depth 0");
+    let options = ProofOptions::default();
+    let inputs1 = ProgramInputs::from_public(&[1, 2]);
+    let inputs2 = ProgramInputs::new(&[], &[4], &[]);
+    let num_outputs = 1;
+
+    let segments = [(&program1, &inputs1), (&program2, &inputs2)];
+    let (outputs, proofs) = super::prove_chain(&segments, num_outputs, &options);
+    assert_eq!(vec![169], outputs);
+    assert_eq!(&[9, 0, 0, 0, 0, 0, 0, 0], proofs[0].outputs());
+
+    let program_hashes = [*program1.hash(), *program2.hash()];
+    let result = super::verify_chain(&program_hashes, inputs1.get_public_inputs(), &outputs, &proofs);
+    assert_eq!(Ok(true), result);
+
+    // wrong order of proofs
+    let proofs = vec![proofs[1].clone(), proofs[0].clone()];
+    let result = super::verify_chain(&program_hashes, inputs1.get_public_inputs(), &outputs, &proofs);
+    let err_msg = format!("verification of program 0 in the chain failed: verification of low-degree proof failed: evaluations did not match column value at depth 0");
     assert_eq!(Err(err_msg), result);
 }
 
+#[test]
+fn decode_trace() {
+    let program = assembly::compile("begin add push.5 mul push.7 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[1, 2]);
+
+    let (trace, ctx_depth, loop_depth) = super::generate_trace(&program, &inputs);
+    let stack_depth = TraceState::compute_stack_depth(trace.len(), ctx_depth, loop_depth);
+
+    // decode the last row of the trace
+    let last_step = trace[0].len() - 1;
+    let mut state = TraceState::new(ctx_depth, loop_depth, stack_depth);
+    state.update_from_trace(&trace, last_step);
+    assert_eq!(trace.len(), state.width());
+    assert_eq!(program.hash(), crate::utils::as_bytes(state.program_hash()));
+    assert_eq!([7, 15, 0, 0, 0, 0, 0, 0], state.user_stack());
+    assert_eq!(2, state.depth());
+    assert_eq!(1, state.noop_flag());
+
+    // raw rows can be decoded as well
+    let row = trace.iter().map(|register| register[last_step]).collect::<Vec<_>>();
+    let state2 = TraceState::from_vec(ctx_depth, loop_depth, stack_depth, &row);
+    assert_eq!(row, state2.to_vec());
+    assert_eq!(state.to_vec(), state2.to_vec());
+}
+
+#[test]
+fn divmod_operation() {
+    let program = assembly::compile("begin divmod.64 end").unwrap();
+
+    let options = ProofOptions::default();
+    let a = 18446744073709551557u128;   // largest 64-bit prime
+    let b = 4294967311u128;             // smallest 33-bit prime
+    let inputs = ProgramInputs::from_public(&[b, a]);
+    let num_outputs = 3;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![a / b, a % b, 0], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn umod_operation() {
+    let m = 4294967311u128;             // smallest 33-bit prime
+    let program = assembly::compile(&format!("begin umod.{} swap umod.{} end", m, m)).unwrap();
+
+    let options = ProofOptions::default();
+    let a = 18446744073709551557u128;   // largest 64-bit prime
+    let b = 12345u128;
+    let inputs = ProgramInputs::from_public(&[a, b]);
+    let num_outputs = 3;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![b % m, a % m, 0], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // modulus must be between 2 and 2^64 - 1
+    assert!(assembly::compile("begin umod.1 end").is_err());
+    assert!(assembly::compile("begin umod.18446744073709551616 end").is_err());
+    assert!(assembly::compile("begin umod end").is_err());
+}
+
+#[test]
+fn split_join_operations() {
+    let program = assembly::compile("begin split perm.2.0.1 split end").unwrap();
+
+    let options = ProofOptions::default();
+    let a = field::MODULUS - 1;
+    let b = (1u128 << 64) + 7;
+    let inputs = ProgramInputs::from_public(&[a, b, 0]);
+    let num_outputs = 5;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![b & u64::MAX as u128, b >> 64, a & u64::MAX as u128, a >> 64, 0], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // joining the halves restores the original values
+    let program = assembly::compile("begin split join end").unwrap();
+    for &x in [0, 5, u64::MAX as u128, b, a].iter() {
+        let stack = crate::processor::interpret(&program, &ProgramInputs::from_public(&[x])).unwrap().stack;
+        assert_eq!(x, stack[0]);
+    }
+
+    // halves must be 64-bit values, and must not encode a value greater than the modulus
+    let program = assembly::compile("begin join end").unwrap();
+    let (hi, lo) = (field::MODULUS >> 64, field::MODULUS & u64::MAX as u128);
+    assert!(crate::processor::interpret(&program, &ProgramInputs::from_public(&[lo - 1, hi])).is_ok());
+    assert!(crate::processor::interpret(&program, &ProgramInputs::from_public(&[lo, hi])).is_err());
+    assert!(crate::processor::interpret(&program, &ProgramInputs::from_public(&[1 << 64, 0])).is_err());
+    assert!(crate::processor::interpret(&program, &ProgramInputs::from_public(&[0, 1 << 64])).is_err());
+
+    assert!(assembly::compile("begin split.64 end").is_err());
+}
+
+#[test]
+fn perm_operation() {
+    let inputs = ProgramInputs::from_public(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    // all arrangements of the top 4 items, and a few arrangements of the top 8 items
+    let mut permutations = Vec::new();
+    for i in 0..24 {
+        let mut items = vec![0, 1, 2, 3];
+        let mut permutation = Vec::new();
+        let mut index = i;
+        for j in (1..=4).rev() {
+            permutation.push(items.remove(index % j));
+            index /= j;
+        }
+        permutations.push(permutation);
+    }
+    permutations.push(vec![7, 6, 5, 4, 3, 2, 1, 0]);
+    permutations.push(vec![1, 3, 5, 7, 0, 2, 4, 6]);
+    permutations.push(vec![0, 1, 2, 3, 4, 6, 5]);
+    permutations.push(vec![4, 0, 1, 2, 3]);
+
+    for permutation in permutations.iter() {
+        let params: Vec<String> = permutation.iter().map(|i| i.to_string()).collect();
+        let program = assembly::compile(&format!("begin perm.{} end", params.join("."))).unwrap();
+        let stack = crate::processor::interpret(&program, &inputs).unwrap().stack;
+
+        let mut expected: Vec<u128> = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        for (i, &index) in permutation.iter().enumerate() {
+            expected[i] = (index + 1) as u128;
+        }
+        assert_eq!(expected, stack[..8].to_vec(), "perm.{}", params.join("."));
+    }
+
+    // execution of a permutation can be proven
+    let program = assembly::compile("begin perm.1.3.5.7.0.2.4.6 end").unwrap();
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 8, &options);
+    assert_eq!(vec![2, 4, 6, 8, 1, 3, 5, 7], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
 #[test]
 fn stack_manipulation() {
     let program = build_program(vec![
@@ -86,6 +1397,24 @@ fn stack_manipulation() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn wide_stack_manipulation() {
+    let program = assembly::compile("begin dup.8 dup.8 add swap.8 drop.8 end").unwrap();
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 2, 3, 4, 5, 6, 7, 8]);
+    let num_outputs = 8;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(outputs, [3, 3, 4, 5, 6, 7, 8, 1]);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    let stack = crate::processor::interpret(&program, &inputs).unwrap().stack;
+    assert_eq!(outputs, stack[..num_outputs]);
+}
+
 #[test]
 fn conditional_operations() {
     // CHOOSE
@@ -186,6 +1515,26 @@ fn math_operations() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn inv_many_operations() {
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+    for &n in [2, 4, 8].iter() {
+        let program = assembly::compile(&format!("begin inv.{} end", n)).unwrap();
+        let (outputs, proof) = super::execute(&program, &inputs, 8, &options);
+
+        // top n items are inverted in place; the rest of the stack is not affected
+        let expected_result: Vec<u128> = inputs.get_public_inputs().iter().enumerate()
+            .map(|(i, &v)| if i < n { field::inv(v) } else { v })
+            .collect();
+        assert_eq!(expected_result, outputs);
+
+        let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result);
+    }
+}
+
 #[test]
 fn bool_operations() {
     let program = build_program(vec![
@@ -223,7 +1572,7 @@ fn hash_operations() {
     ], &[]);
 
     let value = [1, 2, 3, 4];
-    let mut expected_hash = hasher::digest(&value);
+    let mut expected_hash = rescue::digest(&value);
     expected_hash.reverse();
 
     let options = ProofOptions::default();
@@ -253,8 +1602,8 @@ fn hash_operations() {
     ], &[]);
 
     let value = [1, 2, 3, 4];
-    let mut expected_hash = hasher::digest(&value);
-    expected_hash = hasher::digest(&expected_hash);
+    let mut expected_hash = rescue::digest(&value);
+    expected_hash = rescue::digest(&expected_hash);
     expected_hash.reverse();
 
     let options = ProofOptions::default();
@@ -268,6 +1617,130 @@ fn hash_operations() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn mpath_update_operation() {
+    let program = assembly::compile("begin mpath_update.4 end").unwrap();
+
+    // authentication path for a leaf at position 5 in a tree of depth 4
+    let index = 5;
+    let path = [[1, 2], [3, 4], [5, 6]];
+    let old_leaf = [7, 8];
+    let new_leaf = [9, 10];
+    let old_root = merkle::compute_path_root(old_leaf, &path, index);
+    let new_root = merkle::compute_path_root(new_leaf, &path, index);
+
+    let options = ProofOptions::default();
+    let (a, b) = merkle::build_path_tapes(&path, index);
+    let inputs = ProgramInputs::new(&[old_leaf[1], old_leaf[0], new_leaf[1], new_leaf[0]], &a, &b);
+    let num_outputs = 4;
+
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![old_root[1], old_root[0], new_root[1], new_root[0]], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn mpath_update_with_inputs_builder() {
+    let program = assembly::compile("begin mpath_update.4 end").unwrap();
+
+    let path = merkle::MerklePath::new(vec![[1, 2], [3, 4], [5, 6]], 5);
+    let old_leaf = [7, 8];
+    let new_leaf = [9, 10];
+    let old_root = path.root(old_leaf);
+    let new_root = path.root(new_leaf);
+
+    // the old leaf must end up at the top of the stack followed by the new leaf
+    let inputs = ProgramInputsBuilder::new()
+        .push_public(new_leaf[0]).push_public(new_leaf[1])
+        .push_public(old_leaf[0]).push_public(old_leaf[1])
+        .push_merkle_path(&path)
+        .build();
+
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 4, &options);
+    assert_eq!(vec![old_root[1], old_root[0], new_root[1], new_root[0]], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
+#[test]
+fn read_bytes() {
+    let program = assembly::compile("begin read.bytes.20 end").unwrap();
+    let message: Vec<u8> = (1..=20).collect();
+    let inputs = ProgramInputsBuilder::new().push_bytes(&message).build();
+
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 2, &options);
+
+    // the last chunk of the string ends up at the top of the stack
+    let encoded = codec::bytes_to_elements(&message);
+    assert_eq!(vec![encoded[2], encoded[1]], outputs);
+    assert_eq!(Ok(message), codec::elements_to_bytes(&[20, outputs[1], outputs[0]]));
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // strings of a different length or with overflowing chunks are rejected
+    let inputs = ProgramInputsBuilder::new().push_bytes(&[1; 19]).build();
+    assert!(crate::processor::interpret(&program, &inputs).is_err());
+    let inputs = ProgramInputs::new(&[], &[20, 1, 1 << 40], &[]);
+    assert!(crate::processor::interpret(&program, &inputs).is_err());
+
+    assert!(assembly::compile("begin read.bytes.0 end").is_err());
+    assert!(assembly::compile("begin read.bytes.241 end").is_err());
+    assert!(assembly::compile("begin read.bytes end").is_err());
+}
+
+#[test]
+fn read_advice() {
+    // values are looked up by keys computed on the stack, regardless of the order of keys
+    let program = assembly::compile("begin read.key.2 push.5 read.key.1 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[3]).with_advice(5, &[30]).with_advice(3, &[10, 20]);
+
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 5, &options);
+    assert_eq!(vec![30, 5, 20, 10, 3], outputs);
+    assert_eq!(outputs, crate::processor::interpret(&program, &inputs).unwrap().stack);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+
+    // the builder populates the advice map in the same way
+    let built = ProgramInputsBuilder::new().push_public(3).push_advice(3, &[10, 20]).push_advice(5, &[30]).build();
+    assert_eq!(inputs, built);
+
+    // missing keys and unexpected numbers of values are rejected
+    let inputs = ProgramInputs::from_public(&[3]).with_advice(5, &[30]);
+    assert!(crate::processor::interpret(&program, &inputs).is_err());
+    let inputs = ProgramInputs::from_public(&[3]).with_advice(3, &[10]).with_advice(5, &[30]);
+    assert!(crate::processor::interpret(&program, &inputs).is_err());
+
+    assert!(assembly::compile("begin read.key.0 end").is_err());
+    assert!(assembly::compile("begin read.key.17 end").is_err());
+    assert!(assembly::compile("begin read.key end").is_err());
+}
+
+#[test]
+fn prng_sequence() {
+    let program = assembly::compile("begin prng.seed prng.next prng.next drop.6 end").unwrap();
+    let inputs = ProgramInputs::from_public(&[3, 5]);
+
+    let options = ProofOptions::default();
+    let (outputs, proof) = super::execute(&program, &inputs, 4, &options);
+
+    // values drawn later end up closer to the top of the stack
+    let mut prng = Prng::new([3, 5]);
+    let first = prng.next();
+    let second = prng.next();
+    assert_eq!(vec![second[0], second[1], first[0], first[1]], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
 #[test]
 fn read_operations() {
     let program = build_program(vec![
@@ -310,10 +1783,75 @@ fn assert_operations() {
     assert_eq!(Ok(true), result);
 }
 
+#[test]
+fn assertbin_operation() {
+    let program = build_program(vec![
+        OpCode::Begin, OpCode::AssertBin, OpCode::Not,  OpCode::AssertBin,
+        OpCode::Swap,  OpCode::AssertBin, OpCode::Noop, OpCode::Noop,
+        OpCode::Noop,  OpCode::Noop,      OpCode::Noop, OpCode::Noop,
+        OpCode::Noop,  OpCode::Noop,      OpCode::Noop,
+    ], &[]);
+
+    let options = ProofOptions::default();
+    let inputs = ProgramInputs::from_public(&[0, 1, 5]);
+    let num_outputs = 3;
+
+    // the values checked by ASSERTBIN remain on the stack
+    let (outputs, proof) = super::execute(&program, &inputs, num_outputs, &options);
+    assert_eq!(vec![1, 1, 5], outputs);
+
+    let result = super::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+    assert_eq!(Ok(true), result);
+}
+
 // TODO: add more tests
 
 // HELPER FUNCTIONS
 // ================================================================================================
+
+/// Requires the top of the stack to be a binary value, and the second stack register to remain
+/// unchanged throughout the execution.
+struct BinaryTopExtension {
+    id: &'static str,
+}
+
+impl AirExtension for BinaryTopExtension {
+
+    fn id(&self) -> &str {
+        return self.id;
+    }
+
+    fn constraint_degrees(&self) -> Vec<usize> {
+        return vec![2, 1];
+    }
+
+    fn evaluate(&self, current: &TraceState, next: &TraceState, result: &mut [u128]) {
+        let s0 = current.user_stack()[0];
+        result[0] = field::mul(s0, field::sub(s0, field::ONE));
+        result[1] = field::sub(next.user_stack()[1], current.user_stack()[1]);
+    }
+}
+
+/// Builds a program which computes a few Fibonacci numbers starting from the two values at the
+/// top of the stack; e.g. for public inputs [1, 0] the program outputs 3.
+fn add_program() -> Program {
+    return build_program(vec![
+        OpCode::Begin, OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Swap, OpCode::Dup2, OpCode::Drop,
+        OpCode::Add,   OpCode::Noop, OpCode::Noop,
+    ], &[]);
+}
+
+/// Builds a program which executes the `prefix` operations and then repeatedly negates the top
+/// of the stack.
+fn not_program(prefix: &[OpCode]) -> Program {
+    let mut instructions = vec![OpCode::Begin];
+    instructions.extend_from_slice(prefix);
+    instructions.resize(15, OpCode::Not);
+    return build_program(instructions, &[]);
+}
+
 fn build_program(instructions: Vec<OpCode>, push_values: &[u128]) -> Program {
 
     // build hint map for PUSh operations
@@ -0,0 +1,216 @@
+//! Test vectors for verifiers written in other languages.
+//!
+//! `generate()` builds a small suite of programs from a seed, executes them, and records for every
+//! program its source code, inputs, outputs, proof options, and the proof, together with the
+//! intermediate values a verifier derives while checking the proof: commitment roots and query
+//! positions. Programs, inputs, and options are derived deterministically from the seed, and
+//! proof generation is deterministic as well; thus, the same seed always produces the same suite.
+//! The JSON format of the suite is described in docs/test_vectors.md.
+
+use rand::{ Rng, SeedableRng, rngs::StdRng };
+use serde_json::{ json, Value };
+use crate::{ Program, ProgramInputs, ProofOptions, StarkProof, assembly, math::field, crypto::hash };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Version of the JSON document produced by `TestSuite::to_json()`; this must be incremented
+/// whenever the schema of the document changes.
+const JSON_SCHEMA_VERSION: u32 = 1;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Test vectors generated from a single seed.
+pub struct TestSuite {
+    pub seed            : u64,
+    pub vectors         : Vec<TestVector>,
+}
+
+/// A program executed and proven by the reference implementation, together with everything
+/// needed to verify the proof and to check intermediate results of verification.
+pub struct TestVector {
+    pub name            : String,
+    pub source          : String,
+    pub program         : Program,
+    pub inputs          : ProgramInputs,
+    pub outputs         : Vec<u128>,
+    pub options         : ProofOptions,
+    pub proof           : StarkProof,
+    pub digests         : ProofDigests,
+}
+
+/// Values derived by a verifier from a proof.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofDigests {
+    pub trace_root      : [u8; 32],
+    pub constraint_root : [u8; 32],
+    /// Roots of all FRI layers followed by the root of the FRI remainder.
+    pub fri_roots       : Vec<[u8; 32]>,
+    pub query_positions : Vec<usize>,
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Generates a suite of test vectors from the specified `seed`. The suite covers a loop over
+/// public inputs, field arithmetic, reading of secret inputs, and conditional execution; proof
+/// options (extension factor, number of queries, and grinding factor) differ between vectors.
+pub fn generate(seed: u64) -> TestSuite {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // fibonacci sequence
+    let n = rng.gen_range(8, 64);
+    let fibonacci = (
+        format!("fibonacci_{}", n),
+        format!("begin repeat.{} swap dup.2 drop add end end", n - 1),
+        ProgramInputs::from_public(&[1, 0]));
+
+    // arithmetic over random field elements
+    let k = field::rand_with(&mut rng);
+    let arithmetic = (
+        String::from("arithmetic"),
+        format!("begin add mul push.{} add inv dup mul end", k),
+        ProgramInputs::from_public(&field::rand_vector_with(3, &mut rng)));
+
+    // range checks of values read from secret tape A
+    let values: Vec<u128> = (0..rng.gen_range(4, 12)).map(|_| rng.gen::<u64>() as u128).collect();
+    let secret_inputs = (
+        format!("secret_inputs_{}", values.len()),
+        format!("begin {}end", "read rc.63 add ".repeat(values.len())),
+        ProgramInputs::new(&[0], &values, &[]));
+
+    // conditional execution selected by a public input
+    let condition = rng.gen_range(0, 2) as u128;
+    let branches = (
+        format!("branches_{}", condition),
+        String::from("begin if.true push.3 mul else push.5 add end end"),
+        ProgramInputs::from_public(&[condition, field::rand_with(&mut rng)]));
+
+    let vectors = vec![fibonacci, arithmetic, secret_inputs, branches].into_iter()
+        .map(|(name, source, inputs)| {
+            let options = generate_options(&mut rng);
+            build_vector(name, source, inputs, options)
+        })
+        .collect();
+
+    return TestSuite { seed, vectors };
+}
+
+// TEST SUITE IMPLEMENTATION
+// ================================================================================================
+impl TestSuite {
+
+    /// Returns a JSON document describing all test vectors of this suite. Proofs are included
+    /// both as JSON documents (see docs/proof_json.md) and as hex-encoded bincode serializations;
+    /// field elements are encoded in the same way as in proof JSON documents.
+    pub fn to_json(&self) -> String {
+        let document = json!({
+            "version": JSON_SCHEMA_VERSION,
+            "seed": self.seed,
+            "vectors": self.vectors.iter().map(|v| v.to_json()).collect::<Vec<Value>>(),
+        });
+        return serde_json::to_string_pretty(&document).expect("failed to serialize test vectors into JSON");
+    }
+}
+
+// TEST VECTOR IMPLEMENTATION
+// ================================================================================================
+impl TestVector {
+
+    fn to_json(&self) -> Value {
+        let [secret_a, secret_b] = self.inputs.get_secret_inputs();
+        let proof_bytes = bincode::serialize(&self.proof).expect("failed to serialize proof");
+        let proof_json = self.proof.to_json(self.program.hash(), self.inputs.get_public_inputs());
+
+        return json!({
+            "name": self.name,
+            "source": self.source,
+            "program_hash": hex::encode(self.program.hash()),
+            "inputs": json!({
+                "public": elements_to_json(self.inputs.get_public_inputs()),
+                "secret_a": elements_to_json(secret_a),
+                "secret_b": elements_to_json(secret_b),
+            }),
+            "outputs": elements_to_json(&self.outputs),
+            "options": json!({
+                "extension_factor": self.options.extension_factor(),
+                "num_queries": self.options.num_queries(),
+                "grinding_factor": self.options.grinding_factor(),
+            }),
+            "proof": serde_json::from_str::<Value>(&proof_json).expect("failed to parse proof JSON"),
+            "proof_bytes": hex::encode(proof_bytes),
+            "digests": json!({
+                "trace_root": hex::encode(self.digests.trace_root),
+                "constraint_root": hex::encode(self.digests.constraint_root),
+                "fri_roots": self.digests.fri_roots.iter().map(hex::encode).collect::<Vec<String>>(),
+                "query_positions": self.digests.query_positions,
+            }),
+        });
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn build_vector(name: String, source: String, inputs: ProgramInputs, options: ProofOptions) -> TestVector {
+    let program = assembly::compile(&source).expect("test vector program failed to compile");
+    let (outputs, proof) = crate::execute(&program, &inputs, 1, &options);
+
+    let degree_proof = proof.degree_proof();
+    let mut fri_roots: Vec<[u8; 32]> = degree_proof.layers.iter().map(|layer| layer.root).collect();
+    fri_roots.push(degree_proof.rem_root);
+
+    let digests = ProofDigests {
+        trace_root      : *proof.trace_root(),
+        constraint_root : *proof.constraint_root(),
+        fri_roots       : fri_roots,
+        query_positions : crate::stark::get_query_positions(&proof).expect("generated proof is not valid"),
+    };
+
+    return TestVector { name, source, program, inputs, outputs, options, proof, digests };
+}
+
+fn generate_options(rng: &mut StdRng) -> ProofOptions {
+    // options must provide at least 80 bits of security; otherwise, the verifier rejects them
+    let extension_factor = if rng.gen::<bool>() { 32 } else { 64 };
+    let num_queries = rng.gen_range(40, 49);
+    let grinding_factor = rng.gen_range(0, 5);
+    // only blake3 proofs can be serialized
+    return ProofOptions::new(extension_factor, num_queries, grinding_factor, hash::blake3);
+}
+
+fn elements_to_json(values: &[u128]) -> Value {
+    return values.iter().map(|v| hex::encode(v.to_le_bytes())).collect();
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    #[test]
+    fn generate() {
+        let suite = super::generate(42);
+        assert_eq!(4, suite.vectors.len());
+        for vector in suite.vectors.iter() {
+            let result = crate::verify(vector.program.hash(), vector.inputs.get_public_inputs(), &vector.outputs, &vector.proof);
+            assert_eq!(Ok(true), result, "test vector {} failed to verify", vector.name);
+            assert_eq!(vector.options.num_queries(), vector.digests.query_positions.len());
+            assert_eq!(vector.proof.degree_proof().layers.len() + 1, vector.digests.fri_roots.len());
+        }
+
+        // the same seed produces the same vectors, and a different seed produces different ones
+        let json = suite.to_json();
+        assert_eq!(json, super::generate(42).to_json());
+        assert_ne!(json, super::generate(43).to_json());
+
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(42, document["seed"]);
+        let vector = &document["vectors"][0];
+        assert_eq!(vector["digests"]["trace_root"], vector["proof"]["trace_root"]);
+        let proof_bytes = hex::decode(vector["proof_bytes"].as_str().unwrap()).unwrap();
+        let proof: crate::StarkProof = bincode::deserialize(&proof_bytes).unwrap();
+        assert_eq!(suite.vectors[0].proof.trace_root(), proof.trace_root());
+    }
+}
@@ -0,0 +1,106 @@
+// CONSTANTS
+// ================================================================================================
+
+/// Number of bytes packed into a single field element; 15 bytes always fit into a field element
+/// because the field modulus is greater than 2^120.
+pub const BYTES_PER_ELEMENT: usize = 15;
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Encodes a byte string into a list of field elements. The first element of the list is the
+/// number of bytes in the string, and it is followed by the bytes packed into elements 15 bytes
+/// at a time in little-endian order; the last element contains the remaining bytes (if any).
+///
+/// This is the format expected by the `read.bytes` assembly instruction: the returned elements
+/// can be put onto input tape `A` (e.g. via `ProgramInputsBuilder::push_bytes()`).
+pub fn bytes_to_elements(bytes: &[u8]) -> Vec<u128> {
+    let mut result = Vec::with_capacity(1 + num_chunks(bytes.len()));
+    result.push(bytes.len() as u128);
+    for chunk in bytes.chunks(BYTES_PER_ELEMENT) {
+        let mut value = 0u128;
+        for (i, &byte) in chunk.iter().enumerate() {
+            value |= (byte as u128) << (8 * i);
+        }
+        result.push(value);
+    }
+    return result;
+}
+
+/// Decodes a list of field elements produced by `bytes_to_elements()` back into a byte string.
+/// Returns an error if the number of elements does not match the encoded length, or if any of
+/// the elements contains more bytes than it should.
+pub fn elements_to_bytes(elements: &[u128]) -> Result<Vec<u8>, String> {
+    if elements.is_empty() {
+        return Err(format!("byte string length is missing"));
+    }
+
+    let length = elements[0];
+    let chunks = &elements[1..];
+    if length > (chunks.len() * BYTES_PER_ELEMENT) as u128 || num_chunks(length as usize) != chunks.len() {
+        return Err(format!("byte string of length {} must be encoded with {} elements, but {} were provided",
+            length, num_chunks_u128(length), chunks.len()));
+    }
+
+    let length = length as usize;
+    let mut result = Vec::with_capacity(length);
+    for (i, &chunk) in chunks.iter().enumerate() {
+        let chunk_length = std::cmp::min(BYTES_PER_ELEMENT, length - i * BYTES_PER_ELEMENT);
+        if chunk >> (8 * chunk_length) != 0 {
+            return Err(format!("element {} does not fit into {} bytes", i + 1, chunk_length));
+        }
+        for j in 0..chunk_length {
+            result.push((chunk >> (8 * j)) as u8);
+        }
+    }
+
+    return Ok(result);
+}
+
+/// Returns the number of elements needed to encode a byte string of the specified `length`,
+/// not including the length element.
+pub fn num_chunks(length: usize) -> usize {
+    return (length + BYTES_PER_ELEMENT - 1) / BYTES_PER_ELEMENT;
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn num_chunks_u128(length: u128) -> u128 {
+    let bytes_per_element = BYTES_PER_ELEMENT as u128;
+    return length / bytes_per_element + (length % bytes_per_element != 0) as u128;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use super::{ bytes_to_elements, elements_to_bytes };
+
+    #[test]
+    fn round_trip() {
+        for length in [0, 1, 14, 15, 16, 30, 31, 100].iter() {
+            let bytes: Vec<u8> = (0..*length).map(|i| (i * 7 + 3) as u8).collect();
+            let elements = bytes_to_elements(&bytes);
+            assert_eq!(*length as u128, elements[0]);
+            assert_eq!(1 + (length + 14) / 15, elements.len());
+            assert_eq!(Ok(bytes), elements_to_bytes(&elements));
+        }
+
+        // bytes are packed in little-endian order
+        assert_eq!(vec![2, 0x0201], bytes_to_elements(&[1, 2]));
+    }
+
+    #[test]
+    fn invalid_encodings() {
+        assert!(elements_to_bytes(&[]).is_err());
+        assert!(elements_to_bytes(&[2]).is_err());
+        assert!(elements_to_bytes(&[2, 1, 1]).is_err());
+        assert!(elements_to_bytes(&[u128::MAX, 1]).is_err());
+
+        // the last element contains more bytes than the encoded length allows
+        assert!(elements_to_bytes(&[1, 0x0201]).is_err());
+        assert!(elements_to_bytes(&[15, 1u128 << 120]).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{ Serialize, Deserialize };
+use crate::StarkProof;
+
+// CONSTANTS
+// ================================================================================================
+
+/// Compression level passed to zstd; higher levels compress traces only marginally better but
+/// are much slower.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Every zstd frame starts with these bytes; this is used to tell compressed proofs apart from
+/// proofs serialized with bincode.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Execution trace in the form in which it is persisted: the trace is kept as a list of register
+/// columns (the same as returned by `generate_trace()`), so that long runs of zeros (e.g. unused
+/// stack registers, or NOOPs padding the trace) end up next to each other.
+#[derive(Serialize, Deserialize)]
+struct TraceRecord {
+    registers   : Vec<Vec<u128>>,
+    ctx_depth   : usize,
+    loop_depth  : usize,
+}
+
+// TRACE COMPRESSION
+// ================================================================================================
+
+/// Serializes and compresses an execution trace returned by `generate_trace()` function.
+pub fn compress_trace(trace: &[Vec<u128>], ctx_depth: usize, loop_depth: usize) -> Vec<u8> {
+    let record = TraceRecord { registers: trace.to_vec(), ctx_depth, loop_depth };
+    let bytes = bincode::serialize(&record).expect("failed to serialize execution trace");
+    return compress(&bytes);
+}
+
+/// Decompresses an execution trace compressed with `compress_trace()`, and returns the trace
+/// together with context depth and loop depth of the execution.
+pub fn decompress_trace(bytes: &[u8]) -> Result<(Vec<Vec<u128>>, usize, usize), String> {
+    let bytes = decompress(bytes)?;
+    let record: TraceRecord = bincode::deserialize(&bytes)
+        .map_err(|err| format!("failed to deserialize execution trace: {}", err))?;
+
+    let trace_length = record.registers.first().map(|r| r.len()).unwrap_or(0);
+    if record.registers.iter().any(|r| r.len() != trace_length) {
+        return Err(format!("all registers of the execution trace must have the same length"));
+    }
+
+    return Ok((record.registers, record.ctx_depth, record.loop_depth));
+}
+
+// PROOF COMPRESSION
+// ================================================================================================
+
+/// Serializes the `proof` with bincode and compresses the result.
+pub fn compress_proof(proof: &StarkProof) -> Vec<u8> {
+    return compress(&proof.to_bytes());
+}
+
+/// Deserializes a proof from the specified `bytes`; the bytes can be either produced by
+/// `compress_proof()` function, or be a plain bincode serialization of the proof (e.g. written
+/// by `execute_to_writer()` function). Proofs of earlier layout versions are accepted as well
+/// (see `StarkProof::from_bytes()`).
+pub fn decompress_proof(bytes: &[u8]) -> Result<StarkProof, String> {
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        return StarkProof::from_bytes(&decompress(bytes)?);
+    }
+    return StarkProof::from_bytes(bytes);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn compress(bytes: &[u8]) -> Vec<u8> {
+    return zstd::encode_all(bytes, COMPRESSION_LEVEL).expect("failed to compress data");
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    return zstd::decode_all(bytes).map_err(|err| format!("failed to decompress data: {}", err));
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, ProofOptions };
+
+    #[test]
+    fn trace_round_trip() {
+        let program = assembly::compile("begin push.3 push.5 add repeat.10 push.2 mul end end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1, 2]);
+        let (trace, ctx_depth, loop_depth) = crate::generate_trace(&program, &inputs);
+
+        let bytes = super::compress_trace(&trace, ctx_depth, loop_depth);
+        let raw_size = trace.len() * trace[0].len() * 16;
+        assert!(bytes.len() * 4 < raw_size, "compressed {} bytes into {} bytes", raw_size, bytes.len());
+        assert_eq!(Ok((trace, ctx_depth, loop_depth)), super::decompress_trace(&bytes));
+
+        assert!(super::decompress_trace(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn proof_round_trip() {
+        let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+        let inputs = ProgramInputs::none();
+        let options = ProofOptions::default();
+        let (outputs, proof) = crate::execute(&program, &inputs, 1, &options);
+
+        // compressed proofs are smaller than serialized proofs, and both can be loaded
+        let compressed = super::compress_proof(&proof);
+        let serialized = bincode::serialize(&proof).unwrap();
+        assert!(compressed.len() < serialized.len());
+
+        for bytes in [compressed, serialized].iter() {
+            let proof = super::decompress_proof(bytes).unwrap();
+            let result = crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+            assert_eq!(Ok(true), result);
+        }
+
+        assert!(super::decompress_proof(&[1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use serde::{ Serialize, Deserialize };
+use crate::{ crypto::commit, math::field };
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// Secret values which a program commits to using `commit_tape` macro from `assembly::stdlib`
+/// module, together with the salts which hide them. Since the digest computed by the macro is
+/// one of the outputs of the program, the proof of execution binds the values; any subset of
+/// the values can then be revealed via `open()` without generating a new proof.
+///
+/// The digest is computed by absorbing values one at a time: the leaf for every value is
+/// `commit(&[value], Some(salt))`, and the digest is updated as
+/// `commit(&[leaf[0], leaf[1], digest[0], digest[1]], None)` starting with `[0, 0]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TapeCommitment {
+    values  : Vec<u128>,
+    salts   : Vec<u128>,
+}
+
+/// An opening of a `TapeCommitment` which reveals values at some positions; for all other
+/// positions only the leaves are included, and since the leaves are salted, they reveal nothing
+/// about the hidden values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disclosure {
+    leaves  : Vec<DisclosedLeaf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum DisclosedLeaf {
+    Revealed { value: u128, salt: u128 },
+    Hidden([u128; 2]),
+}
+
+// TAPE COMMITMENT IMPLEMENTATION
+// ================================================================================================
+impl TapeCommitment {
+
+    /// Creates a commitment to the specified `values` hidden by the specified `salts`; the salts
+    /// must be random field elements (e.g. generated by `math::field::rand()`) which are kept
+    /// secret until the corresponding values are revealed.
+    ///
+    /// Panics if the number of salts is not equal to the number of values, if there are fewer
+    /// than 2 values, or if any of the values or salts is not a valid field element.
+    pub fn new(values: &[u128], salts: &[u128]) -> TapeCommitment {
+        assert!(values.len() >= 2, "at least 2 values must be committed to");
+        assert!(values.len() == salts.len(),
+            "expected {} salts, but received {}", values.len(), salts.len());
+        for &value in values.iter().chain(salts.iter()) {
+            assert!(value < field::MODULUS, "{} is not a valid field element", value);
+        }
+        return TapeCommitment { values: values.to_vec(), salts: salts.to_vec() };
+    }
+
+    /// Returns the number of committed values; this is the `n` parameter of `commit_tape` macro.
+    pub fn len(&self) -> usize {
+        return self.values.len();
+    }
+
+    /// Returns the digest computed by `commit_tape` macro in stack order - i.e. the first element
+    /// ends up at the top of the stack.
+    pub fn digest(&self) -> [u128; 2] {
+        let leaves: Vec<[u128; 2]> = self.values.iter().zip(self.salts.iter())
+            .map(|(&value, &salt)| commit(&[value], Some(salt))).collect();
+        return digest_leaves(&leaves);
+    }
+
+    /// Builds contents of input tapes A and B expected by `commit_tape` macro; the tapes hold the
+    /// values and the salts respectively.
+    pub fn build_tapes(&self) -> (Vec<u128>, Vec<u128>) {
+        return (self.values.clone(), self.salts.clone());
+    }
+
+    /// Returns an opening which reveals values at the specified `positions`.
+    ///
+    /// Panics if any of the positions is out of bounds.
+    pub fn open(&self, positions: &[usize]) -> Disclosure {
+        for &position in positions.iter() {
+            assert!(position < self.values.len(), "position {} is out of bounds; only {} values were committed to",
+                position, self.values.len());
+        }
+
+        let leaves = (0..self.values.len()).map(|i| {
+            let (value, salt) = (self.values[i], self.salts[i]);
+            if positions.contains(&i) {
+                DisclosedLeaf::Revealed { value, salt }
+            }
+            else {
+                DisclosedLeaf::Hidden(commit(&[value], Some(salt)))
+            }
+        }).collect();
+
+        return Disclosure { leaves };
+    }
+}
+
+// DISCLOSURE IMPLEMENTATION
+// ================================================================================================
+impl Disclosure {
+
+    /// Returns the revealed values as (position, value) tuples.
+    pub fn revealed(&self) -> Vec<(usize, u128)> {
+        return self.leaves.iter().enumerate().filter_map(|(i, leaf)| match leaf {
+            DisclosedLeaf::Revealed { value, .. } => Some((i, *value)),
+            DisclosedLeaf::Hidden(_) => None,
+        }).collect();
+    }
+
+    /// Checks that all revealed values, salts and hidden leaves are valid field elements; since
+    /// the hash function reduces its inputs, a value which is not reduced would otherwise lead to
+    /// the same digest as the committed value and could be disclosed in its place.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, leaf) in self.leaves.iter().enumerate() {
+            let elements = match leaf {
+                DisclosedLeaf::Revealed { value, salt } => [*value, *salt],
+                DisclosedLeaf::Hidden(leaf) => *leaf,
+            };
+            if elements.iter().any(|&element| element >= field::MODULUS) {
+                return Err(format!("disclosed leaf at position {} contains an invalid field element", i));
+            }
+        }
+        return Ok(());
+    }
+
+    /// Recomputes the digest of the commitment from the revealed values and the hidden leaves;
+    /// the opening is valid only if the result is equal to the digest output by the program.
+    pub fn digest(&self) -> [u128; 2] {
+        let leaves: Vec<[u128; 2]> = self.leaves.iter().map(|leaf| match leaf {
+            DisclosedLeaf::Revealed { value, salt } => commit(&[*value], Some(*salt)),
+            DisclosedLeaf::Hidden(leaf) => *leaf,
+        }).collect();
+        return digest_leaves(&leaves);
+    }
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn digest_leaves(leaves: &[[u128; 2]]) -> [u128; 2] {
+    let mut digest = [0, 0];
+    for leaf in leaves.iter() {
+        digest = commit(&[leaf[0], leaf[1], digest[0], digest[1]], None);
+    }
+    return digest;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, ProofOptions, processor, math::field };
+    use super::{ TapeCommitment, Disclosure, DisclosedLeaf };
+
+    #[test]
+    fn commit_tape_macro() {
+        let commitment = TapeCommitment::new(&[10, 20, 30], &[1001, 1002, 1003]);
+        let source = format!("{} begin commit_tape.{} end", assembly::stdlib::COMMIT_TAPE, commitment.len());
+        let program = assembly::compile(&source).unwrap();
+
+        let (a, b) = commitment.build_tapes();
+        let inputs = ProgramInputs::new(&[], &a, &b);
+        let stack = processor::interpret(&program, &inputs).unwrap().stack;
+        assert_eq!(commitment.digest(), [stack[0], stack[1]]);
+
+        // openings resolve to the same digest regardless of which values are revealed
+        let disclosure = commitment.open(&[0, 2]);
+        assert_eq!(vec![(0, 10), (2, 30)], disclosure.revealed());
+        assert_eq!(commitment.digest(), disclosure.digest());
+        assert_eq!(commitment.digest(), commitment.open(&[]).digest());
+
+        // a different salt leads to a different digest
+        let other = TapeCommitment::new(&[10, 20, 30], &[1001, 1002, 1004]);
+        assert_ne!(commitment.digest(), other.open(&[0, 2]).digest());
+    }
+
+    #[test]
+    fn reject_unreduced_disclosure() {
+        let commitment = TapeCommitment::new(&[10, 20, 30], &[1001, 1002, 1003]);
+        let source = format!("{} begin commit_tape.3 end", assembly::stdlib::COMMIT_TAPE);
+        let program = assembly::compile(&source).unwrap();
+        let (a, b) = commitment.build_tapes();
+        let inputs = ProgramInputs::new(&[], &a, &b);
+        let (_, proof) = crate::execute(&program, &inputs, 2, &ProofOptions::default());
+
+        // revealing a value which is not reduced must fail even though it has the same salt
+        let mut disclosure = commitment.open(&[1]);
+        disclosure.leaves[1] = DisclosedLeaf::Revealed { value: 20 + field::MODULUS, salt: 1002 };
+        assert!(disclosure.validate().is_err());
+        let result = crate::verify_disclosure(program.hash(), &[], 0, &disclosure, &proof);
+        assert_eq!(Err(String::from("disclosed leaf at position 1 contains an invalid field element")), result);
+
+        // the same applies to salts and hidden leaves
+        let mut disclosure = commitment.open(&[1]);
+        disclosure.leaves[1] = DisclosedLeaf::Revealed { value: 20, salt: 1002 + field::MODULUS };
+        assert!(disclosure.validate().is_err());
+
+        let disclosure = Disclosure { leaves: vec![DisclosedLeaf::Hidden([field::MODULUS, 0]); 3] };
+        assert!(crate::verify_disclosure(program.hash(), &[], 0, &disclosure, &proof).is_err());
+
+        // a valid opening is still accepted
+        let result = crate::verify_disclosure(program.hash(), &[], 0, &commitment.open(&[1]), &proof);
+        assert_eq!(Ok(vec![(1, 20)]), result);
+    }
+}
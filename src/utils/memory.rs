@@ -0,0 +1,129 @@
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::sync::atomic::{ AtomicUsize, Ordering };
+use crate::ProvingStage;
+
+// GLOBAL STATE
+// ================================================================================================
+
+/// Number of bytes currently allocated via `TrackingAllocator`.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Largest number of bytes allocated via `TrackingAllocator` since the peak was last reset.
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A global allocator which forwards all requests to the system allocator, and keeps track of
+/// the number of allocated bytes. Memory usage can be measured only if this allocator is
+/// installed by the binary which uses the VM:
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: distaff::utils::memory::TrackingAllocator = distaff::utils::memory::TrackingAllocator;
+/// ```
+/// Otherwise, all reported values are zeros.
+pub struct TrackingAllocator;
+
+/// Peak number of bytes allocated while an execution trace was generated and during every stage
+/// of proof generation; stages are listed in the order in which they were executed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub trace_generation    : usize,
+    pub stages              : Vec<(ProvingStage, usize)>,
+}
+
+// ALLOCATOR IMPLEMENTATION
+// ================================================================================================
+unsafe impl GlobalAlloc for TrackingAllocator {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        return ptr;
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        return ptr;
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        return new_ptr;
+    }
+}
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Returns the number of bytes currently allocated via `TrackingAllocator`.
+pub fn allocated() -> usize {
+    return ALLOCATED.load(Ordering::Relaxed);
+}
+
+/// Returns the largest number of bytes allocated via `TrackingAllocator` since the peak was
+/// last reset.
+pub fn peak_allocated() -> usize {
+    return PEAK.load(Ordering::Relaxed);
+}
+
+/// Resets the peak to the number of bytes currently allocated, and returns the peak from before
+/// the reset.
+pub fn reset_peak() -> usize {
+    return PEAK.swap(allocated(), Ordering::Relaxed);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn record_alloc(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    #[global_allocator]
+    static ALLOCATOR: super::TrackingAllocator = super::TrackingAllocator;
+
+    #[test]
+    fn track_peak_allocation() {
+        use crate::{ assembly, ProgramInputs, ProofOptions, ProvingStage };
+
+        // the peak covers memory which is no longer allocated
+        super::reset_peak();
+        let buffer = vec![1u8; 1 << 24];
+        drop(buffer);
+        assert!(super::peak_allocated() >= 1 << 24);
+
+        // memory usage is reported for trace generation and every stage of proof generation
+        let program = assembly::compile("begin push.3 push.5 add repeat.10 push.2 mul end end").unwrap();
+        let inputs = ProgramInputs::from_public(&[1, 2]);
+        let options = ProofOptions::default();
+        let (outputs, proof, report) = crate::execute_with_memory_report(&program, &inputs, 1, &options);
+        assert_eq!(Ok(true), crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof));
+
+        assert!(report.trace_generation > 0);
+        assert_eq!(8, report.stages.len());
+        assert_eq!(ProvingStage::TraceExtension, report.stages[0].0);
+        assert_eq!(ProvingStage::ProofConstruction, report.stages[7].0);
+        assert!(report.stages.iter().all(|&(_, peak)| peak > 0));
+    }
+}
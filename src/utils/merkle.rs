@@ -0,0 +1,83 @@
+use crate::crypto::rescue;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// An authentication path for a leaf of a Merkle tree built using Rescue hash function; this is
+/// the kind of path verified by `smpath` and `mpath_update` instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    nodes   : Vec<[u128; 2]>,
+    index   : usize,
+}
+
+// MERKLE PATH IMPLEMENTATION
+// ================================================================================================
+impl MerklePath {
+
+    /// Returns an authentication path for the leaf at position `index`; `nodes` contains
+    /// siblings of all nodes on the path from the leaf to the root starting with the sibling
+    /// of the leaf.
+    pub fn new(nodes: Vec<[u128; 2]>, index: usize) -> MerklePath {
+        assert!(!nodes.is_empty(), "a Merkle path must contain at least one node");
+        assert!(nodes.len() >= usize::BITS as usize || index >> nodes.len() == 0,
+            "leaf index {} is too large for a path of {} nodes", index, nodes.len());
+        return MerklePath { nodes, index };
+    }
+
+    /// Returns nodes of the path.
+    pub fn nodes(&self) -> &[[u128; 2]] {
+        return &self.nodes;
+    }
+
+    /// Returns position of the leaf for which the path was built.
+    pub fn index(&self) -> usize {
+        return self.index;
+    }
+
+    /// Computes the root to which this path resolves for the specified `leaf`.
+    pub fn root(&self, leaf: [u128; 2]) -> [u128; 2] {
+        return compute_path_root(leaf, &self.nodes, self.index);
+    }
+}
+
+// MERKLE PATH HELPERS
+// ================================================================================================
+
+/// Builds contents of input tapes A and B expected by `smpath` and `mpath_update` instructions
+/// for the specified authentication `path` of a leaf at position `index`. For each node in the
+/// path, tape B receives the next bit of the index (tape A receives a 0), followed by the node
+/// itself split across both tapes.
+pub fn build_path_tapes(path: &[[u128; 2]], index: usize) -> (Vec<u128>, Vec<u128>) {
+    let mut a = Vec::with_capacity(path.len() * 2);
+    let mut b = Vec::with_capacity(path.len() * 2);
+
+    let mut index = index;
+    for node in path.iter() {
+        a.push(0);
+        b.push((index & 1) as u128);
+        a.push(node[0]);
+        b.push(node[1]);
+        index = index >> 1;
+    }
+
+    return (a, b);
+}
+
+/// Computes the root of the authentication `path` for the `leaf` at position `index`.
+pub fn compute_path_root(leaf: [u128; 2], path: &[[u128; 2]], index: usize) -> [u128; 2] {
+    let mut node = leaf;
+    let mut index = index;
+    for sibling in path.iter() {
+        let v = if index & 1 == 0 {
+            rescue::digest(&[node[0], node[1], sibling[0], sibling[1]])
+        }
+        else {
+            rescue::digest(&[sibling[0], sibling[1], node[0], node[1]])
+        };
+        node = [v[0], v[1]];
+        index = index >> 1;
+    }
+
+    return node;
+}
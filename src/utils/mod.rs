@@ -1,16 +1,87 @@
-use std::{ mem, slice, ops::Range };
+use std::ops::Range;
+#[cfg(not(feature = "forbid-unsafe"))]
+use std::{ mem, slice };
 
 // RE-EXPORTS
 // ================================================================================================
-pub mod hasher;
-pub mod sponge;
+pub mod merkle;
+pub mod codec;
+pub mod table;
+pub mod sealing;
+pub mod disclosure;
+
+#[cfg(feature = "compression")]
+pub mod compression;
+
+#[cfg(feature = "memory-stats")]
+pub mod memory;
+
+/// Rescue hash function used by RESCR operation; moved to `crypto::rescue`.
+#[deprecated(note = "use crypto::rescue::HASHER and crypto::rescue::digest() instead")]
+pub mod hasher {
+    use crate::crypto::rescue::HASHER;
+    pub use crate::crypto::rescue::digest;
+    pub use crate::crypto::rescue::hasher::ARK;
+
+    pub fn apply_round(state: &mut [u128], step: usize) {
+        HASHER.apply_round(state, step);
+    }
+
+    pub fn add_constants(state: &mut [u128], idx: usize, offset: usize) {
+        HASHER.add_constants(state, idx, offset);
+    }
+
+    pub fn apply_sbox(state: &mut [u128]) {
+        HASHER.apply_sbox(state);
+    }
+
+    pub fn apply_inv_sbox(state: &mut [u128]) {
+        HASHER.apply_inv_sbox(state);
+    }
+
+    pub fn apply_mds(state: &mut [u128]) {
+        HASHER.apply_mds(state);
+    }
+
+    pub fn apply_inv_mds(state: &mut [u128]) {
+        HASHER.apply_inv_mds(state);
+    }
+}
+
+/// Rescue sponge used by the decoder to hash program operations; moved to `crypto::rescue`.
+#[deprecated(note = "use crypto::rescue::SPONGE and crypto::rescue::apply_hacc_round() instead")]
+pub mod sponge {
+    use crate::crypto::rescue::SPONGE;
+    pub use crate::crypto::rescue::apply_hacc_round as apply_round;
+    pub use crate::crypto::rescue::sponge::ARK;
+
+    pub fn add_constants(state: &mut [u128], idx: usize, offset: usize) {
+        SPONGE.add_constants(state, idx, offset);
+    }
+
+    pub fn apply_sbox(state: &mut [u128]) {
+        SPONGE.apply_sbox(state);
+    }
+
+    pub fn apply_inv_sbox(state: &mut [u128]) {
+        SPONGE.apply_inv_sbox(state);
+    }
+
+    pub fn apply_mds(state: &mut [u128]) {
+        SPONGE.apply_mds(state);
+    }
+
+    pub fn apply_inv_mds(state: &mut [u128]) {
+        SPONGE.apply_inv_mds(state);
+    }
+}
 
 // VECTOR FUNCTIONS
 // ================================================================================================
-pub fn uninit_vector<T>(length: usize) -> Vec<T> {
-    let mut vector = Vec::with_capacity(length);
-    unsafe { vector.set_len(length); }
-    return vector;
+/// Returns a vector of the specified `length` with all elements set to their default values
+/// (i.e. zeros for numeric types and byte arrays).
+pub fn zeroed_vector<T: Copy + Default>(length: usize) -> Vec<T> {
+    return vec![T::default(); length];
 }
 
 pub fn filled_vector<T: Copy>(length: usize, capacity: usize, value: T) -> Vec<T> {
@@ -32,6 +103,8 @@ pub fn remove_leading_zeros(values: &[u128]) -> Vec<u128> {
 
 // TYPE CONVERSIONS
 // ================================================================================================
+/// Returns a view of the specified `values` as a slice of bytes.
+#[cfg(not(feature = "forbid-unsafe"))]
 pub fn as_bytes<T>(values: &[T]) -> &[u8] {
     let value_size = mem::size_of::<T>();
     let result = unsafe {
@@ -40,6 +113,12 @@ pub fn as_bytes<T>(values: &[T]) -> &[u8] {
     return result;
 }
 
+/// Returns a view of the specified `values` as a slice of bytes.
+#[cfg(feature = "forbid-unsafe")]
+pub fn as_bytes<T: bytemuck::Pod>(values: &[T]) -> &[u8] {
+    return bytemuck::cast_slice(values);
+}
+
 // RANGE
 // ================================================================================================
 pub trait RangeSlider {
@@ -72,4 +151,24 @@ mod tests {
         ];
         assert_eq!(expected, bytes);
     }
+
+    #[test]
+    fn zeroed_vector() {
+        assert_eq!(vec![0u128; 5], super::zeroed_vector::<u128>(5));
+        assert_eq!(vec![[0u8; 32]; 3], super::zeroed_vector::<[u8; 32]>(3));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_rescue_modules() {
+        use crate::crypto::rescue;
+
+        assert_eq!(rescue::digest(&[1, 2, 3, 4]), super::hasher::digest(&[1, 2, 3, 4]));
+
+        let mut expected = [1, 2, 3, 4];
+        let mut state = expected;
+        rescue::apply_hacc_round(&mut expected, 5, 6, 7);
+        super::sponge::apply_round(&mut state, 5, 6, 7);
+        assert_eq!(expected, state);
+    }
 }
\ No newline at end of file
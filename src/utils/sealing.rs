@@ -0,0 +1,115 @@
+use std::convert::TryInto;
+use crate::{ crypto::{ hash, ct_eq }, StarkProof };
+
+// CONSTANTS
+// ================================================================================================
+
+/// Every sealed proof starts with these bytes.
+const SEAL_MAGIC: [u8; 4] = *b"DSTF";
+
+/// Version of the sealed proof format.
+const SEAL_VERSION: u8 = 1;
+
+/// Size of the header: magic bytes, format version, and length of the serialized proof.
+const HEADER_SIZE: usize = 4 + 1 + 8;
+
+/// Number of bytes of the BLAKE3 digest of the serialized proof appended to the proof.
+const CHECKSUM_SIZE: usize = 8;
+
+// PUBLIC FUNCTIONS
+// ================================================================================================
+
+/// Serializes the `proof` into a self-contained hex string. The proof already carries the
+/// options it was generated with and the salt of the program commitment (if any), and so, the
+/// string is all a verifier needs in addition to the program hash and public inputs.
+///
+/// The serialized proof is framed by a header containing its length, and is followed by a
+/// checksum; this way, a truncated or corrupted string is detected by `unseal_proof()` before
+/// the proof is deserialized.
+pub fn seal_proof(proof: &StarkProof) -> String {
+    let payload = proof.to_bytes();
+
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len() + CHECKSUM_SIZE);
+    bytes.extend_from_slice(&SEAL_MAGIC);
+    bytes.push(SEAL_VERSION);
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    bytes.extend_from_slice(&checksum(&payload));
+
+    return hex::encode(bytes);
+}
+
+/// Restores a proof from a string produced by `seal_proof()`; returns an error if the string
+/// is malformed, truncated, or corrupted.
+pub fn unseal_proof(sealed: &str) -> Result<StarkProof, String> {
+    let bytes = hex::decode(sealed.trim())
+        .map_err(|err| format!("sealed proof is not a valid hex string: {}", err))?;
+
+    if bytes.len() < HEADER_SIZE || bytes[..4] != SEAL_MAGIC {
+        return Err(format!("sealed proof must start with a {}-byte header", HEADER_SIZE));
+    }
+    if bytes[4] != SEAL_VERSION {
+        return Err(format!("sealed proof version {} is not supported; expected version {}",
+            bytes[4], SEAL_VERSION));
+    }
+
+    let length = u64::from_le_bytes(bytes[5..HEADER_SIZE].try_into().unwrap());
+    let available = (bytes.len() - HEADER_SIZE) as u64;
+    if length.checked_add(CHECKSUM_SIZE as u64) != Some(available) {
+        return Err(format!("sealed proof must contain {} bytes of proof and {} bytes of checksum, but {} bytes were provided",
+            length, CHECKSUM_SIZE, available));
+    }
+
+    let (payload, expected) = bytes[HEADER_SIZE..].split_at(length as usize);
+    if !ct_eq(&checksum(payload), expected) {
+        return Err(format!("sealed proof checksum does not match"));
+    }
+
+    return StarkProof::from_bytes(payload);
+}
+
+// HELPER FUNCTIONS
+// ================================================================================================
+
+fn checksum(payload: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let mut digest = [0u8; 32];
+    hash::blake3(payload, &mut digest);
+    let mut result = [0u8; CHECKSUM_SIZE];
+    result.copy_from_slice(&digest[..CHECKSUM_SIZE]);
+    return result;
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, ProofOptions };
+    use super::{ seal_proof, unseal_proof };
+
+    #[test]
+    fn seal_round_trip() {
+        let program = assembly::compile("begin push.3 push.5 add end").unwrap();
+        let inputs = ProgramInputs::none();
+        let options = ProofOptions::default();
+        let (outputs, proof) = crate::execute(&program, &inputs, 1, &options);
+
+        let sealed = seal_proof(&proof);
+        let proof = unseal_proof(&sealed).unwrap();
+        assert!(&options == proof.options());
+        let result = crate::verify(program.hash(), inputs.get_public_inputs(), &outputs, &proof);
+        assert_eq!(Ok(true), result);
+
+        // truncated and corrupted proofs are rejected before they are deserialized
+        let truncated = unseal_proof(&sealed[..sealed.len() - 2]).err().unwrap();
+        assert!(truncated.contains("bytes were provided"), "{}", truncated);
+
+        let mut corrupted = sealed.clone().into_bytes();
+        corrupted[40] = if corrupted[40] == b'0' { b'1' } else { b'0' };
+        let corrupted = unseal_proof(std::str::from_utf8(&corrupted).unwrap()).err().unwrap();
+        assert!(corrupted.contains("checksum"), "{}", corrupted);
+
+        assert!(unseal_proof("abc").is_err());
+        assert!(unseal_proof(&sealed[10..]).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::crypto::rescue;
+use super::merkle::compute_path_root;
+
+// TYPES AND INTERFACES
+// ================================================================================================
+
+/// A sorted table of values committed to by a Merkle tree built using Rescue hash function; this
+/// is the kind of table searched by `bsearch` macro from `assembly::stdlib` module.
+///
+/// Every leaf of the tree is a pair of adjacent values `[a[i], a[i + 1]]`, and so, proving that
+/// a value falls between two entries of the table requires a single Merkle path. If the number
+/// of pairs is not a power of two, the tree is padded with `[0, 0]` leaves; no value falls
+/// between the values of such a leaf, and thus, padding leaves can never be used in a search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortedTable {
+    values  : Vec<u128>,
+    layers  : Vec<Vec<[u128; 2]>>,
+}
+
+// SORTED TABLE IMPLEMENTATION
+// ================================================================================================
+impl SortedTable {
+
+    /// Builds a table from the specified `values`; the values must be in strictly increasing
+    /// order, and there must be at least 2 of them.
+    pub fn new(values: &[u128]) -> SortedTable {
+        assert!(values.len() >= 2, "a table must contain at least 2 values");
+        for i in 1..values.len() {
+            assert!(values[i - 1] < values[i],
+                "table values must be strictly increasing, but value {} is not", i);
+        }
+
+        // build leaves from adjacent values, and pad them to the next power of two
+        let num_leaves = std::cmp::max(2, (values.len() - 1).next_power_of_two());
+        let mut leaves = vec![[0, 0]; num_leaves];
+        for i in 0..(values.len() - 1) {
+            leaves[i] = [values[i], values[i + 1]];
+        }
+
+        // hash the leaves into the root one layer at a time
+        let mut layers = vec![leaves];
+        while layers[layers.len() - 1].len() > 1 {
+            let layer = layers[layers.len() - 1].chunks(2).map(|pair| {
+                let v = rescue::digest(&[pair[0][0], pair[0][1], pair[1][0], pair[1][1]]);
+                [v[0], v[1]]
+            }).collect();
+            layers.push(layer);
+        }
+
+        return SortedTable { values: values.to_vec(), layers };
+    }
+
+    /// Returns values of the table.
+    pub fn values(&self) -> &[u128] {
+        return &self.values;
+    }
+
+    /// Returns depth of the Merkle tree (i.e. the number of layers including the leaves and the
+    /// root); this is the `d` parameter of `bsearch` macro.
+    pub fn depth(&self) -> usize {
+        return self.layers.len();
+    }
+
+    /// Returns the root of the Merkle tree.
+    pub fn root(&self) -> [u128; 2] {
+        return self.layers[self.layers.len() - 1][0];
+    }
+
+    /// Returns index i of the table entry such that `a[i] <= x < a[i + 1]`, or `None` if `x` is
+    /// less than the first value or is not less than the last value of the table.
+    pub fn find(&self, x: u128) -> Option<usize> {
+        if x < self.values[0] || x >= self.values[self.values.len() - 1] {
+            return None;
+        }
+        return Some(self.values.partition_point(|&value| value <= x) - 1);
+    }
+
+    /// Builds contents of input tapes A and B expected by `bsearch` macro when searching for `x`;
+    /// returns `None` if `x` is outside of the range covered by the table.
+    pub fn build_tapes(&self, x: u128) -> Option<(Vec<u128>, Vec<u128>)> {
+        let index = self.find(x)?;
+        let path = self.build_path(index);
+
+        let mut a = Vec::with_capacity(path.len() + 2);
+        let mut b = Vec::with_capacity(path.len() + 1);
+        a.push(index as u128);
+        a.push(self.values[index]);
+        b.push(self.values[index + 1]);
+        for node in path.iter() {
+            a.push(node[0]);
+            b.push(node[1]);
+        }
+
+        debug_assert!(compute_path_root(self.layers[0][index], &path, index) == self.root(),
+            "authentication path for leaf {} does not resolve to the root", index);
+        return Some((a, b));
+    }
+
+    // HELPER METHODS
+    // --------------------------------------------------------------------------------------------
+
+    /// Returns siblings of all nodes on the path from the leaf at position `index` to the root.
+    fn build_path(&self, index: usize) -> Vec<[u128; 2]> {
+        let mut path = Vec::with_capacity(self.layers.len() - 1);
+        let mut index = index;
+        for layer in self.layers[..(self.layers.len() - 1)].iter() {
+            path.push(layer[index ^ 1]);
+            index = index >> 1;
+        }
+        return path;
+    }
+}
+
+// TESTS
+// ================================================================================================
+#[cfg(test)]
+mod tests {
+
+    use crate::{ assembly, ProgramInputs, processor };
+    use super::SortedTable;
+
+    #[test]
+    fn find() {
+        let table = SortedTable::new(&[3, 7, 10, 20]);
+        assert_eq!(3, table.depth());
+        assert_eq!(None, table.find(2));
+        assert_eq!(Some(0), table.find(3));
+        assert_eq!(Some(0), table.find(6));
+        assert_eq!(Some(1), table.find(7));
+        assert_eq!(Some(2), table.find(19));
+        assert_eq!(None, table.find(20));
+        assert_eq!(None, table.build_tapes(20));
+    }
+
+    #[test]
+    fn bsearch_macro() {
+        let table = SortedTable::new(&[3, 7, 10, 20, 45, 100]);
+        let source = format!("{} begin bsearch.8.{} end", assembly::stdlib::BSEARCH, table.depth());
+        let program = assembly::compile(&source).unwrap();
+        let root = table.root();
+
+        for &(x, index) in [(3, 0), (9, 1), (10, 2), (44, 3), (99, 4)].iter() {
+            let (a, b) = table.build_tapes(x).unwrap();
+            let inputs = ProgramInputs::new(&[x, root[0], root[1]], &a, &b);
+            let stack = processor::interpret(&program, &inputs).unwrap().stack;
+            assert_eq!(index, stack[0]);
+        }
+
+        // the prover cannot claim a different entry of the table
+        let (mut a, b) = table.build_tapes(44).unwrap();
+        a[1] = 10;
+        let inputs = ProgramInputs::new(&[44, root[0], root[1]], &a, &b);
+        assert!(processor::interpret(&program, &inputs).is_err());
+
+        // nor an entry which does not contain the value
+        let (a, b) = table.build_tapes(9).unwrap();
+        let inputs = ProgramInputs::new(&[44, root[0], root[1]], &a, &b);
+        assert!(processor::interpret(&program, &inputs).is_err());
+    }
+}
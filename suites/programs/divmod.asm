@@ -0,0 +1 @@
+begin divmod.64 end
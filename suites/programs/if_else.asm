@@ -0,0 +1,9 @@
+begin
+    read
+    if.true
+        add push.3
+    else
+        push.7 add push.8
+    end
+    mul
+end
@@ -0,0 +1 @@
+begin add read read add add end